@@ -0,0 +1,235 @@
+//! Event payloads built from Claude Code's hook input (--event)
+//!
+//! Windows hooks pipe the JSON they receive from Claude Code to mqtt-publish,
+//! which builds the same payloads as the bash hooks. This avoids building JSON
+//! in PowerShell and works the same whether the hook is started by cmd.exe,
+//! PowerShell or Git Bash.
+
+use chrono::{Local, SecondsFormat};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+/// Events that can be built from the hook input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Event {
+    /// Stop hook (claude-code/events/stop)
+    Stop,
+    /// PermissionRequest hook (claude-code/events/permission-request)
+    PermissionRequest,
+    /// Notification hook (claude-code/events/notification)
+    Notification,
+    /// Statusline input (claude-code/status/<session_id>, retained)
+    Status,
+}
+
+/// Message to publish for an event
+#[derive(Debug, PartialEq)]
+pub struct EventMessage {
+    pub topic: String,
+    pub payload: String,
+    pub retain: bool,
+}
+
+/// Where the hook runs
+pub struct Origin<'a> {
+    /// Host name sent in the payload
+    pub host: &'a str,
+    /// Working directory used when the input has no cwd
+    pub cwd: &'a str,
+    /// Template version of the hook script (None for older scripts)
+    pub template_version: Option<u32>,
+    /// Characters of Claude's final message sent with stop events (0 sends none)
+    pub last_message_length: usize,
+}
+
+/// Transcript lines searched for the final message (same as `tail -n 200` in the bash hook)
+const TRANSCRIPT_TAIL_LINES: usize = 200;
+
+/// Text of the last assistant message in a transcript (JSON Lines)
+fn last_assistant_text(transcript: &str) -> Option<String> {
+    let lines: Vec<&str> = transcript.lines().collect();
+    lines[lines.len().saturating_sub(TRANSCRIPT_TAIL_LINES)..]
+        .iter()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|entry| entry.get("type").and_then(Value::as_str) == Some("assistant"))
+        .find_map(|entry| {
+            let text = entry
+                .pointer("/message/content")?
+                .as_array()?
+                .iter()
+                .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+                .filter_map(|block| block.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n");
+            (!text.is_empty()).then_some(text)
+        })
+}
+
+/// Excerpt of Claude's final message (None when the transcript cannot be read)
+fn last_message(transcript_path: &str, max_chars: usize) -> Option<String> {
+    if max_chars == 0 {
+        return None;
+    }
+    let transcript = std::fs::read_to_string(transcript_path).ok()?;
+    last_assistant_text(&transcript).map(|text| text.chars().take(max_chars).collect())
+}
+
+/// Build the message for an event from Claude Code's hook input
+pub fn build(event: Event, input: &str, origin: &Origin) -> EventMessage {
+    let content: Option<Value> = serde_json::from_str(input.trim()).ok();
+    let field = |name: &str| {
+        content
+            .as_ref()
+            .and_then(|c| c.get(name))
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let cwd = field("cwd").unwrap_or_else(|| origin.cwd.to_string());
+    let timestamp = Local::now().to_rfc3339_opts(SecondsFormat::Secs, false);
+
+    if event == Event::Status {
+        let session_id = field("session_id").unwrap_or_else(|| "default-session".to_string());
+        let number = |pointer: &str| {
+            content
+                .as_ref()
+                .and_then(|c| c.pointer(pointer))
+                .cloned()
+                .unwrap_or(json!(0))
+        };
+        let payload = json!({
+            "session_id": session_id,
+            "host": origin.host,
+            "cwd": cwd,
+            "status": {
+                "state": "active",
+                "context_percent": number("/context_window/used_percentage"),
+                "cost_usd": number("/cost/total_cost_usd"),
+                "lines_added": number("/cost/total_lines_added"),
+                "lines_removed": number("/cost/total_lines_removed"),
+            },
+            "timestamp": timestamp,
+        });
+        return EventMessage {
+            topic: format!("claude-code/status/{}", session_id),
+            payload: payload.to_string(),
+            retain: true,
+        };
+    }
+
+    let name = match event {
+        Event::Stop => "stop",
+        Event::PermissionRequest => "permission-request",
+        Event::Notification => "notification",
+        Event::Status => unreachable!(),
+    };
+    let mut payload = json!({
+        "event": name,
+        "cwd": cwd,
+        "session_id": field("session_id").unwrap_or_else(|| format!("{}-unknown", origin.host)),
+        "host": origin.host,
+        "timestamp": timestamp,
+    });
+    if let Some(version) = origin.template_version {
+        payload["template_version"] = json!(version);
+    }
+    if event == Event::Stop {
+        // The app opens the transcript from the history (and searches it)
+        if let Some(path) = field("transcript_path") {
+            payload["transcript_path"] = json!(path);
+        }
+        if let Some(message) = field("transcript_path")
+            .and_then(|path| last_message(&path, origin.last_message_length))
+        {
+            payload["last_message"] = json!(message);
+        }
+    } else {
+        // Input that is not JSON is still delivered (as raw text)
+        payload["content"] = content.unwrap_or_else(|| json!({ "raw": input }));
+    }
+
+    EventMessage {
+        topic: format!("claude-code/events/{}", name),
+        payload: payload.to_string(),
+        retain: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: Origin = Origin {
+        host: "DESKTOP-1",
+        cwd: r"C:\work",
+        template_version: Some(3),
+        last_message_length: 200,
+    };
+
+    fn payload(message: &EventMessage) -> Value {
+        serde_json::from_str(&message.payload).unwrap()
+    }
+
+    #[test]
+    fn test_build_hook_events() {
+        let input = r#"{"session_id":"abc","cwd":"C:\\Users\\me\\プロジェクト","tool_name":"Bash","transcript_path":"C:\\Users\\me\\.claude\\abc.jsonl"}"#;
+
+        let stop = build(Event::Stop, input, &ORIGIN);
+        assert_eq!(stop.topic, "claude-code/events/stop");
+        assert!(!stop.retain);
+        let value = payload(&stop);
+        assert_eq!(value["event"], "stop");
+        assert_eq!(value["session_id"], "abc");
+        assert_eq!(value["cwd"], r"C:\Users\me\プロジェクト");
+        assert_eq!(value["host"], "DESKTOP-1");
+        assert_eq!(value["template_version"], 3);
+        assert_eq!(value["transcript_path"], r"C:\Users\me\.claude\abc.jsonl");
+        assert!(value.get("content").is_none());
+
+        let request = build(Event::PermissionRequest, input, &ORIGIN);
+        assert_eq!(request.topic, "claude-code/events/permission-request");
+        assert_eq!(payload(&request)["content"]["tool_name"], "Bash");
+    }
+
+    #[test]
+    fn test_build_without_json_input() {
+        let origin = Origin { template_version: None, ..ORIGIN };
+        let value = payload(&build(Event::Notification, "not json", &origin));
+        assert_eq!(value["session_id"], "DESKTOP-1-unknown");
+        assert_eq!(value["cwd"], r"C:\work");
+        assert_eq!(value["content"]["raw"], "not json");
+        assert!(value.get("template_version").is_none());
+    }
+
+    #[test]
+    fn test_last_assistant_text() {
+        let transcript = [
+            r#"{"type":"user","message":{"content":"hi"}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"first"}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"完了しました"},{"type":"text","text":"次は?"}]}}"#,
+            // Tool calls without text are skipped
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Bash"}]}}"#,
+            "not json",
+        ]
+        .join("\n");
+        assert_eq!(
+            last_assistant_text(&transcript).as_deref(),
+            Some("完了しました\n次は?")
+        );
+        assert_eq!(last_assistant_text(r#"{"type":"user"}"#), None);
+    }
+
+    #[test]
+    fn test_build_status() {
+        let input = r#"{"session_id":"abc","cost":{"total_cost_usd":0.25,"total_lines_added":3},"context_window":{"used_percentage":42}}"#;
+        let status = build(Event::Status, input, &ORIGIN);
+        assert_eq!(status.topic, "claude-code/status/abc");
+        assert!(status.retain);
+        let value = payload(&status);
+        assert_eq!(value["status"]["cost_usd"], 0.25);
+        assert_eq!(value["status"]["context_percent"], 42);
+        assert_eq!(value["status"]["lines_added"], 3);
+        assert_eq!(value["status"]["lines_removed"], 0);
+    }
+}
@@ -3,17 +3,38 @@
 //! Usage:
 //!   mqtt-publish -h <host> -p <port> -t <topic> -m <message>
 //!   mqtt-publish -h <host> -p <port> -t <topic> --stdin
+//!   mqtt-publish -h <host> -p <port> --event <event> < hook-input.json
 //!
 //! Example:
 //!   mqtt-publish -h 192.168.1.100 -p 1883 -t "claude-code/events/stop" -m '{"event":"stop"}'
+//!
+//! With --event, stdin is the JSON that Claude Code passes to the hook, and the
+//! event payload and topic are built from it (no JSON handling needed in the hook).
+//!
+//! When an encryption key is given (--key or CLAUDE_NOTIFY_KEY), the payload is
+//! encrypted with AES-256-GCM: {"nonce": "<base64>", "ciphertext": "<base64>"}
+//!
+//! When a shared secret is given (--secret or CLAUDE_NOTIFY_SECRET), the payload
+//! (encrypted or not) is wrapped in a signed envelope:
+//! {"payload": "<json>", "signature": "<hmac-sha256 hex>"}
+//! The app rejects signed payloads without a `timestamp` field or with one outside
+//! its signature window, so include one when publishing with -m / --stdin.
 
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use clap::Parser;
+use hmac::{Hmac, Mac};
 use rumqttc::{Client, MqttOptions, QoS};
+use sha2::Sha256;
 use std::io::{self, Read};
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
+mod event;
+
 #[derive(Parser, Debug)]
 #[command(name = "mqtt-publish")]
 #[command(version)]
@@ -27,14 +48,30 @@ struct Args {
     #[arg(short = 'p', long, default_value_t = 1883)]
     port: u16,
 
-    /// MQTT topic
-    #[arg(short = 't', long)]
-    topic: String,
+    /// MQTT topic (defaults to the event's topic with --event)
+    #[arg(short = 't', long, required_unless_present = "event")]
+    topic: Option<String>,
 
     /// Message payload (mutually exclusive with --stdin)
     #[arg(short = 'm', long, conflicts_with = "stdin")]
     message: Option<String>,
 
+    /// Build the payload for this event from the hook input on stdin
+    #[arg(long, value_enum, conflicts_with_all = ["message", "stdin"])]
+    event: Option<event::Event>,
+
+    /// Host name sent with --event (defaults to the computer name)
+    #[arg(long, env = "CLAUDE_NOTIFY_SOURCE_HOST")]
+    source_host: Option<String>,
+
+    /// Template version of the hook script, sent with --event
+    #[arg(long)]
+    template_version: Option<u32>,
+
+    /// Characters of Claude's final message sent with --event stop (0 sends none)
+    #[arg(long, default_value_t = 0)]
+    last_message_length: usize,
+
     /// Read message from stdin
     #[arg(long)]
     stdin: bool,
@@ -46,39 +83,65 @@ struct Args {
     /// Connection timeout in seconds
     #[arg(long, default_value_t = 5)]
     timeout: u64,
+
+    /// Shared secret for HMAC-SHA256 payload signing (empty disables signing)
+    #[arg(long, env = "CLAUDE_NOTIFY_SECRET", hide_env_values = true)]
+    secret: Option<String>,
+
+    /// AES-256 key (32 bytes, base64) for payload encryption (empty disables encryption)
+    #[arg(long, env = "CLAUDE_NOTIFY_KEY", hide_env_values = true)]
+    key: Option<String>,
 }
 
 fn main() {
     let args = Args::parse();
 
-    // Get message content
-    let payload = if args.stdin {
-        let mut buffer = Vec::new();
-        if let Err(e) = io::stdin().read_to_end(&mut buffer) {
-            eprintln!("Failed to read from stdin: {}", e);
-            std::process::exit(1);
-        }
-
-        // Convert to UTF-8, stripping BOM if present (PowerShell adds UTF-8 BOM)
-        let s = if buffer.starts_with(&[0xEF, 0xBB, 0xBF]) {
-            // UTF-8 BOM detected, skip it
-            String::from_utf8_lossy(&buffer[3..]).to_string()
-        } else if buffer.starts_with(&[0xFF, 0xFE]) {
-            // UTF-16 LE BOM
-            let utf16: Vec<u16> = buffer[2..].chunks(2)
-                .filter_map(|c| if c.len() == 2 { Some(u16::from_le_bytes([c[0], c[1]])) } else { None })
-                .collect();
-            String::from_utf16_lossy(&utf16)
+    // Get message content (and the topic, which --event decides)
+    let (topic, payload, retain) = if let Some(event) = args.event {
+        let source_host = args.source_host.clone().unwrap_or_else(default_source_host);
+        let cwd = std::env::current_dir()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let origin = event::Origin {
+            host: &source_host,
+            cwd: &cwd,
+            template_version: args.template_version,
+            last_message_length: args.last_message_length,
+        };
+        let message = event::build(event, &read_stdin(), &origin);
+        (
+            args.topic.clone().unwrap_or(message.topic),
+            message.payload,
+            args.retain || message.retain,
+        )
+    } else {
+        let payload = if args.stdin {
+            read_stdin()
+        } else if let Some(msg) = args.message.clone() {
+            msg
         } else {
-            String::from_utf8_lossy(&buffer).to_string()
+            eprintln!("Error: Either --message, --stdin or --event must be provided");
+            std::process::exit(1);
         };
+        (args.topic.clone().unwrap_or_default(), payload, args.retain)
+    };
 
-        s.trim_end().to_string()
-    } else if let Some(msg) = args.message {
-        msg
-    } else {
-        eprintln!("Error: Either --message or --stdin must be provided");
-        std::process::exit(1);
+    // Encrypt payload when an encryption key is configured
+    let payload = match args.key.as_deref().map(str::trim).filter(|k| !k.is_empty()) {
+        Some(key) => match encrypt_payload(&payload, key) {
+            Ok(encrypted) => encrypted,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => payload,
+    };
+
+    // Sign payload when a shared secret is configured
+    let payload = match args.secret.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(secret) => sign_payload(&payload, secret),
+        None => payload,
     };
 
     // Use channel to communicate between threads
@@ -86,8 +149,6 @@ fn main() {
     let timeout_secs = args.timeout;
     let host = args.host.clone();
     let port = args.port;
-    let topic = args.topic.clone();
-    let retain = args.retain;
 
     // Spawn worker thread for MQTT operations
     thread::spawn(move || {
@@ -115,11 +176,80 @@ fn main() {
     }
 }
 
+/// Read the message from stdin
+fn read_stdin() -> String {
+    let mut buffer = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut buffer) {
+        eprintln!("Failed to read from stdin: {}", e);
+        std::process::exit(1);
+    }
+
+    // Convert to UTF-8, stripping BOM if present (PowerShell adds UTF-8 BOM)
+    let s = if buffer.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        // UTF-8 BOM detected, skip it
+        String::from_utf8_lossy(&buffer[3..]).to_string()
+    } else if buffer.starts_with(&[0xFF, 0xFE]) {
+        // UTF-16 LE BOM
+        let utf16: Vec<u16> = buffer[2..].chunks(2)
+            .filter_map(|c| if c.len() == 2 { Some(u16::from_le_bytes([c[0], c[1]])) } else { None })
+            .collect();
+        String::from_utf16_lossy(&utf16)
+    } else {
+        String::from_utf8_lossy(&buffer).to_string()
+    };
+
+    s.trim_end().to_string()
+}
+
+/// Computer name used as the host of --event payloads
+fn default_source_host() -> String {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Encrypt the payload with AES-256-GCM
+fn encrypt_payload(payload: &str, key: &str) -> Result<String, String> {
+    let key = BASE64
+        .decode(key)
+        .ok()
+        .filter(|k| k.len() == 32)
+        .ok_or_else(|| "Encryption key must be 32 bytes encoded in base64".to_string())?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, payload.as_bytes())
+        .map_err(|e| format!("Failed to encrypt: {}", e))?;
+
+    Ok(serde_json::json!({
+        "nonce": BASE64.encode(nonce),
+        "ciphertext": BASE64.encode(ciphertext),
+    })
+    .to_string())
+}
+
+/// Wrap the payload in a signed envelope
+fn sign_payload(payload: &str, secret: &str) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    let signature = hex::encode(mac.finalize().into_bytes());
+
+    serde_json::json!({
+        "payload": payload,
+        "signature": signature,
+    })
+    .to_string()
+}
+
 fn publish_message(host: &str, port: u16, topic: &str, retain: bool, payload: &str) -> Result<(), String> {
     // Create MQTT client with unique client ID
     let client_id = format!("mqtt-publish-{}", std::process::id());
     let mut options = MqttOptions::new(client_id, host, port);
     options.set_keep_alive(Duration::from_secs(5));
+    // The broker rejects connections without a login when access control is enabled
+    options.set_credentials("claude-code-notify", "");
 
     let (client, mut connection) = Client::new(options, 10);
 
@@ -0,0 +1,135 @@
+//! 通知の確認（ack）を MQTT で送信するモジュール
+//!
+//! トーストのクリックや履歴の既読化で通知が確認されたときに
+//! `claude-code/ack/<session_id>` へメッセージを送信し、
+//! フックスクリプトなどが反応できるようにする（端末のベルを止める等）。
+
+use crate::client::{topics, MqttPublisher};
+use crate::notification_history::{NotificationEventType, NotificationHistoryEntry};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, warn};
+
+/// 通知が確認された操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AckSource {
+    /// トースト通知のクリック
+    Toast,
+    /// 通知履歴での既読化
+    History,
+}
+
+/// ack メッセージのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct AckPayload {
+    pub session_id: String,
+    /// 通知履歴のID（トーストからの確認では None）
+    pub notification_id: Option<u64>,
+    pub event_type: NotificationEventType,
+    pub source: AckSource,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl AckPayload {
+    /// 履歴エントリの既読化に対応する ack
+    pub fn from_entry(entry: &NotificationHistoryEntry) -> Self {
+        Self {
+            session_id: entry.session_id.clone(),
+            notification_id: Some(entry.id),
+            event_type: entry.event_type.clone(),
+            source: AckSource::History,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// セッションIDから ack のトピックを作成する
+///
+/// セッションIDが空の場合は None。ワイルドカードや階層区切りの文字は置き換える。
+pub fn ack_topic(session_id: &str) -> Option<String> {
+    topics::for_session(topics::ACK_PREFIX, session_id)
+}
+
+/// ack メッセージを送信する
+pub fn publish_ack(app: &AppHandle, payload: &AckPayload) {
+    let Some(topic) = ack_topic(&payload.session_id) else {
+        return;
+    };
+    let Some(publisher) = app.try_state::<MqttPublisher>() else {
+        warn!("MQTT client is not ready, ack not sent");
+        return;
+    };
+    let body = match serde_json::to_vec(payload) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to serialize ack payload: {}", e);
+            return;
+        }
+    };
+
+    match publisher.publish(&topic, body, false) {
+        Ok(()) => debug!("Ack published on {}", topic),
+        Err(e) => warn!("Failed to publish ack on {}: {}", topic, e),
+    }
+}
+
+/// トーストのクリック時に ack を送信するコールバックを作成する
+pub fn on_toast_activated(
+    app: &AppHandle,
+    session_id: &str,
+    event_type: &NotificationEventType,
+) -> Option<crate::toast::OnActivated> {
+    ack_topic(session_id)?;
+
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    let event_type = event_type.clone();
+    Some(Box::new(move |_action: Option<&str>| {
+        publish_ack(
+            &app,
+            &AckPayload {
+                session_id,
+                notification_id: None,
+                event_type,
+                source: AckSource::Toast,
+                timestamp: Utc::now(),
+            },
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ack_topic() {
+        assert_eq!(
+            ack_topic("abc-123").as_deref(),
+            Some("claude-code/ack/abc-123")
+        );
+        assert_eq!(
+            ack_topic("a/b+c#").as_deref(),
+            Some("claude-code/ack/a_b_c_")
+        );
+        assert_eq!(ack_topic("  "), None);
+    }
+
+    #[test]
+    fn test_payload_serialization() {
+        let payload = AckPayload {
+            session_id: "s1".to_string(),
+            notification_id: Some(3),
+            event_type: NotificationEventType::PermissionRequest,
+            source: AckSource::History,
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["session_id"], "s1");
+        assert_eq!(json["notification_id"], 3);
+        assert_eq!(json["event_type"], "PermissionRequest");
+        assert_eq!(json["source"], "history");
+    }
+}
@@ -0,0 +1,112 @@
+//! ブローカーのアクセス制御モジュール
+//!
+//! rumqttd にはトピック単位の ACL フックがないため、接続時の認証ハンドラで
+//! クライアントIDの接頭辞とユーザー名を検査し、どちらも許可リストにないクライアントの接続を拒否する。
+//! 拒否した接続は `security_events` モジュールに記録する。
+//! 接続を拒否されたクライアントは publish も subscribe もできないため、
+//! 他のクライアントの通信を購読されることはない。
+//! アプリ側は `claude-code/#` と設定で追加したトピックのみを購読しているので、許可されたクライアントが
+//! それ以外のトピックに publish しても通知は発生しない。
+
+use serde::{Deserialize, Serialize};
+
+/// アプリ内部の購読クライアントのID（許可リストに関係なく常に許可する）
+pub const INTERNAL_CLIENT_ID: &str = "claude-code-notify-client";
+
+/// フックスクリプト・mqtt-publish が接続時に送るユーザー名
+///
+/// 認証ハンドラを設定した rumqttd はログイン情報のない CONNECT を拒否するため、
+/// 各クライアントはこのユーザー名（パスワードは空）を付けて接続する。
+pub const CLIENT_USERNAME: &str = "claude-code-notify";
+
+/// ブローカーのアクセス制御設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrokerAclSettings {
+    /// アクセス制御を有効にするか（切り替えはアプリの再起動後に反映）
+    pub enabled: bool,
+    /// 接続を許可するクライアントIDの接頭辞
+    pub client_id_prefixes: Vec<String>,
+    /// 接続を許可するユーザー名（完全一致、クライアントIDに関係なく許可する）
+    pub usernames: Vec<String>,
+}
+
+impl Default for BrokerAclSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            client_id_prefixes: vec!["claude-code-".to_string(), "mqtt-publish-".to_string()],
+            usernames: Vec::new(),
+        }
+    }
+}
+
+impl BrokerAclSettings {
+    /// クライアントIDかユーザー名が接続を許可されているか
+    pub fn is_client_allowed(&self, client_id: &str, username: &str) -> bool {
+        if !self.enabled || client_id == INTERNAL_CLIENT_ID {
+            return true;
+        }
+        let prefix_allowed = self
+            .client_id_prefixes
+            .iter()
+            .map(|prefix| prefix.trim())
+            .filter(|prefix| !prefix.is_empty())
+            .any(|prefix| client_id.starts_with(prefix));
+        let username_allowed = !username.is_empty() && self.usernames.iter().any(|allowed| allowed.trim() == username);
+        prefix_allowed || username_allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(prefixes: &[&str]) -> BrokerAclSettings {
+        BrokerAclSettings {
+            enabled: true,
+            client_id_prefixes: prefixes.iter().map(|p| p.to_string()).collect(),
+            usernames: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_disabled_allows_everyone() {
+        let acl = BrokerAclSettings::default();
+        assert!(acl.is_client_allowed("anything", ""));
+    }
+
+    #[test]
+    fn test_prefix_allowlist() {
+        let acl = enabled(&["claude-code-", " mqtt-publish- "]);
+        assert!(acl.is_client_allowed("claude-code-12345", CLIENT_USERNAME));
+        assert!(acl.is_client_allowed("mqtt-publish-42", CLIENT_USERNAME));
+        assert!(!acl.is_client_allowed("mosq-abcdef", CLIENT_USERNAME));
+        assert!(!acl.is_client_allowed("", CLIENT_USERNAME));
+    }
+
+    #[test]
+    fn test_username_allowlist() {
+        let acl = BrokerAclSettings {
+            usernames: vec![" sensor ".to_string(), String::new()],
+            ..enabled(&["claude-code-"])
+        };
+        assert!(acl.is_client_allowed("esp32-kitchen", "sensor"));
+        assert!(acl.is_client_allowed("claude-code-1", ""));
+        assert!(!acl.is_client_allowed("esp32-kitchen", "admin"));
+        // 空のユーザー名は許可リストの空行に一致しない
+        assert!(!acl.is_client_allowed("esp32-kitchen", ""));
+    }
+
+    #[test]
+    fn test_empty_prefix_does_not_allow_everyone() {
+        let acl = enabled(&["", "  "]);
+        assert!(!acl.is_client_allowed("mosq-abcdef", CLIENT_USERNAME));
+    }
+
+    #[test]
+    fn test_internal_client_always_allowed() {
+        let acl = enabled(&[]);
+        assert!(acl.is_client_allowed(INTERNAL_CLIENT_ID, CLIENT_USERNAME));
+    }
+}
@@ -0,0 +1,363 @@
+//! REST API のアクセストークンの管理モジュール
+//!
+//! スクリプト・Stream Deck・ダッシュボードなどのツールごとに名前を付けたトークンを発行し、
+//! 個別に再発行（ローテーション）・失効できるようにする。
+//! トークンは発行した時に一度だけ表示し、アプリには SHA-256 のハッシュだけを OS のキーチェーンに保存する
+//! （キーチェーンを使えない環境では `api_tokens.json` に保存する）。
+//! 発行・再発行・失効はアプリの再起動を待たずに REST API の認証に反映する（保存に失敗した場合は反映しない）。
+
+use crate::credentials::{self, SecretStore};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::RwLock;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// キーチェーンのアカウント名
+const KEYCHAIN_ACCOUNT: &str = "rest-api/tokens";
+/// キーチェーンを使えない場合の保存先
+const STORE_FILE: &str = "api_tokens.json";
+const STORE_KEY: &str = "tokens";
+
+/// 一覧に表示するトークンの情報（トークン自体は含まない）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiTokenInfo {
+    pub id: String,
+    /// 使うツールの名前
+    pub name: String,
+    /// トークンの末尾4文字（どのトークンかを見分けるため）
+    pub hint: String,
+    /// 発行（再発行）した時刻
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    #[serde(flatten)]
+    info: ApiTokenInfo,
+    /// トークンの SHA-256（16進文字列）
+    hash: String,
+}
+
+/// 発行したトークン（`token` は発行した時だけ返す）
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedToken {
+    #[serde(flatten)]
+    pub info: ApiTokenInfo,
+    pub token: String,
+}
+
+#[derive(Debug, Error)]
+pub enum ApiTokenError {
+    #[error("token name is empty")]
+    EmptyName,
+    #[error("token not found: {0}")]
+    NotFound(String),
+    #[error("failed to save tokens: {0}")]
+    Save(String),
+}
+
+/// トークンを生成する（32バイトの乱数を16進文字列で返す）
+fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    hex::encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn stored_token(id: String, name: &str, token: &str, now: DateTime<Utc>) -> StoredToken {
+    let hint = token.chars().rev().take(4).collect::<Vec<_>>().into_iter().rev().collect();
+    StoredToken {
+        info: ApiTokenInfo {
+            id,
+            name: name.to_string(),
+            hint,
+            created_at: now,
+        },
+        hash: hash_token(token),
+    }
+}
+
+/// 発行したトークン（ハッシュ）の一覧
+#[derive(Default)]
+pub struct ApiTokens {
+    tokens: RwLock<Vec<StoredToken>>,
+}
+
+impl ApiTokens {
+    /// 保存したトークンを読み込む
+    pub fn load(app: &AppHandle) -> Self {
+        let stored = match credentials::keychain().get(KEYCHAIN_ACCOUNT) {
+            Ok(Some(json)) => Some(json),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to read API tokens from the keychain: {}", e);
+                None
+            }
+        };
+        let tokens = match stored {
+            Some(json) => serde_json::from_str(&json),
+            None => app
+                .store(STORE_FILE)
+                .ok()
+                .and_then(|store| store.get(STORE_KEY))
+                .map_or(Ok(Vec::new()), serde_json::from_value),
+        };
+        match tokens {
+            Ok(tokens) => Self {
+                tokens: RwLock::new(tokens),
+            },
+            Err(e) => {
+                warn!("Failed to parse API tokens: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// 一覧を変更して保存し、保存できた場合だけ反映する
+    ///
+    /// 保存に失敗した場合は、表示していないトークンが使えるようになったり、
+    /// 再起動後に失効したトークンが使えるようになったりしないよう、以前の一覧のままにする。
+    fn update<T>(
+        &self,
+        change: impl FnOnce(&mut Vec<StoredToken>) -> Result<T, ApiTokenError>,
+        persist: impl FnOnce(&[StoredToken]) -> Result<(), ApiTokenError>,
+    ) -> Result<T, ApiTokenError> {
+        let mut tokens = self.tokens.write().unwrap();
+        let mut updated = tokens.clone();
+        let result = change(&mut updated)?;
+        persist(&updated)?;
+        *tokens = updated;
+        Ok(result)
+    }
+
+    /// トークンの一覧（発行した順）
+    pub fn list(&self) -> Vec<ApiTokenInfo> {
+        self.tokens.read().unwrap().iter().map(|token| token.info.clone()).collect()
+    }
+
+    /// トークンが1つもないか
+    pub fn is_empty(&self) -> bool {
+        self.tokens.read().unwrap().is_empty()
+    }
+
+    /// 一致するトークンが発行済みか
+    pub fn verify(&self, token: &str) -> bool {
+        let hash = hash_token(token);
+        // 一致した時点で止めず、すべてのトークンと比較する
+        self.tokens
+            .read()
+            .unwrap()
+            .iter()
+            .fold(false, |found, stored| constant_time_eq(stored.hash.as_bytes(), hash.as_bytes()) | found)
+    }
+
+    fn issue(
+        &self,
+        name: &str,
+        now: DateTime<Utc>,
+        persist: impl FnOnce(&[StoredToken]) -> Result<(), ApiTokenError>,
+    ) -> Result<IssuedToken, ApiTokenError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ApiTokenError::EmptyName);
+        }
+        self.update(
+            |tokens| {
+                let token = generate_token();
+                let stored = stored_token(generate_token()[..16].to_string(), name, &token, now);
+                let info = stored.info.clone();
+                tokens.push(stored);
+                Ok(IssuedToken { info, token })
+            },
+            persist,
+        )
+    }
+
+    fn rotate(
+        &self,
+        id: &str,
+        now: DateTime<Utc>,
+        persist: impl FnOnce(&[StoredToken]) -> Result<(), ApiTokenError>,
+    ) -> Result<IssuedToken, ApiTokenError> {
+        self.update(
+            |tokens| {
+                let stored = tokens
+                    .iter_mut()
+                    .find(|token| token.info.id == id)
+                    .ok_or_else(|| ApiTokenError::NotFound(id.to_string()))?;
+                let token = generate_token();
+                *stored = stored_token(id.to_string(), &stored.info.name, &token, now);
+                Ok(IssuedToken {
+                    info: stored.info.clone(),
+                    token,
+                })
+            },
+            persist,
+        )
+    }
+
+    fn revoke(&self, id: &str, persist: impl FnOnce(&[StoredToken]) -> Result<(), ApiTokenError>) -> Result<(), ApiTokenError> {
+        self.update(
+            |tokens| {
+                let before = tokens.len();
+                tokens.retain(|token| token.info.id != id);
+                if tokens.len() == before {
+                    return Err(ApiTokenError::NotFound(id.to_string()));
+                }
+                Ok(())
+            },
+            persist,
+        )
+    }
+
+    /// 以前のバージョンの設定のトークンを、名前を付けて一覧に加える
+    pub fn import(&self, app: &AppHandle, name: &str, token: &str) -> Result<(), ApiTokenError> {
+        if !self.verify(token) {
+            self.update(
+                |tokens| {
+                    tokens.push(stored_token(generate_token()[..16].to_string(), name, token, Utc::now()));
+                    Ok(())
+                },
+                |tokens| save(app, tokens),
+            )?;
+            info!("Imported REST API token from settings as {:?}", name);
+        }
+        Ok(())
+    }
+}
+
+/// キーチェーン（使えない場合はファイル）に保存する
+fn save(app: &AppHandle, tokens: &[StoredToken]) -> Result<(), ApiTokenError> {
+    let json = serde_json::to_value(tokens).map_err(|e| ApiTokenError::Save(e.to_string()))?;
+    let store = app.store(STORE_FILE).map_err(|e| ApiTokenError::Save(e.to_string()))?;
+    match credentials::keychain().set(KEYCHAIN_ACCOUNT, &json.to_string()) {
+        Ok(()) => {
+            store.delete(STORE_KEY);
+        }
+        Err(e) => {
+            warn!("Saving API tokens to {} instead of the keychain: {}", STORE_FILE, e);
+            store.set(STORE_KEY, json);
+        }
+    }
+    store.save().map_err(|e| ApiTokenError::Save(e.to_string()))
+}
+
+/// Tauriコマンド: トークンの一覧
+#[tauri::command]
+pub fn list_api_tokens(tokens: tauri::State<'_, ApiTokens>) -> Vec<ApiTokenInfo> {
+    tokens.list()
+}
+
+/// Tauriコマンド: トークンを発行する（トークンはこの応答でだけ返す）
+#[tauri::command]
+pub fn issue_api_token(app: AppHandle, tokens: tauri::State<'_, ApiTokens>, name: String) -> Result<IssuedToken, String> {
+    let issued = tokens
+        .issue(&name, Utc::now(), |list| save(&app, list))
+        .map_err(|e| e.to_string())?;
+    info!("Issued REST API token {:?}", issued.info.name);
+    Ok(issued)
+}
+
+/// Tauriコマンド: トークンを再発行する（以前のトークンはすぐに使えなくなる）
+#[tauri::command]
+pub fn rotate_api_token(app: AppHandle, tokens: tauri::State<'_, ApiTokens>, id: String) -> Result<IssuedToken, String> {
+    let issued = tokens
+        .rotate(&id, Utc::now(), |list| save(&app, list))
+        .map_err(|e| e.to_string())?;
+    info!("Rotated REST API token {:?}", issued.info.name);
+    Ok(issued)
+}
+
+/// Tauriコマンド: トークンを失効させる
+#[tauri::command]
+pub fn revoke_api_token(app: AppHandle, tokens: tauri::State<'_, ApiTokens>, id: String) -> Result<(), String> {
+    tokens.revoke(&id, |list| save(&app, list)).map_err(|e| e.to_string())?;
+    info!("Revoked REST API token {}", id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(_: &[StoredToken]) -> Result<(), ApiTokenError> {
+        Ok(())
+    }
+
+    fn save_failed(_: &[StoredToken]) -> Result<(), ApiTokenError> {
+        Err(ApiTokenError::Save("disk full".to_string()))
+    }
+
+    #[test]
+    fn test_issue_rotate_revoke() {
+        let tokens = ApiTokens::default();
+        let now = Utc::now();
+        assert!(matches!(tokens.issue(" ", now, saved), Err(ApiTokenError::EmptyName)));
+
+        let deck = tokens.issue("Stream Deck", now, saved).unwrap();
+        let waybar = tokens.issue("waybar", now, saved).unwrap();
+        assert_eq!(deck.token.len(), 64);
+        assert_eq!(deck.info.hint, &deck.token[60..]);
+        assert!(tokens.verify(&deck.token) && tokens.verify(&waybar.token));
+        assert!(!tokens.verify("wrong"));
+        assert_eq!(tokens.list(), vec![deck.info.clone(), waybar.info.clone()]);
+
+        // 再発行すると以前のトークンは使えなくなる
+        let rotated = tokens.rotate(&deck.info.id, now, saved).unwrap();
+        assert_eq!(rotated.info.id, deck.info.id);
+        assert_eq!(rotated.info.name, "Stream Deck");
+        assert!(!tokens.verify(&deck.token));
+        assert!(tokens.verify(&rotated.token));
+
+        tokens.revoke(&waybar.info.id, saved).unwrap();
+        assert!(!tokens.verify(&waybar.token));
+        assert!(matches!(
+            tokens.revoke(&waybar.info.id, saved),
+            Err(ApiTokenError::NotFound(_))
+        ));
+        assert_eq!(tokens.list().len(), 1);
+    }
+
+    #[test]
+    fn test_failed_save_keeps_previous_tokens() {
+        let tokens = ApiTokens::default();
+        let now = Utc::now();
+        let deck = tokens.issue("Stream Deck", now, saved).unwrap();
+
+        // 保存できなかったトークンは使えない
+        assert!(matches!(
+            tokens.issue("waybar", now, save_failed),
+            Err(ApiTokenError::Save(_))
+        ));
+        assert_eq!(tokens.list(), vec![deck.info.clone()]);
+
+        // 保存できなかった再発行・失効では以前のトークンを使い続ける
+        assert!(tokens.rotate(&deck.info.id, now, save_failed).is_err());
+        assert!(tokens.verify(&deck.token));
+        assert!(tokens.revoke(&deck.info.id, save_failed).is_err());
+        assert!(tokens.verify(&deck.token));
+        assert_eq!(tokens.list(), vec![deck.info]);
+    }
+
+    #[test]
+    fn test_stored_tokens_keep_only_hashes() {
+        let tokens = ApiTokens::default();
+        let issued = tokens.issue("script", Utc::now(), saved).unwrap();
+        let json = serde_json::to_string(&*tokens.tokens.read().unwrap()).unwrap();
+        assert!(!json.contains(&issued.token));
+        assert!(json.contains(&hash_token(&issued.token)));
+
+        let restored: Vec<StoredToken> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored[0].info, issued.info);
+    }
+}
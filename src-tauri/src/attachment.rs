@@ -0,0 +1,286 @@
+//! 通知の画像添付モジュール
+//!
+//! フックのペイロードの `image` に指定された画像を読み込み、アプリのキャッシュディレクトリに保存する。
+//! 保存した画像はトーストのヒーロー画像と通知履歴の表示に使う。
+//!
+//! 指定できる形式:
+//! - `data:image/png;base64,...` 形式の data URI（リモートのフックからはこの形式を推奨）
+//! - `http://` / `https://` の URL（アプリがダウンロードする）
+//! - このアプリが動いているマシン上のファイルパス
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use image::ImageFormat;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// 添付できる画像の最大サイズ
+const MAX_IMAGE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// 画像をダウンロードする際のタイムアウト（メッセージの処理を長く止めないよう短くする）
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// キャッシュディレクトリ内の保存先
+const ATTACHMENT_DIR: &str = "attachments";
+
+#[derive(Debug, Error)]
+pub enum AttachmentError {
+    #[error("invalid data URI")]
+    InvalidDataUri,
+    #[error("image is larger than {MAX_IMAGE_BYTES} bytes")]
+    TooLarge,
+    #[error("unsupported image format (PNG, JPEG or GIF only)")]
+    UnsupportedFormat,
+    #[error("failed to download image: {0}")]
+    Download(String),
+    #[error("invalid attachment name: {0}")]
+    InvalidName(String),
+    #[error("cache directory is not available: {0}")]
+    CacheDir(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// ペイロードでの画像の指定方法
+#[derive(Debug, PartialEq, Eq)]
+enum ImageSource<'a> {
+    DataUri(&'a str),
+    Url(&'a str),
+    Path(&'a Path),
+}
+
+impl<'a> ImageSource<'a> {
+    fn parse(image: &'a str) -> Option<Self> {
+        let image = image.trim();
+        if image.is_empty() {
+            None
+        } else if image.starts_with("data:") {
+            Some(ImageSource::DataUri(image))
+        } else if image.starts_with("http://") || image.starts_with("https://") {
+            Some(ImageSource::Url(image))
+        } else {
+            Some(ImageSource::Path(Path::new(image)))
+        }
+    }
+}
+
+/// 読み込み元から最大サイズまで読み込む
+fn read_limited(reader: impl Read) -> Result<Vec<u8>, AttachmentError> {
+    let mut bytes = Vec::new();
+    reader.take(MAX_IMAGE_BYTES + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_IMAGE_BYTES {
+        return Err(AttachmentError::TooLarge);
+    }
+    Ok(bytes)
+}
+
+/// base64 の data URI を復号する
+fn decode_data_uri(uri: &str) -> Result<Vec<u8>, AttachmentError> {
+    let (header, data) = uri
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(','))
+        .ok_or(AttachmentError::InvalidDataUri)?;
+    if !header.ends_with(";base64") {
+        return Err(AttachmentError::InvalidDataUri);
+    }
+    // base64 は元のサイズの約 4/3 倍
+    if data.len() as u64 > MAX_IMAGE_BYTES / 3 * 4 + 4 {
+        return Err(AttachmentError::TooLarge);
+    }
+    BASE64
+        .decode(data.trim())
+        .map_err(|_| AttachmentError::InvalidDataUri)
+}
+
+fn download(url: &str) -> Result<Vec<u8>, AttachmentError> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .get(url)
+        .call()
+        .map_err(|e| AttachmentError::Download(e.to_string()))?;
+    read_limited(response.into_reader())
+}
+
+/// 画像の形式を判定し、保存に使う拡張子を返す
+fn image_extension(bytes: &[u8]) -> Result<&'static str, AttachmentError> {
+    match image::guess_format(bytes) {
+        Ok(ImageFormat::Png) => Ok("png"),
+        Ok(ImageFormat::Jpeg) => Ok("jpg"),
+        Ok(ImageFormat::Gif) => Ok("gif"),
+        _ => Err(AttachmentError::UnsupportedFormat),
+    }
+}
+
+/// 保存する画像のファイル名（内容のハッシュなので同じ画像は1つのファイルになる）
+fn attachment_name(bytes: &[u8], extension: &str) -> String {
+    let digest = hex::encode(Sha256::digest(bytes));
+    format!("{}.{}", &digest[..32], extension)
+}
+
+/// このモジュールが作成したファイル名か（パスの指定を受け付けない）
+fn is_valid_name(name: &str) -> bool {
+    name.split_once('.').is_some_and(|(stem, extension)| {
+        !stem.is_empty()
+            && stem.chars().all(|c| c.is_ascii_hexdigit())
+            && matches!(extension, "png" | "jpg" | "gif")
+    })
+}
+
+fn mime_type(name: &str) -> &'static str {
+    match name.rsplit('.').next() {
+        Some("jpg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        _ => "image/png",
+    }
+}
+
+fn attachment_dir(app: &AppHandle) -> Result<PathBuf, AttachmentError> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join(ATTACHMENT_DIR))
+        .map_err(|e| AttachmentError::CacheDir(e.to_string()))
+}
+
+/// 保存した画像のパスを取得する
+pub fn path(app: &AppHandle, name: &str) -> Result<PathBuf, AttachmentError> {
+    if !is_valid_name(name) {
+        return Err(AttachmentError::InvalidName(name.to_string()));
+    }
+    Ok(attachment_dir(app)?.join(name))
+}
+
+/// ペイロードの画像を読み込んで保存し、ファイル名を返す
+pub fn store(app: &AppHandle, image: &str) -> Result<Option<String>, AttachmentError> {
+    let bytes = match ImageSource::parse(image) {
+        None => return Ok(None),
+        Some(ImageSource::DataUri(uri)) => decode_data_uri(uri)?,
+        Some(ImageSource::Url(url)) => download(url)?,
+        Some(ImageSource::Path(path)) => read_limited(std::fs::File::open(path)?)?,
+    };
+    let name = attachment_name(&bytes, image_extension(&bytes)?);
+
+    let dir = attachment_dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(&name);
+    if !path.exists() {
+        std::fs::write(&path, &bytes)?;
+        info!("Attachment saved: {}", name);
+    }
+    Ok(Some(name))
+}
+
+/// ペイロードの画像を保存する（失敗した場合は画像なしで通知する）
+pub fn store_from_payload(app: &AppHandle, image: Option<&str>) -> Option<String> {
+    match store(app, image?) {
+        Ok(name) => name,
+        Err(e) => {
+            warn!("Failed to attach image: {}", e);
+            None
+        }
+    }
+}
+
+/// 保存した画像を data URI で取得する（通知履歴での表示用）
+pub fn data_uri(app: &AppHandle, name: &str) -> Result<String, AttachmentError> {
+    let bytes = std::fs::read(path(app, name)?)?;
+    Ok(format!("data:{};base64,{}", mime_type(name), BASE64.encode(bytes)))
+}
+
+/// 通知履歴から参照されなくなった画像を削除する
+pub fn prune(app: &AppHandle, keep: &HashSet<String>) {
+    let Ok(dir) = attachment_dir(app) else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if is_valid_name(&name) && !keep.contains(&name) {
+            if let Err(e) = std::fs::remove_file(entry.path()) {
+                warn!("Failed to remove attachment {}: {}", name, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1x1 の PNG
+    const PNG: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    ];
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(ImageSource::parse("  "), None);
+        assert_eq!(
+            ImageSource::parse("data:image/png;base64,AAAA"),
+            Some(ImageSource::DataUri("data:image/png;base64,AAAA"))
+        );
+        assert_eq!(
+            ImageSource::parse("https://example.com/a.png"),
+            Some(ImageSource::Url("https://example.com/a.png"))
+        );
+        assert_eq!(
+            ImageSource::parse("/tmp/diff.png"),
+            Some(ImageSource::Path(Path::new("/tmp/diff.png")))
+        );
+    }
+
+    #[test]
+    fn test_decode_data_uri() {
+        let uri = format!("data:image/png;base64,{}", BASE64.encode(PNG));
+        assert_eq!(decode_data_uri(&uri).unwrap(), PNG);
+        assert!(matches!(
+            decode_data_uri("data:image/png,rawdata"),
+            Err(AttachmentError::InvalidDataUri)
+        ));
+        assert!(matches!(
+            decode_data_uri("data:image/png;base64,***"),
+            Err(AttachmentError::InvalidDataUri)
+        ));
+    }
+
+    #[test]
+    fn test_read_limited() {
+        let large = vec![0u8; MAX_IMAGE_BYTES as usize + 1];
+        assert!(matches!(read_limited(large.as_slice()), Err(AttachmentError::TooLarge)));
+        assert_eq!(read_limited(PNG).unwrap(), PNG);
+    }
+
+    #[test]
+    fn test_image_extension() {
+        assert_eq!(image_extension(PNG).unwrap(), "png");
+        assert_eq!(image_extension(&[0xff, 0xd8, 0xff, 0xe0]).unwrap(), "jpg");
+        assert!(matches!(image_extension(b"<svg>"), Err(AttachmentError::UnsupportedFormat)));
+    }
+
+    #[test]
+    fn test_attachment_name_is_valid() {
+        let name = attachment_name(PNG, "png");
+        assert!(name.ends_with(".png"));
+        assert!(is_valid_name(&name));
+        assert_eq!(name, attachment_name(PNG, "png"));
+
+        assert!(!is_valid_name("../settings.json"));
+        assert!(!is_valid_name("abc.exe"));
+        assert!(!is_valid_name(".png"));
+    }
+
+    #[test]
+    fn test_mime_type() {
+        assert_eq!(mime_type("abc.jpg"), "image/jpeg");
+        assert_eq!(mime_type("abc.png"), "image/png");
+    }
+}
@@ -0,0 +1,137 @@
+//! 注意を引く表示（点滅・バッジ・進捗）の抽象化モジュール
+//!
+//! タスクバーや Dock など、プラットフォームごとの表示を [`AttentionSignal`] にまとめ、
+//! NotificationManager はプラットフォームを意識せずに呼び出す。
+//! 実装は実行中のプラットフォームに合わせて選ぶ（Windows はタスクバー、macOS / Linux は
+//! ウィンドウの注意要求と Dock・ランチャーのバッジ）。トレイアイコン（tray_flash）も同じトレイトを実装する。
+//! 新しいプラットフォームは実装を追加して [`for_platform`] で選ぶ。
+
+use crate::notification_state::BadgeKind;
+use crate::settings::NotificationSettings;
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager, UserAttentionType, WebviewWindow};
+
+/// 進捗表示で示す状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttentionProgress {
+    /// 表示しない
+    Hidden,
+    /// 承認待ちの依頼がある（一時停止の色で表示する）
+    Waiting,
+}
+
+/// 通知に気付かせる表示（プラットフォームごとに実装する）
+pub trait AttentionSignal: Send + Sync {
+    /// 実装の名前（ログ用）
+    fn name(&self) -> &'static str;
+
+    /// 点滅などで注意を引く（回数や間隔は設定から読む）
+    fn flash(&self, app: &AppHandle, settings: &NotificationSettings) -> Result<(), String>;
+
+    /// 未確認数・承認待ち数のバッジを表示する（None の場合は消す）
+    fn badge(&self, app: &AppHandle, badge: Option<(BadgeKind, u32)>) -> Result<(), String>;
+
+    /// 進捗表示で状態を示す
+    fn progress(&self, app: &AppHandle, progress: AttentionProgress) -> Result<(), String>;
+
+    /// 点滅を止める（バッジは残す）
+    fn clear(&self, app: &AppHandle) -> Result<(), String>;
+}
+
+/// メインウィンドウ
+pub fn main_window(app: &AppHandle) -> Option<WebviewWindow> {
+    app.get_webview_window("main")
+}
+
+/// ウィンドウの進捗表示を更新する（Windows はタスクバー、macOS は Dock、Linux は対応するランチャー）
+pub fn set_window_progress(app: &AppHandle, progress: AttentionProgress) -> Result<(), String> {
+    let Some(window) = main_window(app) else {
+        return Ok(());
+    };
+    let state = match progress {
+        AttentionProgress::Hidden => ProgressBarState {
+            status: Some(ProgressBarStatus::None),
+            progress: None,
+        },
+        AttentionProgress::Waiting => ProgressBarState {
+            status: Some(ProgressBarStatus::Paused),
+            progress: Some(100),
+        },
+    };
+    window.set_progress_bar(state).map_err(|e| e.to_string())
+}
+
+/// ウィンドウの注意要求と Dock・ランチャーのバッジ（macOS / Linux）
+pub struct WindowSignal;
+
+impl AttentionSignal for WindowSignal {
+    fn name(&self) -> &'static str {
+        "window"
+    }
+
+    fn flash(&self, app: &AppHandle, _settings: &NotificationSettings) -> Result<(), String> {
+        let Some(window) = main_window(app) else {
+            return Ok(());
+        };
+        // macOS は Dock のアイコンを1回跳ねさせ、Linux は緊急ヒントを設定する
+        window
+            .request_user_attention(Some(UserAttentionType::Informational))
+            .map_err(|e| e.to_string())
+    }
+
+    fn badge(&self, app: &AppHandle, badge: Option<(BadgeKind, u32)>) -> Result<(), String> {
+        let Some(window) = main_window(app) else {
+            return Ok(());
+        };
+        // Dock のバッジは色を変えられないため件数のみ表示する
+        window
+            .set_badge_count(badge.map(|(_, count)| i64::from(count)))
+            .map_err(|e| e.to_string())
+    }
+
+    fn progress(&self, app: &AppHandle, progress: AttentionProgress) -> Result<(), String> {
+        set_window_progress(app, progress)
+    }
+
+    fn clear(&self, app: &AppHandle) -> Result<(), String> {
+        let Some(window) = main_window(app) else {
+            return Ok(());
+        };
+        window.request_user_attention(None).map_err(|e| e.to_string())
+    }
+}
+
+/// 表示の実装の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignalKind {
+    Taskbar,
+    Window,
+}
+
+/// OS 名（`std::env::consts::OS`）に対応する実装の種類
+fn signal_kind(os: &str) -> SignalKind {
+    match os {
+        "windows" => SignalKind::Taskbar,
+        _ => SignalKind::Window,
+    }
+}
+
+/// 実行中のプラットフォームの実装
+pub fn for_platform(app: &AppHandle) -> Box<dyn AttentionSignal> {
+    match signal_kind(std::env::consts::OS) {
+        SignalKind::Taskbar => Box::new(crate::taskbar::TaskbarSignal::new(app)),
+        SignalKind::Window => Box::new(WindowSignal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_kind() {
+        assert_eq!(signal_kind("windows"), SignalKind::Taskbar);
+        assert_eq!(signal_kind("macos"), SignalKind::Window);
+        assert_eq!(signal_kind("linux"), SignalKind::Window);
+    }
+}
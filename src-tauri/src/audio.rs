@@ -1,62 +1,428 @@
 //! 通知音再生モジュール
 //!
-//! rodio クレートを使用して MP3 音声を再生する
+//! rodio クレートを使用して MP3 / WAV 音声を再生する。
+//! 出力ストリームは専用スレッドで1つだけ開き、通知ごとに作り直さない。
+//! 長い音は設定した時間で打ち切り、始まりと終わりに短いフェードを掛けられる（[`Envelope`]）。
+//! MP3 は再生時間が分からないため、展開してから掛ける（[`buffered`]）。
 
-use rodio::{Decoder, OutputStream, Sink};
+use rodio::buffer::SamplesBuffer;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Deserialize, Serialize};
 use std::io::Cursor;
-use tracing::{error, info};
+use std::collections::HashMap;
+use std::sync::{mpsc, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
 
 /// 通知音データ（コンパイル時に埋め込み）
 static NOTIFICATION_SOUND: &[u8] = include_bytes!("../resources/sounds/notification.mp3");
+/// 控えめなクリック音
+static CLICK_SOUND: &[u8] = include_bytes!("../resources/sounds/click.wav");
+/// チャイム音
+static CHIME_SOUND: &[u8] = include_bytes!("../resources/sounds/chime.wav");
+/// アラート音
+static ALERT_SOUND: &[u8] = include_bytes!("../resources/sounds/alert.wav");
 
-/// オーディオシステムを初期化（現在は何もしない）
-pub fn init_audio() -> Result<(), String> {
-    info!("Audio system ready");
-    Ok(())
+/// 組み込み通知音の種類
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundId {
+    /// 従来の通知音
+    #[default]
+    Default,
+    Click,
+    Chime,
+    Alert,
 }
 
-/// 通知音を再生する（非同期、別スレッドで実行）
-pub fn play_notification_sound(volume: f32) {
-    std::thread::spawn(move || {
-        play_notification_sound_sync(volume);
-    });
-}
-
-/// 通知音を再生する（同期）
-/// 各呼び出しで新しい OutputStream を作成する
-fn play_notification_sound_sync(volume: f32) {
-    match OutputStream::try_default() {
-        Ok((_stream, handle)) => {
-            match Sink::try_new(&handle) {
-                Ok(sink) => {
-                    let cursor = Cursor::new(NOTIFICATION_SOUND);
-                    match Decoder::new(cursor) {
-                        Ok(source) => {
-                            sink.set_volume(volume.clamp(0.0, 1.0));
-                            sink.append(source);
-                            sink.sleep_until_end();
-                            info!("Notification sound played successfully");
-                        }
-                        Err(e) => {
-                            error!("Failed to decode notification sound: {}", e);
-                        }
-                    }
+impl SoundId {
+    /// 埋め込まれた音声データを取得
+    fn data(self) -> &'static [u8] {
+        match self {
+            SoundId::Default => NOTIFICATION_SOUND,
+            SoundId::Click => CLICK_SOUND,
+            SoundId::Chime => CHIME_SOUND,
+            SoundId::Alert => ALERT_SOUND,
+        }
+    }
+}
+
+/// 同時に複数の通知音が鳴る場合の再生方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundOverlapMode {
+    /// 重ねて再生する
+    #[default]
+    Overlap,
+    /// 前の音が終わるまで待って順番に再生する
+    Queue,
+    /// 同じ通知音が一定時間内に再度鳴った場合は鳴らさない
+    DropDuplicates,
+    /// 再生中の音量を下げて新しい音を重ねる
+    Duck,
+}
+
+/// ダッキング時に再生中の音へ掛ける音量倍率
+const DUCK_GAIN: f32 = 0.3;
+
+/// 通知音の再生ポリシー
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackPolicy {
+    /// 重なった場合の再生方法
+    pub mode: SoundOverlapMode,
+    /// 同時に鳴らす（順番再生では待たせる）通知音の最大数
+    pub max_concurrent: usize,
+    /// 重複とみなす時間幅（DropDuplicates のみ使用）
+    pub duplicate_window: Duration,
+    /// 再生時間の上限（None の場合は最後まで再生する）
+    pub max_duration: Option<Duration>,
+    /// フェードイン・フェードアウトの長さ（0 の場合はフェードしない）
+    pub fade: Duration,
+}
+
+impl Default for PlaybackPolicy {
+    fn default() -> Self {
+        Self {
+            mode: SoundOverlapMode::Overlap,
+            max_concurrent: 3,
+            duplicate_window: Duration::from_millis(2000),
+            max_duration: None,
+            fade: Duration::ZERO,
+        }
+    }
+}
+
+/// 位置（サンプル数）に掛ける音量
+///
+/// - `fade`: フェードに掛けるサンプル数
+/// - `end`: フェードアウトを終える位置（長さが分からない場合は None でフェードインのみ）
+fn envelope_gain(position: u64, fade: u64, end: Option<u64>) -> f32 {
+    if fade == 0 {
+        return 1.0;
+    }
+    let fade_in = position as f32 / fade as f32;
+    let fade_out = end.map_or(1.0, |end| end.saturating_sub(position) as f32 / fade as f32);
+    fade_in.min(fade_out).clamp(0.0, 1.0)
+}
+
+/// 音を展開する（MP3 など再生時間の分からない音でも、フェードアウトの位置を決められるように）
+///
+/// 組み込みの通知音は短いため、すべて展開してもよい。
+fn buffered<S: Source<Item = f32>>(source: S) -> SamplesBuffer<f32> {
+    let (channels, sample_rate) = (source.channels(), source.sample_rate());
+    SamplesBuffer::new(channels, sample_rate, source.collect::<Vec<_>>())
+}
+
+/// 再生時間の上限とフェードイン・フェードアウトを掛ける Source
+struct Envelope<S> {
+    inner: S,
+    max_duration: Option<Duration>,
+    /// 再生するサンプル数の上限（None の場合は最後まで）
+    limit: Option<u64>,
+    /// フェードに掛けるサンプル数
+    fade: u64,
+    /// フェードアウトを終える位置（打ち切る位置か音の終わり）
+    end: Option<u64>,
+    position: u64,
+}
+
+impl<S: Source<Item = f32>> Envelope<S> {
+    fn new(inner: S, max_duration: Option<Duration>, fade: Duration) -> Self {
+        let samples_per_sec = f64::from(inner.sample_rate()) * f64::from(inner.channels());
+        let samples = |duration: Duration| (duration.as_secs_f64() * samples_per_sec) as u64;
+        let limit = max_duration.map(samples);
+        let end = match (limit, inner.total_duration().map(samples)) {
+            (Some(limit), Some(total)) => Some(limit.min(total)),
+            (limit, total) => limit.or(total),
+        };
+        Self {
+            fade: samples(fade),
+            inner,
+            max_duration,
+            limit,
+            end,
+            position: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Envelope<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.limit.is_some_and(|limit| self.position >= limit) {
+            return None;
+        }
+        let sample = self.inner.next()?;
+        let gain = envelope_gain(self.position, self.fade, self.end);
+        self.position += 1;
+        Some(sample * gain)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Envelope<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        let remaining = self.limit.map(|limit| limit.saturating_sub(self.position) as usize);
+        match (self.inner.current_frame_len(), remaining) {
+            (Some(len), Some(remaining)) => Some(len.min(remaining)),
+            (len, remaining) => len.or(remaining),
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner
+            .total_duration()
+            .map(|total| self.max_duration.map_or(total, |max| total.min(max)))
+    }
+}
+
+/// 再生要求の受け入れ判定結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Admission {
+    /// 新しい Sink で再生する（`duck` が true なら再生中の音量を下げる）
+    Play { duck: bool },
+    /// 順番再生の Sink に追加する
+    Enqueue,
+    /// 再生しない
+    Drop,
+}
+
+/// 再生要求を受け入れるか判定する
+///
+/// - `pending`: 再生中の数（順番再生では待ち行列の長さ）
+/// - `since_same`: 同じ通知音を最後に鳴らしてからの経過時間
+fn admit(policy: &PlaybackPolicy, pending: usize, since_same: Option<Duration>) -> Admission {
+    if pending >= policy.max_concurrent.max(1) {
+        return Admission::Drop;
+    }
+
+    match policy.mode {
+        SoundOverlapMode::Overlap => Admission::Play { duck: false },
+        SoundOverlapMode::Queue => Admission::Enqueue,
+        SoundOverlapMode::DropDuplicates => match since_same {
+            Some(elapsed) if elapsed < policy.duplicate_window => Admission::Drop,
+            _ => Admission::Play { duck: false },
+        },
+        SoundOverlapMode::Duck => Admission::Play { duck: pending > 0 },
+    }
+}
+
+/// オーディオエンジンへのコマンド
+enum AudioCommand {
+    Play {
+        sound: SoundId,
+        volume: f32,
+        policy: PlaybackPolicy,
+    },
+}
+
+/// 通知音再生エンジン
+///
+/// 専用スレッドで OutputStream を保持し続け、コマンドチャネル経由で再生要求を受け取る。
+/// OutputStream は Send ではないため、スレッド外には出さない。
+pub struct AudioEngine {
+    tx: mpsc::Sender<AudioCommand>,
+}
+
+/// アプリ全体で共有するオーディオエンジン
+static ENGINE: OnceLock<AudioEngine> = OnceLock::new();
+
+impl AudioEngine {
+    /// エンジンスレッドを起動する
+    fn start() -> Result<Self, String> {
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("audio-engine".to_string())
+            .spawn(move || run_engine(rx))
+            .map_err(|e| format!("Failed to spawn audio thread: {}", e))?;
+
+        Ok(Self { tx })
+    }
+
+    /// 再生要求を送信する（ブロックしない）
+    fn play(&self, sound: SoundId, volume: f32, policy: PlaybackPolicy) {
+        if self.tx.send(AudioCommand::Play { sound, volume, policy }).is_err() {
+            error!("Audio engine is not running");
+        }
+    }
+}
+
+/// エンジンスレッド内の再生状態
+struct EngineState {
+    /// 出力ストリーム（最初の再生時に開き、失敗時は次回再試行する）
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    /// 順番再生用の Sink
+    queue_sink: Option<Sink>,
+    /// 重ねて再生中の Sink
+    active_sinks: Vec<Sink>,
+    /// 通知音ごとの最終再生時刻
+    last_played: HashMap<SoundId, Instant>,
+}
+
+impl EngineState {
+    fn new() -> Self {
+        Self {
+            output: None,
+            queue_sink: None,
+            active_sinks: Vec::new(),
+            last_played: HashMap::new(),
+        }
+    }
+
+    /// 出力ストリームを取得（未作成なら作成する）
+    fn handle(&mut self) -> Option<&OutputStreamHandle> {
+        if self.output.is_none() {
+            match OutputStream::try_default() {
+                Ok(output) => {
+                    info!("Audio output stream opened");
+                    self.output = Some(output);
                 }
                 Err(e) => {
-                    error!("Failed to create audio sink: {}", e);
+                    error!("Failed to create audio output stream: {}", e);
+                    return None;
+                }
+            }
+        }
+        self.output.as_ref().map(|(_, handle)| handle)
+    }
+
+    /// 新しい Sink を作成する（失敗時は出力ストリームを開き直して1回だけ再試行）
+    fn new_sink(&mut self) -> Option<Sink> {
+        for _ in 0..2 {
+            let handle = self.handle()?;
+            match Sink::try_new(handle) {
+                Ok(sink) => return Some(sink),
+                Err(e) => {
+                    warn!("Failed to create audio sink, reopening output: {}", e);
+                    self.queue_sink = None;
+                    self.active_sinks.clear();
+                    self.output = None;
+                }
+            }
+        }
+        error!("Failed to create audio sink");
+        None
+    }
+
+    /// 再生が終わった Sink を破棄する
+    fn prune(&mut self) {
+        self.active_sinks.retain(|sink| !sink.empty());
+        if self.queue_sink.as_ref().is_some_and(|sink| sink.empty()) {
+            self.queue_sink = None;
+        }
+    }
+
+    fn play(&mut self, sound: SoundId, volume: f32, policy: PlaybackPolicy) {
+        self.prune();
+
+        let pending = match policy.mode {
+            SoundOverlapMode::Queue => self.queue_sink.as_ref().map_or(0, |sink| sink.len()),
+            _ => self.active_sinks.len(),
+        };
+        let since_same = self.last_played.get(&sound).map(|at| at.elapsed());
+
+        let admission = admit(&policy, pending, since_same);
+        if admission == Admission::Drop {
+            info!("Notification sound skipped: {:?} ({:?}, pending={})", sound, policy.mode, pending);
+            return;
+        }
+
+        let source = match Decoder::new(Cursor::new(sound.data())) {
+            Ok(source) => Envelope::new(buffered(source.convert_samples::<f32>()), policy.max_duration, policy.fade)
+                .amplify(volume.clamp(0.0, 1.0)),
+            Err(e) => {
+                error!("Failed to decode notification sound: {}", e);
+                return;
+            }
+        };
+
+        match admission {
+            Admission::Play { duck } => {
+                if duck {
+                    for sink in &self.active_sinks {
+                        sink.set_volume(DUCK_GAIN);
+                    }
+                }
+                if let Some(sink) = self.new_sink() {
+                    sink.append(source);
+                    self.active_sinks.push(sink);
                 }
             }
+            Admission::Enqueue => {
+                if self.queue_sink.is_none() {
+                    self.queue_sink = self.new_sink();
+                }
+                if let Some(sink) = &self.queue_sink {
+                    sink.append(source);
+                }
+            }
+            Admission::Drop => {}
         }
-        Err(e) => {
-            error!("Failed to create audio output stream: {}", e);
+
+        self.last_played.insert(sound, Instant::now());
+        info!("Notification sound queued: {:?}", sound);
+    }
+}
+
+/// エンジンスレッドのメインループ
+fn run_engine(rx: mpsc::Receiver<AudioCommand>) {
+    let mut state = EngineState::new();
+
+    while let Ok(command) = rx.recv() {
+        match command {
+            AudioCommand::Play { sound, volume, policy } => state.play(sound, volume, policy),
         }
     }
+
+    info!("Audio engine stopped");
+}
+
+/// オーディオシステムを初期化（エンジンスレッドを起動）
+pub fn init_audio() -> Result<(), String> {
+    if ENGINE.get().is_none() {
+        let engine = AudioEngine::start()?;
+        let _ = ENGINE.set(engine);
+    }
+    info!("Audio system ready");
+    Ok(())
+}
+
+/// 通知音を再生する（非同期、エンジンスレッドで再生）
+pub fn play_notification_sound(sound: SoundId, volume: f32, policy: PlaybackPolicy) {
+    match ENGINE.get() {
+        Some(engine) => engine.play(sound, volume, policy),
+        None => error!("Audio system is not initialized"),
+    }
+}
+
+/// 試聴の再生ポリシー（設定画面で入力中の再生時間の上限とフェードを使う）
+fn preview_policy(max_duration_ms: Option<u64>, fade_ms: Option<u64>) -> PlaybackPolicy {
+    PlaybackPolicy {
+        max_duration: max_duration_ms.filter(|ms| *ms > 0).map(Duration::from_millis),
+        fade: Duration::from_millis(fade_ms.unwrap_or(0)),
+        ..PlaybackPolicy::default()
+    }
 }
 
 /// Tauriコマンド: テスト再生
 #[tauri::command]
-pub fn play_test_sound(volume: f32) {
-    play_notification_sound(volume);
+pub fn play_test_sound(volume: f32, max_duration_ms: Option<u64>, fade_ms: Option<u64>) {
+    play_notification_sound(SoundId::Default, volume, preview_policy(max_duration_ms, fade_ms));
+}
+
+/// Tauriコマンド: 指定した通知音を試聴
+#[tauri::command]
+pub fn preview_sound(sound: SoundId, volume: f32, max_duration_ms: Option<u64>, fade_ms: Option<u64>) {
+    play_notification_sound(sound, volume, preview_policy(max_duration_ms, fade_ms));
 }
 
 #[cfg(test)]
@@ -83,4 +449,150 @@ mod tests {
 
         assert!(has_id3 || has_mp3_frame, "File should be a valid MP3");
     }
+
+    #[test]
+    fn test_builtin_wav_sounds_are_valid() {
+        // WAVファイルのヘッダー (RIFF....WAVE) を確認
+        for sound in [SoundId::Click, SoundId::Chime, SoundId::Alert] {
+            let data = sound.data();
+            assert!(data.len() > 44, "{:?} should contain audio data", sound);
+            assert_eq!(&data[0..4], b"RIFF", "{:?} should be a RIFF file", sound);
+            assert_eq!(&data[8..12], b"WAVE", "{:?} should be a WAVE file", sound);
+        }
+    }
+
+    #[test]
+    fn test_sound_id_serialization() {
+        assert_eq!(serde_json::to_string(&SoundId::Chime).unwrap(), "\"chime\"");
+        let parsed: SoundId = serde_json::from_str("\"alert\"").unwrap();
+        assert_eq!(parsed, SoundId::Alert);
+    }
+
+    #[test]
+    fn test_overlap_mode_serialization() {
+        assert_eq!(SoundOverlapMode::default(), SoundOverlapMode::Overlap);
+        assert_eq!(serde_json::to_string(&SoundOverlapMode::Queue).unwrap(), "\"queue\"");
+        let parsed: SoundOverlapMode = serde_json::from_str("\"drop_duplicates\"").unwrap();
+        assert_eq!(parsed, SoundOverlapMode::DropDuplicates);
+    }
+
+    fn policy(mode: SoundOverlapMode) -> PlaybackPolicy {
+        PlaybackPolicy {
+            mode,
+            ..PlaybackPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_admit_respects_max_concurrent() {
+        let overlap = policy(SoundOverlapMode::Overlap);
+        assert_eq!(admit(&overlap, 2, None), Admission::Play { duck: false });
+        assert_eq!(admit(&overlap, 3, None), Admission::Drop);
+
+        let queue = policy(SoundOverlapMode::Queue);
+        assert_eq!(admit(&queue, 0, None), Admission::Enqueue);
+        assert_eq!(admit(&queue, 3, None), Admission::Drop);
+    }
+
+    #[test]
+    fn test_admit_drops_duplicates_within_window() {
+        let dedup = policy(SoundOverlapMode::DropDuplicates);
+        assert_eq!(admit(&dedup, 0, Some(Duration::from_millis(500))), Admission::Drop);
+        assert_eq!(
+            admit(&dedup, 0, Some(Duration::from_millis(2500))),
+            Admission::Play { duck: false }
+        );
+        assert_eq!(admit(&dedup, 0, None), Admission::Play { duck: false });
+    }
+
+    #[test]
+    fn test_admit_ducks_only_when_overlapping() {
+        let duck = policy(SoundOverlapMode::Duck);
+        assert_eq!(admit(&duck, 0, None), Admission::Play { duck: false });
+        assert_eq!(admit(&duck, 1, None), Admission::Play { duck: true });
+    }
+
+    #[test]
+    fn test_envelope_gain() {
+        assert_eq!(envelope_gain(0, 0, Some(10)), 1.0);
+        assert_eq!(envelope_gain(0, 100, None), 0.0);
+        assert_eq!(envelope_gain(50, 100, None), 0.5);
+        assert_eq!(envelope_gain(500, 100, Some(1000)), 1.0);
+        assert_eq!(envelope_gain(950, 100, Some(1000)), 0.5);
+        assert_eq!(envelope_gain(1200, 100, Some(1000)), 0.0);
+    }
+
+    #[test]
+    fn test_envelope_caps_duration_and_fades() {
+        // 1秒分（1000Hz・モノラル）の一定の音を 0.5 秒で打ち切り、0.1 秒のフェードを掛ける
+        let source = rodio::buffer::SamplesBuffer::new(1, 1000, vec![1.0f32; 1000]);
+        let envelope = Envelope::new(source, Some(Duration::from_millis(500)), Duration::from_millis(100));
+        assert_eq!(envelope.total_duration(), Some(Duration::from_millis(500)));
+
+        let samples: Vec<f32> = envelope.collect();
+        assert_eq!(samples.len(), 500);
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[250], 1.0);
+        assert!(samples[499] < 0.05);
+    }
+
+    /// 再生時間を返さない Source（ヘッダーのない MP3 など）
+    struct UnknownLength(rodio::buffer::SamplesBuffer<f32>);
+
+    impl Iterator for UnknownLength {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            self.0.next()
+        }
+    }
+
+    impl Source for UnknownLength {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            self.0.channels()
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.0.sample_rate()
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_envelope_fades_out_sound_without_duration() {
+        // 再生時間の分からない音も、展開すれば終わりにフェードアウトを掛けられる
+        let source = UnknownLength(rodio::buffer::SamplesBuffer::new(1, 1000, vec![1.0f32; 1000]));
+        let envelope = Envelope::new(buffered(source), None, Duration::from_millis(100));
+        assert_eq!(envelope.total_duration(), Some(Duration::from_secs(1)));
+
+        let samples: Vec<f32> = envelope.collect();
+        assert_eq!(samples.len(), 1000);
+        assert_eq!(samples[500], 1.0);
+        assert!(samples[999] < 0.05);
+    }
+
+    #[test]
+    fn test_preview_policy() {
+        let policy = preview_policy(Some(0), None);
+        assert_eq!(policy.max_duration, None);
+        assert_eq!(policy.fade, Duration::ZERO);
+        let policy = preview_policy(Some(3000), Some(50));
+        assert_eq!(policy.max_duration, Some(Duration::from_secs(3)));
+        assert_eq!(policy.fade, Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_admit_treats_zero_limit_as_one() {
+        let mut overlap = policy(SoundOverlapMode::Overlap);
+        overlap.max_concurrent = 0;
+        assert_eq!(admit(&overlap, 0, None), Admission::Play { duck: false });
+        assert_eq!(admit(&overlap, 1, None), Admission::Drop);
+    }
 }
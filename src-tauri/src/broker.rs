@@ -2,11 +2,36 @@
 //!
 //! This module provides an embedded MQTT broker for local communication
 //! between Claude Code instances and the notification system.
+//! Router meters (connections, subscriptions, publishes) are collected
+//! in a background thread and exposed for the diagnostics tab.
+//! When access control is enabled, connecting clients are checked
+//! against the client-id / username allowlist (see the `acl` module)
+//! and rejected attempts are recorded as security events.
+//! An optional localhost-only listener accepts clients without the allowlist
+//! (see the `broker_listeners` module).
+//! Listener ports are checked before the broker thread starts, so a port that is
+//! already in use is reported to the caller (or replaced by the next free port).
 
-use rumqttd::{Broker, Config};
+use crate::acl::BrokerAclSettings;
+use crate::broker_listeners::{self, BrokerListenerSettings, LOCAL_LISTENER_NAME};
+use crate::security_events::SecurityEventLog;
+use rumqttd::{Broker, Config, Meter};
+use serde::Serialize;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, RwLock};
 use std::thread;
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
+
+/// Port used by the embedded configuration
+pub const DEFAULT_PORT: u16 = 1883;
+
+/// Embedded configuration (a single LAN listener)
+const DEFAULT_CONFIG: &str = include_str!("../config/rumqttd.toml");
+
+/// Ports tried after the configured one when it is in use and fallback is enabled
+const FALLBACK_PORT_ATTEMPTS: u16 = 10;
 
 #[derive(Error, Debug)]
 pub enum BrokerError {
@@ -15,42 +40,292 @@ pub enum BrokerError {
 
     #[error("Failed to start broker: {0}")]
     StartError(String),
+
+    #[error("Port {0} is already in use")]
+    PortInUse(u16),
+}
+
+/// Snapshot of broker metrics reported by the rumqttd router
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BrokerMetrics {
+    /// Currently connected clients (including this app's own subscriber)
+    pub connections: usize,
+    /// Active subscriptions
+    pub subscriptions: usize,
+    /// Total publishes routed since the broker started
+    pub total_publishes: usize,
+    /// Publishes the router failed to deliver
+    pub failed_publishes: usize,
+    /// Publish rate computed from the last two samples
+    pub messages_per_sec: f64,
+    /// Seconds since the last meter sample (None if no sample yet)
+    pub last_sample_secs: Option<u64>,
+}
+
+/// Accumulates router meter samples and derives the publish rate
+#[derive(Debug, Default)]
+struct MetricsSampler {
+    metrics: BrokerMetrics,
+    last_sample: Option<Instant>,
+}
+
+impl MetricsSampler {
+    /// Record a new router meter sample
+    fn record(
+        &mut self,
+        connections: usize,
+        subscriptions: usize,
+        total_publishes: usize,
+        failed_publishes: usize,
+        now: Instant,
+    ) {
+        let rate = match self.last_sample {
+            Some(previous) => {
+                let elapsed = now.duration_since(previous).as_secs_f64();
+                let delta = total_publishes.saturating_sub(self.metrics.total_publishes);
+                if elapsed > 0.0 {
+                    delta as f64 / elapsed
+                } else {
+                    self.metrics.messages_per_sec
+                }
+            }
+            None => 0.0,
+        };
+
+        self.metrics = BrokerMetrics {
+            connections,
+            subscriptions,
+            total_publishes,
+            failed_publishes,
+            messages_per_sec: rate,
+            last_sample_secs: None,
+        };
+        self.last_sample = Some(now);
+    }
+
+    /// Current metrics with the sample age filled in
+    fn snapshot(&self, now: Instant) -> BrokerMetrics {
+        BrokerMetrics {
+            last_sample_secs: self
+                .last_sample
+                .map(|sample| now.duration_since(sample).as_secs()),
+            ..self.metrics.clone()
+        }
+    }
 }
 
 /// MQTT Broker wrapper for embedded usage
 pub struct MqttBroker {
     config: Config,
     handle: Option<thread::JoinHandle<()>>,
+    metrics: Arc<RwLock<MetricsSampler>>,
+    acl: Arc<RwLock<BrokerAclSettings>>,
+    security_events: Arc<SecurityEventLog>,
+    local_port: Option<u16>,
+    port_fallback: bool,
+    started_at: Option<Instant>,
 }
 
 impl MqttBroker {
     /// Create a new MQTT broker with default embedded configuration
     pub fn with_default_config() -> Result<Self, BrokerError> {
-        let toml_config = include_str!("../config/rumqttd.toml");
-
-        let config: Config = toml::from_str(toml_config)
-            .map_err(|e| BrokerError::ConfigLoad(e.to_string()))?;
+        let config: Config =
+            toml::from_str(DEFAULT_CONFIG).map_err(|e| BrokerError::ConfigLoad(e.to_string()))?;
 
         Ok(Self {
             config,
             handle: None,
+            metrics: Arc::new(RwLock::new(MetricsSampler::default())),
+            acl: Arc::new(RwLock::new(BrokerAclSettings::default())),
+            security_events: Arc::new(SecurityEventLog::default()),
+            local_port: None,
+            port_fallback: false,
+            started_at: None,
         })
     }
 
+    /// Change the listen port of the LAN listener (takes effect on the next `start()`)
+    pub fn set_port(&mut self, port: u16) {
+        if let Some(servers) = self.config.v4.as_mut() {
+            for server in servers.values_mut() {
+                if server.name == LOCAL_LISTENER_NAME {
+                    continue;
+                }
+                server.listen.set_port(port);
+            }
+        }
+    }
+
+    /// Port the LAN listener listens on
+    pub fn port(&self) -> u16 {
+        self.config
+            .v4
+            .as_ref()
+            .and_then(|servers| servers.values().find(|s| s.name != LOCAL_LISTENER_NAME))
+            .map(|server| server.listen.port())
+            .unwrap_or(DEFAULT_PORT)
+    }
+
+    /// Port of the localhost-only listener (None if it is not configured)
+    pub fn local_port(&self) -> Option<u16> {
+        self.local_port
+    }
+
+    /// Rebuild the listeners from the settings (takes effect on the next `start()`)
+    pub fn set_listeners(&mut self, listeners: &BrokerListenerSettings) -> Result<(), BrokerError> {
+        let port = self.port();
+        let toml_config = broker_listeners::config_toml(DEFAULT_CONFIG, listeners, port);
+        self.config =
+            toml::from_str(&toml_config).map_err(|e| BrokerError::ConfigLoad(e.to_string()))?;
+        self.set_port(port);
+        self.local_port = listeners.active_local_port(port);
+        if listeners.local_enabled && self.local_port.is_none() {
+            warn!(
+                "Localhost listener not opened: port {} is used by the LAN listener",
+                listeners.local_port
+            );
+        }
+        Ok(())
+    }
+
+    /// Move the LAN listener to the next free port when its port is in use
+    pub fn set_port_fallback(&mut self, enabled: bool) {
+        self.port_fallback = enabled;
+    }
+
+    /// Check that every listener can bind before the broker thread starts
+    ///
+    /// The LAN listener moves to the next free port when fallback is enabled.
+    /// The localhost-only listener is dropped when its port is in use.
+    fn ensure_ports_available(&mut self) -> Result<(), BrokerError> {
+        let can_bind = |addr: SocketAddr| TcpListener::bind(addr).is_ok();
+        let Some(servers) = self.config.v4.as_mut() else {
+            return Ok(());
+        };
+
+        for server in servers.values_mut() {
+            if server.name == LOCAL_LISTENER_NAME || can_bind(server.listen) {
+                continue;
+            }
+            let requested = server.listen.port();
+            let attempts = if self.port_fallback { FALLBACK_PORT_ATTEMPTS } else { 0 };
+            let free_port = (1..=attempts)
+                .filter_map(|offset| requested.checked_add(offset))
+                .filter(|&port| Some(port) != self.local_port)
+                .find(|&port| can_bind(SocketAddr::new(server.listen.ip(), port)))
+                .ok_or(BrokerError::PortInUse(requested))?;
+            warn!("Port {} is already in use, falling back to {}", requested, free_port);
+            server.listen.set_port(free_port);
+        }
+
+        let local_in_use = servers
+            .values()
+            .any(|server| server.name == LOCAL_LISTENER_NAME && !can_bind(server.listen));
+        if local_in_use {
+            warn!("Localhost listener not opened: port {:?} is already in use", self.local_port);
+            servers.retain(|_, server| server.name != LOCAL_LISTENER_NAME);
+            self.local_port = None;
+        }
+        Ok(())
+    }
+
+    /// Update the access control settings
+    ///
+    /// The allowlist is applied to new connections immediately, but enabling
+    /// or disabling access control only takes effect on the next `start()`
+    /// because the auth handler is installed when the broker starts.
+    pub fn set_acl(&self, acl: BrokerAclSettings) {
+        if let Ok(mut current) = self.acl.write() {
+            *current = acl;
+        }
+    }
+
+    /// Log that receives rejected connection attempts (shared with the diagnostics tab)
+    pub fn security_events(&self) -> Arc<SecurityEventLog> {
+        self.security_events.clone()
+    }
+
+    /// Install the allowlist as the connection auth handler of the LAN listener
+    fn install_auth_handler(&mut self) {
+        let Some(servers) = self.config.v4.as_mut() else {
+            return;
+        };
+
+        for server in servers.values_mut() {
+            // The localhost-only listener accepts local hooks without the allowlist
+            if server.name == LOCAL_LISTENER_NAME {
+                continue;
+            }
+            let acl = self.acl.clone();
+            let security_events = self.security_events.clone();
+            server
+                .connections
+                .set_auth_handler(move |client_id: String, user: String, _pass: String| {
+                    let allowed = acl
+                        .read()
+                        .map(|acl| acl.is_client_allowed(&client_id, &user))
+                        .unwrap_or(false);
+                    if !allowed {
+                        warn!("Rejected MQTT client not in allowlist: {} (user {:?})", client_id, user);
+                        security_events.client_rejected(&client_id, &user);
+                    }
+                    async move { allowed }
+                });
+        }
+    }
+
     /// Start the broker in a background thread
     pub fn start(&mut self) -> Result<(), BrokerError> {
-        info!("Starting MQTT broker on port 1883...");
+        self.ensure_ports_available()?;
+        info!("Starting MQTT broker on port {}...", self.port());
+        if let Some(local_port) = self.local_port {
+            info!("Localhost-only listener on port {}", local_port);
+        }
+
+        let acl_enabled = self.acl.read().map(|acl| acl.enabled).unwrap_or(false);
+        if acl_enabled {
+            info!("Broker access control enabled");
+            self.install_auth_handler();
+        }
 
-        let config = self.config.clone();
+        let mut broker = Broker::new(self.config.clone());
+
+        // Meters link must be created before the router starts
+        match broker.meters() {
+            Ok(meters) => {
+                let metrics = self.metrics.clone();
+                thread::spawn(move || {
+                    while let Ok(samples) = meters.recv() {
+                        // Other meter kinds are not shown in diagnostics
+                        for meter in samples {
+                            if let Meter::Router(_, router) = meter {
+                                debug!("Router meter: {:?}", router);
+                                if let Ok(mut sampler) = metrics.write() {
+                                    sampler.record(
+                                        router.total_connections,
+                                        router.total_subscriptions,
+                                        router.total_publishes,
+                                        router.failed_publishes,
+                                        Instant::now(),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    info!("Broker meters link closed");
+                });
+            }
+            Err(e) => warn!("Failed to create broker meters link: {:?}", e),
+        }
 
         let handle = thread::spawn(move || {
-            let mut broker = Broker::new(config);
             if let Err(e) = broker.start() {
                 error!("Broker error: {:?}", e);
             }
         });
 
         self.handle = Some(handle);
+        self.started_at = Some(Instant::now());
         info!("MQTT broker started successfully");
 
         Ok(())
@@ -63,6 +338,21 @@ impl MqttBroker {
             .map(|h| !h.is_finished())
             .unwrap_or(false)
     }
+
+    /// Time since the broker started (None if it is not running)
+    pub fn uptime(&self) -> Option<Duration> {
+        self.started_at
+            .filter(|_| self.is_running())
+            .map(|started_at| started_at.elapsed())
+    }
+
+    /// Latest broker metrics
+    pub fn metrics(&self) -> BrokerMetrics {
+        self.metrics
+            .read()
+            .map(|sampler| sampler.snapshot(Instant::now()))
+            .unwrap_or_default()
+    }
 }
 
 impl Drop for MqttBroker {
@@ -80,4 +370,72 @@ mod tests {
         let result = MqttBroker::with_default_config();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_port_in_use_falls_back_to_next_port() {
+        let taken = TcpListener::bind("0.0.0.0:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        let mut broker = MqttBroker::with_default_config().unwrap();
+        broker.set_port(port);
+        assert!(matches!(broker.ensure_ports_available(), Err(BrokerError::PortInUse(p)) if p == port));
+
+        broker.set_port_fallback(true);
+        broker.ensure_ports_available().unwrap();
+        assert_ne!(broker.port(), port);
+        assert!(broker.port() > port && broker.port() <= port + FALLBACK_PORT_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_metrics_sampler_computes_rate() {
+        let mut sampler = MetricsSampler::default();
+        let t0 = Instant::now();
+
+        sampler.record(2, 1, 10, 0, t0);
+        assert_eq!(sampler.snapshot(t0).messages_per_sec, 0.0);
+
+        sampler.record(3, 2, 30, 1, t0 + std::time::Duration::from_secs(4));
+        let metrics = sampler.snapshot(t0 + std::time::Duration::from_secs(5));
+        assert_eq!(metrics.connections, 3);
+        assert_eq!(metrics.subscriptions, 2);
+        assert_eq!(metrics.failed_publishes, 1);
+        assert!((metrics.messages_per_sec - 5.0).abs() < f64::EPSILON);
+        assert_eq!(metrics.last_sample_secs, Some(1));
+    }
+
+    #[test]
+    fn test_set_port() {
+        let mut broker = MqttBroker::with_default_config().unwrap();
+        assert_eq!(broker.port(), DEFAULT_PORT);
+        broker.set_port(1884);
+        assert_eq!(broker.port(), 1884);
+    }
+
+    #[test]
+    fn test_set_listeners_keeps_lan_port() {
+        let mut broker = MqttBroker::with_default_config().unwrap();
+        broker.set_port(1885);
+        broker
+            .set_listeners(&BrokerListenerSettings {
+                local_enabled: true,
+                local_port: 1884,
+            })
+            .unwrap();
+        assert_eq!(broker.port(), 1885);
+        assert_eq!(broker.local_port(), Some(1884));
+        assert_eq!(broker.config.v4.as_ref().map(|v4| v4.len()), Some(2));
+
+        broker.set_port(1886);
+        assert_eq!(broker.port(), 1886);
+        assert_eq!(broker.local_port(), Some(1884));
+    }
+
+    #[test]
+    fn test_metrics_before_first_sample() {
+        let broker = MqttBroker::with_default_config().unwrap();
+        let metrics = broker.metrics();
+        assert_eq!(metrics.connections, 0);
+        assert_eq!(metrics.last_sample_secs, None);
+        assert_eq!(broker.uptime(), None);
+    }
 }
@@ -0,0 +1,90 @@
+//! ブローカーのリスナー設定モジュール
+//!
+//! LAN 向けのリスナー（`config/rumqttd.toml` の `[v4.1]`）に加えて、localhost 専用のリスナーを開けるようにする。
+//! アクセス制御（`acl` モジュール）は LAN 向けのリスナーだけに適用し、localhost 専用のリスナーは認証なしで受け付けるため、
+//! このPCのフックは設定なしで送信でき、リモートのマシンからの接続だけを許可リストで制限できる。
+//! rumqttd の設定は起動時に TOML を組み立てて読み込む。
+
+use serde::{Deserialize, Serialize};
+
+/// localhost 専用リスナーのサーバー名（認証ハンドラを設定しない）
+pub const LOCAL_LISTENER_NAME: &str = "v4-local";
+
+/// localhost 専用リスナーの既定のポート
+pub const DEFAULT_LOCAL_PORT: u16 = 1884;
+
+/// ブローカーのリスナー設定（変更はアプリの再起動後に反映）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrokerListenerSettings {
+    /// localhost 専用のリスナーを開くか
+    pub local_enabled: bool,
+    /// localhost 専用リスナーのポート
+    pub local_port: u16,
+}
+
+impl Default for BrokerListenerSettings {
+    fn default() -> Self {
+        Self {
+            local_enabled: false,
+            local_port: DEFAULT_LOCAL_PORT,
+        }
+    }
+}
+
+impl BrokerListenerSettings {
+    /// 開く localhost 専用リスナーのポート（無効な場合や LAN 向けのポートと重なる場合は None）
+    pub fn active_local_port(&self, lan_port: u16) -> Option<u16> {
+        Some(self.local_port).filter(|port| self.local_enabled && *port != 0 && *port != lan_port)
+    }
+}
+
+/// 組み込みの設定に localhost 専用リスナーを追加した rumqttd の設定
+pub fn config_toml(base: &str, listeners: &BrokerListenerSettings, lan_port: u16) -> String {
+    let Some(port) = listeners.active_local_port(lan_port) else {
+        return base.to_string();
+    };
+    format!(
+        r#"{base}
+[v4.2]
+name = "{LOCAL_LISTENER_NAME}"
+listen = "127.0.0.1:{port}"
+next_connection_delay_ms = 1
+    [v4.2.connections]
+    connection_timeout_ms = 60000
+    max_payload_size = 20480
+    max_inflight_count = 100
+    dynamic_filters = true
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "[v4.1]\nname = \"v4-1\"\nlisten = \"0.0.0.0:1883\"\n";
+
+    #[test]
+    fn test_local_listener_is_added_when_enabled() {
+        let mut listeners = BrokerListenerSettings::default();
+        assert_eq!(config_toml(BASE, &listeners, 1883), BASE);
+
+        listeners.local_enabled = true;
+        let config = config_toml(BASE, &listeners, 1883);
+        assert!(config.starts_with(BASE));
+        assert!(config.contains(r#"name = "v4-local""#));
+        assert!(config.contains(r#"listen = "127.0.0.1:1884""#));
+    }
+
+    #[test]
+    fn test_local_port_must_differ_from_lan_port() {
+        let listeners = BrokerListenerSettings {
+            local_enabled: true,
+            local_port: 1883,
+        };
+        assert_eq!(listeners.active_local_port(1883), None);
+        assert_eq!(listeners.active_local_port(1885), Some(1883));
+        assert_eq!(config_toml(BASE, &listeners, 1883), BASE);
+    }
+}
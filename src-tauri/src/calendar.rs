@@ -0,0 +1,499 @@
+//! カレンダー連携モジュール
+//!
+//! ICS 形式のカレンダー（Outlook・Google カレンダーの「公開アドレス」など）を定期的に取得し、
+//! 予定の最中は通知を止める（通知履歴には記録する）。
+//! 予定中であることはトレイのツールチップとステータスメニューに表示する。
+//!
+//! 対応していない内容:
+//! - 終日の予定・「空き時間」として登録した予定・キャンセルされた予定（通知は止めない）
+//! - TZID によるタイムゾーンの指定（この PC のタイムゾーンとして扱う）
+//! - 繰り返しの予定は FREQ=DAILY / WEEKLY（INTERVAL・COUNT・UNTIL・BYDAY・EXDATE）のみ展開する
+
+use crate::NotificationManager;
+use chrono::{Datelike, DateTime, Days, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// カレンダーを取得し直す間隔
+const SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// 設定の変更を確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// カレンダーを取得する際のタイムアウト
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 取得するカレンダーの最大サイズ
+const MAX_CALENDAR_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 繰り返しの予定を展開する最大日数（約10年）
+const MAX_RECURRENCE_DAYS: u32 = 3660;
+
+/// カレンダー連携の設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarSettings {
+    /// 予定の最中は通知を止める
+    pub enabled: bool,
+    /// ICS 形式のカレンダーの URL（`webcal://` も可）
+    pub ics_url: String,
+}
+
+impl CalendarSettings {
+    /// 有効な場合に取得する URL
+    pub fn active_url(&self) -> Option<String> {
+        let url = self.ics_url.trim();
+        if !self.enabled || url.is_empty() {
+            return None;
+        }
+        Some(match url.strip_prefix("webcal://") {
+            Some(rest) => format!("https://{}", rest),
+            None => url.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CalendarError {
+    #[error("failed to download calendar: {0}")]
+    Download(String),
+    #[error("calendar is larger than {MAX_CALENDAR_BYTES} bytes")]
+    TooLarge,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+}
+
+/// 繰り返しの規則（RRULE）
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<DateTime<Utc>>,
+    /// 週単位の繰り返しの曜日（空の場合は最初の予定と同じ曜日）
+    by_day: Vec<Weekday>,
+}
+
+impl Recurrence {
+    fn parse(value: &str) -> Option<Self> {
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+        };
+        let mut frequency = None;
+        for part in value.split(';') {
+            let Some((key, value)) = part.split_once('=') else {
+                continue;
+            };
+            match key {
+                "FREQ" => {
+                    frequency = match value {
+                        "DAILY" => Some(Frequency::Daily),
+                        "WEEKLY" => Some(Frequency::Weekly),
+                        _ => None,
+                    }
+                }
+                "INTERVAL" => recurrence.interval = value.parse().unwrap_or(1).max(1),
+                "COUNT" => recurrence.count = value.parse().ok(),
+                "UNTIL" => recurrence.until = parse_until(value),
+                "BYDAY" => recurrence.by_day = value.split(',').filter_map(parse_weekday).collect(),
+                _ => {}
+            }
+        }
+        recurrence.frequency = frequency?;
+        Some(recurrence)
+    }
+
+    /// `limit` 以前に始まる各回の開始時刻
+    fn starts(&self, first: DateTime<Utc>, limit: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let first_local = first.with_timezone(&Local).naive_local();
+        let first_date = first_local.date();
+        let time = first_local.time();
+        // 週単位の繰り返しは、最初の予定を含む週（月曜始まり）から数える
+        let week_start = first_date - Days::new(first_date.weekday().num_days_from_monday().into());
+        let interval = i64::from(self.interval);
+
+        let mut starts = Vec::new();
+        let mut date = first_date;
+        for _ in 0..MAX_RECURRENCE_DAYS {
+            let matches = match self.frequency {
+                Frequency::Daily => (date - first_date).num_days() % interval == 0,
+                Frequency::Weekly => {
+                    let in_week = ((date - week_start).num_days() / 7) % interval == 0;
+                    let on_day = if self.by_day.is_empty() {
+                        date.weekday() == first_date.weekday()
+                    } else {
+                        self.by_day.contains(&date.weekday())
+                    };
+                    in_week && on_day
+                }
+            };
+            if matches {
+                if let Some(start) = local_to_utc(date.and_time(time)) {
+                    if start > limit || self.until.is_some_and(|until| start > until) {
+                        break;
+                    }
+                    starts.push(start);
+                    if self.count.is_some_and(|count| starts.len() >= count as usize) {
+                        break;
+                    }
+                }
+            }
+            let Some(next) = date.succ_opt() else {
+                break;
+            };
+            date = next;
+        }
+        starts
+    }
+}
+
+/// カレンダーの予定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvent {
+    pub summary: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    recurrence: Option<Recurrence>,
+    /// 繰り返しから除外された回の開始時刻
+    exdates: Vec<DateTime<Utc>>,
+}
+
+impl CalendarEvent {
+    /// 指定した時刻に予定の最中か
+    fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        let Some(recurrence) = &self.recurrence else {
+            return self.start <= now && now < self.end;
+        };
+        let duration = self.end - self.start;
+        recurrence
+            .starts(self.start, now)
+            .into_iter()
+            .rev()
+            .filter(|start| !self.exdates.contains(start))
+            .any(|start| now < start + duration)
+    }
+}
+
+/// `20261017T090000Z`（UTC）または `20261017T090000`（この PC のタイムゾーン）を解釈する
+///
+/// 終日の予定（日付のみ）は None を返す。
+fn parse_date_time(params: &str, value: &str) -> Option<DateTime<Utc>> {
+    if params.split(';').any(|param| param == "VALUE=DATE") {
+        return None;
+    }
+    match value.strip_suffix('Z') {
+        Some(value) => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|naive| naive.and_utc()),
+        None => local_to_utc(NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?),
+    }
+}
+
+/// RRULE の UNTIL（日付のみの場合はその日の終わりまで）
+fn parse_until(value: &str) -> Option<DateTime<Utc>> {
+    parse_date_time("", value).or_else(|| {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        local_to_utc(date.and_hms_opt(23, 59, 59)?)
+    })
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn local_to_utc(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+}
+
+/// 折り返された行を連結する（RFC 5545 3.1）
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// 予定の値のエスケープ（`\,` など）を戻す
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+/// ICS 形式のカレンダーから、通知を止める予定を取り出す
+pub fn parse_ics(text: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<(String, String, String)>> = None;
+
+    for line in unfold(text) {
+        match line.trim_end() {
+            "BEGIN:VEVENT" => current = Some(Vec::new()),
+            "END:VEVENT" => {
+                if let Some(event) = current.take().and_then(|properties| build_event(&properties)) {
+                    events.push(event);
+                }
+            }
+            line => {
+                let Some(properties) = current.as_mut() else {
+                    continue;
+                };
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+                let (name, params) = key.split_once(';').unwrap_or((key, ""));
+                properties.push((name.to_ascii_uppercase(), params.to_string(), value.to_string()));
+            }
+        }
+    }
+    events
+}
+
+fn build_event(properties: &[(String, String, String)]) -> Option<CalendarEvent> {
+    let mut summary = String::new();
+    let mut start = None;
+    let mut end = None;
+    let mut recurrence = None;
+    let mut exdates = Vec::new();
+
+    for (name, params, value) in properties {
+        match name.as_str() {
+            "SUMMARY" => summary = unescape(value),
+            "DTSTART" => start = Some(parse_date_time(params, value)?),
+            "DTEND" => end = parse_date_time(params, value),
+            "RRULE" => recurrence = Recurrence::parse(value),
+            "EXDATE" => exdates.extend(value.split(',').filter_map(|value| parse_date_time(params, value))),
+            // 「空き時間」の予定・キャンセルされた予定では通知を止めない
+            "TRANSP" if value == "TRANSPARENT" => return None,
+            "STATUS" if value == "CANCELLED" => return None,
+            _ => {}
+        }
+    }
+
+    let (start, end) = (start?, end?);
+    (start < end).then_some(CalendarEvent {
+        summary,
+        start,
+        end,
+        recurrence,
+        exdates,
+    })
+}
+
+/// カレンダーを取得して予定を取り出す
+fn fetch(url: &str) -> Result<Vec<CalendarEvent>, CalendarError> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .get(url)
+        .call()
+        .map_err(|e| CalendarError::Download(e.to_string()))?;
+
+    let mut text = String::new();
+    response
+        .into_reader()
+        .take(MAX_CALENDAR_BYTES + 1)
+        .read_to_string(&mut text)?;
+    if text.len() as u64 > MAX_CALENDAR_BYTES {
+        return Err(CalendarError::TooLarge);
+    }
+    Ok(parse_ics(&text))
+}
+
+/// 取得したカレンダーの予定
+#[derive(Debug, Default)]
+pub struct CalendarSchedule {
+    events: Mutex<Vec<CalendarEvent>>,
+}
+
+impl CalendarSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn set(&self, events: Vec<CalendarEvent>) {
+        if let Ok(mut current) = self.events.lock() {
+            *current = events;
+        }
+    }
+
+    /// 指定した時刻に最中の予定の件名
+    pub fn current_event(&self, now: DateTime<Utc>) -> Option<String> {
+        let events = self.events.lock().ok()?;
+        events
+            .iter()
+            .find(|event| event.is_active_at(now))
+            .map(|event| event.summary.clone())
+    }
+}
+
+/// カレンダーを定期的に取得する（設定の変更は次の確認で反映する）
+pub fn start_sync(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last_sync: Option<(String, Instant)> = None;
+        loop {
+            if let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>() {
+                match notification_manager.get_settings().calendar.active_url() {
+                    None => {
+                        notification_manager.calendar().set(Vec::new());
+                        last_sync = None;
+                    }
+                    Some(url) => {
+                        let fresh = matches!(&last_sync, Some((last_url, at)) if *last_url == url && at.elapsed() < SYNC_INTERVAL);
+                        if !fresh {
+                            match fetch(&url) {
+                                Ok(events) => {
+                                    info!("Calendar synced: {} events", events.len());
+                                    notification_manager.calendar().set(events);
+                                }
+                                // 取得に失敗した場合は前回の予定を使い続ける
+                                Err(e) => warn!("Failed to sync calendar: {}", e),
+                            }
+                            last_sync = Some((url, Instant::now()));
+                        }
+                    }
+                }
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(value: &str) -> DateTime<Utc> {
+        parse_date_time("", value).unwrap()
+    }
+
+    const ICS: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Design review\\, round 2\r\n\
+DTSTART:20261019T100000\r\n\
+DTEND:20261019T110000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Standup\r\n\
+DTSTART;TZID=Asia/Tokyo:20261019T093000\r\n\
+DTEND;TZID=Asia/Tokyo:20261019T094500\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20261231T000000Z\r\n\
+EXDATE;TZID=Asia/Tokyo:20261021T093000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Focus time\r\n\
+DTSTART:20261019T130000\r\n\
+DTEND:20261019T150000\r\n\
+TRANSP:TRANSPARENT\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Holiday\r\n\
+DTSTART;VALUE=DATE:20261020\r\n\
+DTEND;VALUE=DATE:20261021\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_active_url() {
+        let mut settings = CalendarSettings {
+            enabled: true,
+            ics_url: " webcal://example.com/cal.ics ".to_string(),
+        };
+        assert_eq!(settings.active_url().as_deref(), Some("https://example.com/cal.ics"));
+
+        settings.enabled = false;
+        assert_eq!(settings.active_url(), None);
+    }
+
+    #[test]
+    fn test_parse_ics_skips_free_and_all_day_events() {
+        let events = parse_ics(ICS);
+        let summaries: Vec<_> = events.iter().map(|event| event.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["Design review, round 2", "Standup"]);
+    }
+
+    #[test]
+    fn test_unfold_continuation_lines() {
+        let events = parse_ics(
+            "BEGIN:VEVENT\nSUMMARY:Long\n  meeting\nDTSTART:20261019T100000Z\nDTEND:20261019T110000Z\nEND:VEVENT\n",
+        );
+        assert_eq!(events[0].summary, "Long meeting");
+        assert!(events[0].is_active_at(events[0].start));
+    }
+
+    #[test]
+    fn test_current_event() {
+        let schedule = CalendarSchedule::new();
+        schedule.set(parse_ics(ICS));
+
+        assert_eq!(
+            schedule.current_event(local("20261019T103000")).as_deref(),
+            Some("Design review, round 2")
+        );
+        assert_eq!(schedule.current_event(local("20261019T110000")), None);
+        // 「空き時間」の予定
+        assert_eq!(schedule.current_event(local("20261019T140000")), None);
+    }
+
+    #[test]
+    fn test_weekly_recurrence() {
+        let schedule = CalendarSchedule::new();
+        schedule.set(parse_ics(ICS));
+
+        // 月曜・水曜の繰り返し
+        assert_eq!(schedule.current_event(local("20261026T094000")).as_deref(), Some("Standup"));
+        assert_eq!(schedule.current_event(local("20261028T093000")).as_deref(), Some("Standup"));
+        assert_eq!(schedule.current_event(local("20261027T093000")), None);
+        // EXDATE で除外した回
+        assert_eq!(schedule.current_event(local("20261021T093000")), None);
+        // UNTIL より後
+        assert_eq!(schedule.current_event(local("20270104T093000")), None);
+    }
+
+    #[test]
+    fn test_daily_recurrence_with_count() {
+        let recurrence = Recurrence::parse("FREQ=DAILY;INTERVAL=2;COUNT=3").unwrap();
+        let first = local("20261019T090000");
+        let starts = recurrence.starts(first, local("20261031T000000"));
+        assert_eq!(
+            starts,
+            vec![first, local("20261021T090000"), local("20261023T090000")]
+        );
+
+        assert_eq!(Recurrence::parse("FREQ=MONTHLY"), None);
+    }
+}
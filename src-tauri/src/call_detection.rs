@@ -0,0 +1,55 @@
+//! 通話中の検出モジュール
+//!
+//! Teams・Zoom などで通話している間は、通知音とトーストを止めてバッジ・点滅だけで知らせる。
+//! Windows では既定の通信用の録音デバイス（マイク）に、このアプリ以外のアクティブな
+//! オーディオセッションがあるかで判定する（その他のプラットフォームでは検出しない）。
+
+/// 既定の通信用デバイスを他のアプリが使用中か（通話中か）
+#[cfg(windows)]
+pub fn is_in_call() -> bool {
+    match communications_device_in_use() {
+        Ok(in_use) => in_use,
+        Err(e) => {
+            tracing::warn!("Failed to query communications device sessions: {}", e);
+            false
+        }
+    }
+}
+
+/// 既定の通信用デバイスを他のアプリが使用中か（Windows 以外では検出しない）
+#[cfg(not(windows))]
+pub fn is_in_call() -> bool {
+    false
+}
+
+#[cfg(windows)]
+fn communications_device_in_use() -> windows::core::Result<bool> {
+    use windows::core::Interface;
+    use windows::Win32::Media::Audio::{
+        eCapture, eCommunications, AudioSessionStateActive, IAudioSessionControl2, IAudioSessionManager2,
+        IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+    unsafe {
+        // 呼び出し元のスレッドで初期化済みの場合は失敗するが、そのまま使える
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let device = enumerator.GetDefaultAudioEndpoint(eCapture, eCommunications)?;
+        let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+        let sessions = manager.GetSessionEnumerator()?;
+        let own_pid = std::process::id();
+        for i in 0..sessions.GetCount()? {
+            let session = sessions.GetSession(i)?;
+            if session.GetState()? != AudioSessionStateActive {
+                continue;
+            }
+            let session: IAudioSessionControl2 = session.cast()?;
+            if session.GetProcessId().ok() != Some(own_pid) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
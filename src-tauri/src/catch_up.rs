@@ -0,0 +1,146 @@
+//! 起動直後の取りこぼした通知のまとめ表示モジュール
+//!
+//! ブローカーに残っていたメッセージ（retained・送信待ちのもの）は、アプリの起動直後にまとめて届く。
+//! 起動から一定時間の間に届いた、ペイロードの `timestamp` が一定時間より古いイベントは
+//! トースト・通知音を出さずに履歴に記録し（未確認数・バッジは通常どおり増やす）、
+//! 届き終わった後に「N 件の通知を見逃しました」を1回だけ表示する。
+//! `timestamp` のないペイロードは古さが分からないため、通常どおり通知する。
+
+use crate::timestamps;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 最後のイベントからこの時間届かなければ、まとめて表示する
+pub const QUIET_PERIOD: Duration = Duration::from_secs(3);
+
+/// 起動直後のまとめ表示の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CatchUpSettings {
+    pub enabled: bool,
+    /// 起動から何秒の間に届いたイベントを対象にするか
+    pub startup_secs: u32,
+    /// これより古いイベント（分）はトースト・通知音を出さない
+    pub max_age_minutes: u32,
+}
+
+impl Default for CatchUpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            startup_secs: 60,
+            max_age_minutes: 5,
+        }
+    }
+}
+
+/// 起動直後に届いた古いイベントの数え上げ
+#[derive(Debug)]
+pub struct CatchUp {
+    started: Instant,
+    missed: AtomicU32,
+    last_missed: Mutex<Option<Instant>>,
+    /// まとめて表示するスレッドが動いているか
+    summarizing: AtomicBool,
+}
+
+impl Default for CatchUp {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl CatchUp {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            missed: AtomicU32::new(0),
+            last_missed: Mutex::new(None),
+            summarizing: AtomicBool::new(false),
+        }
+    }
+
+    /// 起動直後に届いた古いイベントか
+    fn is_backlog(&self, settings: &CatchUpSettings, payload: &str, received: Instant, now: DateTime<Utc>) -> bool {
+        if !settings.enabled || received.duration_since(self.started) > Duration::from_secs(u64::from(settings.startup_secs)) {
+            return false;
+        }
+        timestamps::payload_timestamp(payload)
+            .is_some_and(|timestamp| now - timestamp > chrono::Duration::minutes(i64::from(settings.max_age_minutes)))
+    }
+
+    /// 今届いたメッセージが取りこぼしたイベントか
+    pub fn classify(&self, settings: &CatchUpSettings, payload: &str) -> bool {
+        self.is_backlog(settings, payload, Instant::now(), Utc::now())
+    }
+
+    /// 取りこぼしたイベントの通知を数える（まとめ表示のスレッドを開始する必要がある場合は true）
+    pub fn absorb(&self) -> bool {
+        self.missed.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut last) = self.last_missed.lock() {
+            *last = Some(Instant::now());
+        }
+        !self.summarizing.swap(true, Ordering::SeqCst)
+    }
+
+    /// 最後のイベントから `QUIET_PERIOD` が過ぎたか
+    pub fn is_quiet(&self, now: Instant) -> bool {
+        self.last_missed
+            .lock()
+            .map(|last| last.is_none_or(|last| now.duration_since(last) >= QUIET_PERIOD))
+            .unwrap_or(true)
+    }
+
+    /// 数えたイベントの数を取り出し、まとめ表示を終了する
+    pub fn take(&self) -> u32 {
+        self.summarizing.store(false, Ordering::SeqCst);
+        self.missed.swap(0, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(timestamp: &str) -> String {
+        format!(r#"{{"event":"stop","cwd":"/app","timestamp":"{}"}}"#, timestamp)
+    }
+
+    #[test]
+    fn test_backlog_only_during_startup() {
+        let started = Instant::now();
+        let catch_up = CatchUp::new(started);
+        let settings = CatchUpSettings::default();
+        let now = DateTime::parse_from_rfc3339("2026-10-17T10:00:00Z").unwrap().with_timezone(&Utc);
+        let old = payload("2026-10-17T09:30:00Z");
+        let recent = payload("2026-10-17T09:58:00Z");
+
+        assert!(catch_up.is_backlog(&settings, &old, started + Duration::from_secs(5), now));
+        assert!(!catch_up.is_backlog(&settings, &recent, started + Duration::from_secs(5), now));
+        // 時刻のないペイロードは通常どおり通知する
+        assert!(!catch_up.is_backlog(&settings, r#"{"event":"stop"}"#, started, now));
+        // 起動直後を過ぎたら古くても通常どおり
+        assert!(!catch_up.is_backlog(&settings, &old, started + Duration::from_secs(61), now));
+
+        let disabled = CatchUpSettings {
+            enabled: false,
+            ..settings
+        };
+        assert!(!catch_up.is_backlog(&disabled, &old, started, now));
+    }
+
+    #[test]
+    fn test_absorb_counts_missed_notifications() {
+        let catch_up = CatchUp::default();
+        assert!(catch_up.absorb());
+        assert!(!catch_up.absorb());
+
+        assert!(!catch_up.is_quiet(Instant::now()));
+        assert!(catch_up.is_quiet(Instant::now() + QUIET_PERIOD));
+        assert_eq!(catch_up.take(), 2);
+        assert_eq!(catch_up.take(), 0);
+    }
+}
@@ -0,0 +1,78 @@
+//! 通知チャネルのテストモジュール
+//!
+//! 設定画面からトースト・タスクバー・トレイアイコンを個別に試せるようにする。
+//! 「イベント全体」のテストは、フックと同じ形式の停止イベントを handle_mqtt_message に渡し、
+//! 署名の検証・復号から通知履歴への記録、各チャネルでの通知までを通して確認する（[`dev_events`]）。
+
+use crate::attention;
+use crate::dev_events::{self, FakeEventKind};
+use crate::notification_state::BadgeKind;
+use crate::toast::{self, NotificationPriority, ToastContent};
+use crate::tray_flash::FlashTiming;
+use crate::NotificationManager;
+use serde_json::Map;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// テストでトレイアイコンを点滅させる時間
+const TRAY_FLASH_DURATION: Duration = Duration::from_secs(3);
+
+/// テストでタスクバーのバッジを表示する時間
+const TASKBAR_BADGE_DURATION: Duration = Duration::from_secs(3);
+
+/// Tauriコマンド: テスト用のトーストを表示
+#[tauri::command]
+pub fn test_toast(app: AppHandle, notification_manager: tauri::State<'_, Arc<NotificationManager>>) {
+    let strings = notification_manager.strings();
+    let content = ToastContent {
+        title: strings.test_toast_title.to_string(),
+        body: strings.test_toast_body.to_string(),
+        image: None,
+    };
+    toast::show_toast(&app, content, NotificationPriority::Normal, strings, &[], None);
+}
+
+/// Tauriコマンド: タスクバーボタン（macOS / Linux は Dock・ランチャー）を点滅させ、バッジを数秒間表示
+#[tauri::command]
+pub fn test_taskbar(
+    app: AppHandle,
+    notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+) -> Result<(), String> {
+    attention::main_window(&app).ok_or("Main window not found")?;
+    let attention = notification_manager.attention.as_ref();
+    attention.flash(&app, &notification_manager.get_settings())?;
+    attention.badge(&app, Some((BadgeKind::Unread, 1)))?;
+
+    // 未確認数・承認待ちのバッジに戻す
+    let notification_manager = Arc::clone(&notification_manager);
+    std::thread::spawn(move || {
+        std::thread::sleep(TASKBAR_BADGE_DURATION);
+        notification_manager.refresh_badges(&app);
+    });
+    Ok(())
+}
+
+/// Tauriコマンド: トレイアイコンを数秒間点滅
+#[tauri::command]
+pub fn test_tray_flash(app: AppHandle, notification_manager: tauri::State<'_, Arc<NotificationManager>>) {
+    let timing = FlashTiming {
+        max_duration: None,
+        ..notification_manager.get_settings().flash_timing()
+    };
+    notification_manager.tray_flasher.start_flash(timing);
+
+    // 点滅を止め、未確認数のバッジ表示中だった場合は元に戻す
+    let notification_manager = Arc::clone(&notification_manager);
+    std::thread::spawn(move || {
+        std::thread::sleep(TRAY_FLASH_DURATION);
+        notification_manager.tray_flasher.stop_flash();
+        notification_manager.refresh_badges(&app);
+    });
+}
+
+/// Tauriコマンド: 停止イベントを受信した場合と同じ手順で処理する
+#[tauri::command]
+pub fn simulate_stop_event(app: AppHandle) -> Result<(), String> {
+    dev_events::inject(&app, FakeEventKind::Stop, Map::new())
+}
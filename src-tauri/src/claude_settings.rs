@@ -0,0 +1,320 @@
+//! Claude Code の設定（settings.json）へのフックのマージモジュール
+//!
+//! 手作業で settings.json を編集しなくて済むよう、このアプリのフックを既存の設定にマージする。
+//! install.sh / install.ps1 と同じく、スクリプトのフォルダー以外を指すユーザーのフックは残し、
+//! このアプリのフックだけを置き換える。statusLine が別のコマンドに設定済みの場合などは上書きせず、競合として報告する。
+//! このPCで Claude Code を使う場合は、エクスポートタブからスクリプトの配置とマージをまとめて行える。
+
+use crate::export::{self, ExportConfig, ExportPlatform};
+use chrono::Local;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// スクリプトを配置するフォルダー（ホームフォルダーからの相対パス）
+pub const SCRIPTS_DIR_NAME: &str = ".claude-notify-scripts";
+
+#[derive(Debug, Error)]
+pub enum MergeError {
+    #[error("settings.json is not valid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("settings.json must be a JSON object")]
+    NotAnObject,
+    #[error("\"{0}\" in settings.json must be {1}")]
+    WrongType(String, &'static str),
+}
+
+#[derive(Debug, Error)]
+pub enum InstallError {
+    #[error(transparent)]
+    Merge(#[from] MergeError),
+    #[error("Failed to write {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Missing mqtt-publish binary: {0}")]
+    MissingBinary(String),
+}
+
+/// マージで上書きしなかった設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Conflict {
+    /// statusLine が別のコマンドに設定されている（そのまま残す）
+    StatusLineInUse { command: String },
+    /// `disableAllHooks` ですべてのフックが無効になっている
+    HooksDisabled,
+}
+
+/// マージの結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeResult {
+    pub settings: Value,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// このアプリが登録するフック（イベント・matcher・スクリプト）
+const HOOKS: &[(&str, &str, &str)] = &[
+    ("Stop", "", "on-stop"),
+    ("PermissionRequest", "", "on-permission-request"),
+    ("Notification", "elicitation_dialog", "on-notification"),
+];
+
+/// フック・statusLine のコマンド
+fn command(platform: ExportPlatform, scripts_dir: &str, script: &str) -> String {
+    match platform {
+        ExportPlatform::LinuxWsl => format!("{}/{}.sh", scripts_dir, script),
+        ExportPlatform::Windows => {
+            format!("powershell.exe -NoProfile -ExecutionPolicy Bypass -File \"{}\\{}.ps1\"", scripts_dir, script)
+        }
+    }
+}
+
+/// コマンドがこのアプリのスクリプトか
+fn is_ours(command: Option<&Value>, scripts_dir: &str) -> bool {
+    command
+        .and_then(Value::as_str)
+        .is_some_and(|command| command.contains(scripts_dir))
+}
+
+/// フックの設定（matcher ごとの配列）から、このアプリのスクリプトを指すものを除く
+fn without_our_hooks(entries: &[Value], scripts_dir: &str) -> Vec<Value> {
+    entries
+        .iter()
+        .filter(|entry| {
+            let hooks = entry.get("hooks").and_then(Value::as_array);
+            !hooks.is_some_and(|hooks| hooks.iter().any(|hook| is_ours(hook.get("command"), scripts_dir)))
+        })
+        .cloned()
+        .collect()
+}
+
+/// 既存の設定にこのアプリのフックをマージする
+pub fn merge(
+    existing: Option<&str>,
+    platform: ExportPlatform,
+    scripts_dir: &str,
+    with_statusline: bool,
+) -> Result<MergeResult, MergeError> {
+    let mut settings = match existing.map(str::trim).filter(|text| !text.is_empty()) {
+        Some(text) => serde_json::from_str::<Value>(text)?,
+        None => json!({}),
+    };
+    let root = settings.as_object_mut().ok_or(MergeError::NotAnObject)?;
+    let mut conflicts = Vec::new();
+
+    let hooks = root
+        .entry("hooks")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or_else(|| MergeError::WrongType("hooks".to_string(), "an object"))?;
+    for (event, matcher, script) in HOOKS {
+        let mut entries = match hooks.get(*event) {
+            Some(Value::Array(entries)) => without_our_hooks(entries, scripts_dir),
+            Some(_) => return Err(MergeError::WrongType(format!("hooks.{}", event), "an array")),
+            None => Vec::new(),
+        };
+        entries.push(json!({
+            "matcher": matcher,
+            "hooks": [{ "type": "command", "command": command(platform, scripts_dir, script) }],
+        }));
+        hooks.insert(event.to_string(), Value::Array(entries));
+    }
+
+    if with_statusline {
+        match root.get("statusLine") {
+            Some(status_line) if !is_ours(status_line.get("command"), scripts_dir) => {
+                let command = status_line.get("command").and_then(Value::as_str).unwrap_or_default();
+                conflicts.push(Conflict::StatusLineInUse {
+                    command: command.to_string(),
+                });
+            }
+            _ => {
+                root.insert(
+                    "statusLine".to_string(),
+                    json!({ "type": "command", "command": command(platform, scripts_dir, "statusline") }),
+                );
+            }
+        }
+    }
+
+    if root.get("disableAllHooks").and_then(Value::as_bool) == Some(true) {
+        conflicts.push(Conflict::HooksDisabled);
+    }
+
+    Ok(MergeResult { settings, conflicts })
+}
+
+/// このPCへのインストールの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallReport {
+    pub scripts_dir: PathBuf,
+    pub settings_file: PathBuf,
+    /// 変更前の settings.json のバックアップ（なかった場合は None）
+    pub backup: Option<PathBuf>,
+    pub conflicts: Vec<Conflict>,
+}
+
+fn write(path: &Path, contents: &[u8]) -> Result<(), InstallError> {
+    fs::write(path, contents).map_err(|source| InstallError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), InstallError> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(|source| InstallError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), InstallError> {
+    Ok(())
+}
+
+/// このPCのホームフォルダーにスクリプトを配置し、settings.json にフックをマージする
+///
+/// settings.json が読み取れない場合は何も変更しない。
+pub fn install_locally(
+    config: &ExportConfig,
+    platform: ExportPlatform,
+    mqtt_publish_exe: Option<&[u8]>,
+    home: &Path,
+    with_statusline: bool,
+) -> Result<InstallReport, InstallError> {
+    let scripts_dir = home.join(SCRIPTS_DIR_NAME);
+    let settings_file = home.join(".claude").join("settings.json");
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| InstallError::Io { path, source }
+    };
+
+    // スクリプトを書き出す前に、マージできる設定か確認する
+    let existing = match fs::read_to_string(&settings_file) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(io_error(&settings_file)(e)),
+    };
+    let merged = merge(existing.as_deref(), platform, &scripts_dir.to_string_lossy(), with_statusline)?;
+
+    fs::create_dir_all(&scripts_dir).map_err(io_error(&scripts_dir))?;
+    if platform == ExportPlatform::Windows {
+        let exe = mqtt_publish_exe
+            .ok_or_else(|| InstallError::MissingBinary("mqtt-publish.exe not provided".to_string()))?;
+        write(&scripts_dir.join("mqtt-publish.exe"), exe)?;
+    }
+    for (file_name, script) in export::render_scripts(config, platform) {
+        // インストーラはこのPCへのインストールでは使わない
+        if file_name.starts_with("install.") || (file_name.starts_with("statusline.") && !with_statusline) {
+            continue;
+        }
+        let path = scripts_dir.join(file_name);
+        write(&path, script.as_bytes())?;
+        make_executable(&path)?;
+    }
+
+    let settings_dir = settings_file.parent().unwrap_or(home);
+    fs::create_dir_all(settings_dir).map_err(io_error(settings_dir))?;
+    let backup = match existing {
+        Some(_) => {
+            let backup = settings_file.with_file_name(format!(
+                "settings.json.backup.{}",
+                Local::now().format("%Y%m%d_%H%M%S")
+            ));
+            fs::copy(&settings_file, &backup).map_err(io_error(&backup))?;
+            Some(backup)
+        }
+        None => None,
+    };
+    let json = serde_json::to_string_pretty(&merged.settings).map_err(MergeError::from)?;
+    write(&settings_file, json.as_bytes())?;
+
+    Ok(InstallReport {
+        scripts_dir,
+        settings_file,
+        backup,
+        conflicts: merged.conflicts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIR: &str = "/home/me/.claude-notify-scripts";
+
+    #[test]
+    fn test_merge_into_empty_settings() {
+        let result = merge(None, ExportPlatform::LinuxWsl, DIR, false).unwrap();
+        assert!(result.conflicts.is_empty());
+        let hooks = &result.settings["hooks"];
+        assert_eq!(hooks["Stop"][0]["hooks"][0]["command"], "/home/me/.claude-notify-scripts/on-stop.sh");
+        assert_eq!(hooks["Notification"][0]["matcher"], "elicitation_dialog");
+        assert!(result.settings.get("statusLine").is_none());
+    }
+
+    #[test]
+    fn test_merge_keeps_user_hooks_and_replaces_ours() {
+        let existing = json!({
+            "model": "opus",
+            "hooks": {
+                "Stop": [
+                    { "matcher": "", "hooks": [{ "type": "command", "command": "say done" }] },
+                    { "matcher": "", "hooks": [{ "type": "command", "command": format!("{}/on-stop.sh", DIR) }] }
+                ],
+                "PreToolUse": [
+                    { "matcher": "Bash", "hooks": [{ "type": "command", "command": "audit.sh" }] }
+                ]
+            }
+        });
+        let result = merge(Some(&existing.to_string()), ExportPlatform::LinuxWsl, DIR, false).unwrap();
+        let settings = &result.settings;
+        assert_eq!(settings["model"], "opus");
+        let stop = settings["hooks"]["Stop"].as_array().unwrap();
+        assert_eq!(stop.len(), 2);
+        assert_eq!(stop[0]["hooks"][0]["command"], "say done");
+        assert_eq!(settings["hooks"]["PreToolUse"][0]["hooks"][0]["command"], "audit.sh");
+
+        // 2回目のマージでも重複しない
+        let again = merge(Some(&settings.to_string()), ExportPlatform::LinuxWsl, DIR, false).unwrap();
+        assert_eq!(&again.settings, settings);
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let existing = r#"{"statusLine": {"type": "command", "command": "~/my-status.sh"}, "disableAllHooks": true}"#;
+        let result = merge(Some(existing), ExportPlatform::Windows, r"C:\Users\me\.claude-notify-scripts", true).unwrap();
+        assert_eq!(
+            result.conflicts,
+            vec![
+                Conflict::StatusLineInUse {
+                    command: "~/my-status.sh".to_string()
+                },
+                Conflict::HooksDisabled,
+            ]
+        );
+        assert_eq!(result.settings["statusLine"]["command"], "~/my-status.sh");
+        assert_eq!(
+            result.settings["hooks"]["Stop"][0]["hooks"][0]["command"],
+            r#"powershell.exe -NoProfile -ExecutionPolicy Bypass -File "C:\Users\me\.claude-notify-scripts\on-stop.ps1""#
+        );
+    }
+
+    #[test]
+    fn test_merge_rejects_unexpected_structure() {
+        assert!(matches!(merge(Some("{"), ExportPlatform::LinuxWsl, DIR, false), Err(MergeError::InvalidJson(_))));
+        assert!(matches!(merge(Some("[]"), ExportPlatform::LinuxWsl, DIR, false), Err(MergeError::NotAnObject)));
+        assert!(matches!(
+            merge(Some(r#"{"hooks": {"Stop": {}}}"#), ExportPlatform::LinuxWsl, DIR, false),
+            Err(MergeError::WrongType(field, _)) if field == "hooks.Stop"
+        ));
+    }
+}
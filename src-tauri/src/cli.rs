@@ -0,0 +1,212 @@
+//! コマンドライン引数モジュール
+//!
+//! スクリプトからの起動やデバッグのため、起動時のウィンドウ・おやすみモード・プロファイル・
+//! ブローカーのポート・ログレベルを指定できるようにする。
+//! アプリは1つだけ起動する。2つ目の起動では引数を起動中のインスタンスに渡して反映し、
+//! メインウィンドウを前面に表示する（`--history` などを指定した場合はそのタブを開く）。
+
+use crate::{profiles, tray, LogFilterHandle, NotificationManager};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+/// 開くタブを指定するオプション
+const TAB_OPTIONS: &[(&str, &str)] = &[
+    ("--history", "history"),
+    ("--settings", "settings"),
+    ("--export", "export"),
+    ("--diagnostics", "diagnostics"),
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CliError {
+    #[error("missing value for {0}")]
+    MissingValue(&'static str),
+    #[error("invalid value for {option}: {value}")]
+    InvalidValue { option: &'static str, value: String },
+}
+
+/// 解釈したコマンドライン引数
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LaunchArgs {
+    /// 開くタブ（複数指定した場合は最後のもの）
+    pub tab: Option<&'static str>,
+    /// ウィンドウを表示せずにトレイで起動する
+    pub hidden: bool,
+    /// おやすみモードで起動する
+    pub do_not_disturb: bool,
+    /// 切り替える設定プロファイル
+    pub profile: Option<String>,
+    /// 組み込みブローカーの待ち受けポート（起動時のみ反映）
+    pub port: Option<u16>,
+    /// ログレベル（`debug` や `claude_code_notify_lib=trace` などの EnvFilter の書式）
+    pub log_level: Option<String>,
+}
+
+impl LaunchArgs {
+    /// 引数を解釈する（先頭の実行ファイルのパスは含めない）
+    ///
+    /// 値を取るオプションは `--port 1884` と `--port=1884` のどちらの形でも指定できる。
+    pub fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self, CliError> {
+        let mut parsed = Self::default();
+        let mut args = args.iter().map(AsRef::as_ref);
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (arg, None),
+            };
+            let mut value = |option: &'static str| {
+                inline_value
+                    .or_else(|| args.next())
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+                    .ok_or(CliError::MissingValue(option))
+            };
+            match name {
+                "--hidden" => parsed.hidden = true,
+                "--dnd" => parsed.do_not_disturb = true,
+                "--profile" => parsed.profile = Some(value("--profile")?),
+                "--port" => {
+                    let port = value("--port")?;
+                    parsed.port = Some(port.parse().ok().filter(|port| *port > 0).ok_or(
+                        CliError::InvalidValue {
+                            option: "--port",
+                            value: port,
+                        },
+                    )?);
+                }
+                "--log-level" => {
+                    let level = value("--log-level")?;
+                    if EnvFilter::try_new(&level).is_err() {
+                        return Err(CliError::InvalidValue {
+                            option: "--log-level",
+                            value: level,
+                        });
+                    }
+                    parsed.log_level = Some(level);
+                }
+                _ => match TAB_OPTIONS.iter().find(|(option, _)| *option == name) {
+                    Some((_, tab)) => parsed.tab = Some(tab),
+                    None => warn!("Ignoring unknown argument: {}", arg),
+                },
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// 起動時に指定したタブ（フロントエンドの準備ができてから開く）
+#[derive(Debug, Default)]
+pub struct LaunchTab(Mutex<Option<&'static str>>);
+
+impl LaunchTab {
+    pub fn new(tab: Option<&'static str>) -> Self {
+        Self(Mutex::new(tab))
+    }
+}
+
+/// 起動中のインスタンスに反映できるオプション（おやすみモード・プロファイル・ログレベル）を適用する
+pub fn apply(app: &AppHandle, args: &LaunchArgs) {
+    if args.do_not_disturb {
+        if let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>() {
+            notification_manager.set_do_not_disturb(app, true);
+        }
+    }
+    if let Some(name) = &args.profile {
+        if let Err(e) = profiles::switch(app, name) {
+            warn!("Failed to switch settings profile from command line: {}", e);
+        }
+    }
+    if let Some(level) = &args.log_level {
+        if let Some(handle) = app.try_state::<LogFilterHandle>() {
+            match handle.reload(EnvFilter::new(level)) {
+                Ok(()) => info!("Log level changed to {}", level),
+                Err(e) => warn!("Failed to change log level: {}", e),
+            }
+        }
+    }
+}
+
+/// 2つ目のインスタンスが起動された場合に、引数を反映して起動中のメインウィンドウを前面に表示する
+pub fn handle_second_instance(app: &AppHandle, argv: &[String]) {
+    let args = match LaunchArgs::parse(argv.get(1..).unwrap_or_default()) {
+        Ok(args) => args,
+        Err(e) => {
+            warn!("Invalid arguments from second instance: {}", e);
+            LaunchArgs::default()
+        }
+    };
+    info!("Second instance launched with {:?}", args);
+
+    apply(app, &args);
+    if let Some(port) = args.port {
+        warn!("--port {} is ignored because the broker is already running", port);
+    }
+
+    match (args.hidden, args.tab) {
+        (true, _) => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.hide();
+            }
+        }
+        (false, Some(tab)) => tray::show_main_window_with_tab(app, tab),
+        (false, None) => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }
+    }
+}
+
+/// Tauriコマンド: 起動時に指定したタブを取得（1度だけ返す）
+#[tauri::command]
+pub fn take_launch_tab(launch_tab: tauri::State<'_, LaunchTab>) -> Option<&'static str> {
+    launch_tab.0.lock().unwrap().take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tab() {
+        assert_eq!(LaunchArgs::parse::<&str>(&[]), Ok(LaunchArgs::default()));
+        assert_eq!(LaunchArgs::parse(&["--history"]).unwrap().tab, Some("history"));
+        assert_eq!(LaunchArgs::parse(&["--history", "--unknown", "--diagnostics"]).unwrap().tab, Some("diagnostics"));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let args = LaunchArgs::parse(&["--hidden", "--dnd", "--profile", "会議", "--port=1884", "--log-level", "debug"]).unwrap();
+        assert_eq!(
+            args,
+            LaunchArgs {
+                tab: None,
+                hidden: true,
+                do_not_disturb: true,
+                profile: Some("会議".to_string()),
+                port: Some(1884),
+                log_level: Some("debug".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(LaunchArgs::parse(&["--profile"]), Err(CliError::MissingValue("--profile")));
+        assert_eq!(LaunchArgs::parse(&["--port="]), Err(CliError::MissingValue("--port")));
+        assert_eq!(
+            LaunchArgs::parse(&["--port", "0"]),
+            Err(CliError::InvalidValue {
+                option: "--port",
+                value: "0".to_string(),
+            })
+        );
+        assert!(LaunchArgs::parse(&["--port", "70000"]).is_err());
+        assert!(LaunchArgs::parse(&["--log-level", "[invalid"]).is_err());
+    }
+}
@@ -2,10 +2,14 @@
 //!
 //! This module provides an async MQTT client for subscribing to
 //! Claude Code notifications and publishing status updates.
+//! The client is re-created with backoff when its message channel closes.
 
 use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use thiserror::Error;
+use tokio::runtime::Handle;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
@@ -23,10 +27,44 @@ pub mod topics {
     pub const EVENTS_NOTIFICATION: &str = "claude-code/events/notification";
     /// Status updates from Claude Code statusline (prefix for session-specific topics)
     pub const STATUS_PREFIX: &str = "claude-code/status/";
+    /// Notification acknowledgements published by this app (prefix for session-specific topics)
+    pub const ACK_PREFIX: &str = "claude-code/ack/";
+    /// Permission decisions published by this app (prefix for session-specific topics)
+    pub const RESPONSE_PREFIX: &str = "claude-code/response/";
+    /// Session commands published by this app (prefix for session-specific topics, followed by the command)
+    pub const CONTROL_PREFIX: &str = "claude-code/control/";
+    /// Results of session commands from the hook-side listener (prefix for session-specific topics)
+    pub const CONTROL_REPLY_PREFIX: &str = "claude-code/control-reply/";
+    /// Heartbeats from the statusline script while Claude Code is running (prefix for host-specific topics)
+    pub const HEARTBEAT_PREFIX: &str = "claude-code/heartbeat/";
+
+    /// Whether the topic carries messages published by this app itself
+    pub fn is_outbound(topic: &str) -> bool {
+        topic.starts_with(ACK_PREFIX) || topic.starts_with(RESPONSE_PREFIX) || topic.starts_with(CONTROL_PREFIX)
+    }
+
+    /// Whether the topic carries periodic updates (statusline and heartbeats) rather than events
+    pub fn is_periodic(topic: &str) -> bool {
+        topic == STATUS || topic.starts_with(STATUS_PREFIX) || topic.starts_with(HEARTBEAT_PREFIX)
+    }
+
+    /// Build a session-specific topic under `prefix`
+    ///
+    /// Returns None for an empty session ID. Wildcards and level separators are replaced.
+    pub fn for_session(prefix: &str, session_id: &str) -> Option<String> {
+        let session_id = session_id.trim();
+        if session_id.is_empty() {
+            return None;
+        }
+        let sanitized: String = session_id
+            .chars()
+            .map(|c| if matches!(c, '+' | '#' | '/') { '_' } else { c })
+            .collect();
+        Some(format!("{}{}", prefix, sanitized))
+    }
 }
 
 #[derive(Error, Debug)]
-#[allow(dead_code)]
 pub enum ClientError {
     #[error("Connection error: {0}")]
     Connection(#[from] rumqttc::ClientError),
@@ -48,27 +86,161 @@ impl MqttMessage {
     }
 }
 
-/// Start MQTT client and return a receiver for incoming messages
-pub fn start_mqtt_client(client_id: &str) -> (AsyncClient, mpsc::Receiver<MqttMessage>) {
-    let mut options = MqttOptions::new(client_id, "127.0.0.1", 1883);
+/// Delay before the first restart of a closed client
+const INITIAL_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Upper bound of the restart delay
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+/// A client that ran this long before closing is considered healthy again
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+/// Consecutive closures after which the client is reported as failing
+pub const FAILURE_ALERT_THRESHOLD: u32 = 3;
+
+/// Connection state of the app's MQTT client, updated by the event loop
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionStatus {
+    connected: Arc<AtomicBool>,
+    /// Set when restarts keep failing, cleared on the next successful connection
+    failing: Arc<AtomicBool>,
+}
+
+impl ConnectionStatus {
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+        if connected {
+            self.failing.store(false, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_failing(&self) -> bool {
+        self.failing.load(Ordering::Relaxed)
+    }
+
+    /// Record that the message channel closed, marking the client as failing after repeated closures
+    pub fn set_closed(&self, consecutive_failures: u32) {
+        self.connected.store(false, Ordering::Relaxed);
+        if consecutive_failures >= FAILURE_ALERT_THRESHOLD {
+            self.failing.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Exponential backoff for restarting the MQTT client
+#[derive(Debug, Default)]
+pub struct RestartBackoff {
+    failures: u32,
+}
+
+impl RestartBackoff {
+    /// Record a closure of a client that ran for `ran_for` and return the delay before restarting
+    pub fn next_delay(&mut self, ran_for: Duration) -> Duration {
+        if ran_for >= STABLE_RUN {
+            self.failures = 0;
+        }
+        self.failures += 1;
+        let exponent = (self.failures - 1).min(6);
+        (INITIAL_RESTART_DELAY * 2u32.pow(exponent)).min(MAX_RESTART_DELAY)
+    }
+
+    /// Consecutive closures without a stable run in between
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+/// Publishes outbound messages through the app's MQTT client
+pub struct MqttPublisher {
+    client: Mutex<AsyncClient>,
+}
+
+impl MqttPublisher {
+    pub fn new(client: AsyncClient) -> Self {
+        Self {
+            client: Mutex::new(client),
+        }
+    }
+
+    /// Publish through a restarted client from now on
+    pub fn replace(&self, client: AsyncClient) {
+        if let Ok(mut current) = self.client.lock() {
+            *current = client;
+        }
+    }
+
+    /// Queue a QoS 0 publish without blocking the caller
+    pub fn publish(&self, topic: &str, payload: Vec<u8>, retain: bool) -> Result<(), ClientError> {
+        let client = self.client.lock().map_err(|_| ClientError::ConnectionClosed)?;
+        client.try_publish(topic, QoS::AtMostOnce, retain, payload)?;
+        Ok(())
+    }
+
+    /// Apply changes to the extra topic filters (subscribe added ones, unsubscribe removed ones)
+    pub fn update_subscriptions(&self, previous: &[String], current: &[String]) -> Result<(), ClientError> {
+        let client = self.client.lock().map_err(|_| ClientError::ConnectionClosed)?;
+        for filter in previous.iter().filter(|filter| !current.contains(filter)) {
+            info!("Unsubscribing from topic: {}", filter);
+            client.try_unsubscribe(filter.as_str())?;
+        }
+        for filter in current.iter().filter(|filter| !previous.contains(filter)) {
+            info!("Subscribing to topic: {}", filter);
+            client.try_subscribe(filter.as_str(), QoS::AtMostOnce)?;
+        }
+        Ok(())
+    }
+}
+
+/// Broker the app's own client connects to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokerEndpoint {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    /// Username and password (None to connect without login)
+    pub credentials: Option<(String, String)>,
+    /// Whether this is an external broker instead of the embedded one
+    pub external: bool,
+}
+
+impl BrokerEndpoint {
+    /// The embedded broker on this PC
+    pub fn embedded(port: u16) -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port,
+            client_id: crate::acl::INTERNAL_CLIENT_ID.to_string(),
+            // Login is required by the broker when access control is enabled
+            credentials: Some((crate::acl::CLIENT_USERNAME.to_string(), String::new())),
+            external: false,
+        }
+    }
+}
+
+/// Start MQTT client on the shared runtime and return a receiver for incoming messages
+///
+/// `extra_topics` are user-defined filters subscribed in addition to `claude-code/#`.
+pub fn start_mqtt_client(
+    runtime: &Handle,
+    endpoint: &BrokerEndpoint,
+    status: ConnectionStatus,
+    extra_topics: Vec<String>,
+) -> (AsyncClient, mpsc::Receiver<MqttMessage>) {
+    let mut options = MqttOptions::new(endpoint.client_id.as_str(), endpoint.host.as_str(), endpoint.port);
     options.set_keep_alive(Duration::from_secs(30));
+    if let Some((username, password)) = &endpoint.credentials {
+        options.set_credentials(username.as_str(), password.as_str());
+    }
     options.set_clean_session(true);
 
     let (client, eventloop) = AsyncClient::new(options, 100);
     let (tx, rx) = mpsc::channel(100);
 
-    let client_clone = client.clone();
-
-    std::thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .expect("Failed to create tokio runtime");
-
-        rt.block_on(async move {
-            run_event_loop(client_clone, eventloop, tx).await;
-        });
-    });
+    runtime.spawn(run_event_loop(client.clone(), eventloop, tx, status, extra_topics));
 
     (client, rx)
 }
@@ -77,6 +249,8 @@ async fn run_event_loop(
     client: AsyncClient,
     mut eventloop: EventLoop,
     tx: mpsc::Sender<MqttMessage>,
+    status: ConnectionStatus,
+    extra_topics: Vec<String>,
 ) {
     // Subscribe to topics after connection
     let mut subscribed = false;
@@ -85,12 +259,19 @@ async fn run_event_loop(
         match eventloop.poll().await {
             Ok(Event::Incoming(Packet::ConnAck(_))) => {
                 info!("Connected to MQTT broker");
+                status.set_connected(true);
                 if !subscribed {
                     info!("Subscribing to topic: {}", topics::ALL);
                     // Use QoS 0 (AtMostOnce) to prevent duplicate notifications
                     if let Err(e) = client.subscribe(topics::ALL, QoS::AtMostOnce).await {
                         error!("Failed to subscribe: {:?}", e);
                     }
+                    for filter in &extra_topics {
+                        info!("Subscribing to topic: {}", filter);
+                        if let Err(e) = client.subscribe(filter.as_str(), QoS::AtMostOnce).await {
+                            error!("Failed to subscribe to {}: {:?}", filter, e);
+                        }
+                    }
                 }
             }
             Ok(Event::Incoming(Packet::SubAck(_))) => {
@@ -112,6 +293,7 @@ async fn run_event_loop(
             Ok(_) => {}
             Err(e) => {
                 error!("MQTT event loop error: {:?}", e);
+                status.set_connected(false);
                 tokio::time::sleep(Duration::from_secs(5)).await;
             }
         }
@@ -127,4 +309,54 @@ mod tests {
         assert_eq!(topics::ALL, "claude-code/#");
         assert_eq!(topics::TASK_COMPLETE, "claude-code/task/complete");
     }
+
+    #[test]
+    fn test_outbound_topics() {
+        assert!(topics::is_outbound("claude-code/ack/s1"));
+        assert!(topics::is_outbound("claude-code/response/s1"));
+        assert!(topics::is_outbound("claude-code/control/s1/stop"));
+        assert!(!topics::is_outbound("claude-code/control-reply/s1"));
+        assert!(!topics::is_outbound("claude-code/events/stop"));
+        assert!(!topics::is_outbound("claude-code/status/s1"));
+    }
+
+    #[test]
+    fn test_periodic_topics() {
+        assert!(topics::is_periodic("claude-code/status/s1"));
+        assert!(topics::is_periodic(topics::STATUS));
+        assert!(topics::is_periodic("claude-code/heartbeat/devbox"));
+        assert!(!topics::is_periodic(topics::EVENTS_STOP));
+        assert!(!topics::is_periodic("ci/builds/app"));
+    }
+
+    #[test]
+    fn test_restart_backoff() {
+        let mut backoff = RestartBackoff::default();
+        assert_eq!(backoff.next_delay(Duration::ZERO), Duration::from_secs(1));
+        assert_eq!(backoff.next_delay(Duration::ZERO), Duration::from_secs(2));
+        assert_eq!(backoff.next_delay(Duration::ZERO), Duration::from_secs(4));
+        assert_eq!(backoff.failures(), 3);
+        for _ in 0..10 {
+            backoff.next_delay(Duration::ZERO);
+        }
+        assert_eq!(backoff.next_delay(Duration::ZERO), MAX_RESTART_DELAY);
+
+        // A client that stayed up resets the backoff
+        assert_eq!(backoff.next_delay(STABLE_RUN), Duration::from_secs(1));
+        assert_eq!(backoff.failures(), 1);
+    }
+
+    #[test]
+    fn test_connection_status_failing() {
+        let status = ConnectionStatus::default();
+        status.set_connected(true);
+        status.set_closed(1);
+        assert!(!status.is_connected());
+        assert!(!status.is_failing());
+
+        status.set_closed(FAILURE_ALERT_THRESHOLD);
+        assert!(status.is_failing());
+        status.set_connected(true);
+        assert!(!status.is_failing());
+    }
 }
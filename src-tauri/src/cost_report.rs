@@ -0,0 +1,209 @@
+//! 毎日のコストレポートモジュール
+//!
+//! 設定で有効にした場合、毎日設定した時刻に、直近24時間にすべてのセッションでかかったコストを
+//! プロジェクトごとにまとめて通知し、通知履歴にも記録する。
+//! コストはメトリクスの履歴（[`crate::metrics_history`]）から計算するため、終了したセッションの分も含まれる。
+
+use crate::i18n::{self, Strings};
+use crate::metrics_history::MetricsHistory;
+use crate::notification_history::{NewHistoryEntry, NotificationEventType, NotificationHistoryManager};
+use crate::toast::NotificationPriority;
+use crate::{NotificationManager, NotificationOrigin};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+/// レポートの時刻が来たかを調べる間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// レポートで集計する時間
+const REPORT_HOURS: i64 = 24;
+
+/// レポートに表示するプロジェクトの最大数
+const MAX_REPORT_PROJECTS: usize = 5;
+
+/// 毎日のコストレポートの設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostReportSettings {
+    pub enabled: bool,
+    /// レポートを送る時刻（`HH:MM`）
+    pub time: String,
+}
+
+impl Default for CostReportSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: "18:00".to_string(),
+        }
+    }
+}
+
+impl CostReportSettings {
+    /// `now` 以前で最後にレポートを送る時刻（時刻を解釈できない場合は None）
+    fn latest_boundary<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let time = NaiveTime::parse_from_str(self.time.trim(), "%H:%M").ok()?;
+        let today = now.timezone().from_local_datetime(&now.date_naive().and_time(time)).earliest()?;
+        Some(if today > *now {
+            today - ChronoDuration::days(1)
+        } else {
+            today
+        })
+    }
+}
+
+/// 直近24時間のコストのまとめ
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostReport {
+    /// プロジェクトごとのコスト（多い順）
+    pub projects: Vec<(String, f64)>,
+}
+
+impl CostReport {
+    pub fn total_cost_usd(&self) -> f64 {
+        self.projects.iter().map(|(_, cost)| cost).sum()
+    }
+
+    /// 通知のタイトルと本文
+    pub fn text(&self, strings: &Strings) -> (String, String) {
+        let cost = |cost: f64| format!("{:.2}", cost);
+        let mut lines = vec![i18n::fill(strings.digest_cost, &[("cost", &cost(self.total_cost_usd()))])];
+        for (project, project_cost) in self.projects.iter().take(MAX_REPORT_PROJECTS) {
+            lines.push(i18n::fill(
+                strings.cost_report_project,
+                &[("project", project), ("cost", &cost(*project_cost))],
+            ));
+        }
+        if self.projects.len() > MAX_REPORT_PROJECTS {
+            let more = (self.projects.len() - MAX_REPORT_PROJECTS).to_string();
+            lines.push(i18n::fill(strings.digest_more_projects, &[("count", &more)]));
+        }
+        (strings.cost_report_title.to_string(), lines.join("\n"))
+    }
+}
+
+/// 前回のレポートの時刻
+#[derive(Debug)]
+pub struct CostReportState {
+    last_sent: Mutex<DateTime<Utc>>,
+}
+
+impl Default for CostReportState {
+    fn default() -> Self {
+        Self {
+            last_sent: Mutex::new(Utc::now()),
+        }
+    }
+}
+
+/// 直近24時間のコストを通知し、履歴に記録する（コストがない場合は送らない）
+fn send(app: &AppHandle) {
+    let now = Utc::now();
+    *app.state::<CostReportState>().last_sent.lock().unwrap() = now;
+
+    let notification_manager = app.state::<Arc<NotificationManager>>();
+    let settings = notification_manager.get_settings();
+    let report = CostReport {
+        projects: app
+            .state::<Arc<MetricsHistory>>()
+            .project_costs(now - ChronoDuration::hours(REPORT_HOURS), &settings.projects),
+    };
+    if report.projects.is_empty() {
+        info!("Cost report skipped: no cost in the last {} hours", REPORT_HOURS);
+        return;
+    }
+
+    let strings = notification_manager.strings();
+    let (title, body) = report.text(strings);
+    info!("Sending cost report: {:?}", report);
+
+    let history_manager = app.state::<Arc<NotificationHistoryManager>>();
+    if let Err(e) = history_manager.add_entry(app, NewHistoryEntry {
+        event_type: NotificationEventType::Notification,
+        session_name: title.clone(),
+        session_id: String::new(),
+        cwd: None,
+        content: Some(body.clone()),
+        request_id: None,
+        host: None,
+        image: None,
+        transcript_path: None,
+        sent_at: None,
+    }) {
+        warn!("Failed to add history entry: {}", e);
+    } else {
+        let _ = app.emit("notification-added", ());
+    }
+
+    notification_manager.notify(
+        app,
+        &title,
+        &body,
+        NotificationEventType::Notification,
+        NotificationPriority::Low,
+        NotificationOrigin::default(),
+    );
+}
+
+/// 設定した時刻に毎日のコストレポートを送る
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+
+        let settings = app.state::<Arc<NotificationManager>>().get_settings().cost_report;
+        if !settings.enabled {
+            continue;
+        }
+        let Some(boundary) = settings.latest_boundary(&Local::now()) else {
+            continue;
+        };
+        let last_sent = *app.state::<CostReportState>().last_sent.lock().unwrap();
+        if last_sent < boundary {
+            send(&app);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_latest_boundary() {
+        let tz = FixedOffset::east_opt(9 * 3600).unwrap();
+        let settings = CostReportSettings::default();
+
+        // 18時前は前日の18時
+        let before = tz.with_ymd_and_hms(2026, 10, 19, 17, 59, 0).unwrap();
+        assert_eq!(settings.latest_boundary(&before), Some(tz.with_ymd_and_hms(2026, 10, 18, 18, 0, 0).unwrap()));
+        let after = tz.with_ymd_and_hms(2026, 10, 19, 18, 0, 0).unwrap();
+        assert_eq!(settings.latest_boundary(&after), Some(after));
+
+        let invalid = CostReportSettings {
+            time: "6pm".to_string(),
+            ..CostReportSettings::default()
+        };
+        assert_eq!(invalid.latest_boundary(&after), None);
+    }
+
+    #[test]
+    fn test_text() {
+        let strings = Language::En.strings();
+        let report = CostReport {
+            projects: (1..=6).map(|i| (format!("app{}", i), 0.5)).collect(),
+        };
+        let (title, body) = report.text(strings);
+        assert_eq!(title, strings.cost_report_title);
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines[0], "Total cost: $3.00");
+        assert_eq!(lines[1], "• app1: $0.50");
+        assert_eq!(lines.len(), 7);
+    }
+}
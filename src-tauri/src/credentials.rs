@@ -0,0 +1,380 @@
+//! 認証情報の保存モジュール
+//!
+//! 外部のブローカーのパスワード・Slack の Webhook URL・REST API のトークン・署名のシークレット・暗号化の鍵、
+//! Webhook の URL とヘッダーの値・カレンダーの ICS の URL を
+//! 設定ファイル（JSON）に平文で書かず、OS のキーチェーン（Windows の資格情報マネージャー・macOS のキーチェーン・
+//! Linux の Secret Service）に保存する。設定ファイルには空の値を書き、読み込む時にキーチェーンの値で埋める。
+//! 以前のバージョンが設定ファイルに平文で保存した値は、起動時にキーチェーンに移す。
+//! キーチェーンに保存できない環境（Secret Service のない Linux など）では、従来どおり設定ファイルに残す。
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use thiserror::Error;
+use tracing::warn;
+
+/// キーチェーンのサービス名
+const SERVICE: &str = "claude-code-notify";
+
+/// 現在の設定の認証情報のアカウント名の接頭辞
+pub const SETTINGS_SCOPE: &str = "settings";
+
+/// キーチェーンに保存する設定の項目（JSON Pointer）
+const SECRET_FIELDS: &[&str] = &[
+    "/external_broker/password",
+    "/digest/slack_webhook_url",
+    "/rest_api/token",
+    "/payload_signing/secret",
+    "/payload_encryption/key",
+    "/webhook/url",
+    "/calendar/ics_url",
+];
+
+/// キーチェーンに保存する設定の配列の項目（配列の JSON Pointer と要素の項目名）
+///
+/// 要素の値は配列ごとに1つの JSON の文字列の配列として保存する（要素を削除しても値が残らないように）。
+const SECRET_LISTS: &[(&str, &str)] = &[("/webhook/headers", "value")];
+
+#[derive(Debug, Error)]
+pub enum CredentialError {
+    #[error("keychain is not available: {0}")]
+    Keychain(String),
+}
+
+/// 認証情報の保存先
+pub trait SecretStore {
+    fn get(&self, account: &str) -> Result<Option<String>, CredentialError>;
+    fn set(&self, account: &str, secret: &str) -> Result<(), CredentialError>;
+    fn delete(&self, account: &str) -> Result<(), CredentialError>;
+}
+
+/// OS のキーチェーン（読み込んだ値はプロセス内に保持し、設定を読むたびに問い合わせない）
+#[derive(Default)]
+pub struct Keychain {
+    cache: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl Keychain {
+    fn entry(account: &str) -> Result<keyring::Entry, CredentialError> {
+        keyring::Entry::new(SERVICE, account).map_err(|e| CredentialError::Keychain(e.to_string()))
+    }
+}
+
+impl SecretStore for Keychain {
+    fn get(&self, account: &str) -> Result<Option<String>, CredentialError> {
+        if let Some(cached) = self.cache.lock().unwrap().get(account) {
+            return Ok(cached.clone());
+        }
+        let secret = match Self::entry(account)?.get_password() {
+            Ok(secret) => Some(secret),
+            Err(keyring::Error::NoEntry) => None,
+            Err(e) => return Err(CredentialError::Keychain(e.to_string())),
+        };
+        self.cache.lock().unwrap().insert(account.to_string(), secret.clone());
+        Ok(secret)
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), CredentialError> {
+        if self.cache.lock().unwrap().get(account).is_some_and(|cached| cached.as_deref() == Some(secret)) {
+            return Ok(());
+        }
+        Self::entry(account)?
+            .set_password(secret)
+            .map_err(|e| CredentialError::Keychain(e.to_string()))?;
+        self.cache.lock().unwrap().insert(account.to_string(), Some(secret.to_string()));
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<(), CredentialError> {
+        if self.cache.lock().unwrap().get(account).is_some_and(Option::is_none) {
+            return Ok(());
+        }
+        match Self::entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(CredentialError::Keychain(e.to_string())),
+        }
+        self.cache.lock().unwrap().insert(account.to_string(), None);
+        Ok(())
+    }
+}
+
+/// アプリで共有するキーチェーン
+pub fn keychain() -> &'static Keychain {
+    static KEYCHAIN: OnceLock<Keychain> = OnceLock::new();
+    KEYCHAIN.get_or_init(Keychain::default)
+}
+
+/// プロファイルの認証情報のアカウント名の接頭辞
+fn profile_scope(name: &str) -> String {
+    format!("profile/{}", name)
+}
+
+/// 設定の JSON に平文の認証情報が含まれるか
+pub fn has_plaintext(settings: &Value) -> bool {
+    let is_set = |value: Option<&Value>| value.and_then(Value::as_str).is_some_and(|secret| !secret.is_empty());
+    SECRET_FIELDS.iter().any(|field| is_set(settings.pointer(field)))
+        || SECRET_LISTS.iter().any(|(list, key)| {
+            settings
+                .pointer(list)
+                .and_then(Value::as_array)
+                .is_some_and(|items| items.iter().any(|item| is_set(item.get(key))))
+        })
+}
+
+/// 配列の要素の認証情報の項目
+fn list_secrets<'a>(settings: &'a mut Value, list: &str, key: &str) -> Vec<&'a mut String> {
+    settings
+        .pointer_mut(list)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(|item| match item.get_mut(key) {
+            Some(Value::String(secret)) => Some(secret),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 設定の JSON の認証情報をキーチェーンに保存し、空の値に置き換える（空の項目はキーチェーンからも削除する）
+///
+/// キーチェーンに保存できなかった項目は平文のまま残す。
+pub fn seal(settings: &mut Value, scope: &str, store: &dyn SecretStore) {
+    for field in SECRET_FIELDS {
+        let Some(Value::String(secret)) = settings.pointer_mut(field) else {
+            continue;
+        };
+        let account = format!("{}{}", scope, field);
+        let result = if secret.is_empty() {
+            store.delete(&account)
+        } else {
+            store.set(&account, secret)
+        };
+        match result {
+            Ok(()) => secret.clear(),
+            Err(e) => warn!("Keeping {} in the settings file: {}", account, e),
+        }
+    }
+    for (list, key) in SECRET_LISTS {
+        let account = format!("{}{}", scope, list);
+        let mut secrets = list_secrets(settings, list, key);
+        let result = if secrets.iter().all(|secret| secret.is_empty()) {
+            store.delete(&account)
+        } else {
+            let values: Vec<&str> = secrets.iter().map(|secret| secret.as_str()).collect();
+            store.set(&account, &Value::from(values).to_string())
+        };
+        match result {
+            Ok(()) => secrets.iter_mut().for_each(|secret| secret.clear()),
+            Err(e) => warn!("Keeping {} in the settings file: {}", account, e),
+        }
+    }
+}
+
+/// 設定の JSON の空の認証情報をキーチェーンの値で埋める（平文で残っている項目はそのまま使う）
+pub fn unseal(settings: &mut Value, scope: &str, store: &dyn SecretStore) {
+    for field in SECRET_FIELDS {
+        let Some(Value::String(secret)) = settings.pointer_mut(field) else {
+            continue;
+        };
+        if !secret.is_empty() {
+            continue;
+        }
+        let account = format!("{}{}", scope, field);
+        match store.get(&account) {
+            Ok(Some(stored)) => *secret = stored,
+            Ok(None) => {}
+            Err(e) => warn!("Failed to read {} from the keychain: {}", account, e),
+        }
+    }
+    for (list, key) in SECRET_LISTS {
+        let mut secrets = list_secrets(settings, list, key);
+        if secrets.iter().any(|secret| !secret.is_empty()) {
+            continue;
+        }
+        let account = format!("{}{}", scope, list);
+        let stored = match store.get(&account) {
+            Ok(stored) => stored,
+            Err(e) => {
+                warn!("Failed to read {} from the keychain: {}", account, e);
+                continue;
+            }
+        };
+        let Some(stored) = stored else {
+            continue;
+        };
+        match serde_json::from_str::<Vec<String>>(&stored) {
+            Ok(values) => {
+                for (secret, value) in secrets.iter_mut().zip(values) {
+                    **secret = value;
+                }
+            }
+            Err(e) => warn!("Ignoring invalid {} in the keychain: {}", account, e),
+        }
+    }
+}
+
+/// プロファイルごとの設定（`SettingsProfiles` の JSON の `profiles`）
+fn profiles_mut(profiles: &mut Value) -> impl Iterator<Item = (&String, &mut Value)> {
+    profiles
+        .get_mut("profiles")
+        .and_then(Value::as_object_mut)
+        .into_iter()
+        .flat_map(|profiles| profiles.iter_mut())
+}
+
+/// プロファイルの JSON に平文の認証情報が含まれるか
+pub fn profiles_have_plaintext(profiles: &Value) -> bool {
+    profiles
+        .get("profiles")
+        .and_then(Value::as_object)
+        .is_some_and(|profiles| profiles.values().any(has_plaintext))
+}
+
+/// プロファイルごとに認証情報をキーチェーンに保存し、削除されたプロファイルの認証情報を削除する
+pub fn seal_profiles(profiles: &mut Value, previous: Option<&Value>, store: &dyn SecretStore) {
+    for (name, settings) in profiles_mut(profiles) {
+        seal(settings, &profile_scope(name), store);
+    }
+    let removed = previous
+        .and_then(|previous| previous.get("profiles"))
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|previous| previous.keys())
+        .filter(|name| profiles.get("profiles").and_then(|profiles| profiles.get(name.as_str())).is_none());
+    for name in removed {
+        for field in SECRET_FIELDS.iter().chain(SECRET_LISTS.iter().map(|(list, _)| list)) {
+            let account = format!("{}{}", profile_scope(name), field);
+            if let Err(e) = store.delete(&account) {
+                warn!("Failed to delete {} from the keychain: {}", account, e);
+            }
+        }
+    }
+}
+
+/// プロファイルごとの空の認証情報をキーチェーンの値で埋める
+pub fn unseal_profiles(profiles: &mut Value, store: &dyn SecretStore) {
+    for (name, settings) in profiles_mut(profiles) {
+        unseal(settings, &profile_scope(name), store);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        secrets: Mutex<HashMap<String, String>>,
+        unavailable: bool,
+    }
+
+    impl SecretStore for MemoryStore {
+        fn get(&self, account: &str) -> Result<Option<String>, CredentialError> {
+            Ok(self.secrets.lock().unwrap().get(account).cloned())
+        }
+
+        fn set(&self, account: &str, secret: &str) -> Result<(), CredentialError> {
+            if self.unavailable {
+                return Err(CredentialError::Keychain("no secret service".to_string()));
+            }
+            self.secrets.lock().unwrap().insert(account.to_string(), secret.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, account: &str) -> Result<(), CredentialError> {
+            self.secrets.lock().unwrap().remove(account);
+            Ok(())
+        }
+    }
+
+    fn settings(password: &str, token: &str) -> Value {
+        json!({
+            "sound_enabled": true,
+            "external_broker": {"host": "mqtt.local", "password": password},
+            "rest_api": {"port": 18830, "token": token},
+        })
+    }
+
+    #[test]
+    fn test_seal_and_unseal() {
+        let store = MemoryStore::default();
+        let mut value = settings("hunter2", "");
+        assert!(has_plaintext(&value));
+
+        seal(&mut value, SETTINGS_SCOPE, &store);
+        assert_eq!(value, settings("", ""));
+        assert!(!has_plaintext(&value));
+        assert_eq!(
+            store.get("settings/external_broker/password").unwrap().as_deref(),
+            Some("hunter2")
+        );
+        assert_eq!(store.get("settings/rest_api/token").unwrap(), None);
+
+        unseal(&mut value, SETTINGS_SCOPE, &store);
+        assert_eq!(value, settings("hunter2", ""));
+
+        // 空にした項目はキーチェーンからも削除する
+        let mut cleared = settings("", "");
+        seal(&mut cleared, SETTINGS_SCOPE, &store);
+        assert_eq!(store.get("settings/external_broker/password").unwrap(), None);
+    }
+
+    #[test]
+    fn test_seal_and_unseal_list() {
+        let store = MemoryStore::default();
+        let headers = |first: &str, second: &str| {
+            json!({"webhook": {"url": "", "headers": [
+                {"name": "X-Api-Key", "value": first},
+                {"name": "X-Team", "value": second},
+            ]}})
+        };
+        let mut value = headers("secret", "");
+        assert!(has_plaintext(&value));
+
+        seal(&mut value, SETTINGS_SCOPE, &store);
+        assert_eq!(value, headers("", ""));
+        assert!(!has_plaintext(&value));
+        assert_eq!(
+            store.get("settings/webhook/headers").unwrap().as_deref(),
+            Some(r#"["secret",""]"#)
+        );
+
+        unseal(&mut value, SETTINGS_SCOPE, &store);
+        assert_eq!(value, headers("secret", ""));
+
+        // ヘッダーの値をすべて空にした場合はキーチェーンからも削除する
+        let mut cleared = headers("", "");
+        seal(&mut cleared, SETTINGS_SCOPE, &store);
+        assert_eq!(store.get("settings/webhook/headers").unwrap(), None);
+    }
+
+    #[test]
+    fn test_keeps_plaintext_without_keychain() {
+        let store = MemoryStore {
+            unavailable: true,
+            ..MemoryStore::default()
+        };
+        let mut value = settings("hunter2", "api-token");
+        seal(&mut value, SETTINGS_SCOPE, &store);
+        assert_eq!(value, settings("hunter2", "api-token"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let store = MemoryStore::default();
+        let previous = json!({"profiles": {"home": settings("a", ""), "work/vpn": settings("b", "")}});
+        let mut profiles = json!({"active": "home", "profiles": {"home": settings("a", "t")}});
+        assert!(profiles_have_plaintext(&profiles));
+
+        store.set("profile/work/vpn/external_broker/password", "b").unwrap();
+        seal_profiles(&mut profiles, Some(&previous), &store);
+        assert!(!profiles_have_plaintext(&profiles));
+        assert_eq!(store.get("profile/home/rest_api/token").unwrap().as_deref(), Some("t"));
+        // 削除したプロファイルの認証情報は残さない
+        assert_eq!(store.get("profile/work/vpn/external_broker/password").unwrap(), None);
+
+        unseal_profiles(&mut profiles, &store);
+        assert_eq!(profiles["profiles"]["home"], settings("a", "t"));
+    }
+}
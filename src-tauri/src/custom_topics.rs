@@ -0,0 +1,215 @@
+//! 追加で購読するトピックのモジュール
+//!
+//! CI・cron ジョブなど Claude Code 以外のツールからもこのアプリで通知できるよう、
+//! 設定したトピックのフィルター（`build/#` など）を購読し、表示用のテンプレートで通知・履歴に記録する。
+//! `claude-code/` 以下はフックのトピックと重なるため、追加のトピックには指定できない。
+
+use crate::text;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// フックが使うトピックの最上位の階層
+const RESERVED_ROOT: &str = "claude-code";
+
+/// テンプレートを指定しない場合の表示
+pub const DEFAULT_TEMPLATE: &str = "{payload}";
+
+/// 追加で購読するトピック
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomTopic {
+    /// トピックのフィルター（`+`・`#` のワイルドカードを使える）
+    pub filter: String,
+    /// 通知の本文のテンプレート（`{payload}`・`{topic}`、JSON のオブジェクトの場合は `{キー}`）
+    #[serde(default)]
+    pub template: String,
+}
+
+impl CustomTopic {
+    /// 受信したメッセージの通知の本文（プレースホルダーの値は `max_value_length` 文字まで、超えた分は中央を省略）
+    pub fn render(&self, topic: &str, payload: &str, max_value_length: usize) -> String {
+        let template = match self.template.trim() {
+            "" => DEFAULT_TEMPLATE,
+            template => template,
+        };
+        render(template, topic, payload, max_value_length)
+    }
+}
+
+/// 追加で購読するトピックの設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomTopicSettings {
+    pub topics: Vec<CustomTopic>,
+}
+
+impl CustomTopicSettings {
+    /// 購読するフィルター（不正なもの・重複を除く）
+    pub fn filters(&self) -> Vec<String> {
+        let mut filters: Vec<String> = Vec::new();
+        for topic in &self.topics {
+            let filter = topic.filter.trim();
+            if is_valid_filter(filter) && !filters.iter().any(|f| f == filter) {
+                filters.push(filter.to_string());
+            }
+        }
+        filters
+    }
+
+    /// トピックに一致する最初の設定
+    pub fn find(&self, topic: &str) -> Option<&CustomTopic> {
+        self.topics.iter().find(|custom| {
+            let filter = custom.filter.trim();
+            is_valid_filter(filter) && filter_matches(filter, topic)
+        })
+    }
+}
+
+/// 購読できるフィルターか（ワイルドカードの位置が正しく、フックのトピックと重ならないもの）
+pub fn is_valid_filter(filter: &str) -> bool {
+    if filter.is_empty() {
+        return false;
+    }
+    let levels: Vec<&str> = filter.split('/').collect();
+    let root = levels[0];
+    if root == RESERVED_ROOT || root == "#" || root == "+" || root.starts_with('$') {
+        return false;
+    }
+    levels.iter().enumerate().all(|(index, level)| match *level {
+        "#" => index == levels.len() - 1,
+        "+" => true,
+        level => !level.contains(['#', '+']),
+    })
+}
+
+/// トピックがフィルターに一致するか（MQTT のワイルドカードの規則に従う）
+pub fn filter_matches(filter: &str, topic: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    for level in filter.split('/') {
+        match (level, topic_levels.next()) {
+            ("#", _) => return true,
+            (_, None) => return false,
+            ("+", Some(_)) => {}
+            (level, Some(topic_level)) if level == topic_level => {}
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+/// テンプレートのプレースホルダーを置き換える（値に含まれるプレースホルダーは置き換えない）
+///
+/// 長い値はテンプレートの固定の文字が隠れないように、値ごとに中央を省略して切り詰める。
+fn render(template: &str, topic: &str, payload: &str, max_value_length: usize) -> String {
+    let fields = serde_json::from_str::<Value>(payload).ok();
+    let value = |name: &str| -> Option<String> {
+        match name {
+            "payload" => Some(payload.trim().to_string()),
+            "topic" => Some(topic.to_string()),
+            name => match fields.as_ref()?.get(name)? {
+                Value::String(text) => Some(text.clone()),
+                Value::Null => Some(String::new()),
+                other => Some(other.to_string()),
+            },
+        }
+    };
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let placeholder = after
+            .find('}')
+            .map(|end| &after[..end])
+            .and_then(|name| value(name).map(|value| (name, value)));
+        match placeholder {
+            Some((name, value)) => {
+                rendered.push_str(&text::truncate_middle(&value, max_value_length));
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_matches() {
+        assert!(filter_matches("build/#", "build/app/main"));
+        assert!(filter_matches("build/#", "build"));
+        assert!(filter_matches("cron/+/done", "cron/backup/done"));
+        assert!(!filter_matches("cron/+/done", "cron/backup/daily/done"));
+        assert!(!filter_matches("build/app", "build/app/main"));
+        assert!(!filter_matches("build/app/main", "build/app"));
+    }
+
+    #[test]
+    fn test_is_valid_filter() {
+        assert!(is_valid_filter("build/#"));
+        assert!(is_valid_filter("ci/+/status"));
+        assert!(!is_valid_filter(""));
+        assert!(!is_valid_filter("#"));
+        assert!(!is_valid_filter("claude-code/custom"));
+        assert!(!is_valid_filter("$SYS/broker"));
+        assert!(!is_valid_filter("build/#/more"));
+        assert!(!is_valid_filter("build/a+b"));
+    }
+
+    #[test]
+    fn test_filters_and_find() {
+        let settings = CustomTopicSettings {
+            topics: vec![
+                CustomTopic {
+                    filter: " build/# ".to_string(),
+                    template: "🛠 {payload}".to_string(),
+                },
+                CustomTopic {
+                    filter: "claude-code/#".to_string(),
+                    template: String::new(),
+                },
+                CustomTopic {
+                    filter: "build/#".to_string(),
+                    template: "duplicate".to_string(),
+                },
+            ],
+        };
+        assert_eq!(settings.filters(), vec!["build/#".to_string()]);
+        assert_eq!(settings.find("build/app").unwrap().template, "🛠 {payload}");
+        assert!(settings.find("claude-code/events/stop").is_none());
+        assert!(settings.find("deploy/app").is_none());
+    }
+
+    #[test]
+    fn test_render() {
+        let topic = CustomTopic {
+            filter: "ci/#".to_string(),
+            template: "{job}: {status} ({topic}) {unknown}".to_string(),
+        };
+        assert_eq!(
+            topic.render("ci/app", r#"{"job":"test","status":"{payload}","code":1}"#, 0),
+            "test: {payload} (ci/app) {unknown}"
+        );
+
+        let plain = CustomTopic {
+            filter: "build/#".to_string(),
+            template: "🛠 {payload}".to_string(),
+        };
+        assert_eq!(plain.render("build/app", "done\n", 0), "🛠 done");
+        let default = CustomTopic {
+            filter: "build/#".to_string(),
+            ..CustomTopic::default()
+        };
+        assert_eq!(default.render("build/app", "done", 0), "done");
+
+        // 長い値は中央を省略し、テンプレートの固定の文字は残す
+        assert_eq!(plain.render("build/app", "make release-all-targets", 10), "🛠 make …gets");
+    }
+}
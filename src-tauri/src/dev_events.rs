@@ -0,0 +1,193 @@
+//! 開発用の疑似イベントモジュール
+//!
+//! Claude Code を動かさずにルール・テンプレート・連携を試せるよう、
+//! フックやステータスラインと同じ形式のペイロードを組み立てて handle_mqtt_message に渡す。
+//! 署名・暗号化が有効な場合は mqtt-publish と同じく暗号化してから署名するため、検証の処理も通る。
+
+use crate::client::{topics, MqttMessage};
+use crate::notification_history::NotificationHistoryManager;
+use crate::{encryption, handle_mqtt_message, signing, AppState, NotificationManager};
+use chrono::{Local, SecondsFormat};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+/// 疑似イベントのセッションID（フィールドで上書きしない場合）
+const FAKE_SESSION_ID: &str = "claude-code-notify-test";
+
+/// 疑似イベントの作業ディレクトリ（通知にはプロジェクト名として表示される）
+const FAKE_CWD: &str = "/tmp/claude-code-notify-test";
+
+/// 疑似イベントの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FakeEventKind {
+    Stop,
+    PermissionRequest,
+    Notification,
+    Status,
+}
+
+impl FakeEventKind {
+    /// フックが送るペイロードの既定値
+    fn default_payload(self) -> Value {
+        match self {
+            FakeEventKind::Stop => json!({
+                "event": "stop",
+                "cwd": FAKE_CWD,
+                "session_id": FAKE_SESSION_ID,
+            }),
+            FakeEventKind::PermissionRequest => json!({
+                "event": "permission_request",
+                "cwd": FAKE_CWD,
+                "session_id": FAKE_SESSION_ID,
+                "content": {
+                    "tool_name": "Bash",
+                    "tool_input": { "command": "echo test" },
+                },
+            }),
+            FakeEventKind::Notification => json!({
+                "event": "notification",
+                "cwd": FAKE_CWD,
+                "session_id": FAKE_SESSION_ID,
+                "content": {
+                    "type": "idle_prompt",
+                    "message": "Claude is waiting for your input",
+                },
+            }),
+            FakeEventKind::Status => json!({
+                "session_id": FAKE_SESSION_ID,
+                "cwd": FAKE_CWD,
+                "status": {
+                    "state": "working",
+                    "context_percent": 42.0,
+                    "cost_usd": 0.5,
+                },
+            }),
+        }
+    }
+}
+
+/// `overrides` のフィールドで `base` を上書きする（オブジェクトは再帰的に、null のフィールドは削除する）
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                if value.is_null() {
+                    base.remove(&key);
+                } else {
+                    merge(base.entry(key).or_insert(Value::Null), value);
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// 疑似イベントのメッセージを組み立てる（ペイロードは署名・暗号化していない JSON）
+pub fn build_event(kind: FakeEventKind, fields: Map<String, Value>) -> Result<MqttMessage, String> {
+    let mut payload = kind.default_payload();
+    // フックと同じく送信した時刻を付ける（署名の有効期間・古いイベントの判定に使う）
+    payload["timestamp"] = json!(Local::now().to_rfc3339_opts(SecondsFormat::Secs, false));
+    merge(&mut payload, Value::Object(fields));
+
+    let topic = match kind {
+        FakeEventKind::Stop => topics::EVENTS_STOP.to_string(),
+        FakeEventKind::PermissionRequest => topics::EVENTS_PERMISSION_REQUEST.to_string(),
+        FakeEventKind::Notification => topics::EVENTS_NOTIFICATION.to_string(),
+        FakeEventKind::Status => {
+            let session_id = payload["session_id"].as_str().unwrap_or_default();
+            topics::for_session(topics::STATUS_PREFIX, session_id).ok_or("session_id is required for status events")?
+        }
+    };
+    Ok(MqttMessage {
+        topic,
+        payload: payload.to_string().into_bytes(),
+    })
+}
+
+/// 受信したメッセージと同じ手順で疑似イベントを処理する
+pub fn inject(app: &AppHandle, kind: FakeEventKind, fields: Map<String, Value>) -> Result<(), String> {
+    let mut msg = build_event(kind, fields)?;
+    info!("Injecting fake {:?} event on {}", kind, msg.topic);
+
+    let notification_manager = app.state::<Arc<NotificationManager>>();
+    let history_manager = app.state::<Arc<NotificationHistoryManager>>();
+    let (session_manager, session_name_manager) = {
+        let state = app.state::<Mutex<AppState>>();
+        let state = state.lock().map_err(|e| e.to_string())?;
+        (state.session_manager.clone(), state.session_name_manager.clone())
+    };
+
+    let settings = notification_manager.get_settings();
+    let mut payload = String::from_utf8_lossy(&msg.payload).to_string();
+    if let Some(key) = settings.payload_encryption.active_key() {
+        payload = encryption::encrypt_payload(&payload, key).map_err(|e| e.to_string())?;
+    }
+    if let Some(secret) = settings.payload_signing.active_secret() {
+        payload = signing::sign_payload(&payload, secret);
+    }
+    msg.payload = payload.into_bytes();
+
+    handle_mqtt_message(
+        app,
+        &session_manager,
+        &session_name_manager,
+        &notification_manager,
+        &history_manager,
+        msg,
+    );
+    Ok(())
+}
+
+/// Tauriコマンド: 疑似イベントを送る（`fields` で既定のペイロードのフィールドを上書きする）
+#[tauri::command]
+pub fn send_fake_event(app: AppHandle, kind: FakeEventKind, fields: Option<Map<String, Value>>) -> Result<(), String> {
+    inject(&app, kind, fields.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(msg: &MqttMessage) -> Value {
+        serde_json::from_slice(&msg.payload).unwrap()
+    }
+
+    #[test]
+    fn test_build_event_overrides_fields() {
+        let fields = json!({
+            "cwd": "/home/user/project",
+            "host": "devbox",
+            "content": { "tool_name": "Edit", "tool_input": null },
+        });
+        let msg = build_event(FakeEventKind::PermissionRequest, fields.as_object().unwrap().clone()).unwrap();
+        assert_eq!(msg.topic, topics::EVENTS_PERMISSION_REQUEST);
+
+        let payload = payload(&msg);
+        assert_eq!(payload["cwd"], "/home/user/project");
+        assert_eq!(payload["host"], "devbox");
+        assert_eq!(payload["session_id"], FAKE_SESSION_ID);
+        assert_eq!(payload["content"], json!({ "tool_name": "Edit" }));
+        assert!(payload["timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_status_event_topic() {
+        let fields = json!({ "session_id": "host-123", "status": { "state": "idle" } });
+        let msg = build_event(FakeEventKind::Status, fields.as_object().unwrap().clone()).unwrap();
+        assert_eq!(msg.topic, "claude-code/status/host-123");
+        assert_eq!(payload(&msg)["status"]["cost_usd"], 0.5);
+
+        let fields = json!({ "session_id": null });
+        assert!(build_event(FakeEventKind::Status, fields.as_object().unwrap().clone()).is_err());
+    }
+
+    #[test]
+    fn test_kind_deserialization() {
+        let kind: FakeEventKind = serde_json::from_str("\"permission_request\"").unwrap();
+        assert_eq!(kind, FakeEventKind::PermissionRequest);
+    }
+}
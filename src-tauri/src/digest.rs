@@ -0,0 +1,365 @@
+//! ダイジェスト（まとめ）通知モジュール
+//!
+//! 設定で有効にした場合、1時間ごとまたは1日1回、前回のダイジェスト以降の出来事
+//! （プロジェクトごとの完了したタスク・承認依頼・承認待ち・合計コスト）を1件の通知にまとめる。
+//! おやすみモードで多くのエージェントを夜間に動かす場合などのため、Slack の Incoming Webhook にも送信できる。
+
+use crate::i18n::{self, Strings};
+use crate::notification_history::{NotificationEventType, NotificationHistoryEntry, NotificationHistoryManager};
+use crate::project_aliases::ProjectAliasSettings;
+use crate::toast::{self, NotificationPriority, ToastContent};
+use crate::{AppState, NotificationManager};
+use chrono::{DateTime, Duration as ChronoDuration, Local, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// ダイジェストの時期が来たかを調べる間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// ダイジェストに表示するプロジェクトの最大数
+const MAX_DIGEST_PROJECTS: usize = 5;
+
+/// ダイジェストを送る間隔
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestInterval {
+    /// 毎時0分
+    Hourly,
+    /// 毎日 `daily_hour` 時
+    #[default]
+    Daily,
+}
+
+/// ダイジェストの設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestSettings {
+    pub enabled: bool,
+    pub interval: DigestInterval,
+    /// 毎日のダイジェストを送る時刻（0〜23 時）
+    pub daily_hour: u32,
+    /// トーストで表示する（おやすみモード中は表示しない）
+    pub notify: bool,
+    /// Slack の Incoming Webhook の URL（空の場合は送信しない）
+    pub slack_webhook_url: String,
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: DigestInterval::default(),
+            daily_hour: 9,
+            notify: true,
+            slack_webhook_url: String::new(),
+        }
+    }
+}
+
+impl DigestSettings {
+    /// 送信先の Webhook（未設定の場合は None）
+    pub fn active_webhook(&self) -> Option<&str> {
+        let url = self.slack_webhook_url.trim();
+        (!url.is_empty()).then_some(url)
+    }
+
+    /// `now` 以前で最後にダイジェストを送る時刻
+    fn latest_boundary<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> DateTime<Tz> {
+        let hour_start = now.with_minute(0).and_then(|t| t.with_second(0)).and_then(|t| t.with_nanosecond(0));
+        let hour_start = hour_start.unwrap_or_else(|| now.clone());
+        match self.interval {
+            DigestInterval::Hourly => hour_start,
+            DigestInterval::Daily => {
+                let daily = hour_start.with_hour(self.daily_hour.min(23)).unwrap_or(hour_start);
+                if daily > *now {
+                    daily - ChronoDuration::days(1)
+                } else {
+                    daily
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DigestError {
+    #[error("failed to post digest to webhook: {0}")]
+    Webhook(String),
+}
+
+/// 前回のダイジェスト以降の出来事のまとめ
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Digest {
+    /// プロジェクトごとの完了したタスクの数（多い順）
+    pub completed: Vec<(String, usize)>,
+    pub permission_requests: usize,
+    pub notifications: usize,
+    /// 現在の承認待ちの数
+    pub pending_approvals: u32,
+    /// 実行中のセッションの合計コスト
+    pub total_cost_usd: f64,
+}
+
+impl Digest {
+    /// `since` より後の履歴をまとめる（プロジェクトは別名でまとめる）
+    pub fn collect(
+        entries: &[NotificationHistoryEntry],
+        projects: &ProjectAliasSettings,
+        since: DateTime<Utc>,
+        pending_approvals: u32,
+        total_cost_usd: f64,
+    ) -> Self {
+        let mut completed: BTreeMap<String, usize> = BTreeMap::new();
+        let mut digest = Self {
+            pending_approvals,
+            total_cost_usd,
+            ..Self::default()
+        };
+        for entry in entries.iter().filter(|entry| entry.timestamp > since) {
+            match entry.event_type {
+                NotificationEventType::Stop => *completed.entry(project_name(entry, projects)).or_default() += 1,
+                NotificationEventType::PermissionRequest => digest.permission_requests += 1,
+                NotificationEventType::Notification => digest.notifications += 1,
+            }
+        }
+        digest.completed = completed.into_iter().collect();
+        digest.completed.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        digest
+    }
+
+    /// 知らせる出来事がないか
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty() && self.permission_requests == 0 && self.notifications == 0 && self.pending_approvals == 0
+    }
+
+    /// 通知のタイトルと本文
+    pub fn text(&self, interval: DigestInterval, strings: &Strings) -> (String, String) {
+        let title = match interval {
+            DigestInterval::Hourly => strings.digest_hourly_title,
+            DigestInterval::Daily => strings.digest_daily_title,
+        }
+        .to_string();
+
+        let count = |template: &str, count: usize| i18n::fill(template, &[("count", &count.to_string())]);
+        let total_completed: usize = self.completed.iter().map(|(_, count)| count).sum();
+        let mut lines = vec![count(strings.digest_completed, total_completed)];
+        for (project, completed) in self.completed.iter().take(MAX_DIGEST_PROJECTS) {
+            lines.push(i18n::fill(
+                strings.digest_project,
+                &[("project", project), ("count", &completed.to_string())],
+            ));
+        }
+        if self.completed.len() > MAX_DIGEST_PROJECTS {
+            lines.push(count(strings.digest_more_projects, self.completed.len() - MAX_DIGEST_PROJECTS));
+        }
+        if self.permission_requests > 0 {
+            lines.push(count(strings.digest_permission_requests, self.permission_requests));
+        }
+        if self.notifications > 0 {
+            lines.push(count(strings.digest_notifications, self.notifications));
+        }
+        if self.pending_approvals > 0 {
+            lines.push(count(strings.digest_pending, self.pending_approvals as usize));
+        }
+        lines.push(i18n::fill(strings.digest_cost, &[("cost", &format!("{:.2}", self.total_cost_usd))]));
+        (title, lines.join("\n"))
+    }
+}
+
+/// 履歴のプロジェクト名（別名か作業ディレクトリの最後の要素、ない場合はセッション名）
+fn project_name(entry: &NotificationHistoryEntry, projects: &ProjectAliasSettings) -> String {
+    entry
+        .cwd
+        .as_deref()
+        .map(|cwd| projects.project_name(cwd))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| entry.session_name.clone())
+}
+
+fn post_webhook(url: &str, title: &str, body: &str) -> Result<(), DigestError> {
+    ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&json!({ "text": format!("*{}*\n{}", title, body) }).to_string())
+        .map_err(|e| DigestError::Webhook(e.to_string()))?;
+    Ok(())
+}
+
+/// 前回のダイジェストの時刻（次のダイジェストはこれ以降の出来事をまとめる）
+#[derive(Debug)]
+pub struct DigestState {
+    last_sent: Mutex<DateTime<Utc>>,
+}
+
+impl Default for DigestState {
+    fn default() -> Self {
+        Self {
+            last_sent: Mutex::new(Utc::now()),
+        }
+    }
+}
+
+/// 前回以降のダイジェストを送る（`force` でない場合、出来事がなければ送らない）
+pub fn send(app: &AppHandle, force: bool) -> Result<(), String> {
+    let now = Utc::now();
+    let since = std::mem::replace(&mut *app.state::<DigestState>().last_sent.lock().unwrap(), now);
+
+    let notification_manager = app.state::<Arc<NotificationManager>>();
+    let settings = notification_manager.get_settings();
+    let total_cost_usd = {
+        let state = app.state::<Mutex<AppState>>();
+        let state = state.lock().map_err(|e| e.to_string())?;
+        state.session_manager.get_metrics().total_cost_usd
+    };
+    let entries = app.state::<Arc<NotificationHistoryManager>>().get_entries(None);
+    let digest = Digest::collect(
+        &entries,
+        &settings.projects,
+        since,
+        notification_manager.pending_approvals(),
+        total_cost_usd,
+    );
+    if digest.is_empty() && !force {
+        info!("Digest skipped: nothing happened since {}", since);
+        return Ok(());
+    }
+
+    let strings = notification_manager.strings();
+    let (title, body) = digest.text(settings.digest.interval, strings);
+    info!("Sending digest: {:?}", digest);
+
+    let show_toast = settings.digest.notify
+        && settings.toast_notification_enabled
+        && !notification_manager.is_do_not_disturb();
+    if show_toast {
+        let content = ToastContent {
+            title: title.clone(),
+            body: body.clone(),
+            image: None,
+        };
+        toast::show_toast(app, content, NotificationPriority::Low, strings, &[], None);
+    }
+    if let Some(url) = settings.digest.active_webhook() {
+        post_webhook(url, &title, &body).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 設定した間隔でダイジェストを送る
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+
+        let settings = app.state::<Arc<NotificationManager>>().get_settings().digest;
+        if !settings.enabled {
+            continue;
+        }
+        let boundary = settings.latest_boundary(&Local::now());
+        let last_sent = *app.state::<DigestState>().last_sent.lock().unwrap();
+        if last_sent < boundary {
+            if let Err(e) = send(&app, false) {
+                warn!("Failed to send digest: {}", e);
+            }
+        }
+    });
+}
+
+/// Tauriコマンド: ダイジェストを今すぐ送る
+#[tauri::command]
+pub async fn send_digest_now(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || send(&app, true))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+    use chrono::FixedOffset;
+
+    fn entry(event_type: NotificationEventType, cwd: Option<&str>, timestamp: DateTime<Utc>) -> NotificationHistoryEntry {
+        NotificationHistoryEntry {
+            id: 1,
+            event_type,
+            session_name: "session".to_string(),
+            session_id: "s1".to_string(),
+            cwd: cwd.map(str::to_string),
+            content: None,
+            timestamp,
+            sent_at: None,
+            read: false,
+            request_id: None,
+            decision: None,
+            host: None,
+            image: None,
+            pinned: false,
+            outcome: None,
+            resolved_at: None,
+            transcript_path: None,
+        }
+    }
+
+    #[test]
+    fn test_collect_since_last_digest() {
+        let since = Utc::now();
+        let later = since + ChronoDuration::minutes(5);
+        let entries = vec![
+            entry(NotificationEventType::Stop, Some("/home/user/api"), later),
+            entry(NotificationEventType::Stop, Some("/home/user/web"), later),
+            entry(NotificationEventType::Stop, Some("/home/user/web"), later),
+            entry(NotificationEventType::PermissionRequest, None, later),
+            entry(NotificationEventType::Stop, Some("/home/user/old"), since),
+        ];
+        let projects = ProjectAliasSettings::default();
+        let digest = Digest::collect(&entries, &projects, since, 1, 2.5);
+        assert_eq!(digest.completed, vec![("web".to_string(), 2), ("api".to_string(), 1)]);
+        assert_eq!(digest.permission_requests, 1);
+        assert!(!digest.is_empty());
+
+        assert!(Digest::collect(&entries, &projects, later, 0, 2.5).is_empty());
+    }
+
+    #[test]
+    fn test_text() {
+        let strings = Language::En.strings();
+        let digest = Digest {
+            completed: vec![("web".to_string(), 2)],
+            pending_approvals: 1,
+            total_cost_usd: 2.5,
+            ..Digest::default()
+        };
+        let (title, body) = digest.text(DigestInterval::Daily, strings);
+        assert_eq!(title, strings.digest_daily_title);
+        assert_eq!(body, "Tasks completed: 2\n• web: 2\nPending approvals: 1\nTotal cost: $2.50");
+    }
+
+    #[test]
+    fn test_latest_boundary() {
+        let tz = FixedOffset::east_opt(9 * 3600).unwrap();
+        let now = tz.with_ymd_and_hms(2025, 1, 10, 8, 30, 15).unwrap();
+
+        let hourly = DigestSettings {
+            interval: DigestInterval::Hourly,
+            ..DigestSettings::default()
+        };
+        assert_eq!(hourly.latest_boundary(&now), tz.with_ymd_and_hms(2025, 1, 10, 8, 0, 0).unwrap());
+
+        // 9時前は前日の9時
+        let daily = DigestSettings::default();
+        assert_eq!(daily.latest_boundary(&now), tz.with_ymd_and_hms(2025, 1, 9, 9, 0, 0).unwrap());
+        let after = tz.with_ymd_and_hms(2025, 1, 10, 9, 0, 0).unwrap();
+        assert_eq!(daily.latest_boundary(&after), after);
+    }
+}
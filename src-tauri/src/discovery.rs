@@ -0,0 +1,115 @@
+//! mDNS（DNS-SD）によるブローカーの告知モジュール
+//!
+//! ブローカーを `_claude-notify._tcp` として LAN に告知し、エクスポートしたフックのスクリプトや
+//! 連携アプリが IP アドレスを固定せずに接続先を見つけられるようにする。
+//! DHCP で IP アドレスが変わっても、スクリプトは告知から現在のアドレスを取得する
+//! （見つからない場合はエクスポート時の接続先を使う）。
+//! 設定で有効にした場合のみ告知する（切り替えはアプリの再起動後に反映）。
+
+use crate::templates;
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// 告知するサービスの種類
+pub const SERVICE_TYPE: &str = "_claude-notify._tcp.local.";
+
+/// ホスト名を取得できない場合のインスタンス名
+const FALLBACK_INSTANCE_NAME: &str = "claude-code-notify";
+
+#[derive(Error, Debug)]
+pub enum DiscoveryError {
+    #[error("mDNS error: {0}")]
+    Mdns(#[from] mdns_sd::Error),
+}
+
+/// 告知中のサービス（破棄すると告知を取り下げる）
+pub struct BrokerAdvertisement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl Drop for BrokerAdvertisement {
+    fn drop(&mut self) {
+        if let Err(e) = self.daemon.unregister(&self.fullname) {
+            warn!("Failed to withdraw mDNS advertisement: {}", e);
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// DNS のラベルとして使えるホスト名（英数字とハイフンのみ）
+fn host_label(hostname: &str) -> String {
+    let label: String = hostname
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let label = label.trim_matches('-');
+    if label.is_empty() {
+        FALLBACK_INSTANCE_NAME.to_string()
+    } else {
+        label.to_ascii_lowercase()
+    }
+}
+
+/// TXT レコード（連携アプリが接続前に確認できる情報）
+fn txt_properties(local_port: Option<u16>) -> Vec<(&'static str, String)> {
+    let mut properties = vec![
+        ("version", env!("CARGO_PKG_VERSION").to_string()),
+        ("templates", templates::TEMPLATE_VERSION.to_string()),
+    ];
+    if let Some(port) = local_port {
+        properties.push(("local_port", port.to_string()));
+    }
+    properties
+}
+
+/// ブローカーを LAN に告知する（アドレスはネットワークの変化に合わせて更新される）
+pub fn advertise(port: u16, local_port: Option<u16>) -> Result<BrokerAdvertisement, DiscoveryError> {
+    let label = host_label(&gethostname::gethostname().to_string_lossy());
+    let properties = txt_properties(local_port);
+    let properties: Vec<(&str, &str)> = properties
+        .iter()
+        .map(|(key, value)| (*key, value.as_str()))
+        .collect();
+
+    let daemon = ServiceDaemon::new()?;
+    let service = ServiceInfo::new(
+        SERVICE_TYPE,
+        &label,
+        &format!("{}.local.", label),
+        "",
+        port,
+        &properties[..],
+    )?
+    .enable_addr_auto();
+    let fullname = service.get_fullname().to_string();
+    daemon.register(service)?;
+
+    info!("Advertising MQTT broker via mDNS as {} (port {})", fullname, port);
+    Ok(BrokerAdvertisement { daemon, fullname })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_label() {
+        assert_eq!(host_label("DESKTOP-AB12"), "desktop-ab12");
+        assert_eq!(host_label("my_pc.example.lan"), "my-pc");
+        assert_eq!(host_label("パソコン"), FALLBACK_INSTANCE_NAME);
+        assert_eq!(host_label(""), FALLBACK_INSTANCE_NAME);
+    }
+
+    #[test]
+    fn test_txt_properties() {
+        let properties = txt_properties(Some(1884));
+        assert!(properties.contains(&("templates", templates::TEMPLATE_VERSION.to_string())));
+        assert!(properties.contains(&("local_port", "1884".to_string())));
+        assert!(!txt_properties(None).iter().any(|(key, _)| *key == "local_port"));
+    }
+}
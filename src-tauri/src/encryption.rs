@@ -0,0 +1,173 @@
+//! イベントペイロードの暗号化モジュール
+//!
+//! LAN 上のブローカーを経由してもコマンド内容やプロジェクトのパスが
+//! 読まれないよう、mqtt-publish が AES-256-GCM で暗号化したペイロードを復号する。
+//! 鍵はエクスポートしたスクリプトに埋め込まれ、暗号化されたメッセージは
+//! 次のエンベロープで送られる（署名を併用する場合は署名エンベロープの中身になる）。
+//!
+//! ```json
+//! {"nonce": "<12バイトの base64>", "ciphertext": "<暗号文+タグの base64>"}
+//! ```
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum EncryptionError {
+    #[error("payload is not encrypted")]
+    Unencrypted,
+    #[error("payload is encrypted but no key is configured")]
+    MissingKey,
+    #[error("encryption key must be 32 bytes encoded in base64")]
+    InvalidKey,
+    #[error("encrypted envelope is malformed")]
+    Malformed,
+    #[error("failed to decrypt payload")]
+    DecryptFailed,
+    #[error("failed to encrypt payload")]
+    EncryptFailed,
+}
+
+/// ペイロード暗号化の設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayloadEncryptionSettings {
+    /// 暗号化されていないメッセージを破棄し、暗号化されたメッセージを復号する
+    pub enabled: bool,
+    /// AES-256 の鍵（32バイトを base64 で表記）
+    pub key: String,
+}
+
+impl PayloadEncryptionSettings {
+    /// 復号に使う鍵（無効または未設定の場合は None）
+    pub fn active_key(&self) -> Option<&str> {
+        let key = self.key.trim();
+        (self.enabled && !key.is_empty()).then_some(key)
+    }
+}
+
+/// 暗号化されたメッセージのエンベロープ
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedEnvelope {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// 受信したペイロードを復号し、元の JSON 文字列を取り出す
+///
+/// 鍵が未設定の場合、暗号化されていないペイロードはそのまま返す。
+pub fn decrypt_payload(raw: &str, key: Option<&str>) -> Result<String, EncryptionError> {
+    let envelope = serde_json::from_str::<EncryptedEnvelope>(raw).ok();
+
+    let (key, envelope) = match (key, envelope) {
+        (Some(key), Some(envelope)) => (key, envelope),
+        (Some(_), None) => return Err(EncryptionError::Unencrypted),
+        (None, Some(_)) => return Err(EncryptionError::MissingKey),
+        (None, None) => return Ok(raw.to_string()),
+    };
+
+    let cipher = cipher(key)?;
+    let nonce = BASE64
+        .decode(envelope.nonce.trim())
+        .map_err(|_| EncryptionError::Malformed)?;
+    if nonce.len() != 12 {
+        return Err(EncryptionError::Malformed);
+    }
+    let ciphertext = BASE64
+        .decode(envelope.ciphertext.trim())
+        .map_err(|_| EncryptionError::Malformed)?;
+
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| EncryptionError::DecryptFailed)?;
+    String::from_utf8(plaintext).map_err(|_| EncryptionError::DecryptFailed)
+}
+
+/// 暗号化の鍵を生成する（32バイトの乱数を base64 で返す）
+pub fn generate_key() -> String {
+    BASE64.encode(Aes256Gcm::generate_key(&mut OsRng))
+}
+
+fn cipher(key: &str) -> Result<Aes256Gcm, EncryptionError> {
+    let key = BASE64.decode(key).map_err(|_| EncryptionError::InvalidKey)?;
+    if key.len() != 32 {
+        return Err(EncryptionError::InvalidKey);
+    }
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+/// ペイロードを暗号化し、エンベロープの JSON 文字列を返す（mqtt-publish と同じ形式）
+///
+/// アプリ内で組み立てたイベント（設定画面からのテストなど）を受信時と同じ手順で処理するために使う。
+pub fn encrypt_payload(payload: &str, key: &str) -> Result<String, EncryptionError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(key)?
+        .encrypt(&nonce, payload.as_bytes())
+        .map_err(|_| EncryptionError::EncryptFailed)?;
+    serde_json::to_string(&EncryptedEnvelope {
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+    .map_err(|_| EncryptionError::EncryptFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &str = r#"{"event":"stop","cwd":"/work/secret-project"}"#;
+
+    #[test]
+    fn test_roundtrip() {
+        let key = generate_key();
+        let encrypted = encrypt_payload(PAYLOAD, &key).unwrap();
+        assert!(!encrypted.contains("secret-project"));
+        assert_eq!(decrypt_payload(&encrypted, Some(&key)).unwrap(), PAYLOAD);
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let encrypted = encrypt_payload(PAYLOAD, &generate_key()).unwrap();
+        assert_eq!(
+            decrypt_payload(&encrypted, Some(&generate_key())),
+            Err(EncryptionError::DecryptFailed)
+        );
+    }
+
+    #[test]
+    fn test_plaintext_rejected_when_key_configured() {
+        assert_eq!(
+            decrypt_payload(PAYLOAD, Some(&generate_key())),
+            Err(EncryptionError::Unencrypted)
+        );
+    }
+
+    #[test]
+    fn test_without_key() {
+        assert_eq!(decrypt_payload(PAYLOAD, None).unwrap(), PAYLOAD);
+        let encrypted = encrypt_payload(PAYLOAD, &generate_key()).unwrap();
+        assert_eq!(decrypt_payload(&encrypted, None), Err(EncryptionError::MissingKey));
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        assert_eq!(
+            decrypt_payload(r#"{"nonce":"","ciphertext":""}"#, Some("c2hvcnQ=")),
+            Err(EncryptionError::InvalidKey)
+        );
+    }
+
+    #[test]
+    fn test_active_key() {
+        let settings = PayloadEncryptionSettings {
+            enabled: true,
+            key: " abc ".to_string(),
+        };
+        assert_eq!(settings.active_key(), Some("abc"));
+        assert_eq!(PayloadEncryptionSettings::default().active_key(), None);
+    }
+}
@@ -5,6 +5,7 @@
 use crate::templates;
 use local_ip_address::local_ip;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::{Cursor, Write};
 use thiserror::Error;
 use zip::{write::SimpleFileOptions, ZipWriter};
@@ -19,6 +20,55 @@ pub enum ExportError {
     MissingBinary(String),
 }
 
+/// Scripts generated for Linux / WSL (file name, built-in template)
+pub const LINUX_SCRIPTS: &[(&str, &str)] = &[
+    ("on-stop.sh", templates::ON_STOP_SH),
+    ("on-permission-request.sh", templates::ON_PERMISSION_REQUEST_SH),
+    // Waits for Allow/Deny from the app
+    ("await-permission-response.sh", templates::AWAIT_PERMISSION_RESPONSE_SH),
+    // Runs pause / resume / stop / ping commands from the app (started by statusline.sh)
+    ("control-listener.sh", templates::CONTROL_LISTENER_SH),
+    // Resolves the Docker host when the host is "auto" (devcontainer preset)
+    ("resolve-host.sh", templates::RESOLVE_HOST_SH),
+    ("on-notification.sh", templates::ON_NOTIFICATION_SH),
+    // Optional, for users who want real-time status
+    ("statusline.sh", templates::STATUSLINE_SH),
+    // Automated installer
+    ("install.sh", templates::INSTALL_SH),
+    // Removes the hooks and scripts installed by install.sh
+    ("uninstall.sh", templates::UNINSTALL_SH),
+];
+
+/// Scripts generated for Windows (file name, built-in template)
+pub const WINDOWS_SCRIPTS: &[(&str, &str)] = &[
+    ("on-stop.ps1", templates::ON_STOP_PS1),
+    ("on-permission-request.ps1", templates::ON_PERMISSION_REQUEST_PS1),
+    ("on-notification.ps1", templates::ON_NOTIFICATION_PS1),
+    // Optional, for users who want real-time status
+    ("statusline.ps1", templates::STATUSLINE_PS1),
+    // Automated installer for Windows
+    ("install.ps1", templates::INSTALL_PS1),
+    // Removes the hooks and scripts installed by install.ps1
+    ("uninstall.ps1", templates::UNINSTALL_PS1),
+];
+
+/// fish 用のフックのスクリプト（file name, built-in template）
+pub const FISH_HOOKS: &[(&str, &str)] = &[
+    ("on-stop.fish", templates::ON_STOP_FISH),
+    ("on-permission-request.fish", templates::ON_PERMISSION_REQUEST_FISH),
+    ("on-notification.fish", templates::ON_NOTIFICATION_FISH),
+];
+
+/// Nushell 用のフックのスクリプト（file name, built-in template）
+pub const NUSHELL_HOOKS: &[(&str, &str)] = &[
+    ("on-stop.nu", templates::ON_STOP_NU),
+    ("on-permission-request.nu", templates::ON_PERMISSION_REQUEST_NU),
+    ("on-notification.nu", templates::ON_NOTIFICATION_NU),
+];
+
+/// 接続先を実行時に検出させるホスト（Devcontainer / Docker のプリセット）
+pub const AUTO_HOST: &str = "auto";
+
 /// MQTT client types supported for export
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ClientType {
@@ -33,12 +83,92 @@ pub enum ExportPlatform {
     Windows,
 }
 
+/// Linux / WSL でフックを実行するシェル
+///
+/// await-permission-response.sh・statusline.sh・インストーラは bash のまま使う。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookShell {
+    #[default]
+    Bash,
+    Fish,
+    Nushell,
+}
+
+impl HookShell {
+    /// フックのスクリプトの拡張子
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Bash => "sh",
+            Self::Fish => "fish",
+            Self::Nushell => "nu",
+        }
+    }
+
+    /// bash のフックの代わりに使うスクリプト（bash の場合は空）
+    pub fn hook_scripts(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Bash => &[],
+            Self::Fish => FISH_HOOKS,
+            Self::Nushell => NUSHELL_HOOKS,
+        }
+    }
+}
+
+/// エクスポートのプリセット（Linux / WSL）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportPreset {
+    /// 接続先の IP アドレスをスクリプトに埋め込む
+    #[default]
+    Standard,
+    /// devcontainer / Docker 内の Claude Code 用（スクリプトが Docker のホストを検出する）
+    Devcontainer,
+}
+
+impl ExportPreset {
+    /// スクリプトに埋め込む接続先
+    pub fn host(self, host: String) -> String {
+        match self {
+            Self::Standard => host,
+            Self::Devcontainer => AUTO_HOST.to_string(),
+        }
+    }
+}
+
 /// Export configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportConfig {
     pub host: String,
     pub port: u16,
     pub client_type: ClientType,
+    /// ペイロード署名の共有シークレット（None の場合は署名しない）
+    #[serde(default)]
+    pub signing_secret: Option<String>,
+    /// ペイロード暗号化の鍵（None の場合は暗号化しない）
+    #[serde(default)]
+    pub encryption_key: Option<String>,
+    /// 承認依頼への応答を待つ秒数（None の場合は待たない）
+    #[serde(default)]
+    pub response_timeout_secs: Option<u32>,
+    /// ハートビートを送る間隔（None の場合は送らない）
+    #[serde(default)]
+    pub heartbeat_interval_secs: Option<u32>,
+    /// ステータスラインがアプリからのセッションの操作（一時停止・再開・中断・ping）を待ち受ける
+    #[serde(default)]
+    pub session_control: bool,
+    /// 停止イベントで送る Claude の最後のメッセージの文字数（0 の場合は送らない）
+    #[serde(default)]
+    pub last_message_length: u32,
+    /// 編集したスクリプトのテンプレート（ファイル名ごと、ないものは組み込みのテンプレートを使う）
+    #[serde(default)]
+    pub custom_templates: BTreeMap<String, String>,
+    /// Linux / WSL でフックを実行するシェル
+    #[serde(default)]
+    pub shell: HookShell,
+    /// 接続先を mDNS で検出する（見つからない場合は host を使う）
+    #[serde(default)]
+    pub discovery: bool,
 }
 
 impl Default for ExportConfig {
@@ -47,10 +177,85 @@ impl Default for ExportConfig {
             host: "127.0.0.1".to_string(),
             port: 1883,
             client_type: ClientType::MosquittoPub,
+            signing_secret: None,
+            encryption_key: None,
+            response_timeout_secs: None,
+            heartbeat_interval_secs: None,
+            session_control: false,
+            last_message_length: 0,
+            custom_templates: BTreeMap::new(),
+            shell: HookShell::Bash,
+            discovery: false,
         }
     }
 }
 
+impl ExportConfig {
+    /// スクリプトのテンプレート（編集したものがあればそちらを使う）
+    fn template<'a>(&'a self, file_name: &str, builtin: &'a str) -> &'a str {
+        self.custom_templates
+            .get(file_name)
+            .map_or(builtin, String::as_str)
+    }
+}
+
+impl ExportPlatform {
+    /// プラットフォームで生成するスクリプト
+    pub fn scripts(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::LinuxWsl => LINUX_SCRIPTS,
+            Self::Windows => WINDOWS_SCRIPTS,
+        }
+    }
+
+    /// シェルに合わせて生成するスクリプト（Linux / WSL のフックだけを置き換える）
+    pub fn scripts_for(self, shell: HookShell) -> Vec<(&'static str, &'static str)> {
+        self.scripts()
+            .iter()
+            .map(|&(file_name, template)| {
+                let stem = file_name.trim_end_matches(".sh");
+                shell
+                    .hook_scripts()
+                    .iter()
+                    .copied()
+                    .find(|(name, _)| name.rsplit_once('.').map(|(s, _)| s) == Some(stem))
+                    .unwrap_or((file_name, template))
+            })
+            .collect()
+    }
+}
+
+/// Fill template placeholders with the export configuration
+fn render(template: &str, config: &ExportConfig) -> String {
+    template
+        .replace("__HOST__", &config.host)
+        .replace("__PORT__", &config.port.to_string())
+        .replace("__SECRET__", config.signing_secret.as_deref().unwrap_or(""))
+        .replace("__ENCRYPTION_KEY__", config.encryption_key.as_deref().unwrap_or(""))
+        .replace(
+            "__RESPONSE_TIMEOUT__",
+            &config.response_timeout_secs.unwrap_or(0).to_string(),
+        )
+        .replace(
+            "__HEARTBEAT_INTERVAL__",
+            &config.heartbeat_interval_secs.unwrap_or(0).to_string(),
+        )
+        .replace("__TEMPLATE_VERSION__", &templates::TEMPLATE_VERSION.to_string())
+        .replace("__HOOK_EXT__", config.shell.extension())
+        .replace("__DISCOVERY__", if config.discovery { "mdns" } else { "off" })
+        .replace("__SESSION_CONTROL__", if config.session_control { "on" } else { "off" })
+        .replace("__LAST_MESSAGE_LENGTH__", &config.last_message_length.to_string())
+}
+
+/// Render the platform's scripts (file name, contents)
+pub fn render_scripts(config: &ExportConfig, platform: ExportPlatform) -> Vec<(&'static str, String)> {
+    platform
+        .scripts_for(config.shell)
+        .into_iter()
+        .map(|(file_name, template)| (file_name, render(config.template(file_name, template), config)))
+        .collect()
+}
+
 /// Detect local IP address
 pub fn detect_local_ip() -> Result<String, ExportError> {
     local_ip()
@@ -67,67 +272,23 @@ pub fn generate_export_zip(config: &ExportConfig) -> Result<Vec<u8>, ExportError
         let options = SimpleFileOptions::default()
             .compression_method(zip::CompressionMethod::Deflated);
 
-        // on-stop.sh
-        let on_stop = templates::ON_STOP_SH
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("on-stop.sh", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(on_stop.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-
-        // on-permission-request.sh
-        let on_permission_request = templates::ON_PERMISSION_REQUEST_SH
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("on-permission-request.sh", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(on_permission_request.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-
-        // on-notification.sh
-        let on_notification = templates::ON_NOTIFICATION_SH
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("on-notification.sh", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(on_notification.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-
-        // statusline.sh (optional, for users who want real-time status)
-        let statusline = templates::STATUSLINE_SH
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("statusline.sh", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(statusline.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-
-        // install.sh - Automated installer
-        let installer = templates::INSTALL_SH
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("install.sh", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(installer.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
+        // Hook scripts, statusline.sh and the installer / uninstaller (editable from the export tab)
+        for (file_name, script) in render_scripts(config, ExportPlatform::LinuxWsl) {
+            zip.start_file(file_name, options)
+                .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
+            zip.write_all(script.as_bytes())
+                .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
+        }
 
         // hooks-settings-snippet.json (for manual setup reference)
-        let settings = templates::CLAUDE_SETTINGS_SNIPPET;
+        let settings = render(templates::CLAUDE_SETTINGS_SNIPPET, config);
         zip.start_file("hooks-settings-snippet.json", options)
             .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
         zip.write_all(settings.as_bytes())
             .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
 
         // README.txt
-        let readme = templates::README_TEMPLATE
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
+        let readme = render(templates::README_TEMPLATE, config);
 
         zip.start_file("README.txt", options)
             .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
@@ -177,55 +338,13 @@ pub fn generate_windows_export_zip(
         zip.write_all(mqtt_exe)
             .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
 
-        // on-stop.ps1
-        let on_stop = templates::ON_STOP_PS1
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("on-stop.ps1", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(on_stop.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-
-        // on-permission-request.ps1
-        let on_permission_request = templates::ON_PERMISSION_REQUEST_PS1
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("on-permission-request.ps1", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(on_permission_request.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-
-        // on-notification.ps1
-        let on_notification = templates::ON_NOTIFICATION_PS1
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("on-notification.ps1", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(on_notification.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-
-        // statusline.ps1 (optional, for users who want real-time status)
-        let statusline = templates::STATUSLINE_PS1
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("statusline.ps1", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(statusline.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-
-        // install.ps1 - Automated installer for Windows
-        let installer = templates::INSTALL_PS1
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
-
-        zip.start_file("install.ps1", options)
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
-        zip.write_all(installer.as_bytes())
-            .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
+        // Hook scripts, statusline.ps1 and the installer / uninstaller (editable from the export tab)
+        for (file_name, script) in render_scripts(config, ExportPlatform::Windows) {
+            zip.start_file(file_name, options)
+                .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
+            zip.write_all(script.as_bytes())
+                .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
+        }
 
         // hooks-settings-snippet.json (for manual setup reference)
         let settings = templates::CLAUDE_SETTINGS_SNIPPET_WINDOWS;
@@ -235,9 +354,7 @@ pub fn generate_windows_export_zip(
             .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
 
         // README.txt (Windows version)
-        let readme = templates::README_WINDOWS_TEMPLATE
-            .replace("__HOST__", &config.host)
-            .replace("__PORT__", &config.port.to_string());
+        let readme = render(templates::README_WINDOWS_TEMPLATE, config);
 
         zip.start_file("README.txt", options)
             .map_err(|e| ExportError::ZipCreation(e.to_string()))?;
@@ -261,6 +378,15 @@ mod tests {
             host: "192.168.1.100".to_string(),
             port: 1883,
             client_type: ClientType::MosquittoPub,
+            signing_secret: None,
+            encryption_key: None,
+            response_timeout_secs: None,
+            heartbeat_interval_secs: None,
+            session_control: false,
+            last_message_length: 0,
+            custom_templates: BTreeMap::new(),
+            shell: HookShell::Bash,
+            discovery: false,
         };
 
         let result = generate_export_zip(&config);
@@ -269,4 +395,104 @@ mod tests {
         let zip_data = result.unwrap();
         assert!(!zip_data.is_empty());
     }
+
+    #[test]
+    fn test_render_fills_secret() {
+        let mut config = ExportConfig::default();
+        assert_eq!(render("[__SECRET__]", &config), "[]");
+
+        config.signing_secret = Some("abc".to_string());
+        assert_eq!(render("__HOST__:__PORT__ [__SECRET__]", &config), "127.0.0.1:1883 [abc]");
+
+        config.encryption_key = Some("key".to_string());
+        assert_eq!(render("[__ENCRYPTION_KEY__]", &config), "[key]");
+
+        assert_eq!(render("[__RESPONSE_TIMEOUT__]", &config), "[0]");
+        config.response_timeout_secs = Some(30);
+        assert_eq!(render("[__RESPONSE_TIMEOUT__]", &config), "[30]");
+
+        assert_eq!(render("[__HEARTBEAT_INTERVAL__]", &config), "[0]");
+        config.heartbeat_interval_secs = Some(60);
+        assert_eq!(render("[__HEARTBEAT_INTERVAL__]", &config), "[60]");
+        assert_eq!(render("[__SESSION_CONTROL__]", &config), "[off]");
+        config.session_control = true;
+        assert_eq!(render("[__SESSION_CONTROL__]", &config), "[on]");
+        assert_eq!(render("[__LAST_MESSAGE_LENGTH__]", &config), "[0]");
+        config.last_message_length = 200;
+        assert_eq!(render("[__LAST_MESSAGE_LENGTH__]", &config), "[200]");
+
+        assert_eq!(
+            render("[__TEMPLATE_VERSION__]", &config),
+            format!("[{}]", templates::TEMPLATE_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_custom_template_replaces_builtin() {
+        let mut config = ExportConfig::default();
+        assert_eq!(config.template("on-stop.sh", templates::ON_STOP_SH), templates::ON_STOP_SH);
+
+        config
+            .custom_templates
+            .insert("on-stop.sh".to_string(), "echo __HOST__:__PORT__ | jq .".to_string());
+        let template = config.template("on-stop.sh", templates::ON_STOP_SH);
+        assert_eq!(render(template, &config), "echo 127.0.0.1:1883 | jq .");
+        assert_eq!(config.template("install.sh", templates::INSTALL_SH), templates::INSTALL_SH);
+    }
+
+    #[test]
+    fn test_devcontainer_preset_uses_auto_host() {
+        assert_eq!(ExportPreset::Standard.host("192.168.1.10".to_string()), "192.168.1.10");
+        let config = ExportConfig {
+            host: ExportPreset::Devcontainer.host("192.168.1.10".to_string()),
+            ..ExportConfig::default()
+        };
+        let scripts = render_scripts(&config, ExportPlatform::LinuxWsl);
+        let on_stop = &scripts.iter().find(|(name, _)| *name == "on-stop.sh").unwrap().1;
+        assert!(on_stop.contains(r#"HOST="${CLAUDE_NOTIFY_HOST:-auto}""#));
+        assert!(scripts.iter().any(|(name, _)| *name == "resolve-host.sh"));
+    }
+
+    #[test]
+    fn test_discovery_is_rendered_into_resolve_host() {
+        let resolve_host = |discovery| {
+            let config = ExportConfig { discovery, ..ExportConfig::default() };
+            render_scripts(&config, ExportPlatform::LinuxWsl)
+                .into_iter()
+                .find(|(name, _)| *name == "resolve-host.sh")
+                .unwrap()
+                .1
+        };
+        assert!(resolve_host(true).contains(r#"DISCOVERY="${CLAUDE_NOTIFY_DISCOVERY:-mdns}""#));
+        assert!(resolve_host(false).contains(r#"DISCOVERY="${CLAUDE_NOTIFY_DISCOVERY:-off}""#));
+    }
+
+    #[test]
+    fn test_scripts_for_shell_replaces_hooks() {
+        let names = |platform: ExportPlatform, shell| -> Vec<&str> {
+            platform.scripts_for(shell).into_iter().map(|(name, _)| name).collect()
+        };
+        assert_eq!(names(ExportPlatform::LinuxWsl, HookShell::Bash), LINUX_SCRIPTS.iter().map(|(n, _)| *n).collect::<Vec<_>>());
+        assert_eq!(
+            names(ExportPlatform::LinuxWsl, HookShell::Fish),
+            [
+                "on-stop.fish",
+                "on-permission-request.fish",
+                "await-permission-response.sh",
+                "control-listener.sh",
+                "resolve-host.sh",
+                "on-notification.fish",
+                "statusline.sh",
+                "install.sh",
+                "uninstall.sh",
+            ]
+        );
+        assert!(names(ExportPlatform::LinuxWsl, HookShell::Nushell).contains(&"on-notification.nu"));
+        // Windows は PowerShell のまま
+        assert_eq!(names(ExportPlatform::Windows, HookShell::Fish), names(ExportPlatform::Windows, HookShell::Bash));
+
+        let config = ExportConfig { shell: HookShell::Nushell, ..ExportConfig::default() };
+        assert!(render(templates::CLAUDE_SETTINGS_SNIPPET, &config).contains("/on-stop.nu"));
+        assert!(render(templates::INSTALL_SH, &config).contains(r#"HOOK_EXT="nu""#));
+    }
 }
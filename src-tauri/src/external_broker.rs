@@ -0,0 +1,90 @@
+//! 外部のブローカーへの接続モジュール
+//!
+//! mosquitto などのブローカーを別に運用している場合は、組み込みのブローカーを起動せず、
+//! アプリの MQTT クライアントを設定した外部のブローカー（ホスト・ポート・ログイン情報）に接続する。
+//! フックのスクリプトも同じブローカーに送信するようエクスポートする。
+//! 切り替えはアプリの再起動後に反映する。
+
+use crate::acl;
+use crate::client::BrokerEndpoint;
+use serde::{Deserialize, Serialize};
+
+/// 外部のブローカーの設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExternalBrokerSettings {
+    /// 組み込みのブローカーの代わりに外部のブローカーに接続する
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// ユーザー名（空の場合はログインせずに接続する）
+    pub username: String,
+    pub password: String,
+    /// アプリのクライアントID（同じブローカーに接続する他のアプリと重ならないようにする）
+    pub client_id: String,
+}
+
+impl Default for ExternalBrokerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 1883,
+            username: String::new(),
+            password: String::new(),
+            client_id: acl::INTERNAL_CLIENT_ID.to_string(),
+        }
+    }
+}
+
+impl ExternalBrokerSettings {
+    /// 接続先の外部のブローカー（無効な場合やホストが空の場合は None）
+    pub fn active_endpoint(&self) -> Option<BrokerEndpoint> {
+        let host = self.host.trim();
+        if !self.enabled || host.is_empty() {
+            return None;
+        }
+        let client_id = self.client_id.trim();
+        let username = self.username.trim();
+        Some(BrokerEndpoint {
+            host: host.to_string(),
+            port: self.port,
+            client_id: if client_id.is_empty() { acl::INTERNAL_CLIENT_ID } else { client_id }.to_string(),
+            credentials: (!username.is_empty()).then(|| (username.to_string(), self.password.clone())),
+            external: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_endpoint() {
+        let mut settings = ExternalBrokerSettings {
+            host: " mqtt.example.lan ".to_string(),
+            port: 8883,
+            ..ExternalBrokerSettings::default()
+        };
+        assert_eq!(settings.active_endpoint(), None);
+
+        settings.enabled = true;
+        let endpoint = settings.active_endpoint().unwrap();
+        assert_eq!(endpoint.host, "mqtt.example.lan");
+        assert_eq!(endpoint.port, 8883);
+        assert_eq!(endpoint.client_id, acl::INTERNAL_CLIENT_ID);
+        assert_eq!(endpoint.credentials, None);
+        assert!(endpoint.external);
+
+        settings.username = "notify".to_string();
+        settings.password = "secret".to_string();
+        settings.client_id = "notify-desktop".to_string();
+        let endpoint = settings.active_endpoint().unwrap();
+        assert_eq!(endpoint.credentials, Some(("notify".to_string(), "secret".to_string())));
+        assert_eq!(endpoint.client_id, "notify-desktop");
+
+        settings.host = String::new();
+        assert_eq!(settings.active_endpoint(), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! 全画面表示の検出モジュール
+//!
+//! 全画面のアプリ（ゲーム・動画）やプレゼンテーションの実行中は、トーストと通知音を出さずに
+//! 通知を保留し、全画面表示が終わってからまとめて表示する。
+//! Windows では SHQueryUserNotificationState で判定する（その他のプラットフォームでは常に通知する）。
+
+use crate::notification_history::NotificationEventType;
+use crate::toast::NotificationPriority;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 全画面表示の終了を確認する間隔
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 全画面表示の終了後に1件ずつ表示し直す通知の最大数（超えた場合は件数のみ表示する）
+const MAX_REPLAYED: usize = 3;
+
+/// 全画面表示中の通知の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FullscreenSettings {
+    /// 全画面表示中はトーストと通知音を保留する
+    pub enabled: bool,
+    /// 承認依頼など緊急の通知は全画面表示中でも表示する
+    pub allow_critical: bool,
+}
+
+impl Default for FullscreenSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow_critical: true,
+        }
+    }
+}
+
+impl FullscreenSettings {
+    /// この優先度の通知を全画面表示中に保留するか
+    pub fn defers(&self, priority: NotificationPriority) -> bool {
+        self.enabled && !(self.allow_critical && priority == NotificationPriority::Critical)
+    }
+}
+
+/// 全画面表示中に保留した通知
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeferredNotification {
+    pub title: String,
+    pub body: String,
+    pub event_type: NotificationEventType,
+    pub priority: NotificationPriority,
+}
+
+/// 保留した通知の表示方法
+#[derive(Debug, PartialEq)]
+pub enum Replay {
+    /// 1件ずつ表示する
+    Each(Vec<DeferredNotification>),
+    /// 件数のみ表示する（通知音と優先度は最も優先度の高い通知に合わせる）
+    Summary {
+        count: usize,
+        event_type: NotificationEventType,
+        priority: NotificationPriority,
+    },
+}
+
+impl Replay {
+    /// 保留した通知の表示方法を決める（通知がない場合は None）
+    pub fn plan(notifications: Vec<DeferredNotification>) -> Option<Self> {
+        if notifications.len() <= MAX_REPLAYED {
+            return (!notifications.is_empty()).then_some(Replay::Each(notifications));
+        }
+        let count = notifications.len();
+        let top = notifications.into_iter().max_by_key(|n| n.priority)?;
+        Some(Replay::Summary {
+            count,
+            event_type: top.event_type,
+            priority: top.priority,
+        })
+    }
+}
+
+/// 全画面表示中に保留した通知のキュー
+#[derive(Debug, Default)]
+pub struct DeferredQueue {
+    notifications: Mutex<Vec<DeferredNotification>>,
+    /// 全画面表示の終了を監視するスレッドが動いているか
+    watching: AtomicBool,
+}
+
+impl DeferredQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 通知を保留する（監視スレッドを開始する必要がある場合は true を返す）
+    pub fn push(&self, notification: DeferredNotification) -> bool {
+        if let Ok(mut notifications) = self.notifications.lock() {
+            notifications.push(notification);
+        }
+        !self.watching.swap(true, Ordering::SeqCst)
+    }
+
+    /// 保留した通知をすべて取り出し、監視を終了する
+    pub fn take(&self) -> Vec<DeferredNotification> {
+        self.watching.store(false, Ordering::SeqCst);
+        self.notifications
+            .lock()
+            .map(|mut notifications| std::mem::take(&mut *notifications))
+            .unwrap_or_default()
+    }
+}
+
+/// 全画面のアプリやプレゼンテーションが実行中か
+#[cfg(windows)]
+pub fn is_user_busy() -> bool {
+    use windows::Win32::UI::Shell::{
+        SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+    };
+
+    match unsafe { SHQueryUserNotificationState() } {
+        Ok(state) => [QUNS_BUSY, QUNS_RUNNING_D3D_FULL_SCREEN, QUNS_PRESENTATION_MODE].contains(&state),
+        Err(e) => {
+            tracing::warn!("Failed to query user notification state: {}", e);
+            false
+        }
+    }
+}
+
+/// 全画面のアプリやプレゼンテーションが実行中か（Windows 以外では検出しない）
+#[cfg(not(windows))]
+pub fn is_user_busy() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deferred(event_type: NotificationEventType) -> DeferredNotification {
+        DeferredNotification {
+            title: "title".to_string(),
+            body: "body".to_string(),
+            priority: NotificationPriority::for_event(&event_type),
+            event_type,
+        }
+    }
+
+    #[test]
+    fn test_defers_by_priority() {
+        let settings = FullscreenSettings::default();
+        assert!(settings.defers(NotificationPriority::High));
+        assert!(!settings.defers(NotificationPriority::Critical));
+
+        let strict = FullscreenSettings {
+            enabled: true,
+            allow_critical: false,
+        };
+        assert!(strict.defers(NotificationPriority::Critical));
+
+        let disabled = FullscreenSettings {
+            enabled: false,
+            allow_critical: false,
+        };
+        assert!(!disabled.defers(NotificationPriority::Low));
+    }
+
+    #[test]
+    fn test_queue_starts_watcher_once() {
+        let queue = DeferredQueue::new();
+        assert!(queue.push(deferred(NotificationEventType::Stop)));
+        assert!(!queue.push(deferred(NotificationEventType::Notification)));
+        assert_eq!(queue.take().len(), 2);
+
+        // 取り出した後に保留した通知は、新しい監視スレッドで表示する
+        assert!(queue.push(deferred(NotificationEventType::Stop)));
+    }
+
+    #[test]
+    fn test_replay_plan() {
+        assert_eq!(Replay::plan(Vec::new()), None);
+
+        let few = vec![deferred(NotificationEventType::Stop)];
+        assert_eq!(Replay::plan(few.clone()), Some(Replay::Each(few)));
+
+        let mut many = vec![deferred(NotificationEventType::Stop); MAX_REPLAYED];
+        many.push(deferred(NotificationEventType::Notification));
+        assert_eq!(
+            Replay::plan(many),
+            Some(Replay::Summary {
+                count: MAX_REPLAYED + 1,
+                event_type: NotificationEventType::Notification,
+                priority: NotificationPriority::High,
+            })
+        );
+    }
+}
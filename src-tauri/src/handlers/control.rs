@@ -0,0 +1,26 @@
+//! セッションの操作の結果（claude-code/control-reply/<session_id>）のハンドラー
+//!
+//! フック側の control-listener.sh が返した結果を、診断タブのセッションの詳細に表示する。
+
+use super::{EventHandler, HandlerContext};
+use crate::session_control::ControlReply;
+use tauri::Emitter;
+use tracing::{info, warn};
+
+pub struct ControlReplyHandler;
+
+impl EventHandler for ControlReplyHandler {
+    fn name(&self) -> &'static str {
+        "control-reply"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, topic: &str, payload_str: &str) {
+        match serde_json::from_str::<ControlReply>(payload_str) {
+            Ok(reply) => {
+                info!("Control reply on {}: {:?} ok={}", topic, reply.command, reply.ok);
+                let _ = ctx.app.emit("control-reply", &reply);
+            }
+            Err(e) => warn!("Failed to parse control reply: {}", e),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! 追加で購読したトピック（CI・cron ジョブなど）のハンドラー
+//!
+//! 設定したテンプレートで本文を作り、フックの通知と同じく履歴に記録してトーストを表示する。
+
+use super::{EventHandler, HandlerContext};
+use crate::notification_history::{NewHistoryEntry, NotificationEventType};
+use crate::show_simple_notification;
+use crate::timestamps;
+use tauri::Emitter;
+use tracing::{info, warn};
+
+pub struct CustomTopicHandler;
+
+impl EventHandler for CustomTopicHandler {
+    fn name(&self) -> &'static str {
+        "custom-topic"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, topic: &str, payload: &str) {
+        let HandlerContext {
+            app,
+            notification_manager,
+            history_manager,
+            settings,
+            origin,
+            ..
+        } = ctx;
+        let Some(custom) = settings.custom_topics.find(topic) else {
+            info!("Message: {}", payload);
+            return;
+        };
+        info!("Custom topic message on {} (filter {})", topic, custom.filter);
+        let sent_at = timestamps::payload_timestamp(payload);
+        // 値を切り詰める前にマスクする（途中で切れた機密情報が残らないように）
+        let payload = settings.redaction.redact(payload);
+        let body = custom.render(topic, &payload, settings.toast_body_max_length);
+
+        if let Err(e) = history_manager.add_entry(app, NewHistoryEntry {
+            event_type: NotificationEventType::Notification,
+            session_name: topic.to_string(),
+            session_id: String::new(),
+            cwd: None,
+            content: Some(body.clone()),
+            request_id: None,
+            host: None,
+            image: None,
+            transcript_path: None,
+            sent_at,
+        }) {
+            warn!("Failed to add history entry: {}", e);
+        } else {
+            let _ = app.emit("notification-added", ());
+        }
+
+        show_simple_notification(app, notification_manager, topic, &body, NotificationEventType::Notification, origin);
+    }
+}
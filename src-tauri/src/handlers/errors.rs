@@ -0,0 +1,43 @@
+//! フックのエラー（claude-code/error）のハンドラー
+//!
+//! 診断タブの「問題」に記録し、ミュートしたホスト以外は通知する。
+
+use super::{EventHandler, HandlerContext};
+use crate::notification_history::NotificationEventType;
+use crate::{problems, show_simple_notification};
+use tauri::{Emitter, Manager};
+use tracing::{info, warn};
+
+pub struct ErrorHandler;
+
+impl EventHandler for ErrorHandler {
+    fn name(&self) -> &'static str {
+        "error"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, _topic: &str, payload_str: &str) {
+        let HandlerContext {
+            app,
+            notification_manager,
+            settings,
+            origin,
+            ..
+        } = ctx;
+        warn!("Error notification: {}", payload_str);
+        let payload = problems::ErrorPayload::parse(payload_str);
+        let strings = notification_manager.strings();
+        let (title, body) = payload.notification_text(strings);
+        let hint = payload.hint(strings);
+        let muted = settings.host_rules.is_muted(payload.host.as_deref());
+
+        // 診断タブの「問題」に記録する
+        app.state::<problems::ProblemLog>().add(payload, hint);
+        let _ = app.emit("problem-added", ());
+
+        if muted {
+            info!("Error notification from muted host suppressed");
+        } else {
+            show_simple_notification(app, notification_manager, &title, &body, NotificationEventType::Notification, origin);
+        }
+    }
+}
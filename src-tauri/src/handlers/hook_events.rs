@@ -0,0 +1,367 @@
+//! フックのイベント（停止・承認依頼・通知）のハンドラー
+//!
+//! 履歴に記録し、ミュートしたホスト以外は Webhook への送信（勤務時間を設定した場合は勤務時間外のみ）とトーストの表示を行う。
+
+use super::{EventHandler, HandlerContext};
+use crate::notification_history::{NewHistoryEntry, NotificationEventType, PermissionOutcome};
+use crate::{
+    attachment, content_tool_input, hook_version, host_rules, is_ask_user_question, question_prompt,
+    resolve_permission_requests, resolve_session_name, show_notification_event, show_permission_request_notification,
+    show_simple_notification, show_stop_notification, update_tray_status, validation, webhook, NotificationContent,
+    NotificationEventPayload, NotificationOrigin, PermissionRequestContent, PermissionRequestPayload, StopEventPayload,
+};
+use std::time::Instant;
+use tauri::{Emitter, Manager};
+use tracing::{info, warn};
+
+/// 承認依頼の内容（ツール名、なければフックが解析できなかった内容）
+fn permission_content(content: &PermissionRequestContent) -> Option<String> {
+    content.tool_name.clone().or_else(|| content.raw.clone())
+}
+
+/// 通知の内容（メッセージ・タイトル・フックが解析できなかった内容の順）
+fn notification_content(content: &NotificationContent) -> Option<String> {
+    content
+        .message
+        .clone()
+        .or_else(|| content.title.clone())
+        .or_else(|| content.raw.clone())
+}
+
+/// 停止イベント
+pub struct StopHandler;
+
+impl EventHandler for StopHandler {
+    fn name(&self) -> &'static str {
+        "stop"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, topic: &str, payload_str: &str) {
+        let HandlerContext {
+            app,
+            session_manager,
+            session_name_manager,
+            notification_manager,
+            history_manager,
+            settings,
+            origin,
+        } = ctx;
+        match validation::parse::<StopEventPayload>(payload_str, validation::STOP_EVENT) {
+            Ok(payload) => {
+                info!("Stop event received for: {}", payload.cwd);
+                hook_version::check(app, notification_manager, payload.host.as_deref(), payload.template_version);
+
+                // 停止したセッションは承認待ちではない
+                if notification_manager.clear_pending_approvals(app, payload.session_id.as_deref().unwrap_or_default()) {
+                    update_tray_status(app, session_manager, notification_manager);
+                }
+                if let Some(session_id) = payload.session_id.as_deref() {
+                    resolve_permission_requests(app, history_manager, session_id, PermissionOutcome::Stopped);
+                }
+
+                let session_name = resolve_session_name(
+                    session_name_manager,
+                    payload.session_id.as_deref(),
+                    &payload.cwd,
+                    payload.host.as_deref(),
+                )
+                .unwrap_or_else(|| "Claude Code".to_string());
+                let image = attachment::store_from_payload(app, payload.image.as_deref());
+
+                // ステータスラインの値から今回の実行のコスト・時間・変更行数を求める（次の実行はここから数える）
+                let run = payload
+                    .session_id
+                    .as_deref()
+                    .and_then(|session_id| session_manager.finish_run(session_id, Instant::now()));
+
+                // 履歴に追加
+                if let Err(e) = history_manager.add_entry(app, NewHistoryEntry {
+                    event_type: NotificationEventType::Stop,
+                    session_name: session_name.clone(),
+                    session_id: payload.session_id.clone().unwrap_or_default(),
+                    cwd: Some(payload.cwd.clone()),
+                    // Claude の最後のメッセージ（フックが送った場合のみ）
+                    content: payload.last_message(),
+                    request_id: None,
+                    host: host_rules::normalize_host(payload.host.as_deref()),
+                    image: image.clone(),
+                    transcript_path: payload.transcript_path(),
+                    sent_at: payload.timestamp,
+                }) {
+                    warn!("Failed to add history entry: {}", e);
+                } else {
+                    // フロントエンドに通知
+                    let _ = app.emit("notification-added", ());
+                }
+
+                if settings.host_rules.is_muted(payload.host.as_deref()) {
+                    info!("Stop notification from muted host suppressed");
+                } else {
+                    let project = session_name_manager.project_name(&payload.cwd);
+                    let webhook_event = webhook::WebhookEvent {
+                        event_type: NotificationEventType::Stop,
+                        project: project.clone(),
+                        session: session_name,
+                        host: payload.host.clone(),
+                        message: notification_manager.strings().task_complete_body.to_string(),
+                    };
+                    let origin = NotificationOrigin {
+                        webhook: Some(&webhook_event),
+                        spoken_project: Some(&project),
+                        ..origin
+                    };
+                    let image = image.and_then(|name| attachment::path(app, &name).ok());
+                    show_stop_notification(app, session_name_manager, notification_manager, &payload, run.as_ref(), image.as_deref(), origin);
+                }
+            }
+            Err(e) => validation::report(app, notification_manager, topic, payload_str, &e),
+        }
+    }
+}
+
+/// 承認依頼イベント
+pub struct PermissionRequestHandler;
+
+impl EventHandler for PermissionRequestHandler {
+    fn name(&self) -> &'static str {
+        "permission-request"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, topic: &str, payload_str: &str) {
+        let HandlerContext {
+            app,
+            session_manager,
+            session_name_manager,
+            notification_manager,
+            history_manager,
+            settings,
+            origin,
+        } = ctx;
+        match validation::parse::<PermissionRequestPayload>(payload_str, validation::PERMISSION_REQUEST_EVENT) {
+            Ok(payload) => {
+                info!("Permission request received for: {}", payload.cwd);
+                hook_version::check(app, notification_manager, payload.host.as_deref(), payload.template_version);
+                let session_name = resolve_session_name(
+                    session_name_manager,
+                    payload.session_id.as_deref(),
+                    &payload.cwd,
+                    payload.host.as_deref(),
+                )
+                .unwrap_or_else(|| "Claude Code".to_string());
+
+                // ツール名を取得
+                let content = permission_content(&payload.content);
+                let webhook_event = webhook::WebhookEvent {
+                    event_type: NotificationEventType::PermissionRequest,
+                    project: session_name_manager.project_name(&payload.cwd),
+                    session: session_name.clone(),
+                    host: payload.host.clone(),
+                    message: content.clone()
+                        .unwrap_or_else(|| notification_manager.strings().permission_request_title.to_string()),
+                };
+
+                // 質問は選択肢をボタンで表示できる場合のみアプリから回答する
+                let is_question = is_ask_user_question(&payload.content);
+                let questions = if is_question {
+                    content_tool_input(&payload.content)
+                        .map(|input| question_prompt::parse_questions(&input))
+                        .unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+
+                // フックが応答を待っている場合は依頼IDを記録し、アプリから許可・拒否・回答できるようにする
+                let request_id = payload.request_id.clone()
+                    .filter(|id| !id.is_empty() && (!is_question || !questions.is_empty()));
+                let image = attachment::store_from_payload(app, payload.image.as_deref());
+
+                // 履歴に追加
+                let pending_request = match history_manager.add_entry(app, NewHistoryEntry {
+                    event_type: NotificationEventType::PermissionRequest,
+                    session_name: session_name.clone(),
+                    session_id: payload.session_id.clone().unwrap_or_default(),
+                    cwd: Some(payload.cwd.clone()),
+                    content,
+                    request_id: request_id.clone(),
+                    host: host_rules::normalize_host(payload.host.as_deref()),
+                    image: image.clone(),
+                    transcript_path: payload.transcript_path(),
+                    sent_at: payload.timestamp,
+                }) {
+                    Ok(id) => {
+                        if is_question && request_id.is_some() {
+                            app.state::<question_prompt::QuestionPrompts>().insert(id, questions);
+                        }
+                        // フロントエンドに通知
+                        let _ = app.emit("notification-added", ());
+                        request_id.map(|_| id)
+                    }
+                    Err(e) => {
+                        warn!("Failed to add history entry: {}", e);
+                        None
+                    }
+                };
+
+                if settings.host_rules.is_muted(payload.host.as_deref()) {
+                    // ミュートしたホストの依頼は履歴からのみ応答できる
+                    info!("Permission request from muted host suppressed");
+                } else {
+                    // 停止かステータスの変化が届くまで承認待ちとして数える
+                    notification_manager.add_pending_approval(app, payload.session_id.as_deref().unwrap_or_default());
+                    update_tray_status(app, session_manager, notification_manager);
+
+                    let origin = NotificationOrigin {
+                        webhook: Some(&webhook_event),
+                        ..origin
+                    };
+                    let image = image.and_then(|name| attachment::path(app, &name).ok());
+                    show_permission_request_notification(app, session_name_manager, notification_manager, &payload, pending_request, image.as_deref(), origin);
+                }
+            }
+            Err(e) => validation::report(app, notification_manager, topic, payload_str, &e),
+        }
+    }
+}
+
+/// 通知イベント（質問のダイアログなど）
+pub struct NotificationHandler;
+
+impl EventHandler for NotificationHandler {
+    fn name(&self) -> &'static str {
+        "notification"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, topic: &str, payload_str: &str) {
+        let HandlerContext {
+            app,
+            session_name_manager,
+            notification_manager,
+            history_manager,
+            settings,
+            origin,
+            ..
+        } = ctx;
+        match validation::parse::<NotificationEventPayload>(payload_str, validation::NOTIFICATION_EVENT) {
+            Ok(payload) => {
+                info!("Notification event received for: {}", payload.cwd);
+                hook_version::check(app, notification_manager, payload.host.as_deref(), payload.template_version);
+                let session_name = resolve_session_name(
+                    session_name_manager,
+                    payload.session_id.as_deref(),
+                    &payload.cwd,
+                    payload.host.as_deref(),
+                )
+                .unwrap_or_else(|| "Claude Code".to_string());
+
+                // メッセージを取得
+                let content = notification_content(&payload.content);
+                let webhook_event = webhook::WebhookEvent {
+                    event_type: NotificationEventType::Notification,
+                    project: session_name_manager.project_name(&payload.cwd),
+                    session: session_name.clone(),
+                    host: payload.host.clone(),
+                    message: content.clone()
+                        .unwrap_or_else(|| notification_manager.strings().notification_title.to_string()),
+                };
+                let image = attachment::store_from_payload(app, payload.image.as_deref());
+
+                // 履歴に追加
+                if let Err(e) = history_manager.add_entry(app, NewHistoryEntry {
+                    event_type: NotificationEventType::Notification,
+                    session_name: session_name.clone(),
+                    session_id: payload.session_id.clone().unwrap_or_default(),
+                    cwd: Some(payload.cwd.clone()),
+                    content,
+                    request_id: None,
+                    host: host_rules::normalize_host(payload.host.as_deref()),
+                    image: image.clone(),
+                    transcript_path: payload.transcript_path(),
+                    sent_at: payload.timestamp,
+                }) {
+                    warn!("Failed to add history entry: {}", e);
+                } else {
+                    // フロントエンドに通知
+                    let _ = app.emit("notification-added", ());
+                }
+
+                if settings.host_rules.is_muted(payload.host.as_deref()) {
+                    info!("Notification event from muted host suppressed");
+                } else {
+                    let origin = NotificationOrigin {
+                        webhook: Some(&webhook_event),
+                        ..origin
+                    };
+                    let image = image.and_then(|name| attachment::path(app, &name).ok());
+                    show_notification_event(app, session_name_manager, notification_manager, &payload, image.as_deref(), origin);
+                }
+            }
+            Err(e) => validation::report(app, notification_manager, topic, payload_str, &e),
+        }
+    }
+}
+
+/// 旧形式のタスク完了（claude-code/task/complete）
+pub struct TaskCompleteHandler;
+
+impl EventHandler for TaskCompleteHandler {
+    fn name(&self) -> &'static str {
+        "task-complete"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, _topic: &str, payload: &str) {
+        info!("Task completed: {}", payload);
+        let notification_manager = ctx.notification_manager;
+        show_simple_notification(
+            ctx.app,
+            notification_manager,
+            notification_manager.strings().task_complete_title,
+            payload,
+            NotificationEventType::Stop,
+            ctx.origin,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_content() {
+        let content = PermissionRequestContent {
+            tool_name: Some("Bash".to_string()),
+            tool_input: None,
+            transcript_path: None,
+            raw: Some("raw".to_string()),
+        };
+        assert_eq!(permission_content(&content).as_deref(), Some("Bash"));
+
+        let raw_only = PermissionRequestContent {
+            tool_name: None,
+            ..content
+        };
+        assert_eq!(permission_content(&raw_only).as_deref(), Some("raw"));
+    }
+
+    #[test]
+    fn test_stop_last_message_is_one_line() {
+        let payload: StopEventPayload = serde_json::from_str(
+            r#"{"event":"stop","cwd":"/work","last_message":"テストを追加しました。\n\n- 12 件すべて成功  "}"#,
+        )
+        .unwrap();
+        assert_eq!(payload.last_message().as_deref(), Some("テストを追加しました。 - 12 件すべて成功"));
+
+        let empty: StopEventPayload = serde_json::from_str(r#"{"event":"stop","cwd":"/work","last_message":" \n"}"#).unwrap();
+        assert_eq!(empty.last_message(), None);
+    }
+
+    #[test]
+    fn test_notification_content_prefers_message() {
+        let content: NotificationContent =
+            serde_json::from_str(r#"{"type":"idle","title":"Claude Code","message":"Waiting for input"}"#).unwrap();
+        assert_eq!(notification_content(&content).as_deref(), Some("Waiting for input"));
+
+        let title_only: NotificationContent = serde_json::from_str(r#"{"title":"Claude Code"}"#).unwrap();
+        assert_eq!(notification_content(&title_only).as_deref(), Some("Claude Code"));
+    }
+}
@@ -0,0 +1,165 @@
+//! MQTT メッセージのハンドラーモジュール
+//!
+//! トピックのパターンごとに [`EventHandler`] を登録し、受信したメッセージを最初に一致したハンドラーに渡す。
+//! 新しいトピック（連携など）は、ハンドラーを実装したモジュールを追加して
+//! [`HandlerRegistry::with_default_handlers`] に登録する。
+//! どのパターンにも一致しないトピックは、設定で追加したトピックのハンドラーが受け持つ。
+
+mod control;
+mod custom_topics;
+mod errors;
+mod hook_events;
+mod status;
+
+use crate::client::topics;
+use crate::notification_history::NotificationHistoryManager;
+use crate::settings::NotificationSettings;
+use crate::state::{SessionManager, SessionNameManager};
+use crate::{NotificationManager, NotificationOrigin};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// ハンドラーに渡す状態
+#[derive(Clone, Copy)]
+pub struct HandlerContext<'a> {
+    pub app: &'a tauri::AppHandle,
+    pub session_manager: &'a Arc<SessionManager>,
+    pub session_name_manager: &'a Arc<SessionNameManager>,
+    pub notification_manager: &'a Arc<NotificationManager>,
+    pub history_manager: &'a Arc<NotificationHistoryManager>,
+    /// メッセージを受信した時点の設定
+    pub settings: &'a NotificationSettings,
+    /// メッセージから決まる通知の扱い（起動直後に届いた古いイベントか・設定した時間より古いイベントか）
+    pub origin: NotificationOrigin<'a>,
+}
+
+/// ハンドラーが受け持つトピック
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopicPattern {
+    /// トピックが一致する
+    Exact(&'static str),
+    /// トピックが前方一致する（セッション・ホストごとのトピック）
+    Prefix(&'static str),
+    /// すべてのトピック（最後に登録する）
+    Any,
+}
+
+impl TopicPattern {
+    pub fn matches(&self, topic: &str) -> bool {
+        match self {
+            Self::Exact(pattern) => topic == *pattern,
+            Self::Prefix(prefix) => topic.starts_with(prefix),
+            Self::Any => true,
+        }
+    }
+}
+
+/// トピックのメッセージを処理するハンドラー
+pub trait EventHandler: Send + Sync {
+    /// ログに表示する名前
+    fn name(&self) -> &'static str;
+
+    /// 署名の検証・復号を済ませたペイロードを処理する
+    fn handle(&self, ctx: HandlerContext<'_>, topic: &str, payload: &str);
+}
+
+/// トピックのパターンとハンドラーの対応
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: Vec<(TopicPattern, Box<dyn EventHandler>)>,
+}
+
+impl HandlerRegistry {
+    /// アプリが受信するトピックのハンドラーを登録したレジストリ
+    pub fn with_default_handlers() -> Self {
+        let mut registry = Self::default();
+        registry.register(TopicPattern::Exact(topics::EVENTS_STOP), hook_events::StopHandler);
+        registry.register(
+            TopicPattern::Exact(topics::EVENTS_PERMISSION_REQUEST),
+            hook_events::PermissionRequestHandler,
+        );
+        registry.register(TopicPattern::Exact(topics::EVENTS_NOTIFICATION), hook_events::NotificationHandler);
+        registry.register(TopicPattern::Exact(topics::TASK_COMPLETE), hook_events::TaskCompleteHandler);
+        registry.register(TopicPattern::Exact(topics::ERROR), errors::ErrorHandler);
+        registry.register(TopicPattern::Prefix(topics::STATUS_PREFIX), status::StatusHandler);
+        registry.register(TopicPattern::Prefix(topics::HEARTBEAT_PREFIX), status::HeartbeatHandler);
+        registry.register(TopicPattern::Exact(topics::STATUS), status::LegacyStatusHandler);
+        registry.register(TopicPattern::Prefix(topics::CONTROL_REPLY_PREFIX), control::ControlReplyHandler);
+        registry.register(TopicPattern::Any, custom_topics::CustomTopicHandler);
+        registry
+    }
+
+    /// ハンドラーを登録する（複数のパターンが一致する場合は先に登録したものを使う）
+    pub fn register(&mut self, pattern: TopicPattern, handler: impl EventHandler + 'static) {
+        self.handlers.push((pattern, Box::new(handler)));
+    }
+
+    /// トピックを処理するハンドラー
+    pub fn handler_for(&self, topic: &str) -> Option<&dyn EventHandler> {
+        self.handlers
+            .iter()
+            .find(|(pattern, _)| pattern.matches(topic))
+            .map(|(_, handler)| handler.as_ref())
+    }
+
+    /// 一致するハンドラーにメッセージを渡す（一致するものがない場合はログに記録するだけ）
+    pub fn dispatch(&self, ctx: HandlerContext<'_>, topic: &str, payload: &str) {
+        match self.handler_for(topic) {
+            Some(handler) => {
+                debug!("Dispatching {} to {} handler", topic, handler.name());
+                handler.handle(ctx, topic, payload);
+            }
+            None => info!("Message: {}", payload),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NamedHandler(&'static str);
+
+    impl EventHandler for NamedHandler {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn handle(&self, _ctx: HandlerContext<'_>, _topic: &str, _payload: &str) {}
+    }
+
+    fn handler_name(registry: &HandlerRegistry, topic: &str) -> Option<&'static str> {
+        registry.handler_for(topic).map(|handler| handler.name())
+    }
+
+    #[test]
+    fn test_topic_pattern() {
+        assert!(TopicPattern::Exact("claude-code/status").matches("claude-code/status"));
+        assert!(!TopicPattern::Exact("claude-code/status").matches("claude-code/status/s1"));
+        assert!(TopicPattern::Prefix("claude-code/status/").matches("claude-code/status/s1"));
+        assert!(!TopicPattern::Prefix("claude-code/status/").matches("claude-code/status"));
+        assert!(TopicPattern::Any.matches("build/app"));
+    }
+
+    #[test]
+    fn test_default_handlers() {
+        let registry = HandlerRegistry::with_default_handlers();
+        assert_eq!(handler_name(&registry, topics::EVENTS_STOP), Some("stop"));
+        assert_eq!(handler_name(&registry, topics::EVENTS_PERMISSION_REQUEST), Some("permission-request"));
+        assert_eq!(handler_name(&registry, topics::EVENTS_NOTIFICATION), Some("notification"));
+        assert_eq!(handler_name(&registry, "claude-code/status/host-123"), Some("status"));
+        assert_eq!(handler_name(&registry, "claude-code/heartbeat/wsl"), Some("heartbeat"));
+        assert_eq!(handler_name(&registry, topics::STATUS), Some("legacy-status"));
+        assert_eq!(handler_name(&registry, "claude-code/control-reply/s1"), Some("control-reply"));
+        assert_eq!(handler_name(&registry, "build/app"), Some("custom-topic"));
+    }
+
+    #[test]
+    fn test_first_registered_handler_wins() {
+        let mut registry = HandlerRegistry::default();
+        registry.register(TopicPattern::Exact("claude-code/custom/build"), NamedHandler("build"));
+        registry.register(TopicPattern::Prefix("claude-code/custom/"), NamedHandler("custom"));
+        assert_eq!(handler_name(&registry, "claude-code/custom/build"), Some("build"));
+        assert_eq!(handler_name(&registry, "claude-code/custom/deploy"), Some("custom"));
+    }
+}
@@ -0,0 +1,88 @@
+//! ステータスラインのメッセージ（ステータス・ハートビート）のハンドラー
+
+use super::{EventHandler, HandlerContext};
+use crate::state::{self, StatusPayload};
+use crate::notification_history::PermissionOutcome;
+use crate::{heartbeat, mark_session_read, resolve_permission_requests, session_alerts, update_tray_status};
+use tracing::{info, warn};
+
+/// セッションごとのステータス（claude-code/status/<session_id>）
+pub struct StatusHandler;
+
+impl EventHandler for StatusHandler {
+    fn name(&self) -> &'static str {
+        "status"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, topic: &str, payload_str: &str) {
+        let HandlerContext {
+            app,
+            session_manager,
+            session_name_manager,
+            notification_manager,
+            history_manager,
+            settings,
+            ..
+        } = ctx;
+        info!("Status update on {}: {}", topic, payload_str);
+        match serde_json::from_str::<StatusPayload>(payload_str) {
+            Ok(payload) => {
+                let session_id = payload.session_id.clone();
+                // 作業中に戻った場合は、ユーザーがプロジェクトに戻って指示したとみなす
+                let returned_to_work = session_manager.is_working(&session_id) == Some(false)
+                    && payload.status.state.as_deref() == Some(state::WORKING_STATE);
+                let previous_status = session_manager.status(&session_id);
+                session_alerts::on_status_update(
+                    app,
+                    notification_manager,
+                    session_name_manager,
+                    previous_status.as_ref(),
+                    &payload,
+                );
+                // A changed status means the session is working again
+                if session_manager.update_session(payload)
+                    && notification_manager.resume_session(app, &session_id)
+                {
+                    // 承認依頼の履歴に、応答して作業を再開したことを記録する
+                    resolve_permission_requests(app, history_manager, &session_id, PermissionOutcome::Resumed);
+                }
+                if returned_to_work && settings.mark_read_on_resume {
+                    mark_session_read(app, notification_manager, history_manager, &session_id);
+                }
+                // Cleanup expired sessions periodically
+                session_manager.cleanup_expired();
+                // Update tray tooltip
+                update_tray_status(app, session_manager, notification_manager);
+            }
+            Err(e) => {
+                warn!("Failed to parse status payload: {}", e);
+            }
+        }
+    }
+}
+
+/// ステータスラインのハートビート（claude-code/heartbeat/<host>）
+pub struct HeartbeatHandler;
+
+impl EventHandler for HeartbeatHandler {
+    fn name(&self) -> &'static str {
+        "heartbeat"
+    }
+
+    fn handle(&self, ctx: HandlerContext<'_>, _topic: &str, payload: &str) {
+        heartbeat::handle(ctx.app, payload);
+    }
+}
+
+/// セッションを含まない旧形式のステータス（claude-code/status）
+pub struct LegacyStatusHandler;
+
+impl EventHandler for LegacyStatusHandler {
+    fn name(&self) -> &'static str {
+        "legacy-status"
+    }
+
+    fn handle(&self, _ctx: HandlerContext<'_>, _topic: &str, payload: &str) {
+        info!("Status update: {}", payload);
+    }
+}
@@ -0,0 +1,277 @@
+//! フックのハートビート監視モジュール
+//!
+//! ステータスラインのスクリプトは Claude Code の実行中、一定間隔で claude-code/heartbeat/<host> に送信する
+//! （Claude Code が終了すると `"active": false` を送って停止する）。
+//! 実行中と報告していたホストから一定時間届かなくなった場合は、フックの故障や通信の断絶として警告する。
+
+use crate::i18n;
+use crate::timestamps;
+use crate::notification_history::NotificationEventType;
+use crate::toast::NotificationPriority;
+use crate::{NotificationManager, NotificationOrigin};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+/// 応答のないホストを確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// ハートビートの設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeartbeatSettings {
+    /// ハートビートを送信・監視する（エクスポートしたスクリプトに反映）
+    pub enabled: bool,
+    /// スクリプトがハートビートを送る間隔（秒）
+    pub interval_secs: u32,
+    /// 警告するまでの無応答の時間（分）
+    pub silence_minutes: u32,
+}
+
+impl Default for HeartbeatSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            silence_minutes: 10,
+        }
+    }
+}
+
+impl HeartbeatSettings {
+    /// エクスポートするスクリプトがハートビートを送る間隔（無効の場合は None）
+    pub fn active_interval(&self) -> Option<u32> {
+        (self.enabled && self.interval_secs > 0).then_some(self.interval_secs)
+    }
+
+    /// 警告するまでの時間（送信間隔より短い場合は送信間隔の2倍にする）
+    fn silence_threshold(&self) -> Duration {
+        let silence = Duration::from_secs(u64::from(self.silence_minutes) * 60);
+        silence.max(Duration::from_secs(u64::from(self.interval_secs) * 2))
+    }
+}
+
+/// ハートビートのペイロード
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeartbeatPayload {
+    pub host: String,
+    /// Claude Code が実行中か（false の場合は以降のハートビートを待たない）
+    #[serde(default = "default_active")]
+    pub active: bool,
+    /// ハートビートを送信した時刻（ない場合・解析できない場合は None）
+    #[serde(default, deserialize_with = "timestamps::deserialize")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+#[derive(Debug, Clone)]
+struct HostHeartbeat {
+    last_seen: Instant,
+    /// 最後のハートビートの時刻（ペイロードの時刻、ない場合は受信した時刻）
+    reported_at: DateTime<Utc>,
+    active: bool,
+    /// 無応答の警告を表示済みか（次のハートビートで解除する）
+    warned: bool,
+}
+
+/// 診断タブに表示するホストごとの状態
+#[derive(Debug, Clone, Serialize)]
+pub struct HostHeartbeatStatus {
+    pub host: String,
+    pub last_seen_secs: u64,
+    /// 最後のハートビートの時刻
+    pub reported_at: DateTime<Utc>,
+    pub active: bool,
+    pub silent: bool,
+}
+
+/// ホストごとの最後のハートビート
+#[derive(Debug, Default)]
+pub struct HeartbeatMonitor {
+    hosts: Mutex<HashMap<String, HostHeartbeat>>,
+}
+
+impl HeartbeatMonitor {
+    /// ハートビートを記録する（無応答の警告を表示していたホストが復帰した場合は true）
+    pub fn record(&self, payload: &HeartbeatPayload, now: Instant) -> bool {
+        let mut hosts = self.hosts.lock().unwrap();
+        let previous = hosts.insert(
+            payload.host.clone(),
+            HostHeartbeat {
+                last_seen: now,
+                reported_at: payload.timestamp.unwrap_or_else(Utc::now),
+                active: payload.active,
+                warned: false,
+            },
+        );
+        previous.is_some_and(|host| host.warned)
+    }
+
+    /// 実行中と報告したまま応答がなくなったホストを返す（同じホストは再び届くまで1度だけ返す）
+    pub fn newly_silent(&self, now: Instant, threshold: Duration) -> Vec<(String, Duration)> {
+        let mut hosts = self.hosts.lock().unwrap();
+        let mut silent: Vec<_> = hosts
+            .iter_mut()
+            .filter_map(|(name, host)| {
+                let elapsed = now.saturating_duration_since(host.last_seen);
+                if !host.active || host.warned || elapsed < threshold {
+                    return None;
+                }
+                host.warned = true;
+                Some((name.clone(), elapsed))
+            })
+            .collect();
+        silent.sort();
+        silent
+    }
+
+    pub fn statuses(&self, now: Instant) -> Vec<HostHeartbeatStatus> {
+        let hosts = self.hosts.lock().unwrap();
+        let mut statuses: Vec<_> = hosts
+            .iter()
+            .map(|(name, host)| HostHeartbeatStatus {
+                host: name.clone(),
+                last_seen_secs: now.saturating_duration_since(host.last_seen).as_secs(),
+                reported_at: host.reported_at,
+                active: host.active,
+                silent: host.warned,
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.host.cmp(&b.host));
+        statuses
+    }
+}
+
+/// 受信したハートビートを記録する
+pub fn handle(app: &AppHandle, payload_str: &str) {
+    let payload = match serde_json::from_str::<HeartbeatPayload>(payload_str) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Failed to parse heartbeat payload: {}", e);
+            return;
+        }
+    };
+    if app.state::<HeartbeatMonitor>().record(&payload, Instant::now()) {
+        info!("Hooks on {} are reporting again", payload.host);
+    }
+}
+
+/// 応答のないホストを定期的に確認し、警告する
+pub fn start_monitor(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+
+        let notification_manager = app.state::<Arc<NotificationManager>>();
+        let settings = notification_manager.get_settings().heartbeat;
+        if !settings.enabled {
+            continue;
+        }
+
+        let silent = app
+            .state::<HeartbeatMonitor>()
+            .newly_silent(Instant::now(), settings.silence_threshold());
+        let strings = notification_manager.strings();
+        for (host, elapsed) in silent {
+            warn!("Hooks on {} silent for {:?}", host, elapsed);
+            let minutes = (elapsed.as_secs() / 60).to_string();
+            let body = i18n::fill(strings.heartbeat_silent_body, &[("host", &host), ("minutes", &minutes)]);
+            notification_manager.notify(
+                &app,
+                strings.heartbeat_silent_title,
+                &body,
+                NotificationEventType::Notification,
+                NotificationPriority::High,
+                NotificationOrigin::default(),
+            );
+        }
+    });
+}
+
+/// Tauriコマンド: ホストごとのハートビートの状態を取得
+#[tauri::command]
+pub fn get_heartbeats(monitor: tauri::State<'_, HeartbeatMonitor>) -> Vec<HostHeartbeatStatus> {
+    monitor.statuses(Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat(host: &str, active: bool) -> HeartbeatPayload {
+        HeartbeatPayload {
+            host: host.to_string(),
+            active,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_warns_once_for_silent_active_host() {
+        let monitor = HeartbeatMonitor::default();
+        let start = Instant::now();
+        let threshold = Duration::from_secs(600);
+        monitor.record(&heartbeat("devbox", true), start);
+        monitor.record(&heartbeat("laptop", false), start);
+
+        assert!(monitor.newly_silent(start + Duration::from_secs(300), threshold).is_empty());
+
+        // Claude Code の終了を報告したホストは警告しない
+        let later = start + Duration::from_secs(660);
+        assert_eq!(monitor.newly_silent(later, threshold), vec![("devbox".to_string(), Duration::from_secs(660))]);
+        assert!(monitor.newly_silent(later + Duration::from_secs(60), threshold).is_empty());
+        assert!(monitor.statuses(later)[0].silent);
+
+        // 復帰したら再び監視する
+        assert!(monitor.record(&heartbeat("devbox", true), later));
+        assert!(!monitor.record(&heartbeat("devbox", true), later));
+        assert_eq!(monitor.newly_silent(later + threshold, threshold).len(), 1);
+    }
+
+    #[test]
+    fn test_settings() {
+        let settings = HeartbeatSettings::default();
+        assert_eq!(settings.active_interval(), Some(60));
+        assert_eq!(settings.silence_threshold(), Duration::from_secs(600));
+
+        let infrequent = HeartbeatSettings {
+            interval_secs: 600,
+            silence_minutes: 5,
+            ..HeartbeatSettings::default()
+        };
+        assert_eq!(infrequent.silence_threshold(), Duration::from_secs(1200));
+
+        let disabled = HeartbeatSettings {
+            enabled: false,
+            ..HeartbeatSettings::default()
+        };
+        assert_eq!(disabled.active_interval(), None);
+    }
+
+    #[test]
+    fn test_payload_defaults_to_active() {
+        let payload: HeartbeatPayload = serde_json::from_str(r#"{"host":"devbox"}"#).unwrap();
+        assert!(payload.active);
+        assert_eq!(payload.timestamp, None);
+    }
+
+    #[test]
+    fn test_reported_at_uses_payload_timestamp() {
+        let payload: HeartbeatPayload =
+            serde_json::from_str(r#"{"host":"devbox","timestamp":"2026-10-17T10:00:00+09:00"}"#).unwrap();
+        let sent = DateTime::parse_from_rfc3339("2026-10-17T01:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(payload.timestamp, Some(sent));
+
+        let monitor = HeartbeatMonitor::default();
+        let now = Instant::now();
+        monitor.record(&payload, now);
+        assert_eq!(monitor.statuses(now)[0].reported_at, sent);
+    }
+}
@@ -0,0 +1,161 @@
+//! 編集したフックのテンプレートのモジュール
+//!
+//! templates.rs を変更せずにログの出力や jq の処理を追加できるよう、
+//! エクスポートタブで編集したスクリプトのテンプレートを tauri-plugin-store（hook_templates.json）に保存する。
+//! テンプレートは `__HOST__`・`__PORT__` などのプレースホルダーを含んだまま保存し、ZIP の生成時に置き換える。
+
+use crate::export::{ExportPlatform, HookShell, FISH_HOOKS, NUSHELL_HOOKS};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use tracing::{error, info};
+
+const STORE_FILE: &str = "hook_templates.json";
+const STORE_KEY: &str = "templates";
+
+/// エクスポートタブに表示するテンプレート
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HookTemplate {
+    pub file_name: String,
+    /// 編集したテンプレート（ない場合は組み込みのテンプレート）
+    pub content: String,
+    /// 編集したテンプレートを使っているか
+    pub customized: bool,
+}
+
+/// 編集できるスクリプトの組み込みのテンプレート
+fn builtin(file_name: &str) -> Option<&'static str> {
+    [ExportPlatform::LinuxWsl, ExportPlatform::Windows]
+        .into_iter()
+        .flat_map(ExportPlatform::scripts)
+        .chain(FISH_HOOKS)
+        .chain(NUSHELL_HOOKS)
+        .find(|(name, _)| *name == file_name)
+        .map(|(_, template)| *template)
+}
+
+/// プラットフォーム・シェルのテンプレートの一覧
+pub fn list(custom: &BTreeMap<String, String>, platform: ExportPlatform, shell: HookShell) -> Vec<HookTemplate> {
+    platform
+        .scripts_for(shell)
+        .into_iter()
+        .map(|(file_name, template)| {
+            let edited = custom.get(file_name);
+            HookTemplate {
+                file_name: file_name.to_string(),
+                content: edited.map_or(template, String::as_str).to_string(),
+                customized: edited.is_some(),
+            }
+        })
+        .collect()
+}
+
+/// テンプレートを更新する（組み込みのテンプレートと同じ内容は保存しない）
+pub fn update(custom: &mut BTreeMap<String, String>, file_name: &str, content: &str) -> Result<(), String> {
+    let builtin = builtin(file_name).ok_or_else(|| format!("Unknown template: {}", file_name))?;
+    // 改行コードの違いだけの変更は編集とみなさない
+    let content = content.replace("\r\n", "\n");
+    if content == builtin {
+        custom.remove(file_name);
+    } else {
+        custom.insert(file_name.to_string(), content);
+    }
+    Ok(())
+}
+
+/// 保存したテンプレートを読み込む
+pub fn load(app: &AppHandle) -> BTreeMap<String, String> {
+    let store = match app.store(STORE_FILE) {
+        Ok(store) => store,
+        Err(e) => {
+            error!("Failed to open hook template store: {}", e);
+            return BTreeMap::new();
+        }
+    };
+    match store.get(STORE_KEY).map(serde_json::from_value) {
+        Some(Ok(templates)) => templates,
+        Some(Err(e)) => {
+            error!("Failed to deserialize hook templates: {}", e);
+            BTreeMap::new()
+        }
+        None => BTreeMap::new(),
+    }
+}
+
+fn save(app: &AppHandle, templates: &BTreeMap<String, String>) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let value = serde_json::to_value(templates).map_err(|e| e.to_string())?;
+    store.set(STORE_KEY, value);
+    store.save().map_err(|e| e.to_string())
+}
+
+fn parse_platform(platform: &str) -> ExportPlatform {
+    match platform {
+        "windows" => ExportPlatform::Windows,
+        _ => ExportPlatform::LinuxWsl,
+    }
+}
+
+/// Tauriコマンド: プラットフォームのテンプレートを取得（shell を省略した場合は bash）
+#[tauri::command]
+pub fn get_hook_templates(app: AppHandle, platform: String, shell: Option<HookShell>) -> Vec<HookTemplate> {
+    list(&load(&app), parse_platform(&platform), shell.unwrap_or_default())
+}
+
+/// Tauriコマンド: 編集したテンプレートを保存
+#[tauri::command]
+pub fn save_hook_template(app: AppHandle, file_name: String, content: String) -> Result<(), String> {
+    let mut templates = load(&app);
+    update(&mut templates, &file_name, &content)?;
+    save(&app, &templates)?;
+    info!("Hook template saved: {}", file_name);
+    Ok(())
+}
+
+/// Tauriコマンド: テンプレートを組み込みのものに戻す
+#[tauri::command]
+pub fn reset_hook_template(app: AppHandle, file_name: String) -> Result<(), String> {
+    let mut templates = load(&app);
+    if templates.remove(&file_name).is_some() {
+        save(&app, &templates)?;
+        info!("Hook template reset: {}", file_name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates;
+
+    #[test]
+    fn test_update_and_list() {
+        let mut custom = BTreeMap::new();
+        update(&mut custom, "on-stop.sh", "#!/bin/bash\necho __HOST__ >> ~/notify.log\n").unwrap();
+        assert!(update(&mut custom, "README.txt", "").is_err());
+
+        let linux = list(&custom, ExportPlatform::LinuxWsl, HookShell::Bash);
+        assert_eq!(linux[0].file_name, "on-stop.sh");
+        assert!(linux[0].customized);
+        assert!(linux[0].content.contains("__HOST__"));
+        assert!(!linux[1].customized);
+        assert_eq!(linux[1].content, templates::ON_PERMISSION_REQUEST_SH);
+        assert!(list(&custom, ExportPlatform::Windows, HookShell::Bash).iter().all(|t| !t.customized));
+
+        update(&mut custom, "on-stop.fish", "#!/usr/bin/env fish\necho edited\n").unwrap();
+        let fish = list(&custom, ExportPlatform::LinuxWsl, HookShell::Fish);
+        assert_eq!(fish[0].file_name, "on-stop.fish");
+        assert!(fish[0].customized);
+        assert_eq!(fish[2].file_name, "await-permission-response.sh");
+    }
+
+    #[test]
+    fn test_update_with_builtin_content_removes_edit() {
+        let mut custom = BTreeMap::new();
+        update(&mut custom, "on-stop.ps1", "Write-Host edited").unwrap();
+        assert_eq!(custom.len(), 1);
+        update(&mut custom, "on-stop.ps1", &templates::ON_STOP_PS1.replace('\n', "\r\n")).unwrap();
+        assert!(custom.is_empty());
+    }
+}
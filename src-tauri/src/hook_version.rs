@@ -0,0 +1,96 @@
+//! フックのスクリプトのバージョンを確認するモジュール
+//!
+//! アプリを更新しても、インストール済みのスクリプトは再エクスポートするまで古いままになる。
+//! イベントのペイロードに含まれるテンプレートのバージョンが現在のものより古い場合は、
+//! 再エクスポートを促す通知をホストごとに1回（アプリの再起動まで）表示する。
+
+use crate::notification_history::NotificationEventType;
+use crate::templates::TEMPLATE_VERSION;
+use crate::{host_rules, i18n, show_simple_notification, NotificationManager, NotificationOrigin};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+/// バージョンを送らないスクリプト（バージョンを埋め込む前のもの）のバージョン
+const LEGACY_VERSION: u32 = 1;
+
+/// 古いスクリプトを通知したホスト
+#[derive(Default)]
+pub struct HookVersionMonitor {
+    warned_hosts: Mutex<HashSet<String>>,
+}
+
+impl HookVersionMonitor {
+    /// ホストへの最初の警告か（2回目以降は false）
+    fn first_warning(&self, host: Option<&str>) -> bool {
+        let host = host_rules::normalize_host(host).unwrap_or_default();
+        self.warned_hosts.lock().map(|mut hosts| hosts.insert(host)).unwrap_or(false)
+    }
+}
+
+/// インストール済みのスクリプトが古い場合はそのバージョン
+pub fn outdated_version(template_version: Option<u32>) -> Option<u32> {
+    let installed = template_version.unwrap_or(LEGACY_VERSION);
+    (installed < TEMPLATE_VERSION).then_some(installed)
+}
+
+/// イベントを送ったスクリプトのバージョンを確認し、古い場合は再エクスポートを促す
+pub fn check(app: &AppHandle, notification_manager: &NotificationManager, host: Option<&str>, template_version: Option<u32>) {
+    let Some(installed) = outdated_version(template_version) else {
+        return;
+    };
+    if !app.state::<HookVersionMonitor>().first_warning(host) {
+        return;
+    }
+    warn!(
+        "Hook scripts v{} on {} are older than the current templates v{}",
+        installed,
+        host.unwrap_or("unknown host"),
+        TEMPLATE_VERSION
+    );
+    if notification_manager.get_settings().host_rules.is_muted(host) {
+        info!("Outdated hook notification from muted host suppressed");
+        return;
+    }
+    let strings = notification_manager.strings();
+    let mut body = i18n::fill(
+        strings.outdated_hooks_body,
+        &[("installed", &installed.to_string()), ("current", &TEMPLATE_VERSION.to_string())],
+    );
+    if let Some(host) = host_rules::normalize_host(host) {
+        body = format!("{}: {}", host, body);
+    }
+    show_simple_notification(
+        app,
+        notification_manager,
+        strings.outdated_hooks_title,
+        &body,
+        NotificationEventType::Notification,
+        NotificationOrigin::default(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outdated_version() {
+        assert_eq!(outdated_version(None), Some(LEGACY_VERSION));
+        assert_eq!(outdated_version(Some(TEMPLATE_VERSION - 1)), Some(TEMPLATE_VERSION - 1));
+        assert_eq!(outdated_version(Some(TEMPLATE_VERSION)), None);
+        // 新しいアプリで生成したスクリプトを古いアプリで受信した場合は警告しない
+        assert_eq!(outdated_version(Some(TEMPLATE_VERSION + 1)), None);
+    }
+
+    #[test]
+    fn test_warns_once_per_host() {
+        let monitor = HookVersionMonitor::default();
+        assert!(monitor.first_warning(Some("wsl")));
+        assert!(!monitor.first_warning(Some(" wsl ")));
+        assert!(monitor.first_warning(Some("build-server")));
+        assert!(monitor.first_warning(None));
+        assert!(!monitor.first_warning(Some("")));
+    }
+}
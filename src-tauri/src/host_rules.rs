@@ -0,0 +1,96 @@
+//! 送信元ホストのルールモジュール
+//!
+//! 複数のマシンのフックが1つのアプリに通知を送る場合に、イベントの `host` フィールドで
+//! 送信元を区別する。ホストごとに通知のミュートと、通知履歴・セッション一覧での
+//! 表示色を設定できる（表示色はフロントエンドで使用する）。
+
+use serde::{Deserialize, Serialize};
+
+/// ホストごとのルール
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostRule {
+    /// ホスト名（大文字・小文字は区別しない）
+    pub host: String,
+    /// このホストからの通知を表示しない（通知履歴には記録する）
+    #[serde(default)]
+    pub muted: bool,
+    /// 通知履歴・セッション一覧での表示色（`#rrggbb`）
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// 送信元ホストのルール設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostRuleSettings {
+    pub rules: Vec<HostRule>,
+}
+
+impl HostRuleSettings {
+    /// ホストに一致するルールを取得
+    pub fn find(&self, host: Option<&str>) -> Option<&HostRule> {
+        let host = normalize_host(host)?;
+        self.rules
+            .iter()
+            .find(|rule| rule.host.trim().eq_ignore_ascii_case(&host))
+    }
+
+    /// ホストからの通知をミュートするか
+    pub fn is_muted(&self, host: Option<&str>) -> bool {
+        self.find(host).is_some_and(|rule| rule.muted)
+    }
+}
+
+/// ペイロードのホスト名を整える（空の場合は None）
+pub fn normalize_host(host: Option<&str>) -> Option<String> {
+    host.map(str::trim)
+        .filter(|host| !host.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> HostRuleSettings {
+        HostRuleSettings {
+            rules: vec![
+                HostRule {
+                    host: "build-server".to_string(),
+                    muted: true,
+                    color: None,
+                },
+                HostRule {
+                    host: " Laptop ".to_string(),
+                    muted: false,
+                    color: Some("#3399ff".to_string()),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_find_ignores_case_and_whitespace() {
+        let settings = settings();
+        let rule = settings.find(Some("laptop")).unwrap();
+        assert_eq!(rule.color.as_deref(), Some("#3399ff"));
+        assert!(settings.find(Some("desktop")).is_none());
+        assert!(settings.find(None).is_none());
+        assert!(settings.find(Some("  ")).is_none());
+    }
+
+    #[test]
+    fn test_is_muted() {
+        let settings = settings();
+        assert!(settings.is_muted(Some("BUILD-SERVER")));
+        assert!(!settings.is_muted(Some("laptop")));
+        assert!(!settings.is_muted(None));
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host(Some(" wsl ")), Some("wsl".to_string()));
+        assert_eq!(normalize_host(Some("")), None);
+        assert_eq!(normalize_host(None), None);
+    }
+}
@@ -0,0 +1,246 @@
+//! 接続先の更新モジュール
+//!
+//! エクスポートしたスクリプトは接続先を環境変数か `~/.claude/notify.env` から読む
+//! （どちらもない場合はエクスポート時の接続先）。
+//! このPCの IP アドレスを定期的に確認し、変わった場合は通知して、登録したリモートのマシンに
+//! SSH で新しい接続先の `notify.env` を送る（自動で送らない場合は設定タブのボタンから送る）。
+//! ノートPCの IP アドレスが変わっても、通知が届かなくなったことに気付かないままにならないようにする。
+
+use crate::export;
+use crate::i18n;
+use crate::notification_history::NotificationEventType;
+use crate::toast::NotificationPriority;
+use crate::{AppState, NotificationManager, NotificationOrigin};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// IP アドレスを確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// リモートのマシンで `notify.env` を書き込むコマンド（POSIX シェル）
+const REMOTE_COMMAND: &str = "mkdir -p ~/.claude && cat > ~/.claude/notify.env";
+
+#[derive(Error, Debug)]
+pub enum HostUpdateError {
+    #[error("Invalid SSH destination: {0}")]
+    InvalidTarget(String),
+    #[error("Failed to run ssh: {0}")]
+    Spawn(#[from] std::io::Error),
+    #[error("ssh exited with {status}: {stderr}")]
+    Ssh { status: String, stderr: String },
+}
+
+/// 接続先の更新の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostUpdateSettings {
+    /// このPCの IP アドレスの変化を監視して通知する
+    pub watch_ip: bool,
+    /// 接続先を送る SSH の宛先（`user@host` や ~/.ssh/config のホスト名）
+    pub ssh_targets: Vec<String>,
+    /// IP アドレスが変わった場合に自動で送る
+    pub auto_push: bool,
+}
+
+impl Default for HostUpdateSettings {
+    fn default() -> Self {
+        Self {
+            watch_ip: true,
+            ssh_targets: Vec::new(),
+            auto_push: false,
+        }
+    }
+}
+
+/// リモートのマシンへの送信結果
+#[derive(Debug, Clone, Serialize)]
+pub struct PushResult {
+    pub target: String,
+    /// 失敗した場合のエラー（成功した場合は None）
+    pub error: Option<String>,
+}
+
+/// スクリプトが読む `notify.env` の内容
+pub fn notify_env(host: &str, port: u16) -> String {
+    format!(
+        "# Generated by Claude Code Notify ({})\nCLAUDE_NOTIFY_HOST={}\nCLAUDE_NOTIFY_PORT={}\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        host,
+        port
+    )
+}
+
+/// SSH で `notify.env` を書き込む
+fn push_to(target: &str, content: &str) -> Result<(), HostUpdateError> {
+    // オプションとして解釈される宛先は受け付けない
+    if target.is_empty() || target.starts_with('-') || target.contains(char::is_whitespace) {
+        return Err(HostUpdateError::InvalidTarget(target.to_string()));
+    }
+
+    let mut command = Command::new("ssh");
+    command
+        .args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10", target, REMOTE_COMMAND])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // コンソールウィンドウを表示しない（CREATE_NO_WINDOW）
+        command.creation_flags(0x0800_0000);
+    }
+
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(HostUpdateError::Ssh {
+            status: output.status.to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+/// 登録したすべてのマシンに接続先を送る
+pub fn push_all(targets: &[String], host: &str, port: u16) -> Vec<PushResult> {
+    let content = notify_env(host, port);
+    targets
+        .iter()
+        .map(|target| target.trim())
+        .filter(|target| !target.is_empty())
+        .map(|target| {
+            let error = push_to(target, &content).err().map(|e| e.to_string());
+            match &error {
+                None => info!("Pushed broker host {} to {}", host, target),
+                Some(e) => warn!("Failed to push broker host to {}: {}", target, e),
+            }
+            PushResult {
+                target: target.to_string(),
+                error,
+            }
+        })
+        .collect()
+}
+
+/// IP アドレスの変化の検出
+#[derive(Debug, Default)]
+pub struct IpWatcher {
+    last: Mutex<Option<String>>,
+}
+
+impl IpWatcher {
+    /// 確認した IP アドレスを記録し、変わった場合は変更前のアドレスを返す（初回は None）
+    pub fn observe(&self, ip: &str) -> Option<String> {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        match last.replace(ip.to_string()) {
+            Some(previous) if previous != ip => Some(previous),
+            _ => None,
+        }
+    }
+}
+
+/// 組み込みのブローカーの待ち受けポート（LAN 向け、外部のブローカーに接続している場合は None）
+fn broker_port(app: &AppHandle) -> Option<u16> {
+    app.state::<Mutex<AppState>>()
+        .lock()
+        .ok()
+        .and_then(|state| state.broker.as_ref().map(|broker| broker.port()))
+}
+
+/// IP アドレスを定期的に確認し、変わった場合は通知する（自動送信が有効な場合は送る）
+pub fn start_monitor(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let watcher = IpWatcher::default();
+        loop {
+            let notification_manager = app.state::<Arc<NotificationManager>>();
+            let settings = notification_manager.get_settings().host_update;
+            // オフラインの間は確認しない（復帰した時に変化を検出する）
+            if let (true, Ok(ip)) = (settings.watch_ip, export::detect_local_ip()) {
+                if let Some(previous) = watcher.observe(&ip) {
+                    warn!("Local IP address changed: {} -> {}", previous, ip);
+                    let strings = notification_manager.strings();
+                    let mut body = i18n::fill(strings.ip_changed_body, &[("old", &previous), ("new", &ip)]);
+                    let port = broker_port(&app);
+                    if let (true, false, Some(port)) = (settings.auto_push, settings.ssh_targets.is_empty(), port) {
+                        let results = push_all(&settings.ssh_targets, &ip, port);
+                        let pushed = results.iter().filter(|result| result.error.is_none()).count();
+                        body = i18n::fill(
+                            strings.ip_changed_pushed_body,
+                            &[("new", &ip), ("pushed", &pushed.to_string()), ("total", &results.len().to_string())],
+                        );
+                    }
+                    notification_manager.notify(
+                        &app,
+                        strings.ip_changed_title,
+                        &body,
+                        NotificationEventType::Notification,
+                        NotificationPriority::High,
+                        NotificationOrigin::default(),
+                    );
+                }
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+/// Tauriコマンド: 登録したマシンに接続先を送る（host が空の場合はこのPCの IP アドレス）
+#[tauri::command]
+pub fn push_broker_host(
+    app: AppHandle,
+    notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+    host: Option<String>,
+) -> Result<Vec<PushResult>, String> {
+    let Some(port) = broker_port(&app) else {
+        return Err("The embedded broker is not running".to_string());
+    };
+    let targets = notification_manager.get_settings().host_update.ssh_targets;
+    if targets.iter().all(|target| target.trim().is_empty()) {
+        return Err("No SSH destinations configured".to_string());
+    }
+    let host = match host.map(|host| host.trim().to_string()).filter(|host| !host.is_empty()) {
+        Some(host) => host,
+        None => export::detect_local_ip().map_err(|e| e.to_string())?,
+    };
+    Ok(push_all(&targets, &host, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify_env() {
+        let content = notify_env("192.168.1.20", 1883);
+        assert!(content.starts_with("# Generated by Claude Code Notify"));
+        assert!(content.contains("\nCLAUDE_NOTIFY_HOST=192.168.1.20\n"));
+        assert!(content.ends_with("\nCLAUDE_NOTIFY_PORT=1883\n"));
+    }
+
+    #[test]
+    fn test_ip_watcher_reports_changes() {
+        let watcher = IpWatcher::default();
+        assert_eq!(watcher.observe("192.168.1.10"), None);
+        assert_eq!(watcher.observe("192.168.1.10"), None);
+        assert_eq!(watcher.observe("10.0.0.5"), Some("192.168.1.10".to_string()));
+        assert_eq!(watcher.observe("10.0.0.5"), None);
+    }
+
+    #[test]
+    fn test_rejects_option_like_targets() {
+        assert!(matches!(push_to("-oProxyCommand=x", ""), Err(HostUpdateError::InvalidTarget(_))));
+        assert!(matches!(push_to("user@host extra", ""), Err(HostUpdateError::InvalidTarget(_))));
+    }
+}
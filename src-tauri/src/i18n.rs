@@ -0,0 +1,511 @@
+//! 多言語対応モジュール
+//!
+//! 通知本文・トレイメニュー・ウィンドウタイトルの文字列テーブルを提供する。
+//! 言語設定が `Auto` の場合は OS のロケールから自動判定する。
+
+use serde::{Deserialize, Serialize};
+
+/// 言語設定（設定ファイルに保存される値）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageSetting {
+    /// OS のロケールから自動判定
+    #[default]
+    Auto,
+    Ja,
+    En,
+}
+
+impl LanguageSetting {
+    /// 実際に使用する言語を決定する
+    pub fn resolve(self) -> Language {
+        match self {
+            LanguageSetting::Auto => detect_os_language(),
+            LanguageSetting::Ja => Language::Ja,
+            LanguageSetting::En => Language::En,
+        }
+    }
+}
+
+/// 表示言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Ja,
+    En,
+}
+
+impl Language {
+    /// ロケール文字列（"ja-JP", "en_US.UTF-8" など）から言語を判定する
+    pub fn from_locale(locale: &str) -> Self {
+        if locale.trim().to_ascii_lowercase().starts_with("ja") {
+            Language::Ja
+        } else {
+            Language::En
+        }
+    }
+
+    /// 言語に対応する文字列テーブルを取得
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Language::Ja => &JA,
+            Language::En => &EN,
+        }
+    }
+}
+
+/// OS のロケールから言語を判定する（取得できない場合は日本語）
+pub fn detect_os_language() -> Language {
+    sys_locale::get_locale()
+        .map(|locale| Language::from_locale(&locale))
+        .unwrap_or(Language::Ja)
+}
+
+/// 文字列テーブル
+///
+/// `{name}` 形式のプレースホルダーは [`fill`] で置換する。
+#[derive(Debug)]
+pub struct Strings {
+    // ----- 通知 -----
+    pub task_complete_title: &'static str,
+    pub task_complete_body: &'static str,
+    pub permission_request_title: &'static str,
+    pub approval_required: &'static str,
+    /// プレースホルダー: `{tool}`
+    pub tool_permission_required: &'static str,
+    pub tool_permission_generic: &'static str,
+    /// プレースホルダー: `{command}`
+    pub command_label: &'static str,
+    pub question_header: &'static str,
+    pub question_default: &'static str,
+    pub question_window_title: &'static str,
+    pub notification_title: &'static str,
+    pub input_required: &'static str,
+    pub waiting_for_input: &'static str,
+    /// プレースホルダー: `{host}`
+    pub source_host: &'static str,
+    /// プレースホルダー: `{summary}`
+    pub run_summary: &'static str,
+    /// プレースホルダー: `{seconds}`
+    pub run_seconds: &'static str,
+    /// プレースホルダー: `{minutes}`
+    pub run_minutes: &'static str,
+    /// プレースホルダー: `{added}`, `{removed}`
+    pub run_lines: &'static str,
+    pub error_title: &'static str,
+    /// プレースホルダー: `{hook}`
+    pub error_hook_title: &'static str,
+    /// プレースホルダー: `{code}`
+    pub error_exit_code: &'static str,
+    pub error_hint_missing_dependency: &'static str,
+    pub error_hint_not_executable: &'static str,
+    pub error_hint_connection: &'static str,
+    pub error_hint_authentication: &'static str,
+    pub error_hint_hook: &'static str,
+    pub error_hint_invalid_payload: &'static str,
+    pub error_hint_other: &'static str,
+    pub invalid_payload_title: &'static str,
+    /// プレースホルダー: `{line}`, `{column}`
+    pub invalid_payload_json: &'static str,
+    pub invalid_payload_not_object: &'static str,
+    /// プレースホルダー: `{field}`
+    pub invalid_payload_missing_field: &'static str,
+    /// プレースホルダー: `{field}`, `{expected}`, `{found}`
+    pub invalid_payload_wrong_type: &'static str,
+    pub outdated_hooks_title: &'static str,
+    /// プレースホルダー: `{installed}`, `{current}`
+    pub outdated_hooks_body: &'static str,
+    pub toast_dismiss: &'static str,
+    pub toast_allow: &'static str,
+    pub toast_deny: &'static str,
+    pub toast_open: &'static str,
+    pub toast_mute_session: &'static str,
+    pub toast_snooze: &'static str,
+    pub catch_up_summary_title: &'static str,
+    /// プレースホルダー: `{count}`
+    pub catch_up_summary_body: &'static str,
+    pub deferred_summary_title: &'static str,
+    /// プレースホルダー: `{count}`
+    pub deferred_summary_body: &'static str,
+    /// プレースホルダー: `{project}`
+    pub speech_finished_one: &'static str,
+    /// プレースホルダー: `{count}`, `{projects}`
+    pub speech_finished_many: &'static str,
+    /// 読み上げるプロジェクト名の区切り
+    pub speech_separator: &'static str,
+    pub test_toast_title: &'static str,
+    pub test_toast_body: &'static str,
+    pub heartbeat_silent_title: &'static str,
+    /// プレースホルダー: `{host}`, `{minutes}`
+    pub heartbeat_silent_body: &'static str,
+    /// プレースホルダー: `{source}`
+    pub flooding_title: &'static str,
+    /// プレースホルダー: `{count}`, `{window}`, `{mute}`
+    pub flooding_body: &'static str,
+    pub client_restart_failed_title: &'static str,
+    /// プレースホルダー: `{count}`
+    pub client_restart_failed_body: &'static str,
+    pub port_fallback_title: &'static str,
+    /// プレースホルダー: `{requested}`, `{port}`
+    pub port_fallback_body: &'static str,
+    pub ip_changed_title: &'static str,
+    /// プレースホルダー: `{old}`, `{new}`
+    pub ip_changed_body: &'static str,
+    /// プレースホルダー: `{new}`, `{pushed}`, `{total}`
+    pub ip_changed_pushed_body: &'static str,
+    /// プレースホルダー: `{version}`
+    pub update_available_title: &'static str,
+    pub update_open_download: &'static str,
+    pub update_up_to_date: &'static str,
+    /// プレースホルダー: `{version}`
+    pub update_current_version: &'static str,
+    pub digest_hourly_title: &'static str,
+    pub digest_daily_title: &'static str,
+    /// プレースホルダー: `{count}`
+    pub digest_completed: &'static str,
+    /// プレースホルダー: `{project}`, `{count}`
+    pub digest_project: &'static str,
+    /// プレースホルダー: `{count}`
+    pub digest_more_projects: &'static str,
+    /// プレースホルダー: `{count}`
+    pub digest_permission_requests: &'static str,
+    /// プレースホルダー: `{count}`
+    pub digest_notifications: &'static str,
+    /// プレースホルダー: `{count}`
+    pub digest_pending: &'static str,
+    /// プレースホルダー: `{cost}`
+    pub digest_cost: &'static str,
+    pub cost_report_title: &'static str,
+    /// プレースホルダー: `{project}`, `{cost}`
+    pub cost_report_project: &'static str,
+    /// プレースホルダー: `{lines}`, `{added}`, `{removed}`
+    pub line_milestone_body: &'static str,
+    pub state_waiting_body: &'static str,
+    /// プレースホルダー: `{minutes}`
+    pub waiting_reminder_body: &'static str,
+    pub all_clear_title: &'static str,
+    pub all_clear_body: &'static str,
+
+    // ----- トレイメニュー -----
+    pub menu_status_idle: &'static str,
+    /// プレースホルダー: `{count}`
+    pub menu_status_sessions: &'static str,
+    pub menu_status_broker_running: &'static str,
+    pub menu_status_broker_stopped: &'static str,
+    pub menu_status_client_connected: &'static str,
+    pub menu_status_client_disconnected: &'static str,
+    pub menu_status_client_failing: &'static str,
+    /// プレースホルダー: `{count}`
+    pub menu_status_unread: &'static str,
+    /// プレースホルダー: `{cost}`
+    pub menu_status_cost: &'static str,
+    pub menu_status_no_sessions: &'static str,
+    /// プレースホルダー: `{count}`
+    pub menu_status_more_sessions: &'static str,
+    pub menu_status_calendar_busy: &'static str,
+    pub menu_profiles: &'static str,
+    pub menu_profiles_empty: &'static str,
+    /// プレースホルダー: `{summary}`
+    pub calendar_busy: &'static str,
+    pub menu_do_not_disturb: &'static str,
+    pub menu_settings: &'static str,
+    pub menu_export: &'static str,
+    pub menu_check_update: &'static str,
+    /// プレースホルダー: `{version}`
+    pub menu_download_update: &'static str,
+    pub menu_quit: &'static str,
+
+    // ----- ウィンドウタイトル -----
+    pub window_title_main: &'static str,
+    pub window_title_history: &'static str,
+    pub window_title_settings: &'static str,
+    pub window_title_export: &'static str,
+    pub window_title_diagnostics: &'static str,
+}
+
+impl Strings {
+    /// タブ名に対応するウィンドウタイトルを取得
+    pub fn window_title_for_tab(&self, tab: &str) -> &'static str {
+        match tab {
+            "history" => self.window_title_history,
+            "settings" => self.window_title_settings,
+            "export" => self.window_title_export,
+            "diagnostics" => self.window_title_diagnostics,
+            _ => self.window_title_main,
+        }
+    }
+}
+
+static JA: Strings = Strings {
+    task_complete_title: "✅ タスク完了",
+    task_complete_body: "✅ タスクが完了しました",
+    permission_request_title: "⚠️ 承認依頼",
+    approval_required: "⚠️ 承認が必要です",
+    tool_permission_required: "{tool} の実行許可が必要です",
+    tool_permission_generic: "ツールの実行許可が必要です",
+    command_label: "コマンド: {command}",
+    question_header: "❓ 質問があります",
+    question_default: "質問が来ています",
+    question_window_title: "Claude Code からの質問",
+    notification_title: "💬 通知",
+    input_required: "💬 入力が必要です",
+    waiting_for_input: "入力を待っています",
+    source_host: "🖥️ ホスト: {host}",
+    run_summary: "💰 今回: {summary}",
+    run_seconds: "{seconds} 秒",
+    run_minutes: "{minutes} 分",
+    run_lines: "+{added}/-{removed} 行",
+    error_title: "❌ エラー",
+    error_hook_title: "❌ {hook} でエラー",
+    error_exit_code: "終了コード {code}",
+    error_hint_missing_dependency: "必要なコマンド（jq・mosquitto_pub・mqtt-publish など）をインストールしてください",
+    error_hint_not_executable: "スクリプトに実行権限があるか確認してください（chmod +x）",
+    error_hint_connection: "アプリが起動しているか、ファイアウォールでポートが許可されているか確認してください",
+    error_hint_authentication: "署名・暗号化の設定を再エクスポートしてフックを再インストールしてください",
+    error_hint_hook: "フックのスクリプトを手動で実行してエラーを確認してください",
+    error_hint_invalid_payload: "フックとアプリのバージョンが一致しているか確認してください（診断タブの「問題」で受信した内容を確認できます）",
+    error_hint_other: "診断タブの「問題」で詳細を確認してください",
+    invalid_payload_title: "⚠️ フックのペイロードが不正です",
+    invalid_payload_json: "JSON として読み取れません（{line} 行 {column} 文字目）",
+    invalid_payload_not_object: "JSON のオブジェクトではありません",
+    invalid_payload_missing_field: "`{field}` がありません",
+    invalid_payload_wrong_type: "`{field}` は {expected} である必要があります（実際は {found}）",
+    outdated_hooks_title: "🔄 フックのスクリプトが古くなっています",
+    outdated_hooks_body: "スクリプト v{installed} がインストールされています。v{current} が利用できます — 設定の再エクスポートをおすすめします",
+    toast_dismiss: "閉じる",
+    toast_allow: "許可",
+    toast_deny: "拒否",
+    toast_open: "開く",
+    toast_mute_session: "このセッションをミュート",
+    toast_snooze: "10分スヌーズ",
+    catch_up_summary_title: "起動前の通知",
+    catch_up_summary_body: "アプリが起動していない間に {count} 件の通知を見逃しました。通知履歴で確認できます",
+    deferred_summary_title: "全画面表示中の通知",
+    deferred_summary_body: "全画面表示中に {count} 件の通知がありました",
+    speech_finished_one: "{project} の作業が完了しました",
+    speech_finished_many: "{count} つのプロジェクトが完了しました: {projects}",
+    speech_separator: "、",
+    test_toast_title: "テスト通知",
+    test_toast_body: "トースト通知は正常に表示されています",
+    heartbeat_silent_title: "⚠️ フックからの応答がありません",
+    heartbeat_silent_body: "{host} のフックが {minutes} 分間応答していません",
+    flooding_title: "⚠️ {source} が大量のイベントを送信しています",
+    flooding_body: "{window} 秒間に {count} 件を超えたため、{mute} 秒間この送信元のイベントを無視します。フックがループしていないか確認してください",
+    client_restart_failed_title: "⚠️ MQTT クライアントが停止しました",
+    client_restart_failed_body: "再起動しても {count} 回続けて停止しています。通知を受け取れない可能性があります",
+    port_fallback_title: "⚠️ ブローカーのポートを変更しました",
+    port_fallback_body: "ポート {requested} は使用中のため、ポート {port} で起動しました。スクリプトを再エクスポートしてください",
+    ip_changed_title: "⚠️ IP アドレスが変わりました",
+    ip_changed_body: "このPCの IP アドレスが {old} から {new} に変わりました。リモートのマシンの接続先を更新してください",
+    ip_changed_pushed_body: "新しい接続先 {new} を {total} 台中 {pushed} 台のマシンに送信しました",
+    update_available_title: "新しいバージョン {version} があります",
+    update_open_download: "クリックしてダウンロードページを開きます",
+    update_up_to_date: "最新版を使用しています",
+    update_current_version: "現在のバージョン: {version}",
+    digest_hourly_title: "📋 この1時間のまとめ",
+    digest_daily_title: "📋 1日のまとめ",
+    digest_completed: "完了したタスク: {count} 件",
+    digest_project: "・{project}: {count} 件",
+    digest_more_projects: "・ほか {count} プロジェクト",
+    digest_permission_requests: "承認依頼: {count} 件",
+    digest_notifications: "その他の通知: {count} 件",
+    digest_pending: "承認待ち: {count} 件",
+    digest_cost: "合計コスト: ${cost}",
+    cost_report_title: "💰 直近24時間のコスト",
+    cost_report_project: "・{project}: ${cost}",
+    line_milestone_body: "変更が {lines} 行を超えました（+{added} / -{removed}）",
+    state_waiting_body: "入力を待っています",
+    waiting_reminder_body: "{minutes} 分間入力を待っています",
+    all_clear_title: "すべてのセッションが完了しました",
+    all_clear_body: "作業中・入力待ちのセッションはありません",
+
+    menu_status_idle: "状態: 待機中",
+    menu_status_sessions: "状態: {count} セッション",
+    menu_status_broker_running: "ブローカー: 稼働中",
+    menu_status_broker_stopped: "ブローカー: 停止",
+    menu_status_client_connected: "クライアント: 接続中",
+    menu_status_client_disconnected: "クライアント: 未接続",
+    menu_status_client_failing: "クライアント: 再起動に失敗",
+    menu_status_unread: "未確認: {count} 件",
+    menu_status_cost: "合計コスト: ${cost}",
+    menu_status_no_sessions: "セッションはありません",
+    menu_status_more_sessions: "ほか {count} セッション",
+    menu_status_calendar_busy: "状態: 予定中（通知を停止中）",
+    menu_profiles: "プロファイル",
+    menu_profiles_empty: "プロファイルはありません",
+    calendar_busy: "予定中のため通知を停止中: {summary}",
+    menu_do_not_disturb: "おやすみモード",
+    menu_settings: "通知設定...",
+    menu_export: "設定エクスポート...",
+    menu_check_update: "更新を確認",
+    menu_download_update: "v{version} をダウンロード...",
+    menu_quit: "終了",
+
+    window_title_main: "Claude Code Notify",
+    window_title_history: "Claude Code Notify - 通知履歴",
+    window_title_settings: "Claude Code Notify - 通知設定",
+    window_title_export: "Claude Code Notify - 設定エクスポート",
+    window_title_diagnostics: "Claude Code Notify - 診断",
+};
+
+static EN: Strings = Strings {
+    task_complete_title: "✅ Task complete",
+    task_complete_body: "✅ Task completed",
+    permission_request_title: "⚠️ Approval request",
+    approval_required: "⚠️ Approval required",
+    tool_permission_required: "Permission required to run {tool}",
+    tool_permission_generic: "Permission required to run a tool",
+    command_label: "Command: {command}",
+    question_header: "❓ Claude has a question",
+    question_default: "A question is waiting",
+    question_window_title: "Question from Claude Code",
+    notification_title: "💬 Notification",
+    input_required: "💬 Input required",
+    waiting_for_input: "Waiting for your input",
+    source_host: "🖥️ Host: {host}",
+    run_summary: "💰 This run: {summary}",
+    run_seconds: "{seconds} s",
+    run_minutes: "{minutes} min",
+    run_lines: "+{added}/-{removed} lines",
+    error_title: "❌ Error",
+    error_hook_title: "❌ {hook} failed",
+    error_exit_code: "exit code {code}",
+    error_hint_missing_dependency: "Install the required commands (jq, mosquitto_pub, mqtt-publish, etc.)",
+    error_hint_not_executable: "Make sure the script is executable (chmod +x)",
+    error_hint_connection: "Make sure the app is running and the port is allowed through the firewall",
+    error_hint_authentication: "Re-export the signing/encryption settings and reinstall the hooks",
+    error_hint_hook: "Run the hook script manually to see the error",
+    error_hint_invalid_payload: "Make sure the hooks match the app version (see Problems in the Diagnostics tab for the received payload)",
+    error_hint_other: "See Problems in the Diagnostics tab for details",
+    invalid_payload_title: "⚠️ Invalid hook payload",
+    invalid_payload_json: "Not valid JSON (line {line}, column {column})",
+    invalid_payload_not_object: "Not a JSON object",
+    invalid_payload_missing_field: "Missing field `{field}`",
+    invalid_payload_wrong_type: "Field `{field}` must be {expected} (got {found})",
+    outdated_hooks_title: "🔄 Hook scripts are out of date",
+    outdated_hooks_body: "Hook scripts v{installed} installed, v{current} available — re-export recommended",
+    toast_dismiss: "Dismiss",
+    toast_allow: "Allow",
+    toast_deny: "Deny",
+    toast_open: "Open",
+    toast_mute_session: "Mute session",
+    toast_snooze: "Snooze 10 min",
+    catch_up_summary_title: "Notifications before launch",
+    catch_up_summary_body: "You missed {count} notifications while the app was not running. See the history for details",
+    deferred_summary_title: "Notifications while in full screen",
+    deferred_summary_body: "{count} notifications arrived while you were in full screen",
+    speech_finished_one: "{project} finished",
+    speech_finished_many: "{count} projects finished: {projects}",
+    speech_separator: ", ",
+    test_toast_title: "Test notification",
+    test_toast_body: "Toast notifications are working",
+    heartbeat_silent_title: "⚠️ Hooks stopped reporting",
+    heartbeat_silent_body: "Hooks on {host} silent for {minutes} min",
+    flooding_title: "⚠️ {source} is flooding",
+    flooding_body: "Received over {count} events in {window}s, ignoring its events for {mute}s. Check whether a hook is looping",
+    client_restart_failed_title: "⚠️ MQTT client stopped",
+    client_restart_failed_body: "Stopped {count} times in a row after restarting. Notifications may not arrive",
+    port_fallback_title: "⚠️ Broker port changed",
+    port_fallback_body: "Port {requested} is in use, so the broker started on port {port}. Export the scripts again",
+    ip_changed_title: "⚠️ IP address changed",
+    ip_changed_body: "This PC's IP address changed from {old} to {new}. Update the host on your remote machines",
+    ip_changed_pushed_body: "Pushed the new host {new} to {pushed} of {total} machines",
+    update_available_title: "Version {version} is available",
+    update_open_download: "Click to open the download page",
+    update_up_to_date: "You're up to date",
+    update_current_version: "Current version: {version}",
+    digest_hourly_title: "📋 Hourly digest",
+    digest_daily_title: "📋 Daily digest",
+    digest_completed: "Tasks completed: {count}",
+    digest_project: "• {project}: {count}",
+    digest_more_projects: "• {count} more projects",
+    digest_permission_requests: "Permission requests: {count}",
+    digest_notifications: "Other notifications: {count}",
+    digest_pending: "Pending approvals: {count}",
+    digest_cost: "Total cost: ${cost}",
+    cost_report_title: "💰 Cost in the last 24 hours",
+    cost_report_project: "• {project}: ${cost}",
+    line_milestone_body: "Changed over {lines} lines (+{added} / -{removed})",
+    state_waiting_body: "Waiting for your input",
+    waiting_reminder_body: "Has been waiting for {minutes} min",
+    all_clear_title: "All sessions are done",
+    all_clear_body: "No session is working or waiting for input",
+
+    menu_status_idle: "Status: Idle",
+    menu_status_sessions: "Status: {count} sessions",
+    menu_status_broker_running: "Broker: running",
+    menu_status_broker_stopped: "Broker: stopped",
+    menu_status_client_connected: "Client: connected",
+    menu_status_client_disconnected: "Client: disconnected",
+    menu_status_client_failing: "Client: restart failing",
+    menu_status_unread: "Unread: {count}",
+    menu_status_cost: "Total cost: ${cost}",
+    menu_status_no_sessions: "No sessions",
+    menu_status_more_sessions: "+{count} more sessions",
+    menu_status_calendar_busy: "Status: In a meeting (muted)",
+    menu_profiles: "Profile",
+    menu_profiles_empty: "No profiles",
+    calendar_busy: "Muted during calendar event: {summary}",
+    menu_do_not_disturb: "Do not disturb",
+    menu_settings: "Notification settings...",
+    menu_export: "Export configuration...",
+    menu_check_update: "Check for updates",
+    menu_download_update: "Download v{version}...",
+    menu_quit: "Quit",
+
+    window_title_main: "Claude Code Notify",
+    window_title_history: "Claude Code Notify - History",
+    window_title_settings: "Claude Code Notify - Settings",
+    window_title_export: "Claude Code Notify - Export",
+    window_title_diagnostics: "Claude Code Notify - Diagnostics",
+};
+
+/// テンプレート内の `{name}` プレースホルダーを置換する
+pub fn fill(template: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(template.to_string(), |acc, (name, value)| {
+        acc.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Language::from_locale("ja-JP"), Language::Ja);
+        assert_eq!(Language::from_locale("ja_JP.UTF-8"), Language::Ja);
+        assert_eq!(Language::from_locale("en-US"), Language::En);
+        assert_eq!(Language::from_locale("de-DE"), Language::En);
+        assert_eq!(Language::from_locale(""), Language::En);
+    }
+
+    #[test]
+    fn test_explicit_setting_ignores_os_locale() {
+        assert_eq!(LanguageSetting::Ja.resolve(), Language::Ja);
+        assert_eq!(LanguageSetting::En.resolve(), Language::En);
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        let ja = fill(Language::Ja.strings().tool_permission_required, &[("tool", "Bash")]);
+        assert_eq!(ja, "Bash の実行許可が必要です");
+
+        let en = fill(Language::En.strings().command_label, &[("command", "npm install")]);
+        assert_eq!(en, "Command: npm install");
+    }
+
+    #[test]
+    fn test_window_title_for_tab() {
+        let strings = Language::En.strings();
+        assert_eq!(strings.window_title_for_tab("settings"), "Claude Code Notify - Settings");
+        assert_eq!(strings.window_title_for_tab("unknown"), "Claude Code Notify");
+    }
+
+    #[test]
+    fn test_setting_serialization() {
+        let json = serde_json::to_string(&LanguageSetting::Auto).unwrap();
+        assert_eq!(json, "\"auto\"");
+        let parsed: LanguageSetting = serde_json::from_str("\"en\"").unwrap();
+        assert_eq!(parsed, LanguageSetting::En);
+    }
+}
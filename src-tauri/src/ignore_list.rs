@@ -0,0 +1,59 @@
+//! 無視するパスのモジュール
+//!
+//! 一時的な作業用のディレクトリや CI のランナーなど、設定したパスからのイベントを完全に無視する。
+//! 無視したイベントは通知・履歴・セッションの追跡・スクリプトのいずれにも渡さない。
+//! パターンはプロジェクトの別名と同じ形式（パスまたはディレクトリ名、`*` は任意の文字列）で指定する。
+
+use crate::project_aliases;
+use serde::{Deserialize, Serialize};
+
+/// 無視するパスの設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IgnoreListSettings {
+    /// 無視する作業ディレクトリのパスまたは名前
+    pub paths: Vec<String>,
+}
+
+impl IgnoreListSettings {
+    /// 作業ディレクトリからのイベントを無視するか
+    pub fn is_ignored(&self, cwd: &str) -> bool {
+        self.paths.iter().any(|pattern| project_aliases::path_matches(pattern, cwd))
+    }
+
+    /// ペイロードの作業ディレクトリ（`cwd`）が無視するパスであれば、その作業ディレクトリを返す
+    ///
+    /// 作業ディレクトリを含まないペイロード（追加のトピックなど）は無視しない。
+    pub fn ignored_cwd(&self, payload: &str) -> Option<String> {
+        if self.paths.is_empty() {
+            return None;
+        }
+        let value: serde_json::Value = serde_json::from_str(payload).ok()?;
+        let cwd = value.get("cwd")?.as_str()?;
+        self.is_ignored(cwd).then(|| cwd.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignores_matching_payloads() {
+        let settings = IgnoreListSettings {
+            paths: vec!["/tmp".to_string(), "scratch-*".to_string(), "C:\\actions-runner".to_string()],
+        };
+        assert!(settings.is_ignored("/tmp/try-1"));
+        assert!(settings.is_ignored("/home/me/scratch-api"));
+        assert!(settings.is_ignored("c:\\actions-runner\\_work\\app"));
+        assert!(!settings.is_ignored("/home/me/app"));
+
+        assert_eq!(
+            settings.ignored_cwd(r#"{"session_id":"s1","cwd":"/tmp/try-1"}"#),
+            Some("/tmp/try-1".to_string())
+        );
+        assert_eq!(settings.ignored_cwd(r#"{"cwd":"/home/me/app"}"#), None);
+        assert_eq!(settings.ignored_cwd(r#"{"message":"deploy done"}"#), None);
+        assert_eq!(settings.ignored_cwd("not json"), None);
+    }
+}
@@ -3,32 +3,110 @@
 //! A Tauri v2 application that provides desktop notifications
 //! for Claude Code task completions via MQTT.
 
+mod acl;
+mod ack;
+mod api_tokens;
+mod attachment;
+mod attention;
 mod audio;
 mod broker;
+mod broker_listeners;
+mod calendar;
+mod catch_up;
+mod call_detection;
+mod channel_test;
+mod claude_settings;
+mod cli;
 mod client;
+mod cost_report;
+mod credentials;
+mod custom_topics;
+mod dev_events;
+mod digest;
+mod discovery;
+mod encryption;
 mod export;
+mod external_broker;
+mod fullscreen;
+mod handlers;
+mod heartbeat;
+mod hook_templates;
+mod hook_version;
+mod host_rules;
+mod host_update;
+mod i18n;
+mod ignore_list;
+mod metrics_history;
 mod notification_history;
 mod notification_state;
+mod permission_response;
+mod problems;
+mod profile_rules;
+mod profiles;
+mod project_aliases;
+mod project_rules;
+mod question_prompt;
+mod quick_actions;
+mod rate_limit;
+mod redaction;
+mod rest_api;
+mod routing;
+mod script_hook;
+mod security_events;
+mod session_alerts;
+mod session_control;
+mod stale_events;
 mod settings;
+mod signing;
+mod speech;
 mod state;
 mod taskbar;
 mod templates;
+mod text;
+mod timestamps;
+mod toast;
+mod transcripts;
 mod tray;
 mod tray_flash;
-
+mod tray_theme;
+mod updater;
+mod validation;
+mod webhook;
+mod window_mode;
+mod window_placement;
+
+use attention::{AttentionProgress, AttentionSignal};
 use broker::MqttBroker;
 use client::{topics, MqttMessage};
+use i18n::Strings;
 use notification_history::{NotificationEventType, NotificationHistoryEntry, NotificationHistoryManager};
-use notification_state::NotificationState;
+use notification_state::{BadgeKind, NotificationState, PersistedState};
 use serde::{Deserialize, Serialize};
 use settings::NotificationSettings;
-use state::{SessionManager, SessionNameManager, StatusPayload};
+use state::{RunSummary, SessionManager, SessionNameManager};
+use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use tauri::{Emitter, Manager};
-use tauri_plugin_notification::NotificationExt;
-use tracing::{error, info, warn};
+use toast::NotificationPriority;
+use tauri_plugin_store::StoreExt;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::prelude::*;
 use tracing_subscriber::EnvFilter;
 
+/// トレイのステータスメニューを更新する間隔
+const TRAY_STATUS_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Worker threads of the shared runtime (MQTT traffic is light, so a few are enough)
+const RUNTIME_WORKER_THREADS: usize = 2;
+
+/// Max characters of unparsed hook content shown in a notification
+const MAX_RAW_CONTENT_LENGTH: usize = 100;
+
+/// 未読数・承認待ちを保存するストア
+const NOTIFICATION_STATE_FILE: &str = "notification_state.json";
+const NOTIFICATION_STATE_KEY: &str = "state";
+
 /// Payload structure for stop events from Claude Code
 #[derive(Debug, Deserialize)]
 struct StopEventPayload {
@@ -40,8 +118,40 @@ struct StopEventPayload {
     /// Legacy: Human-readable session name (deprecated, use session_id instead)
     #[allow(dead_code)]
     session_name: Option<String>,
-    #[allow(dead_code)]
-    timestamp: Option<String>,
+    /// Host the hook runs on (to tell machines apart)
+    #[serde(default)]
+    host: Option<String>,
+    /// Image to attach (data URI, http(s) URL or a file path on this machine)
+    #[serde(default)]
+    image: Option<String>,
+    /// Version of the templates the hook was generated from (absent in scripts before v2)
+    #[serde(default)]
+    template_version: Option<u32>,
+    /// Excerpt of Claude's final message, read from the transcript by the hook
+    #[serde(default)]
+    last_message: Option<String>,
+    /// Transcript of the session (path on the machine the hook runs on)
+    #[serde(default)]
+    transcript_path: Option<String>,
+    /// WSL distribution the hook runs in (to open the transcript from Windows)
+    #[serde(default)]
+    wsl_distro: Option<String>,
+    /// Time the hook sent the event (None when missing or malformed)
+    #[serde(default, deserialize_with = "timestamps::deserialize")]
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl StopEventPayload {
+    /// Claude's final message on one line (None when the hook did not send it)
+    fn last_message(&self) -> Option<String> {
+        let message = self.last_message.as_deref()?.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!message.is_empty()).then_some(message)
+    }
+
+    /// Transcript path readable from this machine
+    fn transcript_path(&self) -> Option<String> {
+        transcripts::local_path(self.transcript_path.as_deref(), self.wsl_distro.as_deref())
+    }
 }
 
 /// Payload structure for permission request events from Claude Code
@@ -56,8 +166,31 @@ struct PermissionRequestPayload {
     #[allow(dead_code)]
     session_name: Option<String>,
     content: PermissionRequestContent,
-    #[allow(dead_code)]
-    timestamp: Option<String>,
+    /// Set when the hook waits for a decision on claude-code/response/<session_id>
+    #[serde(default)]
+    request_id: Option<String>,
+    /// Host the hook runs on (to tell machines apart)
+    #[serde(default)]
+    host: Option<String>,
+    /// Image to attach (data URI, http(s) URL or a file path on this machine)
+    #[serde(default)]
+    image: Option<String>,
+    /// WSL distribution the hook runs in (to open the transcript from Windows)
+    #[serde(default)]
+    wsl_distro: Option<String>,
+    /// Version of the templates the hook was generated from (absent in scripts before v2)
+    #[serde(default)]
+    template_version: Option<u32>,
+    /// Time the hook sent the event (None when missing or malformed)
+    #[serde(default, deserialize_with = "timestamps::deserialize")]
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl PermissionRequestPayload {
+    /// Transcript path readable from this machine
+    fn transcript_path(&self) -> Option<String> {
+        transcripts::local_path(self.content.transcript_path.as_deref(), self.wsl_distro.as_deref())
+    }
 }
 
 /// Content of a permission request (tool name, input, etc.)
@@ -65,6 +198,9 @@ struct PermissionRequestPayload {
 struct PermissionRequestContent {
     tool_name: Option<String>,
     tool_input: Option<serde_json::Value>,
+    /// Transcript of the session (the hook forwards Claude Code's input as is)
+    #[serde(default)]
+    transcript_path: Option<String>,
     /// Fallback raw content when JSON parsing fails in the hook script
     raw: Option<String>,
 }
@@ -81,8 +217,28 @@ struct NotificationEventPayload {
     #[allow(dead_code)]
     session_name: Option<String>,
     content: NotificationContent,
-    #[allow(dead_code)]
-    timestamp: Option<String>,
+    /// Host the hook runs on (to tell machines apart)
+    #[serde(default)]
+    host: Option<String>,
+    /// Image to attach (data URI, http(s) URL or a file path on this machine)
+    #[serde(default)]
+    image: Option<String>,
+    /// WSL distribution the hook runs in (to open the transcript from Windows)
+    #[serde(default)]
+    wsl_distro: Option<String>,
+    /// Version of the templates the hook was generated from (absent in scripts before v2)
+    #[serde(default)]
+    template_version: Option<u32>,
+    /// Time the hook sent the event (None when missing or malformed)
+    #[serde(default, deserialize_with = "timestamps::deserialize")]
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl NotificationEventPayload {
+    /// Transcript path readable from this machine
+    fn transcript_path(&self) -> Option<String> {
+        transcripts::local_path(self.content.transcript_path.as_deref(), self.wsl_distro.as_deref())
+    }
 }
 
 /// Content of a notification (elicitation dialogs, etc.)
@@ -95,50 +251,126 @@ struct NotificationContent {
     message: Option<String>,
     #[allow(dead_code)]
     question: Option<String>,
+    /// Transcript of the session (the hook forwards Claude Code's input as is)
+    #[serde(default)]
+    transcript_path: Option<String>,
     /// Fallback raw content when JSON parsing fails in the hook script
     raw: Option<String>,
 }
 
-fn init_logging() {
-    let filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+/// 実行中にログレベルを変更するためのハンドル（`--log-level` を2つ目の起動で指定した場合に使う）
+pub type LogFilterHandle = tracing_subscriber::reload::Handle<EnvFilter, tracing_subscriber::Registry>;
 
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_target(false)
+/// ログを初期化する（`--log-level` を RUST_LOG より優先する）
+fn init_logging(level: Option<&str>) -> LogFilterHandle {
+    let filter = match level {
+        Some(level) => EnvFilter::new(level),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    };
+    let (filter, handle) = tracing_subscriber::reload::Layer::new(filter);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_target(false))
         .init();
+    handle
 }
 
 pub struct AppState {
     pub broker: Option<MqttBroker>,
+    /// Runtime shared by the MQTT client, the message handler and async Tauri commands
+    pub runtime: Arc<tokio::runtime::Runtime>,
     pub session_manager: Arc<SessionManager>,
     pub session_name_manager: Arc<SessionNameManager>,
 }
 
+/// 通知の発生元（トーストのクリックで ack や承認依頼への応答を送るために使う）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NotificationOrigin<'a> {
+    pub session_id: Option<&'a str>,
+    /// 応答を待っている承認依頼の履歴ID
+    pub pending_request: Option<u64>,
+    /// 回答を待っている AskUserQuestion の履歴ID
+    pub pending_question: Option<u64>,
+    /// イベントに添付された画像（トーストに表示する）
+    pub image: Option<&'a Path>,
+    /// 発生元の作業ディレクトリ（プロジェクトの色に使う）
+    pub cwd: Option<&'a str>,
+    /// 起動直後に届いた古いイベントか（トースト・通知音を出さず、届き終わった後にまとめて知らせる）
+    pub backlog: bool,
+    /// 設定した時間より古いイベントか（トースト・通知音を出さない）
+    pub stale: bool,
+    /// Webhook に送るイベント（フックのイベントのみ、ルーティングで送るかを決める）
+    pub webhook: Option<&'a webhook::WebhookEvent>,
+    /// 読み上げるプロジェクト名（タスク完了のみ、短い間に完了したものはまとめて読み上げる）
+    pub spoken_project: Option<&'a str>,
+}
+
+impl<'a> NotificationOrigin<'a> {
+    /// セッションからの通知（応答を待たない）
+    pub fn session(session_id: Option<&'a str>) -> Self {
+        Self {
+            session_id,
+            ..Self::default()
+        }
+    }
+}
+
+/// 点滅・バッジの更新に失敗した場合はログに記録する
+fn log_attention_error(signal: &dyn AttentionSignal, result: Result<(), String>) {
+    if let Err(e) = result {
+        error!("Failed to update {} attention signal: {}", signal.name(), e);
+    }
+}
+
 /// 通知を一元管理するマネージャー
 /// 設定に基づいて、音声・タスクバー・トレイアイコン・Toast通知を制御する
 pub struct NotificationManager {
     settings: Arc<RwLock<NotificationSettings>>,
     state: NotificationState,
     tray_flasher: tray_flash::TrayFlasher,
+    /// タスクバー・Dock の表示（実行中のプラットフォームに合わせて選ぶ）
+    attention: Box<dyn AttentionSignal>,
+    deferred: fullscreen::DeferredQueue,
+    calendar: calendar::CalendarSchedule,
+    /// 起動直後に届いた古いイベントの数え上げ
+    catch_up: catch_up::CatchUp,
+    /// 読み上げを待っているタスク完了
+    speech: speech::SpeechQueue,
 }
 
-// NotificationManager を Send + Sync にするため、HWND を保持しない
-unsafe impl Send for NotificationManager {}
-unsafe impl Sync for NotificationManager {}
-
 impl NotificationManager {
     /// 新しい NotificationManager を作成
     pub fn new(app: &tauri::AppHandle) -> Self {
         let settings = settings::load_settings(app);
+        let tray_icon = settings
+            .tray_icon_theme
+            .icon_bytes(tray_theme::os_prefers_dark(app));
 
         Self {
             settings: Arc::new(RwLock::new(settings)),
             state: NotificationState::new(),
-            tray_flasher: tray_flash::TrayFlasher::new(),
+            tray_flasher: tray_flash::TrayFlasher::new(app, tray_icon),
+            attention: attention::for_platform(app),
+            deferred: fullscreen::DeferredQueue::new(),
+            calendar: calendar::CalendarSchedule::new(),
+            catch_up: catch_up::CatchUp::default(),
+            speech: speech::SpeechQueue::default(),
         }
     }
 
+    /// 現在のテーマに対応するトレイアイコン
+    pub fn tray_icon(&self, app: &tauri::AppHandle) -> &'static [u8] {
+        self.get_settings()
+            .tray_icon_theme
+            .icon_bytes(tray_theme::os_prefers_dark(app))
+    }
+
+    /// トレイアイコンのテーマ設定・OS テーマの変更を反映する
+    pub fn apply_tray_theme(&self, app: &tauri::AppHandle) {
+        self.tray_flasher.set_normal_icon(self.tray_icon(app));
+    }
+
     /// 設定を更新
     pub fn update_settings(&self, new_settings: NotificationSettings) {
         if let Ok(mut settings) = self.settings.write() {
@@ -151,25 +383,177 @@ impl NotificationManager {
         self.settings.read().map(|s| s.clone()).unwrap_or_default()
     }
 
+    /// 現在の言語設定に対応する文字列テーブルを取得
+    pub fn strings(&self) -> &'static Strings {
+        self.get_settings().language.resolve().strings()
+    }
+
     /// 通知を発火（すべての通知チャネルを統合管理）
-    pub fn notify(&self, app: &tauri::AppHandle, title: &str, body: &str) {
+    ///
+    /// 発生元のセッションがある場合、トーストのクリックで ack を送信する。
+    /// 応答を待っている承認依頼であれば、トーストに「許可」「拒否」ボタンを表示する。
+    /// 回答を待っている質問であれば、トーストのクリックで回答ウィンドウを開く。
+    pub fn notify(
+        &self,
+        app: &tauri::AppHandle,
+        title: &str,
+        body: &str,
+        event_type: NotificationEventType,
+        priority: NotificationPriority,
+        origin: NotificationOrigin<'_>,
+    ) {
         let settings = self.get_settings();
+        // API キー・パスワードなどはトースト・ルーティング・Webhook に渡す前にマスクする
+        let title = settings.redaction.redact(title);
+        let body = settings.redaction.redact(body);
+
+        // おやすみモード・スヌーズ中・ミュートしたセッションの通知は表示しない（履歴には記録済み）
+        if self.state.is_do_not_disturb() {
+            info!("Notification suppressed in do not disturb mode: {}", title);
+            return;
+        }
+        if self.state.is_snoozed(Instant::now()) {
+            info!("Notification suppressed while snoozed: {}", title);
+            return;
+        }
+        if origin.session_id.is_some_and(|session_id| self.state.is_session_muted(session_id)) {
+            info!("Notification suppressed for muted session: {}", title);
+            return;
+        }
+        if let Some(summary) = self.current_calendar_event() {
+            info!("Notification suppressed during calendar event ({}): {}", summary, title);
+            return;
+        }
+
+        // ルーティングスクリプトと勤務時間で表示・優先度・通知方法を変える
+        let now = chrono::Local::now().naive_local();
+        let event = routing::RoutingEvent {
+            event_type: &event_type,
+            title: &title,
+            body: &body,
+            priority,
+            session_id: origin.session_id,
+            working_hours: settings.routing.working_hours.is_working(now),
+            pushes: settings.routing.working_hours.pushes(now),
+        };
+        let Some(route) = routing::route(app, &settings.routing, event) else {
+            info!("Notification dropped by routing script: {}", title);
+            return;
+        };
+        let (title, body) = (route.title.as_str(), route.body.as_str());
+        let (priority, channels) = (route.priority, route.channels);
+
+        // 発生元のプロジェクトの色（トーストの絵文字・トレイのドットに使う）
+        let project_color = origin
+            .cwd
+            .and_then(|cwd| project_rules::parse_color(&settings.project_color(cwd)));
+
+        // 設定した時間より古いイベントはトーストと通知音を出さず、バッジ・点滅だけで知らせる
+        let demoted = origin.stale;
+        if demoted {
+            info!("Notification for a stale event shown silently: {}", title);
+        }
 
-        // 1. Toast通知
-        if settings.toast_notification_enabled {
-            match app.notification().builder().title(title).body(body).show() {
-                Ok(_) => info!("Toast notification sent"),
-                Err(e) => error!("Failed to show toast notification: {}", e),
+        // 全画面表示中はトーストと通知音を保留し、全画面表示の終了後に表示する
+        let deferred = !demoted && settings.fullscreen.defers(priority) && fullscreen::is_user_busy();
+        if deferred {
+            info!("Notification deferred while in full screen: {}", title);
+            let notification = fullscreen::DeferredNotification {
+                title: title.to_string(),
+                body: body.to_string(),
+                event_type: event_type.clone(),
+                priority,
+            };
+            if self.deferred.push(notification) {
+                watch_fullscreen(app);
             }
         }
 
+        // 起動直後に届いた古いイベントはトーストと通知音を出さず、届き終わった後にまとめて知らせる
+        let missed = !deferred && origin.backlog;
+        if missed {
+            if self.catch_up.absorb() {
+                watch_catch_up(app);
+            }
+            info!("Notification from the startup backlog shown silently: {}", title);
+        }
+
+        // 通話中はトーストと通知音を出さず、バッジ・点滅だけで知らせる
+        let in_call = !deferred && !missed && !demoted && settings.mute_during_calls && call_detection::is_in_call();
+        if in_call {
+            info!("Notification muted during a call: {}", title);
+        }
+
+        // Webhook（全画面表示中・通話中も送る、古いイベントは送らない）
+        if let Some(event) = origin.webhook {
+            if channels.webhook && !missed && !demoted {
+                webhook::send(&settings.webhook, &settings.redaction, event.clone());
+            }
+        }
+
+        // 1. Toast通知（優先度に応じて表示スタイルを切り替える）
+        if settings.toast_notification_enabled && channels.toast && !deferred && !missed && !demoted && !in_call {
+            // 承認依頼の応答ボタンがある場合、クイックアクションは表示しない
+            let actions = if origin.pending_request.is_some() {
+                permission_response::toast_actions(self.strings()).to_vec()
+            } else {
+                quick_actions::toast_actions(self.strings(), origin.session_id.is_some())
+            };
+            let callbacks: Vec<toast::OnActivated> = [
+                origin
+                    .pending_request
+                    .map(|entry_id| permission_response::on_toast_activated(app, entry_id)),
+                origin
+                    .pending_request
+                    .is_none()
+                    .then(|| quick_actions::on_toast_activated(app, origin.session_id)),
+                origin
+                    .pending_question
+                    .map(|entry_id| question_prompt::on_toast_activated(app, entry_id, self.strings())),
+                origin
+                    .session_id
+                    .and_then(|session_id| ack::on_toast_activated(app, session_id, &event_type)),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            let on_activated = (!callbacks.is_empty()).then(|| -> toast::OnActivated {
+                Box::new(move |action: Option<&str>| {
+                    for callback in callbacks {
+                        callback(action);
+                    }
+                })
+            });
+            let title = match project_color.filter(|_| settings.project_rules.color_toasts) {
+                Some(color) => format!("{} {}", project_rules::color_marker(color), title),
+                None => title.to_string(),
+            };
+            let content = toast::ToastContent {
+                title,
+                body: body.to_string(),
+                image: origin.image.map(Path::to_path_buf),
+            };
+            toast::show_toast(app, content, priority, self.strings(), &actions, on_activated);
+        }
+
         // 2. 通知音
-        if settings.sound_enabled {
-            audio::play_notification_sound(settings.sound_volume);
+        if settings.sound_enabled && channels.sound && !deferred && !missed && !demoted && !in_call {
+            let sound = settings.event_sounds.for_event(&event_type);
+            audio::play_notification_sound(sound, settings.sound_volume, settings.playback_policy());
+        }
+
+        // 読み上げ（短い間に完了したプロジェクトは1つの文にまとめる）
+        if let Some(project) = origin.spoken_project {
+            let speaks = settings.speech.enabled && channels.speech && !deferred && !missed && !demoted && !in_call;
+            if speaks && self.speech.push(project, Instant::now()) {
+                watch_speech(app);
+            }
         }
 
         // 3. 未確認カウント増加
         let count = self.state.increment();
+        self.save_state(app);
+        self.refresh_tray_status(app);
 
         // 4. ウィンドウの表示状態を確認
         let window_visible = app
@@ -177,63 +561,452 @@ impl NotificationManager {
             .map(|w| w.is_visible().unwrap_or(false))
             .unwrap_or(false);
 
-        // 5. タスクバー機能（Windows専用、ウィンドウが表示されている場合）
-        #[cfg(windows)]
-        if window_visible {
-            if let Some(window) = app.get_webview_window("main") {
-                if let Some(hwnd) = taskbar::get_hwnd(&window) {
-                    // タスクバー点滅
-                    if settings.taskbar_flash_enabled {
-                        taskbar::flash_taskbar(hwnd, 3);
-                    }
+        // 5. タスクバー・Dock（ウィンドウが表示されている場合）
+        if window_visible && channels.taskbar {
+            let attention = self.attention.as_ref();
+            if settings.taskbar_flash_enabled {
+                log_attention_error(attention, attention.flash(app, &settings));
+            }
 
-                    // バッジ更新
-                    if settings.taskbar_badge_enabled {
-                        if let Err(e) = taskbar::set_overlay_badge(hwnd, count) {
-                            error!("Failed to set overlay badge: {}", e);
-                        }
-                    }
+            // バッジ更新（承認待ちがあれば承認待ち数を優先する）
+            if settings.taskbar_badge_enabled {
+                let badge = self.state.badge().unwrap_or((BadgeKind::Unread, count));
+                log_attention_error(attention, attention.badge(app, Some(badge)));
+            }
+        }
+
+        // 6. トレイアイコン点滅 / 未確認数バッジ（ウィンドウが非表示の場合）
+        if !window_visible && channels.tray {
+            self.tray_flasher
+                .set_dot_color(project_color.filter(|_| settings.project_rules.color_tray_dot));
+            self.start_tray_attention(app, &settings, count);
+        }
+    }
+
+    /// トレイアイコンの点滅か未確認数バッジを開始する
+    fn start_tray_attention(&self, app: &tauri::AppHandle, settings: &NotificationSettings, count: u32) {
+        if !settings.tray_flash_enabled {
+            return;
+        }
+        let result = match settings.tray_attention_mode {
+            tray_flash::TrayAttentionMode::Flash => self.tray_flasher.flash(app, settings),
+            tray_flash::TrayAttentionMode::Badge => {
+                let badge = self.state.badge().unwrap_or((BadgeKind::Unread, count));
+                self.tray_flasher.badge(app, Some(badge))
+            }
+        };
+        log_attention_error(&self.tray_flasher, result);
+    }
+
+    /// トレイアイコンとタスクバー・Dock の点滅を止める（バッジは残す）
+    fn stop_attention(&self, app: &tauri::AppHandle) {
+        for signal in [&self.tray_flasher as &dyn AttentionSignal, self.attention.as_ref()] {
+            log_attention_error(signal, signal.clear(app));
+        }
+    }
+
+    /// 未読数・承認待ち・スヌーズを保存する（異常終了しても再起動後に復元できるよう、変化のたびに保存する）
+    fn save_state(&self, app: &tauri::AppHandle) {
+        let persisted = self.state.snapshot(Instant::now(), chrono::Utc::now());
+        let result = app
+            .store(NOTIFICATION_STATE_FILE)
+            .map_err(|e| e.to_string())
+            .and_then(|store| {
+                let value = serde_json::to_value(&persisted).map_err(|e| e.to_string())?;
+                store.set(NOTIFICATION_STATE_KEY, value);
+                store.save().map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            warn!("Failed to save notification state: {}", e);
+        }
+    }
+
+    /// 保存した通知状態を復元し、バッジと点滅を再開する（起動時）
+    pub fn restore_state(&self, app: &tauri::AppHandle) {
+        let store = match app.store(NOTIFICATION_STATE_FILE) {
+            Ok(store) => store,
+            Err(e) => {
+                warn!("Failed to open notification state store: {}", e);
+                return;
+            }
+        };
+        let persisted: PersistedState = match store.get(NOTIFICATION_STATE_KEY).map(serde_json::from_value) {
+            Some(Ok(persisted)) => persisted,
+            Some(Err(e)) => {
+                warn!("Failed to deserialize notification state: {}", e);
+                return;
+            }
+            None => return,
+        };
+        self.state.restore(&persisted, Instant::now(), chrono::Utc::now());
+
+        self.refresh_badges(app);
+        let window_visible = app
+            .get_webview_window("main")
+            .map(|w| w.is_visible().unwrap_or(false))
+            .unwrap_or(false);
+        if !window_visible && self.state.badge().is_some() {
+            self.start_tray_attention(app, &self.get_settings(), self.state.get());
+        }
+    }
+
+    /// 全画面表示中に保留した通知を表示する
+    fn flush_deferred(&self, app: &tauri::AppHandle) {
+        let settings = self.get_settings();
+        let strings = self.strings();
+        let in_call = settings.mute_during_calls && call_detection::is_in_call();
+        let play = |event_type: &NotificationEventType| {
+            if settings.sound_enabled && !in_call {
+                let sound = settings.event_sounds.for_event(event_type);
+                audio::play_notification_sound(sound, settings.sound_volume, settings.playback_policy());
+            }
+        };
+        let show = |title: String, body: String, priority: NotificationPriority| {
+            if settings.toast_notification_enabled && !in_call {
+                let content = toast::ToastContent { title, body, image: None };
+                toast::show_toast(app, content, priority, strings, &[], None);
+            }
+        };
+
+        match fullscreen::Replay::plan(self.deferred.take()) {
+            None => {}
+            Some(fullscreen::Replay::Each(notifications)) => {
+                for notification in notifications {
+                    play(&notification.event_type);
+                    show(notification.title, notification.body, notification.priority);
                 }
             }
+            Some(fullscreen::Replay::Summary {
+                count,
+                event_type,
+                priority,
+            }) => {
+                play(&event_type);
+                let body = i18n::fill(strings.deferred_summary_body, &[("count", &count.to_string())]);
+                show(strings.deferred_summary_title.to_string(), body, priority);
+            }
         }
+    }
 
-        // 6. トレイアイコン点滅（ウィンドウが非表示の場合）
-        if !window_visible && settings.tray_flash_enabled {
-            self.tray_flasher.start_flash(app);
+    /// 起動直後に見逃した通知の数をまとめて表示する
+    fn flush_catch_up(&self, app: &tauri::AppHandle) {
+        let count = self.catch_up.take();
+        let settings = self.get_settings();
+        if count == 0 || !settings.toast_notification_enabled || self.state.is_do_not_disturb() {
+            return;
         }
+        let strings = self.strings();
+        let content = toast::ToastContent {
+            title: strings.catch_up_summary_title.to_string(),
+            body: i18n::fill(strings.catch_up_summary_body, &[("count", &count.to_string())]),
+            image: None,
+        };
+        toast::show_toast(app, content, NotificationPriority::Normal, strings, &[], None);
     }
 
     /// 通知状態をリセット（ウィンドウがフォーカスを得た時など）
+    ///
+    /// 承認待ちは未読と別に管理しているため、リセット後も承認待ちのバッジは残す。
     pub fn reset(&self, app: &tauri::AppHandle) {
         self.state.reset();
+        self.save_state(app);
 
-        // トレイアイコン点滅を停止
-        self.tray_flasher.stop_flash(app);
+        // トレイアイコン・タスクバーの点滅を停止
+        self.stop_attention(app);
 
-        #[cfg(windows)]
-        if let Some(window) = app.get_webview_window("main") {
-            if let Some(hwnd) = taskbar::get_hwnd(&window) {
-                if let Err(e) = taskbar::clear_overlay_badge(hwnd) {
-                    error!("Failed to clear overlay badge: {}", e);
-                }
-                taskbar::stop_flash(hwnd);
-            }
+        self.refresh_badges(app);
+        self.refresh_tray_status(app);
+    }
+
+    /// トレイのツールチップとステータスメニューを更新（未確認数や設定が変わったとき）
+    pub fn refresh_tray_status(&self, app: &tauri::AppHandle) {
+        let Some(state) = app.try_state::<std::sync::Mutex<AppState>>() else {
+            return;
+        };
+        let session_manager = match state.lock() {
+            Ok(state) => state.session_manager.clone(),
+            Err(_) => return,
+        };
+        update_tray_status(app, &session_manager, self);
+    }
+
+    /// セッションからの通知をミュートする（アプリの再起動まで）
+    pub fn mute_session(&self, session_id: &str) {
+        self.state.mute_session(session_id);
+    }
+
+    /// 一定時間すべての通知を止める
+    pub fn snooze(&self, app: &tauri::AppHandle, duration: Duration) {
+        self.state.snooze_until(Instant::now() + duration);
+        self.save_state(app);
+    }
+
+    /// おやすみモードを切り替え、トレイメニューのチェックを更新する
+    pub fn set_do_not_disturb(&self, app: &tauri::AppHandle, enabled: bool) {
+        self.state.set_do_not_disturb(enabled);
+        tray::rebuild_menu(app, self.strings());
+    }
+
+    /// おやすみモード中か
+    pub fn is_do_not_disturb(&self) -> bool {
+        self.state.is_do_not_disturb()
+    }
+
+    /// 取得したカレンダーの予定
+    pub fn calendar(&self) -> &calendar::CalendarSchedule {
+        &self.calendar
+    }
+
+    /// 最中のカレンダーの予定の件名（予定中は通知を止める）
+    pub fn current_calendar_event(&self) -> Option<String> {
+        self.calendar.current_event(chrono::Utc::now())
+    }
+
+    /// 承認依頼を承認待ちとして記録する（バッジは続く通知で更新される）
+    pub fn add_pending_approval(&self, app: &tauri::AppHandle, session_id: &str) -> u32 {
+        let total = self.state.add_pending_approval(session_id, Instant::now());
+        self.save_state(app);
+        total
+    }
+
+    /// セッションの承認待ちを解除する（停止イベントを受け取った時）
+    pub fn clear_pending_approvals(&self, app: &tauri::AppHandle, session_id: &str) -> bool {
+        let cleared = self.state.clear_pending_approvals(session_id);
+        if cleared {
+            self.save_state(app);
+            self.refresh_badges(app);
+        }
+        cleared
+    }
+
+    /// セッションが作業を再開した場合に承認待ちを解除する（ステータスが変化した時）
+    pub fn resume_session(&self, app: &tauri::AppHandle, session_id: &str) -> bool {
+        let resumed = self.state.resume_session(session_id, Instant::now());
+        if resumed {
+            self.save_state(app);
+            self.refresh_badges(app);
+        }
+        resumed
+    }
+
+    /// 承認待ちの件数を取得
+    pub fn pending_approvals(&self) -> u32 {
+        self.state.pending_approvals()
+    }
+
+    /// 未確認数・承認待ち数をバッジに反映する（点滅は開始しない）
+    fn refresh_badges(&self, app: &tauri::AppHandle) {
+        let settings = self.get_settings();
+        let badge = self.state.badge();
+
+        // 承認待ちがある間はタスクバー・Dock の進捗を一時停止の色で表示する
+        let attention = self.attention.as_ref();
+        let window_badge = badge.filter(|_| settings.taskbar_badge_enabled);
+        let progress = match window_badge {
+            Some((BadgeKind::PendingApproval, _)) => AttentionProgress::Waiting,
+            _ => AttentionProgress::Hidden,
+        };
+        log_attention_error(attention, attention.badge(app, window_badge));
+        log_attention_error(attention, attention.progress(app, progress));
+
+        if settings.tray_flash_enabled
+            && settings.tray_attention_mode == tray_flash::TrayAttentionMode::Badge
+        {
+            log_attention_error(&self.tray_flasher, self.tray_flasher.badge(app, badge));
         }
     }
 
+    /// 既読にした通知の分だけ未確認数を減らし、なくなった場合は点滅を止める
+    pub fn mark_read(&self, app: &tauri::AppHandle, count: u32) {
+        if count == 0 {
+            return;
+        }
+        self.state.decrement(count);
+        self.save_state(app);
+
+        if self.state.badge().is_none() {
+            self.stop_attention(app);
+        }
+        self.refresh_badges(app);
+        self.refresh_tray_status(app);
+    }
+
     /// 未確認カウントを取得
-    #[allow(dead_code)]
     pub fn get_unread_count(&self) -> u32 {
         self.state.get()
     }
 }
 
+/// セッションの通知を既読にし、その分だけ未確認数を減らす（作業の再開時）
+fn mark_session_read(
+    app: &tauri::AppHandle,
+    notification_manager: &NotificationManager,
+    history_manager: &NotificationHistoryManager,
+    session_id: &str,
+) {
+    let marked = match history_manager.mark_session_as_read(app, session_id) {
+        Ok(marked) => marked,
+        Err(e) => {
+            warn!("Failed to mark session notifications as read: {}", e);
+            return;
+        }
+    };
+    if marked.is_empty() {
+        return;
+    }
+    info!("Marked {} notifications as read for resumed session {}", marked.len(), session_id);
+    for entry in &marked {
+        ack::publish_ack(app, &ack::AckPayload::from_entry(entry));
+    }
+    notification_manager.mark_read(app, marked.len() as u32);
+    let _ = app.emit("notification-added", ());
+}
+
+/// セッションの未確定の承認依頼の履歴に、そのあとのセッションの変化を記録する
+fn resolve_permission_requests(
+    app: &tauri::AppHandle,
+    history_manager: &NotificationHistoryManager,
+    session_id: &str,
+    outcome: notification_history::PermissionOutcome,
+) {
+    match history_manager.resolve_permission_requests(app, session_id, outcome, chrono::Utc::now()) {
+        Ok(0) => {}
+        Ok(resolved) => {
+            info!("Resolved {} permission requests for {} as {:?}", resolved, session_id, outcome);
+            let _ = app.emit("notification-added", ());
+        }
+        Err(e) => warn!("Failed to resolve permission requests: {}", e),
+    }
+}
+
+/// 起動直後の古いイベントが届き終わるのを待ち、見逃した通知の数を表示する
+fn watch_catch_up(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>().map(|state| state.inner().clone()) else {
+            return;
+        };
+        while !notification_manager.catch_up.is_quiet(Instant::now()) {
+            std::thread::sleep(catch_up::QUIET_PERIOD);
+        }
+        notification_manager.flush_catch_up(&app);
+    });
+}
+
+/// タスクの完了が途切れるのを待ち、完了したプロジェクトをまとめて読み上げる
+fn watch_speech(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>().map(|state| state.inner().clone()) else {
+            return;
+        };
+        loop {
+            while !notification_manager.speech.is_quiet(Instant::now()) {
+                std::thread::sleep(speech::QUIET_PERIOD);
+            }
+            let projects = notification_manager.speech.take();
+            let Some(text) = speech::summary(notification_manager.strings(), &projects) else {
+                break;
+            };
+            speech::speak(&text);
+        }
+    });
+}
+
+/// 全画面表示の終了を待って、保留した通知を表示する
+fn watch_fullscreen(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        while fullscreen::is_user_busy() {
+            std::thread::sleep(fullscreen::POLL_INTERVAL);
+        }
+        if let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>() {
+            notification_manager.flush_deferred(&app);
+        }
+    });
+}
+
+/// 診断タブに表示するブローカーの状態
+#[derive(Debug, Clone, Serialize)]
+struct BrokerDiagnostics {
+    running: bool,
+    metrics: broker::BrokerMetrics,
+    /// ステータスを送信しているセッションをプロジェクト・ホストごとにまとめたもの（新しい順）
+    projects: Vec<state::ProjectSummary>,
+}
+
+/// Tauriコマンド: ブローカーのメトリクスと接続中のセッションを取得
+#[tauri::command]
+fn get_broker_metrics(state: tauri::State<'_, std::sync::Mutex<AppState>>) -> Result<BrokerDiagnostics, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    let (running, metrics) = match state.broker.as_ref() {
+        Some(broker) => (broker.is_running(), broker.metrics()),
+        None => (false, broker::BrokerMetrics::default()),
+    };
+
+    Ok(BrokerDiagnostics {
+        running,
+        metrics,
+        projects: state.session_manager.project_summaries(),
+    })
+}
+
+/// ホームタブに表示するブローカーの状態
+#[derive(Debug, Clone, Serialize)]
+struct BrokerStatus {
+    running: bool,
+    /// 外部のブローカーのホスト（組み込みのブローカーを使っている場合は None）
+    external_host: Option<String>,
+    /// 待ち受けポート（`--port` で変更できる。外部のブローカーの場合はそのポート）
+    port: u16,
+    /// localhost 専用リスナーのポート（開いていない場合は None）
+    local_port: Option<u16>,
+    /// 起動してからの秒数（停止中は None）
+    uptime_secs: Option<u64>,
+    /// アプリ自身の MQTT クライアントが接続しているか
+    client_connected: bool,
+}
+
+/// Tauriコマンド: ブローカーの稼働状況・待ち受けポート・稼働時間を取得
+#[tauri::command]
+fn get_broker_status(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, std::sync::Mutex<AppState>>,
+) -> Result<BrokerStatus, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    let mqtt_broker = state.broker.as_ref();
+    // クライアントはブローカーの起動を待ってから接続する
+    let client_connected = app
+        .try_state::<client::ConnectionStatus>()
+        .is_some_and(|status| status.is_connected());
+
+    // 外部のブローカーは接続できているかで稼働状況を表す
+    if let Some(endpoint) = app.try_state::<client::BrokerEndpoint>().filter(|endpoint| endpoint.external) {
+        return Ok(BrokerStatus {
+            running: client_connected,
+            external_host: Some(endpoint.host.clone()),
+            port: endpoint.port,
+            local_port: None,
+            uptime_secs: None,
+            client_connected,
+        });
+    }
+
+    Ok(BrokerStatus {
+        running: mqtt_broker.is_some_and(|b| b.is_running()),
+        external_host: None,
+        port: mqtt_broker.map_or(broker::DEFAULT_PORT, |b| b.port()),
+        local_port: mqtt_broker.and_then(|b| b.local_port()),
+        uptime_secs: mqtt_broker.and_then(|b| b.uptime()).map(|uptime| uptime.as_secs()),
+        client_connected,
+    })
+}
+
+/// Tauriコマンド: ステータスを送信中のセッションの集計を取得
 #[tauri::command]
-fn get_broker_status(state: tauri::State<'_, std::sync::Mutex<AppState>>) -> bool {
-    state
-        .lock()
-        .map(|s| s.broker.as_ref().map(|b| b.is_running()).unwrap_or(false))
-        .unwrap_or(false)
+fn get_metrics(state: tauri::State<'_, std::sync::Mutex<AppState>>) -> Result<state::AggregatedMetrics, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.session_manager.get_metrics())
 }
 
 /// Tauriコマンド: 設定を保存（NotificationManagerのメモリ内設定も同時に更新）
@@ -242,12 +1015,65 @@ fn save_settings_command(
     app: tauri::AppHandle,
     settings: NotificationSettings,
     notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+) -> Result<(), String> {
+    // 有効なプロファイルにも反映
+    let mut profiles = settings::load_profiles(&app);
+    profiles.update_active(&settings);
+    settings::save_profiles(&app, &profiles)?;
+
+    apply_settings(&app, &notification_manager, settings)?;
+    info!("Settings saved and NotificationManager updated");
+    Ok(())
+}
+
+/// 設定をファイルに保存し、NotificationManager・トレイ・ブローカーに反映する
+pub(crate) fn apply_settings(
+    app: &tauri::AppHandle,
+    notification_manager: &NotificationManager,
+    settings: NotificationSettings,
 ) -> Result<(), String> {
     // ファイルに保存
-    settings::save_settings(&app, &settings)?;
+    settings::save_settings(app, &settings)?;
+    let previous_topics = notification_manager.get_settings().custom_topics.filters();
+    let previous_window_mode = notification_manager.get_settings().window_mode;
+    // 通知履歴の保持期間・機密情報のマスク・内容を残さない設定を反映
+    if let Some(history_manager) = app.try_state::<Arc<NotificationHistoryManager>>() {
+        history_manager.set_retention(settings.history_retention.clone());
+        history_manager.set_redaction(settings.redaction.clone());
+        history_manager.set_metadata_only(settings.history_metadata_only);
+        // 内容を残さない設定に切り替えた場合は、記録済みの内容も削除する
+        if settings.history_metadata_only && !notification_manager.get_settings().history_metadata_only {
+            history_manager.strip_contents(app)?;
+            let _ = app.emit("notification-added", ());
+        }
+    }
     // NotificationManager のメモリ内設定を更新
     notification_manager.update_settings(settings);
-    info!("Settings saved and NotificationManager updated");
+    // 言語設定・プロファイルをトレイメニューに反映
+    tray::rebuild_menu(app, notification_manager.strings());
+    // トレイアイコンのテーマを反映
+    notification_manager.apply_tray_theme(app);
+    // ツールチップのテンプレートを反映
+    notification_manager.refresh_tray_status(app);
+    // メインウィンドウの表示モードを反映
+    window_mode::apply(app, &previous_window_mode, &notification_manager.get_settings().window_mode);
+    if let Ok(state) = app.state::<std::sync::Mutex<AppState>>().lock() {
+        // ブローカーの許可リストを反映（有効/無効の切り替えは再起動後）
+        if let Some(broker) = state.broker.as_ref() {
+            broker.set_acl(notification_manager.get_settings().broker_acl);
+        }
+        // プロジェクトの別名を反映（割り当て済みのセッション名は変えない）
+        let projects = notification_manager.get_settings().projects;
+        state.session_manager.set_project_aliases(projects.clone());
+        state.session_name_manager.set_project_aliases(projects);
+    }
+    // 追加で購読するトピックを反映
+    if let Some(publisher) = app.try_state::<client::MqttPublisher>() {
+        let topics = notification_manager.get_settings().custom_topics.filters();
+        if let Err(e) = publisher.update_subscriptions(&previous_topics, &topics) {
+            warn!("Failed to update custom topic subscriptions: {}", e);
+        }
+    }
     Ok(())
 }
 
@@ -266,13 +1092,63 @@ fn get_notification_history(
     history_manager.get_entries(filter_session.as_deref())
 }
 
+/// 承認依頼への応答の統計（応答した依頼の数と応答までの時間の中央値）
+#[tauri::command]
+fn get_response_stats(
+    history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
+) -> notification_history::ResponseStats {
+    history_manager.response_stats()
+}
+
+/// 履歴を検索する（本文・プロジェクト・セッション名・トランスクリプトの文字列、期間、イベントの種類で絞り込み、ページ単位で返す）
+#[tauri::command]
+fn search_history(
+    query: notification_history::HistoryQuery,
+    history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
+    transcript_index: tauri::State<'_, transcripts::TranscriptIndex>,
+) -> notification_history::HistoryPage {
+    history_manager.search(&query, &|path, text| transcript_index.contains(path, text))
+}
+
+/// 履歴のトランスクリプトを既定のアプリで開く
+#[tauri::command]
+fn open_transcript(
+    id: u64,
+    app: tauri::AppHandle,
+    history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
+) -> Result<(), String> {
+    let path = history_manager
+        .get_entry(id)
+        .and_then(|entry| entry.transcript_path)
+        .ok_or_else(|| "No transcript for this entry".to_string())?;
+    transcripts::open(&app, &path)
+}
+
 #[tauri::command]
 fn mark_notification_read(
     id: u64,
     app: tauri::AppHandle,
     history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
 ) -> Result<(), String> {
-    history_manager.mark_as_read(&app, id)
+    if let Some(entry) = history_manager.mark_as_read(&app, id)? {
+        ack::publish_ack(&app, &ack::AckPayload::from_entry(&entry));
+    }
+    Ok(())
+}
+
+/// 履歴のエントリをピン留めする（保持期間による削除の対象外にし、履歴タブの上部に表示する）
+#[tauri::command]
+fn pin_notification(
+    id: u64,
+    pinned: bool,
+    app: tauri::AppHandle,
+    history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
+) -> Result<(), String> {
+    if history_manager.set_pinned(&app, id, pinned)? {
+        Ok(())
+    } else {
+        Err(format!("History entry {} not found", id))
+    }
 }
 
 #[tauri::command]
@@ -280,7 +1156,103 @@ fn mark_all_notifications_read(
     app: tauri::AppHandle,
     history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
 ) -> Result<(), String> {
-    history_manager.mark_all_as_read(&app)
+    for entry in history_manager.mark_all_as_read(&app)? {
+        ack::publish_ack(&app, &ack::AckPayload::from_entry(&entry));
+    }
+    Ok(())
+}
+
+/// Tauriコマンド: 通知履歴から承認依頼を許可・拒否する
+#[tauri::command]
+fn respond_to_permission_request(
+    id: u64,
+    decision: permission_response::PermissionDecision,
+    app: tauri::AppHandle,
+    prompts: tauri::State<'_, question_prompt::QuestionPrompts>,
+    notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+    history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
+) -> Result<(), String> {
+    // 質問には回答（answer_question）で応答する
+    if prompts.get(id).is_some() {
+        return Err("Questions must be answered with answer_question".to_string());
+    }
+    let settings = notification_manager.get_settings();
+    permission_response::respond(
+        &app,
+        &history_manager,
+        settings.payload_signing.active_secret(),
+        id,
+        decision,
+        None,
+    )
+}
+
+/// 回答ウィンドウに表示する質問
+#[derive(Debug, Clone, Serialize)]
+struct QuestionPromptView {
+    session_name: String,
+    questions: Vec<question_prompt::Question>,
+}
+
+/// Tauriコマンド: 回答を待っている質問を取得する
+#[tauri::command]
+fn get_question_prompt(
+    id: u64,
+    prompts: tauri::State<'_, question_prompt::QuestionPrompts>,
+    history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
+) -> Result<QuestionPromptView, String> {
+    let questions = prompts
+        .get(id)
+        .ok_or_else(|| "Question is no longer waiting for an answer".to_string())?;
+    let session_name = history_manager
+        .get_entry(id)
+        .map(|entry| entry.session_name)
+        .unwrap_or_default();
+    Ok(QuestionPromptView {
+        session_name,
+        questions,
+    })
+}
+
+/// Tauriコマンド: 質問の回答ウィンドウを開く
+///
+/// Windows では同期コマンドからウィンドウを作成するとデッドロックするため async にする。
+#[tauri::command]
+async fn open_question_prompt(
+    id: u64,
+    app: tauri::AppHandle,
+    notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+) -> Result<(), String> {
+    question_prompt::open_window(&app, id, notification_manager.strings())
+}
+
+/// Tauriコマンド: 質問に回答する
+#[tauri::command]
+fn answer_question(
+    id: u64,
+    answers: question_prompt::Answers,
+    app: tauri::AppHandle,
+    prompts: tauri::State<'_, question_prompt::QuestionPrompts>,
+    notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+    history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
+) -> Result<(), String> {
+    let questions = prompts
+        .get(id)
+        .ok_or_else(|| "Question is no longer waiting for an answer".to_string())?;
+    question_prompt::validate_answers(&questions, &answers)?;
+
+    let settings = notification_manager.get_settings();
+    permission_response::respond(
+        &app,
+        &history_manager,
+        settings.payload_signing.active_secret(),
+        id,
+        permission_response::PermissionDecision::Allow,
+        Some(answers),
+    )?;
+    prompts.remove(id);
+    question_prompt::close_window(&app, id);
+    Ok(())
 }
 
 #[tauri::command]
@@ -288,7 +1260,26 @@ fn clear_notification_history(
     app: tauri::AppHandle,
     history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
 ) -> Result<(), String> {
-    history_manager.clear(&app)
+    history_manager.clear(&app)?;
+    attachment::prune(&app, &history_manager.attachments());
+    Ok(())
+}
+
+/// Tauriコマンド: 保持期間を過ぎた通知履歴を今すぐ削除（削除した件数を返す）
+#[tauri::command]
+fn compact_notification_history(
+    app: tauri::AppHandle,
+    history_manager: tauri::State<'_, Arc<NotificationHistoryManager>>,
+) -> Result<usize, String> {
+    let removed = history_manager.compact(&app)?;
+    attachment::prune(&app, &history_manager.attachments());
+    Ok(removed)
+}
+
+/// Tauriコマンド: 通知履歴の添付画像を data URI で取得
+#[tauri::command]
+fn get_attachment_image(app: tauri::AppHandle, name: String) -> Result<String, String> {
+    attachment::data_uri(&app, &name).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -298,13 +1289,39 @@ fn get_unread_count(
     history_manager.get_unread_count()
 }
 
-#[tauri::command]
-fn generate_config_zip(host: String, port: u16) -> Result<Vec<u8>, String> {
-    let config = export::ExportConfig {
+/// エクスポートの設定（署名用シークレット・暗号化の鍵・応答の待ち時間・ハートビートの間隔・セッションの操作の待ち受け・最後のメッセージの文字数は保存済みの設定から埋め込み、編集したテンプレートを使う）
+fn export_config(app: &tauri::AppHandle, host: String, port: u16) -> export::ExportConfig {
+    let settings = settings::load_settings(app);
+    export::ExportConfig {
         host,
         port,
         client_type: export::ClientType::MosquittoPub,
-    };
+        signing_secret: settings.payload_signing.active_secret().map(str::to_string),
+        encryption_key: settings.payload_encryption.active_key().map(str::to_string),
+        response_timeout_secs: settings.permission_response.active_timeout(),
+        heartbeat_interval_secs: settings.heartbeat.active_interval(),
+        session_control: settings.session_control.enabled,
+        last_message_length: settings.last_message_length,
+        custom_templates: hook_templates::load(app),
+        shell: export::HookShell::Bash,
+        // 外部のブローカーは告知しない
+        discovery: settings.broker_discovery && settings.external_broker.active_endpoint().is_none(),
+    }
+}
+
+#[tauri::command]
+fn generate_signing_secret() -> String {
+    signing::generate_secret()
+}
+
+#[tauri::command]
+fn generate_encryption_key() -> String {
+    encryption::generate_key()
+}
+
+#[tauri::command]
+fn generate_config_zip(app: tauri::AppHandle, host: String, port: u16) -> Result<Vec<u8>, String> {
+    let config = export_config(&app, host, port);
     export::generate_export_zip(&config).map_err(|e| e.to_string())
 }
 
@@ -314,26 +1331,33 @@ pub struct ExportOptions {
     pub host: String,
     pub port: u16,
     pub platform: String, // "linux_wsl" or "windows"
+    /// Shell that runs the Linux / WSL hooks
+    #[serde(default)]
+    pub shell: export::HookShell,
+    /// Linux / WSL preset (devcontainer scripts resolve the Docker host themselves)
+    #[serde(default)]
+    pub preset: export::ExportPreset,
 }
 
 #[tauri::command]
-fn generate_config_zip_v2(options: ExportOptions) -> Result<Vec<u8>, String> {
+fn generate_config_zip_v2(app: tauri::AppHandle, options: ExportOptions) -> Result<Vec<u8>, String> {
     let platform = match options.platform.as_str() {
         "windows" => export::ExportPlatform::Windows,
         _ => export::ExportPlatform::LinuxWsl,
     };
 
+    let host = match platform {
+        export::ExportPlatform::LinuxWsl => options.preset.host(options.host),
+        export::ExportPlatform::Windows => options.host,
+    };
     let config = export::ExportConfig {
-        host: options.host,
-        port: options.port,
-        client_type: export::ClientType::MosquittoPub,
+        shell: options.shell,
+        ..export_config(&app, host, options.port)
     };
 
     // For Windows export, try to include the mqtt-publish.exe binary
     let mqtt_publish_exe = if platform == export::ExportPlatform::Windows {
-        // Try to read from the workspace target directory
-        let exe_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../target/release/mqtt-publish.exe");
-        std::fs::read(exe_path).ok()
+        read_mqtt_publish_exe()
     } else {
         None
     };
@@ -346,35 +1370,128 @@ fn generate_config_zip_v2(options: ExportOptions) -> Result<Vec<u8>, String> {
     .map_err(|e| e.to_string())
 }
 
+/// Read the mqtt-publish.exe binary bundled with Windows hooks
+fn read_mqtt_publish_exe() -> Option<Vec<u8>> {
+    // Try to read from the workspace target directory
+    let exe_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../target/release/mqtt-publish.exe");
+    std::fs::read(exe_path).ok()
+}
+
+/// Tauriコマンド: このPCにスクリプトを配置し、Claude Code の設定にフックをマージする
+#[tauri::command]
+fn install_hooks_locally(
+    app: tauri::AppHandle,
+    port: u16,
+    with_statusline: bool,
+) -> Result<claude_settings::InstallReport, String> {
+    let platform = if cfg!(windows) {
+        export::ExportPlatform::Windows
+    } else {
+        export::ExportPlatform::LinuxWsl
+    };
+    // このPCのブローカー（外部のブローカーに接続している場合はそのブローカー）に送るため、mDNS での検出は使わない
+    let (host, port) = match app.try_state::<client::BrokerEndpoint>().filter(|endpoint| endpoint.external) {
+        Some(endpoint) => (endpoint.host.clone(), endpoint.port),
+        None => ("127.0.0.1".to_string(), port),
+    };
+    let config = export::ExportConfig {
+        discovery: false,
+        ..export_config(&app, host, port)
+    };
+    let mqtt_publish_exe = if platform == export::ExportPlatform::Windows {
+        read_mqtt_publish_exe()
+    } else {
+        None
+    };
+    let home = app.path().home_dir().map_err(|e| e.to_string())?;
+    let report = claude_settings::install_locally(&config, platform, mqtt_publish_exe.as_deref(), &home, with_statusline)
+        .map_err(|e| e.to_string())?;
+    info!("Hooks installed to {}", report.scripts_dir.display());
+    Ok(report)
+}
+
 fn start_message_handler(
+    runtime: tokio::runtime::Handle,
     app_handle: tauri::AppHandle,
+    endpoint: client::BrokerEndpoint,
     session_manager: Arc<SessionManager>,
     session_name_manager: Arc<SessionNameManager>,
     notification_manager: Arc<NotificationManager>,
     history_manager: Arc<NotificationHistoryManager>,
 ) {
-    // Wait for broker to start
-    std::thread::sleep(std::time::Duration::from_secs(1));
-
-    let (_client, mut rx) = client::start_mqtt_client("claude-code-notify-client");
-
-    info!("MQTT client started, listening for notifications...");
-
-    std::thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .expect("Failed to create tokio runtime");
-
-        rt.block_on(async move {
+    runtime.clone().spawn(async move {
+        // Wait for broker to start
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let connection_status = client::ConnectionStatus::default();
+        let (client, mut rx) = client::start_mqtt_client(
+            &runtime,
+            &endpoint,
+            connection_status.clone(),
+            notification_manager.get_settings().custom_topics.filters(),
+        );
+        app_handle.manage(client::MqttPublisher::new(client));
+        app_handle.manage(connection_status.clone());
+
+        info!("MQTT client started, listening for notifications...");
+
+        let mut backoff = client::RestartBackoff::default();
+        loop {
+            let started = Instant::now();
             while let Some(msg) = rx.recv().await {
-                handle_mqtt_message(&app_handle, &session_manager, &session_name_manager, &notification_manager, &history_manager, msg);
+                // The handler does blocking work (toasts, sounds, store writes), so let the runtime move other tasks away
+                tokio::task::block_in_place(|| {
+                    handle_mqtt_message(&app_handle, &session_manager, &session_name_manager, &notification_manager, &history_manager, msg);
+                });
             }
-            warn!("MQTT message receiver closed");
-        });
+
+            // Re-create the client when its event loop stops, backing off while it keeps closing
+            let delay = backoff.next_delay(started.elapsed());
+            let failures = backoff.failures();
+            warn!("MQTT message receiver closed (failure {}), restarting in {:?}", failures, delay);
+            connection_status.set_closed(failures);
+            if failures == client::FAILURE_ALERT_THRESHOLD {
+                error!("MQTT client keeps closing after {} restarts", failures);
+                let strings = notification_manager.strings();
+                let body = i18n::fill(strings.client_restart_failed_body, &[("count", &failures.to_string())]);
+                notification_manager.notify(
+                    &app_handle,
+                    strings.client_restart_failed_title,
+                    &body,
+                    NotificationEventType::Notification,
+                    NotificationPriority::High,
+                    NotificationOrigin::default(),
+                );
+            }
+            update_tray_status(&app_handle, &session_manager, &notification_manager);
+
+            tokio::time::sleep(delay).await;
+            let (client, new_rx) = client::start_mqtt_client(
+                &runtime,
+                &endpoint,
+                connection_status.clone(),
+                notification_manager.get_settings().custom_topics.filters(),
+            );
+            app_handle.state::<client::MqttPublisher>().replace(client);
+            rx = new_rx;
+            info!("MQTT client restarted");
+        }
     });
 }
 
+/// 署名を検証してから復号する（mqtt-publish は暗号化してから署名する）
+///
+/// 署名を必須にしている場合は、復号したペイロードの時刻が有効期間内かも確認する。
+fn open_payload(raw: &str, settings: &signing::PayloadSigningSettings, key: Option<&str>) -> Result<String, String> {
+    let secret = settings.active_secret();
+    let verified = signing::verify_payload(raw, secret).map_err(|e| e.to_string())?;
+    let payload = encryption::decrypt_payload(&verified, key).map_err(|e| e.to_string())?;
+    if secret.is_some() {
+        signing::check_freshness(&payload, settings.window(), chrono::Utc::now()).map_err(|e| e.to_string())?;
+    }
+    Ok(payload)
+}
+
 fn handle_mqtt_message(
     app: &tauri::AppHandle,
     session_manager: &Arc<SessionManager>,
@@ -385,167 +1502,95 @@ fn handle_mqtt_message(
 ) {
     info!("Received MQTT message on topic: {}", msg.topic);
 
-    match msg.topic.as_str() {
-        topics::EVENTS_STOP => {
-            if let Some(payload_str) = msg.payload_str() {
-                match serde_json::from_str::<StopEventPayload>(payload_str) {
-                    Ok(payload) => {
-                        info!("Stop event received for: {}", payload.cwd);
-                        let session_name = resolve_session_name(session_name_manager, payload.session_id.as_deref(), &payload.cwd)
-                            .unwrap_or_else(|| "Claude Code".to_string());
-
-                        // 履歴に追加
-                        if let Err(e) = history_manager.add_entry(
-                            app,
-                            NotificationEventType::Stop,
-                            session_name.clone(),
-                            payload.session_id.clone().unwrap_or_default(),
-                            Some(payload.cwd.clone()),
-                            None,
-                        ) {
-                            warn!("Failed to add history entry: {}", e);
-                        } else {
-                            // フロントエンドに通知
-                            let _ = app.emit("notification-added", ());
-                        }
+    // このアプリが送信した ack・応答はブローカーから折り返し届くため無視する
+    if topics::is_outbound(&msg.topic) {
+        return;
+    }
 
-                        show_stop_notification(app, session_name_manager, notification_manager, &payload);
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse stop event payload: {}", e);
-                        // Show notification with raw payload as fallback
-                        show_simple_notification(app, notification_manager, "✅ タスク完了", payload_str);
-                    }
-                }
-            }
+    // 署名の検証・復号を行い、エンベロープから元のペイロードを取り出す
+    let settings = notification_manager.get_settings();
+    let secret = settings.payload_signing.active_secret();
+    let key = settings.payload_encryption.active_key();
+    let opened = msg.payload_str().map(|raw| open_payload(raw, &settings.payload_signing, key));
+    let msg = match opened {
+        Some(Ok(payload)) => MqttMessage {
+            topic: msg.topic,
+            payload: payload.into_bytes(),
+        },
+        Some(Err(e)) => {
+            warn!("Dropping message on {}: {}", msg.topic, e);
+            return;
         }
-        topics::EVENTS_PERMISSION_REQUEST => {
-            if let Some(payload_str) = msg.payload_str() {
-                match serde_json::from_str::<PermissionRequestPayload>(payload_str) {
-                    Ok(payload) => {
-                        info!("Permission request received for: {}", payload.cwd);
-                        let session_name = resolve_session_name(session_name_manager, payload.session_id.as_deref(), &payload.cwd)
-                            .unwrap_or_else(|| "Claude Code".to_string());
-
-                        // ツール名を取得
-                        let content = payload.content.tool_name.clone()
-                            .or_else(|| payload.content.raw.clone());
-
-                        // 履歴に追加
-                        if let Err(e) = history_manager.add_entry(
-                            app,
-                            NotificationEventType::PermissionRequest,
-                            session_name.clone(),
-                            payload.session_id.clone().unwrap_or_default(),
-                            Some(payload.cwd.clone()),
-                            content,
-                        ) {
-                            warn!("Failed to add history entry: {}", e);
-                        } else {
-                            // フロントエンドに通知
-                            let _ = app.emit("notification-added", ());
-                        }
-
-                        show_permission_request_notification(app, session_name_manager, notification_manager, &payload);
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse permission request payload: {}", e);
-                        show_simple_notification(app, notification_manager, "⚠️ 承認依頼", payload_str);
-                    }
-                }
-            }
+        None if secret.is_some() || key.is_some() => {
+            warn!("Dropping non UTF-8 message on {}", msg.topic);
+            return;
         }
-        topics::EVENTS_NOTIFICATION => {
-            if let Some(payload_str) = msg.payload_str() {
-                match serde_json::from_str::<NotificationEventPayload>(payload_str) {
-                    Ok(payload) => {
-                        info!("Notification event received for: {}", payload.cwd);
-                        let session_name = resolve_session_name(session_name_manager, payload.session_id.as_deref(), &payload.cwd)
-                            .unwrap_or_else(|| "Claude Code".to_string());
-
-                        // メッセージを取得
-                        let content = payload.content.message.clone()
-                            .or_else(|| payload.content.title.clone())
-                            .or_else(|| payload.content.raw.clone());
-
-                        // 履歴に追加
-                        if let Err(e) = history_manager.add_entry(
-                            app,
-                            NotificationEventType::Notification,
-                            session_name.clone(),
-                            payload.session_id.clone().unwrap_or_default(),
-                            Some(payload.cwd.clone()),
-                            content,
-                        ) {
-                            warn!("Failed to add history entry: {}", e);
-                        } else {
-                            // フロントエンドに通知
-                            let _ = app.emit("notification-added", ());
-                        }
+        None => msg,
+    };
 
-                        show_notification_event(app, session_name_manager, notification_manager, &payload);
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse notification event payload: {}", e);
-                        show_simple_notification(app, notification_manager, "💬 通知", payload_str);
-                    }
-                }
-            }
-        }
-        topics::TASK_COMPLETE => {
-            if let Some(payload) = msg.payload_str() {
-                info!("Task completed: {}", payload);
-                show_simple_notification(app, notification_manager, "✅ タスク完了", payload);
-            }
-        }
-        topics::ERROR => {
-            if let Some(payload) = msg.payload_str() {
-                warn!("Error notification: {}", payload);
-                show_simple_notification(app, notification_manager, "❌ エラー", payload);
-            }
-        }
-        topic if topic.starts_with(topics::STATUS_PREFIX) => {
-            if let Some(payload_str) = msg.payload_str() {
-                info!("Status update on {}: {}", topic, payload_str);
-                match serde_json::from_str::<StatusPayload>(payload_str) {
-                    Ok(payload) => {
-                        session_manager.update_session(payload);
-                        // Cleanup expired sessions periodically
-                        session_manager.cleanup_expired();
-                        // Update tray tooltip
-                        update_tray_tooltip(app, session_manager);
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse status payload: {}", e);
-                    }
-                }
-            }
-        }
-        topics::STATUS => {
-            if let Some(payload) = msg.payload_str() {
-                info!("Status update: {}", payload);
-            }
-        }
-        _ => {
-            if let Some(payload) = msg.payload_str() {
-                info!("Message: {}", payload);
-            }
-        }
+    let Some(payload) = msg.payload_str() else {
+        return;
+    };
+    // 無視するパスからのイベントは通知・履歴・セッションの追跡のいずれにも使わない
+    if let Some(cwd) = settings.ignore_list.ignored_cwd(payload) {
+        debug!("Ignoring message on {} from ignored path {}", msg.topic, cwd);
+        return;
     }
-}
-
-/// Extract project name from path
-#[allow(dead_code)]
-fn extract_project_name(cwd: &str) -> &str {
-    std::path::Path::new(cwd)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or(cwd)
+    // 設定した時間より古いイベントは捨てるか、履歴にだけ記録する（ステータス・ハートビートは除く）
+    let stale = if topics::is_periodic(&msg.topic) {
+        None
+    } else {
+        settings.stale_events.classify(timestamps::payload_timestamp(payload), chrono::Utc::now())
+    };
+    if stale == Some(stale_events::StaleEventAction::Discard) {
+        info!("Discarding stale message on {}", msg.topic);
+        return;
+    }
+    // ループしたフックなど、短時間に大量のイベントを送る送信元はしばらくミュートする
+    let allowed = rate_limit::allow(
+        app,
+        &app.state::<rate_limit::RateLimiter>(),
+        notification_manager,
+        session_name_manager,
+        &settings.rate_limit,
+        &msg.topic,
+        payload,
+    );
+    if !allowed {
+        debug!("Dropping message on {} from a flooding publisher", msg.topic);
+        return;
+    }
+    // 設定したスクリプトにもイベントを渡す（別のスレッドで実行）
+    script_hook::dispatch(app, &settings.script_hook, &msg.topic, payload);
+    // 起動直後に届いた古いイベントは履歴に記録するだけにする
+    let backlog = notification_manager.catch_up.classify(&settings.catch_up, payload);
+    if backlog {
+        debug!("Message on {} is from the startup backlog", msg.topic);
+    }
+    let ctx = handlers::HandlerContext {
+        app,
+        session_manager,
+        session_name_manager,
+        notification_manager,
+        history_manager,
+        settings: &settings,
+        origin: NotificationOrigin {
+            backlog,
+            stale: stale == Some(stale_events::StaleEventAction::Demote),
+            ..NotificationOrigin::default()
+        },
+    };
+    app.state::<handlers::HandlerRegistry>().dispatch(ctx, &msg.topic, payload);
 }
 
 /// Resolve session name from session_id and cwd using SessionNameManager
-fn resolve_session_name(session_name_manager: &SessionNameManager, session_id: Option<&str>, cwd: &str) -> Option<String> {
-    session_id.map(|id| session_name_manager.get_or_create_name(id, cwd))
+fn resolve_session_name(
+    session_name_manager: &SessionNameManager,
+    session_id: Option<&str>,
+    cwd: &str,
+    host: Option<&str>,
+) -> Option<String> {
+    session_id.map(|id| session_name_manager.get_or_create_name(id, cwd, host))
 }
 
 /// Show notification for stop event
@@ -554,18 +1599,40 @@ fn show_stop_notification(
     session_name_manager: &SessionNameManager,
     notification_manager: &NotificationManager,
     payload: &StopEventPayload,
+    run: Option<&RunSummary>,
+    image: Option<&Path>,
+    origin: NotificationOrigin<'_>,
 ) {
     // Resolve session name from session_id (SMS-style: sender name as title, includes project name)
-    let session_name = resolve_session_name(session_name_manager, payload.session_id.as_deref(), &payload.cwd);
+    let session_name = resolve_session_name(
+        session_name_manager,
+        payload.session_id.as_deref(),
+        &payload.cwd,
+        payload.host.as_deref(),
+    );
     let title = session_name.unwrap_or_else(|| "Claude Code".to_string());
 
-    // SMS-style body: event type only (project name is in the title)
-    let body = "✅ タスクが完了しました".to_string();
+    // SMS-style body: event type and what this run did (project name is in the title)
+    let strings = notification_manager.strings();
+    let mut body = strings.task_complete_body.to_string();
+    if let Some(summary) = run.and_then(|run| run.text(strings)) {
+        body = format!("{}\n{}", body, summary);
+    }
+    // Claude's final message lets the user decide whether to switch to the terminal
+    if let Some(message) = payload.last_message() {
+        body = format!("{}\n{}", body, message);
+    }
+    let body = with_source_host(body, payload.host.as_deref(), strings);
 
     info!("Attempting to show notification: {} - {}", title, body);
 
     // Use NotificationManager for unified notification handling
-    notification_manager.notify(app, &title, &body);
+    notification_manager.notify(app, &title, &body, NotificationEventType::Stop, NotificationPriority::Low, NotificationOrigin {
+        session_id: payload.session_id.as_deref(),
+        image,
+        cwd: Some(&payload.cwd),
+        ..origin
+    });
 }
 
 /// Show notification for permission request (approval needed) or AskUserQuestion
@@ -574,49 +1641,84 @@ fn show_permission_request_notification(
     session_name_manager: &SessionNameManager,
     notification_manager: &NotificationManager,
     payload: &PermissionRequestPayload,
+    pending_request: Option<u64>,
+    image: Option<&Path>,
+    origin: NotificationOrigin<'_>,
 ) {
     // Resolve session name from session_id (includes project name)
-    let session_name = resolve_session_name(session_name_manager, payload.session_id.as_deref(), &payload.cwd);
+    let session_name = resolve_session_name(
+        session_name_manager,
+        payload.session_id.as_deref(),
+        &payload.cwd,
+        payload.host.as_deref(),
+    );
+
+    if is_ask_user_question(&payload.content) {
+        // Show as a question notification
+        show_ask_user_question_notification(app, notification_manager, payload, session_name.as_deref(), pending_request, image, origin);
+    } else {
+        // Show as a permission request notification
+        show_tool_permission_notification(app, notification_manager, payload, session_name.as_deref(), pending_request, image, origin);
+    }
+}
 
-    // Check if this is an AskUserQuestion (question from Claude, not a permission request)
-    let is_ask_user_question = payload.content.tool_name.as_deref() == Some("AskUserQuestion")
-        || payload.content.raw.as_ref().map_or(false, |raw| {
+/// Check if this is an AskUserQuestion (question from Claude, not a permission request)
+fn is_ask_user_question(content: &PermissionRequestContent) -> bool {
+    content.tool_name.as_deref() == Some("AskUserQuestion")
+        || content.raw.as_ref().is_some_and(|raw| {
             serde_json::from_str::<serde_json::Value>(raw)
                 .ok()
                 .and_then(|v| v.get("tool_name").and_then(|t| t.as_str()).map(|s| s == "AskUserQuestion"))
                 .unwrap_or(false)
-        });
-
-    if is_ask_user_question {
-        // Show as a question notification
-        show_ask_user_question_notification(app, notification_manager, payload, session_name.as_deref());
-    } else {
-        // Show as a permission request notification
-        show_tool_permission_notification(app, notification_manager, payload, session_name.as_deref());
-    }
+        })
 }
 
 /// Show notification for AskUserQuestion (Claude is asking a question)
+///
+/// `pending_question` is the history entry the hook is waiting on for an answer.
 fn show_ask_user_question_notification(
     app: &tauri::AppHandle,
     notification_manager: &NotificationManager,
     payload: &PermissionRequestPayload,
     session_name: Option<&str>,
+    pending_question: Option<u64>,
+    image: Option<&Path>,
+    origin: NotificationOrigin<'_>,
 ) {
     // SMS-style: sender name as title (now includes project name)
     let title = session_name.unwrap_or("Claude Code").to_string();
 
+    let strings = notification_manager.strings();
+
     // Try to extract the question text
     let question_text = extract_question_text(&payload.content)
-        .unwrap_or_else(|| "質問が来ています".to_string());
+        .unwrap_or_else(|| strings.question_default.to_string());
 
     // SMS-style body: event type + question (project name is in the title)
-    let body = format!("❓ 質問があります\n{}", question_text);
+    let body = format!("{}\n{}", strings.question_header, question_text);
+    let body = with_source_host(body, payload.host.as_deref(), strings);
 
     info!("Attempting to show AskUserQuestion notification: {} - {}", title, body);
 
     // Use NotificationManager for unified notification handling
-    notification_manager.notify(app, &title, &body);
+    notification_manager.notify(app, &title, &body, NotificationEventType::PermissionRequest, NotificationPriority::High, NotificationOrigin {
+        session_id: payload.session_id.as_deref(),
+        pending_question,
+        image,
+        cwd: Some(&payload.cwd),
+        ..origin
+    });
+}
+
+/// Get tool_input from the content, falling back to the raw JSON from Claude Code
+fn content_tool_input(content: &PermissionRequestContent) -> Option<serde_json::Value> {
+    content.tool_input.clone().or_else(|| {
+        content
+            .raw
+            .as_ref()
+            .and_then(|raw| serde_json::from_str::<serde_json::Value>(raw).ok())
+            .and_then(|raw_json| raw_json.get("tool_input").cloned())
+    })
 }
 
 /// Extract question text from AskUserQuestion content
@@ -653,14 +1755,20 @@ fn extract_question_text(content: &PermissionRequestContent) -> Option<String> {
 }
 
 /// Show notification for tool permission request (approval needed)
+///
+/// `pending_request` is the history entry the hook is waiting on for a decision.
 fn show_tool_permission_notification(
     app: &tauri::AppHandle,
     notification_manager: &NotificationManager,
     payload: &PermissionRequestPayload,
     session_name: Option<&str>,
+    pending_request: Option<u64>,
+    image: Option<&Path>,
+    origin: NotificationOrigin<'_>,
 ) {
     // SMS-style: sender name as title (now includes project name)
     let title = session_name.unwrap_or("Claude Code").to_string();
+    let strings = notification_manager.strings();
 
     // Try to extract useful info from content
     let tool_info = if let Some(tool_name) = &payload.content.tool_name {
@@ -669,10 +1777,10 @@ fn show_tool_permission_notification(
             if let Some(command) = input.get("command").and_then(|v| v.as_str()) {
                 format!("{}: {}", tool_name, command)
             } else {
-                format!("{} の実行許可が必要です", tool_name)
+                i18n::fill(strings.tool_permission_required, &[("tool", tool_name)])
             }
         } else {
-            format!("{} の実行許可が必要です", tool_name)
+            i18n::fill(strings.tool_permission_required, &[("tool", tool_name)])
         }
     } else if let Some(raw) = &payload.content.raw {
         // Fallback: try to parse raw JSON from Claude Code
@@ -689,37 +1797,60 @@ fn show_tool_permission_notification(
 
             match (tool, command) {
                 (Some(t), Some(c)) => format!("{}: {}", t, c),
-                (Some(t), None) => format!("{} の実行許可が必要です", t),
-                (None, Some(c)) => format!("コマンド: {}", c),
-                (None, None) => "ツールの実行許可が必要です".to_string(),
+                (Some(t), None) => i18n::fill(strings.tool_permission_required, &[("tool", t)]),
+                (None, Some(c)) => i18n::fill(strings.command_label, &[("command", c)]),
+                (None, None) => strings.tool_permission_generic.to_string(),
             }
         } else {
             // Raw is not valid JSON, show truncated version
-            let truncated = if raw.len() > 100 {
-                format!("{}...", &raw[..100])
-            } else {
-                raw.clone()
-            };
-            truncated
+            text::truncate(raw, MAX_RAW_CONTENT_LENGTH)
         }
     } else {
-        "ツールの実行許可が必要です".to_string()
+        strings.tool_permission_generic.to_string()
     };
 
+    // Long commands keep their head and tail instead of being cut off by the OS
+    // (secrets are masked first so that a secret cut in the middle is not left half visible)
+    let settings = notification_manager.get_settings();
+    let tool_info = text::truncate_middle(&settings.redaction.redact(&tool_info), settings.toast_body_max_length);
+
     // SMS-style body: event type + tool info (project name is in the title)
-    let body = format!("⚠️ 承認が必要です\n{}", tool_info);
+    let body = format!("{}\n{}", strings.approval_required, tool_info);
+    let body = with_source_host(body, payload.host.as_deref(), strings);
 
     info!("Attempting to show notification: {} - {}", title, body);
 
     // Use NotificationManager for unified notification handling
-    notification_manager.notify(app, &title, &body);
+    notification_manager.notify(app, &title, &body, NotificationEventType::PermissionRequest, NotificationPriority::Critical, NotificationOrigin {
+        session_id: payload.session_id.as_deref(),
+        pending_request,
+        image,
+        cwd: Some(&payload.cwd),
+        ..origin
+    });
+}
+
+/// Append the host the event came from to a notification body
+fn with_source_host(body: String, host: Option<&str>, strings: &Strings) -> String {
+    match host_rules::normalize_host(host) {
+        Some(host) => format!("{}\n{}", body, i18n::fill(strings.source_host, &[("host", &host)])),
+        None => body,
+    }
 }
 
 /// Show simple notification with title and body
-fn show_simple_notification(app: &tauri::AppHandle, notification_manager: &NotificationManager, title: &str, body: &str) {
+fn show_simple_notification(
+    app: &tauri::AppHandle,
+    notification_manager: &NotificationManager,
+    title: &str,
+    body: &str,
+    event_type: NotificationEventType,
+    origin: NotificationOrigin<'_>,
+) {
     info!("Attempting to show notification: {} - {}", title, body);
     // Use NotificationManager for unified notification handling
-    notification_manager.notify(app, title, body);
+    let priority = NotificationPriority::for_event(&event_type);
+    notification_manager.notify(app, title, body, event_type, priority, origin);
 }
 
 /// Show notification for elicitation dialogs (user input requests)
@@ -728,10 +1859,18 @@ fn show_notification_event(
     session_name_manager: &SessionNameManager,
     notification_manager: &NotificationManager,
     payload: &NotificationEventPayload,
+    image: Option<&Path>,
+    origin: NotificationOrigin<'_>,
 ) {
     // Resolve session name from session_id (SMS-style: sender name as title, includes project name)
-    let session_name = resolve_session_name(session_name_manager, payload.session_id.as_deref(), &payload.cwd);
+    let session_name = resolve_session_name(
+        session_name_manager,
+        payload.session_id.as_deref(),
+        &payload.cwd,
+        payload.host.as_deref(),
+    );
     let title = session_name.unwrap_or_else(|| "Claude Code".to_string());
+    let strings = notification_manager.strings();
 
     // Try to extract message from content
     let message = if let Some(msg) = payload.content.message.as_deref() {
@@ -746,56 +1885,109 @@ fn show_notification_event(
                 .or_else(|| raw_json.get("question"))
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string())
-                .unwrap_or_else(|| "入力を待っています".to_string())
+                .unwrap_or_else(|| strings.waiting_for_input.to_string())
         } else {
             // Raw is not valid JSON
-            let truncated = if raw.len() > 100 {
-                format!("{}...", &raw[..100])
-            } else {
-                raw.clone()
-            };
-            truncated
+            text::truncate(raw, MAX_RAW_CONTENT_LENGTH)
         }
     } else {
-        "入力を待っています".to_string()
+        strings.waiting_for_input.to_string()
     };
 
     // SMS-style body: event type + message (project name is now in the title)
-    let body = format!("💬 入力が必要です\n{}", message);
+    let body = format!("{}\n{}", strings.input_required, message);
+    let body = with_source_host(body, payload.host.as_deref(), strings);
 
     info!("Attempting to show notification: {} - {}", title, body);
 
     // Use NotificationManager for unified notification handling
-    notification_manager.notify(app, &title, &body);
+    notification_manager.notify(app, &title, &body, NotificationEventType::Notification, NotificationPriority::High, NotificationOrigin {
+        session_id: payload.session_id.as_deref(),
+        image,
+        cwd: Some(&payload.cwd),
+        ..origin
+    });
 }
 
-/// Update tray icon tooltip with session metrics
-fn update_tray_tooltip(app: &tauri::AppHandle, session_manager: &Arc<SessionManager>) {
-    let tooltip = session_manager.generate_tooltip();
+/// Update tray icon tooltip (from the configured template) and the status submenu
+fn update_tray_status(app: &tauri::AppHandle, session_manager: &Arc<SessionManager>, notification_manager: &NotificationManager) {
+    let mut tooltip = session_manager.generate_tooltip(
+        &notification_manager.get_settings().tooltip_template,
+        notification_manager.pending_approvals(),
+        notification_manager.get_unread_count(),
+    );
+    let calendar_event = notification_manager.current_calendar_event();
+    if let Some(summary) = &calendar_event {
+        let line = i18n::fill(notification_manager.strings().calendar_busy, &[("summary", summary)]);
+        tooltip = format!("{}\n{}", tooltip, line);
+    }
 
     if let Some(tray) = app.tray_by_id("main-tray") {
         if let Err(e) = tray.set_tooltip(Some(&tooltip)) {
             warn!("Failed to update tray tooltip: {}", e);
         }
     }
+
+    let Some((broker_running, session_name_manager)) = app
+        .try_state::<std::sync::Mutex<AppState>>()
+        .and_then(|state| {
+            state.lock().ok().map(|state| {
+                let running = state.broker.as_ref().is_some_and(|broker| broker.is_running());
+                (running, state.session_name_manager.clone())
+            })
+        })
+    else {
+        return;
+    };
+
+    let connection_status = app.try_state::<client::ConnectionStatus>();
+    let status = tray::TrayStatus {
+        broker_running,
+        client_connected: connection_status.as_ref().is_some_and(|status| status.is_connected()),
+        client_failing: connection_status.as_ref().is_some_and(|status| status.is_failing()),
+        unread: notification_manager.get_unread_count(),
+        calendar_event,
+        total_cost_usd: session_manager.get_metrics().total_cost_usd,
+        sessions: session_manager
+            .summaries()
+            .into_iter()
+            .map(|session| tray::TraySession {
+                name: session_name_manager.get_or_create_name(
+                    &session.session_id,
+                    &session.cwd,
+                    session.host.as_deref(),
+                ),
+                host: session.host,
+                state: session.state,
+                cost_usd: session.cost_usd,
+            })
+            .collect(),
+    };
+    tray::refresh_status_menu(app, status, notification_manager.strings());
+
+    // ホームタブの表示を更新する
+    let _ = app.emit("state-changed", ());
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    init_logging();
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    let args = cli::LaunchArgs::parse(&argv);
+    let log_filter = init_logging(args.as_ref().ok().and_then(|args| args.log_level.as_deref()));
 
     info!("Starting Claude Code Notify...");
 
+    let args = args.unwrap_or_else(|e| {
+        error!("Invalid command-line arguments: {}", e);
+        cli::LaunchArgs::default()
+    });
+
     // Initialize audio system
     if let Err(e) = audio::init_audio() {
         error!("Failed to initialize audio system: {}", e);
     }
 
-    // Initialize taskbar system (Windows only)
-    if let Err(e) = taskbar::init_taskbar() {
-        error!("Failed to initialize taskbar system: {}", e);
-    }
-
+    // ブローカーはアクセス制御の設定を読み込んだ後、setup 内で起動する
     let mut broker = match MqttBroker::with_default_config() {
         Ok(b) => b,
         Err(e) => {
@@ -803,77 +1995,336 @@ pub fn run() {
             return;
         }
     };
-
-    if let Err(e) = broker.start() {
-        error!("Failed to start MQTT broker: {:?}", e);
-        return;
+    if let Some(port) = args.port {
+        broker.set_port(port);
     }
+    let broker_port = broker.port();
+    // 接続を拒否したクライアントの記録（診断タブに表示する）
+    let security_events = broker.security_events();
+
+    // One multi-thread runtime runs the MQTT client and message handler, and backs async Tauri commands
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(RUNTIME_WORKER_THREADS)
+        .thread_name("ccnotify-runtime")
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => Arc::new(runtime),
+        Err(e) => {
+            error!("Failed to create tokio runtime: {:?}", e);
+            return;
+        }
+    };
+    tauri::async_runtime::set(runtime.handle().clone());
 
     let session_manager = Arc::new(SessionManager::new());
     let session_name_manager = Arc::new(SessionNameManager::new());
     let app_state = std::sync::Mutex::new(AppState {
         broker: Some(broker),
+        runtime: runtime.clone(),
         session_manager: session_manager.clone(),
         session_name_manager: session_name_manager.clone(),
     });
 
     tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            // When a second instance is launched, show notification
-            info!("Second instance detected, application is already running");
-            if let Err(e) = app
-                .notification()
-                .builder()
-                .title("Claude Code Notify")
-                .body("アプリケーションは既に起動しています")
-                .show()
-            {
-                warn!("Failed to show duplicate instance notification: {}", e);
-            }
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            // When a second instance is launched, bring the running instance to the foreground
+            cli::handle_second_instance(app, &args);
         }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_store::Builder::default().build())
+        .plugin(profiles::hotkey_plugin())
         .manage(app_state)
+        .manage(log_filter)
+        .manage(security_events)
+        .manage(cli::LaunchTab::new(args.tab))
         .setup(move |app| {
             info!("Setting up Tauri application...");
 
-            let _tray = tray::init_tray(app)?;
+            // 設定ファイルに平文で残っている認証情報をキーチェーンに移す
+            settings::migrate_credentials(app.handle());
 
             // Create NotificationManager
             let notification_manager = Arc::new(NotificationManager::new(app.handle()));
 
+            // Start MQTT broker with the saved listener and access control settings
+            // (the port may change when it is in use, so the client uses the port the broker started on)
+            let mut broker_port = broker_port;
+            let mut port_fallback = None;
+            // 外部のブローカーに接続する場合は組み込みのブローカーを起動しない
+            let external_endpoint = notification_manager.get_settings().external_broker.active_endpoint();
+            {
+                let state = app.state::<std::sync::Mutex<AppState>>();
+                let mut state = state.lock().map_err(|e| e.to_string())?;
+                if let Some(endpoint) = &external_endpoint {
+                    info!("Using external MQTT broker at {}:{}", endpoint.host, endpoint.port);
+                    state.broker = None;
+                }
+                if let Some(broker) = state.broker.as_mut() {
+                    let settings = notification_manager.get_settings();
+                    if let Err(e) = broker.set_listeners(&settings.broker_listeners) {
+                        warn!("Failed to configure broker listeners: {:?}", e);
+                    }
+                    broker.set_acl(settings.broker_acl);
+                    broker.set_port_fallback(settings.broker_port_fallback);
+                    if let Err(e) = broker.start() {
+                        error!("Failed to start MQTT broker: {:?}", e);
+                        return Err(Box::new(e));
+                    }
+                    if broker.port() != broker_port {
+                        port_fallback = Some((broker_port, broker.port()));
+                        broker_port = broker.port();
+                    }
+
+                    // LAN に告知する（opt-in、破棄すると告知を取り下げるためアプリの状態として保持する）
+                    if settings.broker_discovery {
+                        match discovery::advertise(broker.port(), broker.local_port()) {
+                            Ok(advertisement) => {
+                                app.manage(advertisement);
+                            }
+                            Err(e) => warn!("Failed to advertise MQTT broker via mDNS: {}", e),
+                        }
+                    }
+                }
+            }
+
+            let endpoint = external_endpoint.unwrap_or_else(|| client::BrokerEndpoint::embedded(broker_port));
+            app.manage(endpoint.clone());
+
+            let tray_icon = notification_manager.tray_icon(app.handle());
+            let _tray = tray::init_tray(app, notification_manager.strings(), tray_icon)?;
+
+            // メインウィンドウのタイトルを言語設定に合わせる
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_title(notification_manager.strings().window_title_main);
+            }
+            // メインウィンドウの表示モード（コンパクト表示・不透明度・常に手前に表示）を反映する
+            window_mode::apply(
+                app.handle(),
+                &window_mode::WindowModeSettings::default(),
+                &notification_manager.get_settings().window_mode,
+            );
+
+            // プロジェクトの別名をセッションの集計と名前に反映する
+            let projects = notification_manager.get_settings().projects;
+            session_manager.set_project_aliases(projects.clone());
+            session_name_manager.set_project_aliases(projects);
+
             // Create NotificationHistoryManager and load existing history
             let history_manager = Arc::new(NotificationHistoryManager::new());
             if let Err(e) = history_manager.load(app.handle()) {
                 warn!("Failed to load notification history: {}", e);
             }
+            history_manager.set_redaction(notification_manager.get_settings().redaction);
+            history_manager.set_metadata_only(notification_manager.get_settings().history_metadata_only);
+            // 保持期間を過ぎた履歴を削除する
+            history_manager.set_retention(notification_manager.get_settings().history_retention);
+            if let Err(e) = history_manager.compact(app.handle()) {
+                warn!("Failed to compact notification history: {}", e);
+            }
+            // 履歴から削除された通知の添付画像を片付ける
+            attachment::prune(app.handle(), &history_manager.attachments());
+
+            // セッションのメトリクスの記録を読み込む
+            let metrics_history = Arc::new(metrics_history::MetricsHistory::default());
+            if let Err(e) = metrics_history.load(app.handle()) {
+                warn!("Failed to load metrics history: {}", e);
+            }
 
             // Store managers in app state for access from window events and commands
             app.manage(notification_manager.clone());
             app.manage(history_manager.clone());
+            app.manage(metrics_history);
+            // 前回の終了時（異常終了を含む）の未読数・承認待ちからバッジと点滅を再開する
+            notification_manager.restore_state(app.handle());
+
+            // コマンドライン引数のおやすみモード・プロファイルを反映する
+            cli::apply(app.handle(), &args);
+            if args.hidden {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+            }
+            app.manage(question_prompt::QuestionPrompts::default());
+            app.manage(transcripts::TranscriptIndex::default());
+            app.manage(problems::ProblemLog::default());
+            app.manage(rate_limit::RateLimiter::default());
+            app.manage(hook_version::HookVersionMonitor::default());
+            app.manage(script_hook::ScriptHookRunner::default());
+            app.manage(routing::RoutingEngine::default());
+            app.manage(heartbeat::HeartbeatMonitor::default());
+            app.manage(updater::UpdateState::default());
+            app.manage(digest::DigestState::default());
+            app.manage(cost_report::CostReportState::default());
+
+            // 以前のバージョンの設定のトークンは、発行済みのトークンの一覧に移す
+            let api_tokens = api_tokens::ApiTokens::load(app.handle());
+            let mut settings = notification_manager.get_settings();
+            let legacy_token = settings.rest_api.token.trim().to_string();
+            if !legacy_token.is_empty() {
+                match api_tokens.import(app.handle(), "REST API", &legacy_token) {
+                    Ok(()) => {
+                        settings.rest_api.token.clear();
+                        if let Err(e) = settings::save_settings(app.handle(), &settings) {
+                            warn!("Failed to clear the REST API token from settings: {}", e);
+                        }
+                        notification_manager.update_settings(settings);
+                    }
+                    Err(e) => warn!("Failed to import the REST API token: {}", e),
+                }
+            }
+            app.manage(api_tokens);
+
+            // Start local REST API (opt-in)
+            rest_api::start(
+                &notification_manager.get_settings().rest_api,
+                rest_api::ApiContext {
+                    app: app.handle().clone(),
+                    session_manager: session_manager.clone(),
+                    history_manager: history_manager.clone(),
+                    notification_manager: notification_manager.clone(),
+                },
+            );
+
+            // トピックごとのメッセージのハンドラー
+            app.manage(handlers::HandlerRegistry::with_default_handlers());
+
+            // 使用中のポートを避けた場合は、スクリプトの再エクスポートを促す
+            // （ルーティングなどの状態を参照するため、すべての状態を登録した後に送る）
+            if let Some((requested, port)) = port_fallback {
+                let strings = notification_manager.strings();
+                let body = i18n::fill(
+                    strings.port_fallback_body,
+                    &[("requested", &requested.to_string()), ("port", &port.to_string())],
+                );
+                notification_manager.notify(
+                    app.handle(),
+                    strings.port_fallback_title,
+                    &body,
+                    NotificationEventType::Notification,
+                    NotificationPriority::High,
+                    NotificationOrigin::default(),
+                );
+            }
 
             let app_handle = app.handle().clone();
-            start_message_handler(app_handle, session_manager.clone(), session_name_manager.clone(), notification_manager, history_manager);
+            start_message_handler(runtime.handle().clone(), app_handle, endpoint, session_manager.clone(), session_name_manager.clone(), notification_manager, history_manager);
+
+            // プロファイルを切り替えるホットキーを登録する
+            if let Err(e) = profiles::register_hotkey(app.handle(), &settings::load_profiles(app.handle()).hotkey) {
+                warn!("Failed to register profile hotkey: {}", e);
+            }
+
+            // 時間帯・ネットワーク・ディスプレイに応じてプロファイルを自動で切り替える
+            profile_rules::start(app.handle());
+
+            // カレンダーの予定を定期的に取得する（予定中は通知を止める）
+            calendar::start_sync(app.handle());
+
+            // ハートビートが途絶えたホストを警告する
+            heartbeat::start_monitor(app.handle());
+
+            // IP アドレスの変化を通知し、設定したマシンに新しい接続先を送る
+            host_update::start_monitor(app.handle());
+
+            // 設定で有効な場合は更新を確認する
+            updater::start(app.handle());
+
+            // 設定で有効な場合はダイジェストを送る
+            digest::start(app.handle());
+
+            // 設定で有効な場合は毎日のコストレポートを送る
+            cost_report::start(app.handle());
+
+            // 保持期間を過ぎた通知履歴を定期的に削除する
+            notification_history::start_pruning(app.handle());
+
+            // セッションのコスト・変更行数の推移を記録する
+            metrics_history::start_sampling(app.handle());
+
+            // 設定で有効な場合は、入力待ちに変わったセッションを知らせる
+            session_alerts::start_monitor(app.handle());
+
+            // 設定で有効な場合は、ルーティングスクリプトの変更を読み込み直す
+            routing::start_watcher(app.handle());
+
+            // トレイのステータスメニューを定期的に更新する（ブローカー・クライアントの状態を反映）
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                if let Some(notification_manager) = app_handle.try_state::<Arc<NotificationManager>>() {
+                    notification_manager.refresh_tray_status(&app_handle);
+                }
+                std::thread::sleep(TRAY_STATUS_REFRESH_INTERVAL);
+            });
 
             info!("Application setup complete");
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_broker_status,
+            get_metrics,
+            metrics_history::get_session_metrics,
+            metrics_history::get_daily_costs,
+            cli::take_launch_tab,
+            get_broker_metrics,
             detect_ip,
             generate_config_zip,
             generate_config_zip_v2,
+            install_hooks_locally,
+            hook_templates::get_hook_templates,
+            hook_templates::save_hook_template,
+            hook_templates::reset_hook_template,
+            generate_signing_secret,
+            generate_encryption_key,
+            api_tokens::list_api_tokens,
+            api_tokens::issue_api_token,
+            api_tokens::rotate_api_token,
+            api_tokens::revoke_api_token,
             settings::get_settings,
             save_settings_command,
             audio::play_test_sound,
+            audio::preview_sound,
+            channel_test::test_toast,
+            channel_test::test_taskbar,
+            channel_test::test_tray_flash,
+            channel_test::simulate_stop_event,
+            dev_events::send_fake_event,
+            problems::get_problems,
+            routing::open_routing_script,
+            problems::clear_problems,
+            security_events::get_security_events,
+            security_events::clear_security_events,
+            heartbeat::get_heartbeats,
+            host_update::push_broker_host,
+            updater::check_for_update,
+            digest::send_digest_now,
+            webhook::test_webhook,
+            redaction::validate_redaction_pattern,
+            session_control::send_session_command,
             get_notification_history,
+            search_history,
+            open_transcript,
+            get_response_stats,
+            pin_notification,
             mark_notification_read,
             mark_all_notifications_read,
+            respond_to_permission_request,
+            get_question_prompt,
+            open_question_prompt,
+            answer_question,
             clear_notification_history,
-            get_unread_count
+            compact_notification_history,
+            get_attachment_image,
+            get_unread_count,
+            profiles::get_profiles,
+            profiles::save_profile,
+            profiles::delete_profile,
+            profiles::switch_profile,
+            profiles::set_profile_hotkey,
+            profiles::set_profile_rules
         ])
         .on_window_event(|window, event| {
             match event {
@@ -885,8 +2336,16 @@ pub fn run() {
                         info!("Notification state reset on window focus");
                     }
                 }
-                tauri::WindowEvent::CloseRequested { api, .. } => {
-                    // Prevent the window from closing, hide it instead
+                tauri::WindowEvent::ThemeChanged(_) => {
+                    // OS のテーマ変更に合わせてトレイアイコンを切り替える（テーマ設定が auto の場合）
+                    let app_handle = window.app_handle();
+                    if let Some(notification_manager) = app_handle.try_state::<Arc<NotificationManager>>() {
+                        notification_manager.apply_tray_theme(app_handle);
+                    }
+                }
+                tauri::WindowEvent::CloseRequested { api, .. } if window.label() == "main" => {
+                    // Prevent the main window from closing, hide it instead
+                    // (other windows such as the question prompt close normally)
                     api.prevent_close();
                     if let Err(e) = window.hide() {
                         error!("Failed to hide window: {}", e);
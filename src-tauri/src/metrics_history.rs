@@ -0,0 +1,329 @@
+//! メトリクスの履歴モジュール
+//!
+//! SessionManager はステータスラインから届いた最新の値しか持たないため、
+//! セッションごとのコスト・コンテキスト使用率・変更行数を定期的に記録し、
+//! セッションの推移のグラフや日ごとのコスト・プロジェクトごとのコストの集計に使えるようにする。
+//! 記録は tauri-plugin-store（metrics_history.json）に保存し、一定期間を過ぎたものは削除する。
+
+use crate::project_aliases::ProjectAliasSettings;
+use crate::state::{SessionData, SessionManager};
+use crate::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tracing::warn;
+
+const STORE_FILE: &str = "metrics_history.json";
+const STORE_KEY: &str = "sessions";
+
+/// 記録する間隔
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// 記録を保持する日数
+const RETENTION_DAYS: i64 = 30;
+
+/// 1セッションあたりの最大の記録数（5分間隔で約1週間分）
+const MAX_SAMPLES_PER_SESSION: usize = 2000;
+
+/// ある時点のセッションのメトリクス
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricSample {
+    pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
+    #[serde(default)]
+    pub context_percent: Option<f64>,
+    #[serde(default)]
+    pub lines_added: Option<i64>,
+    #[serde(default)]
+    pub lines_removed: Option<i64>,
+}
+
+impl MetricSample {
+    fn from_session(session: &SessionData, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            timestamp,
+            cost_usd: session.status.cost_usd,
+            context_percent: session.status.context_percent,
+            lines_added: session.status.lines_added,
+            lines_removed: session.status.lines_removed,
+        }
+    }
+
+    /// 時刻以外の値が同じか（変化のない記録は追加しない）
+    fn same_values(&self, other: &Self) -> bool {
+        self.cost_usd == other.cost_usd
+            && self.context_percent == other.context_percent
+            && self.lines_added == other.lines_added
+            && self.lines_removed == other.lines_removed
+    }
+}
+
+/// セッションごとの記録
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionMetricsHistory {
+    pub cwd: String,
+    #[serde(default)]
+    pub host: Option<String>,
+    /// 古い順
+    pub samples: Vec<MetricSample>,
+}
+
+/// 日ごとのコスト
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyCost {
+    pub date: NaiveDate,
+    pub cost_usd: f64,
+}
+
+/// セッションごとのメトリクスの記録
+#[derive(Debug, Default)]
+pub struct MetricsHistory {
+    sessions: RwLock<BTreeMap<String, SessionMetricsHistory>>,
+}
+
+impl MetricsHistory {
+    /// セッションの現在の値を記録する（前回から変化がない場合は記録しない）
+    pub fn record(&self, session: &SessionData, now: DateTime<Utc>) -> bool {
+        let sample = MetricSample::from_session(session, now);
+        let mut sessions = self.sessions.write().unwrap();
+        let history = sessions.entry(session.session_id.clone()).or_default();
+        history.cwd = session.cwd.clone();
+        history.host = session.host.clone();
+        if history.samples.last().is_some_and(|last| last.same_values(&sample)) {
+            return false;
+        }
+        history.samples.push(sample);
+        if history.samples.len() > MAX_SAMPLES_PER_SESSION {
+            let excess = history.samples.len() - MAX_SAMPLES_PER_SESSION;
+            history.samples.drain(..excess);
+        }
+        true
+    }
+
+    /// 保持期間を過ぎた記録を削除する
+    pub fn prune(&self, now: DateTime<Utc>) {
+        let cutoff = now - ChronoDuration::days(RETENTION_DAYS);
+        let mut sessions = self.sessions.write().unwrap();
+        for history in sessions.values_mut() {
+            history.samples.retain(|sample| sample.timestamp >= cutoff);
+        }
+        sessions.retain(|_, history| !history.samples.is_empty());
+    }
+
+    pub fn session(&self, session_id: &str) -> Option<SessionMetricsHistory> {
+        self.sessions.read().unwrap().get(session_id).cloned()
+    }
+
+    /// 直近 `days` 日の日ごとのコスト（古い順、コストのない日も含む）
+    ///
+    /// ステータスラインのコストはセッションの累計のため、前回の記録からの増分をその記録の日に加える。
+    pub fn daily_costs<Tz: TimeZone>(&self, days: u32, now: &DateTime<Tz>) -> Vec<DailyCost> {
+        let today = now.date_naive();
+        let first = today - ChronoDuration::days(i64::from(days.max(1)) - 1);
+        let mut costs: BTreeMap<NaiveDate, f64> = (0..days.max(1))
+            .map(|offset| (first + ChronoDuration::days(i64::from(offset)), 0.0))
+            .collect();
+
+        let sessions = self.sessions.read().unwrap();
+        for history in sessions.values() {
+            let mut previous = 0.0;
+            for sample in &history.samples {
+                let Some(cost) = sample.cost_usd else {
+                    continue;
+                };
+                let date = sample.timestamp.with_timezone(&now.timezone()).date_naive();
+                if let Some(total) = costs.get_mut(&date) {
+                    *total += (cost - previous).max(0.0);
+                }
+                previous = cost;
+            }
+        }
+
+        costs
+            .into_iter()
+            .map(|(date, cost_usd)| DailyCost { date, cost_usd })
+            .collect()
+    }
+
+    /// `since` より後にかかったプロジェクトごとのコスト（多い順、コストのないプロジェクトは除く）
+    ///
+    /// `since` 以前の最後の記録からの増分を数える。プロジェクトは別名でまとめる。
+    pub fn project_costs(&self, since: DateTime<Utc>, projects: &ProjectAliasSettings) -> Vec<(String, f64)> {
+        let mut costs: BTreeMap<String, f64> = BTreeMap::new();
+        let sessions = self.sessions.read().unwrap();
+        for history in sessions.values() {
+            let mut previous = 0.0;
+            let mut cost_in_period = 0.0;
+            for sample in &history.samples {
+                let Some(cost) = sample.cost_usd else {
+                    continue;
+                };
+                if sample.timestamp > since {
+                    cost_in_period += (cost - previous).max(0.0);
+                }
+                previous = cost;
+            }
+            if cost_in_period > 0.0 {
+                *costs.entry(projects.project_name(&history.cwd)).or_default() += cost_in_period;
+            }
+        }
+
+        let mut costs: Vec<(String, f64)> = costs.into_iter().collect();
+        costs.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        costs
+    }
+
+    pub fn load(&self, app: &AppHandle) -> Result<(), String> {
+        let store = app
+            .store(STORE_FILE)
+            .map_err(|e| format!("Failed to open store: {}", e))?;
+
+        if let Some(value) = store.get(STORE_KEY) {
+            let sessions: BTreeMap<String, SessionMetricsHistory> = serde_json::from_value(value.clone())
+                .map_err(|e| format!("Failed to parse metrics history: {}", e))?;
+            *self.sessions.write().unwrap() = sessions;
+        }
+
+        Ok(())
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let store = app
+            .store(STORE_FILE)
+            .map_err(|e| format!("Failed to open store: {}", e))?;
+
+        let value = serde_json::to_value(&*self.sessions.read().unwrap())
+            .map_err(|e| format!("Failed to serialize metrics history: {}", e))?;
+
+        store.set(STORE_KEY, value);
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store: {}", e))?;
+
+        Ok(())
+    }
+}
+
+fn sample_sessions(app: &AppHandle, session_manager: &SessionManager) {
+    let metrics_history = app.state::<Arc<MetricsHistory>>();
+    let now = Utc::now();
+    let mut changed = false;
+    for session in session_manager.get_sessions() {
+        changed |= metrics_history.record(&session, now);
+    }
+    metrics_history.prune(now);
+    if changed {
+        if let Err(e) = metrics_history.save(app) {
+            warn!("Failed to save metrics history: {}", e);
+        }
+    }
+}
+
+/// ステータスを送信中のセッションのメトリクスを定期的に記録する
+pub fn start_sampling(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SAMPLE_INTERVAL);
+
+        let session_manager = match app.state::<std::sync::Mutex<AppState>>().lock() {
+            Ok(state) => state.session_manager.clone(),
+            Err(_) => continue,
+        };
+        sample_sessions(&app, &session_manager);
+    });
+}
+
+/// Tauriコマンド: セッションのメトリクスの推移を取得
+#[tauri::command]
+pub fn get_session_metrics(
+    metrics_history: tauri::State<'_, Arc<MetricsHistory>>,
+    session_id: String,
+) -> Option<SessionMetricsHistory> {
+    metrics_history.session(&session_id)
+}
+
+/// Tauriコマンド: 直近の日ごとのコストを取得
+#[tauri::command]
+pub fn get_daily_costs(metrics_history: tauri::State<'_, Arc<MetricsHistory>>, days: u32) -> Vec<DailyCost> {
+    metrics_history.daily_costs(days.min(RETENTION_DAYS as u32), &Local::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{SessionStatus, StatusPayload};
+    use chrono::FixedOffset;
+
+    fn session(id: &str, cost: f64, lines_added: i64) -> SessionData {
+        session_in("/work/my-app", id, cost, lines_added)
+    }
+
+    fn session_in(cwd: &str, id: &str, cost: f64, lines_added: i64) -> SessionData {
+        SessionData::new(StatusPayload {
+            session_id: id.to_string(),
+            cwd: cwd.to_string(),
+            status: SessionStatus {
+                cost_usd: Some(cost),
+                lines_added: Some(lines_added),
+                ..SessionStatus::default()
+            },
+            host: None,
+            timestamp: None,
+        })
+    }
+
+    #[test]
+    fn test_record_skips_unchanged_values() {
+        let history = MetricsHistory::default();
+        let now = Utc::now();
+        assert!(history.record(&session("s1", 0.5, 10), now));
+        assert!(!history.record(&session("s1", 0.5, 10), now + ChronoDuration::minutes(5)));
+        assert!(history.record(&session("s1", 0.8, 40), now + ChronoDuration::minutes(10)));
+
+        let samples = history.session("s1").unwrap().samples;
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1].lines_added, Some(40));
+
+        history.prune(now + ChronoDuration::days(RETENTION_DAYS + 1));
+        assert!(history.session("s1").is_none());
+    }
+
+    #[test]
+    fn test_daily_costs_use_increments() {
+        let tz = FixedOffset::east_opt(9 * 3600).unwrap();
+        let day = |d: u32, h: u32| tz.with_ymd_and_hms(2026, 3, d, h, 0, 0).unwrap().with_timezone(&Utc);
+        let history = MetricsHistory::default();
+        history.record(&session("s1", 1.0, 0), day(1, 10));
+        history.record(&session("s1", 1.5, 0), day(1, 20));
+        // 日をまたいだセッションは増分だけをその日に数える
+        history.record(&session("s1", 2.5, 0), day(2, 9));
+        history.record(&session("s2", 0.25, 0), day(2, 12));
+
+        let now = tz.with_ymd_and_hms(2026, 3, 2, 23, 0, 0).unwrap();
+        let costs = history.daily_costs(3, &now);
+        assert_eq!(costs.len(), 3);
+        assert_eq!(costs[0].cost_usd, 0.0);
+        assert_eq!(costs[1], DailyCost { date: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(), cost_usd: 1.5 });
+        assert_eq!(costs[2].cost_usd, 1.25);
+    }
+
+    #[test]
+    fn test_project_costs_since() {
+        let now = Utc::now();
+        let history = MetricsHistory::default();
+        history.record(&session_in("/work/api", "s1", 1.0, 0), now - ChronoDuration::hours(30));
+        // 期間の前の記録からの増分だけを数える
+        history.record(&session_in("/work/api", "s1", 1.5, 0), now - ChronoDuration::hours(2));
+        history.record(&session_in("/work/web", "s2", 0.75, 0), now - ChronoDuration::hours(1));
+        history.record(&session_in("/work/web", "s3", 0.25, 0), now);
+        history.record(&session_in("/work/old", "s4", 3.0, 0), now - ChronoDuration::hours(25));
+
+        let costs = history.project_costs(now - ChronoDuration::hours(24), &ProjectAliasSettings::default());
+        assert_eq!(costs, vec![("web".to_string(), 1.0), ("api".to_string(), 0.5)]);
+    }
+}
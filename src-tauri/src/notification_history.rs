@@ -1,12 +1,31 @@
 //! 通知履歴管理モジュール
 //!
 //! 通知イベントの履歴を管理し、永続化する。
+//! 保持件数・保持日数（イベント種別ごとに変更可能）を超えた履歴は、
+//! 追加時と定期的な整理で削除する（ピン留めしたエントリは削除しない）。
+//! 履歴タブは全件を読み込まず、検索条件（[`HistoryQuery`]）に一致するエントリをページ単位で取得する。
+//! 内容は記録する前に機密情報をマスクする（[`RedactionSettings`]）。
+//! 内容を残さない設定では、プロジェクト・種類・時刻などのメタデータだけを記録する。
 
+use crate::attachment;
+use crate::permission_response::PermissionDecision;
+use crate::redaction::RedactionSettings;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::sync::RwLock;
-use tauri::AppHandle;
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
 use tauri_plugin_store::StoreExt;
+use tracing::{info, warn};
+
+/// 保持期間を過ぎた履歴を整理する間隔
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 検索結果の1ページの既定の件数
+const DEFAULT_PAGE_SIZE: usize = 50;
+/// 検索結果の1ページの最大件数
+const MAX_PAGE_SIZE: usize = 500;
 
 /// 通知イベントの種類
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -16,6 +35,16 @@ pub enum NotificationEventType {
     Notification,
 }
 
+/// 承認依頼のあとにセッションがどうなったか
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionOutcome {
+    /// 作業を再開した（承認された）
+    Resumed,
+    /// 作業を終えた（拒否・中断された）
+    Stopped,
+}
+
 /// 通知履歴エントリ
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationHistoryEntry {
@@ -25,15 +54,277 @@ pub struct NotificationHistoryEntry {
     pub session_id: String,
     pub cwd: Option<String>,
     pub content: Option<String>,
+    /// 履歴に記録した（イベントを受信した）時刻
     pub timestamp: DateTime<Utc>,
+    /// フックがイベントを送信した時刻（ペイロードの `timestamp`。ない・読めない場合は None）
+    #[serde(default)]
+    pub sent_at: Option<DateTime<Utc>>,
     pub read: bool,
+    /// フックスクリプトが応答を待っている承認依頼のID
+    #[serde(default)]
+    pub request_id: Option<String>,
+    /// 承認依頼への応答（未応答の場合は None）
+    #[serde(default)]
+    pub decision: Option<PermissionDecision>,
+    /// イベントを送信したホスト
+    #[serde(default)]
+    pub host: Option<String>,
+    /// 添付画像のファイル名（attachment モジュールで保存したもの）
+    #[serde(default)]
+    pub image: Option<String>,
+    /// ピン留め（保持期間・最大件数による削除の対象外）
+    #[serde(default)]
+    pub pinned: bool,
+    /// 承認依頼のあとにセッションがどうなったか（未確定の場合は None）
+    #[serde(default)]
+    pub outcome: Option<PermissionOutcome>,
+    /// 承認依頼に応答した時刻（アプリから応答した時刻、またはセッションの状態が変わった時刻）
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// セッションのトランスクリプトのパス（このマシンで読めるパスに変換したもの）
+    #[serde(default)]
+    pub transcript_path: Option<String>,
+}
+
+impl NotificationHistoryEntry {
+    /// 承認依頼から応答までにかかった時間
+    pub fn response_time(&self) -> Option<chrono::Duration> {
+        self.resolved_at.map(|resolved_at| resolved_at - self.timestamp)
+    }
+
+    /// メッセージ・コマンド・添付画像・トランスクリプトを取り除き、メタデータだけにする
+    ///
+    /// 承認依頼のツール名（`Bash`・`AskUserQuestion` など）は種類として残す（質問への回答ボタンの表示に使う）。
+    fn strip_content(&mut self) {
+        let is_tool_name = |content: &String| {
+            !content.is_empty()
+                && content
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+        };
+        self.content = self
+            .content
+            .take()
+            .filter(|content| self.event_type == NotificationEventType::PermissionRequest && is_tool_name(content));
+        self.image = None;
+        self.transcript_path = None;
+    }
+}
+
+/// 承認依頼への応答の統計
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResponseStats {
+    /// 応答した承認依頼の数
+    pub responded: usize,
+    /// 応答までにかかった時間の中央値（秒）
+    pub median_secs: Option<u64>,
+}
+
+impl ResponseStats {
+    fn from_entries(entries: &[NotificationHistoryEntry]) -> Self {
+        let mut secs: Vec<u64> = entries
+            .iter()
+            .filter(|e| e.event_type == NotificationEventType::PermissionRequest)
+            .filter_map(|e| e.response_time())
+            .map(|d| d.num_seconds().max(0) as u64)
+            .collect();
+        secs.sort_unstable();
+        let median_secs = match secs.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(secs[n / 2]),
+            n => Some((secs[n / 2 - 1] + secs[n / 2]) / 2),
+        };
+        Self {
+            responded: secs.len(),
+            median_secs,
+        }
+    }
+}
+
+/// 履歴の保持期間の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryRetention {
+    /// 保持する最大件数
+    pub max_entries: usize,
+    /// 保持する日数（0 の場合は無期限）
+    pub max_age_days: u32,
+    /// タスク完了の保持日数（None の場合は max_age_days に従う）
+    pub stop_days: Option<u32>,
+    /// 承認依頼の保持日数（None の場合は max_age_days に従う）
+    pub permission_request_days: Option<u32>,
+    /// その他の通知の保持日数（None の場合は max_age_days に従う）
+    pub notification_days: Option<u32>,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_entries: 100,
+            max_age_days: 0,
+            stop_days: None,
+            permission_request_days: None,
+            notification_days: None,
+        }
+    }
+}
+
+impl HistoryRetention {
+    /// イベント種別の保持日数（0 の場合は無期限）
+    fn max_age_days(&self, event_type: &NotificationEventType) -> u32 {
+        let days = match event_type {
+            NotificationEventType::Stop => self.stop_days,
+            NotificationEventType::PermissionRequest => self.permission_request_days,
+            NotificationEventType::Notification => self.notification_days,
+        };
+        days.unwrap_or(self.max_age_days)
+    }
+
+    /// 保持期間を過ぎたエントリと最大件数を超えた古いエントリを削除し、削除した件数を返す
+    ///
+    /// ピン留めしたエントリは削除せず、最大件数にも数えない。
+    fn prune(&self, entries: &mut Vec<NotificationHistoryEntry>, now: DateTime<Utc>) -> usize {
+        let before = entries.len();
+        entries.retain(|entry| {
+            let days = self.max_age_days(&entry.event_type);
+            entry.pinned || days == 0 || now - entry.timestamp < chrono::Duration::days(i64::from(days))
+        });
+        // 新しいものが先頭にあるため、末尾から削除する
+        let max_entries = self.max_entries.max(1);
+        let mut kept = 0;
+        entries.retain(|entry| {
+            if entry.pinned {
+                return true;
+            }
+            kept += 1;
+            kept <= max_entries
+        });
+        before - entries.len()
+    }
+}
+
+/// 履歴の検索条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryQuery {
+    /// 内容・プロジェクト（作業ディレクトリ）・セッション名・トランスクリプトに含まれる文字（大文字と小文字は区別しない）
+    pub text: Option<String>,
+    /// ピン留めしたもの（true）・していないもの（false）のみ
+    pub pinned: Option<bool>,
+    /// セッション名（完全一致）
+    pub session_name: Option<String>,
+    /// イベントの種類（空の場合はすべて）
+    pub event_types: Vec<NotificationEventType>,
+    /// この時刻以降
+    pub from: Option<DateTime<Utc>>,
+    /// この時刻以前
+    pub to: Option<DateTime<Utc>>,
+    /// 読み飛ばす件数
+    pub offset: usize,
+    /// 1ページの件数（None の場合は既定の件数）
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    fn matches(&self, entry: &NotificationHistoryEntry, text: Option<&str>, transcript_contains: &TranscriptMatcher<'_>) -> bool {
+        if self.session_name.as_deref().is_some_and(|name| name != entry.session_name) {
+            return false;
+        }
+        if self.pinned.is_some_and(|pinned| pinned != entry.pinned) {
+            return false;
+        }
+        if !self.event_types.is_empty() && !self.event_types.contains(&entry.event_type) {
+            return false;
+        }
+        if self.from.is_some_and(|from| entry.timestamp < from) || self.to.is_some_and(|to| entry.timestamp > to) {
+            return false;
+        }
+        let Some(text) = text else {
+            return true;
+        };
+        [Some(entry.session_name.as_str()), entry.cwd.as_deref(), entry.content.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(|field| field.to_lowercase().contains(text))
+            || entry.transcript_path.as_deref().is_some_and(|path| transcript_contains(path, text))
+    }
+
+    /// 新しい順に並んだ履歴から、条件に一致するページを取り出す
+    fn apply(&self, entries: &[NotificationHistoryEntry], transcript_contains: &TranscriptMatcher<'_>) -> HistoryPage {
+        let text = self
+            .text
+            .as_deref()
+            .map(|text| text.trim().to_lowercase())
+            .filter(|text| !text.is_empty());
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+        let mut total = 0;
+        let mut page = Vec::new();
+        for entry in entries.iter().filter(|entry| self.matches(entry, text.as_deref(), transcript_contains)) {
+            if total >= self.offset && page.len() < limit {
+                page.push(entry.clone());
+            }
+            total += 1;
+        }
+
+        let mut session_names: Vec<String> = entries
+            .iter()
+            .map(|entry| entry.session_name.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        session_names.sort();
+
+        HistoryPage {
+            entries: page,
+            total,
+            offset: self.offset,
+            session_names,
+        }
+    }
+}
+
+/// トランスクリプト（パス）に検索する文字（小文字にしたもの）が含まれるか
+pub type TranscriptMatcher<'a> = dyn Fn(&str, &str) -> bool + 'a;
+
+/// 履歴の検索結果
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage {
+    pub entries: Vec<NotificationHistoryEntry>,
+    /// 条件に一致した件数（ページに含まれない分も含む）
+    pub total: usize,
+    pub offset: usize,
+    /// 履歴にあるすべてのセッション名（絞り込みの候補）
+    pub session_names: Vec<String>,
+}
+
+/// 履歴に追加する通知
+#[derive(Debug, Clone)]
+pub struct NewHistoryEntry {
+    pub event_type: NotificationEventType,
+    pub session_name: String,
+    pub session_id: String,
+    pub cwd: Option<String>,
+    pub content: Option<String>,
+    /// フックスクリプトが応答を待っている承認依頼のID
+    pub request_id: Option<String>,
+    /// イベントを送信したホスト
+    pub host: Option<String>,
+    /// 添付画像のファイル名
+    pub image: Option<String>,
+    /// セッションのトランスクリプトのパス
+    pub transcript_path: Option<String>,
+    /// フックがイベントを送信した時刻
+    pub sent_at: Option<DateTime<Utc>>,
 }
 
 /// 通知履歴マネージャー
 pub struct NotificationHistoryManager {
     entries: RwLock<Vec<NotificationHistoryEntry>>,
     next_id: RwLock<u64>,
-    max_entries: usize,
+    retention: RwLock<HistoryRetention>,
+    redaction: RwLock<RedactionSettings>,
+    metadata_only: RwLock<bool>,
 }
 
 impl Default for NotificationHistoryManager {
@@ -48,8 +339,35 @@ impl NotificationHistoryManager {
         Self {
             entries: RwLock::new(Vec::new()),
             next_id: RwLock::new(1),
-            max_entries: 100,
+            retention: RwLock::new(HistoryRetention::default()),
+            redaction: RwLock::new(RedactionSettings::default()),
+            metadata_only: RwLock::new(false),
+        }
+    }
+
+    /// 保持期間の設定を変更する（次の追加・整理から反映）
+    pub fn set_retention(&self, retention: HistoryRetention) {
+        *self.retention.write().unwrap() = retention;
+    }
+
+    /// 機密情報のマスクの設定を変更する（次の追加から反映）
+    pub fn set_redaction(&self, redaction: RedactionSettings) {
+        *self.redaction.write().unwrap() = redaction;
+    }
+
+    /// 内容を残さない設定を変更する（次の追加から反映。記録済みの内容は [`Self::strip_contents`] で削除する）
+    pub fn set_metadata_only(&self, metadata_only: bool) {
+        *self.metadata_only.write().unwrap() = metadata_only;
+    }
+
+    /// 記録済みの履歴から内容を取り除き、メタデータだけにする
+    pub fn strip_contents(&self, app: &AppHandle) -> Result<(), String> {
+        {
+            let mut entries = self.entries.write().unwrap();
+            entries.iter_mut().for_each(NotificationHistoryEntry::strip_content);
         }
+        info!("Removed contents from notification history");
+        self.save(app)
     }
 
     /// 履歴をロード
@@ -91,15 +409,7 @@ impl NotificationHistoryManager {
     }
 
     /// 新しいエントリを追加
-    pub fn add_entry(
-        &self,
-        app: &AppHandle,
-        event_type: NotificationEventType,
-        session_name: String,
-        session_id: String,
-        cwd: Option<String>,
-        content: Option<String>,
-    ) -> Result<u64, String> {
+    pub fn add_entry(&self, app: &AppHandle, new_entry: NewHistoryEntry) -> Result<u64, String> {
         let id = {
             let mut next_id = self.next_id.write().unwrap();
             let id = *next_id;
@@ -107,26 +417,38 @@ impl NotificationHistoryManager {
             id
         };
 
-        let entry = NotificationHistoryEntry {
+        let mut entry = NotificationHistoryEntry {
             id,
-            event_type,
-            session_name,
-            session_id,
-            cwd,
-            content,
+            event_type: new_entry.event_type,
+            session_name: new_entry.session_name,
+            session_id: new_entry.session_id,
+            cwd: new_entry.cwd,
+            content: new_entry
+                .content
+                .map(|content| self.redaction.read().unwrap().redact(&content)),
             timestamp: Utc::now(),
+            sent_at: new_entry.sent_at,
             read: false,
+            request_id: new_entry.request_id,
+            decision: None,
+            host: new_entry.host,
+            pinned: false,
+            image: new_entry.image,
+            outcome: None,
+            resolved_at: None,
+            transcript_path: new_entry.transcript_path,
         };
+        if *self.metadata_only.read().unwrap() {
+            entry.strip_content();
+        }
 
         {
             let mut entries = self.entries.write().unwrap();
             // 先頭に追加（新しいものが上）
             entries.insert(0, entry);
 
-            // 最大件数を超えたら古いものを削除
-            if entries.len() > self.max_entries {
-                entries.truncate(self.max_entries);
-            }
+            // 保持期間・最大件数を超えたら古いものを削除
+            self.retention.read().unwrap().prune(&mut entries, Utc::now());
         }
 
         // 永続化
@@ -149,26 +471,141 @@ impl NotificationHistoryManager {
         }
     }
 
-    /// 特定のエントリを既読にする
-    pub fn mark_as_read(&self, app: &AppHandle, id: u64) -> Result<(), String> {
+    /// 条件に一致する履歴をページ単位で検索する
+    pub fn search(&self, query: &HistoryQuery, transcript_contains: &TranscriptMatcher<'_>) -> HistoryPage {
+        let entries = self.entries.read().unwrap();
+        query.apply(&entries, transcript_contains)
+    }
+
+    /// IDを指定してエントリを取得
+    pub fn get_entry(&self, id: u64) -> Option<NotificationHistoryEntry> {
+        let entries = self.entries.read().unwrap();
+        entries.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// ピン留めを切り替える（見つからない場合は false）
+    pub fn set_pinned(&self, app: &AppHandle, id: u64, pinned: bool) -> Result<bool, String> {
+        let found = {
+            let mut entries = self.entries.write().unwrap();
+            entries
+                .iter_mut()
+                .find(|e| e.id == id)
+                .map(|entry| entry.pinned = pinned)
+                .is_some()
+        };
+        if found {
+            self.save(app)?;
+        }
+        Ok(found)
+    }
+
+    /// 承認依頼への応答を記録し、既読にする
+    pub fn set_decision(
+        &self,
+        app: &AppHandle,
+        id: u64,
+        decision: PermissionDecision,
+    ) -> Result<(), String> {
         {
             let mut entries = self.entries.write().unwrap();
             if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+                entry.decision = Some(decision);
+                entry.resolved_at.get_or_insert_with(Utc::now);
                 entry.read = true;
             }
         }
         self.save(app)
     }
 
+    /// セッションの未確定の承認依頼に、そのあとのセッションの変化を記録する
+    ///
+    /// 応答した時刻が未記録の場合は `now` を記録する。記録したエントリの数を返す。
+    pub fn resolve_permission_requests(
+        &self,
+        app: &AppHandle,
+        session_id: &str,
+        outcome: PermissionOutcome,
+        now: DateTime<Utc>,
+    ) -> Result<usize, String> {
+        let resolved = {
+            let mut entries = self.entries.write().unwrap();
+            resolve_permission_requests(&mut entries, session_id, outcome, now)
+        };
+        if resolved > 0 {
+            self.save(app)?;
+        }
+        Ok(resolved)
+    }
+
+    /// 承認依頼への応答の統計
+    pub fn response_stats(&self) -> ResponseStats {
+        ResponseStats::from_entries(&self.entries.read().unwrap())
+    }
+
+    /// 特定のエントリを既読にする
+    ///
+    /// 未読から既読に変わったエントリを返す（既読済み・存在しない場合は None）。
+    pub fn mark_as_read(
+        &self,
+        app: &AppHandle,
+        id: u64,
+    ) -> Result<Option<NotificationHistoryEntry>, String> {
+        let marked = {
+            let mut entries = self.entries.write().unwrap();
+            entries
+                .iter_mut()
+                .find(|e| e.id == id && !e.read)
+                .map(|entry| {
+                    entry.read = true;
+                    entry.clone()
+                })
+        };
+        self.save(app)?;
+        Ok(marked)
+    }
+
     /// すべてのエントリを既読にする
-    pub fn mark_all_as_read(&self, app: &AppHandle) -> Result<(), String> {
-        {
+    ///
+    /// 未読から既読に変わったエントリを返す。
+    pub fn mark_all_as_read(&self, app: &AppHandle) -> Result<Vec<NotificationHistoryEntry>, String> {
+        let marked = {
             let mut entries = self.entries.write().unwrap();
-            for entry in entries.iter_mut() {
-                entry.read = true;
-            }
+            entries
+                .iter_mut()
+                .filter(|e| !e.read)
+                .map(|entry| {
+                    entry.read = true;
+                    entry.clone()
+                })
+                .collect()
+        };
+        self.save(app)?;
+        Ok(marked)
+    }
+
+    /// セッションの未読のエントリをすべて既読にする
+    ///
+    /// 未読から既読に変わったエントリを返す。
+    pub fn mark_session_as_read(
+        &self,
+        app: &AppHandle,
+        session_id: &str,
+    ) -> Result<Vec<NotificationHistoryEntry>, String> {
+        let marked: Vec<_> = {
+            let mut entries = self.entries.write().unwrap();
+            entries
+                .iter_mut()
+                .filter(|e| !e.read && e.session_id == session_id)
+                .map(|entry| {
+                    entry.read = true;
+                    entry.clone()
+                })
+                .collect()
+        };
+        if !marked.is_empty() {
+            self.save(app)?;
         }
-        self.save(app)
+        Ok(marked)
     }
 
     /// 履歴をクリア
@@ -180,9 +617,309 @@ impl NotificationHistoryManager {
         self.save(app)
     }
 
+    /// 保持期間を過ぎた履歴を削除し、削除した件数を返す
+    pub fn compact(&self, app: &AppHandle) -> Result<usize, String> {
+        let removed = {
+            let mut entries = self.entries.write().unwrap();
+            self.retention.read().unwrap().prune(&mut entries, Utc::now())
+        };
+        if removed > 0 {
+            info!("Removed {} notification history entries past retention", removed);
+            self.save(app)?;
+        }
+        Ok(removed)
+    }
+
+    /// 履歴から参照されている添付画像のファイル名
+    pub fn attachments(&self) -> HashSet<String> {
+        let entries = self.entries.read().unwrap();
+        entries.iter().filter_map(|e| e.image.clone()).collect()
+    }
+
     /// 未読件数を取得
     pub fn get_unread_count(&self) -> usize {
         let entries = self.entries.read().unwrap();
         entries.iter().filter(|e| !e.read).count()
     }
 }
+
+/// セッションの未確定の承認依頼に結果を記録し、記録した件数を返す
+fn resolve_permission_requests(
+    entries: &mut [NotificationHistoryEntry],
+    session_id: &str,
+    outcome: PermissionOutcome,
+    now: DateTime<Utc>,
+) -> usize {
+    let mut resolved = 0;
+    for entry in entries.iter_mut().filter(|e| {
+        e.event_type == NotificationEventType::PermissionRequest
+            && e.session_id == session_id
+            && e.outcome.is_none()
+    }) {
+        entry.outcome = Some(outcome);
+        entry.resolved_at.get_or_insert(now);
+        resolved += 1;
+    }
+    resolved
+}
+
+/// 保持期間を過ぎた履歴と、その添付画像を定期的に削除する
+pub fn start_pruning(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(PRUNE_INTERVAL);
+
+        let history_manager = app.state::<Arc<NotificationHistoryManager>>();
+        match history_manager.compact(&app) {
+            Ok(0) => {}
+            Ok(_) => attachment::prune(&app, &history_manager.attachments()),
+            Err(e) => warn!("Failed to prune notification history: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, event_type: NotificationEventType, age_days: i64, now: DateTime<Utc>) -> NotificationHistoryEntry {
+        NotificationHistoryEntry {
+            id,
+            event_type,
+            session_name: "session".to_string(),
+            session_id: "s1".to_string(),
+            cwd: None,
+            content: None,
+            timestamp: now - chrono::Duration::days(age_days),
+            sent_at: None,
+            read: false,
+            request_id: None,
+            decision: None,
+            host: None,
+            image: None,
+            pinned: false,
+            outcome: None,
+            resolved_at: None,
+            transcript_path: None,
+        }
+    }
+
+    fn ids(entries: &[NotificationHistoryEntry]) -> Vec<u64> {
+        entries.iter().map(|e| e.id).collect()
+    }
+
+    fn no_transcripts(_path: &str, _text: &str) -> bool {
+        false
+    }
+
+    #[test]
+    fn test_prune_by_age_per_event_type() {
+        let now = Utc::now();
+        let mut entries = vec![
+            entry(4, NotificationEventType::Notification, 2, now),
+            entry(3, NotificationEventType::Stop, 5, now),
+            entry(2, NotificationEventType::PermissionRequest, 20, now),
+            entry(1, NotificationEventType::Stop, 40, now),
+        ];
+        let retention = HistoryRetention {
+            max_age_days: 30,
+            notification_days: Some(1),
+            permission_request_days: Some(0),
+            ..HistoryRetention::default()
+        };
+        assert_eq!(retention.prune(&mut entries, now), 2);
+        // 承認依頼は無期限、その他の通知は1日、タスク完了は全体の30日
+        assert_eq!(ids(&entries), vec![3, 2]);
+    }
+
+    #[test]
+    fn test_search_filters_and_paginates() {
+        let now = Utc::now();
+        let mut entries: Vec<_> = (1..=5)
+            .rev()
+            .map(|id| entry(id, NotificationEventType::Stop, 5 - id as i64, now))
+            .collect();
+        entries[0].cwd = Some("/home/user/API-server".to_string());
+        entries[1].content = Some("Build failed in api tests".to_string());
+        entries[2].event_type = NotificationEventType::PermissionRequest;
+        entries[3].session_name = "アオイ".to_string();
+
+        let query = HistoryQuery {
+            text: Some(" api ".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&query.apply(&entries, &no_transcripts).entries), vec![5, 4]);
+
+        let query = HistoryQuery {
+            event_types: vec![NotificationEventType::PermissionRequest],
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&query.apply(&entries, &no_transcripts).entries), vec![3]);
+
+        let query = HistoryQuery {
+            text: Some("アオイ".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&query.apply(&entries, &no_transcripts).entries), vec![2]);
+
+        let query = HistoryQuery {
+            from: Some(now - chrono::Duration::days(3)),
+            to: Some(now - chrono::Duration::days(1)),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&query.apply(&entries, &no_transcripts).entries), vec![4, 3, 2]);
+
+        let query = HistoryQuery {
+            offset: 2,
+            limit: Some(2),
+            ..HistoryQuery::default()
+        };
+        let page = query.apply(&entries, &no_transcripts);
+        assert_eq!(ids(&page.entries), vec![3, 2]);
+        assert_eq!(page.total, 5);
+        assert_eq!(page.session_names, vec!["session".to_string(), "アオイ".to_string()]);
+    }
+
+    #[test]
+    fn test_search_matches_transcripts() {
+        let now = Utc::now();
+        let mut entries: Vec<_> = (1..=3)
+            .rev()
+            .map(|id| entry(id, NotificationEventType::Stop, 0, now))
+            .collect();
+        entries[1].transcript_path = Some("/transcripts/a.jsonl".to_string());
+        entries[2].transcript_path = Some("/transcripts/b.jsonl".to_string());
+
+        let query = HistoryQuery {
+            text: Some("Migration".to_string()),
+            ..HistoryQuery::default()
+        };
+        let contains = |path: &str, text: &str| path == "/transcripts/a.jsonl" && text == "migration";
+        assert_eq!(ids(&query.apply(&entries, &contains).entries), vec![2]);
+    }
+
+    #[test]
+    fn test_strip_content_keeps_metadata() {
+        let now = Utc::now();
+        let mut stop = NotificationHistoryEntry {
+            cwd: Some("/work/app".to_string()),
+            content: Some("DB_PASSWORD を設定しました".to_string()),
+            image: Some("summary.png".to_string()),
+            transcript_path: Some("/home/me/.claude/projects/app/abc.jsonl".to_string()),
+            ..entry(1, NotificationEventType::Stop, 0, now)
+        };
+        stop.strip_content();
+        assert_eq!(stop.content, None);
+        assert_eq!(stop.image, None);
+        assert_eq!(stop.transcript_path, None);
+        assert_eq!(stop.cwd.as_deref(), Some("/work/app"));
+        assert_eq!(stop.timestamp, now);
+
+        // 承認依頼はツール名だけを残す
+        let mut question = NotificationHistoryEntry {
+            content: Some("AskUserQuestion".to_string()),
+            request_id: Some("req-1".to_string()),
+            ..entry(2, NotificationEventType::PermissionRequest, 0, now)
+        };
+        question.strip_content();
+        assert_eq!(question.content.as_deref(), Some("AskUserQuestion"));
+        assert_eq!(question.request_id.as_deref(), Some("req-1"));
+        let mut raw = NotificationHistoryEntry {
+            content: Some(r#"{"tool":"Bash","input":{"command":"rm -rf build"}}"#.to_string()),
+            ..entry(3, NotificationEventType::PermissionRequest, 0, now)
+        };
+        raw.strip_content();
+        assert_eq!(raw.content, None);
+    }
+
+    #[test]
+    fn test_sent_at_serialization() {
+        let now = Utc::now();
+        let sent = NotificationHistoryEntry {
+            sent_at: Some(now - chrono::Duration::seconds(5)),
+            ..entry(1, NotificationEventType::Stop, 0, now)
+        };
+        let value = serde_json::to_value(&sent).unwrap();
+        let restored: NotificationHistoryEntry = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(restored.sent_at, sent.sent_at);
+
+        // 送信時刻を記録する前の履歴も読める
+        let mut old = value;
+        old.as_object_mut().unwrap().remove("sent_at");
+        let restored: NotificationHistoryEntry = serde_json::from_value(old).unwrap();
+        assert_eq!(restored.sent_at, None);
+    }
+
+    #[test]
+    fn test_prune_by_count() {
+        let now = Utc::now();
+        let mut entries: Vec<_> = (1..=5)
+            .rev()
+            .map(|id| entry(id, NotificationEventType::Stop, 0, now))
+            .collect();
+        let retention = HistoryRetention {
+            max_entries: 3,
+            ..HistoryRetention::default()
+        };
+        assert_eq!(retention.prune(&mut entries, now), 2);
+        assert_eq!(ids(&entries), vec![5, 4, 3]);
+
+        // 既定では日数で削除しない
+        let mut old = vec![entry(1, NotificationEventType::Stop, 3650, now)];
+        assert_eq!(HistoryRetention::default().prune(&mut old, now), 0);
+    }
+
+    #[test]
+    fn test_prune_keeps_pinned_entries() {
+        let now = Utc::now();
+        let mut entries: Vec<_> = (1..=5)
+            .rev()
+            .map(|id| entry(id, NotificationEventType::PermissionRequest, (5 - id as i64) * 20, now))
+            .collect();
+        // 保持日数を過ぎたエントリと、最大件数を超える位置のエントリをピン留めする
+        entries[4].pinned = true;
+        entries[1].pinned = true;
+        let retention = HistoryRetention {
+            max_entries: 1,
+            max_age_days: 45,
+            ..HistoryRetention::default()
+        };
+        assert_eq!(retention.prune(&mut entries, now), 2);
+        assert_eq!(ids(&entries), vec![5, 4, 1]);
+
+        let pinned = HistoryQuery {
+            pinned: Some(true),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&pinned.apply(&entries, &no_transcripts).entries), vec![4, 1]);
+    }
+
+    #[test]
+    fn test_resolve_permission_requests_and_stats() {
+        let now = Utc::now();
+        let mut entries = vec![
+            entry(4, NotificationEventType::PermissionRequest, 0, now),
+            entry(3, NotificationEventType::Stop, 0, now),
+            entry(2, NotificationEventType::PermissionRequest, 0, now),
+            entry(1, NotificationEventType::PermissionRequest, 0, now),
+        ];
+        entries[0].session_id = "s2".to_string();
+        entries[2].timestamp = now - chrono::Duration::seconds(30);
+        entries[3].timestamp = now - chrono::Duration::seconds(120);
+        // アプリから応答済みの依頼は応答した時刻を保持する
+        entries[3].resolved_at = Some(now - chrono::Duration::seconds(110));
+
+        assert_eq!(resolve_permission_requests(&mut entries, "s1", PermissionOutcome::Resumed, now), 2);
+        assert_eq!(entries[0].outcome, None);
+        assert_eq!(entries[1].outcome, None);
+        assert_eq!(entries[2].outcome, Some(PermissionOutcome::Resumed));
+        assert_eq!(entries[3].response_time(), Some(chrono::Duration::seconds(10)));
+        // 結果が確定した依頼は上書きしない
+        assert_eq!(resolve_permission_requests(&mut entries, "s1", PermissionOutcome::Stopped, now), 0);
+
+        let stats = ResponseStats::from_entries(&entries);
+        assert_eq!(stats.responded, 2);
+        assert_eq!(stats.median_secs, Some(20));
+        assert_eq!(ResponseStats::from_entries(&[]).median_secs, None);
+    }
+}
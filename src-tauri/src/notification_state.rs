@@ -1,16 +1,69 @@
 //! 未確認通知の状態管理モジュール
 //!
-//! 未読の通知数を追跡し、バッジ表示やリセットを管理する
+//! 未読の通知数を追跡し、バッジ表示やリセットを管理する。
+//! 停止やステータスの変化がまだ届いていない承認依頼は「承認待ち」として別に数え、
+//! 未読より優先してバッジに表示する。
+//! トーストのボタンでミュートしたセッション・スヌーズの期限・おやすみモードも保持する。
+//! 未読数・承認待ち・スヌーズは [`PersistedState`] としてストアに保存し、
+//! 異常終了や再起動の後もバッジと点滅を再開できるようにする。
 
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::info;
 
+/// 承認依頼の直後に届いたステータスの変化は依頼前の作業によるものとみなす猶予
+///
+/// ステータスラインの更新は非同期で送られるため、承認依頼より後に届くことがある。
+pub const PENDING_APPROVAL_GRACE: Duration = Duration::from_secs(3);
+
+/// バッジに表示する件数の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BadgeKind {
+    /// 未確認の通知
+    Unread,
+    /// 承認待ちの依頼
+    PendingApproval,
+}
+
+/// セッションの承認待ち
+#[derive(Debug, Clone, Copy)]
+struct PendingApproval {
+    count: u32,
+    /// 最後に承認依頼を受け取った時刻
+    last_requested: Instant,
+}
+
+/// ストアに保存する通知状態（時刻は再起動をまたいで比較できるよう実時間で保存する）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistedState {
+    pub unread: u32,
+    /// 最後に未読をリセットした時刻
+    pub last_reset: Option<DateTime<Utc>>,
+    /// セッションごとの承認待ちの件数
+    pub pending_approvals: BTreeMap<String, u32>,
+    pub snoozed_until: Option<DateTime<Utc>>,
+}
+
 /// 通知状態を管理する構造体
 #[derive(Debug, Clone)]
 pub struct NotificationState {
     /// 未読通知カウント
     unread_count: Arc<AtomicU32>,
+    /// 承認待ちの依頼（セッションIDごと）
+    pending_approvals: Arc<Mutex<HashMap<String, PendingApproval>>>,
+    /// 通知をミュートしたセッション（アプリの再起動まで）
+    muted_sessions: Arc<Mutex<HashSet<String>>>,
+    /// すべての通知を止めておく期限
+    snoozed_until: Arc<Mutex<Option<Instant>>>,
+    /// 最後に未読をリセットした時刻
+    last_reset: Arc<Mutex<Option<DateTime<Utc>>>>,
+    /// おやすみモード（解除するまですべての通知を止める、アプリの再起動まで）
+    do_not_disturb: Arc<AtomicBool>,
 }
 
 impl NotificationState {
@@ -18,6 +71,11 @@ impl NotificationState {
     pub fn new() -> Self {
         Self {
             unread_count: Arc::new(AtomicU32::new(0)),
+            pending_approvals: Arc::new(Mutex::new(HashMap::new())),
+            muted_sessions: Arc::new(Mutex::new(HashSet::new())),
+            snoozed_until: Arc::new(Mutex::new(None)),
+            last_reset: Arc::new(Mutex::new(None)),
+            do_not_disturb: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -28,6 +86,19 @@ impl NotificationState {
         new_count
     }
 
+    /// 未読カウントを減らし（0 未満にはしない）、新しい値を返す
+    pub fn decrement(&self, count: u32) -> u32 {
+        let previous = self
+            .unread_count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                Some(current.saturating_sub(count))
+            })
+            .unwrap_or_default();
+        let new_count = previous.saturating_sub(count);
+        info!("Notification count decremented to {}", new_count);
+        new_count
+    }
+
     /// 現在の未読カウントを取得
     pub fn get(&self) -> u32 {
         self.unread_count.load(Ordering::SeqCst)
@@ -36,8 +107,158 @@ impl NotificationState {
     /// 未読カウントをリセット（0に戻す）
     pub fn reset(&self) {
         self.unread_count.store(0, Ordering::SeqCst);
+        *self.last_reset.lock().unwrap() = Some(Utc::now());
         info!("Notification count reset to 0");
     }
+
+    /// セッションの承認待ちを1件追加し、全体の承認待ち数を返す
+    pub fn add_pending_approval(&self, session_id: &str, now: Instant) -> u32 {
+        let mut pending = self.pending_approvals.lock().unwrap();
+        let entry = pending
+            .entry(session_id.to_string())
+            .or_insert(PendingApproval {
+                count: 0,
+                last_requested: now,
+            });
+        entry.count += 1;
+        entry.last_requested = now;
+        let total = pending.values().map(|p| p.count).sum();
+        info!("Pending approval added for {} (total: {})", session_id, total);
+        total
+    }
+
+    /// セッションの承認待ちをすべて解除する（解除した場合は true）
+    pub fn clear_pending_approvals(&self, session_id: &str) -> bool {
+        let removed = self.pending_approvals.lock().unwrap().remove(session_id).is_some();
+        if removed {
+            info!("Pending approvals cleared for {}", session_id);
+        }
+        removed
+    }
+
+    /// セッションが作業を再開したとみなし、承認待ちを解除する
+    ///
+    /// 最後の承認依頼から猶予時間が経っていない場合は解除しない。
+    pub fn resume_session(&self, session_id: &str, now: Instant) -> bool {
+        let resumed = self
+            .pending_approvals
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .is_some_and(|p| now.saturating_duration_since(p.last_requested) >= PENDING_APPROVAL_GRACE);
+        resumed && self.clear_pending_approvals(session_id)
+    }
+
+    /// 全セッションの承認待ち数を取得
+    pub fn pending_approvals(&self) -> u32 {
+        self.pending_approvals.lock().unwrap().values().map(|p| p.count).sum()
+    }
+
+    /// バッジに表示する件数（承認待ちを未読より優先する）
+    pub fn badge(&self) -> Option<(BadgeKind, u32)> {
+        let pending = self.pending_approvals();
+        let unread = self.get();
+        if pending > 0 {
+            Some((BadgeKind::PendingApproval, pending))
+        } else if unread > 0 {
+            Some((BadgeKind::Unread, unread))
+        } else {
+            None
+        }
+    }
+
+    /// セッションからの通知をミュートする
+    pub fn mute_session(&self, session_id: &str) {
+        self.muted_sessions.lock().unwrap().insert(session_id.to_string());
+        info!("Session muted: {}", session_id);
+    }
+
+    /// セッションがミュートされているか
+    pub fn is_session_muted(&self, session_id: &str) -> bool {
+        self.muted_sessions.lock().unwrap().contains(session_id)
+    }
+
+    /// 指定した時刻まですべての通知を止める
+    pub fn snooze_until(&self, until: Instant) {
+        *self.snoozed_until.lock().unwrap() = Some(until);
+        info!("Notifications snoozed");
+    }
+
+    /// スヌーズ中か（期限を過ぎていれば解除する）
+    pub fn is_snoozed(&self, now: Instant) -> bool {
+        let mut snoozed_until = self.snoozed_until.lock().unwrap();
+        match *snoozed_until {
+            Some(until) if now < until => true,
+            Some(_) => {
+                *snoozed_until = None;
+                info!("Snooze expired");
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// おやすみモードを切り替える
+    pub fn set_do_not_disturb(&self, enabled: bool) {
+        self.do_not_disturb.store(enabled, Ordering::SeqCst);
+        info!("Do not disturb {}", if enabled { "enabled" } else { "disabled" });
+    }
+
+    /// おやすみモード中か
+    pub fn is_do_not_disturb(&self) -> bool {
+        self.do_not_disturb.load(Ordering::SeqCst)
+    }
+
+    /// 保存する状態を取得する（ミュートしたセッションはアプリの再起動までのため含めない）
+    pub fn snapshot(&self, now: Instant, now_utc: DateTime<Utc>) -> PersistedState {
+        let snoozed_until = self
+            .snoozed_until
+            .lock()
+            .unwrap()
+            .filter(|until| *until > now)
+            .and_then(|until| chrono::Duration::from_std(until - now).ok())
+            .map(|remaining| now_utc + remaining);
+        PersistedState {
+            unread: self.get(),
+            last_reset: *self.last_reset.lock().unwrap(),
+            pending_approvals: self
+                .pending_approvals
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(session_id, pending)| (session_id.clone(), pending.count))
+                .collect(),
+            snoozed_until,
+        }
+    }
+
+    /// 保存した状態を復元する（期限を過ぎたスヌーズは復元しない）
+    pub fn restore(&self, persisted: &PersistedState, now: Instant, now_utc: DateTime<Utc>) {
+        self.unread_count.store(persisted.unread, Ordering::SeqCst);
+        *self.last_reset.lock().unwrap() = persisted.last_reset;
+        *self.pending_approvals.lock().unwrap() = persisted
+            .pending_approvals
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(session_id, count)| {
+                let pending = PendingApproval {
+                    count: *count,
+                    last_requested: now,
+                };
+                (session_id.clone(), pending)
+            })
+            .collect();
+        *self.snoozed_until.lock().unwrap() = persisted
+            .snoozed_until
+            .and_then(|until| (until - now_utc).to_std().ok())
+            .filter(|remaining| !remaining.is_zero())
+            .map(|remaining| now + remaining);
+        info!(
+            "Notification state restored (unread: {}, pending approvals: {})",
+            persisted.unread,
+            self.pending_approvals()
+        );
+    }
 }
 
 impl Default for NotificationState {
@@ -76,6 +297,17 @@ mod tests {
         assert_eq!(state.get(), 0);
     }
 
+    #[test]
+    fn test_decrement_saturates() {
+        let state = NotificationState::new();
+        state.increment();
+        state.increment();
+        state.increment();
+        assert_eq!(state.decrement(2), 1);
+        assert_eq!(state.decrement(5), 0);
+        assert_eq!(state.get(), 0);
+    }
+
     #[test]
     fn test_clone_shares_state() {
         let state1 = NotificationState::new();
@@ -115,6 +347,122 @@ mod tests {
         assert_eq!(state.get(), 1000);
     }
 
+    #[test]
+    fn test_pending_approvals() {
+        let state = NotificationState::new();
+        let now = Instant::now();
+        assert_eq!(state.add_pending_approval("s1", now), 1);
+        assert_eq!(state.add_pending_approval("s1", now), 2);
+        assert_eq!(state.add_pending_approval("s2", now), 3);
+
+        assert!(state.clear_pending_approvals("s1"));
+        assert!(!state.clear_pending_approvals("s1"));
+        assert_eq!(state.pending_approvals(), 1);
+    }
+
+    #[test]
+    fn test_resume_session_respects_grace() {
+        let state = NotificationState::new();
+        let now = Instant::now();
+        state.add_pending_approval("s1", now);
+
+        // 依頼直後のステータス更新では解除しない
+        assert!(!state.resume_session("s1", now + Duration::from_secs(1)));
+        assert_eq!(state.pending_approvals(), 1);
+
+        assert!(state.resume_session("s1", now + PENDING_APPROVAL_GRACE));
+        assert_eq!(state.pending_approvals(), 0);
+        assert!(!state.resume_session("unknown", now + PENDING_APPROVAL_GRACE));
+    }
+
+    #[test]
+    fn test_badge_prefers_pending_approvals() {
+        let state = NotificationState::new();
+        assert_eq!(state.badge(), None);
+
+        state.increment();
+        state.increment();
+        assert_eq!(state.badge(), Some((BadgeKind::Unread, 2)));
+
+        state.add_pending_approval("s1", Instant::now());
+        assert_eq!(state.badge(), Some((BadgeKind::PendingApproval, 1)));
+
+        // 未読をリセットしても承認待ちは残る
+        state.reset();
+        assert_eq!(state.badge(), Some((BadgeKind::PendingApproval, 1)));
+        state.clear_pending_approvals("s1");
+        assert_eq!(state.badge(), None);
+    }
+
+    #[test]
+    fn test_mute_session() {
+        let state = NotificationState::new();
+        assert!(!state.is_session_muted("s1"));
+
+        state.mute_session("s1");
+        assert!(state.is_session_muted("s1"));
+        assert!(!state.is_session_muted("s2"));
+    }
+
+    #[test]
+    fn test_snooze_expires() {
+        let state = NotificationState::new();
+        let now = Instant::now();
+        assert!(!state.is_snoozed(now));
+
+        state.snooze_until(now + Duration::from_secs(600));
+        assert!(state.is_snoozed(now + Duration::from_secs(599)));
+        assert!(!state.is_snoozed(now + Duration::from_secs(600)));
+        // 期限切れで解除されている
+        assert!(!state.is_snoozed(now));
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let state = NotificationState::new();
+        let now = Instant::now();
+        let now_utc = Utc::now();
+        state.increment();
+        state.increment();
+        state.add_pending_approval("s1", now);
+        state.mute_session("s1");
+        state.snooze_until(now + Duration::from_secs(600));
+
+        let persisted = state.snapshot(now, now_utc);
+        assert_eq!(persisted.unread, 2);
+        assert_eq!(persisted.pending_approvals.get("s1"), Some(&1));
+        assert_eq!(persisted.snoozed_until, Some(now_utc + chrono::Duration::seconds(600)));
+
+        // 5分後に再起動した場合はスヌーズの残り時間だけ復元する
+        let restored = NotificationState::new();
+        let later = Instant::now();
+        restored.restore(&persisted, later, now_utc + chrono::Duration::seconds(300));
+        assert_eq!(restored.badge(), Some((BadgeKind::PendingApproval, 1)));
+        assert_eq!(restored.get(), 2);
+        assert!(restored.is_snoozed(later + Duration::from_secs(299)));
+        assert!(!restored.is_snoozed(later + Duration::from_secs(300)));
+        assert!(!restored.is_session_muted("s1"));
+    }
+
+    #[test]
+    fn test_restore_skips_expired_snooze() {
+        let now_utc = Utc::now();
+        let persisted = PersistedState {
+            unread: 1,
+            snoozed_until: Some(now_utc - chrono::Duration::seconds(1)),
+            ..PersistedState::default()
+        };
+        let state = NotificationState::new();
+        let now = Instant::now();
+        state.restore(&persisted, now, now_utc);
+        assert_eq!(state.get(), 1);
+        assert!(!state.is_snoozed(now));
+
+        // 保存後に未読をリセットした時刻を保持する
+        state.reset();
+        assert!(state.snapshot(now, Utc::now()).last_reset.is_some());
+    }
+
     #[test]
     fn test_default() {
         let state = NotificationState::default();
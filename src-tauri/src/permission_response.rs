@@ -0,0 +1,257 @@
+//! 承認依頼への応答モジュール
+//!
+//! トーストのボタンや通知履歴から承認依頼を許可・拒否し、
+//! `claude-code/response/<session_id>` へ応答を送信する。
+//! フック側のヘルパースクリプト（await-permission-response.sh）がこのトピックを待ち受け、
+//! 依頼IDが一致した応答を Claude Code の PermissionRequest フックの出力に変換する。
+//!
+//! AskUserQuestion への回答（question_prompt）も `answers` を付けた許可として同じトピックで送信する。
+//!
+//! 応答は承認そのものになるため、共有シークレットが設定されている場合は署名して送信し、
+//! ヘルパースクリプト側で検証する。
+
+use crate::client::{topics, MqttPublisher};
+use crate::i18n::Strings;
+use crate::notification_history::NotificationHistoryManager;
+use crate::question_prompt::Answers;
+use crate::signing;
+use crate::toast::{OnActivated, ToastAction};
+use crate::NotificationManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+/// トーストの「許可」ボタンのID
+const ACTION_ALLOW: &str = "allow";
+/// トーストの「拒否」ボタンのID
+const ACTION_DENY: &str = "deny";
+
+/// アプリからの応答の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermissionResponseSettings {
+    /// フックスクリプトがアプリからの応答を待つか（エクスポートしたスクリプトに反映）
+    pub enabled: bool,
+    /// 応答を待つ秒数（過ぎると通常どおり Claude Code の確認ダイアログを表示する）
+    pub timeout_secs: u32,
+}
+
+impl Default for PermissionResponseSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            // Claude Code のフックの既定タイムアウト（60秒）より短くする
+            timeout_secs: 50,
+        }
+    }
+}
+
+impl PermissionResponseSettings {
+    /// エクスポートするスクリプトが応答を待つ秒数（無効の場合は None）
+    pub fn active_timeout(&self) -> Option<u32> {
+        (self.enabled && self.timeout_secs > 0).then_some(self.timeout_secs)
+    }
+}
+
+/// 承認依頼への応答
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionDecision {
+    Allow,
+    Deny,
+}
+
+impl PermissionDecision {
+    /// トーストのボタンIDから応答を判定する
+    fn from_action(action: &str) -> Option<Self> {
+        match action {
+            ACTION_ALLOW => Some(PermissionDecision::Allow),
+            ACTION_DENY => Some(PermissionDecision::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// 応答メッセージのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionResponse {
+    /// フックスクリプトが発行した依頼ID
+    pub request_id: String,
+    pub session_id: String,
+    pub decision: PermissionDecision,
+    /// AskUserQuestion への回答（質問文ごとの選択肢のラベル）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answers: Option<Answers>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// セッションIDから応答のトピックを作成する
+pub fn response_topic(session_id: &str) -> Option<String> {
+    topics::for_session(topics::RESPONSE_PREFIX, session_id)
+}
+
+/// 承認依頼のトーストに表示するボタン
+pub fn toast_actions(strings: &'static Strings) -> [ToastAction; 2] {
+    [
+        ToastAction {
+            id: ACTION_ALLOW,
+            label: strings.toast_allow,
+        },
+        ToastAction {
+            id: ACTION_DENY,
+            label: strings.toast_deny,
+        },
+    ]
+}
+
+/// 通知履歴のエントリに対して応答を送信し、応答済みとして記録する
+pub fn respond(
+    app: &AppHandle,
+    history_manager: &NotificationHistoryManager,
+    secret: Option<&str>,
+    entry_id: u64,
+    decision: PermissionDecision,
+    answers: Option<Answers>,
+) -> Result<(), String> {
+    let entry = history_manager
+        .get_entry(entry_id)
+        .ok_or_else(|| format!("Notification {} not found", entry_id))?;
+    if entry.decision.is_some() {
+        return Err("Permission request has already been answered".to_string());
+    }
+    let request_id = entry
+        .request_id
+        .ok_or_else(|| "Notification is not waiting for a response".to_string())?;
+
+    publish_response(
+        app,
+        &PermissionResponse {
+            request_id,
+            session_id: entry.session_id,
+            decision,
+            answers,
+            timestamp: Utc::now(),
+        },
+        secret,
+    )?;
+    info!("Permission request {} answered: {:?}", entry_id, decision);
+
+    history_manager.set_decision(app, entry_id, decision)?;
+    let _ = app.emit("notification-added", ());
+    Ok(())
+}
+
+/// 応答メッセージを送信する（シークレットがあれば署名する）
+fn publish_response(
+    app: &AppHandle,
+    response: &PermissionResponse,
+    secret: Option<&str>,
+) -> Result<(), String> {
+    let topic = response_topic(&response.session_id)
+        .ok_or_else(|| "Notification has no session ID".to_string())?;
+    let publisher = app
+        .try_state::<MqttPublisher>()
+        .ok_or_else(|| "MQTT client is not ready".to_string())?;
+
+    let payload = serde_json::to_string(response)
+        .map_err(|e| format!("Failed to serialize response: {}", e))?;
+    let payload = match secret {
+        Some(secret) => signing::sign_payload(&payload, secret),
+        None => payload,
+    };
+
+    publisher
+        .publish(&topic, payload.into_bytes(), false)
+        .map_err(|e| format!("Failed to publish response on {}: {}", topic, e))
+}
+
+/// トーストの「許可」「拒否」ボタンで応答するコールバックを作成する
+pub fn on_toast_activated(app: &AppHandle, entry_id: u64) -> OnActivated {
+    let app = app.clone();
+    Box::new(move |action: Option<&str>| {
+        let Some(decision) = action.and_then(PermissionDecision::from_action) else {
+            return;
+        };
+        let (Some(history_manager), Some(notification_manager)) = (
+            app.try_state::<Arc<NotificationHistoryManager>>(),
+            app.try_state::<Arc<NotificationManager>>(),
+        ) else {
+            return;
+        };
+        let settings = notification_manager.get_settings();
+        if let Err(e) = respond(
+            &app,
+            &history_manager,
+            settings.payload_signing.active_secret(),
+            entry_id,
+            decision,
+            None,
+        ) {
+            warn!("Failed to respond to permission request: {}", e);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_topic() {
+        assert_eq!(
+            response_topic("abc-123").as_deref(),
+            Some("claude-code/response/abc-123")
+        );
+        assert_eq!(response_topic(""), None);
+    }
+
+    #[test]
+    fn test_active_timeout() {
+        let mut settings = PermissionResponseSettings::default();
+        assert_eq!(settings.active_timeout(), None);
+        settings.enabled = true;
+        assert_eq!(settings.active_timeout(), Some(50));
+        settings.timeout_secs = 0;
+        assert_eq!(settings.active_timeout(), None);
+    }
+
+    #[test]
+    fn test_decision_from_action() {
+        assert_eq!(PermissionDecision::from_action("allow"), Some(PermissionDecision::Allow));
+        assert_eq!(PermissionDecision::from_action("deny"), Some(PermissionDecision::Deny));
+        assert_eq!(PermissionDecision::from_action("dismiss"), None);
+    }
+
+    #[test]
+    fn test_payload_serialization() {
+        let response = PermissionResponse {
+            request_id: "r1".to_string(),
+            session_id: "s1".to_string(),
+            decision: PermissionDecision::Deny,
+            answers: None,
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["request_id"], "r1");
+        assert_eq!(json["decision"], "deny");
+        assert!(json.get("answers").is_none());
+    }
+
+    #[test]
+    fn test_payload_with_answers() {
+        let mut answers = Answers::new();
+        answers.insert("Which database?".to_string(), "Postgres".to_string());
+        let response = PermissionResponse {
+            request_id: "r1".to_string(),
+            session_id: "s1".to_string(),
+            decision: PermissionDecision::Allow,
+            answers: Some(answers),
+            timestamp: Utc::now(),
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["decision"], "allow");
+        assert_eq!(json["answers"]["Which database?"], "Postgres");
+    }
+}
@@ -0,0 +1,234 @@
+//! フックのエラー（claude-code/error）の記録モジュール
+//!
+//! エラーのペイロードを分類・フック名・終了コード付きで受け取り、対処方法と合わせて通知する。
+//! 受信したエラーは診断タブの「問題」に一覧表示し、壊れたフックの原因を調べられるようにする。
+//! JSON でないペイロード（旧形式）はテキスト全体をメッセージとして扱う。
+
+use crate::i18n::{self, Strings};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// 記録するエラーの最大数（超えた場合は古いものから削除する）
+const MAX_PROBLEMS: usize = 100;
+
+/// コマンドが見つからない場合のシェルの終了コード
+const EXIT_COMMAND_NOT_FOUND: i32 = 127;
+
+/// コマンドを実行できない場合（実行権限がないなど）のシェルの終了コード
+const EXIT_NOT_EXECUTABLE: i32 = 126;
+
+/// エラーの分類
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// フックのスクリプトが失敗した
+    Hook,
+    /// 必要なコマンド（jq・mosquitto_pub・mqtt-publish など）が見つからない
+    MissingDependency,
+    /// ブローカーに接続・送信できない
+    Connection,
+    /// 署名・暗号化の設定がアプリと一致しない
+    Authentication,
+    /// アプリが受信したフックのペイロードが不正
+    InvalidPayload,
+    /// 分類できないエラー
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+/// エラーのペイロード
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorPayload {
+    #[serde(default)]
+    pub category: ErrorCategory,
+    pub message: String,
+    /// エラーが発生したフック（`on-stop.sh` など）
+    #[serde(default)]
+    pub hook: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// フックを実行したホスト
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// 受信した生のペイロード（不正なペイロードの確認用）
+    #[serde(default)]
+    pub raw: Option<String>,
+}
+
+impl ErrorPayload {
+    /// ペイロードを解釈する（JSON でない場合はテキスト全体をメッセージとする）
+    pub fn parse(raw: &str) -> Self {
+        serde_json::from_str(raw).unwrap_or_else(|_| Self {
+            category: ErrorCategory::Other,
+            message: raw.trim().to_string(),
+            hook: None,
+            exit_code: None,
+            host: None,
+            session_id: None,
+            cwd: None,
+            raw: None,
+        })
+    }
+
+    /// 対処方法（終了コードから原因がわかる場合は分類より優先する）
+    pub fn hint(&self, strings: &Strings) -> &'static str {
+        match (self.exit_code, self.category) {
+            (Some(EXIT_COMMAND_NOT_FOUND), _) | (_, ErrorCategory::MissingDependency) => {
+                strings.error_hint_missing_dependency
+            }
+            (Some(EXIT_NOT_EXECUTABLE), _) => strings.error_hint_not_executable,
+            (_, ErrorCategory::Connection) => strings.error_hint_connection,
+            (_, ErrorCategory::Authentication) => strings.error_hint_authentication,
+            (_, ErrorCategory::Hook) => strings.error_hint_hook,
+            (_, ErrorCategory::InvalidPayload) => strings.error_hint_invalid_payload,
+            (_, ErrorCategory::Other) => strings.error_hint_other,
+        }
+    }
+
+    /// 通知のタイトルと本文
+    pub fn notification_text(&self, strings: &Strings) -> (String, String) {
+        let title = match self.hook.as_deref().filter(|hook| !hook.is_empty()) {
+            Some(hook) => i18n::fill(strings.error_hook_title, &[("hook", hook)]),
+            None => strings.error_title.to_string(),
+        };
+
+        let mut body = self.message.clone();
+        if let Some(code) = self.exit_code {
+            body.push_str(&format!(
+                " ({})",
+                i18n::fill(strings.error_exit_code, &[("code", &code.to_string())])
+            ));
+        }
+        body.push('\n');
+        body.push_str(self.hint(strings));
+        (title, body)
+    }
+}
+
+/// 記録したエラー
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    pub id: u64,
+    pub received_at: DateTime<Local>,
+    #[serde(flatten)]
+    pub payload: ErrorPayload,
+    pub hint: String,
+}
+
+/// 受信したエラーの記録（アプリの終了までメモリに保持する）
+#[derive(Debug, Default)]
+pub struct ProblemLog {
+    problems: Mutex<VecDeque<Problem>>,
+    next_id: AtomicU64,
+}
+
+impl ProblemLog {
+    pub fn add(&self, payload: ErrorPayload, hint: &str) {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut problems = self.problems.lock().unwrap();
+        problems.push_front(Problem {
+            id,
+            received_at: Local::now(),
+            payload,
+            hint: hint.to_string(),
+        });
+        problems.truncate(MAX_PROBLEMS);
+    }
+
+    /// 新しい順に取得
+    pub fn list(&self) -> Vec<Problem> {
+        self.problems.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.problems.lock().unwrap().clear();
+    }
+}
+
+/// Tauriコマンド: 記録したエラーを取得
+#[tauri::command]
+pub fn get_problems(problems: tauri::State<'_, ProblemLog>) -> Vec<Problem> {
+    problems.list()
+}
+
+/// Tauriコマンド: 記録したエラーを削除
+#[tauri::command]
+pub fn clear_problems(problems: tauri::State<'_, ProblemLog>) {
+    problems.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+
+    #[test]
+    fn test_parse_structured_and_legacy_payloads() {
+        let payload = ErrorPayload::parse(
+            r#"{"category":"connection","message":"Connection refused","hook":"on-stop.sh","exit_code":1,"host":"devbox"}"#,
+        );
+        assert_eq!(payload.category, ErrorCategory::Connection);
+        assert_eq!(payload.hook.as_deref(), Some("on-stop.sh"));
+        assert_eq!(payload.exit_code, Some(1));
+
+        // 未知の分類は Other として扱う
+        let payload = ErrorPayload::parse(r#"{"category":"disk_full","message":"No space left"}"#);
+        assert_eq!(payload.category, ErrorCategory::Other);
+
+        let legacy = ErrorPayload::parse("  jq: command not found\n");
+        assert_eq!(legacy.category, ErrorCategory::Other);
+        assert_eq!(legacy.message, "jq: command not found");
+    }
+
+    #[test]
+    fn test_hint_prefers_exit_code() {
+        let strings = Language::En.strings();
+        let mut payload = ErrorPayload::parse(r#"{"category":"hook","message":"failed"}"#);
+        assert_eq!(payload.hint(strings), strings.error_hint_hook);
+
+        payload.exit_code = Some(EXIT_COMMAND_NOT_FOUND);
+        assert_eq!(payload.hint(strings), strings.error_hint_missing_dependency);
+
+        payload.exit_code = Some(EXIT_NOT_EXECUTABLE);
+        assert_eq!(payload.hint(strings), strings.error_hint_not_executable);
+    }
+
+    #[test]
+    fn test_notification_text() {
+        let strings = Language::En.strings();
+        let payload = ErrorPayload::parse(
+            r#"{"category":"missing_dependency","message":"mosquitto_pub not found","hook":"on-stop.sh","exit_code":127}"#,
+        );
+        let (title, body) = payload.notification_text(strings);
+        assert_eq!(title, "❌ on-stop.sh failed");
+        assert_eq!(
+            body,
+            format!("mosquitto_pub not found (exit code 127)\n{}", strings.error_hint_missing_dependency)
+        );
+
+        let (title, _) = ErrorPayload::parse("oops").notification_text(strings);
+        assert_eq!(title, strings.error_title);
+    }
+
+    #[test]
+    fn test_log_keeps_newest_first() {
+        let log = ProblemLog::default();
+        for i in 0..MAX_PROBLEMS + 1 {
+            log.add(ErrorPayload::parse(&format!("error {}", i)), "hint");
+        }
+        let problems = log.list();
+        assert_eq!(problems.len(), MAX_PROBLEMS);
+        assert_eq!(problems[0].payload.message, format!("error {}", MAX_PROBLEMS));
+
+        log.clear();
+        assert!(log.list().is_empty());
+    }
+}
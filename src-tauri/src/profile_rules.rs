@@ -0,0 +1,311 @@
+//! 設定プロファイルの自動切り替えモジュール
+//!
+//! 時間帯・接続中の Wi-Fi・ネットワーク内のホスト・外部ディスプレイの接続を定期的に確認し、
+//! 条件に一致したルールのプロファイルに切り替える。
+//! 条件に一致しなくなった場合は、自動で切り替える前のプロファイルに戻す。
+//! 手動での切り替えを妨げないよう、切り替えるのは一致するルールが変わった時のみ。
+
+use crate::profiles;
+use crate::settings;
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::net::ToSocketAddrs;
+use std::time::Duration;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+/// 条件を確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// プロファイルを切り替える条件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProfileCondition {
+    /// 曜日と時間帯（`HH:MM`、終了が開始より前の場合は日をまたぐ。曜日が空の場合は毎日）
+    Schedule {
+        #[serde(default)]
+        days: Vec<Weekday>,
+        start: String,
+        end: String,
+    },
+    /// 接続中の Wi-Fi の SSID
+    Ssid { ssid: String },
+    /// ホスト名を名前解決できる（社内ネットワークでのみ引けるホストなど）
+    Host { host: String },
+    /// 外部ディスプレイが接続されている
+    ExternalDisplay,
+}
+
+/// プロファイルの自動切り替えのルール
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileRule {
+    pub condition: ProfileCondition,
+    /// 条件に一致した場合に切り替えるプロファイル
+    pub profile: String,
+}
+
+/// 条件の判定に使う現在の状態
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    pub now: NaiveDateTime,
+    pub ssid: Option<String>,
+    pub display_count: usize,
+}
+
+impl ProfileCondition {
+    fn matches(&self, environment: &Environment) -> bool {
+        match self {
+            ProfileCondition::Schedule { days, start, end } => in_schedule(days, start, end, environment.now),
+            ProfileCondition::Ssid { ssid } => environment
+                .ssid
+                .as_deref()
+                .is_some_and(|current| current == ssid.trim()),
+            ProfileCondition::Host { host } => resolves(host.trim()),
+            ProfileCondition::ExternalDisplay => environment.display_count > 1,
+        }
+    }
+}
+
+/// 曜日と時間帯（`HH:MM`）に含まれるか（終了が開始より前の場合は日をまたぐ。曜日が空の場合は毎日）
+///
+/// 時刻を解釈できない場合は含まれないとする。
+pub fn in_schedule(days: &[Weekday], start: &str, end: &str, now: NaiveDateTime) -> bool {
+    let (Ok(start), Ok(end)) = (
+        NaiveTime::parse_from_str(start, "%H:%M"),
+        NaiveTime::parse_from_str(end, "%H:%M"),
+    ) else {
+        return false;
+    };
+    let on_day = |day: Weekday| days.is_empty() || days.contains(&day);
+    let time = now.time();
+    let today = now.weekday();
+    if start <= end {
+        on_day(today) && start <= time && time < end
+    } else {
+        // 日をまたぐ時間帯は、開始した曜日で判定する
+        (on_day(today) && time >= start) || (on_day(today.pred()) && time < end)
+    }
+}
+
+/// ルールを順に確認し、最初に一致したルールのプロファイルを返す
+pub fn matching_profile<'a>(rules: &'a [ProfileRule], environment: &Environment) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|rule| rule.condition.matches(environment))
+        .map(|rule| rule.profile.as_str())
+}
+
+/// 一致するルールの変化からプロファイルの切り替えを決める
+#[derive(Debug, Default)]
+pub struct ProfileScheduler {
+    /// 前回一致したルールのプロファイル
+    matched: Option<String>,
+    /// 自動で切り替える前のプロファイル（条件に一致しなくなった時に戻す）
+    restore: Option<String>,
+}
+
+impl ProfileScheduler {
+    /// 切り替えるプロファイルを返す（切り替えない場合は None）
+    pub fn decide(&mut self, matched: Option<&str>, active: Option<&str>) -> Option<String> {
+        if self.matched.as_deref() == matched {
+            return None;
+        }
+        self.matched = matched.map(str::to_string);
+
+        let target = match matched {
+            Some(profile) => {
+                if self.restore.is_none() {
+                    self.restore = active.map(str::to_string);
+                }
+                Some(profile.to_string())
+            }
+            None => self.restore.take(),
+        };
+        target.filter(|target| Some(target.as_str()) != active)
+    }
+}
+
+fn resolves(host: &str) -> bool {
+    !host.is_empty() && (host, 0).to_socket_addrs().is_ok_and(|mut addrs| addrs.next().is_some())
+}
+
+/// `netsh wlan show interfaces` の出力から SSID を取り出す
+fn parse_netsh_ssid(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("SSID")?.trim_start().strip_prefix(':')?;
+        Some(value.trim().to_string()).filter(|ssid| !ssid.is_empty())
+    })
+}
+
+/// `nmcli -t -f active,ssid dev wifi` の出力から接続中の SSID を取り出す
+fn parse_nmcli_ssid(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let ssid = line.strip_prefix("yes:")?.replace("\\:", ":");
+        Some(ssid).filter(|ssid| !ssid.is_empty())
+    })
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = std::process::Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // コンソールウィンドウを表示しない（CREATE_NO_WINDOW）
+        command.creation_flags(0x0800_0000);
+    }
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 接続中の Wi-Fi の SSID（取得できない場合は None）
+fn current_ssid() -> Option<String> {
+    if cfg!(windows) {
+        parse_netsh_ssid(&command_output("netsh", &["wlan", "show", "interfaces"])?)
+    } else if cfg!(target_os = "linux") {
+        parse_nmcli_ssid(&command_output("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"])?)
+    } else {
+        None
+    }
+}
+
+/// ルールの判定に必要な状態を取得する（SSID はルールで使う場合のみ取得する）
+fn environment(app: &AppHandle, rules: &[ProfileRule]) -> Environment {
+    let uses_ssid = rules
+        .iter()
+        .any(|rule| matches!(rule.condition, ProfileCondition::Ssid { .. }));
+    Environment {
+        now: Local::now().naive_local(),
+        ssid: uses_ssid.then(current_ssid).flatten(),
+        display_count: app.available_monitors().map(|monitors| monitors.len()).unwrap_or(1),
+    }
+}
+
+/// ルールを定期的に確認し、プロファイルを自動で切り替える
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut scheduler = ProfileScheduler::default();
+        loop {
+            let settings_profiles = settings::load_profiles(&app);
+            if !settings_profiles.rules.is_empty() {
+                let current = environment(&app, &settings_profiles.rules);
+                let matched = matching_profile(&settings_profiles.rules, &current);
+                if let Some(target) = scheduler.decide(matched, settings_profiles.active.as_deref()) {
+                    info!("Switching settings profile by rule: {}", target);
+                    if let Err(e) = profiles::switch(&app, &target) {
+                        warn!("Failed to switch settings profile: {}", e);
+                    }
+                }
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> Environment {
+        Environment {
+            now: NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap(),
+            ..Environment::default()
+        }
+    }
+
+    fn schedule(days: Vec<Weekday>, start: &str, end: &str) -> ProfileCondition {
+        ProfileCondition::Schedule {
+            days,
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_schedule_condition() {
+        let work = schedule(vec![Weekday::Mon, Weekday::Fri], "09:00", "18:00");
+        // 2026-10-19 は月曜日
+        assert!(work.matches(&at("2026-10-19 09:00")));
+        assert!(!work.matches(&at("2026-10-19 18:00")));
+        assert!(!work.matches(&at("2026-10-20 10:00")));
+
+        // 日をまたぐ時間帯は開始した曜日で判定する
+        let night = schedule(vec![Weekday::Fri], "22:00", "02:00");
+        assert!(night.matches(&at("2026-10-23 23:00")));
+        assert!(night.matches(&at("2026-10-24 01:00")));
+        assert!(!night.matches(&at("2026-10-25 01:00")));
+
+        assert!(!schedule(Vec::new(), "9", "18:00").matches(&at("2026-10-19 10:00")));
+    }
+
+    #[test]
+    fn test_matching_profile_uses_first_rule() {
+        let rules = vec![
+            ProfileRule {
+                condition: ProfileCondition::ExternalDisplay,
+                profile: "presentation".to_string(),
+            },
+            ProfileRule {
+                condition: ProfileCondition::Ssid {
+                    ssid: "Office".to_string(),
+                },
+                profile: "work".to_string(),
+            },
+        ];
+        let mut environment = Environment {
+            ssid: Some("Office".to_string()),
+            display_count: 2,
+            ..Environment::default()
+        };
+        assert_eq!(matching_profile(&rules, &environment), Some("presentation"));
+
+        environment.display_count = 1;
+        assert_eq!(matching_profile(&rules, &environment), Some("work"));
+
+        environment.ssid = None;
+        assert_eq!(matching_profile(&rules, &environment), None);
+    }
+
+    #[test]
+    fn test_scheduler_switches_on_change_and_restores() {
+        let mut scheduler = ProfileScheduler::default();
+        assert_eq!(scheduler.decide(None, Some("home")), None);
+
+        assert_eq!(scheduler.decide(Some("presentation"), Some("home")).as_deref(), Some("presentation"));
+        // 一致するルールが変わらない間は、手動の切り替えを戻さない
+        assert_eq!(scheduler.decide(Some("presentation"), Some("work")), None);
+
+        // 条件に一致しなくなったら、自動で切り替える前のプロファイルに戻す
+        assert_eq!(scheduler.decide(None, Some("presentation")).as_deref(), Some("home"));
+        assert_eq!(scheduler.decide(None, Some("home")), None);
+    }
+
+    #[test]
+    fn test_parse_ssid() {
+        let netsh = "    Name                   : Wi-Fi\r\n    SSID                   : Office WiFi\r\n    BSSID                  : aa:bb:cc:dd:ee:ff\r\n";
+        assert_eq!(parse_netsh_ssid(netsh).as_deref(), Some("Office WiFi"));
+        assert_eq!(parse_netsh_ssid("    State : disconnected\r\n"), None);
+
+        let nmcli = "no:Cafe\nyes:Home\\:5G\n";
+        assert_eq!(parse_nmcli_ssid(nmcli).as_deref(), Some("Home:5G"));
+    }
+
+    #[test]
+    fn test_rule_serialization() {
+        let rule = ProfileRule {
+            condition: schedule(vec![Weekday::Sat, Weekday::Sun], "10:00", "12:00"),
+            profile: "home".to_string(),
+        };
+        let json = serde_json::to_value(&rule).unwrap();
+        assert_eq!(json["condition"]["kind"], "schedule");
+        assert_eq!(serde_json::from_value::<ProfileRule>(json).unwrap(), rule);
+
+        let display: ProfileRule =
+            serde_json::from_str(r#"{"condition":{"kind":"external_display"},"profile":"presentation"}"#).unwrap();
+        assert_eq!(display.condition, ProfileCondition::ExternalDisplay);
+    }
+}
@@ -0,0 +1,140 @@
+//! 設定プロファイルの切り替えモジュール
+//!
+//! 設定画面・トレイメニュー・ホットキーからプロファイルを切り替え、
+//! プロファイルの設定を NotificationManager に反映する（再起動は不要）。
+//! プロファイル自体は settings.json に保存する（[`crate::settings::SettingsProfiles`]）。
+
+use crate::profile_rules::ProfileRule;
+use crate::settings::{self, NotificationSettings};
+use crate::{apply_settings, NotificationManager};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tracing::{info, warn};
+
+/// 設定画面に表示するプロファイルの一覧
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileList {
+    pub active: Option<String>,
+    pub names: Vec<String>,
+    pub hotkey: String,
+    pub rules: Vec<ProfileRule>,
+}
+
+/// プロファイルを切り替え、設定を反映する
+pub fn switch(app: &AppHandle, name: &str) -> Result<(), String> {
+    let mut profiles = settings::load_profiles(app);
+    let settings = profiles
+        .activate(name)
+        .ok_or_else(|| format!("Profile not found: {}", name))?;
+    // トレイメニューの再構築で有効なプロファイルを表示するため、先に保存する
+    settings::save_profiles(app, &profiles)?;
+
+    let notification_manager = app.state::<Arc<NotificationManager>>();
+    apply_settings(app, &notification_manager, settings)?;
+    info!("Switched to settings profile: {}", name);
+
+    // 設定画面を開いている場合は表示を更新する
+    let _ = app.emit("settings-changed", ());
+    Ok(())
+}
+
+/// 次のプロファイルに切り替える（ホットキー用）
+fn cycle(app: &AppHandle) {
+    let Some(name) = settings::load_profiles(app).next() else {
+        return;
+    };
+    if let Err(e) = switch(app, &name) {
+        warn!("Failed to switch settings profile: {}", e);
+    }
+}
+
+/// グローバルショートカットのプラグイン（登録されたホットキーでプロファイルを切り替える）
+pub fn hotkey_plugin() -> tauri::plugin::TauriPlugin<tauri::Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                cycle(app);
+            }
+        })
+        .build()
+}
+
+/// プロファイルを切り替えるホットキーを登録し直す（空の場合は登録しない）
+pub fn register_hotkey(app: &AppHandle, hotkey: &str) -> Result<(), String> {
+    let shortcuts = app.global_shortcut();
+    shortcuts.unregister_all().map_err(|e| e.to_string())?;
+
+    let hotkey = hotkey.trim();
+    if hotkey.is_empty() {
+        return Ok(());
+    }
+    shortcuts.register(hotkey).map_err(|e| e.to_string())?;
+    info!("Profile hotkey registered: {}", hotkey);
+    Ok(())
+}
+
+/// Tauriコマンド: プロファイルの一覧を取得
+#[tauri::command]
+pub fn get_profiles(app: AppHandle) -> ProfileList {
+    let profiles = settings::load_profiles(&app);
+    ProfileList {
+        names: profiles.names(),
+        active: profiles.active,
+        hotkey: profiles.hotkey,
+        rules: profiles.rules,
+    }
+}
+
+/// Tauriコマンド: 現在の設定をプロファイルとして保存（同じ名前のプロファイルは上書き）
+#[tauri::command]
+pub fn save_profile(
+    app: AppHandle,
+    name: String,
+    settings: NotificationSettings,
+    notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+) -> Result<(), String> {
+    let mut profiles = settings::load_profiles(&app);
+    profiles.save_profile(&name, settings.clone())?;
+    settings::save_profiles(&app, &profiles)?;
+    apply_settings(&app, &notification_manager, settings)
+}
+
+/// Tauriコマンド: プロファイルを削除
+#[tauri::command]
+pub fn delete_profile(
+    app: AppHandle,
+    name: String,
+    notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+) -> Result<(), String> {
+    let mut profiles = settings::load_profiles(&app);
+    if profiles.remove(&name) {
+        settings::save_profiles(&app, &profiles)?;
+        crate::tray::rebuild_menu(&app, notification_manager.strings());
+    }
+    Ok(())
+}
+
+/// Tauriコマンド: プロファイルを切り替え
+#[tauri::command]
+pub fn switch_profile(app: AppHandle, name: String) -> Result<(), String> {
+    switch(&app, &name)
+}
+
+/// Tauriコマンド: プロファイルを切り替えるホットキーを保存
+#[tauri::command]
+pub fn set_profile_hotkey(app: AppHandle, hotkey: String) -> Result<(), String> {
+    register_hotkey(&app, &hotkey)?;
+    let mut profiles = settings::load_profiles(&app);
+    profiles.hotkey = hotkey.trim().to_string();
+    settings::save_profiles(&app, &profiles)
+}
+
+/// Tauriコマンド: プロファイルを自動で切り替えるルールを保存
+#[tauri::command]
+pub fn set_profile_rules(app: AppHandle, rules: Vec<ProfileRule>) -> Result<(), String> {
+    let mut profiles = settings::load_profiles(&app);
+    profiles.rules = rules;
+    settings::save_profiles(&app, &profiles)
+}
@@ -0,0 +1,126 @@
+//! プロジェクトの別名モジュール
+//!
+//! worktree や別の場所のクローンなど、複数の作業ディレクトリを1つのプロジェクト名にまとめる。
+//! まとめた名前はセッション名・通知履歴の表示、プロジェクトごとのルール、プロジェクト別のメトリクスの集計に使う。
+//! 作業ディレクトリは、パス（そのディレクトリと配下）かディレクトリの名前で指定し、`*` で任意の文字列に一致させられる
+//! （src/main.js の projectAlias と同じ判定）。
+
+use crate::project_rules;
+use serde::{Deserialize, Serialize};
+
+/// 1つのプロジェクトにまとめる作業ディレクトリ
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectAlias {
+    /// 表示に使うプロジェクト名
+    pub name: String,
+    /// 作業ディレクトリのパスまたは名前（大文字・小文字は区別しない）
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+impl ProjectAlias {
+    fn matches(&self, cwd: &str) -> bool {
+        self.paths.iter().any(|pattern| path_matches(pattern, cwd))
+    }
+}
+
+/// 作業ディレクトリがパスまたはディレクトリ名のパターンに一致するか
+///
+/// パスの場合は配下のディレクトリも含める。大文字・小文字と区切り文字（`/` と `\`）は区別しない。
+pub fn path_matches(pattern: &str, cwd: &str) -> bool {
+    let pattern = normalize(pattern);
+    let cwd = normalize(cwd);
+    if pattern.is_empty() {
+        false
+    } else if pattern.contains('/') {
+        glob_match(&pattern, &cwd) || glob_match(&format!("{}/*", pattern), &cwd)
+    } else {
+        glob_match(&pattern, project_rules::project_name(&cwd))
+    }
+}
+
+/// プロジェクトの別名の設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectAliasSettings {
+    pub aliases: Vec<ProjectAlias>,
+}
+
+impl ProjectAliasSettings {
+    /// 作業ディレクトリをまとめるプロジェクト名（一致する別名がない場合は None）
+    pub fn alias(&self, cwd: &str) -> Option<&str> {
+        self.aliases
+            .iter()
+            .filter(|alias| !alias.name.trim().is_empty())
+            .find(|alias| alias.matches(cwd))
+            .map(|alias| alias.name.trim())
+    }
+
+    /// 作業ディレクトリのプロジェクト名（別名がなければディレクトリの名前）
+    pub fn project_name(&self, cwd: &str) -> String {
+        self.alias(cwd)
+            .unwrap_or_else(|| project_rules::project_name(cwd))
+            .to_string()
+    }
+}
+
+/// 比較用に小文字にし、区切り文字を `/` に揃えて末尾の区切り文字を除く
+fn normalize(path: &str) -> String {
+    path.trim().to_lowercase().replace('\\', "/").trim_end_matches('/').to_string()
+}
+
+/// `*` を任意の文字列（空も含む）として照合する
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // 最後に見た `*` の位置と、その `*` に対応させ始めた text の位置
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("my-app", "my-app"));
+        assert!(glob_match("my-app-*", "my-app-feature"));
+        assert!(glob_match("*-wt-*", "my-app-wt-fix"));
+        assert!(!glob_match("my-app-*", "my-app"));
+        assert!(!glob_match("my-app", "my-app2"));
+    }
+
+    #[test]
+    fn test_alias_by_path_or_name() {
+        let settings = ProjectAliasSettings {
+            aliases: vec![ProjectAlias {
+                name: "My App".to_string(),
+                paths: vec!["/work/my-app".to_string(), "my-app-*".to_string(), "C:\\src\\MyApp\\".to_string()],
+            }],
+        };
+        assert_eq!(settings.alias("/work/my-app"), Some("My App"));
+        assert_eq!(settings.alias("/work/my-app/packages/web"), Some("My App"));
+        assert_eq!(settings.alias("/home/me/worktrees/my-app-feature"), Some("My App"));
+        assert_eq!(settings.alias("c:\\src\\myapp"), Some("My App"));
+        assert_eq!(settings.alias("/work/my-app2"), None);
+        assert_eq!(settings.project_name("/work/other/"), "other");
+        assert_eq!(settings.project_name("/work/my-app-review"), "My App");
+    }
+}
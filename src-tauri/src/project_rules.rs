@@ -0,0 +1,180 @@
+//! プロジェクトごとのルールモジュール
+//!
+//! ステータスラインから導く通知（変更行数の節目・入力待ちのリマインダー）の設定を、プロジェクトごとに上書きする。
+//! プロジェクトは作業ディレクトリの名前（最後の要素）またはパスで指定する。
+//! プロジェクトの表示色もここで決める。ルールに色があればその色、なければプロジェクト名のハッシュで
+//! パレットから選ぶため、同じプロジェクトは常に同じ色になる（src/main.js の projectColor と同じ計算）。
+
+use serde::{Deserialize, Serialize};
+
+/// プロジェクトごとのルール
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectRule {
+    /// プロジェクト名または作業ディレクトリのパス（大文字・小文字は区別しない）
+    pub project: String,
+    /// 変更行数を通知する間隔（None の場合は全体の設定に従い、0 の場合は通知しない）
+    #[serde(default)]
+    pub line_milestone: Option<u32>,
+    /// 入力待ちを知らせるまでの分数（None の場合は全体の設定に従い、0 の場合は知らせない）
+    #[serde(default)]
+    pub waiting_reminder: Option<u32>,
+    /// 表示色（#rrggbb、None の場合はプロジェクト名から決める）
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl ProjectRule {
+    fn matches(&self, cwd: &str) -> bool {
+        let project = self.project.trim().trim_end_matches(['/', '\\']);
+        if project.is_empty() {
+            return false;
+        }
+        let cwd = cwd.trim_end_matches(['/', '\\']);
+        cwd.eq_ignore_ascii_case(project) || project_name(cwd).eq_ignore_ascii_case(project)
+    }
+}
+
+/// 作業ディレクトリのプロジェクト名（最後の要素）
+///
+/// WSL・リモートのパスと Windows のパスのどちらでも名前を取り出せるようにする。
+pub fn project_name(cwd: &str) -> &str {
+    let cwd = cwd.trim_end_matches(['/', '\\']);
+    cwd.rsplit(['/', '\\']).next().unwrap_or(cwd)
+}
+
+/// プロジェクトの表示色のパレット（色と、トーストのタイトルに付ける同じ色の絵文字）
+///
+/// トーストは色を指定できないため、絵文字の色に揃えたパレットから選ぶ。
+const PALETTE: [(&str, &str); 7] = [
+    ("#e5484d", "🔴"),
+    ("#f76b15", "🟠"),
+    ("#f5b800", "🟡"),
+    ("#30a46c", "🟢"),
+    ("#0090ff", "🔵"),
+    ("#8e4ec6", "🟣"),
+    ("#a18072", "🟤"),
+];
+
+/// `#rrggbb` 形式の色を RGB に変換する
+pub fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// プロジェクト名から決まるパレットの色（大文字・小文字は区別しない、FNV-1a）
+fn hashed_color(name: &str) -> &'static str {
+    let hash = name
+        .to_lowercase()
+        .bytes()
+        .fold(0x811c_9dc5_u32, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193));
+    PALETTE[hash as usize % PALETTE.len()].0
+}
+
+/// 色に最も近いパレットの絵文字
+pub fn color_marker(color: [u8; 3]) -> &'static str {
+    let distance = |hex: &str| {
+        parse_color(hex).map_or(u32::MAX, |rgb| {
+            rgb.iter()
+                .zip(color)
+                .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+                .sum()
+        })
+    };
+    PALETTE
+        .iter()
+        .min_by_key(|(hex, _)| distance(hex))
+        .map_or("", |(_, marker)| marker)
+}
+
+/// プロジェクトごとのルールの設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectRuleSettings {
+    pub rules: Vec<ProjectRule>,
+    /// トーストのタイトルの先頭にプロジェクトの色の絵文字を付ける
+    pub color_toasts: bool,
+    /// トレイアイコンの点滅のドットをプロジェクトの色にする
+    pub color_tray_dot: bool,
+}
+
+impl ProjectRuleSettings {
+    /// 作業ディレクトリに一致する最初のルールを取得
+    pub fn find(&self, cwd: &str) -> Option<&ProjectRule> {
+        self.rules.iter().find(|rule| rule.matches(cwd))
+    }
+
+    /// 作業ディレクトリのプロジェクトの表示色（#rrggbb）
+    ///
+    /// ルールは作業ディレクトリかプロジェクト名（別名でまとめた名前）で探し、色がなければプロジェクト名から決める。
+    pub fn color(&self, cwd: &str, project: &str) -> String {
+        self.find(cwd)
+            .or_else(|| self.find(project))
+            .and_then(|rule| rule.color.as_deref())
+            .filter(|color| parse_color(color).is_some())
+            .map(|color| color.trim().to_ascii_lowercase())
+            .unwrap_or_else(|| hashed_color(project).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_by_name_or_path() {
+        let settings = ProjectRuleSettings {
+            rules: vec![
+                ProjectRule {
+                    project: "/work/api/".to_string(),
+                    line_milestone: Some(0),
+                    ..ProjectRule::default()
+                },
+                ProjectRule {
+                    project: "My-App".to_string(),
+                    line_milestone: Some(1000),
+                    waiting_reminder: Some(30),
+                    color: None,
+                },
+            ],
+            ..ProjectRuleSettings::default()
+        };
+        assert_eq!(settings.find("/home/me/my-app").unwrap().line_milestone, Some(1000));
+        assert_eq!(settings.find("C:\\src\\my-app\\").unwrap().line_milestone, Some(1000));
+        assert_eq!(settings.find("/work/api").unwrap().line_milestone, Some(0));
+        assert!(settings.find("/other/api").is_none());
+    }
+
+    #[test]
+    fn test_project_color() {
+        let settings = ProjectRuleSettings {
+            rules: vec![ProjectRule {
+                project: "my-app".to_string(),
+                color: Some("#3399FF".to_string()),
+                ..ProjectRule::default()
+            }],
+            ..ProjectRuleSettings::default()
+        };
+        assert_eq!(settings.color("/home/me/my-app", "my-app"), "#3399ff");
+        assert_eq!(settings.color("/work/my-app-feature", "my-app"), "#3399ff");
+
+        // ルールがなければ名前から決まり、大文字・小文字が違っても同じ色になる
+        let color = settings.color("/work/api", "api");
+        assert!(PALETTE.iter().any(|(hex, _)| *hex == color));
+        assert_eq!(settings.color("C:\\src\\API\\", "API"), color);
+        // src/main.js の projectColor と同じ結果になること
+        assert_eq!(hashed_color("api"), "#8e4ec6");
+    }
+
+    #[test]
+    fn test_color_marker() {
+        assert_eq!(parse_color("#3399ff"), Some([0x33, 0x99, 0xff]));
+        assert_eq!(parse_color("3399ff"), None);
+        assert_eq!(parse_color("#33zz99"), None);
+        assert_eq!(color_marker([0x33, 0x99, 0xff]), "🔵");
+        assert_eq!(color_marker([0xe5, 0x48, 0x4d]), "🔴");
+    }
+}
@@ -0,0 +1,230 @@
+//! AskUserQuestion への回答モジュール
+//!
+//! AskUserQuestion の `tool_input.questions[].options` をアプリ内のプロンプトウィンドウに
+//! ボタンとして表示し、選ばれた回答を承認依頼への応答と同じトピック
+//! （`claude-code/response/<session_id>`）で送信する。
+//! フック側のヘルパースクリプトは回答を AskUserQuestion の入力（`answers`）に加えて
+//! Claude Code に返すため、端末に切り替えずに質問に答えられる。
+
+use crate::i18n::Strings;
+use crate::toast::OnActivated;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tracing::{info, warn};
+
+/// 質問文ごとの回答（複数選択の場合はラベルを ", " で連結する）
+pub type Answers = BTreeMap<String, String>;
+
+/// 複数選択の回答を連結する区切り
+const MULTI_SELECT_SEPARATOR: &str = ", ";
+
+/// 質問の選択肢
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestionOption {
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// AskUserQuestion の質問（tool_input と同じ形式）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Question {
+    pub question: String,
+    #[serde(default)]
+    pub header: String,
+    #[serde(default)]
+    pub options: Vec<QuestionOption>,
+    #[serde(default)]
+    pub multi_select: bool,
+}
+
+/// tool_input から質問を取り出す
+///
+/// 選択肢のない質問が含まれる場合はボタンで回答できないため空を返す。
+pub fn parse_questions(tool_input: &serde_json::Value) -> Vec<Question> {
+    let questions: Vec<Question> = tool_input
+        .get("questions")
+        .and_then(|questions| serde_json::from_value(questions.clone()).ok())
+        .unwrap_or_default();
+    if questions.iter().any(|q| q.options.is_empty()) {
+        return Vec::new();
+    }
+    questions
+}
+
+/// すべての質問に選択肢のいずれかで回答しているか検証する
+pub fn validate_answers(questions: &[Question], answers: &Answers) -> Result<(), String> {
+    for question in questions {
+        let answer = answers
+            .get(&question.question)
+            .ok_or_else(|| format!("No answer for: {}", question.question))?;
+        let labels: Vec<&str> = if question.multi_select {
+            answer.split(MULTI_SELECT_SEPARATOR).collect()
+        } else {
+            vec![answer.as_str()]
+        };
+        let valid = !answer.is_empty()
+            && labels
+                .iter()
+                .all(|label| question.options.iter().any(|option| option.label == *label));
+        if !valid {
+            return Err(format!("Invalid answer for: {}", question.question));
+        }
+    }
+    if answers.len() != questions.len() {
+        return Err("Answers contain unknown questions".to_string());
+    }
+    Ok(())
+}
+
+/// 回答を待っている質問（通知履歴のIDごと）
+#[derive(Default)]
+pub struct QuestionPrompts {
+    pending: Mutex<HashMap<u64, Vec<Question>>>,
+}
+
+impl QuestionPrompts {
+    pub fn insert(&self, entry_id: u64, questions: Vec<Question>) {
+        self.pending.lock().unwrap().insert(entry_id, questions);
+    }
+
+    pub fn get(&self, entry_id: u64) -> Option<Vec<Question>> {
+        self.pending.lock().unwrap().get(&entry_id).cloned()
+    }
+
+    pub fn remove(&self, entry_id: u64) {
+        self.pending.lock().unwrap().remove(&entry_id);
+    }
+}
+
+/// プロンプトウィンドウのラベル（フロントエンドはラベルから履歴IDを読み取る）
+pub fn window_label(entry_id: u64) -> String {
+    format!("question-{}", entry_id)
+}
+
+/// プロンプトウィンドウを開く（開いている場合は前面に表示する）
+pub fn open_window(app: &AppHandle, entry_id: u64, strings: &Strings) -> Result<(), String> {
+    let label = window_label(entry_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+
+    WebviewWindowBuilder::new(app, &label, WebviewUrl::App("question.html".into()))
+        .title(strings.question_window_title)
+        .inner_size(420.0, 480.0)
+        .min_inner_size(360.0, 320.0)
+        .always_on_top(true)
+        .center()
+        .build()
+        .map_err(|e| format!("Failed to open question window: {}", e))?;
+    info!("Question window opened for notification {}", entry_id);
+    Ok(())
+}
+
+/// プロンプトウィンドウを閉じる
+pub fn close_window(app: &AppHandle, entry_id: u64) {
+    if let Some(window) = app.get_webview_window(&window_label(entry_id)) {
+        if let Err(e) = window.close() {
+            warn!("Failed to close question window: {}", e);
+        }
+    }
+}
+
+/// トーストのクリックでプロンプトウィンドウを開くコールバックを作成する
+pub fn on_toast_activated(app: &AppHandle, entry_id: u64, strings: &'static Strings) -> OnActivated {
+    let app = app.clone();
+    Box::new(move |action: Option<&str>| {
+        // 本文のクリックのみ（「閉じる」ボタンでは開かない）
+        if action.is_some() {
+            return;
+        }
+        if let Err(e) = open_window(&app, entry_id, strings) {
+            warn!("{}", e);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_input() -> serde_json::Value {
+        serde_json::json!({
+            "questions": [
+                {
+                    "question": "Which database?",
+                    "header": "DB",
+                    "options": [
+                        {"label": "SQLite", "description": "File based"},
+                        {"label": "Postgres"}
+                    ],
+                    "multiSelect": false
+                },
+                {
+                    "question": "Which features?",
+                    "options": [{"label": "Auth"}, {"label": "Search"}],
+                    "multiSelect": true
+                }
+            ]
+        })
+    }
+
+    fn answers(pairs: &[(&str, &str)]) -> Answers {
+        pairs
+            .iter()
+            .map(|(q, a)| (q.to_string(), a.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_questions() {
+        let questions = parse_questions(&tool_input());
+        assert_eq!(questions.len(), 2);
+        assert_eq!(questions[0].header, "DB");
+        assert_eq!(questions[0].options[0].description, "File based");
+        assert_eq!(questions[0].options[1].description, "");
+        assert!(questions[1].multi_select);
+    }
+
+    #[test]
+    fn test_parse_questions_without_options() {
+        let input = serde_json::json!({"questions": [{"question": "Name?"}]});
+        assert!(parse_questions(&input).is_empty());
+        assert!(parse_questions(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_validate_answers() {
+        let questions = parse_questions(&tool_input());
+        let valid = answers(&[
+            ("Which database?", "Postgres"),
+            ("Which features?", "Auth, Search"),
+        ]);
+        assert!(validate_answers(&questions, &valid).is_ok());
+
+        let missing = answers(&[("Which database?", "Postgres")]);
+        assert!(validate_answers(&questions, &missing).is_err());
+
+        let unknown_label = answers(&[
+            ("Which database?", "MySQL"),
+            ("Which features?", "Auth"),
+        ]);
+        assert!(validate_answers(&questions, &unknown_label).is_err());
+
+        // 単一選択の質問に複数のラベルは指定できない
+        let multiple = answers(&[
+            ("Which database?", "SQLite, Postgres"),
+            ("Which features?", "Auth"),
+        ]);
+        assert!(validate_answers(&questions, &multiple).is_err());
+    }
+
+    #[test]
+    fn test_window_label() {
+        assert_eq!(window_label(12), "question-12");
+    }
+}
@@ -0,0 +1,114 @@
+//! トーストのクイックアクションモジュール
+//!
+//! トーストに「開く」「セッションをミュート」「10分スヌーズ」ボタンを表示し、
+//! クリックされたボタンを NotificationManager に反映する。
+//! 承認依頼のトーストは「許可」「拒否」ボタンを優先するため、クイックアクションは表示しない
+//! （Windows のトーストに表示できるボタンは5つまで）。
+
+use crate::i18n::Strings;
+use crate::toast::{OnActivated, ToastAction};
+use crate::{tray, NotificationManager};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// トーストの「開く」ボタンのID
+const ACTION_OPEN: &str = "open";
+/// トーストの「セッションをミュート」ボタンのID
+const ACTION_MUTE_SESSION: &str = "mute-session";
+/// トーストの「スヌーズ」ボタンのID
+const ACTION_SNOOZE: &str = "snooze";
+
+/// スヌーズで通知を止める時間
+pub const SNOOZE_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// トーストのボタンで選べる操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickAction {
+    /// 通知履歴を開く
+    Open,
+    /// 通知元のセッションをミュートする
+    MuteSession,
+    /// すべての通知をしばらく止める
+    Snooze,
+}
+
+impl QuickAction {
+    /// トーストのボタンIDから操作を判定する
+    pub fn from_action(action: &str) -> Option<Self> {
+        match action {
+            ACTION_OPEN => Some(QuickAction::Open),
+            ACTION_MUTE_SESSION => Some(QuickAction::MuteSession),
+            ACTION_SNOOZE => Some(QuickAction::Snooze),
+            _ => None,
+        }
+    }
+}
+
+/// トーストに表示するクイックアクションのボタン
+///
+/// 発生元のセッションがない通知にはミュートのボタンを表示しない。
+pub fn toast_actions(strings: &'static Strings, has_session: bool) -> Vec<ToastAction> {
+    let mut actions = vec![ToastAction {
+        id: ACTION_OPEN,
+        label: strings.toast_open,
+    }];
+    if has_session {
+        actions.push(ToastAction {
+            id: ACTION_MUTE_SESSION,
+            label: strings.toast_mute_session,
+        });
+    }
+    actions.push(ToastAction {
+        id: ACTION_SNOOZE,
+        label: strings.toast_snooze,
+    });
+    actions
+}
+
+/// トーストのボタンがクリックされた時に操作を実行するコールバックを作成する
+pub fn on_toast_activated(app: &AppHandle, session_id: Option<&str>) -> OnActivated {
+    let app = app.clone();
+    let session_id = session_id.map(str::to_string);
+    Box::new(move |action: Option<&str>| {
+        let Some(action) = action.and_then(QuickAction::from_action) else {
+            return;
+        };
+        let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>() else {
+            return;
+        };
+        match action {
+            QuickAction::Open => tray::show_main_window_with_tab(&app, "history"),
+            QuickAction::MuteSession => {
+                if let Some(session_id) = session_id.as_deref() {
+                    notification_manager.mute_session(session_id);
+                }
+            }
+            QuickAction::Snooze => notification_manager.snooze(&app, SNOOZE_DURATION),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+
+    #[test]
+    fn test_from_action() {
+        assert_eq!(QuickAction::from_action("open"), Some(QuickAction::Open));
+        assert_eq!(QuickAction::from_action("mute-session"), Some(QuickAction::MuteSession));
+        assert_eq!(QuickAction::from_action("snooze"), Some(QuickAction::Snooze));
+        assert_eq!(QuickAction::from_action("allow"), None);
+    }
+
+    #[test]
+    fn test_toast_actions_without_session() {
+        let strings = Language::En.strings();
+        let ids: Vec<_> = toast_actions(strings, false).iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["open", "snooze"]);
+
+        let ids: Vec<_> = toast_actions(strings, true).iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["open", "mute-session", "snooze"]);
+    }
+}
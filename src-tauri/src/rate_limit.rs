@@ -0,0 +1,233 @@
+//! 送信元ごとの流量制限モジュール
+//!
+//! ループしたフックが1秒に何百件もイベントを送った場合に、通知・履歴・スクリプトが埋め尽くされないよう、
+//! 送信元（ペイロードの `session_id`、ない場合はトピック）ごとに一定時間内のイベント数を数える。
+//! 上限を超えた送信元は一定時間ミュートし、ミュートした時に1回だけ警告を通知する。
+//! ステータス・ハートビートは定期的に送られるため数えない。
+
+use crate::client::topics;
+use crate::i18n;
+use crate::notification_history::NotificationEventType;
+use crate::state::SessionNameManager;
+use crate::toast::NotificationPriority;
+use crate::{NotificationManager, NotificationOrigin};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tracing::warn;
+
+/// 流量制限の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitSettings {
+    pub enabled: bool,
+    /// `window_secs` 秒間に受け付けるイベントの最大数
+    pub max_events: u32,
+    pub window_secs: u32,
+    /// 上限を超えた送信元をミュートする時間（秒）
+    pub mute_secs: u32,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_events: 30,
+            window_secs: 10,
+            mute_secs: 60,
+        }
+    }
+}
+
+/// イベントを受け付けるかの判定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// 上限を超えたためミュートした（警告はこの1回だけ）
+    Flooding,
+    /// ミュート中
+    Muted,
+}
+
+#[derive(Debug, Default)]
+struct Publisher {
+    received: VecDeque<Instant>,
+    muted_until: Option<Instant>,
+}
+
+/// 送信元のセッション（ペイロードに `session_id` が含まれる場合）
+#[derive(Debug, PartialEq, Eq)]
+struct Session {
+    id: String,
+    cwd: String,
+    host: Option<String>,
+}
+
+fn session_of(payload: &str) -> Option<Session> {
+    let value: serde_json::Value = serde_json::from_str(payload).ok()?;
+    let field = |name: &str| value.get(name).and_then(|value| value.as_str());
+    let id = field("session_id").filter(|id| !id.is_empty())?;
+    Some(Session {
+        id: id.to_string(),
+        cwd: field("cwd").unwrap_or_default().to_string(),
+        host: field("host").map(str::to_string),
+    })
+}
+
+/// 送信元ごとのイベントの受信記録
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    publishers: Mutex<HashMap<String, Publisher>>,
+}
+
+impl RateLimiter {
+    /// 送信元のイベントを数え、受け付けるかを判定する
+    pub fn check(&self, key: &str, settings: &RateLimitSettings, now: Instant) -> Verdict {
+        if !settings.enabled || settings.max_events == 0 {
+            return Verdict::Allow;
+        }
+        let window = Duration::from_secs(u64::from(settings.window_secs.max(1)));
+        let mut publishers = self.publishers.lock().unwrap();
+        // 記録が増え続けないよう、しばらく送ってこない送信元を削除する
+        publishers.retain(|_, publisher| {
+            publisher.muted_until.is_some_and(|until| until > now)
+                || publisher.received.back().is_some_and(|last| now.duration_since(*last) < window)
+        });
+
+        let publisher = publishers.entry(key.to_string()).or_default();
+        match publisher.muted_until {
+            Some(until) if until > now => return Verdict::Muted,
+            Some(_) => publisher.muted_until = None,
+            None => {}
+        }
+        while publisher.received.front().is_some_and(|first| now.duration_since(*first) >= window) {
+            publisher.received.pop_front();
+        }
+        publisher.received.push_back(now);
+        if publisher.received.len() > settings.max_events as usize {
+            publisher.received.clear();
+            publisher.muted_until = Some(now + Duration::from_secs(u64::from(settings.mute_secs)));
+            return Verdict::Flooding;
+        }
+        Verdict::Allow
+    }
+}
+
+/// メッセージを処理するか（ミュートした時は警告を通知する）
+pub fn allow(
+    app: &AppHandle,
+    limiter: &RateLimiter,
+    notification_manager: &NotificationManager,
+    session_name_manager: &SessionNameManager,
+    settings: &RateLimitSettings,
+    topic: &str,
+    payload: &str,
+) -> bool {
+    // 定期的に送られるステータス・ハートビートは数えない
+    if topics::is_periodic(topic) {
+        return true;
+    }
+    let session = session_of(payload);
+    let key = session.as_ref().map_or(topic, |session| session.id.as_str());
+    match limiter.check(key, settings, Instant::now()) {
+        Verdict::Allow => true,
+        Verdict::Muted => false,
+        Verdict::Flooding => {
+            let source = match &session {
+                Some(session) => session_name_manager.get_or_create_name(&session.id, &session.cwd, session.host.as_deref()),
+                None => topic.to_string(),
+            };
+            warn!(
+                "{} is flooding (over {} events in {}s), muting for {}s",
+                key, settings.max_events, settings.window_secs, settings.mute_secs
+            );
+            let strings = notification_manager.strings();
+            let title = i18n::fill(strings.flooding_title, &[("source", &source)]);
+            let body = i18n::fill(
+                strings.flooding_body,
+                &[
+                    ("count", &settings.max_events.to_string()),
+                    ("window", &settings.window_secs.to_string()),
+                    ("mute", &settings.mute_secs.to_string()),
+                ],
+            );
+            notification_manager.notify(
+                app,
+                &title,
+                &body,
+                NotificationEventType::Notification,
+                NotificationPriority::High,
+                NotificationOrigin::default(),
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(max_events: u32) -> RateLimitSettings {
+        RateLimitSettings {
+            enabled: true,
+            max_events,
+            window_secs: 10,
+            mute_secs: 60,
+        }
+    }
+
+    #[test]
+    fn test_mutes_flooding_publisher_once() {
+        let limiter = RateLimiter::default();
+        let settings = settings(3);
+        let start = Instant::now();
+        for i in 0..3 {
+            assert_eq!(limiter.check("s1", &settings, start + Duration::from_millis(i)), Verdict::Allow);
+        }
+        assert_eq!(limiter.check("s1", &settings, start + Duration::from_millis(3)), Verdict::Flooding);
+        // ミュート中は警告せずに捨てる
+        assert_eq!(limiter.check("s1", &settings, start + Duration::from_secs(30)), Verdict::Muted);
+        // 他の送信元には影響しない
+        assert_eq!(limiter.check("s2", &settings, start + Duration::from_secs(30)), Verdict::Allow);
+        // ミュートが明けると数え直す
+        assert_eq!(limiter.check("s1", &settings, start + Duration::from_secs(61)), Verdict::Allow);
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let limiter = RateLimiter::default();
+        let settings = settings(2);
+        let start = Instant::now();
+        assert_eq!(limiter.check("s1", &settings, start), Verdict::Allow);
+        assert_eq!(limiter.check("s1", &settings, start + Duration::from_secs(5)), Verdict::Allow);
+        assert_eq!(limiter.check("s1", &settings, start + Duration::from_secs(11)), Verdict::Allow);
+        assert_eq!(limiter.check("s1", &settings, start + Duration::from_secs(12)), Verdict::Flooding);
+    }
+
+    #[test]
+    fn test_disabled_allows_everything() {
+        let limiter = RateLimiter::default();
+        let settings = RateLimitSettings {
+            enabled: false,
+            ..settings(1)
+        };
+        let now = Instant::now();
+        assert!((0..10).all(|_| limiter.check("s1", &settings, now) == Verdict::Allow));
+    }
+
+    #[test]
+    fn test_session_of() {
+        assert_eq!(
+            session_of(r#"{"session_id":"devbox-123","cwd":"/home/me/app","host":"devbox"}"#),
+            Some(Session {
+                id: "devbox-123".to_string(),
+                cwd: "/home/me/app".to_string(),
+                host: Some("devbox".to_string()),
+            })
+        );
+        assert_eq!(session_of(r#"{"message":"deploy done"}"#), None);
+    }
+}
@@ -0,0 +1,316 @@
+//! ローカル REST API モジュール
+//!
+//! スクリプトや Stream Deck プラグイン、ステータスバー（waybar / polybar）から
+//! アプリの状態を参照・通知を送信するための API を localhost で提供する。
+//! 設定で有効にした場合のみ起動し、ダッシュボード以外のすべてのリクエストに
+//! `Authorization: Bearer <token>` を要求する（トークンは `api_tokens` で発行したもの）。
+//!
+//! - `GET /` 読み取り専用の Web ダッシュボード（ページ自体はデータを含まず、トークンを入力して API から取得する）
+//! - `GET /sessions` ステータスを送信中のセッション
+//! - `GET /projects` プロジェクト（とホスト）ごとのセッションと集計
+//! - `GET /history?limit=N` 通知履歴（新しい順）
+//! - `GET /unread` 未確認の通知数
+//! - `POST /notify` 任意の通知を表示（`{"title": "...", "body": "...", "priority": "high"}`）
+
+use crate::api_tokens::ApiTokens;
+use crate::notification_history::{NewHistoryEntry, NotificationEventType, NotificationHistoryManager};
+use crate::state::SessionManager;
+use crate::toast::NotificationPriority;
+use crate::{NotificationManager, NotificationOrigin};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{error, info, warn};
+
+/// `/notify` で受け付けるリクエストボディの最大サイズ
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+
+/// 同じ LAN の別の端末からも状態を確認できるダッシュボード
+static DASHBOARD_HTML: &str = include_str!("../resources/dashboard.html");
+
+/// REST API の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestApiSettings {
+    /// API を有効にするか（切り替えはアプリの再起動後に反映）
+    pub enabled: bool,
+    /// 待ち受けポート
+    pub port: u16,
+    /// 以前のバージョンのトークン（起動時にトークンの一覧に移して空にする）
+    pub token: String,
+    /// 127.0.0.1 だけでなく LAN からの接続も受け付ける（再起動後に反映）
+    ///
+    /// TLS は使わないため、トークンと応答は LAN 上を平文で流れる（設定画面と起動時のログで警告する）。
+    pub allow_lan: bool,
+}
+
+impl Default for RestApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 18830,
+            token: String::new(),
+            allow_lan: false,
+        }
+    }
+}
+
+/// API のエンドポイント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Dashboard,
+    Sessions,
+    Projects,
+    History { limit: Option<usize> },
+    Unread,
+    Notify,
+    MethodNotAllowed,
+    NotFound,
+}
+
+/// メソッドとURLからエンドポイントを判定する
+fn route(method: &Method, url: &str) -> Route {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let path = path.trim_end_matches('/');
+
+    let expected = match path {
+        "" | "/dashboard" | "/sessions" | "/projects" | "/history" | "/unread" => Method::Get,
+        "/notify" => Method::Post,
+        _ => return Route::NotFound,
+    };
+    if *method != expected {
+        return Route::MethodNotAllowed;
+    }
+
+    match path {
+        "" | "/dashboard" => Route::Dashboard,
+        "/sessions" => Route::Sessions,
+        "/projects" => Route::Projects,
+        "/history" => Route::History {
+            limit: query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "limit")
+                .and_then(|(_, value)| value.parse().ok()),
+        },
+        "/unread" => Route::Unread,
+        _ => Route::Notify,
+    }
+}
+
+/// Authorization ヘッダーの Bearer トークン
+fn bearer_token(header: Option<&str>) -> Option<&str> {
+    header
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Authorization ヘッダーのトークンが発行済みのトークンと一致するか
+fn is_authorized(header: Option<&str>, tokens: &ApiTokens) -> bool {
+    bearer_token(header).is_some_and(|token| tokens.verify(token))
+}
+
+/// `/notify` のリクエストボディ
+#[derive(Debug, Deserialize)]
+struct NotifyRequest {
+    title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    priority: NotificationPriority,
+}
+
+/// API から参照するアプリの状態
+pub struct ApiContext {
+    pub app: AppHandle,
+    pub session_manager: Arc<SessionManager>,
+    pub history_manager: Arc<NotificationHistoryManager>,
+    pub notification_manager: Arc<NotificationManager>,
+}
+
+/// 設定が有効であれば API サーバーをバックグラウンドで起動する
+pub fn start(settings: &RestApiSettings, context: ApiContext) {
+    if !settings.enabled {
+        return;
+    }
+    if context.app.state::<ApiTokens>().is_empty() {
+        warn!("REST API has no tokens, requests are rejected until one is issued");
+    }
+
+    let host = if settings.allow_lan { "0.0.0.0" } else { "127.0.0.1" };
+    if settings.allow_lan {
+        warn!("REST API accepts LAN connections over plain HTTP, tokens and notifications can be read on the network");
+    }
+    let server = match Server::http((host, settings.port)) {
+        Ok(server) => server,
+        Err(e) => {
+            error!("Failed to start REST API on port {}: {}", settings.port, e);
+            return;
+        }
+    };
+    info!("REST API listening on {}:{}", host, settings.port);
+
+    let spawned = std::thread::Builder::new()
+        .name("rest-api".to_string())
+        .spawn(move || {
+            for request in server.incoming_requests() {
+                handle_request(&context, request);
+            }
+        });
+    if let Err(e) = spawned {
+        error!("Failed to spawn REST API thread: {}", e);
+    }
+}
+
+fn handle_request(context: &ApiContext, mut request: Request) {
+    let authorization = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .map(|header| header.value.as_str().to_string());
+
+    let (status, body) = match route(request.method(), request.url()) {
+        // ダッシュボードのページはデータを含まないため、トークンなしで返す
+        Route::Dashboard => {
+            respond(request, 200, "text/html; charset=utf-8", DASHBOARD_HTML.to_string());
+            return;
+        }
+        _ if !is_authorized(authorization.as_deref(), &context.app.state::<ApiTokens>()) => {
+            (401, serde_json::json!({ "error": "unauthorized" }))
+        }
+        Route::Sessions => (200, serde_json::json!(context.session_manager.summaries())),
+        Route::Projects => (200, serde_json::json!(context.session_manager.project_summaries())),
+        Route::History { limit } => {
+            let mut entries = context.history_manager.get_entries(None);
+            if let Some(limit) = limit {
+                entries.truncate(limit);
+            }
+            (200, serde_json::json!(entries))
+        }
+        Route::Unread => (
+            200,
+            serde_json::json!({ "count": context.history_manager.get_unread_count() }),
+        ),
+        Route::Notify => notify(context, &mut request),
+        Route::MethodNotAllowed => (405, serde_json::json!({ "error": "method not allowed" })),
+        Route::NotFound => (404, serde_json::json!({ "error": "not found" })),
+    };
+
+    respond(request, status, "application/json", body.to_string());
+}
+
+fn respond(request: Request, status: u16, content_type: &str, body: String) {
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(
+            Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes())
+                .expect("static header is valid"),
+        );
+    if let Err(e) = request.respond(response) {
+        warn!("Failed to send REST API response: {}", e);
+    }
+}
+
+fn notify(context: &ApiContext, request: &mut Request) -> (u16, serde_json::Value) {
+    let mut body = String::new();
+    if let Err(e) = request
+        .as_reader()
+        .take(MAX_BODY_BYTES)
+        .read_to_string(&mut body)
+    {
+        return (400, serde_json::json!({ "error": e.to_string() }));
+    }
+
+    let payload = match serde_json::from_str::<NotifyRequest>(&body) {
+        Ok(payload) => payload,
+        Err(e) => return (400, serde_json::json!({ "error": e.to_string() })),
+    };
+
+    let id = match context.history_manager.add_entry(
+        &context.app,
+        NewHistoryEntry {
+            event_type: NotificationEventType::Notification,
+            session_name: payload.title.clone(),
+            session_id: String::new(),
+            cwd: None,
+            content: Some(payload.body.clone()),
+            request_id: None,
+            host: None,
+            image: None,
+            transcript_path: None,
+            sent_at: None,
+        },
+    ) {
+        Ok(id) => {
+            let _ = context.app.emit("notification-added", ());
+            Some(id)
+        }
+        Err(e) => {
+            warn!("Failed to add history entry: {}", e);
+            None
+        }
+    };
+
+    context.notification_manager.notify(
+        &context.app,
+        &payload.title,
+        &payload.body,
+        NotificationEventType::Notification,
+        payload.priority,
+        NotificationOrigin::default(),
+    );
+
+    (200, serde_json::json!({ "id": id }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route(&Method::Get, "/"), Route::Dashboard);
+        assert_eq!(route(&Method::Get, "/dashboard"), Route::Dashboard);
+        assert_eq!(route(&Method::Get, "/sessions"), Route::Sessions);
+        assert_eq!(route(&Method::Get, "/projects"), Route::Projects);
+        assert_eq!(route(&Method::Get, "/unread/"), Route::Unread);
+        assert_eq!(route(&Method::Post, "/notify"), Route::Notify);
+        assert_eq!(route(&Method::Get, "/notify"), Route::MethodNotAllowed);
+        assert_eq!(route(&Method::Post, "/sessions"), Route::MethodNotAllowed);
+        assert_eq!(route(&Method::Get, "/unknown"), Route::NotFound);
+    }
+
+    #[test]
+    fn test_history_limit() {
+        assert_eq!(
+            route(&Method::Get, "/history?limit=5"),
+            Route::History { limit: Some(5) }
+        );
+        assert_eq!(
+            route(&Method::Get, "/history?limit=abc"),
+            Route::History { limit: None }
+        );
+        assert_eq!(route(&Method::Get, "/history"), Route::History { limit: None });
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token(Some("Bearer secret")), Some("secret"));
+        assert_eq!(bearer_token(Some("Bearer  secret ")), Some("secret"));
+        assert_eq!(bearer_token(Some("secret")), None);
+        assert_eq!(bearer_token(None), None);
+        assert_eq!(bearer_token(Some("Bearer ")), None);
+        // トークンが1つもない場合はすべて拒否する
+        assert!(!is_authorized(Some("Bearer secret"), &ApiTokens::default()));
+    }
+
+    #[test]
+    fn test_notify_request_defaults() {
+        let request: NotifyRequest = serde_json::from_str(r#"{"title":"Build"}"#).unwrap();
+        assert_eq!(request.body, "");
+        assert_eq!(request.priority, NotificationPriority::Normal);
+    }
+}
@@ -0,0 +1,559 @@
+//! ルーティングスクリプトモジュール
+//!
+//! 上級者向けに、通知を表示する前に Rhai のスクリプト（`route(event)`）を呼び出し、
+//! 通知しない・タイトルや本文・優先度を変える・通知方法（Webhook を含む）ごとに切り替えるといった振り分けを自由に書けるようにする。
+//! スクリプトはアプリの設定フォルダーの `routing.rhai` で、初回は現在の動作と同じ既定のスクリプトを書き出す。
+//! 保存されたスクリプトは定期的に確認して読み込み直し、構文エラーの場合は前のスクリプトを使い続ける。
+//!
+//! 勤務時間を設定した場合、勤務時間外はこの PC ではトーストと通知音を使わず、Webhook（ntfy・Telegram など）にのみ送る。
+//! 勤務時間による切り替えは通知方法の既定として決め、Webhook への送信も含めてスクリプトで上書きできる。
+
+use crate::notification_history::NotificationEventType;
+use crate::problems::{ErrorCategory, ErrorPayload, ProblemLog};
+use crate::profile_rules;
+use crate::toast::NotificationPriority;
+use crate::NotificationManager;
+use chrono::{NaiveDateTime, Weekday};
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_opener::OpenerExt;
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// 既定のスクリプト（現在の動作と同じく、すべての通知をそのまま表示する）
+const DEFAULT_SCRIPT: &str = include_str!("../resources/routing.rhai");
+
+/// 設定フォルダー内のスクリプトのファイル名
+const SCRIPT_FILE: &str = "routing.rhai";
+
+/// スクリプトの変更を確認する間隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// スクリプトの1回の呼び出しで実行できる命令数（無限ループで通知が止まらないように）
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// スクリプトで呼び出す関数
+const ROUTE_FN: &str = "route";
+
+/// ルーティングスクリプトの設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoutingSettings {
+    pub enabled: bool,
+    /// 勤務時間による通知方法の切り替え（スクリプトを使わない場合も有効）
+    pub working_hours: WorkingHours,
+}
+
+/// 勤務時間（勤務時間中はトーストと通知音、勤務時間外は Webhook で知らせる）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkingHours {
+    pub enabled: bool,
+    /// 勤務日（空の場合は毎日）
+    pub days: Vec<Weekday>,
+    /// 開始時刻（`HH:MM`）
+    pub start: String,
+    /// 終了時刻（`HH:MM`、開始より前の場合は日をまたぐ）
+    pub end: String,
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            start: "09:00".to_string(),
+            end: "18:00".to_string(),
+        }
+    }
+}
+
+impl WorkingHours {
+    /// 勤務時間中か（無効の場合は常に勤務時間中とする）
+    pub fn is_working(&self, now: NaiveDateTime) -> bool {
+        !self.enabled || profile_rules::in_schedule(&self.days, &self.start, &self.end, now)
+    }
+
+    /// Webhook に送るか（有効な場合は勤務時間外のみ送る）
+    pub fn pushes(&self, now: NaiveDateTime) -> bool {
+        !self.enabled || !self.is_working(now)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RoutingError {
+    #[error("config directory is not available: {0}")]
+    ConfigDir(String),
+    #[error("failed to read or write the script: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("syntax error in {SCRIPT_FILE}: {0}")]
+    Compile(String),
+    #[error("{SCRIPT_FILE} failed: {0}")]
+    Runtime(String),
+}
+
+/// スクリプトに渡す通知
+#[derive(Debug, Clone, Copy)]
+pub struct RoutingEvent<'a> {
+    pub event_type: &'a NotificationEventType,
+    pub title: &'a str,
+    pub body: &'a str,
+    pub priority: NotificationPriority,
+    pub session_id: Option<&'a str>,
+    /// 勤務時間中か（勤務時間を設定していない場合は常に true）
+    pub working_hours: bool,
+    /// 勤務時間の設定で Webhook に送るか（勤務時間を設定していない場合は常に true）
+    pub pushes: bool,
+}
+
+/// 通知方法ごとの有効・無効
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Channels {
+    pub toast: bool,
+    pub sound: bool,
+    /// タスクバーの点滅・バッジ
+    pub taskbar: bool,
+    /// トレイアイコンの点滅・バッジ
+    pub tray: bool,
+    /// Webhook（ntfy・Telegram など）への送信
+    pub webhook: bool,
+    /// タスク完了の読み上げ
+    pub speech: bool,
+}
+
+impl Default for Channels {
+    fn default() -> Self {
+        Self {
+            toast: true,
+            sound: true,
+            taskbar: true,
+            tray: true,
+            webhook: true,
+            speech: true,
+        }
+    }
+}
+
+impl Channels {
+    /// 勤務時間外の既定（トースト・通知音・読み上げを使わず、未確認数の表示だけ残す）
+    pub const OUTSIDE_WORKING_HOURS: Self = Self {
+        toast: false,
+        sound: false,
+        taskbar: true,
+        tray: true,
+        webhook: true,
+        speech: false,
+    };
+
+    /// 通知の既定の通知方法（勤務時間を設定した場合、Webhook は勤務時間外だけ送る）
+    fn for_event(event: &RoutingEvent<'_>) -> Self {
+        let channels = if event.working_hours {
+            Self::default()
+        } else {
+            Self::OUTSIDE_WORKING_HOURS
+        };
+        Self {
+            webhook: event.pushes,
+            ..channels
+        }
+    }
+}
+
+/// スクリプトが決めた通知の方法と表示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub drop: bool,
+    pub title: String,
+    pub body: String,
+    pub priority: NotificationPriority,
+    pub channels: Channels,
+}
+
+impl Route {
+    /// 通知をそのまま表示する（通知方法は勤務時間の既定に従う）
+    pub fn unchanged(event: &RoutingEvent<'_>) -> Self {
+        Self {
+            drop: false,
+            title: event.title.to_string(),
+            body: event.body.to_string(),
+            priority: event.priority,
+            channels: Channels::for_event(event),
+        }
+    }
+}
+
+fn event_name(event_type: &NotificationEventType) -> &'static str {
+    match event_type {
+        NotificationEventType::Stop => "stop",
+        NotificationEventType::PermissionRequest => "permission_request",
+        NotificationEventType::Notification => "notification",
+    }
+}
+
+fn priority_name(priority: NotificationPriority) -> &'static str {
+    match priority {
+        NotificationPriority::Low => "low",
+        NotificationPriority::Normal => "normal",
+        NotificationPriority::High => "high",
+        NotificationPriority::Critical => "critical",
+    }
+}
+
+fn parse_priority(name: &str) -> Option<NotificationPriority> {
+    match name {
+        "low" => Some(NotificationPriority::Low),
+        "normal" => Some(NotificationPriority::Normal),
+        "high" => Some(NotificationPriority::High),
+        "critical" => Some(NotificationPriority::Critical),
+        _ => None,
+    }
+}
+
+fn event_map(event: &RoutingEvent<'_>) -> Map {
+    let mut map = Map::new();
+    map.insert("event".into(), event_name(event.event_type).into());
+    map.insert("title".into(), event.title.into());
+    map.insert("body".into(), event.body.into());
+    map.insert("priority".into(), priority_name(event.priority).into());
+    map.insert("session_id".into(), event.session_id.unwrap_or_default().into());
+    map.insert("working_hours".into(), event.working_hours.into());
+    map
+}
+
+fn string_field(map: &Map, name: &str) -> Option<String> {
+    map.get(name).and_then(|value| value.clone().into_string().ok())
+}
+
+fn bool_field(map: &Map, name: &str) -> Option<bool> {
+    map.get(name).and_then(|value| value.as_bool().ok())
+}
+
+/// スクリプトの戻り値を通知の方法に変換する（マップ以外・不明な値は変更しない）
+fn route_from(result: Dynamic, event: &RoutingEvent<'_>) -> Route {
+    let mut route = Route::unchanged(event);
+    let Some(map) = result.try_cast::<Map>() else {
+        return route;
+    };
+    route.drop = bool_field(&map, "drop").unwrap_or(false);
+    if let Some(title) = string_field(&map, "title") {
+        route.title = title;
+    }
+    if let Some(body) = string_field(&map, "body") {
+        route.body = body;
+    }
+    if let Some(priority) = string_field(&map, "priority").as_deref().and_then(parse_priority) {
+        route.priority = priority;
+    }
+    if let Some(channels) = map.get("channels").and_then(|value| value.clone().try_cast::<Map>()) {
+        let channel = |name, current| bool_field(&channels, name).unwrap_or(current);
+        route.channels = Channels {
+            toast: channel("toast", route.channels.toast),
+            sound: channel("sound", route.channels.sound),
+            taskbar: channel("taskbar", route.channels.taskbar),
+            tray: channel("tray", route.channels.tray),
+            webhook: channel("webhook", route.channels.webhook),
+            speech: channel("speech", route.channels.speech),
+        };
+    }
+    route
+}
+
+/// 読み込んだスクリプト
+pub struct RoutingEngine {
+    engine: Engine,
+    ast: RwLock<Option<AST>>,
+    /// 読み込んだファイルの更新日時
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl Default for RoutingEngine {
+    fn default() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        Self {
+            engine,
+            ast: RwLock::new(None),
+            modified: Mutex::new(None),
+        }
+    }
+}
+
+impl RoutingEngine {
+    /// スクリプトを読み込む（構文エラーの場合は前のスクリプトを使い続ける）
+    pub fn load(&self, source: &str) -> Result<(), RoutingError> {
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|e| RoutingError::Compile(e.to_string()))?;
+        *self.ast.write().unwrap() = Some(ast);
+        Ok(())
+    }
+
+    /// スクリプトで通知の方法を決める（スクリプトを読み込んでいない場合はそのまま）
+    pub fn route(&self, event: &RoutingEvent<'_>) -> Result<Route, RoutingError> {
+        let ast = self.ast.read().unwrap();
+        let Some(ast) = ast.as_ref() else {
+            return Ok(Route::unchanged(event));
+        };
+        let result = self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), ast, ROUTE_FN, (event_map(event),))
+            .map_err(|e| RoutingError::Runtime(e.to_string()))?;
+        Ok(route_from(result, event))
+    }
+}
+
+/// スクリプトのパス（ない場合は既定のスクリプトを書き出す）
+fn script_path(app: &AppHandle) -> Result<PathBuf, RoutingError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| RoutingError::ConfigDir(e.to_string()))?;
+    let path = dir.join(SCRIPT_FILE);
+    if !path.exists() {
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(&path, DEFAULT_SCRIPT)?;
+        info!("Wrote default routing script to {}", path.display());
+    }
+    Ok(path)
+}
+
+/// スクリプトが変更されていれば読み込み直す
+fn reload_if_changed(app: &AppHandle, engine: &RoutingEngine) -> Result<(), RoutingError> {
+    let path = script_path(app)?;
+    let modified = std::fs::metadata(&path)?.modified().ok();
+    let mut loaded = engine.modified.lock().unwrap();
+    if loaded.is_some() && *loaded == modified {
+        return Ok(());
+    }
+    // 構文エラーでも同じ内容を繰り返し報告しないよう、読み込みを試みた日時を記録する
+    *loaded = modified;
+    engine.load(&std::fs::read_to_string(&path)?)?;
+    info!("Loaded routing script from {}", path.display());
+    Ok(())
+}
+
+fn report(app: &AppHandle, error: &RoutingError) {
+    warn!("Routing script error: {}", error);
+    let strings = app.state::<Arc<NotificationManager>>().strings();
+    let problem = ErrorPayload {
+        category: ErrorCategory::Other,
+        message: error.to_string(),
+        hook: Some(SCRIPT_FILE.to_string()),
+        exit_code: None,
+        host: None,
+        session_id: None,
+        cwd: None,
+        raw: None,
+    };
+    let hint = problem.hint(strings);
+    app.state::<ProblemLog>().add(problem, hint);
+    let _ = app.emit("problem-added", ());
+}
+
+/// 通知の方法を決める（通知しない場合は None、スクリプトのエラーではそのまま通知する）
+pub fn route(app: &AppHandle, settings: &RoutingSettings, event: RoutingEvent<'_>) -> Option<Route> {
+    if !settings.enabled {
+        return Some(Route::unchanged(&event));
+    }
+    let route = match app.state::<RoutingEngine>().route(&event) {
+        Ok(route) => route,
+        Err(e) => {
+            warn!("Routing script failed, notifying as is: {}", e);
+            Route::unchanged(&event)
+        }
+    };
+    (!route.drop).then_some(route)
+}
+
+/// 有効な間、スクリプトの変更を確認して読み込み直す
+pub fn start_watcher(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        let enabled = app.state::<Arc<NotificationManager>>().get_settings().routing.enabled;
+        let engine = app.state::<RoutingEngine>();
+        if enabled {
+            if let Err(e) = reload_if_changed(&app, &engine) {
+                report(&app, &e);
+            }
+        } else {
+            // 再び有効にしたときに読み込み直す
+            *engine.modified.lock().unwrap() = None;
+        }
+        std::thread::sleep(WATCH_INTERVAL);
+    });
+}
+
+/// Tauriコマンド: ルーティングスクリプトを既定のアプリで開く
+#[tauri::command]
+pub fn open_routing_script(app: AppHandle) -> Result<(), String> {
+    let path = script_path(&app).map_err(|e| e.to_string())?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> RoutingEvent<'static> {
+        RoutingEvent {
+            event_type: &NotificationEventType::Stop,
+            title: "my-app (1)",
+            body: "Task completed",
+            priority: NotificationPriority::Low,
+            session_id: Some("s1"),
+            working_hours: true,
+            pushes: true,
+        }
+    }
+
+    #[test]
+    fn test_default_script_keeps_behavior() {
+        let engine = RoutingEngine::default();
+        assert_eq!(engine.route(&event()).unwrap(), Route::unchanged(&event()));
+        engine.load(DEFAULT_SCRIPT).unwrap();
+        assert_eq!(engine.route(&event()).unwrap(), Route::unchanged(&event()));
+    }
+
+    #[test]
+    fn test_script_decisions() {
+        let engine = RoutingEngine::default();
+        engine
+            .load(
+                r#"
+                fn route(event) {
+                    if event.event == "stop" {
+                        return #{ title: "✅ " + event.title, priority: "high", channels: #{ toast: false, webhook: false } };
+                    }
+                    #{ drop: true }
+                }
+                "#,
+            )
+            .unwrap();
+        let route = engine.route(&event()).unwrap();
+        assert_eq!(route.title, "✅ my-app (1)");
+        assert_eq!(route.body, "Task completed");
+        assert_eq!(route.priority, NotificationPriority::High);
+        assert_eq!(
+            route.channels,
+            Channels {
+                toast: false,
+                webhook: false,
+                ..Channels::default()
+            }
+        );
+
+        let notification = RoutingEvent {
+            event_type: &NotificationEventType::Notification,
+            ..event()
+        };
+        assert!(engine.route(&notification).unwrap().drop);
+    }
+
+    #[test]
+    fn test_working_hours() {
+        let at = |value| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap();
+        let hours = WorkingHours {
+            enabled: true,
+            ..WorkingHours::default()
+        };
+        // 2026-10-19 は月曜日、2026-10-24 は土曜日
+        assert!(hours.is_working(at("2026-10-19 10:00")));
+        assert!(!hours.pushes(at("2026-10-19 10:00")));
+        assert!(!hours.is_working(at("2026-10-19 20:00")));
+        assert!(hours.pushes(at("2026-10-24 10:00")));
+
+        // 無効の場合は時間帯に関係なくローカルにも Webhook にも通知する
+        let disabled = WorkingHours::default();
+        assert!(disabled.is_working(at("2026-10-24 10:00")));
+        assert!(disabled.pushes(at("2026-10-19 10:00")));
+    }
+
+    #[test]
+    fn test_outside_working_hours_channels() {
+        let outside = RoutingEvent {
+            working_hours: false,
+            ..event()
+        };
+        assert_eq!(Route::unchanged(&outside).channels, Channels::OUTSIDE_WORKING_HOURS);
+
+        // スクリプトは勤務時間外の既定を上書きできる
+        let engine = RoutingEngine::default();
+        engine
+            .load(
+                r#"
+                fn route(event) {
+                    if !event.working_hours && event.priority == "low" {
+                        return #{ channels: #{ toast: true } };
+                    }
+                    ()
+                }
+                "#,
+            )
+            .unwrap();
+        let route = engine.route(&outside).unwrap();
+        assert!(route.channels.toast);
+        assert!(!route.channels.sound);
+        assert_eq!(engine.route(&event()).unwrap().channels, Channels::default());
+    }
+
+    #[test]
+    fn test_working_hours_webhook_channel() {
+        let inside = RoutingEvent {
+            pushes: false,
+            ..event()
+        };
+        let outside = RoutingEvent {
+            working_hours: false,
+            ..event()
+        };
+        // 勤務時間中は Webhook に送らず、勤務時間外だけ送る
+        assert!(!Route::unchanged(&inside).channels.webhook);
+        assert!(Route::unchanged(&outside).channels.webhook);
+
+        // スクリプトは勤務時間外の Webhook を止められる
+        let engine = RoutingEngine::default();
+        engine
+            .load(
+                r#"
+                fn route(event) {
+                    if !event.working_hours && event.event == "stop" {
+                        return #{ channels: #{ webhook: false } };
+                    }
+                    ()
+                }
+                "#,
+            )
+            .unwrap();
+        let route = engine.route(&outside).unwrap();
+        assert_eq!(
+            route.channels,
+            Channels {
+                webhook: false,
+                ..Channels::OUTSIDE_WORKING_HOURS
+            }
+        );
+        let permission = RoutingEvent {
+            event_type: &NotificationEventType::PermissionRequest,
+            ..outside
+        };
+        assert!(engine.route(&permission).unwrap().channels.webhook);
+    }
+
+    #[test]
+    fn test_errors_keep_previous_script() {
+        let engine = RoutingEngine::default();
+        engine.load(r#"fn route(event) { #{ body: "routed" } }"#).unwrap();
+        assert!(matches!(engine.load("fn route(event) {"), Err(RoutingError::Compile(_))));
+        assert_eq!(engine.route(&event()).unwrap().body, "routed");
+
+        engine.load("fn route(event) { loop {} }").unwrap();
+        assert!(matches!(engine.route(&event()), Err(RoutingError::Runtime(_))));
+    }
+}
@@ -0,0 +1,278 @@
+//! イベントごとにスクリプトを実行するモジュール
+//!
+//! アプリを改変せずに独自の処理（LED の点灯・データベースへの記録など）を追加できるよう、
+//! イベントを受信するたびに設定したコマンドを実行し、ペイロードの JSON を標準入力に渡す。
+//! コマンドはシェル（Windows は `cmd /C`、それ以外は `sh -c`）で実行し、制限時間を過ぎたら終了させる。
+//! 失敗は診断タブの「問題」に記録し、失敗が続いている間は最初の1回だけ通知する。
+
+use crate::client::topics;
+use crate::notification_history::NotificationEventType;
+use crate::problems::{ErrorCategory, ErrorPayload, ProblemLog};
+use crate::{show_simple_notification, text, NotificationManager, NotificationOrigin};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+/// 終了を確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 制限時間の上限（秒）
+const MAX_TIMEOUT_SECS: u64 = 300;
+
+/// 問題に記録する標準エラー出力の最大長
+const MAX_STDERR_CHARS: usize = 500;
+
+/// 問題・通知に表示するコマンドの最大長
+const MAX_COMMAND_CHARS: usize = 40;
+
+/// スクリプトの実行の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptHookSettings {
+    pub enabled: bool,
+    /// 実行するコマンド（シェルで解釈する）
+    pub command: String,
+    /// 制限時間（秒）
+    pub timeout_secs: u64,
+}
+
+impl Default for ScriptHookSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: String::new(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl ScriptHookSettings {
+    /// 実行するコマンド（無効・未設定の場合は None）
+    pub fn active_command(&self) -> Option<&str> {
+        Some(self.command.trim()).filter(|command| self.enabled && !command.is_empty())
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.clamp(1, MAX_TIMEOUT_SECS))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ScriptHookError {
+    #[error("Failed to start the script: {0}")]
+    Spawn(#[from] std::io::Error),
+    #[error("The script did not finish within {0} seconds")]
+    Timeout(u64),
+    #[error("The script failed: {stderr}")]
+    Failed { code: Option<i32>, stderr: String },
+}
+
+impl ScriptHookError {
+    fn exit_code(&self) -> Option<i32> {
+        match self {
+            Self::Failed { code, .. } => *code,
+            _ => None,
+        }
+    }
+}
+
+/// 連続した失敗の通知を抑える状態
+#[derive(Default)]
+pub struct ScriptHookRunner {
+    failing: AtomicBool,
+}
+
+/// スクリプトを実行するトピックか（頻繁に届くステータス・ハートビートは除く）
+pub fn runs_for(topic: &str) -> bool {
+    !(topic == topics::STATUS
+        || topic.starts_with(topics::STATUS_PREFIX)
+        || topic.starts_with(topics::HEARTBEAT_PREFIX))
+}
+
+/// トピックの最後の階層（`claude-code/events/stop` なら `stop`）
+fn event_name(topic: &str) -> &str {
+    topic.rsplit('/').next().unwrap_or(topic)
+}
+
+fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").raw_arg(command);
+        // コンソールウィンドウを表示しない（CREATE_NO_WINDOW）
+        shell.creation_flags(0x0800_0000);
+        shell
+    }
+    #[cfg(not(windows))]
+    {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+/// 制限時間まで終了を待つ（過ぎた場合は終了させる）
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<Option<std::process::ExitStatus>, std::io::Error> {
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// コマンドを実行し、ペイロードを標準入力に渡す
+pub fn run(command: &str, topic: &str, payload: &str, timeout: Duration) -> Result<(), ScriptHookError> {
+    let mut child = shell_command(command)
+        .env("CLAUDE_NOTIFY_TOPIC", topic)
+        .env("CLAUDE_NOTIFY_EVENT", event_name(topic))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // 標準入力を読まないスクリプトでも止まらないよう、書き込みと標準エラー出力の読み取りは別のスレッドで行う
+    let stdin = child.stdin.take();
+    let payload = payload.to_string();
+    std::thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(payload.as_bytes());
+        }
+    });
+    let stderr = child.stderr.take().map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            output
+        })
+    });
+
+    let status = wait_with_timeout(&mut child, timeout)?;
+    let Some(status) = status else {
+        return Err(ScriptHookError::Timeout(timeout.as_secs()));
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = stderr
+        .and_then(|reader| reader.join().ok())
+        .map(|output| text::truncate(output.trim(), MAX_STDERR_CHARS))
+        .filter(|output| !output.is_empty())
+        .unwrap_or_else(|| status.to_string());
+    Err(ScriptHookError::Failed {
+        code: status.code(),
+        stderr,
+    })
+}
+
+/// 失敗を診断タブに記録し、失敗が続いていなければ通知する
+fn report(app: &AppHandle, command: &str, topic: &str, error: &ScriptHookError) {
+    warn!("Script hook failed for {}: {}", topic, error);
+    let notification_manager = app.state::<Arc<NotificationManager>>();
+    let strings = notification_manager.strings();
+    let problem = ErrorPayload {
+        category: ErrorCategory::Hook,
+        message: error.to_string(),
+        hook: Some(text::truncate(command, MAX_COMMAND_CHARS)),
+        exit_code: error.exit_code(),
+        host: None,
+        session_id: None,
+        cwd: None,
+        raw: None,
+    };
+    let (title, body) = problem.notification_text(strings);
+    let hint = problem.hint(strings);
+    app.state::<ProblemLog>().add(problem, hint);
+    let _ = app.emit("problem-added", ());
+
+    if app.state::<ScriptHookRunner>().failing.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    show_simple_notification(app, &notification_manager, &title, &body, NotificationEventType::Notification, NotificationOrigin::default());
+}
+
+/// イベントのスクリプトを別のスレッドで実行する
+pub fn dispatch(app: &AppHandle, settings: &ScriptHookSettings, topic: &str, payload: &str) {
+    let Some(command) = settings.active_command() else {
+        return;
+    };
+    if !runs_for(topic) {
+        return;
+    }
+    let app = app.clone();
+    let command = command.to_string();
+    let topic = topic.to_string();
+    let payload = payload.to_string();
+    let timeout = settings.timeout();
+    std::thread::spawn(move || {
+        debug!("Running script hook for {}", topic);
+        match run(&command, &topic, &payload, timeout) {
+            Ok(()) => {
+                if app.state::<ScriptHookRunner>().failing.swap(false, Ordering::SeqCst) {
+                    info!("Script hook recovered");
+                }
+            }
+            Err(e) => report(&app, &command, &topic, &e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_command() {
+        let mut settings = ScriptHookSettings {
+            command: " ./on-event.sh ".to_string(),
+            ..ScriptHookSettings::default()
+        };
+        assert_eq!(settings.active_command(), None);
+        settings.enabled = true;
+        assert_eq!(settings.active_command(), Some("./on-event.sh"));
+        settings.timeout_secs = 0;
+        assert_eq!(settings.timeout(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_runs_for_events_only() {
+        assert!(runs_for("claude-code/events/stop"));
+        assert!(runs_for("claude-code/error"));
+        assert!(runs_for("build/app"));
+        assert!(!runs_for("claude-code/status/s1"));
+        assert!(!runs_for("claude-code/heartbeat/wsl"));
+        assert_eq!(event_name("claude-code/events/permission-request"), "permission-request");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_passes_payload_and_reports_failures() {
+        let timeout = Duration::from_secs(5);
+        let check = r#"read -r line; [ "$line" = '{"cwd":"/work"}' ] && [ "$CLAUDE_NOTIFY_EVENT" = stop ]"#;
+        run(check, "claude-code/events/stop", r#"{"cwd":"/work"}"#, timeout).unwrap();
+
+        match run("echo broken >&2; exit 3", "claude-code/events/stop", "{}", timeout) {
+            Err(ScriptHookError::Failed { code, stderr }) => {
+                assert_eq!(code, Some(3));
+                assert_eq!(stderr, "broken");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let error = run("sleep 5", "claude-code/events/stop", "{}", Duration::from_millis(200)).unwrap_err();
+        assert!(matches!(error, ScriptHookError::Timeout(_)));
+    }
+}
@@ -0,0 +1,139 @@
+//! セキュリティイベントの記録モジュール
+//!
+//! ブローカーのアクセス制御（`acl` モジュール）で接続を拒否したクライアントを記録し、
+//! 診断タブの「セキュリティイベント」に一覧表示する。
+//! 拒否されたクライアントは再接続を繰り返すため、同じクライアントIDとユーザー名の記録は
+//! 1件にまとめて回数と最後の時刻を更新する。
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 記録するイベントの最大数（超えた場合は古いものから削除する）
+const MAX_EVENTS: usize = 100;
+
+/// イベントの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityEventKind {
+    /// 許可リストにないクライアントの接続を拒否した
+    ClientRejected,
+}
+
+/// 記録したイベント
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityEvent {
+    pub id: u64,
+    pub kind: SecurityEventKind,
+    pub client_id: String,
+    /// 接続時に送られたユーザー名（空の場合あり）
+    pub username: String,
+    /// 同じクライアントの回数
+    pub count: u32,
+    pub first_at: DateTime<Local>,
+    pub last_at: DateTime<Local>,
+}
+
+/// セキュリティイベントの記録（アプリの終了までメモリに保持する）
+#[derive(Debug, Default)]
+pub struct SecurityEventLog {
+    events: Mutex<VecDeque<SecurityEvent>>,
+    next_id: AtomicU64,
+}
+
+impl SecurityEventLog {
+    /// 接続を拒否したクライアントを記録する
+    pub fn client_rejected(&self, client_id: &str, username: &str) {
+        self.add(SecurityEventKind::ClientRejected, client_id, username, Local::now());
+    }
+
+    fn add(&self, kind: SecurityEventKind, client_id: &str, username: &str, now: DateTime<Local>) {
+        let mut events = self.events.lock().unwrap();
+        let existing = events
+            .iter()
+            .position(|event| event.kind == kind && event.client_id == client_id && event.username == username);
+        let event = match existing.and_then(|index| events.remove(index)) {
+            Some(event) => SecurityEvent {
+                count: event.count.saturating_add(1),
+                last_at: now,
+                ..event
+            },
+            None => SecurityEvent {
+                id: self.next_id.fetch_add(1, Ordering::SeqCst) + 1,
+                kind,
+                client_id: client_id.to_string(),
+                username: username.to_string(),
+                count: 1,
+                first_at: now,
+                last_at: now,
+            },
+        };
+        events.push_front(event);
+        events.truncate(MAX_EVENTS);
+    }
+
+    /// 新しい順に取得
+    pub fn list(&self) -> Vec<SecurityEvent> {
+        self.events.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+}
+
+/// Tauriコマンド: 記録したセキュリティイベントを取得
+#[tauri::command]
+pub fn get_security_events(events: tauri::State<'_, Arc<SecurityEventLog>>) -> Vec<SecurityEvent> {
+    events.list()
+}
+
+/// Tauriコマンド: 記録したセキュリティイベントを削除
+#[tauri::command]
+pub fn clear_security_events(events: tauri::State<'_, Arc<SecurityEventLog>>) {
+    events.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_repeated_rejections_are_merged() {
+        let log = SecurityEventLog::default();
+        let start = Local::now();
+        log.add(SecurityEventKind::ClientRejected, "mosq-abc", "", start);
+        log.add(SecurityEventKind::ClientRejected, "probe", "admin", start);
+        log.add(SecurityEventKind::ClientRejected, "mosq-abc", "", start + Duration::seconds(5));
+
+        let events = log.list();
+        assert_eq!(events.len(), 2);
+        // まとめた記録は先頭に移る
+        assert_eq!(events[0].client_id, "mosq-abc");
+        assert_eq!(events[0].count, 2);
+        assert_eq!(events[0].first_at, start);
+        assert_eq!(events[0].last_at, start + Duration::seconds(5));
+        assert_eq!(events[1].username, "admin");
+
+        // ユーザー名が違う場合は別の記録にする
+        log.add(SecurityEventKind::ClientRejected, "mosq-abc", "me", start);
+        assert_eq!(log.list().len(), 3);
+    }
+
+    #[test]
+    fn test_log_keeps_newest_first() {
+        let log = SecurityEventLog::default();
+        for i in 0..MAX_EVENTS + 1 {
+            log.client_rejected(&format!("client-{}", i), "");
+        }
+        let events = log.list();
+        assert_eq!(events.len(), MAX_EVENTS);
+        assert_eq!(events[0].client_id, format!("client-{}", MAX_EVENTS));
+
+        log.clear();
+        assert!(log.list().is_empty());
+    }
+}
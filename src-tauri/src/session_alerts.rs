@@ -0,0 +1,387 @@
+//! セッションの状態からの通知モジュール
+//!
+//! フックのイベントとは別に、ステータスラインが送るセッションの状態の変化から通知する。
+//! 変更行数（追加と削除の合計）が設定した間隔の節目を超えた場合に知らせ、
+//! エージェントが想定より大きな変更をしていることに気付けるようにする。
+//! 間隔はプロジェクトごとのルール（[`crate::project_rules`]）で上書き・無効にできる。
+//!
+//! また、作業中から入力待ちに変わったセッションを、フックのイベントを待たずに知らせる。
+//! 状態が短い間に行き来しても通知が続かないよう、入力待ちが一定時間続いた場合にのみ通知する。
+//! 入力待ち・待機中のまま一定時間が過ぎたセッションは、プロジェクトごとの時間でもう一度知らせる。
+//! 作業中・入力待ちのセッションがなくなった（すべて待機中になったか終了した）場合は、そのことを一度だけ知らせる。
+
+use crate::i18n;
+use crate::notification_history::NotificationEventType;
+use crate::project_rules::ProjectRule;
+use crate::state::{
+    SessionData, SessionNameManager, SessionStatus, StatusPayload, IDLE_STATE, WAITING_STATE, WORKING_STATE,
+};
+use crate::toast::NotificationPriority;
+use crate::{AppState, NotificationManager, NotificationOrigin};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+/// 状態の変化を確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// すべてのセッションが待機中・終了になってから知らせるまでの時間（次のプロンプトまでの合間に知らせないように）
+const ALL_CLEAR_DEBOUNCE: Duration = Duration::from_secs(10);
+
+/// 変更行数の節目の通知の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LineMilestoneSettings {
+    pub enabled: bool,
+    /// 通知する間隔（行）
+    pub step: u32,
+}
+
+impl Default for LineMilestoneSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            step: 500,
+        }
+    }
+}
+
+impl LineMilestoneSettings {
+    /// プロジェクトで通知する間隔（通知しない場合は None）
+    ///
+    /// プロジェクトのルールで間隔を指定した場合は、全体の設定が無効でも通知する。
+    pub fn step_for(&self, rule: Option<&ProjectRule>) -> Option<u32> {
+        match rule.and_then(|rule| rule.line_milestone) {
+            Some(step) => (step > 0).then_some(step),
+            None => (self.enabled && self.step > 0).then_some(self.step),
+        }
+    }
+}
+
+/// 状態の変化（作業中 → 入力待ち）の通知の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateTransitionSettings {
+    pub enabled: bool,
+    /// 入力待ちがこの秒数続いたら通知する
+    pub debounce_secs: u64,
+}
+
+impl Default for StateTransitionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            debounce_secs: 5,
+        }
+    }
+}
+
+impl StateTransitionSettings {
+    pub fn debounce(&self) -> Duration {
+        Duration::from_secs(self.debounce_secs)
+    }
+}
+
+/// 入力待ちが続いているセッションのリマインダーの設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaitingReminderSettings {
+    pub enabled: bool,
+    /// 入力待ち・待機中がこの分数続いたら知らせる
+    pub minutes: u32,
+}
+
+impl Default for WaitingReminderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            minutes: 15,
+        }
+    }
+}
+
+impl WaitingReminderSettings {
+    /// プロジェクトで知らせるまでの分数（知らせない場合は None）
+    ///
+    /// プロジェクトのルールで分数を指定した場合は、全体の設定が無効でも知らせる。
+    pub fn minutes_for(&self, rule: Option<&ProjectRule>) -> Option<u32> {
+        match rule.and_then(|rule| rule.waiting_reminder) {
+            Some(minutes) => (minutes > 0).then_some(minutes),
+            None => (self.enabled && self.minutes > 0).then_some(self.minutes),
+        }
+    }
+}
+
+/// すべてのセッションが待機中・終了になったときの通知の設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AllClearSettings {
+    pub enabled: bool,
+}
+
+/// 待機中でないセッションの数の変化から、すべて片付いたことを検出する
+#[derive(Debug, Default)]
+struct AllClearDetector {
+    /// 前回の通知の後に待機中でないセッションがあった
+    active: bool,
+    /// 待機中でないセッションがなくなった時刻
+    clear_since: Option<Instant>,
+}
+
+impl AllClearDetector {
+    /// 待機中でないセッションの数を記録し、知らせる場合は true を返す
+    ///
+    /// 0 になった状態が [`ALL_CLEAR_DEBOUNCE`] 続いた場合に一度だけ知らせる。
+    fn update(&mut self, active_sessions: usize, now: Instant) -> bool {
+        if active_sessions > 0 {
+            self.active = true;
+            self.clear_since = None;
+            return false;
+        }
+        if !self.active {
+            return false;
+        }
+        let since = *self.clear_since.get_or_insert(now);
+        if now.saturating_duration_since(since) < ALL_CLEAR_DEBOUNCE {
+            return false;
+        }
+        self.active = false;
+        self.clear_since = None;
+        true
+    }
+}
+
+/// 追加・削除した行数の合計
+fn lines_changed(status: &SessionStatus) -> Option<i64> {
+    match (status.lines_added, status.lines_removed) {
+        (None, None) => None,
+        (added, removed) => Some(added.unwrap_or(0) + removed.unwrap_or(0)),
+    }
+}
+
+/// 前回から今回までに超えた節目（複数を超えた場合は最も大きいもの）
+fn crossed_milestone(previous: i64, current: i64, step: u32) -> Option<i64> {
+    let step = i64::from(step);
+    let milestone = current / step * step;
+    (milestone > 0 && milestone > previous).then_some(milestone)
+}
+
+/// ステータスの更新から通知する（`previous` は前回のステータス、初めて届いたセッションの場合は None）
+pub fn on_status_update(
+    app: &AppHandle,
+    notification_manager: &NotificationManager,
+    session_name_manager: &SessionNameManager,
+    previous: Option<&SessionStatus>,
+    payload: &StatusPayload,
+) {
+    // アプリの起動前から続いているセッションは、最初のステータスを基準にする
+    let Some(previous) = previous else {
+        return;
+    };
+    let settings = notification_manager.get_settings();
+    if settings.host_rules.is_muted(payload.host.as_deref()) {
+        return;
+    }
+    let rule = settings.project_rule(&payload.cwd);
+
+    let Some(step) = settings.line_milestones.step_for(rule) else {
+        return;
+    };
+    let (Some(before), Some(after)) = (lines_changed(previous), lines_changed(&payload.status)) else {
+        return;
+    };
+    let Some(milestone) = crossed_milestone(before, after, step) else {
+        return;
+    };
+
+    info!("Session {} crossed {} changed lines", payload.session_id, milestone);
+    let strings = notification_manager.strings();
+    let title = session_name_manager.get_or_create_name(&payload.session_id, &payload.cwd, payload.host.as_deref());
+    let body = i18n::fill(
+        strings.line_milestone_body,
+        &[
+            ("lines", &milestone.to_string()),
+            ("added", &payload.status.lines_added.unwrap_or(0).to_string()),
+            ("removed", &payload.status.lines_removed.unwrap_or(0).to_string()),
+        ],
+    );
+    notification_manager.notify(
+        app,
+        &title,
+        &body,
+        NotificationEventType::Notification,
+        NotificationPriority::Normal,
+        NotificationOrigin {
+            cwd: Some(&payload.cwd),
+            ..NotificationOrigin::session(Some(&payload.session_id))
+        },
+    );
+}
+
+/// 入力待ちに変わったセッションと、入力待ちが続いているセッションを定期的に確認し、通知する
+pub fn start_monitor(app: &AppHandle) {
+    let app = app.clone();
+    let mut all_clear = AllClearDetector::default();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+
+        let notification_manager = app.state::<Arc<NotificationManager>>();
+        let settings = notification_manager.get_settings();
+        let (session_manager, session_name_manager) = match app.state::<Mutex<AppState>>().lock() {
+            Ok(state) => (state.session_manager.clone(), state.session_name_manager.clone()),
+            Err(_) => continue,
+        };
+        let now = Instant::now();
+        let strings = notification_manager.strings();
+
+        let mut alerts: Vec<(SessionData, String)> = Vec::new();
+        if settings.state_transitions.enabled {
+            let waiting = session_manager.settled_transitions(
+                WORKING_STATE,
+                WAITING_STATE,
+                settings.state_transitions.debounce(),
+                now,
+            );
+            for session in waiting {
+                info!("Session {} is waiting for input", session.session_id);
+                alerts.push((session, strings.state_waiting_body.to_string()));
+            }
+        }
+
+        let stale = session_manager.stale_sessions(&[WAITING_STATE, IDLE_STATE], now, |session| {
+            let rule = settings.project_rule(&session.cwd);
+            settings
+                .waiting_reminder
+                .minutes_for(rule)
+                .map(|minutes| Duration::from_secs(u64::from(minutes) * 60))
+        });
+        for (session, elapsed) in stale {
+            info!("Session {} has been waiting for {:?}", session.session_id, elapsed);
+            let minutes = (elapsed.as_secs() / 60).to_string();
+            alerts.push((session, i18n::fill(strings.waiting_reminder_body, &[("minutes", &minutes)])));
+        }
+
+        for (session, body) in alerts {
+            if settings.host_rules.is_muted(session.host.as_deref()) {
+                continue;
+            }
+            let title =
+                session_name_manager.get_or_create_name(&session.session_id, &session.cwd, session.host.as_deref());
+            notification_manager.notify(
+                &app,
+                &title,
+                &body,
+                NotificationEventType::Notification,
+                NotificationPriority::Normal,
+                NotificationOrigin {
+                    cwd: Some(&session.cwd),
+                    ..NotificationOrigin::session(Some(&session.session_id))
+                },
+            );
+        }
+
+        // 無効の間も記録し、有効にした直後に過去の状態で知らせないようにする
+        if all_clear.update(session_manager.active_count(), now) && settings.all_clear.enabled {
+            info!("All sessions are idle");
+            notification_manager.notify(
+                &app,
+                strings.all_clear_title,
+                strings.all_clear_body,
+                NotificationEventType::Notification,
+                NotificationPriority::Normal,
+                NotificationOrigin::default(),
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossed_milestone() {
+        assert_eq!(crossed_milestone(450, 520, 500), Some(500));
+        assert_eq!(crossed_milestone(520, 900, 500), None);
+        // 一度に複数の節目を超えた場合は1度だけ通知する
+        assert_eq!(crossed_milestone(0, 1200, 500), Some(1000));
+        assert_eq!(crossed_milestone(0, 10, 500), None);
+    }
+
+    #[test]
+    fn test_step_for_project() {
+        let settings = LineMilestoneSettings::default();
+        assert_eq!(settings.step_for(None), None);
+
+        let rule = ProjectRule {
+            project: "my-app".to_string(),
+            line_milestone: Some(1000),
+            ..ProjectRule::default()
+        };
+        assert_eq!(settings.step_for(Some(&rule)), Some(1000));
+
+        let enabled = LineMilestoneSettings {
+            enabled: true,
+            ..LineMilestoneSettings::default()
+        };
+        assert_eq!(enabled.step_for(None), Some(500));
+        let disabled_rule = ProjectRule {
+            line_milestone: Some(0),
+            ..rule
+        };
+        assert_eq!(enabled.step_for(Some(&disabled_rule)), None);
+    }
+
+    #[test]
+    fn test_minutes_for_project() {
+        let settings = WaitingReminderSettings::default();
+        assert_eq!(settings.minutes_for(None), None);
+
+        let rule = ProjectRule {
+            project: "my-app".to_string(),
+            waiting_reminder: Some(30),
+            ..ProjectRule::default()
+        };
+        assert_eq!(settings.minutes_for(Some(&rule)), Some(30));
+
+        let enabled = WaitingReminderSettings {
+            enabled: true,
+            ..WaitingReminderSettings::default()
+        };
+        let other_rule = ProjectRule {
+            waiting_reminder: None,
+            line_milestone: Some(1000),
+            ..rule
+        };
+        assert_eq!(enabled.minutes_for(Some(&other_rule)), Some(15));
+    }
+
+    #[test]
+    fn test_all_clear_fires_once_after_debounce() {
+        let mut detector = AllClearDetector::default();
+        let start = Instant::now();
+        // セッションが動いていない間は知らせない
+        assert!(!detector.update(0, start + ALL_CLEAR_DEBOUNCE));
+
+        assert!(!detector.update(2, start));
+        assert!(!detector.update(0, start));
+        // 合間に次のプロンプトが始まった場合は待ち直す
+        assert!(!detector.update(1, start + Duration::from_secs(5)));
+        assert!(!detector.update(0, start + Duration::from_secs(6)));
+        assert!(detector.update(0, start + Duration::from_secs(6) + ALL_CLEAR_DEBOUNCE));
+        assert!(!detector.update(0, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_lines_changed() {
+        let status = SessionStatus {
+            lines_added: Some(120),
+            lines_removed: Some(30),
+            ..SessionStatus::default()
+        };
+        assert_eq!(lines_changed(&status), Some(150));
+        assert_eq!(lines_changed(&SessionStatus::default()), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! セッションの操作モジュール
+//!
+//! 暴走したエージェントを通知アプリから止められるように、診断タブで選択したセッションへ
+//! `claude-code/control/<session_id>/<command>` でコマンドを送信する。
+//!
+//! | コマンド | フック側の動作 |
+//! |----------|----------------|
+//! | `pause`  | Claude Code のプロセスを一時停止する（SIGSTOP） |
+//! | `resume` | 一時停止したプロセスを再開する（SIGCONT） |
+//! | `stop`   | 実行中の処理を中断する（SIGINT、Esc キーで中断した場合と同じ） |
+//! | `ping`   | 何もせずに応答だけを返す（待ち受けているかの確認） |
+//!
+//! フック側のヘルパースクリプト（control-listener.sh、statusline.sh が起動する）がこのトピックを待ち受け、
+//! 実行した結果を `claude-code/control-reply/<session_id>` に返す。
+//! コマンドはセッションを止めるため、共有シークレットが設定されている場合は署名して送信し、
+//! ヘルパースクリプト側で検証する。
+
+use crate::client::{topics, MqttPublisher};
+use crate::signing;
+use crate::NotificationManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+/// セッションの操作の設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionControlSettings {
+    /// ステータスラインがアプリからのコマンドを待ち受けるか（エクスポートしたスクリプトに反映）
+    pub enabled: bool,
+}
+
+/// セッションに送るコマンド
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlCommand {
+    Pause,
+    Resume,
+    Stop,
+    Ping,
+}
+
+impl ControlCommand {
+    /// トピックの最後の階層
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Stop => "stop",
+            Self::Ping => "ping",
+        }
+    }
+}
+
+/// コマンドのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlRequest {
+    pub session_id: String,
+    pub command: ControlCommand,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// フック側からのコマンドの結果（claude-code/control-reply/<session_id>）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlReply {
+    pub session_id: String,
+    pub command: ControlCommand,
+    /// コマンドを実行できたか（Claude Code のプロセスにシグナルを送れたか）
+    pub ok: bool,
+    /// 応答したホスト
+    #[serde(default)]
+    pub host: Option<String>,
+}
+
+/// セッションIDとコマンドからトピックを作成する
+pub fn command_topic(session_id: &str, command: ControlCommand) -> Option<String> {
+    topics::for_session(topics::CONTROL_PREFIX, session_id).map(|topic| format!("{}/{}", topic, command.as_str()))
+}
+
+/// セッションにコマンドを送信する（シークレットがあれば署名する）
+pub fn send_command(
+    app: &AppHandle,
+    session_id: &str,
+    command: ControlCommand,
+    secret: Option<&str>,
+) -> Result<(), String> {
+    let topic = command_topic(session_id, command).ok_or_else(|| "Session ID is empty".to_string())?;
+    let publisher = app
+        .try_state::<MqttPublisher>()
+        .ok_or_else(|| "MQTT client is not ready".to_string())?;
+
+    let request = ControlRequest {
+        session_id: session_id.trim().to_string(),
+        command,
+        timestamp: Utc::now(),
+    };
+    let payload = serde_json::to_string(&request)
+        .map_err(|e| format!("Failed to serialize control command: {}", e))?;
+    let payload = match secret {
+        Some(secret) => signing::sign_payload(&payload, secret),
+        None => payload,
+    };
+
+    publisher
+        .publish(&topic, payload.into_bytes(), false)
+        .map_err(|e| format!("Failed to publish control command on {}: {}", topic, e))?;
+    info!("Sent {:?} to session {}", command, session_id);
+    Ok(())
+}
+
+/// Tauriコマンド: 診断タブで選択したセッションにコマンドを送信する
+#[tauri::command]
+pub fn send_session_command(
+    app: AppHandle,
+    session_id: String,
+    command: ControlCommand,
+    notification_manager: tauri::State<'_, Arc<NotificationManager>>,
+) -> Result<(), String> {
+    let settings = notification_manager.get_settings();
+    if !settings.session_control.enabled {
+        return Err("Session control is disabled".to_string());
+    }
+    send_command(&app, &session_id, command, settings.payload_signing.active_secret())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_topic() {
+        assert_eq!(
+            command_topic("abc-123", ControlCommand::Stop).as_deref(),
+            Some("claude-code/control/abc-123/stop")
+        );
+        assert_eq!(
+            command_topic("a/b", ControlCommand::Ping).as_deref(),
+            Some("claude-code/control/a_b/ping")
+        );
+        assert_eq!(command_topic(" ", ControlCommand::Pause), None);
+    }
+
+    #[test]
+    fn test_reply_deserialization() {
+        let reply: ControlReply =
+            serde_json::from_str(r#"{"session_id":"s1","command":"resume","ok":false}"#).unwrap();
+        assert_eq!(reply.command, ControlCommand::Resume);
+        assert!(!reply.ok);
+        assert_eq!(reply.host, None);
+    }
+}
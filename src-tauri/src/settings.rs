@@ -2,9 +2,51 @@
 //!
 //! tauri-plugin-store を使用して設定を永続化する
 
+use crate::acl::BrokerAclSettings;
+use crate::broker_listeners::BrokerListenerSettings;
+use crate::audio::{PlaybackPolicy, SoundId, SoundOverlapMode};
+use crate::calendar::CalendarSettings;
+use crate::catch_up::CatchUpSettings;
+use crate::cost_report::CostReportSettings;
+use crate::credentials;
+use crate::custom_topics::CustomTopicSettings;
+use crate::digest::DigestSettings;
+use crate::encryption::PayloadEncryptionSettings;
+use crate::external_broker::ExternalBrokerSettings;
+use crate::fullscreen::FullscreenSettings;
+use crate::heartbeat::HeartbeatSettings;
+use crate::host_rules::HostRuleSettings;
+use crate::host_update::HostUpdateSettings;
+use crate::i18n::LanguageSetting;
+use crate::ignore_list::IgnoreListSettings;
+use crate::notification_history::{HistoryRetention, NotificationEventType};
+use crate::permission_response::PermissionResponseSettings;
+use crate::profile_rules::ProfileRule;
+use crate::project_aliases::ProjectAliasSettings;
+use crate::project_rules::{ProjectRule, ProjectRuleSettings};
+use crate::rate_limit::RateLimitSettings;
+use crate::redaction::RedactionSettings;
+use crate::rest_api::RestApiSettings;
+use crate::routing::RoutingSettings;
+use crate::script_hook::ScriptHookSettings;
+use crate::session_alerts::{
+    AllClearSettings, LineMilestoneSettings, StateTransitionSettings, WaitingReminderSettings,
+};
+use crate::session_control::SessionControlSettings;
+use crate::signing::PayloadSigningSettings;
+use crate::speech::SpeechSettings;
+use crate::stale_events::StaleEventSettings;
+use crate::tray_flash::{FlashTiming, TrayAttentionMode};
+use crate::tray_theme::TrayIconTheme;
+use crate::updater::UpdateCheckSettings;
+use crate::webhook::WebhookSettings;
+use crate::window_mode::WindowModeSettings;
+use crate::window_placement::WindowPlacement;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
 use tauri_plugin_store::StoreExt;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// 通知設定
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,12 +64,258 @@ pub struct NotificationSettings {
     pub tray_flash_enabled: bool,
     /// 音量（0.0 - 1.0）
     pub sound_volume: f32,
+    /// 表示言語（auto の場合は OS のロケールから判定）
+    #[serde(default)]
+    pub language: LanguageSetting,
+    /// イベントごとの通知音
+    #[serde(default)]
+    pub event_sounds: EventSounds,
+    /// 通知音が重なった場合の再生方法
+    #[serde(default)]
+    pub sound_overlap: SoundOverlapMode,
+    /// 同時に鳴らす通知音の最大数
+    #[serde(default = "default_max_concurrent_sounds")]
+    pub max_concurrent_sounds: u32,
+    /// 同じ通知音を重複とみなす時間幅（ミリ秒）
+    #[serde(default = "default_duplicate_sound_window_ms")]
+    pub duplicate_sound_window_ms: u64,
+    /// 通知音の再生時間の上限（ミリ秒、0 の場合は最後まで再生）
+    #[serde(default)]
+    pub max_sound_duration_ms: u64,
+    /// 通知音のフェードイン・フェードアウトの長さ（ミリ秒、0 の場合はフェードしない）
+    #[serde(default)]
+    pub sound_fade_ms: u64,
+    /// トレイアイコンのテーマ
+    #[serde(default)]
+    pub tray_icon_theme: TrayIconTheme,
+    /// トレイアイコンでの通知方法（点滅 / 未確認数バッジ）
+    #[serde(default)]
+    pub tray_attention_mode: TrayAttentionMode,
+    /// 通知やトレイからメインウィンドウを開いた時の表示位置
+    #[serde(default)]
+    pub window_placement: WindowPlacement,
+    /// メインウィンドウのコンパクト表示・不透明度・常に手前に表示
+    #[serde(default)]
+    pub window_mode: WindowModeSettings,
+    /// トレイアイコンの点滅間隔（ミリ秒）
+    #[serde(default = "default_tray_flash_interval_ms")]
+    pub tray_flash_interval_ms: u64,
+    /// トレイアイコンを点滅させ続ける最大時間（秒、0 は無制限）
+    #[serde(default)]
+    pub tray_flash_max_duration_secs: u64,
+    /// タスクバーボタンの点滅回数
+    #[serde(default = "default_taskbar_flash_count")]
+    pub taskbar_flash_count: u32,
+    /// ブローカーのアクセス制御（クライアントIDの許可リスト）
+    #[serde(default)]
+    pub broker_acl: BrokerAclSettings,
+    /// ブローカーのリスナー（localhost 専用のリスナー）
+    #[serde(default)]
+    pub broker_listeners: BrokerListenerSettings,
+    /// ブローカーを mDNS で LAN に告知し、エクスポートするスクリプトで接続先を検出する（再起動後に反映）
+    #[serde(default)]
+    pub broker_discovery: bool,
+    /// ポートが使用中の場合は空いている次のポートでブローカーを起動する
+    #[serde(default = "default_true")]
+    pub broker_port_fallback: bool,
+    /// 組み込みのブローカーの代わりに接続する外部のブローカー（再起動後に反映）
+    #[serde(default)]
+    pub external_broker: ExternalBrokerSettings,
+    /// イベントペイロードの署名検証
+    #[serde(default)]
+    pub payload_signing: PayloadSigningSettings,
+    /// イベントペイロードの暗号化
+    #[serde(default)]
+    pub payload_encryption: PayloadEncryptionSettings,
+    /// ローカル REST API
+    #[serde(default)]
+    pub rest_api: RestApiSettings,
+    /// アプリからの承認依頼への応答
+    #[serde(default)]
+    pub permission_response: PermissionResponseSettings,
+    /// セッションの操作（一時停止・再開・中断・ping）
+    #[serde(default)]
+    pub session_control: SessionControlSettings,
+    /// 停止イベントで送る Claude の最後のメッセージの文字数（0 の場合は送らない、エクスポートしたスクリプトに反映）
+    #[serde(default = "default_last_message_length")]
+    pub last_message_length: u32,
+    /// トーストに表示するコマンド・テンプレートの値の最大文字数（超えた分は中央を省略、0 の場合は切り詰めない）
+    #[serde(default = "default_toast_body_max_length")]
+    pub toast_body_max_length: usize,
+    /// 送信元ホストごとのルール（ミュート・表示色）
+    #[serde(default)]
+    pub host_rules: HostRuleSettings,
+    /// トレイのツールチップのテンプレート（空の場合は既定のテンプレート）
+    #[serde(default)]
+    pub tooltip_template: String,
+    /// 全画面表示中の通知の保留
+    #[serde(default)]
+    pub fullscreen: FullscreenSettings,
+    /// 通話中はトーストと通知音を止める（Windows のみ）
+    #[serde(default)]
+    pub mute_during_calls: bool,
+    /// カレンダーの予定中は通知を止める
+    #[serde(default)]
+    pub calendar: CalendarSettings,
+    /// フックのハートビートの送信・監視
+    #[serde(default)]
+    pub heartbeat: HeartbeatSettings,
+    /// IP アドレスの変化の監視とリモートのマシンへの接続先の送信
+    #[serde(default)]
+    pub host_update: HostUpdateSettings,
+    /// GitHub のリリースで更新を確認する
+    #[serde(default)]
+    pub update_check: UpdateCheckSettings,
+    /// 通知履歴の保持件数・保持日数
+    #[serde(default)]
+    pub history_retention: HistoryRetention,
+    /// 通知履歴にメタデータ（プロジェクト・種類・時刻）だけを記録し、メッセージやコマンドを残さない
+    #[serde(default)]
+    pub history_metadata_only: bool,
+    /// セッションが作業を再開したら、そのセッションの通知を既読にする
+    #[serde(default = "default_true")]
+    pub mark_read_on_resume: bool,
+    /// 1時間ごと・1日ごとのまとめ通知
+    #[serde(default)]
+    pub digest: DigestSettings,
+    /// イベントを任意の URL に送る Webhook
+    #[serde(default)]
+    pub webhook: WebhookSettings,
+    /// 毎日のコストレポート
+    #[serde(default)]
+    pub cost_report: CostReportSettings,
+    /// 変更行数の節目の通知
+    #[serde(default)]
+    pub line_milestones: LineMilestoneSettings,
+    /// 作業中から入力待ちに変わったときの通知
+    #[serde(default)]
+    pub state_transitions: StateTransitionSettings,
+    /// 入力待ちが続いているセッションのリマインダー
+    #[serde(default)]
+    pub waiting_reminder: WaitingReminderSettings,
+    /// すべてのセッションが待機中・終了になったときの通知
+    #[serde(default)]
+    pub all_clear: AllClearSettings,
+    /// 複数の作業ディレクトリを1つのプロジェクトにまとめる別名
+    #[serde(default)]
+    pub projects: ProjectAliasSettings,
+    /// イベントを無視するパス
+    #[serde(default)]
+    pub ignore_list: IgnoreListSettings,
+    /// 短時間に大量のイベントを送る送信元のミュート
+    #[serde(default)]
+    pub rate_limit: RateLimitSettings,
+    /// 起動直後に届いた古いイベントをまとめて知らせる
+    #[serde(default)]
+    pub catch_up: CatchUpSettings,
+    /// 設定した時間より古いイベントの扱い
+    #[serde(default)]
+    pub stale_events: StaleEventSettings,
+    /// 通知・履歴・Webhook に渡す前にマスクする機密情報
+    #[serde(default)]
+    pub redaction: RedactionSettings,
+    /// プロジェクトごとのルール
+    #[serde(default)]
+    pub project_rules: ProjectRuleSettings,
+    /// 追加で購読するトピック（CI・cron ジョブなどからの通知）
+    #[serde(default)]
+    pub custom_topics: CustomTopicSettings,
+    /// イベントごとに実行するスクリプト
+    #[serde(default)]
+    pub script_hook: ScriptHookSettings,
+    /// ルーティングスクリプト
+    #[serde(default)]
+    pub routing: RoutingSettings,
+    /// タスク完了の読み上げ
+    #[serde(default)]
+    pub speech: SpeechSettings,
+}
+
+/// イベント種別ごとの通知音の割り当て
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventSounds {
+    /// タスク完了
+    pub stop: SoundId,
+    /// 承認依頼
+    pub permission_request: SoundId,
+    /// 入力要求
+    pub notification: SoundId,
+}
+
+impl EventSounds {
+    /// イベント種別に割り当てられた通知音を取得
+    pub fn for_event(&self, event_type: &NotificationEventType) -> SoundId {
+        match event_type {
+            NotificationEventType::Stop => self.stop,
+            NotificationEventType::PermissionRequest => self.permission_request,
+            NotificationEventType::Notification => self.notification,
+        }
+    }
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_max_concurrent_sounds() -> u32 {
+    3
+}
+
+fn default_duplicate_sound_window_ms() -> u64 {
+    2000
+}
+
+fn default_tray_flash_interval_ms() -> u64 {
+    500
+}
+
+fn default_taskbar_flash_count() -> u32 {
+    3
+}
+
+fn default_last_message_length() -> u32 {
+    200
+}
+
+fn default_toast_body_max_length() -> usize {
+    120
+}
+
+impl NotificationSettings {
+    /// 通知音の再生ポリシーを取得
+    pub fn playback_policy(&self) -> PlaybackPolicy {
+        PlaybackPolicy {
+            mode: self.sound_overlap,
+            max_concurrent: self.max_concurrent_sounds as usize,
+            duplicate_window: Duration::from_millis(self.duplicate_sound_window_ms),
+            max_duration: (self.max_sound_duration_ms > 0).then(|| Duration::from_millis(self.max_sound_duration_ms)),
+            fade: Duration::from_millis(self.sound_fade_ms),
+        }
+    }
+
+    /// 作業ディレクトリのプロジェクトに適用するルール（別名でまとめたプロジェクト名でも探す）
+    pub fn project_rule(&self, cwd: &str) -> Option<&ProjectRule> {
+        self.project_rules
+            .find(cwd)
+            .or_else(|| self.projects.alias(cwd).and_then(|name| self.project_rules.find(name)))
+    }
+
+    /// 作業ディレクトリのプロジェクトの表示色（#rrggbb）
+    pub fn project_color(&self, cwd: &str) -> String {
+        self.project_rules.color(cwd, &self.projects.project_name(cwd))
+    }
+
+    /// トレイアイコン点滅のタイミングを取得
+    pub fn flash_timing(&self) -> FlashTiming {
+        FlashTiming {
+            interval: Duration::from_millis(self.tray_flash_interval_ms),
+            max_duration: (self.tray_flash_max_duration_secs > 0)
+                .then(|| Duration::from_secs(self.tray_flash_max_duration_secs)),
+        }
+    }
+}
+
 impl Default for NotificationSettings {
     fn default() -> Self {
         Self {
@@ -37,20 +325,144 @@ impl Default for NotificationSettings {
             toast_notification_enabled: true,
             tray_flash_enabled: true,
             sound_volume: 0.8,
+            language: LanguageSetting::Auto,
+            event_sounds: EventSounds::default(),
+            sound_overlap: SoundOverlapMode::Overlap,
+            max_concurrent_sounds: default_max_concurrent_sounds(),
+            duplicate_sound_window_ms: default_duplicate_sound_window_ms(),
+            max_sound_duration_ms: 0,
+            sound_fade_ms: 0,
+            tray_icon_theme: TrayIconTheme::Color,
+            window_placement: WindowPlacement::LastPosition,
+            window_mode: WindowModeSettings::default(),
+            tray_attention_mode: TrayAttentionMode::default(),
+            tray_flash_interval_ms: default_tray_flash_interval_ms(),
+            tray_flash_max_duration_secs: 0,
+            taskbar_flash_count: default_taskbar_flash_count(),
+            broker_acl: BrokerAclSettings::default(),
+            broker_listeners: BrokerListenerSettings::default(),
+            broker_discovery: false,
+            broker_port_fallback: true,
+            external_broker: ExternalBrokerSettings::default(),
+            payload_signing: PayloadSigningSettings::default(),
+            payload_encryption: PayloadEncryptionSettings::default(),
+            rest_api: RestApiSettings::default(),
+            permission_response: PermissionResponseSettings::default(),
+            session_control: SessionControlSettings::default(),
+            last_message_length: default_last_message_length(),
+            toast_body_max_length: default_toast_body_max_length(),
+            host_rules: HostRuleSettings::default(),
+            tooltip_template: String::new(),
+            fullscreen: FullscreenSettings::default(),
+            mute_during_calls: false,
+            calendar: CalendarSettings::default(),
+            heartbeat: HeartbeatSettings::default(),
+            host_update: HostUpdateSettings::default(),
+            update_check: UpdateCheckSettings::default(),
+            history_retention: HistoryRetention::default(),
+            history_metadata_only: false,
+            mark_read_on_resume: true,
+            digest: DigestSettings::default(),
+            webhook: WebhookSettings::default(),
+            cost_report: CostReportSettings::default(),
+            line_milestones: LineMilestoneSettings::default(),
+            state_transitions: StateTransitionSettings::default(),
+            waiting_reminder: WaitingReminderSettings::default(),
+            all_clear: AllClearSettings::default(),
+            projects: ProjectAliasSettings::default(),
+            ignore_list: IgnoreListSettings::default(),
+            rate_limit: RateLimitSettings::default(),
+            catch_up: CatchUpSettings::default(),
+            stale_events: StaleEventSettings::default(),
+            redaction: RedactionSettings::default(),
+            project_rules: ProjectRuleSettings::default(),
+            custom_topics: CustomTopicSettings::default(),
+            script_hook: ScriptHookSettings::default(),
+            routing: RoutingSettings::default(),
+            speech: SpeechSettings::default(),
         }
     }
 }
 
 const SETTINGS_FILE: &str = "settings.json";
 const SETTINGS_KEY: &str = "notification";
+const PROFILES_KEY: &str = "profiles";
+
+/// 名前付きの設定プロファイル（「仕事」「自宅」「プレゼン」など）
+///
+/// 現在の設定は有効なプロファイルの内容で、設定を保存すると有効なプロファイルにも反映する。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsProfiles {
+    /// 有効なプロファイルの名前
+    pub active: Option<String>,
+    /// プロファイル名ごとの設定
+    pub profiles: BTreeMap<String, NotificationSettings>,
+    /// プロファイルを順に切り替えるホットキー（例: `CommandOrControl+Shift+P`、空の場合は無効）
+    pub hotkey: String,
+    /// プロファイルを自動で切り替えるルール（上にあるルールを優先）
+    pub rules: Vec<ProfileRule>,
+}
+
+impl SettingsProfiles {
+    /// プロファイル名の一覧（名前順）
+    pub fn names(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
+    /// 設定をプロファイルとして保存し、有効にする
+    pub fn save_profile(&mut self, name: &str, settings: NotificationSettings) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name is empty".to_string());
+        }
+        self.profiles.insert(name.to_string(), settings);
+        self.active = Some(name.to_string());
+        Ok(())
+    }
+
+    /// プロファイルを削除する（有効なプロファイルの場合も現在の設定はそのまま使う）
+    pub fn remove(&mut self, name: &str) -> bool {
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+        self.profiles.remove(name).is_some()
+    }
+
+    /// プロファイルを有効にし、その設定を返す
+    pub fn activate(&mut self, name: &str) -> Option<NotificationSettings> {
+        let settings = self.profiles.get(name)?.clone();
+        self.active = Some(name.to_string());
+        Some(settings)
+    }
+
+    /// 次に切り替えるプロファイル（ホットキーで名前順に切り替える）
+    pub fn next(&self) -> Option<String> {
+        let names = self.names();
+        let index = self
+            .active
+            .as_ref()
+            .and_then(|active| names.iter().position(|name| name == active))
+            .map_or(0, |index| (index + 1) % names.len());
+        names.get(index).cloned()
+    }
+
+    /// 保存した設定を有効なプロファイルに反映する
+    pub fn update_active(&mut self, settings: &NotificationSettings) {
+        if let Some(profile) = self.active.as_ref().and_then(|name| self.profiles.get_mut(name)) {
+            *profile = settings.clone();
+        }
+    }
+}
 
 /// 設定を読み込む
 pub fn load_settings(app: &tauri::AppHandle) -> NotificationSettings {
     match app.store(SETTINGS_FILE) {
         Ok(store) => {
             match store.get(SETTINGS_KEY) {
-                Some(value) => {
-                    match serde_json::from_value(value.clone()) {
+                Some(mut value) => {
+                    credentials::unseal(&mut value, credentials::SETTINGS_SCOPE, credentials::keychain());
+                    match serde_json::from_value(value) {
                         Ok(settings) => {
                             info!("Settings loaded successfully");
                             settings
@@ -77,13 +489,75 @@ pub fn load_settings(app: &tauri::AppHandle) -> NotificationSettings {
 /// 設定を保存する
 pub fn save_settings(app: &tauri::AppHandle, settings: &NotificationSettings) -> Result<(), String> {
     let store = app.store(SETTINGS_FILE).map_err(|e| e.to_string())?;
-    let value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    let mut value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    // 認証情報はキーチェーンに保存し、設定ファイルには書かない
+    credentials::seal(&mut value, credentials::SETTINGS_SCOPE, credentials::keychain());
     store.set(SETTINGS_KEY, value);
     store.save().map_err(|e| e.to_string())?;
     info!("Settings saved successfully");
     Ok(())
 }
 
+/// 設定プロファイルを読み込む
+pub fn load_profiles(app: &tauri::AppHandle) -> SettingsProfiles {
+    let store = match app.store(SETTINGS_FILE) {
+        Ok(store) => store,
+        Err(e) => {
+            error!("Failed to open settings store: {}", e);
+            return SettingsProfiles::default();
+        }
+    };
+    let profiles = store.get(PROFILES_KEY).map(|mut value| {
+        credentials::unseal_profiles(&mut value, credentials::keychain());
+        serde_json::from_value(value)
+    });
+    match profiles {
+        Some(Ok(profiles)) => profiles,
+        Some(Err(e)) => {
+            error!("Failed to deserialize settings profiles: {}", e);
+            SettingsProfiles::default()
+        }
+        None => SettingsProfiles::default(),
+    }
+}
+
+/// 設定プロファイルを保存する
+pub fn save_profiles(app: &tauri::AppHandle, profiles: &SettingsProfiles) -> Result<(), String> {
+    let store = app.store(SETTINGS_FILE).map_err(|e| e.to_string())?;
+    let mut value = serde_json::to_value(profiles).map_err(|e| e.to_string())?;
+    credentials::seal_profiles(&mut value, store.get(PROFILES_KEY).as_ref(), credentials::keychain());
+    store.set(PROFILES_KEY, value);
+    store.save().map_err(|e| e.to_string())
+}
+
+/// 以前のバージョンが設定ファイルに平文で保存した認証情報をキーチェーンに移す
+pub fn migrate_credentials(app: &tauri::AppHandle) {
+    let store = match app.store(SETTINGS_FILE) {
+        Ok(store) => store,
+        Err(e) => {
+            error!("Failed to open settings store: {}", e);
+            return;
+        }
+    };
+    let mut migrated = false;
+    if let Some(mut value) = store.get(SETTINGS_KEY).filter(credentials::has_plaintext) {
+        credentials::seal(&mut value, credentials::SETTINGS_SCOPE, credentials::keychain());
+        migrated |= !credentials::has_plaintext(&value);
+        store.set(SETTINGS_KEY, value);
+    }
+    if let Some(mut value) = store.get(PROFILES_KEY).filter(credentials::profiles_have_plaintext) {
+        credentials::seal_profiles(&mut value, None, credentials::keychain());
+        migrated |= !credentials::profiles_have_plaintext(&value);
+        store.set(PROFILES_KEY, value);
+    }
+    if migrated {
+        match store.save() {
+            Ok(()) => info!("Moved credentials from the settings file to the keychain"),
+            Err(e) => warn!("Failed to save settings after moving credentials: {}", e),
+        }
+    }
+}
+
 /// Tauriコマンド: 設定を取得
 #[tauri::command]
 pub fn get_settings(app: tauri::AppHandle) -> NotificationSettings {
@@ -96,6 +570,13 @@ pub fn get_settings(app: tauri::AppHandle) -> NotificationSettings {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::digest::DigestInterval;
+    use crate::host_rules::HostRule;
+    use crate::custom_topics::CustomTopic;
+    use crate::project_aliases::ProjectAlias;
+    use crate::routing::WorkingHours;
+    use crate::stale_events::StaleEventAction;
+    use crate::webhook::WebhookHeader;
 
     #[test]
     fn test_default_settings() {
@@ -106,6 +587,7 @@ mod tests {
         assert!(settings.toast_notification_enabled);
         assert!(settings.tray_flash_enabled);
         assert!((settings.sound_volume - 0.8).abs() < 0.01);
+        assert_eq!(settings.language, LanguageSetting::Auto);
     }
 
     #[test]
@@ -117,6 +599,195 @@ mod tests {
             toast_notification_enabled: true,
             tray_flash_enabled: false,
             sound_volume: 0.5,
+            language: LanguageSetting::En,
+            event_sounds: EventSounds {
+                stop: SoundId::Chime,
+                permission_request: SoundId::Alert,
+                notification: SoundId::Click,
+            },
+            sound_overlap: SoundOverlapMode::Queue,
+            max_concurrent_sounds: 5,
+            duplicate_sound_window_ms: 1000,
+            max_sound_duration_ms: 3000,
+            sound_fade_ms: 30,
+            tray_icon_theme: TrayIconTheme::Auto,
+            window_placement: WindowPlacement::ActiveMonitor,
+            window_mode: WindowModeSettings {
+                compact: true,
+                opacity: 80,
+                always_on_top: true,
+            },
+            tray_attention_mode: TrayAttentionMode::Badge,
+            tray_flash_interval_ms: 800,
+            tray_flash_max_duration_secs: 60,
+            taskbar_flash_count: 5,
+            broker_acl: BrokerAclSettings {
+                enabled: true,
+                client_id_prefixes: vec!["claude-code-".to_string()],
+                usernames: vec!["sensor".to_string()],
+            },
+            broker_listeners: BrokerListenerSettings {
+                local_enabled: true,
+                local_port: 1884,
+            },
+            broker_discovery: true,
+            broker_port_fallback: false,
+            external_broker: ExternalBrokerSettings {
+                enabled: true,
+                host: "mqtt.example.lan".to_string(),
+                port: 1883,
+                username: "notify".to_string(),
+                password: "secret".to_string(),
+                client_id: "notify-desktop".to_string(),
+            },
+            payload_signing: PayloadSigningSettings {
+                enabled: true,
+                secret: "secret".to_string(),
+                window_secs: 600,
+            },
+            payload_encryption: PayloadEncryptionSettings {
+                enabled: true,
+                key: "a2V5".to_string(),
+            },
+            rest_api: RestApiSettings {
+                enabled: true,
+                port: 18000,
+                token: "token".to_string(),
+                allow_lan: true,
+            },
+            permission_response: PermissionResponseSettings {
+                enabled: true,
+                timeout_secs: 30,
+            },
+            session_control: SessionControlSettings { enabled: true },
+            last_message_length: 80,
+            toast_body_max_length: 60,
+            host_rules: HostRuleSettings {
+                rules: vec![HostRule {
+                    host: "build-server".to_string(),
+                    muted: true,
+                    color: Some("#ff8800".to_string()),
+                }],
+            },
+            tooltip_template: "Unread: {unread}".to_string(),
+            fullscreen: FullscreenSettings {
+                enabled: false,
+                allow_critical: false,
+            },
+            mute_during_calls: true,
+            calendar: CalendarSettings {
+                enabled: true,
+                ics_url: "webcal://example.com/calendar.ics".to_string(),
+            },
+            heartbeat: HeartbeatSettings {
+                enabled: true,
+                interval_secs: 30,
+                silence_minutes: 5,
+            },
+            host_update: HostUpdateSettings {
+                watch_ip: false,
+                ssh_targets: vec!["dev@build-server".to_string()],
+                auto_push: true,
+            },
+            update_check: UpdateCheckSettings { enabled: true },
+            history_retention: HistoryRetention {
+                max_entries: 500,
+                max_age_days: 30,
+                stop_days: Some(7),
+                ..HistoryRetention::default()
+            },
+            history_metadata_only: true,
+            mark_read_on_resume: false,
+            digest: DigestSettings {
+                enabled: true,
+                interval: DigestInterval::Hourly,
+                slack_webhook_url: "https://hooks.slack.com/services/T000/B000/XXXX".to_string(),
+                ..DigestSettings::default()
+            },
+            webhook: WebhookSettings {
+                enabled: true,
+                url: "https://maker.ifttt.com/trigger/claude/with/key/XXXX".to_string(),
+                body_template: r#"{"value1":"{project}","value2":"{message}"}"#.to_string(),
+                headers: vec![WebhookHeader {
+                    name: "X-Api-Key".to_string(),
+                    value: "secret".to_string(),
+                }],
+            },
+            cost_report: CostReportSettings {
+                enabled: true,
+                time: "21:30".to_string(),
+            },
+            line_milestones: LineMilestoneSettings {
+                enabled: true,
+                step: 1000,
+            },
+            state_transitions: StateTransitionSettings {
+                enabled: true,
+                debounce_secs: 10,
+            },
+            waiting_reminder: WaitingReminderSettings {
+                enabled: true,
+                minutes: 20,
+            },
+            all_clear: AllClearSettings { enabled: true },
+            projects: ProjectAliasSettings {
+                aliases: vec![ProjectAlias {
+                    name: "docs".to_string(),
+                    paths: vec!["docs-*".to_string()],
+                }],
+            },
+            ignore_list: IgnoreListSettings {
+                paths: vec!["/tmp".to_string()],
+            },
+            rate_limit: RateLimitSettings {
+                enabled: true,
+                max_events: 50,
+                window_secs: 5,
+                mute_secs: 120,
+            },
+            catch_up: CatchUpSettings {
+                enabled: false,
+                startup_secs: 30,
+                max_age_minutes: 10,
+            },
+            stale_events: StaleEventSettings {
+                action: StaleEventAction::Discard,
+                max_age_minutes: 60,
+                clock_skew_secs: 30,
+            },
+            redaction: RedactionSettings {
+                enabled: true,
+                patterns: vec![r"corp-[0-9a-f]{8}".to_string()],
+            },
+            project_rules: ProjectRuleSettings {
+                rules: vec![ProjectRule {
+                    project: "docs".to_string(),
+                    line_milestone: Some(0),
+                    waiting_reminder: Some(60),
+                    color: Some("#3399ff".to_string()),
+                }],
+                color_toasts: true,
+                color_tray_dot: true,
+            },
+            custom_topics: CustomTopicSettings {
+                topics: vec![CustomTopic {
+                    filter: "build/#".to_string(),
+                    template: "🛠 {payload}".to_string(),
+                }],
+            },
+            script_hook: ScriptHookSettings {
+                enabled: true,
+                command: "python C:\\scripts\\on_event.py".to_string(),
+                timeout_secs: 30,
+            },
+            routing: RoutingSettings {
+                enabled: true,
+                working_hours: WorkingHours {
+                    enabled: true,
+                    ..WorkingHours::default()
+                },
+            },
+            speech: SpeechSettings { enabled: true },
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -128,5 +799,177 @@ mod tests {
         assert!(deserialized.toast_notification_enabled);
         assert!(!deserialized.tray_flash_enabled);
         assert!((deserialized.sound_volume - 0.5).abs() < 0.01);
+        assert_eq!(deserialized.language, LanguageSetting::En);
+        assert_eq!(deserialized.event_sounds.stop, SoundId::Chime);
+        assert_eq!(deserialized.event_sounds.permission_request, SoundId::Alert);
+        assert_eq!(deserialized.event_sounds.notification, SoundId::Click);
+        assert_eq!(deserialized.sound_overlap, SoundOverlapMode::Queue);
+        assert_eq!(deserialized.max_concurrent_sounds, 5);
+        assert_eq!(deserialized.duplicate_sound_window_ms, 1000);
+        assert_eq!(deserialized.playback_policy().max_duration, Some(Duration::from_secs(3)));
+        assert_eq!(deserialized.playback_policy().fade, Duration::from_millis(30));
+        assert_eq!(deserialized.tray_icon_theme, TrayIconTheme::Auto);
+        assert_eq!(deserialized.window_placement, WindowPlacement::ActiveMonitor);
+        assert!(deserialized.window_mode.compact);
+        assert_eq!(deserialized.window_mode.opacity, 80);
+        assert_eq!(deserialized.tray_attention_mode, TrayAttentionMode::Badge);
+        assert_eq!(deserialized.taskbar_flash_count, 5);
+        assert!(deserialized.broker_acl.enabled);
+        assert_eq!(deserialized.broker_acl.client_id_prefixes, vec!["claude-code-"]);
+        assert_eq!(deserialized.broker_acl.usernames, vec!["sensor"]);
+        assert_eq!(deserialized.broker_listeners.active_local_port(1883), Some(1884));
+        assert!(deserialized.broker_discovery);
+        assert!(!deserialized.broker_port_fallback);
+        assert_eq!(deserialized.external_broker.active_endpoint().map(|e| e.host), Some("mqtt.example.lan".to_string()));
+        assert_eq!(deserialized.payload_signing.active_secret(), Some("secret"));
+        assert_eq!(deserialized.payload_encryption.active_key(), Some("a2V5"));
+        assert_eq!(deserialized.rest_api.port, 18000);
+        assert!(deserialized.rest_api.allow_lan);
+        assert_eq!(deserialized.permission_response.active_timeout(), Some(30));
+        assert!(deserialized.session_control.enabled);
+        assert_eq!(deserialized.last_message_length, 80);
+        assert_eq!(deserialized.toast_body_max_length, 60);
+        assert!(deserialized.host_rules.is_muted(Some("build-server")));
+        assert_eq!(deserialized.tooltip_template, "Unread: {unread}");
+        assert_eq!(
+            deserialized.fullscreen,
+            FullscreenSettings {
+                enabled: false,
+                allow_critical: false,
+            }
+        );
+        assert!(deserialized.mute_during_calls);
+        assert_eq!(
+            deserialized.calendar.active_url().as_deref(),
+            Some("https://example.com/calendar.ics")
+        );
+        assert_eq!(deserialized.heartbeat.active_interval(), Some(30));
+        assert_eq!(deserialized.heartbeat.silence_minutes, 5);
+        assert!(!deserialized.host_update.watch_ip);
+        assert_eq!(deserialized.host_update.ssh_targets, vec!["dev@build-server"]);
+        assert!(deserialized.host_update.auto_push);
+        assert!(deserialized.update_check.enabled);
+        assert_eq!(deserialized.history_retention.max_entries, 500);
+        assert_eq!(deserialized.history_retention.stop_days, Some(7));
+        assert_eq!(deserialized.history_retention.notification_days, None);
+        assert!(deserialized.history_metadata_only);
+        assert!(!deserialized.mark_read_on_resume);
+        assert_eq!(deserialized.digest.interval, DigestInterval::Hourly);
+        assert_eq!(
+            deserialized.digest.active_webhook(),
+            Some("https://hooks.slack.com/services/T000/B000/XXXX")
+        );
+        assert_eq!(
+            deserialized.webhook.active_url(),
+            Some("https://maker.ifttt.com/trigger/claude/with/key/XXXX")
+        );
+        assert_eq!(deserialized.webhook.headers[0].name, "X-Api-Key");
+        assert_eq!(deserialized.line_milestones.step_for(None), Some(1000));
+        assert!(deserialized.state_transitions.enabled);
+        assert_eq!(deserialized.state_transitions.debounce_secs, 10);
+        let docs = deserialized.project_rules.find("/work/docs");
+        assert_eq!(deserialized.line_milestones.step_for(docs), None);
+        assert_eq!(deserialized.waiting_reminder.minutes_for(None), Some(20));
+        assert!(deserialized.all_clear.enabled);
+        assert_eq!(deserialized.cost_report.time, "21:30");
+        assert_eq!(deserialized.waiting_reminder.minutes_for(docs), Some(60));
+        assert_eq!(deserialized.project_color("/work/docs"), "#3399ff");
+        let worktree = deserialized.project_rule("/work/docs-review");
+        assert_eq!(worktree.and_then(|rule| rule.waiting_reminder), Some(60));
+        assert_eq!(deserialized.project_color("/work/docs-review"), "#3399ff");
+        assert!(deserialized.ignore_list.is_ignored("/tmp/scratch"));
+        assert_eq!(deserialized.rate_limit.max_events, 50);
+        assert_eq!(deserialized.rate_limit.mute_secs, 120);
+        assert!(!deserialized.catch_up.enabled);
+        assert_eq!(deserialized.catch_up.max_age_minutes, 10);
+        assert_eq!(deserialized.stale_events.action, StaleEventAction::Discard);
+        assert_eq!(deserialized.stale_events.max_age_minutes, 60);
+        assert_eq!(deserialized.redaction.redact("id corp-deadbeef"), "id [REDACTED]");
+        assert!(deserialized.project_rules.color_toasts && deserialized.project_rules.color_tray_dot);
+        assert_eq!(deserialized.custom_topics.filters(), vec!["build/#".to_string()]);
+        assert_eq!(deserialized.script_hook.active_command(), Some("python C:\\scripts\\on_event.py"));
+        assert_eq!(deserialized.script_hook.timeout(), Duration::from_secs(30));
+        assert!(deserialized.routing.enabled);
+        assert!(deserialized.routing.working_hours.enabled);
+        assert_eq!(deserialized.routing.working_hours.start, "09:00");
+        assert!(deserialized.speech.enabled);
+        assert_eq!(
+            deserialized.flash_timing(),
+            FlashTiming {
+                interval: Duration::from_millis(800),
+                max_duration: Some(Duration::from_secs(60)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_event_sounds_for_event() {
+        let sounds = EventSounds {
+            stop: SoundId::Chime,
+            permission_request: SoundId::Alert,
+            notification: SoundId::Default,
+        };
+        assert_eq!(sounds.for_event(&NotificationEventType::Stop), SoundId::Chime);
+        assert_eq!(sounds.for_event(&NotificationEventType::PermissionRequest), SoundId::Alert);
+        assert_eq!(sounds.for_event(&NotificationEventType::Notification), SoundId::Default);
+    }
+
+    #[test]
+    fn test_settings_without_language_defaults_to_auto() {
+        // 言語設定追加前に保存された設定ファイルとの互換性
+        let json = r#"{
+            "sound_enabled": true,
+            "taskbar_flash_enabled": true,
+            "taskbar_badge_enabled": true,
+            "toast_notification_enabled": true,
+            "sound_volume": 0.8
+        }"#;
+        let settings: NotificationSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.language, LanguageSetting::Auto);
+        assert_eq!(settings.event_sounds, EventSounds::default());
+        assert_eq!(settings.playback_policy(), PlaybackPolicy::default());
+        assert_eq!(settings.flash_timing(), FlashTiming::default());
+        assert_eq!(settings.taskbar_flash_count, 3);
+        assert!(settings.rate_limit.enabled);
+        assert!(settings.catch_up.enabled);
+        assert_eq!(settings.stale_events.action, StaleEventAction::Demote);
+        assert!(settings.redaction.enabled);
+        assert!(!settings.history_metadata_only);
+        assert_eq!(settings.last_message_length, 200);
+        assert_eq!(settings.toast_body_max_length, 120);
+    }
+
+    #[test]
+    fn test_profiles_switch_and_update() {
+        let mut profiles = SettingsProfiles::default();
+        assert_eq!(profiles.next(), None);
+        assert!(profiles.save_profile("  ", NotificationSettings::default()).is_err());
+
+        let quiet = NotificationSettings {
+            sound_enabled: false,
+            ..NotificationSettings::default()
+        };
+        profiles.save_profile("work", NotificationSettings::default()).unwrap();
+        profiles.save_profile("presentation", quiet).unwrap();
+        assert_eq!(profiles.active.as_deref(), Some("presentation"));
+        assert_eq!(profiles.names(), vec!["presentation", "work"]);
+
+        // 名前順に切り替え、最後のプロファイルの次は最初に戻る
+        assert_eq!(profiles.next().as_deref(), Some("work"));
+        let settings = profiles.activate("work").unwrap();
+        assert!(settings.sound_enabled);
+        assert_eq!(profiles.next().as_deref(), Some("presentation"));
+        assert!(profiles.activate("home").is_none());
+
+        // 保存した設定は有効なプロファイルにのみ反映する
+        let mut edited = settings.clone();
+        edited.sound_volume = 0.3;
+        profiles.update_active(&edited);
+        assert!((profiles.profiles["work"].sound_volume - 0.3).abs() < 0.01);
+        assert!(!profiles.profiles["presentation"].sound_enabled);
+
+        assert!(profiles.remove("work"));
+        assert_eq!(profiles.active, None);
+        assert_eq!(profiles.next().as_deref(), Some("presentation"));
     }
 }
@@ -0,0 +1,222 @@
+//! イベントペイロードの署名検証モジュール
+//!
+//! ブローカーを localhost 以外に公開する環境向けに、フックスクリプト /
+//! mqtt-publish が共有シークレットで付けた HMAC-SHA256 署名を検証する。
+//! 署名付きのメッセージは次のエンベロープで送られる。
+//!
+//! ```json
+//! {"payload": "<元の JSON 文字列>", "signature": "<HMAC-SHA256 の16進文字列>"}
+//! ```
+//!
+//! 署名したメッセージを LAN 上で記録して送り直す（リプレイ）ことを防ぐため、署名の対象に含まれる
+//! ペイロードの `timestamp` が有効期間から外れたメッセージ・`timestamp` のないメッセージも破棄する（[`check_freshness`]）。
+
+use crate::timestamps;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SigningError {
+    #[error("payload is not signed")]
+    Unsigned,
+    #[error("signature is not valid hex")]
+    MalformedSignature,
+    #[error("signature does not match")]
+    InvalidSignature,
+    #[error("signed payload has no timestamp")]
+    MissingTimestamp,
+    #[error("signed payload is outside the accepted time window")]
+    Expired,
+}
+
+/// ペイロード署名の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayloadSigningSettings {
+    /// 署名のないメッセージ・署名が一致しないメッセージを破棄する
+    pub enabled: bool,
+    /// 共有シークレット（エクスポートしたスクリプトに埋め込まれる）
+    pub secret: String,
+    /// 署名したメッセージの有効期間（秒、送信元との時計のずれも同じ幅まで許す）
+    pub window_secs: u32,
+}
+
+impl Default for PayloadSigningSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            secret: String::new(),
+            window_secs: 300,
+        }
+    }
+}
+
+impl PayloadSigningSettings {
+    /// 署名したメッセージの有効期間
+    pub fn window(&self) -> Duration {
+        Duration::seconds(i64::from(self.window_secs))
+    }
+
+    /// 検証に使うシークレット（無効または未設定の場合は None）
+    pub fn active_secret(&self) -> Option<&str> {
+        let secret = self.secret.trim();
+        (self.enabled && !secret.is_empty()).then_some(secret)
+    }
+}
+
+/// 署名付きメッセージのエンベロープ
+#[derive(Debug, Deserialize)]
+struct SignedEnvelope {
+    payload: String,
+    signature: String,
+}
+
+/// 受信したペイロードを検証し、元の JSON 文字列を取り出す
+///
+/// シークレットが未設定の場合は検証せず、エンベロープであれば中身を、
+/// そうでなければ受信したペイロードをそのまま返す。
+pub fn verify_payload(raw: &str, secret: Option<&str>) -> Result<String, SigningError> {
+    let envelope = serde_json::from_str::<SignedEnvelope>(raw).ok();
+
+    let Some(secret) = secret else {
+        return Ok(envelope.map(|e| e.payload).unwrap_or_else(|| raw.to_string()));
+    };
+
+    let envelope = envelope.ok_or(SigningError::Unsigned)?;
+    let signature =
+        hex::decode(envelope.signature.trim()).map_err(|_| SigningError::MalformedSignature)?;
+
+    let mut mac = new_mac(secret);
+    mac.update(envelope.payload.as_bytes());
+    mac.verify_slice(&signature)
+        .map_err(|_| SigningError::InvalidSignature)?;
+
+    Ok(envelope.payload)
+}
+
+/// 署名したペイロードの `timestamp` が有効期間内か確認する
+///
+/// 復号した後のペイロードで確認する（mqtt-publish は暗号化してから署名するため）。
+pub fn check_freshness(payload: &str, window: Duration, now: DateTime<Utc>) -> Result<(), SigningError> {
+    let timestamp = timestamps::payload_timestamp(payload).ok_or(SigningError::MissingTimestamp)?;
+    if (now - timestamp).abs() > window {
+        return Err(SigningError::Expired);
+    }
+    Ok(())
+}
+
+/// 共有シークレットを生成する（32バイトの乱数を16進文字列で返す）
+pub fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::random();
+    hex::encode(bytes)
+}
+
+fn new_mac(secret: &str) -> HmacSha256 {
+    // HMAC は任意長の鍵を受け付けるため失敗しない
+    HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+/// ペイロードに署名し、署名付きエンベロープの JSON 文字列を返す
+///
+/// アプリから送信するメッセージ（承認依頼への応答など）に使う。
+pub fn sign_payload(payload: &str, secret: &str) -> String {
+    let mut mac = new_mac(secret);
+    mac.update(payload.as_bytes());
+    serde_json::json!({
+        "payload": payload,
+        "signature": hex::encode(mac.finalize().into_bytes()),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &str = r#"{"event":"stop","cwd":"/work"}"#;
+
+    #[test]
+    fn test_valid_signature_is_accepted() {
+        let signed = sign_payload(PAYLOAD, "secret");
+        assert_eq!(verify_payload(&signed, Some("secret")).unwrap(), PAYLOAD);
+    }
+
+    #[test]
+    fn test_wrong_secret_is_rejected() {
+        let signed = sign_payload(PAYLOAD, "secret");
+        assert_eq!(
+            verify_payload(&signed, Some("other")),
+            Err(SigningError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_tampered_payload_is_rejected() {
+        let signed = sign_payload(PAYLOAD, "secret").replace("/work", "/evil");
+        assert_eq!(
+            verify_payload(&signed, Some("secret")),
+            Err(SigningError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_unsigned_payload_is_rejected_when_required() {
+        assert_eq!(verify_payload(PAYLOAD, Some("secret")), Err(SigningError::Unsigned));
+    }
+
+    #[test]
+    fn test_malformed_signature_is_rejected() {
+        let raw = r#"{"payload":"{}","signature":"not-hex"}"#;
+        assert_eq!(
+            verify_payload(raw, Some("secret")),
+            Err(SigningError::MalformedSignature)
+        );
+    }
+
+    #[test]
+    fn test_without_secret_passes_through() {
+        assert_eq!(verify_payload(PAYLOAD, None).unwrap(), PAYLOAD);
+        let signed = sign_payload(PAYLOAD, "secret");
+        assert_eq!(verify_payload(&signed, None).unwrap(), PAYLOAD);
+    }
+
+    #[test]
+    fn test_freshness() {
+        let now = DateTime::parse_from_rfc3339("2026-10-17T10:00:00Z").unwrap().with_timezone(&Utc);
+        let window = PayloadSigningSettings::default().window();
+        let payload = |timestamp: &str| format!(r#"{{"event":"stop","timestamp":"{}"}}"#, timestamp);
+
+        assert_eq!(check_freshness(&payload("2026-10-17T09:56:00Z"), window, now), Ok(()));
+        // 送信元の時計が進んでいる場合も同じ幅まで許す
+        assert_eq!(check_freshness(&payload("2026-10-17T10:04:00Z"), window, now), Ok(()));
+        assert_eq!(check_freshness(&payload("2026-10-17T09:50:00Z"), window, now), Err(SigningError::Expired));
+        assert_eq!(check_freshness(&payload("2026-10-17T10:10:00Z"), window, now), Err(SigningError::Expired));
+        assert_eq!(check_freshness(PAYLOAD, window, now), Err(SigningError::MissingTimestamp));
+    }
+
+    #[test]
+    fn test_active_secret() {
+        let mut settings = PayloadSigningSettings {
+            enabled: true,
+            secret: "  ".to_string(),
+            ..PayloadSigningSettings::default()
+        };
+        assert_eq!(settings.active_secret(), None);
+        settings.secret = "abc".to_string();
+        assert_eq!(settings.active_secret(), Some("abc"));
+        settings.enabled = false;
+        assert_eq!(settings.active_secret(), None);
+    }
+
+    #[test]
+    fn test_generate_secret() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 64);
+        assert_ne!(secret, generate_secret());
+    }
+}
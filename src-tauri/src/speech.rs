@@ -0,0 +1,163 @@
+//! 読み上げ（TTS）モジュール
+//!
+//! 設定で有効にした場合、タスク完了を OS の音声合成でプロジェクト名とともに読み上げる
+//! （Windows は System.Speech、macOS は `say`、Linux は `spd-say`）。
+//! 短い間に複数のセッションが完了した場合は発話が重ならないよう、
+//! 最後の完了から `QUIET_PERIOD` 待ってから「3 つのプロジェクトが完了しました: api、web、docs」のように1つの文にまとめて読み上げる。
+//! 読み上げ中に完了したものは、読み上げが終わった後に次の文にまとめる。
+
+use crate::i18n::{self, Strings};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// 最後の完了からこの時間届かなければ、まとめて読み上げる
+pub const QUIET_PERIOD: Duration = Duration::from_secs(3);
+
+/// Windows で読み上げる文を PowerShell に渡す環境変数（引数に埋め込まないため）
+#[cfg(windows)]
+const TEXT_ENV: &str = "CLAUDE_NOTIFY_SPEECH";
+
+/// 読み上げの設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeechSettings {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Default)]
+struct Pending {
+    /// 完了したプロジェクト（完了した順、重複なし）
+    projects: Vec<String>,
+    last_finished: Option<Instant>,
+    /// まとめて読み上げるスレッドが動いているか
+    gathering: bool,
+}
+
+/// 読み上げを待っているタスク完了
+#[derive(Debug, Default)]
+pub struct SpeechQueue {
+    pending: Mutex<Pending>,
+}
+
+impl SpeechQueue {
+    /// 完了したプロジェクトを加える（読み上げるスレッドを開始する必要がある場合は true）
+    pub fn push(&self, project: &str, now: Instant) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        if !pending.projects.iter().any(|pushed| pushed == project) {
+            pending.projects.push(project.to_string());
+        }
+        pending.last_finished = Some(now);
+        !std::mem::replace(&mut pending.gathering, true)
+    }
+
+    /// 最後の完了から `QUIET_PERIOD` が過ぎたか
+    pub fn is_quiet(&self, now: Instant) -> bool {
+        let pending = self.pending.lock().unwrap();
+        pending
+            .last_finished
+            .is_none_or(|last| now.duration_since(last) >= QUIET_PERIOD)
+    }
+
+    /// 読み上げるプロジェクトを取り出す（空の場合はまとめる処理を終了する）
+    pub fn take(&self) -> Vec<String> {
+        let mut pending = self.pending.lock().unwrap();
+        let projects = std::mem::take(&mut pending.projects);
+        pending.gathering = !projects.is_empty();
+        projects
+    }
+}
+
+/// 完了したプロジェクトを1つの文にまとめる
+pub fn summary(strings: &Strings, projects: &[String]) -> Option<String> {
+    match projects {
+        [] => None,
+        [project] => Some(i18n::fill(strings.speech_finished_one, &[("project", project)])),
+        _ => Some(i18n::fill(
+            strings.speech_finished_many,
+            &[
+                ("count", &projects.len().to_string()),
+                ("projects", &projects.join(strings.speech_separator)),
+            ],
+        )),
+    }
+}
+
+#[cfg(windows)]
+fn speech_command(text: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut command = Command::new("powershell");
+    command
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak($env:CLAUDE_NOTIFY_SPEECH)",
+        ])
+        .env(TEXT_ENV, text);
+    // コンソールウィンドウを表示しない（CREATE_NO_WINDOW）
+    command.creation_flags(0x0800_0000);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn speech_command(text: &str) -> Command {
+    let mut command = Command::new("say");
+    command.arg(text);
+    command
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn speech_command(text: &str) -> Command {
+    let mut command = Command::new("spd-say");
+    command.arg("--wait").arg(text);
+    command
+}
+
+/// 文を読み上げる（読み上げが終わるまで戻らない）
+pub fn speak(text: &str) {
+    match speech_command(text).status() {
+        Ok(status) if status.success() => info!("Spoke summary: {}", text),
+        Ok(status) => warn!("Speech command exited with {}", status),
+        Err(e) => warn!("Failed to run speech command: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+
+    #[test]
+    fn test_push_gathers_until_taken() {
+        let queue = SpeechQueue::default();
+        let now = Instant::now();
+        assert!(queue.push("api", now));
+        assert!(!queue.push("web", now));
+        assert!(!queue.push("api", now));
+        assert!(!queue.is_quiet(now));
+        assert!(queue.is_quiet(now + QUIET_PERIOD));
+
+        assert_eq!(queue.take(), vec!["api".to_string(), "web".to_string()]);
+        // 読み上げ中に完了したものは同じスレッドが次にまとめる
+        assert!(!queue.push("docs", now));
+        assert_eq!(queue.take(), vec!["docs".to_string()]);
+        assert!(queue.take().is_empty());
+        assert!(queue.push("api", now));
+    }
+
+    #[test]
+    fn test_summary() {
+        let en = Language::En.strings();
+        assert_eq!(summary(en, &[]), None);
+        assert_eq!(summary(en, &["api".to_string()]).unwrap(), "api finished");
+        let projects = ["api", "web", "docs"].map(String::from);
+        assert_eq!(summary(en, &projects).unwrap(), "3 projects finished: api, web, docs");
+        assert_eq!(
+            summary(Language::Ja.strings(), &projects).unwrap(),
+            "3 つのプロジェクトが完了しました: api、web、docs"
+        );
+    }
+}
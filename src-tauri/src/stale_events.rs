@@ -0,0 +1,96 @@
+//! 古いイベントの扱いのモジュール
+//!
+//! ブローカーに残っていたメッセージや、ネットワークの断絶で遅れて届いたメッセージで、
+//! 1時間前に終わったタスクの通知音が鳴らないよう、ペイロードの `timestamp` から経過時間を求め、
+//! 設定した時間より古いイベントは履歴にだけ記録する（または完全に捨てる）。
+//! フックを実行するマシンとこのPCの時計のずれを許容するため、判定には許容する時間を加える。
+//! 許容する時間より先の時刻（時計が大きく進んでいるマシン）や `timestamp` のないイベントは通常どおり扱う。
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 古いイベントの扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleEventAction {
+    /// 通常どおり通知する
+    Off,
+    /// トースト・通知音を出さずに履歴に記録する（未確認数・バッジは増やす）
+    #[default]
+    Demote,
+    /// 履歴にも記録せずに捨てる
+    Discard,
+}
+
+/// 古いイベントの設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StaleEventSettings {
+    pub action: StaleEventAction,
+    /// これより古いイベント（分）を古いとみなす
+    pub max_age_minutes: u32,
+    /// 時計のずれとして許容する時間（秒）
+    pub clock_skew_secs: u32,
+}
+
+impl Default for StaleEventSettings {
+    fn default() -> Self {
+        Self {
+            action: StaleEventAction::Demote,
+            max_age_minutes: 30,
+            clock_skew_secs: 120,
+        }
+    }
+}
+
+impl StaleEventSettings {
+    /// 古いイベントであれば、その扱いを返す（通常どおり扱う場合は None）
+    pub fn classify(&self, timestamp: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<StaleEventAction> {
+        if self.action == StaleEventAction::Off || self.max_age_minutes == 0 {
+            return None;
+        }
+        let age = now - timestamp?;
+        let cutoff = Duration::minutes(i64::from(self.max_age_minutes)) + Duration::seconds(i64::from(self.clock_skew_secs));
+        (age > cutoff).then_some(self.action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_classify() {
+        let settings = StaleEventSettings::default();
+        let now = at("2026-10-17T12:00:00Z");
+        assert_eq!(settings.classify(Some(at("2026-10-17T11:00:00Z")), now), Some(StaleEventAction::Demote));
+        assert_eq!(settings.classify(Some(at("2026-10-17T11:45:00Z")), now), None);
+        // 時計のずれの分は許容する
+        assert_eq!(settings.classify(Some(at("2026-10-17T11:29:00Z")), now), None);
+        assert_eq!(settings.classify(Some(at("2026-10-17T11:27:59Z")), now), Some(StaleEventAction::Demote));
+        // 時計が進んでいるマシン・時刻のないイベントは通常どおり
+        assert_eq!(settings.classify(Some(at("2026-10-17T13:00:00+00:00")), now), None);
+        assert_eq!(settings.classify(None, now), None);
+    }
+
+    #[test]
+    fn test_action_setting() {
+        let now = at("2026-10-17T12:00:00Z");
+        let old = Some(at("2026-10-17T09:00:00+09:00"));
+        let discard = StaleEventSettings {
+            action: StaleEventAction::Discard,
+            ..StaleEventSettings::default()
+        };
+        assert_eq!(discard.classify(old, now), Some(StaleEventAction::Discard));
+
+        let off = StaleEventSettings {
+            action: StaleEventAction::Off,
+            ..StaleEventSettings::default()
+        };
+        assert_eq!(off.classify(old, now), None);
+    }
+}
@@ -3,7 +3,14 @@
 //! Manages the state of Claude Code sessions, including
 //! tracking active sessions, their status, and aggregated metrics.
 //! Also handles session ID to display name mapping.
-
+//! Project names honour the configured project aliases, so worktrees and clones
+//! of the same repository are shown and aggregated as one project.
+
+use crate::i18n::{self, Strings};
+use crate::project_aliases::ProjectAliasSettings;
+use crate::text;
+use crate::timestamps;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
@@ -13,18 +20,48 @@ use tracing::{debug, info};
 /// Default timeout for session cleanup (5 minutes)
 const SESSION_TIMEOUT_SECS: u64 = 300;
 
+/// Maximum number of projects listed in the tray tooltip
+const MAX_TOOLTIP_PROJECTS: usize = 2;
+
+/// Default tray tooltip template, used when the configured template is empty
+///
+/// Placeholders: {sessions}, {cost}, {context}, {lines_added}, {lines_removed},
+/// {pending}, {unread} and {projects} (per-project breakdown, one line per project).
+pub const DEFAULT_TOOLTIP_TEMPLATE: &str = "Claude Code Notify\n\
+     Sessions: {sessions}\n\
+     Cost: ${cost}\n\
+     Context: {context}%\n\
+     {projects}\n\
+     Pending approvals: {pending}";
+
+/// Placeholders that are only meaningful while there are active sessions
+const SESSION_PLACEHOLDERS: [&str; 5] = ["sessions", "cost", "context", "lines_added", "lines_removed"];
+
+/// State reported while Claude Code is processing a prompt
+pub const WORKING_STATE: &str = "working";
+
+/// State reported while Claude Code is waiting for user input
+pub const WAITING_STATE: &str = "waiting";
+
+/// State reported while Claude Code has nothing to do
+pub const IDLE_STATE: &str = "idle";
+
 /// Status payload from Claude Code statusline
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StatusPayload {
     pub session_id: String,
     pub cwd: String,
     pub status: SessionStatus,
+    /// Host the statusline runs on
     #[serde(default)]
-    pub timestamp: Option<String>,
+    pub host: Option<String>,
+    /// Time the statusline reported the status (None when missing or malformed)
+    #[serde(default, deserialize_with = "timestamps::deserialize")]
+    pub timestamp: Option<DateTime<Utc>>,
 }
 
 /// Session status details from statusline
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct SessionStatus {
     /// Current state (e.g., "idle", "working", "waiting")
     #[serde(default)]
@@ -49,28 +86,159 @@ pub struct SessionData {
     pub session_id: String,
     pub cwd: String,
     pub status: SessionStatus,
+    pub host: Option<String>,
     pub last_updated: Instant,
+    /// Time of the last status report (the payload timestamp, or the arrival time when missing)
+    pub reported_at: DateTime<Utc>,
+    /// State reported before the current one (None until the state changes)
+    pub previous_state: Option<String>,
+    /// When the current state was first reported
+    pub state_since: Instant,
+    /// Whether the change to the current state has already been notified
+    transition_notified: bool,
+    /// Whether a reminder has been sent for the current state
+    reminder_sent: bool,
+    /// Status at the end of the previous run (or when the session was first seen)
+    run_baseline: SessionStatus,
+    /// When the status first changed after the previous run (None while nothing has happened)
+    run_started: Option<Instant>,
+    /// When the session last switched to the working state during the current run
+    working_since: Option<Instant>,
+    /// How long the previous run took
+    last_task: Option<Duration>,
 }
 
 impl SessionData {
     pub fn new(payload: StatusPayload) -> Self {
+        let now = Instant::now();
+        let working = payload.status.state.as_deref() == Some(WORKING_STATE);
         Self {
             session_id: payload.session_id,
             cwd: payload.cwd,
+            run_baseline: payload.status.clone(),
             status: payload.status,
-            last_updated: Instant::now(),
+            host: payload.host,
+            last_updated: now,
+            reported_at: payload.timestamp.unwrap_or_else(Utc::now),
+            previous_state: None,
+            state_since: now,
+            transition_notified: false,
+            reminder_sent: false,
+            run_started: None,
+            working_since: working.then_some(now),
+            last_task: None,
         }
     }
 
     pub fn update(&mut self, payload: StatusPayload) {
+        let now = Instant::now();
+        if self.run_started.is_none() && self.status != payload.status {
+            self.run_started = Some(now);
+        }
+        if self.status.state != payload.status.state {
+            if payload.status.state.as_deref() == Some(WORKING_STATE) {
+                self.working_since = Some(now);
+            }
+            self.previous_state = self.status.state.take();
+            self.state_since = now;
+            self.transition_notified = false;
+            self.reminder_sent = false;
+        }
         self.cwd = payload.cwd;
         self.status = payload.status;
-        self.last_updated = Instant::now();
+        self.host = payload.host;
+        self.last_updated = now;
+        self.reported_at = payload.timestamp.unwrap_or_else(Utc::now);
     }
 
     pub fn is_expired(&self, timeout: Duration) -> bool {
         self.last_updated.elapsed() > timeout
     }
+
+    /// How long the current task has been running while working, otherwise how long the previous run took
+    pub fn task_elapsed(&self, now: Instant) -> Option<Duration> {
+        match self.working_since {
+            Some(since) if self.status.state.as_deref() == Some(WORKING_STATE) => {
+                Some(now.saturating_duration_since(since))
+            }
+            _ => self.last_task,
+        }
+    }
+}
+
+/// What a session did since its previous run ended (cost, time and lines changed)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunSummary {
+    pub cost_usd: Option<f64>,
+    /// Time from the last switch to working (or the first status change of the run) until it ended
+    pub duration: Option<Duration>,
+    pub lines_added: Option<i64>,
+    pub lines_removed: Option<i64>,
+}
+
+impl RunSummary {
+    /// One-line summary such as "This run: $0.42 ・ 15 min ・ +320/-80 lines" (None when nothing is known)
+    pub fn text(&self, strings: &Strings) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(cost) = self.cost_usd {
+            parts.push(format!("${:.2}", cost));
+        }
+        if let Some(duration) = self.duration {
+            parts.push(format_duration(duration, strings));
+        }
+        if self.lines_added.is_some() || self.lines_removed.is_some() {
+            parts.push(i18n::fill(
+                strings.run_lines,
+                &[
+                    ("added", &self.lines_added.unwrap_or(0).to_string()),
+                    ("removed", &self.lines_removed.unwrap_or(0).to_string()),
+                ],
+            ));
+        }
+        (!parts.is_empty()).then(|| i18n::fill(strings.run_summary, &[("summary", &parts.join(" ・ "))]))
+    }
+}
+
+/// Task duration in seconds under a minute, otherwise in minutes
+pub fn format_duration(duration: Duration, strings: &Strings) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        i18n::fill(strings.run_seconds, &[("seconds", &secs.to_string())])
+    } else {
+        i18n::fill(strings.run_minutes, &[("minutes", &(secs / 60).to_string())])
+    }
+}
+
+/// Session summary for the diagnostics tab
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub cwd: String,
+    pub state: Option<String>,
+    pub host: Option<String>,
+    pub cost_usd: Option<f64>,
+    /// Seconds since the last status update
+    pub last_seen_secs: u64,
+    /// Time of the last status report
+    pub reported_at: DateTime<Utc>,
+    /// Seconds the current task has been running while working, otherwise how long the previous run took
+    pub task_secs: Option<u64>,
+}
+
+/// Sessions grouped by project (directory or alias) and host, with per-project metrics
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectSummary {
+    /// Display name of the project (alias, or last component of cwd)
+    pub project: String,
+    /// Working directory of the most recently updated session
+    pub cwd: String,
+    pub host: Option<String>,
+    pub active_sessions: usize,
+    pub total_cost_usd: f64,
+    /// Seconds since the most recent status update in the project
+    pub last_activity_secs: u64,
+    /// Sessions in the project, most recently updated first
+    pub sessions: Vec<SessionSummary>,
 }
 
 /// Aggregated metrics across all sessions
@@ -88,6 +256,8 @@ pub struct AggregatedMetrics {
 pub struct SessionManager {
     sessions: Arc<RwLock<HashMap<String, SessionData>>>,
     timeout: Duration,
+    /// Project aliases used to group sessions by project
+    aliases: Arc<RwLock<ProjectAliasSettings>>,
 }
 
 impl Default for SessionManager {
@@ -101,21 +271,130 @@ impl SessionManager {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             timeout: Duration::from_secs(SESSION_TIMEOUT_SECS),
+            aliases: Arc::new(RwLock::new(ProjectAliasSettings::default())),
         }
     }
 
+    /// Replace the project aliases used to group sessions
+    pub fn set_project_aliases(&self, aliases: ProjectAliasSettings) {
+        *self.aliases.write().expect("Failed to acquire write lock") = aliases;
+    }
+
     /// Update session with new status payload
-    pub fn update_session(&self, payload: StatusPayload) {
+    ///
+    /// Returns true when an existing session reported a different status,
+    /// which means the session has made progress since its last update.
+    pub fn update_session(&self, payload: StatusPayload) -> bool {
         let session_id = payload.session_id.clone();
         let mut sessions = self.sessions.write().expect("Failed to acquire write lock");
 
         if let Some(session) = sessions.get_mut(&session_id) {
             debug!("Updating existing session: {}", session_id);
+            let changed = session.status != payload.status;
             session.update(payload);
+            changed
         } else {
             info!("New session registered: {}", session_id);
             sessions.insert(session_id.clone(), SessionData::new(payload));
+            false
+        }
+    }
+
+    /// Last reported status of the session (None for unknown sessions)
+    pub fn status(&self, session_id: &str) -> Option<SessionStatus> {
+        let sessions = self.sessions.read().expect("Failed to acquire read lock");
+        sessions.get(session_id).map(|session| session.status.clone())
+    }
+
+    /// Whether the session last reported the working state (None for unknown sessions)
+    pub fn is_working(&self, session_id: &str) -> Option<bool> {
+        let sessions = self.sessions.read().expect("Failed to acquire read lock");
+        sessions
+            .get(session_id)
+            .map(|session| session.status.state.as_deref() == Some(WORKING_STATE))
+    }
+
+    /// Sessions that moved from `from` to `to` and have stayed in `to` for at least `debounce`
+    ///
+    /// Each transition is returned only once. A session that flaps back before `debounce` elapses
+    /// restarts the wait, so short-lived states are never reported.
+    pub fn settled_transitions(&self, from: &str, to: &str, debounce: Duration, now: Instant) -> Vec<SessionData> {
+        let mut sessions = self.sessions.write().expect("Failed to acquire write lock");
+        sessions
+            .values_mut()
+            .filter(|session| {
+                !session.transition_notified
+                    && session.previous_state.as_deref() == Some(from)
+                    && session.status.state.as_deref() == Some(to)
+                    && now.saturating_duration_since(session.state_since) >= debounce
+            })
+            .map(|session| {
+                session.transition_notified = true;
+                session.clone()
+            })
+            .collect()
+    }
+
+    /// Sessions that have stayed in one of `states` longer than the threshold returned by `threshold`
+    ///
+    /// `threshold` returns None for sessions that should not be reminded. Each session is returned
+    /// once per state, together with how long it has been in the state.
+    pub fn stale_sessions<F>(&self, states: &[&str], now: Instant, threshold: F) -> Vec<(SessionData, Duration)>
+    where
+        F: Fn(&SessionData) -> Option<Duration>,
+    {
+        let mut sessions = self.sessions.write().expect("Failed to acquire write lock");
+        let mut stale = Vec::new();
+        for session in sessions.values_mut() {
+            let in_state = session.status.state.as_deref().is_some_and(|state| states.contains(&state));
+            if session.reminder_sent || !in_state {
+                continue;
+            }
+            let elapsed = now.saturating_duration_since(session.state_since);
+            if threshold(session).is_some_and(|threshold| elapsed >= threshold) {
+                session.reminder_sent = true;
+                stale.push((session.clone(), elapsed));
+            }
+        }
+        stale
+    }
+
+    /// Number of sessions that have not gone idle (working, waiting for input, or reporting no state)
+    pub fn active_count(&self) -> usize {
+        let sessions = self.sessions.read().expect("Failed to acquire read lock");
+        sessions
+            .values()
+            .filter(|session| session.status.state.as_deref() != Some(IDLE_STATE))
+            .count()
+    }
+
+    /// End the current run of the session and return what changed since the previous run
+    ///
+    /// Totals reported by the statusline are cumulative, so the run is the difference from the
+    /// status at the end of the previous run. Returns None for unknown sessions.
+    pub fn finish_run(&self, session_id: &str, now: Instant) -> Option<RunSummary> {
+        let mut sessions = self.sessions.write().expect("Failed to acquire write lock");
+        let session = sessions.get_mut(session_id)?;
+        let delta = |current: Option<i64>, baseline: Option<i64>| current.map(|value| (value - baseline.unwrap_or(0)).max(0));
+        let summary = RunSummary {
+            cost_usd: session
+                .status
+                .cost_usd
+                .map(|cost| (cost - session.run_baseline.cost_usd.unwrap_or(0.0)).max(0.0)),
+            duration: session
+                .working_since
+                .or(session.run_started)
+                .map(|started| now.saturating_duration_since(started)),
+            lines_added: delta(session.status.lines_added, session.run_baseline.lines_added),
+            lines_removed: delta(session.status.lines_removed, session.run_baseline.lines_removed),
+        };
+        session.run_baseline = session.status.clone();
+        session.run_started = None;
+        session.working_since = None;
+        if summary.duration.is_some() {
+            session.last_task = summary.duration;
         }
+        Some(summary)
     }
 
     /// Remove expired sessions
@@ -184,21 +463,69 @@ impl SessionManager {
         }
     }
 
-    /// Generate tooltip text for tray icon
-    pub fn generate_tooltip(&self) -> String {
+    /// Generate tooltip text for tray icon from a template
+    ///
+    /// Each template line is rendered on its own. Lines are dropped when they have nothing to show:
+    /// {pending} / {unread} lines when the count is zero, the {projects} line when there is only
+    /// one project, and session metric lines when there are no sessions (replaced by a single
+    /// "No active sessions" line).
+    pub fn generate_tooltip(&self, template: &str, pending_approvals: u32, unread: u32) -> String {
+        let template = if template.trim().is_empty() {
+            DEFAULT_TOOLTIP_TEMPLATE
+        } else {
+            template
+        };
         let metrics = self.get_metrics();
-
-        if metrics.active_sessions == 0 {
-            return "Claude Code Notify\nNo active sessions".to_string();
+        let projects = self.project_summaries();
+
+        let mut project_lines: Vec<String> = projects
+            .iter()
+            .take(MAX_TOOLTIP_PROJECTS)
+            .map(|project| {
+                let host = project.host.as_deref().map(|host| format!("@{}", host)).unwrap_or_default();
+                format!(
+                    "{}{}: {} (${:.2})",
+                    project.project, host, project.active_sessions, project.total_cost_usd
+                )
+            })
+            .collect();
+        if projects.len() > MAX_TOOLTIP_PROJECTS {
+            project_lines.push(format!("+{} more projects", projects.len() - MAX_TOOLTIP_PROJECTS));
         }
 
-        format!(
-            "Claude Code Notify\n\
-             Sessions: {}\n\
-             Cost: ${:.2}\n\
-             Context: {:.0}%",
-            metrics.active_sessions, metrics.total_cost_usd, metrics.average_context_percent
-        )
+        let values = [
+            ("sessions", metrics.active_sessions.to_string()),
+            ("cost", format!("{:.2}", metrics.total_cost_usd)),
+            ("context", format!("{:.0}", metrics.average_context_percent)),
+            ("lines_added", metrics.total_lines_added.to_string()),
+            ("lines_removed", metrics.total_lines_removed.to_string()),
+            ("pending", pending_approvals.to_string()),
+            ("unread", unread.to_string()),
+            ("projects", project_lines.join("\n")),
+        ];
+        let args: Vec<(&str, &str)> = values.iter().map(|(name, value)| (*name, value.as_str())).collect();
+
+        let mut lines = Vec::new();
+        let mut no_sessions_shown = false;
+        for line in template.lines() {
+            let uses = |name: &str| line.contains(&format!("{{{}}}", name));
+
+            if metrics.active_sessions == 0 && SESSION_PLACEHOLDERS.iter().any(|name| uses(name)) {
+                if !no_sessions_shown {
+                    lines.push("No active sessions".to_string());
+                    no_sessions_shown = true;
+                }
+                continue;
+            }
+            if (uses("pending") && pending_approvals == 0)
+                || (uses("unread") && unread == 0)
+                || (uses("projects") && projects.len() <= 1)
+            {
+                continue;
+            }
+            lines.push(i18n::fill(line, &args));
+        }
+        lines.join("\n")
     }
 
     /// Get list of all active sessions
@@ -212,6 +539,59 @@ impl SessionManager {
         let sessions = self.sessions.read().expect("Failed to acquire read lock");
         sessions.len()
     }
+
+    /// Get session summaries, most recently updated first
+    pub fn summaries(&self) -> Vec<SessionSummary> {
+        let mut sessions = self.get_sessions();
+        sessions.sort_by_key(|session| session.last_updated.elapsed());
+        let now = Instant::now();
+
+        sessions
+            .into_iter()
+            .map(|session| SessionSummary {
+                last_seen_secs: session.last_updated.elapsed().as_secs(),
+                task_secs: session.task_elapsed(now).map(|elapsed| elapsed.as_secs()),
+                reported_at: session.reported_at,
+                session_id: session.session_id,
+                cwd: session.cwd,
+                state: session.status.state,
+                host: session.host,
+                cost_usd: session.status.cost_usd,
+            })
+            .collect()
+    }
+
+    /// Group sessions by project directory (or alias) and host, most recently active project first
+    pub fn project_summaries(&self) -> Vec<ProjectSummary> {
+        let aliases = self.aliases.read().expect("Failed to acquire read lock").clone();
+        let mut projects: Vec<ProjectSummary> = Vec::new();
+        let mut index: HashMap<(String, Option<String>), usize> = HashMap::new();
+
+        // summaries() is sorted by recency, so the first session of each project is its latest
+        for session in self.summaries() {
+            let group = aliases.alias(&session.cwd).unwrap_or(&session.cwd).to_string();
+            let key = (group, session.host.clone());
+            let position = *index.entry(key).or_insert_with(|| {
+                projects.push(ProjectSummary {
+                    project: display_project_name(&aliases.project_name(&session.cwd)),
+                    cwd: session.cwd.clone(),
+                    host: session.host.clone(),
+                    active_sessions: 0,
+                    total_cost_usd: 0.0,
+                    last_activity_secs: session.last_seen_secs,
+                    sessions: Vec::new(),
+                });
+                projects.len() - 1
+            });
+
+            let project = &mut projects[position];
+            project.active_sessions += 1;
+            project.total_cost_usd += session.cost_usd.unwrap_or(0.0);
+            project.sessions.push(session);
+        }
+
+        projects
+    }
 }
 
 // =============================================================================
@@ -221,6 +601,36 @@ impl SessionManager {
 /// Maximum length for project name display
 const MAX_PROJECT_NAME_LENGTH: usize = 30;
 
+/// Session ID the statusline sends when Claude Code does not provide one
+const STATUSLINE_FALLBACK_SESSION: &str = "default-session";
+
+/// Suffix of the session ID hooks send when Claude Code does not provide one ("<hostname>-unknown")
+const HOOK_FALLBACK_SUFFIX: &str = "-unknown";
+
+/// Name shown until a session reports its working directory
+const UNNAMED_SESSION: &str = "Claude Code";
+
+/// Key that gives a session the same name whether the event came from a hook or the statusline
+///
+/// Without a session ID from Claude Code, hooks send "<hostname>-unknown" while the statusline sends
+/// "default-session"; both are keyed by the reporting host so they share one name.
+pub fn session_name_key(session_id: &str, host: Option<&str>) -> String {
+    let session_id = session_id.trim();
+    let is_fallback = session_id.is_empty()
+        || session_id == STATUSLINE_FALLBACK_SESSION
+        || session_id.ends_with(HOOK_FALLBACK_SUFFIX);
+    match host.map(str::trim).filter(|host| !host.is_empty()) {
+        Some(host) if is_fallback => format!("{}{}", host, HOOK_FALLBACK_SUFFIX),
+        _ => session_id.to_string(),
+    }
+}
+
+/// Shorten a project name for display
+fn display_project_name(project_name: &str) -> String {
+    // Truncate if too long (by characters, so multi-byte names don't panic)
+    text::truncate_with(project_name, MAX_PROJECT_NAME_LENGTH, "...")
+}
+
 /// Session name manager - maps session IDs to project-based names
 ///
 /// This manager creates display names based on the project directory (cwd).
@@ -228,10 +638,12 @@ const MAX_PROJECT_NAME_LENGTH: usize = 30;
 /// Multiple sessions in the same project get sequential numbers.
 #[derive(Debug, Clone)]
 pub struct SessionNameManager {
-    /// Map from session_id to display name
+    /// Map from session name key (see `session_name_key`) to display name
     names: Arc<RwLock<HashMap<String, String>>>,
     /// Map from project_name to list of session_ids (for sequential numbering)
     project_sessions: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    /// Project aliases applied to the project part of the name
+    aliases: Arc<RwLock<ProjectAliasSettings>>,
 }
 
 impl Default for SessionNameManager {
@@ -245,15 +657,29 @@ impl SessionNameManager {
         Self {
             names: Arc::new(RwLock::new(HashMap::new())),
             project_sessions: Arc::new(RwLock::new(HashMap::new())),
+            aliases: Arc::new(RwLock::new(ProjectAliasSettings::default())),
         }
     }
 
-    /// Get or create a display name for a session ID based on cwd
+    /// Replace the project aliases (names already assigned are kept)
+    pub fn set_project_aliases(&self, aliases: ProjectAliasSettings) {
+        *self.aliases.write().expect("Failed to acquire write lock") = aliases;
+    }
+
+    /// Project name for a cwd (alias, or last component of the path), not truncated
+    pub fn project_name(&self, cwd: &str) -> String {
+        self.aliases.read().expect("Failed to acquire read lock").project_name(cwd)
+    }
+
+    /// Get or create a display name for a session based on cwd
     ///
-    /// If the session ID already has a name, returns it.
+    /// If the session already has a name, returns it (hooks and the statusline share names,
+    /// see `session_name_key`).
     /// Otherwise, creates a name based on the project directory with sequential numbering.
     /// Format: "project-name (1)", "project-name (2)", etc.
-    pub fn get_or_create_name(&self, session_id: &str, cwd: &str) -> String {
+    /// A session without a working directory is not named yet, so the first event with one decides the name.
+    pub fn get_or_create_name(&self, session_id: &str, cwd: &str, host: Option<&str>) -> String {
+        let session_id = &session_name_key(session_id, host);
         // Check if name already exists
         {
             let names = self.names.read().expect("Failed to acquire read lock");
@@ -263,7 +689,11 @@ impl SessionNameManager {
         }
 
         // Extract project name from cwd
-        let project_name = Self::extract_project_name(cwd);
+        let project_name = self.project_name(cwd);
+        if project_name.trim().is_empty() {
+            return UNNAMED_SESSION.to_string();
+        }
+        let project_name = display_project_name(&project_name);
 
         // Create new name with sequential number
         let new_name = {
@@ -296,24 +726,12 @@ impl SessionNameManager {
         new_name
     }
 
-    /// Extract project name from cwd path
-    fn extract_project_name(cwd: &str) -> String {
-        let project_name = std::path::Path::new(cwd)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or(cwd);
-
-        // Truncate if too long
-        if project_name.len() > MAX_PROJECT_NAME_LENGTH {
-            format!("{}...", &project_name[..MAX_PROJECT_NAME_LENGTH - 3])
-        } else {
-            project_name.to_string()
-        }
-    }
-
     /// Remove a session and update sequential numbering
+    ///
+    /// Takes the same host as `get_or_create_name` so that sessions without an ID are removed too.
     #[allow(dead_code)]
-    pub fn remove_session(&self, session_id: &str) {
+    pub fn remove_session(&self, session_id: &str, host: Option<&str>) {
+        let session_id = &session_name_key(session_id, host);
         let mut names = self.names.write().expect("Failed to acquire write lock");
         let mut project_sessions = self.project_sessions.write().expect("Failed to acquire write lock");
 
@@ -344,6 +762,7 @@ impl SessionNameManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::project_aliases::ProjectAlias;
 
     fn create_test_payload(session_id: &str) -> StatusPayload {
         StatusPayload {
@@ -356,6 +775,7 @@ mod tests {
                 lines_added: Some(100),
                 lines_removed: Some(20),
             },
+            host: Some("wsl".to_string()),
             timestamp: None,
         }
     }
@@ -410,16 +830,283 @@ mod tests {
         let manager = SessionManager::new();
 
         // Empty state
-        let tooltip = manager.generate_tooltip();
-        assert!(tooltip.contains("No active sessions"));
+        let tooltip = manager.generate_tooltip("", 0, 0);
+        assert_eq!(tooltip, "Claude Code Notify\nNo active sessions");
 
         // With session
         let payload = create_test_payload("session-1");
         manager.update_session(payload);
 
-        let tooltip = manager.generate_tooltip();
-        assert!(tooltip.contains("Sessions: 1"));
-        assert!(tooltip.contains("$0.05"));
+        let tooltip = manager.generate_tooltip("", 0, 0);
+        assert_eq!(tooltip, "Claude Code Notify\nSessions: 1\nCost: $0.05\nContext: 46%");
+
+        // With pending approvals
+        let tooltip = manager.generate_tooltip("", 2, 0);
+        assert!(tooltip.ends_with("\nPending approvals: 2"));
+    }
+
+    #[test]
+    fn test_tooltip_custom_template() {
+        let manager = SessionManager::new();
+        let template = "Unread: {unread}\n+{lines_added} -{lines_removed}\nSessions: {sessions}";
+
+        assert_eq!(manager.generate_tooltip(template, 0, 3), "Unread: 3\nNo active sessions");
+
+        let mut payload = create_test_payload("session-1");
+        payload.status.lines_added = Some(120);
+        payload.status.lines_removed = Some(8);
+        manager.update_session(payload);
+
+        assert_eq!(manager.generate_tooltip(template, 0, 0), "+120 -8\nSessions: 1");
+    }
+
+    #[test]
+    fn test_update_session_reports_status_change() {
+        let manager = SessionManager::new();
+        assert!(!manager.update_session(create_test_payload("session-1")));
+        assert!(!manager.update_session(create_test_payload("session-1")));
+
+        let mut payload = create_test_payload("session-1");
+        payload.status.lines_added = Some(120);
+        assert!(manager.update_session(payload));
+    }
+
+    #[test]
+    fn test_finish_run_reports_difference_from_previous_run() {
+        let manager = SessionManager::new();
+        assert_eq!(manager.finish_run("session-1", Instant::now()), None);
+
+        manager.update_session(create_test_payload("session-1"));
+        let mut payload = create_test_payload("session-1");
+        payload.status.cost_usd = Some(0.47);
+        payload.status.lines_added = Some(420);
+        payload.status.lines_removed = Some(100);
+        manager.update_session(payload.clone());
+
+        let ended = Instant::now() + Duration::from_secs(15 * 60);
+        let run = manager.finish_run("session-1", ended).unwrap();
+        assert!((run.cost_usd.unwrap() - 0.42).abs() < 1e-9);
+        assert!(run.duration.unwrap() >= Duration::from_secs(15 * 60));
+        assert_eq!((run.lines_added, run.lines_removed), (Some(320), Some(80)));
+
+        let strings = crate::i18n::Language::En.strings();
+        assert_eq!(run.text(strings).as_deref(), Some("💰 This run: $0.42 ・ 15 min ・ +320/-80 lines"));
+
+        // Nothing happened since the previous run
+        manager.update_session(payload);
+        let run = manager.finish_run("session-1", ended).unwrap();
+        assert_eq!(run.cost_usd, Some(0.0));
+        assert_eq!(run.duration, None);
+        assert_eq!(run.lines_added, Some(0));
+        assert_eq!(RunSummary::default().text(strings), None);
+    }
+
+    #[test]
+    fn test_task_elapsed_follows_working_state() {
+        let manager = SessionManager::new();
+        let mut idle = create_test_payload("session-1");
+        idle.status.state = Some(IDLE_STATE.to_string());
+        manager.update_session(idle);
+        assert_eq!(manager.summaries()[0].task_secs, None);
+
+        // Elapsed time counts from the switch to working
+        manager.update_session(create_test_payload("session-1"));
+        let session = manager.get_sessions().remove(0);
+        let later = Instant::now() + Duration::from_secs(30);
+        assert!(session.task_elapsed(later).unwrap() >= Duration::from_secs(30));
+
+        // After the run ends, the previous run's duration is shown
+        let run = manager.finish_run("session-1", later).unwrap();
+        let mut waiting = create_test_payload("session-1");
+        waiting.status.state = Some(WAITING_STATE.to_string());
+        manager.update_session(waiting);
+        let session = manager.get_sessions().remove(0);
+        assert_eq!(session.task_elapsed(Instant::now()), run.duration);
+
+        let strings = crate::i18n::Language::Ja.strings();
+        assert_eq!(format_duration(Duration::from_secs(30), strings), "30 秒");
+        assert_eq!(format_duration(Duration::from_secs(30 * 60 + 5), strings), "30 分");
+    }
+
+    #[test]
+    fn test_active_count() {
+        let manager = SessionManager::new();
+        assert_eq!(manager.active_count(), 0);
+        manager.update_session(create_test_payload("session-1"));
+        let mut waiting = create_test_payload("session-2");
+        waiting.status.state = Some(WAITING_STATE.to_string());
+        manager.update_session(waiting);
+        assert_eq!(manager.active_count(), 2);
+
+        let mut idle = create_test_payload("session-1");
+        idle.status.state = Some(IDLE_STATE.to_string());
+        manager.update_session(idle);
+        assert_eq!(manager.active_count(), 1);
+    }
+
+    #[test]
+    fn test_is_working() {
+        let manager = SessionManager::new();
+        assert_eq!(manager.is_working("session-1"), None);
+
+        manager.update_session(create_test_payload("session-1"));
+        assert_eq!(manager.is_working("session-1"), Some(true));
+
+        let mut payload = create_test_payload("session-1");
+        payload.status.state = Some("idle".to_string());
+        manager.update_session(payload);
+        assert_eq!(manager.is_working("session-1"), Some(false));
+    }
+
+    #[test]
+    fn test_settled_transitions_debounce() {
+        let manager = SessionManager::new();
+        let debounce = Duration::from_secs(5);
+        manager.update_session(create_test_payload("session-1"));
+
+        let mut waiting = create_test_payload("session-1");
+        waiting.status.state = Some(WAITING_STATE.to_string());
+        manager.update_session(waiting.clone());
+        // Flapping back to working restarts the wait
+        manager.update_session(create_test_payload("session-1"));
+        manager.update_session(waiting);
+        let now = Instant::now();
+        assert!(manager.settled_transitions(WORKING_STATE, WAITING_STATE, debounce, now).is_empty());
+
+        let settled = manager.settled_transitions(WORKING_STATE, WAITING_STATE, debounce, now + debounce);
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].previous_state.as_deref(), Some(WORKING_STATE));
+
+        // Reported only once per transition
+        let later = now + debounce * 2;
+        assert!(manager.settled_transitions(WORKING_STATE, WAITING_STATE, debounce, later).is_empty());
+    }
+
+    #[test]
+    fn test_stale_sessions() {
+        let manager = SessionManager::new();
+        manager.update_session(create_test_payload("session-1"));
+        let mut idle = create_test_payload("session-2");
+        idle.status.state = Some(IDLE_STATE.to_string());
+        manager.update_session(idle);
+
+        let threshold = |_: &SessionData| Some(Duration::from_secs(600));
+        let now = Instant::now();
+        assert!(manager.stale_sessions(&[WAITING_STATE, IDLE_STATE], now, threshold).is_empty());
+
+        let later = now + Duration::from_secs(900);
+        let stale = manager.stale_sessions(&[WAITING_STATE, IDLE_STATE], later, threshold);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].0.session_id, "session-2");
+        assert!(stale[0].1 >= Duration::from_secs(900));
+
+        // Reminded only once until the state changes
+        assert!(manager.stale_sessions(&[WAITING_STATE, IDLE_STATE], later, threshold).is_empty());
+        assert!(manager.stale_sessions(&[WAITING_STATE], later, |_| None).is_empty());
+    }
+
+    #[test]
+    fn test_session_summaries() {
+        let manager = SessionManager::new();
+        manager.update_session(create_test_payload("session-1"));
+
+        let summaries = manager.summaries();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].session_id, "session-1");
+        assert_eq!(summaries[0].state.as_deref(), Some("working"));
+        assert_eq!(summaries[0].host.as_deref(), Some("wsl"));
+        assert_eq!(summaries[0].cost_usd, Some(0.05));
+        assert_eq!(summaries[0].last_seen_secs, 0);
+    }
+
+    #[test]
+    fn test_reported_at_uses_payload_timestamp() {
+        let payload: StatusPayload = serde_json::from_str(
+            r#"{"session_id":"s1","cwd":"/app","status":{},"timestamp":"2026-10-17T10:00:00+09:00"}"#,
+        )
+        .unwrap();
+        let sent = payload.timestamp.unwrap();
+        let mut session = SessionData::new(payload);
+        assert_eq!(session.reported_at, sent);
+
+        // Malformed timestamps fall back to the arrival time
+        let before = Utc::now();
+        let payload: StatusPayload =
+            serde_json::from_str(r#"{"session_id":"s1","cwd":"/app","status":{},"timestamp":"now"}"#).unwrap();
+        session.update(payload);
+        assert!(session.reported_at >= before);
+    }
+
+    #[test]
+    fn test_project_summaries_group_by_project_and_host() {
+        let manager = SessionManager::new();
+        manager.update_session(create_test_payload("session-1"));
+        manager.update_session(create_test_payload("session-2"));
+
+        let mut other_host = create_test_payload("session-3");
+        other_host.host = Some("build-server".to_string());
+        manager.update_session(other_host);
+
+        let mut other_project = create_test_payload("session-4");
+        other_project.cwd = "/test/other".to_string();
+        other_project.status.cost_usd = None;
+        manager.update_session(other_project);
+
+        let projects = manager.project_summaries();
+        assert_eq!(projects.len(), 3);
+
+        let wsl = projects
+            .iter()
+            .find(|p| p.cwd == "/test/path" && p.host.as_deref() == Some("wsl"))
+            .unwrap();
+        assert_eq!(wsl.project, "path");
+        assert_eq!(wsl.active_sessions, 2);
+        assert_eq!(wsl.sessions.len(), 2);
+        assert!((wsl.total_cost_usd - 0.10).abs() < 0.001);
+
+        let other = projects.iter().find(|p| p.cwd == "/test/other").unwrap();
+        assert_eq!(other.active_sessions, 1);
+        assert_eq!(other.total_cost_usd, 0.0);
+    }
+
+    #[test]
+    fn test_project_summaries_group_aliases() {
+        let manager = SessionManager::new();
+        manager.set_project_aliases(ProjectAliasSettings {
+            aliases: vec![ProjectAlias {
+                name: "app".to_string(),
+                paths: vec!["/test/path".to_string(), "path-*".to_string()],
+            }],
+        });
+        manager.update_session(create_test_payload("session-1"));
+        let mut worktree = create_test_payload("session-2");
+        worktree.cwd = "/worktrees/path-feature".to_string();
+        manager.update_session(worktree);
+
+        let projects = manager.project_summaries();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].project, "app");
+        assert_eq!(projects[0].active_sessions, 2);
+    }
+
+    #[test]
+    fn test_tooltip_lists_projects() {
+        let manager = SessionManager::new();
+        manager.update_session(create_test_payload("session-1"));
+
+        // A single project adds nothing to the global line
+        assert!(!manager.generate_tooltip("", 0, 0).contains("path@wsl"));
+
+        for (id, cwd) in [("session-2", "/test/a"), ("session-3", "/test/b")] {
+            let mut payload = create_test_payload(id);
+            payload.cwd = cwd.to_string();
+            manager.update_session(payload);
+        }
+
+        let tooltip = manager.generate_tooltip("", 1, 0);
+        assert_eq!(tooltip.matches("@wsl: 1 ($0.05)").count(), MAX_TOOLTIP_PROJECTS);
+        assert!(tooltip.contains("\n+1 more projects"));
+        assert!(tooltip.ends_with("\nPending approvals: 1"));
     }
 
     // SessionNameManager tests
@@ -427,7 +1114,7 @@ mod tests {
     #[test]
     fn test_session_name_manager_assigns_name() {
         let manager = SessionNameManager::new();
-        let name = manager.get_or_create_name("wsl-12345", "/home/user/my-project");
+        let name = manager.get_or_create_name("wsl-12345", "/home/user/my-project", None);
 
         // Name should be "project-name (1)" format
         assert_eq!(name, "my-project (1)");
@@ -436,8 +1123,8 @@ mod tests {
     #[test]
     fn test_session_name_manager_returns_same_name() {
         let manager = SessionNameManager::new();
-        let name1 = manager.get_or_create_name("session-abc", "/home/user/project");
-        let name2 = manager.get_or_create_name("session-abc", "/home/user/project");
+        let name1 = manager.get_or_create_name("session-abc", "/home/user/project", None);
+        let name2 = manager.get_or_create_name("session-abc", "/home/user/project", None);
 
         assert_eq!(name1, name2);
     }
@@ -447,9 +1134,9 @@ mod tests {
         let manager = SessionNameManager::new();
 
         // Same project, different sessions should get sequential numbers
-        let name1 = manager.get_or_create_name("session-1", "/home/user/my-app");
-        let name2 = manager.get_or_create_name("session-2", "/home/user/my-app");
-        let name3 = manager.get_or_create_name("session-3", "/home/user/my-app");
+        let name1 = manager.get_or_create_name("session-1", "/home/user/my-app", None);
+        let name2 = manager.get_or_create_name("session-2", "/home/user/my-app", None);
+        let name3 = manager.get_or_create_name("session-3", "/home/user/my-app", None);
 
         assert_eq!(name1, "my-app (1)");
         assert_eq!(name2, "my-app (2)");
@@ -461,22 +1148,44 @@ mod tests {
         let manager = SessionNameManager::new();
 
         // Different projects should each start from (1)
-        let name1 = manager.get_or_create_name("session-1", "/home/user/project-a");
-        let name2 = manager.get_or_create_name("session-2", "/home/user/project-b");
-        let name3 = manager.get_or_create_name("session-3", "/home/user/project-a");
+        let name1 = manager.get_or_create_name("session-1", "/home/user/project-a", None);
+        let name2 = manager.get_or_create_name("session-2", "/home/user/project-b", None);
+        let name3 = manager.get_or_create_name("session-3", "/home/user/project-a", None);
 
         assert_eq!(name1, "project-a (1)");
         assert_eq!(name2, "project-b (1)");
         assert_eq!(name3, "project-a (2)");
     }
 
+    #[test]
+    fn test_session_name_manager_uses_aliases() {
+        let manager = SessionNameManager::new();
+        manager.set_project_aliases(ProjectAliasSettings {
+            aliases: vec![ProjectAlias {
+                name: "my-app".to_string(),
+                paths: vec!["my-app-*".to_string()],
+            }],
+        });
+
+        // Worktrees of the same project share the numbering
+        assert_eq!(manager.get_or_create_name("session-1", "/home/user/my-app", None), "my-app (1)");
+        assert_eq!(manager.get_or_create_name("session-2", "/home/user/my-app-review", None), "my-app (2)");
+        assert_eq!(manager.project_name("C:\\src\\my-app-fix"), "my-app");
+    }
+
     #[test]
     fn test_session_name_manager_remove_session() {
         let manager = SessionNameManager::new();
-        let _name = manager.get_or_create_name("session-to-remove", "/home/user/test");
+        let _name = manager.get_or_create_name("session-to-remove", "/home/user/test", None);
 
         assert_eq!(manager.session_count(), 1);
-        manager.remove_session("session-to-remove");
+        manager.remove_session("session-to-remove", None);
+        assert_eq!(manager.session_count(), 0);
+
+        // Sessions without an ID are removed by the same key they were named with
+        let _name = manager.get_or_create_name("default-session", "/home/user/test", Some("devbox"));
+        assert_eq!(manager.session_count(), 1);
+        manager.remove_session("devbox-unknown", Some("devbox"));
         assert_eq!(manager.session_count(), 0);
     }
 
@@ -485,11 +1194,65 @@ mod tests {
         let manager = SessionNameManager::new();
         let long_name = "a".repeat(50);
         let cwd = format!("/home/user/{}", long_name);
-        let name = manager.get_or_create_name("session-1", &cwd);
+        let name = manager.get_or_create_name("session-1", &cwd, None);
 
         // Should be truncated with "..."
         assert!(name.len() < 50);
         assert!(name.contains("..."));
         assert!(name.ends_with(" (1)"));
     }
+
+    #[test]
+    fn test_session_name_manager_long_multibyte_project_name() {
+        let manager = SessionNameManager::new();
+        let cwd = format!("/home/user/{}", "日本語のプロジェクト".repeat(5));
+        let name = manager.get_or_create_name("session-1", &cwd, None);
+
+        assert_eq!(name, format!("{}... (1)", "日本語のプロジェクト日本語のプロジェクト日本語のプロジ"));
+    }
+
+    #[test]
+    fn test_session_name_key() {
+        assert_eq!(session_name_key("abc-123", Some("devbox")), "abc-123");
+        // Fallback IDs from hooks and the statusline are keyed by the reporting host
+        assert_eq!(session_name_key("devbox-unknown", Some("devbox")), "devbox-unknown");
+        assert_eq!(session_name_key("default-session", Some("devbox")), "devbox-unknown");
+        assert_eq!(session_name_key("", Some("devbox")), "devbox-unknown");
+        assert_eq!(session_name_key("default-session", None), "default-session");
+    }
+
+    #[test]
+    fn test_session_name_manager_same_name_from_hooks_and_statusline() {
+        // Hook first
+        let manager = SessionNameManager::new();
+        let hook = manager.get_or_create_name("abc-123", "/home/user/my-app", Some("devbox"));
+        let status = manager.get_or_create_name("abc-123", "/home/user/my-app/", Some("devbox"));
+        assert_eq!(hook, "my-app (1)");
+        assert_eq!(status, hook);
+
+        // Statusline first, before it knows the working directory
+        let manager = SessionNameManager::new();
+        assert_eq!(manager.get_or_create_name("abc-123", "", Some("devbox")), "Claude Code");
+        assert_eq!(manager.session_count(), 0);
+        let hook = manager.get_or_create_name("abc-123", "/home/user/my-app", Some("devbox"));
+        let status = manager.get_or_create_name("abc-123", "/home/user/my-app", Some("devbox"));
+        assert_eq!(hook, "my-app (1)");
+        assert_eq!(status, hook);
+    }
+
+    #[test]
+    fn test_session_name_manager_fallback_ids_share_name() {
+        for hook_first in [true, false] {
+            let manager = SessionNameManager::new();
+            let hook = || manager.get_or_create_name("devbox-unknown", "/home/user/my-app", Some("devbox"));
+            let status = || manager.get_or_create_name("default-session", "/home/user/my-app", Some("devbox"));
+            let (first, second) = if hook_first { (hook(), status()) } else { (status(), hook()) };
+            assert_eq!(first, "my-app (1)");
+            assert_eq!(second, first);
+
+            // Another host's unknown session gets its own name
+            let other = manager.get_or_create_name("default-session", "/home/user/my-app", Some("laptop"));
+            assert_eq!(other, "my-app (2)");
+        }
+    }
 }
@@ -1,11 +1,24 @@
 //! タスクバー制御モジュール（Windows専用）
 //!
 //! タスクバーボタンの点滅とバッジ（オーバーレイアイコン）表示を制御する
-
+//! 承認待ちの依頼はバッジの色を変えて未確認数と区別する
+//! バッジのアイコン（1〜9 と 9+）と ITaskbarList3 はワーカースレッドで作成して使い回す
+//! NotificationManager からは [`TaskbarSignal`]（AttentionSignal の Windows 向けの実装）として使う
+//! HWND と COM はスレッドをまたいで共有できないため、タスクバーの操作はすべて専用のワーカースレッドで行い、
+//! TaskbarSignal はコマンドチャネルで要求を送るだけにする（NotificationManager は unsafe なしで Send + Sync になる）
+
+use crate::attention::{self, AttentionProgress, AttentionSignal};
+use crate::notification_state::BadgeKind;
+use crate::settings::NotificationSettings;
+use std::sync::mpsc;
+use tauri::AppHandle;
+use tracing::error;
 #[cfg(windows)]
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 #[cfg(windows)]
-use tracing::{error, info, warn};
+use std::collections::HashMap;
+#[cfg(windows)]
+use tracing::{info, warn};
 #[cfg(windows)]
 use windows::{
     core::PCWSTR,
@@ -22,8 +35,9 @@ use windows::{
         UI::{
             Shell::{ITaskbarList3, TaskbarList},
             WindowsAndMessaging::{
-                CreateIconIndirect, DestroyIcon, FlashWindowEx,
+                CreateIconIndirect, DestroyIcon, FlashWindowEx, GetSystemMetrics,
                 FLASHWINFO, FLASHW_ALL, FLASHW_STOP, FLASHW_TIMERNOFG, HICON, ICONINFO,
+                SM_CXSMICON,
             },
         },
     },
@@ -35,7 +49,76 @@ fn rgb(r: u8, g: u8, b: u8) -> COLORREF {
     COLORREF((r as u32) | ((g as u32) << 8) | ((b as u32) << 16))
 }
 
-/// タスクバーシステムを初期化（COM初期化のみ）
+/// バッジの種類に対応する背景色
+#[cfg(windows)]
+fn badge_color(kind: BadgeKind) -> COLORREF {
+    match kind {
+        BadgeKind::Unread => rgb(220, 53, 69),
+        BadgeKind::PendingApproval => rgb(245, 124, 0),
+    }
+}
+
+/// バッジのアイコンを使い分ける件数（10 件以上は同じ "9+" のアイコン）
+fn badge_slot(count: u32) -> u32 {
+    count.min(10)
+}
+
+/// バッジに表示する文字
+#[cfg_attr(not(windows), allow(dead_code))]
+fn badge_text(count: u32) -> String {
+    match badge_slot(count) {
+        10 => "9+".to_string(),
+        slot => slot.to_string(),
+    }
+}
+
+/// ワーカースレッドで使い回すタスクバーの資源
+#[cfg(windows)]
+#[derive(Default)]
+struct TaskbarResources {
+    taskbar_list: Option<ITaskbarList3>,
+    /// 作成したバッジのアイコン（キーは badge_slot の件数と種類）
+    icons: HashMap<(u32, BadgeKind), HICON>,
+}
+
+#[cfg(windows)]
+impl TaskbarResources {
+    /// ITaskbarList3（作成に失敗した場合は次の要求で作り直す）
+    fn taskbar_list(&mut self) -> Option<ITaskbarList3> {
+        if self.taskbar_list.is_none() {
+            self.taskbar_list = get_taskbar_list();
+        }
+        self.taskbar_list.clone()
+    }
+
+    /// 件数と種類に対応するバッジのアイコン（初回のみ作成する）
+    fn badge_icon(&mut self, count: u32, kind: BadgeKind) -> Result<HICON, String> {
+        let key = (badge_slot(count), kind);
+        if let Some(icon) = self.icons.get(&key) {
+            return Ok(*icon);
+        }
+        let icon = create_badge_icon(count, kind)?;
+        self.icons.insert(key, icon);
+        Ok(icon)
+    }
+}
+
+#[cfg(windows)]
+impl Drop for TaskbarResources {
+    fn drop(&mut self) {
+        for icon in self.icons.values() {
+            unsafe {
+                let _ = DestroyIcon(*icon);
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+#[derive(Default)]
+struct TaskbarResources {}
+
+/// タスクバーシステムを初期化（COM初期化のみ、タスクバーのワーカースレッドで呼ぶ）
 #[cfg(windows)]
 pub fn init_taskbar() -> Result<(), String> {
     unsafe {
@@ -131,65 +214,167 @@ pub fn stop_flash(hwnd: HWND) {
 #[cfg(not(windows))]
 pub fn stop_flash(_hwnd: ()) {}
 
-/// オーバーレイバッジを設定（未確認メッセージ数を表示）
+/// オーバーレイバッジを設定（未確認メッセージ数・承認待ち数を表示）
 #[cfg(windows)]
-pub fn set_overlay_badge(hwnd: HWND, count: u32) -> Result<(), String> {
-    if let Some(taskbar) = get_taskbar_list() {
-        unsafe {
-            if count == 0 {
-                // バッジをクリア（null HICONを渡す）
-                taskbar
-                    .SetOverlayIcon(hwnd, HICON::default(), PCWSTR::null())
-                    .map_err(|e| format!("Failed to clear overlay icon: {}", e))?;
-                info!("Overlay badge cleared");
-            } else {
-                // 数字付きアイコンを動的生成して設定
-                let icon = create_badge_icon(count)?;
-                let description: Vec<u16> = format!("{}件の通知\0", count)
-                    .encode_utf16()
-                    .collect();
+fn set_overlay_badge(
+    resources: &mut TaskbarResources,
+    hwnd: HWND,
+    count: u32,
+    kind: BadgeKind,
+) -> Result<(), String> {
+    // 数字付きアイコンは件数ごとに作成済みのものを使う（タスクバーが複製するため破棄しない）
+    let icon = if count == 0 { None } else { Some(resources.badge_icon(count, kind)?) };
+    let taskbar = resources
+        .taskbar_list()
+        .ok_or_else(|| "Failed to get taskbar list".to_string())?;
+    let result = unsafe {
+        match icon {
+            // バッジをクリア（null HICONを渡す）
+            None => taskbar
+                .SetOverlayIcon(hwnd, HICON::default(), PCWSTR::null())
+                .map_err(|e| format!("Failed to clear overlay icon: {}", e)),
+            Some(icon) => {
+                let description = match kind {
+                    BadgeKind::Unread => format!("{}件の通知\0", count),
+                    BadgeKind::PendingApproval => format!("{}件の承認待ち\0", count),
+                };
+                let description: Vec<u16> = description.encode_utf16().collect();
                 taskbar
                     .SetOverlayIcon(hwnd, icon, PCWSTR(description.as_ptr()))
-                    .map_err(|e| format!("Failed to set overlay icon: {}", e))?;
-                // アイコンを破棄
-                let _ = DestroyIcon(icon);
-                info!("Overlay badge set to {}", count);
+                    .map_err(|e| format!("Failed to set overlay icon: {}", e))
             }
         }
-    } else {
-        return Err("Failed to get taskbar list".to_string());
+    };
+    match &result {
+        Ok(()) if count == 0 => info!("Overlay badge cleared"),
+        Ok(()) => info!("Overlay badge set to {} ({:?})", count, kind),
+        // エクスプローラーの再起動などで使えなくなった場合は次の要求で作り直す
+        Err(_) => resources.taskbar_list = None,
     }
-    Ok(())
+    result
 }
 
 #[cfg(not(windows))]
-pub fn set_overlay_badge(_hwnd: (), _count: u32) -> Result<(), String> {
+fn set_overlay_badge(
+    _resources: &mut TaskbarResources,
+    _hwnd: (),
+    _count: u32,
+    _kind: BadgeKind,
+) -> Result<(), String> {
     Ok(())
 }
 
 /// オーバーレイバッジをクリア
 #[cfg(windows)]
-pub fn clear_overlay_badge(hwnd: HWND) -> Result<(), String> {
-    set_overlay_badge(hwnd, 0)
+fn clear_overlay_badge(resources: &mut TaskbarResources, hwnd: HWND) -> Result<(), String> {
+    set_overlay_badge(resources, hwnd, 0, BadgeKind::Unread)
 }
 
 #[cfg(not(windows))]
-pub fn clear_overlay_badge(_hwnd: ()) -> Result<(), String> {
+fn clear_overlay_badge(_resources: &mut TaskbarResources, _hwnd: ()) -> Result<(), String> {
     Ok(())
 }
 
-/// バッジアイコンを動的に生成（赤丸に白文字で数字）
+/// タスクバーのワーカーへの要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskbarCommand {
+    /// 指定した回数だけ点滅させる
+    Flash(u32),
+    StopFlash,
+    /// バッジを表示する（None の場合は消す）
+    Badge(Option<(BadgeKind, u32)>),
+}
+
+/// タスクバーボタンの点滅とオーバーレイバッジ（Windows）
+///
+/// 要求はワーカースレッドに送り、HWND の取得から表示までをワーカーで行う。
+pub struct TaskbarSignal {
+    tx: mpsc::Sender<TaskbarCommand>,
+}
+
+impl TaskbarSignal {
+    /// 新しい TaskbarSignal を作成し、ワーカースレッドを起動する
+    pub fn new(app: &AppHandle) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let app_handle = app.clone();
+        if let Err(e) = std::thread::Builder::new()
+            .name("taskbar".to_string())
+            .spawn(move || run_worker(app_handle, rx))
+        {
+            error!("Failed to spawn taskbar thread: {}", e);
+        }
+        Self { tx }
+    }
+
+    fn send(&self, command: TaskbarCommand) -> Result<(), String> {
+        self.tx
+            .send(command)
+            .map_err(|_| "Taskbar worker is not running".to_string())
+    }
+}
+
+impl AttentionSignal for TaskbarSignal {
+    fn name(&self) -> &'static str {
+        "taskbar"
+    }
+
+    fn flash(&self, _app: &AppHandle, settings: &NotificationSettings) -> Result<(), String> {
+        self.send(TaskbarCommand::Flash(settings.taskbar_flash_count.max(1)))
+    }
+
+    fn badge(&self, _app: &AppHandle, badge: Option<(BadgeKind, u32)>) -> Result<(), String> {
+        self.send(TaskbarCommand::Badge(badge))
+    }
+
+    /// 進捗はウィンドウの API で表示する（HWND を扱わない）
+    fn progress(&self, app: &AppHandle, progress: AttentionProgress) -> Result<(), String> {
+        attention::set_window_progress(app, progress)
+    }
+
+    fn clear(&self, _app: &AppHandle) -> Result<(), String> {
+        self.send(TaskbarCommand::StopFlash)
+    }
+}
+
+/// ワーカースレッドのメインループ（COM の初期化もこのスレッドで行う）
+fn run_worker(app: AppHandle, rx: mpsc::Receiver<TaskbarCommand>) {
+    if let Err(e) = init_taskbar() {
+        error!("Failed to initialize taskbar system: {}", e);
+    }
+    let mut resources = TaskbarResources::default();
+    for command in rx {
+        // ウィンドウが作られる前の要求は無視する
+        let Some(hwnd) = attention::main_window(&app).and_then(|window| get_hwnd(&window)) else {
+            continue;
+        };
+        let result = match command {
+            TaskbarCommand::Flash(count) => {
+                flash_taskbar(hwnd, count);
+                Ok(())
+            }
+            TaskbarCommand::StopFlash => {
+                stop_flash(hwnd);
+                Ok(())
+            }
+            TaskbarCommand::Badge(Some((kind, count))) => {
+                set_overlay_badge(&mut resources, hwnd, count, kind)
+            }
+            TaskbarCommand::Badge(None) => clear_overlay_badge(&mut resources, hwnd),
+        };
+        if let Err(e) = result {
+            error!("Failed to update overlay badge: {}", e);
+        }
+    }
+}
+
+/// バッジアイコンを動的に生成（色付きの丸に白文字で数字）
 #[cfg(windows)]
-fn create_badge_icon(count: u32) -> Result<HICON, String> {
-    let display_text = if count > 9 {
-        "9+".to_string()
-    } else {
-        count.to_string()
-    };
+fn create_badge_icon(count: u32, kind: BadgeKind) -> Result<HICON, String> {
+    let display_text = badge_text(count);
 
     unsafe {
-        // アイコンサイズ（16x16）
-        let size: i32 = 16;
+        // アイコンサイズ（小アイコンのサイズ。高DPI環境では 24px, 32px などになる）
+        let size: i32 = GetSystemMetrics(SM_CXSMICON).max(16);
 
         // デスクトップDCを取得
         let screen_dc = GetDC(None);
@@ -224,9 +409,9 @@ fn create_badge_icon(count: u32) -> Result<HICON, String> {
         // カラービットマップを選択
         let old_bitmap = SelectObject(mem_dc, color_bitmap.into());
 
-        // 赤いブラシで円を描画
-        let red_brush: HBRUSH = CreateSolidBrush(rgb(220, 53, 69));
-        let old_brush = SelectObject(mem_dc, red_brush.into());
+        // バッジの種類に応じた色のブラシで円を描画
+        let brush: HBRUSH = CreateSolidBrush(badge_color(kind));
+        let old_brush = SelectObject(mem_dc, brush.into());
 
         // 円を描画
         let _ = Ellipse(mem_dc, 0, 0, size, size);
@@ -238,7 +423,7 @@ fn create_badge_icon(count: u32) -> Result<HICON, String> {
         // フォントを作成
         let font_name: Vec<u16> = "Arial\0".encode_utf16().collect();
         let font = CreateFontW(
-            12,                    // 高さ
+            size * 3 / 4,          // 高さ
             0,                     // 幅（0=自動）
             0,                     // 傾斜角度
             0,                     // 方向
@@ -280,7 +465,7 @@ fn create_badge_icon(count: u32) -> Result<HICON, String> {
 
         // リソースを解放
         let _ = DeleteObject(font.into());
-        let _ = DeleteObject(red_brush.into());
+        let _ = DeleteObject(brush.into());
         let _ = DeleteDC(mem_dc);
         let _ = ReleaseDC(None, screen_dc);
 
@@ -310,11 +495,21 @@ mod tests {
     fn test_non_windows_functions_compile() {
         #[cfg(not(windows))]
         {
+            let mut resources = super::TaskbarResources {};
             let _ = super::init_taskbar();
             super::flash_taskbar((), 0);
             super::stop_flash(());
-            let _ = super::set_overlay_badge((), 0);
-            let _ = super::clear_overlay_badge(());
+            let _ = super::set_overlay_badge(&mut resources, (), 0, super::BadgeKind::PendingApproval);
+            let _ = super::clear_overlay_badge(&mut resources, ());
         }
     }
+
+    #[test]
+    fn test_badge_icons_are_shared_above_nine() {
+        assert_eq!(super::badge_slot(3), 3);
+        assert_eq!(super::badge_slot(10), super::badge_slot(42));
+        assert_eq!(super::badge_text(9), "9");
+        assert_eq!(super::badge_text(10), "9+");
+        assert_eq!(super::badge_text(120), "9+");
+    }
 }
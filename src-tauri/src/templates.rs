@@ -2,13 +2,33 @@
 //!
 //! Contains script templates for Claude Code hooks integration.
 
+/// テンプレートのバージョン（フックのペイロードで送られ、古いスクリプトの検出に使う）
+///
+/// インストール済みのスクリプトの再エクスポートが必要な変更をしたら1つ上げる。
+pub const TEMPLATE_VERSION: u32 = 2;
+
 /// on-stop.sh template (mosquitto_pub version)
 pub const ON_STOP_SH: &str = r#"#!/bin/bash
 # Claude Code Stop Hook - Sends notification via MQTT
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+NOTIFY_ENV="${CLAUDE_NOTIFY_ENV_FILE:-$HOME/.claude/notify.env}"
+if [ -r "$NOTIFY_ENV" ]; then
+    while IFS='=' read -r KEY VALUE; do
+        if [[ "$KEY" =~ ^CLAUDE_NOTIFY_[A-Z_]+$ ]] && [ -z "${!KEY}" ]; then
+            export "$KEY=$VALUE"
+        fi
+    done < "$NOTIFY_ENV"
+fi
 
 HOST="${CLAUDE_NOTIFY_HOST:-__HOST__}"
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+HOST=$("$(dirname "$0")/resolve-host.sh" "$HOST")
 PORT="${CLAUDE_NOTIFY_PORT:-__PORT__}"
+SECRET="${CLAUDE_NOTIFY_SECRET:-__SECRET__}"
+ENCRYPTION_KEY="${CLAUDE_NOTIFY_KEY:-__ENCRYPTION_KEY__}"
+SOURCE_HOST="${CLAUDE_NOTIFY_SOURCE_HOST:-$(hostname)}"
 TOPIC="claude-code/events/stop"
 
 # Read input from stdin (Claude Code provides session info as JSON)
@@ -26,29 +46,74 @@ if [ -z "$CWD" ]; then
     CWD="${PWD}"
 fi
 
+# Excerpt of Claude's final message from the transcript (text blocks of the last assistant message)
+TRANSCRIPT=$(echo "$INPUT" | jq -r '.transcript_path // empty')
+LAST_MESSAGE=""
+if [ -r "$TRANSCRIPT" ]; then
+    LAST_MESSAGE=$(tail -n 200 "$TRANSCRIPT" | jq -rs \
+        '[.[] | select(.type == "assistant") | [.message.content[]? | select(.type == "text") | .text] | join("\n") | select(length > 0)] | last // "" | .[0:__LAST_MESSAGE_LENGTH__]' 2>/dev/null)
+fi
+
 # Create JSON payload
 PAYLOAD=$(cat <<EOF
 {
   "event": "stop",
   "cwd": "${CWD}",
   "session_id": "${SESSION_ID}",
+  "host": "${SOURCE_HOST}",
+  "transcript_path": "${TRANSCRIPT}",
+  "wsl_distro": "${WSL_DISTRO_NAME}",
+  "template_version": __TEMPLATE_VERSION__,
   "timestamp": "$(date -Iseconds)"
 }
 EOF
 )
+# The message can contain quotes and newlines, so jq adds it
+if [ -n "$LAST_MESSAGE" ]; then
+    PAYLOAD=$(printf '%s' "$PAYLOAD" | jq --arg last_message "$LAST_MESSAGE" '. + {last_message: $last_message}')
+fi
+
+# Encrypted payloads are sent with mqtt-publish (it also signs them)
+if [ -n "$ENCRYPTION_KEY" ]; then
+    printf '%s' "$PAYLOAD" | CLAUDE_NOTIFY_SECRET="$SECRET" CLAUDE_NOTIFY_KEY="$ENCRYPTION_KEY" \
+        mqtt-publish -h "$HOST" -p "$PORT" -t "$TOPIC" --stdin
+    exit $?
+fi
+
+# Sign payload when a shared secret is configured
+if [ -n "$SECRET" ]; then
+    SIGNATURE=$(printf '%s' "$PAYLOAD" | openssl dgst -sha256 -hmac "$SECRET" -r | cut -d' ' -f1)
+    PAYLOAD=$(jq -cn --arg payload "$PAYLOAD" --arg signature "$SIGNATURE" '{payload: $payload, signature: $signature}')
+fi
 
 # Send MQTT message
-mosquitto_pub -h "$HOST" -p "$PORT" -t "$TOPIC" -m "$PAYLOAD"
+mosquitto_pub -h "$HOST" -p "$PORT" -I "claude-code-" -u "claude-code-notify" -t "$TOPIC" -m "$PAYLOAD"
 "#;
 
 /// on-permission-request.sh template (mosquitto_pub version)
 /// Handles permission/approval requests from Claude Code
 pub const ON_PERMISSION_REQUEST_SH: &str = r#"#!/bin/bash
 # Claude Code Permission Request Hook - Sends approval request notifications via MQTT
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+NOTIFY_ENV="${CLAUDE_NOTIFY_ENV_FILE:-$HOME/.claude/notify.env}"
+if [ -r "$NOTIFY_ENV" ]; then
+    while IFS='=' read -r KEY VALUE; do
+        if [[ "$KEY" =~ ^CLAUDE_NOTIFY_[A-Z_]+$ ]] && [ -z "${!KEY}" ]; then
+            export "$KEY=$VALUE"
+        fi
+    done < "$NOTIFY_ENV"
+fi
 
 HOST="${CLAUDE_NOTIFY_HOST:-__HOST__}"
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+HOST=$("$(dirname "$0")/resolve-host.sh" "$HOST")
 PORT="${CLAUDE_NOTIFY_PORT:-__PORT__}"
+SECRET="${CLAUDE_NOTIFY_SECRET:-__SECRET__}"
+ENCRYPTION_KEY="${CLAUDE_NOTIFY_KEY:-__ENCRYPTION_KEY__}"
+SOURCE_HOST="${CLAUDE_NOTIFY_SOURCE_HOST:-$(hostname)}"
+RESPONSE_TIMEOUT="${CLAUDE_NOTIFY_RESPONSE_TIMEOUT:-__RESPONSE_TIMEOUT__}"
 TOPIC="claude-code/events/permission-request"
 
 # Read input from stdin (Claude Code provides session info as JSON)
@@ -66,30 +131,335 @@ if [ -z "$CWD" ]; then
     CWD="${PWD}"
 fi
 
+# Request ID lets the app answer this request (only when waiting for a response)
+REQUEST_ID=""
+if [ "$RESPONSE_TIMEOUT" -gt 0 ] 2>/dev/null; then
+    REQUEST_ID="$(date +%s%N)-$$"
+fi
+
 # Create JSON payload (use entire input as content)
 PAYLOAD=$(cat <<EOF
 {
   "event": "permission-request",
   "cwd": "${CWD}",
   "session_id": "${SESSION_ID}",
+  "host": "${SOURCE_HOST}",
+  "wsl_distro": "${WSL_DISTRO_NAME}",
+  "template_version": __TEMPLATE_VERSION__,
+  "request_id": "${REQUEST_ID}",
   "content": ${INPUT},
   "timestamp": "$(date -Iseconds)"
 }
 EOF
 )
 
-# Send MQTT message
-mosquitto_pub -h "$HOST" -p "$PORT" -t "$TOPIC" -m "$PAYLOAD"
+if [ -n "$ENCRYPTION_KEY" ]; then
+    # Encrypted payloads are sent with mqtt-publish (it also signs them)
+    printf '%s' "$PAYLOAD" | CLAUDE_NOTIFY_SECRET="$SECRET" CLAUDE_NOTIFY_KEY="$ENCRYPTION_KEY" \
+        mqtt-publish -h "$HOST" -p "$PORT" -t "$TOPIC" --stdin || exit $?
+else
+    # Sign payload when a shared secret is configured
+    if [ -n "$SECRET" ]; then
+        SIGNATURE=$(printf '%s' "$PAYLOAD" | openssl dgst -sha256 -hmac "$SECRET" -r | cut -d' ' -f1)
+        PAYLOAD=$(jq -cn --arg payload "$PAYLOAD" --arg signature "$SIGNATURE" '{payload: $payload, signature: $signature}')
+    fi
+
+    # Send MQTT message
+    mosquitto_pub -h "$HOST" -p "$PORT" -I "claude-code-" -u "claude-code-notify" -t "$TOPIC" -m "$PAYLOAD" || exit $?
+fi
+
+# Wait for Allow/Deny (or answers to AskUserQuestion) from the app and pass it back to Claude Code
+# (prints nothing on timeout, so Claude Code shows its own permission dialog)
+if [ -n "$REQUEST_ID" ]; then
+    printf '%s' "$INPUT" | "$(dirname "$0")/await-permission-response.sh" "$SESSION_ID" "$REQUEST_ID" "$RESPONSE_TIMEOUT"
+fi
+"#;
+
+/// await-permission-response.sh template (mosquitto_sub version)
+/// Waits for a permission decision published by the app on claude-code/response/<session_id>
+pub const AWAIT_PERMISSION_RESPONSE_SH: &str = r#"#!/bin/bash
+# Claude Code Notify - Waits for a permission decision made in the app
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+#
+# Usage: await-permission-response.sh <session_id> <request_id> [timeout_secs] < hook-input.json
+# Prints the PermissionRequest hook output when the app answers in time.
+# Prints nothing on timeout, so Claude Code falls back to its own permission dialog.
+# Answers to AskUserQuestion are added to the tool input read from stdin.
+
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+NOTIFY_ENV="${CLAUDE_NOTIFY_ENV_FILE:-$HOME/.claude/notify.env}"
+if [ -r "$NOTIFY_ENV" ]; then
+    while IFS='=' read -r KEY VALUE; do
+        if [[ "$KEY" =~ ^CLAUDE_NOTIFY_[A-Z_]+$ ]] && [ -z "${!KEY}" ]; then
+            export "$KEY=$VALUE"
+        fi
+    done < "$NOTIFY_ENV"
+fi
+
+HOST="${CLAUDE_NOTIFY_HOST:-__HOST__}"
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+HOST=$("$(dirname "$0")/resolve-host.sh" "$HOST")
+PORT="${CLAUDE_NOTIFY_PORT:-__PORT__}"
+SECRET="${CLAUDE_NOTIFY_SECRET:-__SECRET__}"
+
+SESSION_ID="$1"
+REQUEST_ID="$2"
+TIMEOUT="${3:-__RESPONSE_TIMEOUT__}"
+
+if [ -z "$SESSION_ID" ] || [ -z "$REQUEST_ID" ] || ! [ "$TIMEOUT" -gt 0 ] 2>/dev/null; then
+    exit 0
+fi
+
+# Hook input from Claude Code (needed to return answers to AskUserQuestion)
+HOOK_INPUT=""
+if [ ! -t 0 ]; then
+    HOOK_INPUT=$(cat)
+fi
+if [ -z "$HOOK_INPUT" ]; then
+    HOOK_INPUT="{}"
+fi
+
+# MQTT wildcards and level separators are replaced in the same way as the app
+TOPIC="claude-code/response/$(printf '%s' "$SESSION_ID" | tr '+#/' '___')"
+DEADLINE=$(( $(date +%s) + TIMEOUT ))
+
+while true; do
+    REMAINING=$(( DEADLINE - $(date +%s) ))
+    if [ "$REMAINING" -le 0 ]; then
+        exit 0
+    fi
+
+    MESSAGE=$(mosquitto_sub -h "$HOST" -p "$PORT" -I "claude-code-" -u "claude-code-notify" \
+        -t "$TOPIC" -C 1 -W "$REMAINING" 2>/dev/null) || exit 0
+
+    # Responses approve commands, so only signed ones are accepted when a secret is configured
+    if [ -n "$SECRET" ]; then
+        PAYLOAD=$(echo "$MESSAGE" | jq -r '.payload // empty')
+        SIGNATURE=$(echo "$MESSAGE" | jq -r '.signature // empty')
+        EXPECTED=$(printf '%s' "$PAYLOAD" | openssl dgst -sha256 -hmac "$SECRET" -r | cut -d' ' -f1)
+        if [ -z "$SIGNATURE" ] || [ "$SIGNATURE" != "$EXPECTED" ]; then
+            continue
+        fi
+    else
+        PAYLOAD="$MESSAGE"
+    fi
+
+    # Ignore answers to other requests from the same session
+    if [ "$(echo "$PAYLOAD" | jq -r '.request_id // empty')" != "$REQUEST_ID" ]; then
+        continue
+    fi
+
+    DECISION=$(echo "$PAYLOAD" | jq -r '.decision // empty')
+    ANSWERS=$(echo "$PAYLOAD" | jq -c '.answers // empty')
+    if [ "$DECISION" = "allow" ] && [ -n "$ANSWERS" ]; then
+        # AskUserQuestion: allow with the selected answers added to the tool input
+        echo "$HOOK_INPUT" | jq -c --argjson answers "$ANSWERS" '{
+            hookSpecificOutput: {
+                hookEventName: "PermissionRequest",
+                decision: {
+                    behavior: "allow",
+                    updatedInput: ((.tool_input // {}) + {answers: $answers})
+                }
+            }
+        }'
+        exit 0
+    fi
+
+    case "$DECISION" in
+        allow|deny)
+            jq -cn --arg behavior "$DECISION" '{
+                hookSpecificOutput: {
+                    hookEventName: "PermissionRequest",
+                    decision: ({behavior: $behavior}
+                        + (if $behavior == "deny" then {message: "Denied from Claude Code Notify"} else {} end))
+                }
+            }'
+            ;;
+    esac
+    exit 0
+done
+"#;
+
+/// control-listener.sh template (mosquitto_sub version)
+/// Runs session commands published by the app on claude-code/control/<session_id>/<command>
+/// and replies on claude-code/control-reply/<session_id> (started in the background by statusline.sh)
+pub const CONTROL_LISTENER_SH: &str = r#"#!/bin/bash
+# Claude Code Notify - Runs session commands sent from the app
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+#
+# Usage: control-listener.sh <session_id> <claude_pid>
+# Listens on claude-code/control/<session_id>/<command> until the Claude Code process exits:
+#   pause  : suspends <claude_pid> (SIGSTOP)
+#   resume : continues a suspended <claude_pid> (SIGCONT)
+#   stop   : interrupts the current turn (SIGINT, same as pressing Esc)
+#   ping   : does nothing (checks that the listener is running)
+# Each command is answered on claude-code/control-reply/<session_id>:
+#   {"session_id": "...", "command": "stop", "ok": true, "host": "...", "timestamp": "..."}
+
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+NOTIFY_ENV="${CLAUDE_NOTIFY_ENV_FILE:-$HOME/.claude/notify.env}"
+if [ -r "$NOTIFY_ENV" ]; then
+    while IFS='=' read -r KEY VALUE; do
+        if [[ "$KEY" =~ ^CLAUDE_NOTIFY_[A-Z_]+$ ]] && [ -z "${!KEY}" ]; then
+            export "$KEY=$VALUE"
+        fi
+    done < "$NOTIFY_ENV"
+fi
+
+HOST="${CLAUDE_NOTIFY_HOST:-__HOST__}"
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+HOST=$("$(dirname "$0")/resolve-host.sh" "$HOST")
+PORT="${CLAUDE_NOTIFY_PORT:-__PORT__}"
+SECRET="${CLAUDE_NOTIFY_SECRET:-__SECRET__}"
+ENCRYPTION_KEY="${CLAUDE_NOTIFY_KEY:-__ENCRYPTION_KEY__}"
+SOURCE_HOST="${CLAUDE_NOTIFY_SOURCE_HOST:-$(hostname)}"
+
+SESSION_ID="$1"
+CLAUDE_PID="$2"
+
+if [ -z "$SESSION_ID" ] || ! kill -0 "$CLAUDE_PID" 2>/dev/null; then
+    exit 0
+fi
+
+# MQTT wildcards and level separators are replaced in the same way as the app
+TOPIC_SESSION=$(printf '%s' "$SESSION_ID" | tr '+#/' '___')
+TOPIC="claude-code/control/${TOPIC_SESSION}"
+REPLY_TOPIC="claude-code/control-reply/${TOPIC_SESSION}"
+
+# Publish a reply (signed / encrypted when configured, in the same way as statusline.sh)
+reply() {
+    local payload
+    payload=$(jq -cn --arg session_id "$SESSION_ID" --arg command "$1" --argjson ok "$2" --arg host "$SOURCE_HOST" \
+        --arg timestamp "$(date -Iseconds)" \
+        '{session_id: $session_id, command: $command, ok: $ok, host: $host, timestamp: $timestamp}')
+    if [ -n "$ENCRYPTION_KEY" ]; then
+        printf '%s' "$payload" | CLAUDE_NOTIFY_SECRET="$SECRET" CLAUDE_NOTIFY_KEY="$ENCRYPTION_KEY" \
+            mqtt-publish -h "$HOST" -p "$PORT" -t "$REPLY_TOPIC" --stdin 2>/dev/null
+        return
+    fi
+    if [ -n "$SECRET" ]; then
+        local signature
+        signature=$(printf '%s' "$payload" | openssl dgst -sha256 -hmac "$SECRET" -r | cut -d' ' -f1)
+        payload=$(jq -cn --arg payload "$payload" --arg signature "$signature" '{payload: $payload, signature: $signature}')
+    fi
+    mosquitto_pub -h "$HOST" -p "$PORT" -I "claude-code-" -u "claude-code-notify" -t "$REPLY_TOPIC" -m "$payload" 2>/dev/null
+}
+
+while kill -0 "$CLAUDE_PID" 2>/dev/null; do
+    # Wake up every minute to check whether Claude Code is still running
+    MESSAGE=$(mosquitto_sub -h "$HOST" -p "$PORT" -I "claude-code-" -u "claude-code-notify" \
+        -t "$TOPIC/+" -C 1 -W 60 2>/dev/null) || { sleep 1; continue; }
+
+    # Commands can stop the session, so only signed ones are accepted when a secret is configured
+    if [ -n "$SECRET" ]; then
+        PAYLOAD=$(echo "$MESSAGE" | jq -r '.payload // empty')
+        SIGNATURE=$(echo "$MESSAGE" | jq -r '.signature // empty')
+        EXPECTED=$(printf '%s' "$PAYLOAD" | openssl dgst -sha256 -hmac "$SECRET" -r | cut -d' ' -f1)
+        if [ -z "$SIGNATURE" ] || [ "$SIGNATURE" != "$EXPECTED" ]; then
+            continue
+        fi
+    else
+        PAYLOAD="$MESSAGE"
+    fi
+
+    if [ "$(echo "$PAYLOAD" | jq -r '.session_id // empty')" != "$SESSION_ID" ]; then
+        continue
+    fi
+
+    COMMAND=$(echo "$PAYLOAD" | jq -r '.command // empty')
+    case "$COMMAND" in
+        pause)  SIGNAL=STOP ;;
+        resume) SIGNAL=CONT ;;
+        stop)   SIGNAL=INT ;;
+        ping)   reply ping true; continue ;;
+        *)      continue ;;
+    esac
+    if kill -"$SIGNAL" "$CLAUDE_PID" 2>/dev/null; then
+        reply "$COMMAND" true
+    else
+        reply "$COMMAND" false
+    fi
+done
+"#;
+
+/// resolve-host.sh template
+/// Prints the address of the machine running the app: the Docker host when Claude Code runs
+/// in a devcontainer / Docker container, or the broker advertised via mDNS
+pub const RESOLVE_HOST_SH: &str = r#"#!/bin/bash
+# Claude Code Notify - Resolves the host to send notifications to
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+#
+# Usage: resolve-host.sh <host>
+#   "auto"    : detect the Docker host (devcontainer preset, or CLAUDE_NOTIFY_HOST=auto)
+#   otherwise : with mDNS discovery, the broker advertised as _claude-notify._tcp
+#               (needs avahi-browse), falling back to <host>
+# Discovery can be overridden: CLAUDE_NOTIFY_DISCOVERY (mdns / off)
+# Route file can be overridden for testing: CLAUDE_NOTIFY_ROUTE_FILE
+
+HOST="${1:-auto}"
+DISCOVERY="${CLAUDE_NOTIFY_DISCOVERY:-__DISCOVERY__}"
+ROUTE_FILE="${CLAUDE_NOTIFY_ROUTE_FILE:-/proc/net/route}"
+# Browsing takes up to a second, so a discovered address is reused for a few minutes
+CACHE_FILE="${XDG_RUNTIME_DIR:-/tmp}/claude-notify-host-$(id -u)"
+CACHE_MINUTES=5
+
+if [ "$HOST" != "auto" ]; then
+    if [ "$DISCOVERY" = "mdns" ]; then
+        if [ -n "$(find "$CACHE_FILE" -mmin -"$CACHE_MINUTES" 2>/dev/null)" ]; then
+            ADDRESS=$(cat "$CACHE_FILE")
+        elif command -v avahi-browse >/dev/null 2>&1; then
+            # Resolved entries: =;interface;protocol;name;type;domain;hostname;address;port;txt
+            ADDRESS=$(timeout 3 avahi-browse -prt _claude-notify._tcp 2>/dev/null \
+                | awk -F';' '$1 == "=" && $3 == "IPv4" { print $8; exit }')
+            if [ -n "$ADDRESS" ]; then
+                echo "$ADDRESS" > "$CACHE_FILE"
+            fi
+        fi
+    fi
+    echo "${ADDRESS:-$HOST}"
+    exit 0
+fi
+
+# Docker Desktop (or --add-host=host.docker.internal:host-gateway)
+ADDRESS=$(getent hosts host.docker.internal 2>/dev/null | awk '{ print $1; exit }')
+
+# Otherwise the container's default gateway (the Docker bridge on Linux)
+if [ -z "$ADDRESS" ] && [ -r "$ROUTE_FILE" ]; then
+    while read -r _ DESTINATION GATEWAY _; do
+        if [ "$DESTINATION" = "00000000" ] && [ "$GATEWAY" != "00000000" ]; then
+            # Little-endian hex (0100A8C0 -> 192.168.0.1)
+            ADDRESS=$(printf '%d.%d.%d.%d' "0x${GATEWAY:6:2}" "0x${GATEWAY:4:2}" "0x${GATEWAY:2:2}" "0x${GATEWAY:0:2}")
+            break
+        fi
+    done < "$ROUTE_FILE"
+fi
+
+echo "${ADDRESS:-host.docker.internal}"
 "#;
 
 /// on-notification.sh template (mosquitto_pub version)
 /// Handles elicitation dialogs from Claude Code (user input requests)
 pub const ON_NOTIFICATION_SH: &str = r#"#!/bin/bash
 # Claude Code Notification Hook - Sends elicitation dialog notifications via MQTT
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+NOTIFY_ENV="${CLAUDE_NOTIFY_ENV_FILE:-$HOME/.claude/notify.env}"
+if [ -r "$NOTIFY_ENV" ]; then
+    while IFS='=' read -r KEY VALUE; do
+        if [[ "$KEY" =~ ^CLAUDE_NOTIFY_[A-Z_]+$ ]] && [ -z "${!KEY}" ]; then
+            export "$KEY=$VALUE"
+        fi
+    done < "$NOTIFY_ENV"
+fi
 
 HOST="${CLAUDE_NOTIFY_HOST:-__HOST__}"
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+HOST=$("$(dirname "$0")/resolve-host.sh" "$HOST")
 PORT="${CLAUDE_NOTIFY_PORT:-__PORT__}"
+SECRET="${CLAUDE_NOTIFY_SECRET:-__SECRET__}"
+ENCRYPTION_KEY="${CLAUDE_NOTIFY_KEY:-__ENCRYPTION_KEY__}"
+SOURCE_HOST="${CLAUDE_NOTIFY_SOURCE_HOST:-$(hostname)}"
 TOPIC="claude-code/events/notification"
 
 # Read input from stdin (Claude Code provides session info as JSON)
@@ -113,24 +483,56 @@ PAYLOAD=$(cat <<EOF
   "event": "notification",
   "cwd": "${CWD}",
   "session_id": "${SESSION_ID}",
+  "host": "${SOURCE_HOST}",
+  "wsl_distro": "${WSL_DISTRO_NAME}",
+  "template_version": __TEMPLATE_VERSION__,
   "content": ${INPUT},
   "timestamp": "$(date -Iseconds)"
 }
 EOF
 )
 
+# Encrypted payloads are sent with mqtt-publish (it also signs them)
+if [ -n "$ENCRYPTION_KEY" ]; then
+    printf '%s' "$PAYLOAD" | CLAUDE_NOTIFY_SECRET="$SECRET" CLAUDE_NOTIFY_KEY="$ENCRYPTION_KEY" \
+        mqtt-publish -h "$HOST" -p "$PORT" -t "$TOPIC" --stdin
+    exit $?
+fi
+
+# Sign payload when a shared secret is configured
+if [ -n "$SECRET" ]; then
+    SIGNATURE=$(printf '%s' "$PAYLOAD" | openssl dgst -sha256 -hmac "$SECRET" -r | cut -d' ' -f1)
+    PAYLOAD=$(jq -cn --arg payload "$PAYLOAD" --arg signature "$SIGNATURE" '{payload: $payload, signature: $signature}')
+fi
+
 # Send MQTT message
-mosquitto_pub -h "$HOST" -p "$PORT" -t "$TOPIC" -m "$PAYLOAD"
+mosquitto_pub -h "$HOST" -p "$PORT" -I "claude-code-" -u "claude-code-notify" -t "$TOPIC" -m "$PAYLOAD"
 "#;
 
 /// statusline.sh template (mosquitto_pub version)
 /// Sends periodic status updates from Claude Code and outputs status text
 pub const STATUSLINE_SH: &str = r#"#!/bin/bash
 # Claude Code Statusline - Sends status updates via MQTT and outputs status
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+NOTIFY_ENV="${CLAUDE_NOTIFY_ENV_FILE:-$HOME/.claude/notify.env}"
+if [ -r "$NOTIFY_ENV" ]; then
+    while IFS='=' read -r KEY VALUE; do
+        if [[ "$KEY" =~ ^CLAUDE_NOTIFY_[A-Z_]+$ ]] && [ -z "${!KEY}" ]; then
+            export "$KEY=$VALUE"
+        fi
+    done < "$NOTIFY_ENV"
+fi
 
 HOST="${CLAUDE_NOTIFY_HOST:-__HOST__}"
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+HOST=$("$(dirname "$0")/resolve-host.sh" "$HOST")
 PORT="${CLAUDE_NOTIFY_PORT:-__PORT__}"
+SECRET="${CLAUDE_NOTIFY_SECRET:-__SECRET__}"
+ENCRYPTION_KEY="${CLAUDE_NOTIFY_KEY:-__ENCRYPTION_KEY__}"
+SOURCE_HOST="${CLAUDE_NOTIFY_SOURCE_HOST:-$(hostname)}"
+HEARTBEAT_INTERVAL="${CLAUDE_NOTIFY_HEARTBEAT_INTERVAL:-__HEARTBEAT_INTERVAL__}"
 
 # Read the statusline JSON from stdin
 INPUT=$(cat)
@@ -154,6 +556,7 @@ LINES_REMOVED=$(echo "$INPUT" | jq -r '.cost.total_lines_removed // 0')
 PAYLOAD=$(cat <<EOF
 {
   "session_id": "${SESSION_ID}",
+  "host": "${SOURCE_HOST}",
   "cwd": "$(echo "$INPUT" | jq -r '.cwd // empty')",
   "status": {
     "state": "active",
@@ -167,8 +570,59 @@ PAYLOAD=$(cat <<EOF
 EOF
 )
 
+# Publish a payload (signed / encrypted when configured); extra arguments are passed to the publisher
+publish() {
+    local topic="$1" payload="$2"
+    shift 2
+    if [ -n "$ENCRYPTION_KEY" ]; then
+        # Encrypted payloads are sent with mqtt-publish (it also signs them)
+        printf '%s' "$payload" | CLAUDE_NOTIFY_SECRET="$SECRET" CLAUDE_NOTIFY_KEY="$ENCRYPTION_KEY" \
+            mqtt-publish -h "$HOST" -p "$PORT" -t "$topic" "$@" --stdin 2>/dev/null
+    else
+        if [ -n "$SECRET" ]; then
+            local signature
+            signature=$(printf '%s' "$payload" | openssl dgst -sha256 -hmac "$SECRET" -r | cut -d' ' -f1)
+            payload=$(jq -cn --arg payload "$payload" --arg signature "$signature" '{payload: $payload, signature: $signature}')
+        fi
+        mosquitto_pub -h "$HOST" -p "$PORT" -I "claude-code-" -u "claude-code-notify" -t "$topic" "$@" -m "$payload" 2>/dev/null
+    fi
+}
+
 # Send MQTT message in background (don't block statusline output)
-mosquitto_pub -h "$HOST" -p "$PORT" -t "$TOPIC" -r -m "$PAYLOAD" 2>/dev/null &
+publish "$TOPIC" "$PAYLOAD" -r &
+
+# Heartbeat: one background loop per user publishes every HEARTBEAT_INTERVAL seconds while Claude Code runs,
+# then sends "active": false and exits (the app warns when an active host goes silent)
+HEARTBEAT_PID_FILE="${TMPDIR:-/tmp}/claude-notify-heartbeat-$(id -u).pid"
+if [ "$HEARTBEAT_INTERVAL" -gt 0 ] 2>/dev/null && ! kill -0 "$(cat "$HEARTBEAT_PID_FILE" 2>/dev/null)" 2>/dev/null; then
+    (
+        HEARTBEAT_TOPIC="claude-code/heartbeat/${SOURCE_HOST//[+#\/]/_}"
+        while :; do
+            ACTIVE=false
+            pgrep -x claude > /dev/null && ACTIVE=true
+            publish "$HEARTBEAT_TOPIC" "{\"host\": \"${SOURCE_HOST}\", \"active\": ${ACTIVE}, \"timestamp\": \"$(date -Iseconds)\"}"
+            [ "$ACTIVE" = true ] || break
+            sleep "$HEARTBEAT_INTERVAL"
+        done
+        rm -f "$HEARTBEAT_PID_FILE"
+    ) < /dev/null > /dev/null 2>&1 &
+    echo $! > "$HEARTBEAT_PID_FILE"
+fi
+
+# Session control: one background listener per session runs pause / resume / stop / ping commands from the app
+# (the statusline runs as a descendant of the Claude Code process)
+SESSION_CONTROL="${CLAUDE_NOTIFY_SESSION_CONTROL:-__SESSION_CONTROL__}"
+CONTROL_PID_FILE="${TMPDIR:-/tmp}/claude-notify-control-$(id -u)-${SESSION_ID//[^A-Za-z0-9_-]/_}.pid"
+if [ "$SESSION_CONTROL" = "on" ] && ! kill -0 "$(cat "$CONTROL_PID_FILE" 2>/dev/null)" 2>/dev/null; then
+    CLAUDE_PID=$PPID
+    while [ "$CLAUDE_PID" -gt 1 ] 2>/dev/null && [ "$(ps -o comm= -p "$CLAUDE_PID" 2>/dev/null)" != "claude" ]; do
+        CLAUDE_PID=$(ps -o ppid= -p "$CLAUDE_PID" 2>/dev/null | tr -d ' ')
+    done
+    if [ "$CLAUDE_PID" -gt 1 ] 2>/dev/null; then
+        "$(dirname "$0")/control-listener.sh" "$SESSION_ID" "$CLAUDE_PID" < /dev/null > /dev/null 2>&1 &
+        echo $! > "$CONTROL_PID_FILE"
+    fi
+fi
 
 # Output status text for Claude Code statusline display
 printf "[%s] $%.4f | Ctx: %.0f%% | +%d/-%d" "$MODEL" "$COST" "$CONTEXT" "$LINES_ADDED" "$LINES_REMOVED"
@@ -183,7 +637,7 @@ pub const CLAUDE_SETTINGS_SNIPPET: &str = r#"{
         "hooks": [
           {
             "type": "command",
-            "command": "__SCRIPTS_DIR__/on-stop.sh"
+            "command": "__SCRIPTS_DIR__/on-stop.__HOOK_EXT__"
           }
         ]
       }
@@ -194,7 +648,7 @@ pub const CLAUDE_SETTINGS_SNIPPET: &str = r#"{
         "hooks": [
           {
             "type": "command",
-            "command": "__SCRIPTS_DIR__/on-permission-request.sh"
+            "command": "__SCRIPTS_DIR__/on-permission-request.__HOOK_EXT__"
           }
         ]
       }
@@ -205,7 +659,7 @@ pub const CLAUDE_SETTINGS_SNIPPET: &str = r#"{
         "hooks": [
           {
             "type": "command",
-            "command": "__SCRIPTS_DIR__/on-notification.sh"
+            "command": "__SCRIPTS_DIR__/on-notification.__HOOK_EXT__"
           }
         ]
       }
@@ -223,7 +677,7 @@ pub const CLAUDE_SETTINGS_SNIPPET: &str = r#"{
 /// install.sh template - Automated installer for Claude Code side
 pub const INSTALL_SH: &str = r#"#!/bin/bash
 # Claude Code Notify - インストーラスクリプト
-# Claude Code Notify により生成
+# Claude Code Notify により生成（テンプレート v__TEMPLATE_VERSION__）
 #
 # 使用方法: ./install.sh [--with-statusline]
 
@@ -234,6 +688,8 @@ HOST="__HOST__"
 PORT="__PORT__"
 SCRIPTS_DIR="${HOME}/.claude-notify-scripts"
 SETTINGS_FILE="${HOME}/.claude/settings.json"
+# フックのスクリプトの拡張子（sh: bash, fish: fish, nu: Nushell）
+HOOK_EXT="__HOOK_EXT__"
 
 # 出力用カラー
 RED='\033[0;31m'
@@ -274,6 +730,58 @@ if ! command -v jq &> /dev/null; then
 fi
 echo -e "  ${GREEN}✓${NC} jq"
 
+# fish・Nushell 用のフックはそのシェルで実行する
+case "$HOOK_EXT" in
+    fish) HOOK_SHELL=fish ;;
+    nu) HOOK_SHELL=nu ;;
+    *) HOOK_SHELL="" ;;
+esac
+if [ -n "$HOOK_SHELL" ]; then
+    if ! command -v "$HOOK_SHELL" &> /dev/null; then
+        echo -e "${RED}エラー: ${HOOK_SHELL} が見つかりません（フックの実行に必要）${NC}"
+        exit 1
+    fi
+    echo -e "  ${GREEN}✓${NC} ${HOOK_SHELL}"
+fi
+
+# ペイロードを暗号化する場合は mqtt-publish が必要
+ENCRYPTION_KEY="__ENCRYPTION_KEY__"
+if [ -n "$ENCRYPTION_KEY" ]; then
+    if ! command -v mqtt-publish &> /dev/null; then
+        echo -e "${RED}エラー: mqtt-publish が見つかりません（ペイロード暗号化に必要）${NC}"
+        echo "インストール: cargo install --git https://github.com/hexylab/claude-code-notify mqtt-publish"
+        exit 1
+    fi
+    echo -e "  ${GREEN}✓${NC} mqtt-publish"
+fi
+
+# ペイロード署名を使う場合は openssl が必要（暗号化時は mqtt-publish が署名する）
+SECRET="__SECRET__"
+if [ -n "$SECRET" ] && [ -z "$ENCRYPTION_KEY" ]; then
+    if ! command -v openssl &> /dev/null; then
+        echo -e "${RED}エラー: openssl が見つかりません（ペイロード署名に必要）${NC}"
+        echo "インストール: sudo apt install openssl"
+        exit 1
+    fi
+    echo -e "  ${GREEN}✓${NC} openssl"
+fi
+
+# アプリからの承認依頼への応答を待つ場合は mosquitto_sub が必要
+RESPONSE_TIMEOUT="__RESPONSE_TIMEOUT__"
+if [ "$RESPONSE_TIMEOUT" -gt 0 ] 2>/dev/null; then
+    if ! command -v mosquitto_sub &> /dev/null; then
+        echo -e "${RED}エラー: mosquitto_sub が見つかりません（承認依頼への応答に必要）${NC}"
+        echo "インストール: sudo apt install mosquitto-clients"
+        exit 1
+    fi
+    echo -e "  ${GREEN}✓${NC} mosquitto_sub"
+    if [ -n "$SECRET" ] && ! command -v openssl &> /dev/null; then
+        echo -e "${RED}エラー: openssl が見つかりません（応答の署名検証に必要）${NC}"
+        echo "インストール: sudo apt install openssl"
+        exit 1
+    fi
+fi
+
 # スクリプトのソースディレクトリを取得
 SCRIPT_SOURCE_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 
@@ -283,15 +791,20 @@ echo -e "${YELLOW}スクリプトを ${SCRIPTS_DIR} にインストール中...$
 mkdir -p "$SCRIPTS_DIR"
 
 # スクリプトをコピー
-cp "$SCRIPT_SOURCE_DIR/on-stop.sh" "$SCRIPTS_DIR/"
-cp "$SCRIPT_SOURCE_DIR/on-permission-request.sh" "$SCRIPTS_DIR/"
-cp "$SCRIPT_SOURCE_DIR/on-notification.sh" "$SCRIPTS_DIR/"
+cp "$SCRIPT_SOURCE_DIR/on-stop.${HOOK_EXT}" "$SCRIPTS_DIR/"
+cp "$SCRIPT_SOURCE_DIR/on-permission-request.${HOOK_EXT}" "$SCRIPTS_DIR/"
+cp "$SCRIPT_SOURCE_DIR/await-permission-response.sh" "$SCRIPTS_DIR/"
+cp "$SCRIPT_SOURCE_DIR/control-listener.sh" "$SCRIPTS_DIR/"
+cp "$SCRIPT_SOURCE_DIR/resolve-host.sh" "$SCRIPTS_DIR/"
+cp "$SCRIPT_SOURCE_DIR/on-notification.${HOOK_EXT}" "$SCRIPTS_DIR/"
 if [ "$WITH_STATUSLINE" = true ]; then
     cp "$SCRIPT_SOURCE_DIR/statusline.sh" "$SCRIPTS_DIR/"
 fi
+# ZIP を削除した後もアンインストールできるようにする
+cp "$SCRIPT_SOURCE_DIR/uninstall.sh" "$SCRIPTS_DIR/"
 
 # 実行権限を設定
-chmod +x "$SCRIPTS_DIR"/*.sh
+chmod +x "$SCRIPTS_DIR"/*.sh "$SCRIPTS_DIR"/on-*."${HOOK_EXT}"
 echo -e "  ${GREEN}✓${NC} スクリプトをインストールしました"
 
 # settings.json を更新
@@ -310,6 +823,15 @@ else
     EXISTING_SETTINGS="{}"
 fi
 
+# マージできない設定は変更しない（手で直してから再実行してもらう）
+if ! echo "$EXISTING_SETTINGS" | jq -e 'type == "object" and ((.hooks // {}) | type == "object")
+        and ([(.hooks // {}) | .Stop, .Notification, .PermissionRequest | select(. != null) | type == "array"] | all)' \
+        > /dev/null 2>&1; then
+    echo -e "${RED}エラー: ${SETTINGS_FILE} を読み取れないか、hooks の形式が想定と異なります${NC}"
+    echo "JSON の構文を確認してから、もう一度実行してください（設定は変更していません）"
+    exit 1
+fi
+
 # フック設定を作成
 HOOKS_CONFIG=$(cat <<EOF
 {
@@ -319,7 +841,7 @@ HOOKS_CONFIG=$(cat <<EOF
       "hooks": [
         {
           "type": "command",
-          "command": "${SCRIPTS_DIR}/on-stop.sh"
+          "command": "${SCRIPTS_DIR}/on-stop.${HOOK_EXT}"
         }
       ]
     }
@@ -330,7 +852,7 @@ HOOKS_CONFIG=$(cat <<EOF
       "hooks": [
         {
           "type": "command",
-          "command": "${SCRIPTS_DIR}/on-permission-request.sh"
+          "command": "${SCRIPTS_DIR}/on-permission-request.${HOOK_EXT}"
         }
       ]
     }
@@ -341,7 +863,7 @@ HOOKS_CONFIG=$(cat <<EOF
       "hooks": [
         {
           "type": "command",
-          "command": "${SCRIPTS_DIR}/on-notification.sh"
+          "command": "${SCRIPTS_DIR}/on-notification.${HOOK_EXT}"
         }
       ]
     }
@@ -374,7 +896,7 @@ STOP_HOOK=$(cat <<EOF
   "hooks": [
     {
       "type": "command",
-      "command": "${SCRIPTS_DIR}/on-stop.sh"
+      "command": "${SCRIPTS_DIR}/on-stop.${HOOK_EXT}"
     }
   ]
 }
@@ -387,7 +909,7 @@ NOTIFICATION_HOOK=$(cat <<EOF
   "hooks": [
     {
       "type": "command",
-      "command": "${SCRIPTS_DIR}/on-notification.sh"
+      "command": "${SCRIPTS_DIR}/on-notification.${HOOK_EXT}"
     }
   ]
 }
@@ -400,7 +922,7 @@ PERMISSION_HOOK=$(cat <<EOF
   "hooks": [
     {
       "type": "command",
-      "command": "${SCRIPTS_DIR}/on-permission-request.sh"
+      "command": "${SCRIPTS_DIR}/on-permission-request.${HOOK_EXT}"
     }
   ]
 }
@@ -429,15 +951,25 @@ MERGED_SETTINGS=$(echo "$EXISTING_SETTINGS" | jq \
     }
 ')
 
-# statusline が要求された場合は追加
+# statusline が要求された場合は追加（別のコマンドが設定済みの場合は競合として残す）
 if [ "$WITH_STATUSLINE" = true ]; then
-    MERGED_SETTINGS=$(echo "$MERGED_SETTINGS" | jq --arg cmd "${SCRIPTS_DIR}/statusline.sh" '
-      .statusLine = {
-        "type": "command",
-        "command": $cmd
-      }
-    ')
-    echo -e "  ${GREEN}✓${NC} statusline を設定しました"
+    EXISTING_STATUSLINE=$(echo "$EXISTING_SETTINGS" | jq -r '.statusLine.command // empty')
+    if [ -n "$EXISTING_STATUSLINE" ] && [[ "$EXISTING_STATUSLINE" != *"$SCRIPTS_DIR"* ]]; then
+        echo -e "  ${YELLOW}!${NC} 競合: statusLine は既に設定されています（${EXISTING_STATUSLINE}）"
+        echo "    変更していません。使う場合は statusLine.command を ${SCRIPTS_DIR}/statusline.sh にしてください"
+    else
+        MERGED_SETTINGS=$(echo "$MERGED_SETTINGS" | jq --arg cmd "${SCRIPTS_DIR}/statusline.sh" '
+          .statusLine = {
+            "type": "command",
+            "command": $cmd
+          }
+        ')
+        echo -e "  ${GREEN}✓${NC} statusline を設定しました"
+    fi
+fi
+
+if [ "$(echo "$MERGED_SETTINGS" | jq '.disableAllHooks == true')" = true ]; then
+    echo -e "  ${YELLOW}!${NC} 競合: disableAllHooks が true のため、フックは実行されません"
 fi
 
 # マージした設定を書き込み
@@ -449,11 +981,18 @@ echo ""
 echo -e "${YELLOW}環境変数（オプション）:${NC}"
 echo "  export CLAUDE_NOTIFY_HOST=\"$HOST\""
 echo "  export CLAUDE_NOTIFY_PORT=\"$PORT\""
+echo "  export CLAUDE_NOTIFY_SOURCE_HOST=\"$(hostname)\"  # 通知に表示するホスト名"
 echo ""
 
 # 接続テスト
 echo -e "${YELLOW}Windows への接続をテスト中...${NC}"
-if mosquitto_pub -h "$HOST" -p "$PORT" -t "claude-code/test" -m "install-test" 2>/dev/null; then
+# コンテナ内・mDNS での検出はフックと同じ方法で接続先を決める
+RESOLVED_HOST=$("$SCRIPTS_DIR/resolve-host.sh" "$HOST")
+if [ "$RESOLVED_HOST" != "$HOST" ]; then
+    HOST="$RESOLVED_HOST"
+    echo "  接続先（自動検出）: ${HOST}"
+fi
+if mosquitto_pub -h "$HOST" -p "$PORT" -I "claude-code-" -u "claude-code-notify" -t "claude-code/test" -m "install-test" 2>/dev/null; then
     echo -e "  ${GREEN}✓${NC} 接続成功"
 else
     echo -e "  ${RED}✗${NC} 接続失敗 - ファイアウォール設定を確認してください"
@@ -472,6 +1011,115 @@ if [ "$WITH_STATUSLINE" = true ]; then
 fi
 echo ""
 echo "変更を反映するには Claude Code を再起動してください。"
+echo "アンインストール: ${SCRIPTS_DIR}/uninstall.sh"
+echo -e "${GREEN}════════════════════════════════════════${NC}"
+"#;
+
+/// uninstall.sh template - Removes the hooks installed by install.sh
+pub const UNINSTALL_SH: &str = r#"#!/bin/bash
+# Claude Code Notify - アンインストーラスクリプト
+# Claude Code Notify により生成（テンプレート v__TEMPLATE_VERSION__）
+#
+# 使用方法: ./uninstall.sh [--restore-backup]
+#   --restore-backup: 設定を最初のインストール前のバックアップに戻す（それ以降の変更も失われます）
+
+set -e
+
+SCRIPTS_DIR="${HOME}/.claude-notify-scripts"
+SETTINGS_FILE="${HOME}/.claude/settings.json"
+SCRIPTS="on-stop.sh on-permission-request.sh await-permission-response.sh control-listener.sh resolve-host.sh on-notification.sh statusline.sh uninstall.sh"
+SCRIPTS="$SCRIPTS on-stop.fish on-permission-request.fish on-notification.fish"
+SCRIPTS="$SCRIPTS on-stop.nu on-permission-request.nu on-notification.nu"
+
+# 出力用カラー
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+YELLOW='\033[1;33m'
+NC='\033[0m' # No Color
+
+echo -e "${GREEN}╔════════════════════════════════════════╗${NC}"
+echo -e "${GREEN}║  Claude Code Notify アンインストーラ   ║${NC}"
+echo -e "${GREEN}╚════════════════════════════════════════╝${NC}"
+echo ""
+
+# 引数の解析
+RESTORE_BACKUP=false
+for arg in "$@"; do
+    case $arg in
+        --restore-backup)
+            RESTORE_BACKUP=true
+            ;;
+    esac
+done
+
+# settings.json を元に戻す
+echo -e "${YELLOW}Claude Code の設定を元に戻しています...${NC}"
+
+# install.sh が最初に作ったバックアップ（インストール前の設定）
+OLDEST_BACKUP=$(ls -1 "${SETTINGS_FILE}".backup.* 2>/dev/null | sort | head -n 1 || true)
+
+if [ ! -f "$SETTINGS_FILE" ]; then
+    echo -e "  ${YELLOW}-${NC} ${SETTINGS_FILE} がありません"
+elif [ "$RESTORE_BACKUP" = true ] && [ -n "$OLDEST_BACKUP" ]; then
+    cp "$SETTINGS_FILE" "${SETTINGS_FILE}.uninstall.$(date +%Y%m%d_%H%M%S)"
+    cp "$OLDEST_BACKUP" "$SETTINGS_FILE"
+    echo -e "  ${GREEN}✓${NC} ${OLDEST_BACKUP} を復元しました"
+else
+    if [ "$RESTORE_BACKUP" = true ]; then
+        echo -e "  ${YELLOW}-${NC} バックアップがないため、このアプリのフックだけを削除します"
+    fi
+    if ! command -v jq &> /dev/null; then
+        echo -e "${RED}エラー: jq が見つかりません${NC}"
+        echo "インストール: sudo apt install jq"
+        exit 1
+    fi
+    cp "$SETTINGS_FILE" "${SETTINGS_FILE}.uninstall.$(date +%Y%m%d_%H%M%S)"
+
+    # このアプリのフック（~/.claude-notify-scripts/ のコマンド）だけを削除し、ユーザー定義のフックは残す
+    # 空になったフックの種類・hooks、このアプリの statusLine も削除する
+    jq --arg dir "$SCRIPTS_DIR" '
+        def ours: (.command // "") | contains($dir);
+        if .hooks then
+            .hooks |= (
+                with_entries(.value |= map(select(.hooks | any(ours) | not)))
+                | with_entries(select(.value | length > 0))
+            )
+            | if .hooks == {} then del(.hooks) else . end
+        else . end
+        | if .statusLine and (.statusLine | ours) then del(.statusLine) else . end
+    ' "$SETTINGS_FILE" > "${SETTINGS_FILE}.tmp"
+    mv "${SETTINGS_FILE}.tmp" "$SETTINGS_FILE"
+    echo -e "  ${GREEN}✓${NC} このアプリのフックを削除しました"
+fi
+
+# ハートビートの送信を止める
+HEARTBEAT_PID_FILE="${TMPDIR:-/tmp}/claude-notify-heartbeat-$(id -u).pid"
+if [ -f "$HEARTBEAT_PID_FILE" ]; then
+    kill "$(cat "$HEARTBEAT_PID_FILE")" 2>/dev/null || true
+    rm -f "$HEARTBEAT_PID_FILE"
+fi
+
+# スクリプトを削除（このアプリのファイル以外は残す）
+echo ""
+echo -e "${YELLOW}${SCRIPTS_DIR} のスクリプトを削除しています...${NC}"
+if [ -d "$SCRIPTS_DIR" ]; then
+    for script in $SCRIPTS; do
+        rm -f "${SCRIPTS_DIR:?}/${script}"
+    done
+    if rmdir "$SCRIPTS_DIR" 2>/dev/null; then
+        echo -e "  ${GREEN}✓${NC} スクリプトを削除しました"
+    else
+        echo -e "  ${YELLOW}-${NC} ほかのファイルがあるため ${SCRIPTS_DIR} は残しました"
+    fi
+else
+    echo -e "  ${YELLOW}-${NC} ${SCRIPTS_DIR} がありません"
+fi
+
+echo ""
+echo -e "${GREEN}════════════════════════════════════════${NC}"
+echo -e "${GREEN}アンインストール完了！${NC}"
+echo ""
+echo "変更を反映するには Claude Code を再起動してください。"
 echo -e "${GREEN}════════════════════════════════════════${NC}"
 "#;
 
@@ -498,6 +1146,11 @@ pub const README_TEMPLATE: &str = r#"Claude Code Notify セットアップガイ
 
 4. Claude Code を再起動
 
+※ アンインストール:
+   ~/.claude-notify-scripts/uninstall.sh
+   settings.json からこのアプリのフックだけを削除します。
+   最初のインストール前の設定に戻す場合は --restore-backup を付けてください。
+
 
 【手動セットアップ】
 ====================================
@@ -515,13 +1168,24 @@ pub const README_TEMPLATE: &str = r#"Claude Code Notify セットアップガイ
    推奨: ~/.claude-notify-scripts/
 
 2. 実行権限を付与:
-   chmod +x ~/.claude-notify-scripts/*.sh
+   chmod +x ~/.claude-notify-scripts/*.sh ~/.claude-notify-scripts/on-*.__HOOK_EXT__
 
 含まれるスクリプト:
 - on-stop.sh              : タスク完了時の通知 (Stop hook)
 - on-permission-request.sh: 承認依頼通知 (PermissionRequest hook)
+- await-permission-response.sh: アプリからの許可・拒否を待つヘルパー
+                            (on-permission-request.sh から呼ばれる)
+- control-listener.sh     : アプリからの一時停止・再開・中断・ping を実行するヘルパー
+                            (statusline.sh から起動される)
+- resolve-host.sh         : 接続先を決めるヘルパー（auto の場合は Docker のホスト、
+                            mDNS での検出が有効な場合は告知されたブローカー）
 - on-notification.sh      : 入力要求通知 (Notification hook - elicitation_dialog)
 - statusline.sh           : ステータスライン更新 (オプション)
+- uninstall.sh            : フックの設定とスクリプトの削除
+
+※ fish・Nushell 用にエクスポートした場合、on-stop・on-permission-request・on-notification は
+  .__HOOK_EXT__ のスクリプトになります（fish / nu が必要です）。
+  await-permission-response.sh・statusline.sh・インストーラは bash で実行します。
 
 3. Claude Code の設定
 -------------------
@@ -545,16 +1209,35 @@ pub const README_TEMPLATE: &str = r#"Claude Code Notify セットアップガイ
 Windows PC の IP アドレス: __HOST__
 MQTT ポート: __PORT__
 
+※ auto の場合（Devcontainer / Docker 用のエクスポート）、スクリプトは実行のたびに
+  host.docker.internal、なければコンテナのデフォルトゲートウェイを接続先にします。
+  検出される接続先は ~/.claude-notify-scripts/resolve-host.sh で確認できます。
+
+※ アプリで「mDNS で告知する」を有効にしてエクスポートした場合、スクリプトは
+  avahi-browse（avahi-utils）で _claude-notify._tcp を検索し、見つかったアドレスに送信します
+  （見つからない場合は上記の IP アドレス）。DHCP で IP アドレスが変わっても再エクスポートは不要です。
+  検出を止めるには CLAUDE_NOTIFY_DISCOVERY=off を設定してください。
+
+※ 接続先は ~/.claude/notify.env（CLAUDE_NOTIFY_HOST=... の形式）でも変更できます。
+  環境変数が優先され、どちらもない場合は上記の IP アドレスを使います。
+  アプリの設定タブで SSH の宛先を登録すると、IP アドレスが変わった時にアプリから
+  このファイルを書き込めます。
+
 5. テスト方法
 -------------------
 手動でスクリプトを実行して通知が届くか確認:
-  ~/.claude-notify-scripts/on-stop.sh
+  ~/.claude-notify-scripts/on-stop.__HOOK_EXT__
 
 または mosquitto_pub で直接テスト:
-  mosquitto_pub -h __HOST__ -p __PORT__ -t "claude-code/events/stop" -m '{"event":"stop","cwd":"/test"}'
+  mosquitto_pub -h __HOST__ -p __PORT__ -I "claude-code-" -u "claude-code-notify" -t "claude-code/events/stop" -m '{"event":"stop","cwd":"/test"}'
 
 承認依頼通知のテスト:
-  echo '{"tool_name":"Bash","tool_input":{"command":"npm install"}}' | ~/.claude-notify-scripts/on-permission-request.sh
+  echo '{"tool_name":"Bash","tool_input":{"command":"npm install"}}' | ~/.claude-notify-scripts/on-permission-request.__HOOK_EXT__
+
+アプリからの応答を有効にしてエクスポートした場合、承認依頼のトーストや通知履歴で
+「許可」「拒否」を選ぶと Claude Code に反映されます（mosquitto_sub が必要）。
+AskUserQuestion の質問はトーストのクリックで開く回答ウィンドウから選択肢を選んで回答できます。
+__RESPONSE_TIMEOUT__ 秒以内に応答がなければ通常の確認ダイアログが表示されます。
 
 6. トラブルシューティング
 -------------------
@@ -566,6 +1249,564 @@ MQTT ポート: __PORT__
     ping __HOST__
 "#;
 
+// =============================================================================
+// fish Templates (Linux / WSL)
+// =============================================================================
+
+/// on-stop.fish template (mosquitto_pub version)
+pub const ON_STOP_FISH: &str = r#"#!/usr/bin/env fish
+# Claude Code Stop Hook (fish) - Sends notification via MQTT
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Environment variable, then the settings pushed from the app (~/.claude/notify.env),
+# then the exported default
+function env_or --argument-names name default
+    if set -q $name; and test -n "$$name"
+        echo $$name
+        return
+    end
+    set -l notify_env $HOME/.claude/notify.env
+    set -q CLAUDE_NOTIFY_ENV_FILE; and set notify_env $CLAUDE_NOTIFY_ENV_FILE
+    if test -r $notify_env
+        set -l value (string match -r -g "^$name=(.*)" < $notify_env)
+        if test -n "$value[1]"
+            echo $value[1]
+            return
+        end
+    end
+    echo $default
+end
+
+set -g HOST (env_or CLAUDE_NOTIFY_HOST "__HOST__")
+set -g SCRIPT_DIR (dirname (status filename))
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+set HOST ($SCRIPT_DIR/resolve-host.sh $HOST)
+set -g PORT (env_or CLAUDE_NOTIFY_PORT "__PORT__")
+set -g SECRET (env_or CLAUDE_NOTIFY_SECRET "__SECRET__")
+set -g ENCRYPTION_KEY (env_or CLAUDE_NOTIFY_KEY "__ENCRYPTION_KEY__")
+set -g SOURCE_HOST (env_or CLAUDE_NOTIFY_SOURCE_HOST (hostname))
+set -g TOPIC "claude-code/events/stop"
+
+# Publish a payload (signed / encrypted when configured)
+function publish --argument-names topic payload
+    # Encrypted payloads are sent with mqtt-publish (it also signs them)
+    if test -n "$ENCRYPTION_KEY"
+        printf '%s' $payload | env CLAUDE_NOTIFY_SECRET=$SECRET CLAUDE_NOTIFY_KEY=$ENCRYPTION_KEY \
+            mqtt-publish -h $HOST -p $PORT -t $topic --stdin
+        return $status
+    end
+    # Sign payload when a shared secret is configured
+    if test -n "$SECRET"
+        set -l signature (printf '%s' $payload | openssl dgst -sha256 -hmac $SECRET -r | string split -f1 ' ')
+        set payload (jq -cn --arg payload $payload --arg signature $signature '{payload: $payload, signature: $signature}')
+    end
+    mosquitto_pub -h $HOST -p $PORT -I "claude-code-" -u "claude-code-notify" -t $topic -m $payload
+end
+
+# Read input from stdin (Claude Code provides session info as JSON)
+set -l input (cat | string collect)
+
+# Extract session_id / cwd from Claude Code's JSON
+set -l session_id (printf '%s' $input | jq -r '.session_id // empty' 2>/dev/null)
+test -n "$session_id"; or set session_id (hostname)"-unknown"
+set -l cwd (printf '%s' $input | jq -r '.cwd // empty' 2>/dev/null)
+test -n "$cwd"; or set cwd $PWD
+
+# Excerpt of Claude's final message from the transcript (text blocks of the last assistant message)
+set -l transcript (printf '%s' $input | jq -r '.transcript_path // empty' 2>/dev/null)
+set -l last_message ""
+if test -n "$transcript"; and test -r "$transcript"
+    set last_message (tail -n 200 $transcript | jq -rs \
+        '[.[] | select(.type == "assistant") | [.message.content[]? | select(.type == "text") | .text] | join("\n") | select(length > 0)] | last // "" | .[0:__LAST_MESSAGE_LENGTH__]' 2>/dev/null | string collect)
+end
+
+# Create JSON payload (jq escapes the values)
+set -l payload (jq -cn \
+    --arg cwd $cwd \
+    --arg session_id $session_id \
+    --arg host $SOURCE_HOST \
+    --arg last_message "$last_message" \
+    --arg transcript_path "$transcript" \
+    --arg wsl_distro "$WSL_DISTRO_NAME" \
+    --arg timestamp (date -Iseconds) \
+    '{event: "stop", cwd: $cwd, session_id: $session_id, host: $host, template_version: __TEMPLATE_VERSION__, transcript_path: $transcript_path, wsl_distro: $wsl_distro, timestamp: $timestamp}
+     + (if $last_message != "" then {last_message: $last_message} else {} end)')
+
+publish $TOPIC $payload
+"#;
+
+/// on-permission-request.fish template (mosquitto_pub version)
+pub const ON_PERMISSION_REQUEST_FISH: &str = r#"#!/usr/bin/env fish
+# Claude Code Permission Request Hook (fish) - Sends approval request notifications via MQTT
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Environment variable, then the settings pushed from the app (~/.claude/notify.env),
+# then the exported default
+function env_or --argument-names name default
+    if set -q $name; and test -n "$$name"
+        echo $$name
+        return
+    end
+    set -l notify_env $HOME/.claude/notify.env
+    set -q CLAUDE_NOTIFY_ENV_FILE; and set notify_env $CLAUDE_NOTIFY_ENV_FILE
+    if test -r $notify_env
+        set -l value (string match -r -g "^$name=(.*)" < $notify_env)
+        if test -n "$value[1]"
+            echo $value[1]
+            return
+        end
+    end
+    echo $default
+end
+
+set -g HOST (env_or CLAUDE_NOTIFY_HOST "__HOST__")
+set -g SCRIPT_DIR (dirname (status filename))
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+set HOST ($SCRIPT_DIR/resolve-host.sh $HOST)
+set -g PORT (env_or CLAUDE_NOTIFY_PORT "__PORT__")
+set -g SECRET (env_or CLAUDE_NOTIFY_SECRET "__SECRET__")
+set -g ENCRYPTION_KEY (env_or CLAUDE_NOTIFY_KEY "__ENCRYPTION_KEY__")
+set -g SOURCE_HOST (env_or CLAUDE_NOTIFY_SOURCE_HOST (hostname))
+set -g RESPONSE_TIMEOUT (env_or CLAUDE_NOTIFY_RESPONSE_TIMEOUT "__RESPONSE_TIMEOUT__")
+set -g TOPIC "claude-code/events/permission-request"
+
+# Publish a payload (signed / encrypted when configured)
+function publish --argument-names topic payload
+    # Encrypted payloads are sent with mqtt-publish (it also signs them)
+    if test -n "$ENCRYPTION_KEY"
+        printf '%s' $payload | env CLAUDE_NOTIFY_SECRET=$SECRET CLAUDE_NOTIFY_KEY=$ENCRYPTION_KEY \
+            mqtt-publish -h $HOST -p $PORT -t $topic --stdin
+        return $status
+    end
+    # Sign payload when a shared secret is configured
+    if test -n "$SECRET"
+        set -l signature (printf '%s' $payload | openssl dgst -sha256 -hmac $SECRET -r | string split -f1 ' ')
+        set payload (jq -cn --arg payload $payload --arg signature $signature '{payload: $payload, signature: $signature}')
+    end
+    mosquitto_pub -h $HOST -p $PORT -I "claude-code-" -u "claude-code-notify" -t $topic -m $payload
+end
+
+# Read input from stdin (Claude Code provides session info as JSON)
+set -l input (cat | string collect)
+
+# Extract session_id / cwd from Claude Code's JSON
+set -l session_id (printf '%s' $input | jq -r '.session_id // empty' 2>/dev/null)
+test -n "$session_id"; or set session_id (hostname)"-unknown"
+set -l cwd (printf '%s' $input | jq -r '.cwd // empty' 2>/dev/null)
+test -n "$cwd"; or set cwd $PWD
+
+# Use the entire input as content (wrapped as raw when it is not JSON)
+set -l content (printf '%s' $input | jq -c . 2>/dev/null)
+or set content (jq -cn --arg raw $input '{raw: $raw}')
+
+# Request ID lets the app answer this request (only when waiting for a response)
+set -l request_id ""
+if string match -qr '^[1-9][0-9]*$' -- $RESPONSE_TIMEOUT
+    set request_id (date +%s%N)"-"$fish_pid
+end
+
+# Create JSON payload (jq escapes the values)
+set -l payload (jq -cn \
+    --arg cwd $cwd \
+    --arg session_id $session_id \
+    --arg host $SOURCE_HOST \
+    --arg request_id $request_id \
+    --argjson content $content \
+    --arg wsl_distro "$WSL_DISTRO_NAME" \
+    --arg timestamp (date -Iseconds) \
+    '{event: "permission-request", cwd: $cwd, session_id: $session_id, host: $host, template_version: __TEMPLATE_VERSION__, request_id: $request_id, content: $content, wsl_distro: $wsl_distro, timestamp: $timestamp}')
+
+publish $TOPIC $payload; or exit $status
+
+# Wait for Allow/Deny (or answers to AskUserQuestion) from the app and pass it back to Claude Code
+# (the bash helper prints nothing on timeout, so Claude Code shows its own permission dialog)
+if test -n "$request_id"
+    printf '%s' $input | $SCRIPT_DIR/await-permission-response.sh $session_id $request_id $RESPONSE_TIMEOUT
+end
+"#;
+
+/// on-notification.fish template (mosquitto_pub version)
+pub const ON_NOTIFICATION_FISH: &str = r#"#!/usr/bin/env fish
+# Claude Code Notification Hook (fish) - Sends elicitation dialog notifications via MQTT
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Environment variable, then the settings pushed from the app (~/.claude/notify.env),
+# then the exported default
+function env_or --argument-names name default
+    if set -q $name; and test -n "$$name"
+        echo $$name
+        return
+    end
+    set -l notify_env $HOME/.claude/notify.env
+    set -q CLAUDE_NOTIFY_ENV_FILE; and set notify_env $CLAUDE_NOTIFY_ENV_FILE
+    if test -r $notify_env
+        set -l value (string match -r -g "^$name=(.*)" < $notify_env)
+        if test -n "$value[1]"
+            echo $value[1]
+            return
+        end
+    end
+    echo $default
+end
+
+set -g HOST (env_or CLAUDE_NOTIFY_HOST "__HOST__")
+set -g SCRIPT_DIR (dirname (status filename))
+# "auto" (Docker host) or mDNS discovery, falling back to HOST (see resolve-host.sh)
+set HOST ($SCRIPT_DIR/resolve-host.sh $HOST)
+set -g PORT (env_or CLAUDE_NOTIFY_PORT "__PORT__")
+set -g SECRET (env_or CLAUDE_NOTIFY_SECRET "__SECRET__")
+set -g ENCRYPTION_KEY (env_or CLAUDE_NOTIFY_KEY "__ENCRYPTION_KEY__")
+set -g SOURCE_HOST (env_or CLAUDE_NOTIFY_SOURCE_HOST (hostname))
+set -g TOPIC "claude-code/events/notification"
+
+# Publish a payload (signed / encrypted when configured)
+function publish --argument-names topic payload
+    # Encrypted payloads are sent with mqtt-publish (it also signs them)
+    if test -n "$ENCRYPTION_KEY"
+        printf '%s' $payload | env CLAUDE_NOTIFY_SECRET=$SECRET CLAUDE_NOTIFY_KEY=$ENCRYPTION_KEY \
+            mqtt-publish -h $HOST -p $PORT -t $topic --stdin
+        return $status
+    end
+    # Sign payload when a shared secret is configured
+    if test -n "$SECRET"
+        set -l signature (printf '%s' $payload | openssl dgst -sha256 -hmac $SECRET -r | string split -f1 ' ')
+        set payload (jq -cn --arg payload $payload --arg signature $signature '{payload: $payload, signature: $signature}')
+    end
+    mosquitto_pub -h $HOST -p $PORT -I "claude-code-" -u "claude-code-notify" -t $topic -m $payload
+end
+
+# Read input from stdin (Claude Code provides session info as JSON)
+set -l input (cat | string collect)
+
+# Extract session_id / cwd from Claude Code's JSON
+set -l session_id (printf '%s' $input | jq -r '.session_id // empty' 2>/dev/null)
+test -n "$session_id"; or set session_id (hostname)"-unknown"
+set -l cwd (printf '%s' $input | jq -r '.cwd // empty' 2>/dev/null)
+test -n "$cwd"; or set cwd $PWD
+
+# Use the entire input as content (wrapped as raw when it is not JSON)
+set -l content (printf '%s' $input | jq -c . 2>/dev/null)
+or set content (jq -cn --arg raw $input '{raw: $raw}')
+
+# Create JSON payload (jq escapes the values)
+set -l payload (jq -cn \
+    --arg cwd $cwd \
+    --arg session_id $session_id \
+    --arg host $SOURCE_HOST \
+    --argjson content $content \
+    --arg wsl_distro "$WSL_DISTRO_NAME" \
+    --arg timestamp (date -Iseconds) \
+    '{event: "notification", cwd: $cwd, session_id: $session_id, host: $host, template_version: __TEMPLATE_VERSION__, content: $content, wsl_distro: $wsl_distro, timestamp: $timestamp}')
+
+publish $TOPIC $payload
+"#;
+
+// =============================================================================
+// Nushell Templates (Linux / WSL)
+// =============================================================================
+
+/// on-stop.nu template (mosquitto_pub version)
+pub const ON_STOP_NU: &str = r#"#!/usr/bin/env -S nu --stdin
+# Claude Code Stop Hook (Nushell) - Sends notification via MQTT
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Environment variable, then the settings pushed from the app (~/.claude/notify.env),
+# then the exported default
+def env-or [name: string, fallback: string] {
+    let value = if ($name in ($env | columns)) { $env | get $name | into string } else { "" }
+    let value = if ($value | is-empty) { notify-env $name } else { $value }
+    if ($value | is-empty) { $fallback } else { $value }
+}
+
+# Setting in ~/.claude/notify.env ("" when missing)
+def notify-env [name: string] {
+    let file = if ("CLAUDE_NOTIFY_ENV_FILE" in ($env | columns)) {
+        $env.CLAUDE_NOTIFY_ENV_FILE
+    } else {
+        $env.HOME | path join ".claude" "notify.env"
+    }
+    if not ($file | path exists) { return "" }
+    open --raw $file | lines | parse "{key}={value}" | where key == $name | get value | append "" | first
+}
+
+# "auto" (Docker host) or mDNS discovery, falling back to the host (see resolve-host.sh)
+def resolve-host [host: string] {
+    run-external ($env.FILE_PWD | path join "resolve-host.sh") $host | str trim
+}
+
+def settings [] {
+    {
+        host: (resolve-host (env-or CLAUDE_NOTIFY_HOST "__HOST__"))
+        port: (env-or CLAUDE_NOTIFY_PORT "__PORT__")
+        secret: (env-or CLAUDE_NOTIFY_SECRET "__SECRET__")
+        encryption_key: (env-or CLAUDE_NOTIFY_KEY "__ENCRYPTION_KEY__")
+        source_host: (env-or CLAUDE_NOTIFY_SOURCE_HOST (^hostname | str trim))
+    }
+}
+
+# Field of Claude Code's JSON as a string ("" when missing)
+def field [data: any, name: string] {
+    try { $data | get $name | default "" | into string } catch { "" }
+}
+
+# Publish a payload (signed / encrypted when configured)
+def publish [config: record, topic: string, payload: string] {
+    # Encrypted payloads are sent with mqtt-publish (it also signs them)
+    if ($config.encryption_key | is-not-empty) {
+        with-env {CLAUDE_NOTIFY_SECRET: $config.secret, CLAUDE_NOTIFY_KEY: $config.encryption_key} {
+            $payload | ^mqtt-publish -h $config.host -p $config.port -t $topic --stdin
+        }
+        return
+    }
+    # Sign payload when a shared secret is configured
+    let message = if ($config.secret | is-not-empty) {
+        let signature = ($payload | ^openssl dgst -sha256 -hmac $config.secret -r | split row " " | first)
+        {payload: $payload, signature: $signature} | to json -r
+    } else {
+        $payload
+    }
+    ^mosquitto_pub -h $config.host -p $config.port -I "claude-code-" -u "claude-code-notify" -t $topic -m $message
+}
+
+# Excerpt of Claude's final message from the transcript ("" when missing)
+def last-message [transcript: string] {
+    if ($transcript | is-empty) or not ($transcript | path exists) { return "" }
+    let texts = (try {
+        open --raw $transcript | lines | last 200
+            | each { |line| try { $line | from json } catch { null } }
+            | where { |entry| ($entry | get -i type) == "assistant" }
+            | each { |entry| $entry.message.content? | default [] | where { |block| ($block | get -i type) == "text" } | get text | str join "\n" }
+            | where { |text| $text | is-not-empty }
+    } catch { [] })
+    if ($texts | is-empty) { return "" }
+    $texts | last | str substring --grapheme-clusters 0..<__LAST_MESSAGE_LENGTH__
+}
+
+def main [] {
+    # Read input from stdin (Claude Code provides session info as JSON)
+    let input = ($in | default "" | into string)
+    let data = (try { $input | from json } catch { null })
+    let config = (settings)
+
+    let session_id = (field $data session_id)
+    let session_id = if ($session_id | is-empty) { $"(^hostname | str trim)-unknown" } else { $session_id }
+    let cwd = (field $data cwd)
+    let cwd = if ($cwd | is-empty) { $env.PWD } else { $cwd }
+
+    let payload = {
+        event: "stop"
+        cwd: $cwd
+        session_id: $session_id
+        host: $config.source_host
+        template_version: __TEMPLATE_VERSION__
+        transcript_path: (field $data transcript_path)
+        wsl_distro: ($env.WSL_DISTRO_NAME? | default "")
+        timestamp: (^date -Iseconds | str trim)
+    }
+    let last_message = (last-message (field $data transcript_path))
+    let payload = if ($last_message | is-empty) { $payload } else { $payload | insert last_message $last_message }
+    let payload = ($payload | to json -r)
+
+    publish $config "claude-code/events/stop" $payload
+}
+"#;
+
+/// on-permission-request.nu template (mosquitto_pub version)
+pub const ON_PERMISSION_REQUEST_NU: &str = r#"#!/usr/bin/env -S nu --stdin
+# Claude Code Permission Request Hook (Nushell) - Sends approval request notifications via MQTT
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Environment variable, then the settings pushed from the app (~/.claude/notify.env),
+# then the exported default
+def env-or [name: string, fallback: string] {
+    let value = if ($name in ($env | columns)) { $env | get $name | into string } else { "" }
+    let value = if ($value | is-empty) { notify-env $name } else { $value }
+    if ($value | is-empty) { $fallback } else { $value }
+}
+
+# Setting in ~/.claude/notify.env ("" when missing)
+def notify-env [name: string] {
+    let file = if ("CLAUDE_NOTIFY_ENV_FILE" in ($env | columns)) {
+        $env.CLAUDE_NOTIFY_ENV_FILE
+    } else {
+        $env.HOME | path join ".claude" "notify.env"
+    }
+    if not ($file | path exists) { return "" }
+    open --raw $file | lines | parse "{key}={value}" | where key == $name | get value | append "" | first
+}
+
+# "auto" (Docker host) or mDNS discovery, falling back to the host (see resolve-host.sh)
+def resolve-host [host: string] {
+    run-external ($env.FILE_PWD | path join "resolve-host.sh") $host | str trim
+}
+
+def settings [] {
+    {
+        host: (resolve-host (env-or CLAUDE_NOTIFY_HOST "__HOST__"))
+        port: (env-or CLAUDE_NOTIFY_PORT "__PORT__")
+        secret: (env-or CLAUDE_NOTIFY_SECRET "__SECRET__")
+        encryption_key: (env-or CLAUDE_NOTIFY_KEY "__ENCRYPTION_KEY__")
+        source_host: (env-or CLAUDE_NOTIFY_SOURCE_HOST (^hostname | str trim))
+        response_timeout: (env-or CLAUDE_NOTIFY_RESPONSE_TIMEOUT "__RESPONSE_TIMEOUT__")
+    }
+}
+
+# Field of Claude Code's JSON as a string ("" when missing)
+def field [data: any, name: string] {
+    try { $data | get $name | default "" | into string } catch { "" }
+}
+
+# Publish a payload (signed / encrypted when configured)
+def publish [config: record, topic: string, payload: string] {
+    # Encrypted payloads are sent with mqtt-publish (it also signs them)
+    if ($config.encryption_key | is-not-empty) {
+        with-env {CLAUDE_NOTIFY_SECRET: $config.secret, CLAUDE_NOTIFY_KEY: $config.encryption_key} {
+            $payload | ^mqtt-publish -h $config.host -p $config.port -t $topic --stdin
+        }
+        return
+    }
+    # Sign payload when a shared secret is configured
+    let message = if ($config.secret | is-not-empty) {
+        let signature = ($payload | ^openssl dgst -sha256 -hmac $config.secret -r | split row " " | first)
+        {payload: $payload, signature: $signature} | to json -r
+    } else {
+        $payload
+    }
+    ^mosquitto_pub -h $config.host -p $config.port -I "claude-code-" -u "claude-code-notify" -t $topic -m $message
+}
+
+def main [] {
+    # Read input from stdin (Claude Code provides session info as JSON)
+    let input = ($in | default "" | into string)
+    let data = (try { $input | from json } catch { null })
+    let config = (settings)
+
+    let session_id = (field $data session_id)
+    let session_id = if ($session_id | is-empty) { $"(^hostname | str trim)-unknown" } else { $session_id }
+    let cwd = (field $data cwd)
+    let cwd = if ($cwd | is-empty) { $env.PWD } else { $cwd }
+
+    # Request ID lets the app answer this request (only when waiting for a response)
+    let request_id = if ($config.response_timeout =~ '^[1-9][0-9]*$') {
+        $"(^date +%s%N | str trim)-($nu.pid)"
+    } else {
+        ""
+    }
+
+    # Use the entire input as content (wrapped as raw when it is not JSON)
+    let payload = ({
+        event: "permission-request"
+        cwd: $cwd
+        session_id: $session_id
+        host: $config.source_host
+        template_version: __TEMPLATE_VERSION__
+        request_id: $request_id
+        content: (if $data == null { {raw: $input} } else { $data })
+        wsl_distro: ($env.WSL_DISTRO_NAME? | default "")
+        timestamp: (^date -Iseconds | str trim)
+    } | to json -r)
+
+    publish $config "claude-code/events/permission-request" $payload
+
+    # Wait for Allow/Deny (or answers to AskUserQuestion) from the app and pass it back to Claude Code
+    # (the bash helper prints nothing on timeout, so Claude Code shows its own permission dialog)
+    if ($request_id | is-not-empty) {
+        let helper = ($env.FILE_PWD | path join "await-permission-response.sh")
+        $input | run-external $helper $session_id $request_id $config.response_timeout
+    }
+}
+"#;
+
+/// on-notification.nu template (mosquitto_pub version)
+pub const ON_NOTIFICATION_NU: &str = r#"#!/usr/bin/env -S nu --stdin
+# Claude Code Notification Hook (Nushell) - Sends elicitation dialog notifications via MQTT
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+
+# Environment variable, then the settings pushed from the app (~/.claude/notify.env),
+# then the exported default
+def env-or [name: string, fallback: string] {
+    let value = if ($name in ($env | columns)) { $env | get $name | into string } else { "" }
+    let value = if ($value | is-empty) { notify-env $name } else { $value }
+    if ($value | is-empty) { $fallback } else { $value }
+}
+
+# Setting in ~/.claude/notify.env ("" when missing)
+def notify-env [name: string] {
+    let file = if ("CLAUDE_NOTIFY_ENV_FILE" in ($env | columns)) {
+        $env.CLAUDE_NOTIFY_ENV_FILE
+    } else {
+        $env.HOME | path join ".claude" "notify.env"
+    }
+    if not ($file | path exists) { return "" }
+    open --raw $file | lines | parse "{key}={value}" | where key == $name | get value | append "" | first
+}
+
+# "auto" (Docker host) or mDNS discovery, falling back to the host (see resolve-host.sh)
+def resolve-host [host: string] {
+    run-external ($env.FILE_PWD | path join "resolve-host.sh") $host | str trim
+}
+
+def settings [] {
+    {
+        host: (resolve-host (env-or CLAUDE_NOTIFY_HOST "__HOST__"))
+        port: (env-or CLAUDE_NOTIFY_PORT "__PORT__")
+        secret: (env-or CLAUDE_NOTIFY_SECRET "__SECRET__")
+        encryption_key: (env-or CLAUDE_NOTIFY_KEY "__ENCRYPTION_KEY__")
+        source_host: (env-or CLAUDE_NOTIFY_SOURCE_HOST (^hostname | str trim))
+    }
+}
+
+# Field of Claude Code's JSON as a string ("" when missing)
+def field [data: any, name: string] {
+    try { $data | get $name | default "" | into string } catch { "" }
+}
+
+# Publish a payload (signed / encrypted when configured)
+def publish [config: record, topic: string, payload: string] {
+    # Encrypted payloads are sent with mqtt-publish (it also signs them)
+    if ($config.encryption_key | is-not-empty) {
+        with-env {CLAUDE_NOTIFY_SECRET: $config.secret, CLAUDE_NOTIFY_KEY: $config.encryption_key} {
+            $payload | ^mqtt-publish -h $config.host -p $config.port -t $topic --stdin
+        }
+        return
+    }
+    # Sign payload when a shared secret is configured
+    let message = if ($config.secret | is-not-empty) {
+        let signature = ($payload | ^openssl dgst -sha256 -hmac $config.secret -r | split row " " | first)
+        {payload: $payload, signature: $signature} | to json -r
+    } else {
+        $payload
+    }
+    ^mosquitto_pub -h $config.host -p $config.port -I "claude-code-" -u "claude-code-notify" -t $topic -m $message
+}
+
+def main [] {
+    # Read input from stdin (Claude Code provides session info as JSON)
+    let input = ($in | default "" | into string)
+    let data = (try { $input | from json } catch { null })
+    let config = (settings)
+
+    let session_id = (field $data session_id)
+    let session_id = if ($session_id | is-empty) { $"(^hostname | str trim)-unknown" } else { $session_id }
+    let cwd = (field $data cwd)
+    let cwd = if ($cwd | is-empty) { $env.PWD } else { $cwd }
+
+    # Use the entire input as content (wrapped as raw when it is not JSON)
+    let payload = ({
+        event: "notification"
+        cwd: $cwd
+        session_id: $session_id
+        host: $config.source_host
+        template_version: __TEMPLATE_VERSION__
+        content: (if $data == null { {raw: $input} } else { $data })
+        wsl_distro: ($env.WSL_DISTRO_NAME? | default "")
+        timestamp: (^date -Iseconds | str trim)
+    } | to json -r)
+
+    publish $config "claude-code/events/notification" $payload
+}
+"#;
+
 // =============================================================================
 // Windows (PowerShell) Templates
 // =============================================================================
@@ -573,7 +1814,7 @@ MQTT ポート: __PORT__
 /// on-stop.ps1 template for Windows
 pub const ON_STOP_PS1: &str = r#"#Requires -Version 5.1
 # Claude Code Stop Hook - Sends notification via MQTT
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
 
 param()
 
@@ -583,9 +1824,22 @@ $ErrorActionPreference = "SilentlyContinue"
 $OutputEncoding = [System.Text.Encoding]::UTF8
 [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
 
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+$NotifyEnv = if ($env:CLAUDE_NOTIFY_ENV_FILE) { $env:CLAUDE_NOTIFY_ENV_FILE } else { Join-Path $HOME ".claude\notify.env" }
+if (Test-Path $NotifyEnv) {
+    foreach ($Line in Get-Content $NotifyEnv) {
+        if ($Line -match '^(CLAUDE_NOTIFY_[A-Z_]+)=(.*)$' -and -not [Environment]::GetEnvironmentVariable($Matches[1])) {
+            [Environment]::SetEnvironmentVariable($Matches[1], $Matches[2])
+        }
+    }
+}
+
 $NotifyHost = if ($env:CLAUDE_NOTIFY_HOST) { $env:CLAUDE_NOTIFY_HOST } else { "__HOST__" }
 $NotifyPort = if ($env:CLAUDE_NOTIFY_PORT) { $env:CLAUDE_NOTIFY_PORT } else { "__PORT__" }
-$Topic = "claude-code/events/stop"
+$SourceHost = if ($env:CLAUDE_NOTIFY_SOURCE_HOST) { $env:CLAUDE_NOTIFY_SOURCE_HOST } else { $env:COMPUTERNAME }
+# Shared secret / encryption key for payloads (read by mqtt-publish.exe)
+if (-not $env:CLAUDE_NOTIFY_SECRET) { $env:CLAUDE_NOTIFY_SECRET = "__SECRET__" }
+if (-not $env:CLAUDE_NOTIFY_KEY) { $env:CLAUDE_NOTIFY_KEY = "__ENCRYPTION_KEY__" }
 $ScriptDir = Split-Path -Parent $MyInvocation.MyCommand.Path
 
 # Read input from stdin (Claude Code provides session info as JSON)
@@ -593,35 +1847,15 @@ $reader = New-Object System.IO.StreamReader([Console]::OpenStandardInput(), [Sys
 $InputJson = $reader.ReadToEnd()
 $reader.Close()
 
-# Extract session_id from Claude Code's JSON
-try {
-    $InputObj = $InputJson | ConvertFrom-Json
-    $SessionId = $InputObj.session_id
-    if (-not $SessionId) { $SessionId = "$env:COMPUTERNAME-unknown" }
-    $Cwd = if ($InputObj.cwd) { $InputObj.cwd } else { (Get-Location).Path }
-} catch {
-    $SessionId = "$env:COMPUTERNAME-unknown"
-    $Cwd = (Get-Location).Path
-}
-
-$Timestamp = Get-Date -Format "o"
-
-$PayloadObj = @{
-    event = "stop"
-    cwd = $Cwd
-    session_id = $SessionId
-    timestamp = $Timestamp
-}
-$Payload = $PayloadObj | ConvertTo-Json -Compress
-
-# Use stdin to avoid escaping issues
-$Payload | & "$ScriptDir\mqtt-publish.exe" -h $NotifyHost -p $NotifyPort -t $Topic --stdin
+# mqtt-publish.exe builds the event payload from Claude Code's JSON
+# (no JSON handling in PowerShell, so paths and non-ASCII text are sent as is)
+$InputJson | & "$ScriptDir\mqtt-publish.exe" -h $NotifyHost -p $NotifyPort --event stop --source-host $SourceHost --template-version __TEMPLATE_VERSION__ --last-message-length __LAST_MESSAGE_LENGTH__
 "#;
 
 /// on-permission-request.ps1 template for Windows
 pub const ON_PERMISSION_REQUEST_PS1: &str = r#"#Requires -Version 5.1
 # Claude Code Permission Request Hook - Sends approval request notifications via MQTT
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
 
 param()
 
@@ -631,9 +1865,22 @@ $ErrorActionPreference = "SilentlyContinue"
 $OutputEncoding = [System.Text.Encoding]::UTF8
 [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
 
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+$NotifyEnv = if ($env:CLAUDE_NOTIFY_ENV_FILE) { $env:CLAUDE_NOTIFY_ENV_FILE } else { Join-Path $HOME ".claude\notify.env" }
+if (Test-Path $NotifyEnv) {
+    foreach ($Line in Get-Content $NotifyEnv) {
+        if ($Line -match '^(CLAUDE_NOTIFY_[A-Z_]+)=(.*)$' -and -not [Environment]::GetEnvironmentVariable($Matches[1])) {
+            [Environment]::SetEnvironmentVariable($Matches[1], $Matches[2])
+        }
+    }
+}
+
 $NotifyHost = if ($env:CLAUDE_NOTIFY_HOST) { $env:CLAUDE_NOTIFY_HOST } else { "__HOST__" }
 $NotifyPort = if ($env:CLAUDE_NOTIFY_PORT) { $env:CLAUDE_NOTIFY_PORT } else { "__PORT__" }
-$Topic = "claude-code/events/permission-request"
+$SourceHost = if ($env:CLAUDE_NOTIFY_SOURCE_HOST) { $env:CLAUDE_NOTIFY_SOURCE_HOST } else { $env:COMPUTERNAME }
+# Shared secret / encryption key for payloads (read by mqtt-publish.exe)
+if (-not $env:CLAUDE_NOTIFY_SECRET) { $env:CLAUDE_NOTIFY_SECRET = "__SECRET__" }
+if (-not $env:CLAUDE_NOTIFY_KEY) { $env:CLAUDE_NOTIFY_KEY = "__ENCRYPTION_KEY__" }
 $ScriptDir = Split-Path -Parent $MyInvocation.MyCommand.Path
 
 # Read input from stdin (Claude Code provides session info as JSON)
@@ -641,37 +1888,15 @@ $reader = New-Object System.IO.StreamReader([Console]::OpenStandardInput(), [Sys
 $InputJson = $reader.ReadToEnd()
 $reader.Close()
 
-# Extract session_id from Claude Code's JSON
-try {
-    $InputObj = $InputJson | ConvertFrom-Json
-    $SessionId = $InputObj.session_id
-    if (-not $SessionId) { $SessionId = "$env:COMPUTERNAME-unknown" }
-    $Cwd = if ($InputObj.cwd) { $InputObj.cwd } else { (Get-Location).Path }
-} catch {
-    $SessionId = "$env:COMPUTERNAME-unknown"
-    $Cwd = (Get-Location).Path
-    $InputObj = @{ raw = $InputJson }
-}
-
-$Timestamp = Get-Date -Format "o"
-
-$PayloadObj = @{
-    event = "permission-request"
-    cwd = $Cwd
-    session_id = $SessionId
-    content = $InputObj
-    timestamp = $Timestamp
-}
-$Payload = $PayloadObj | ConvertTo-Json -Depth 10 -Compress
-
-# Use stdin to avoid escaping issues
-$Payload | & "$ScriptDir\mqtt-publish.exe" -h $NotifyHost -p $NotifyPort -t $Topic --stdin
+# mqtt-publish.exe builds the event payload from Claude Code's JSON
+# (no JSON handling in PowerShell, so paths and non-ASCII text are sent as is)
+$InputJson | & "$ScriptDir\mqtt-publish.exe" -h $NotifyHost -p $NotifyPort --event permission-request --source-host $SourceHost --template-version __TEMPLATE_VERSION__
 "#;
 
 /// on-notification.ps1 template for Windows
 pub const ON_NOTIFICATION_PS1: &str = r#"#Requires -Version 5.1
 # Claude Code Notification Hook - Sends elicitation dialog notifications via MQTT
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
 
 param()
 
@@ -681,9 +1906,22 @@ $ErrorActionPreference = "SilentlyContinue"
 $OutputEncoding = [System.Text.Encoding]::UTF8
 [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
 
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+$NotifyEnv = if ($env:CLAUDE_NOTIFY_ENV_FILE) { $env:CLAUDE_NOTIFY_ENV_FILE } else { Join-Path $HOME ".claude\notify.env" }
+if (Test-Path $NotifyEnv) {
+    foreach ($Line in Get-Content $NotifyEnv) {
+        if ($Line -match '^(CLAUDE_NOTIFY_[A-Z_]+)=(.*)$' -and -not [Environment]::GetEnvironmentVariable($Matches[1])) {
+            [Environment]::SetEnvironmentVariable($Matches[1], $Matches[2])
+        }
+    }
+}
+
 $NotifyHost = if ($env:CLAUDE_NOTIFY_HOST) { $env:CLAUDE_NOTIFY_HOST } else { "__HOST__" }
 $NotifyPort = if ($env:CLAUDE_NOTIFY_PORT) { $env:CLAUDE_NOTIFY_PORT } else { "__PORT__" }
-$Topic = "claude-code/events/notification"
+$SourceHost = if ($env:CLAUDE_NOTIFY_SOURCE_HOST) { $env:CLAUDE_NOTIFY_SOURCE_HOST } else { $env:COMPUTERNAME }
+# Shared secret / encryption key for payloads (read by mqtt-publish.exe)
+if (-not $env:CLAUDE_NOTIFY_SECRET) { $env:CLAUDE_NOTIFY_SECRET = "__SECRET__" }
+if (-not $env:CLAUDE_NOTIFY_KEY) { $env:CLAUDE_NOTIFY_KEY = "__ENCRYPTION_KEY__" }
 $ScriptDir = Split-Path -Parent $MyInvocation.MyCommand.Path
 
 # Read input from stdin (Claude Code provides session info as JSON)
@@ -691,37 +1929,15 @@ $reader = New-Object System.IO.StreamReader([Console]::OpenStandardInput(), [Sys
 $InputJson = $reader.ReadToEnd()
 $reader.Close()
 
-# Extract session_id from Claude Code's JSON
-try {
-    $InputObj = $InputJson | ConvertFrom-Json
-    $SessionId = $InputObj.session_id
-    if (-not $SessionId) { $SessionId = "$env:COMPUTERNAME-unknown" }
-    $Cwd = if ($InputObj.cwd) { $InputObj.cwd } else { (Get-Location).Path }
-} catch {
-    $SessionId = "$env:COMPUTERNAME-unknown"
-    $Cwd = (Get-Location).Path
-    $InputObj = @{ raw = $InputJson }
-}
-
-$Timestamp = Get-Date -Format "o"
-
-$PayloadObj = @{
-    event = "notification"
-    cwd = $Cwd
-    session_id = $SessionId
-    content = $InputObj
-    timestamp = $Timestamp
-}
-$Payload = $PayloadObj | ConvertTo-Json -Depth 10 -Compress
-
-# Use stdin to avoid escaping issues
-$Payload | & "$ScriptDir\mqtt-publish.exe" -h $NotifyHost -p $NotifyPort -t $Topic --stdin
+# mqtt-publish.exe builds the event payload from Claude Code's JSON
+# (no JSON handling in PowerShell, so paths and non-ASCII text are sent as is)
+$InputJson | & "$ScriptDir\mqtt-publish.exe" -h $NotifyHost -p $NotifyPort --event notification --source-host $SourceHost --template-version __TEMPLATE_VERSION__
 "#;
 
 /// statusline.ps1 template for Windows
 pub const STATUSLINE_PS1: &str = r#"#Requires -Version 5.1
 # Claude Code Statusline - Sends status updates via MQTT and outputs status
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
 
 param()
 
@@ -731,8 +1947,22 @@ $ErrorActionPreference = "SilentlyContinue"
 $OutputEncoding = [System.Text.Encoding]::UTF8
 [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
 
+# Settings pushed from the app (~/.claude/notify.env) apply unless set in the environment
+$NotifyEnv = if ($env:CLAUDE_NOTIFY_ENV_FILE) { $env:CLAUDE_NOTIFY_ENV_FILE } else { Join-Path $HOME ".claude\notify.env" }
+if (Test-Path $NotifyEnv) {
+    foreach ($Line in Get-Content $NotifyEnv) {
+        if ($Line -match '^(CLAUDE_NOTIFY_[A-Z_]+)=(.*)$' -and -not [Environment]::GetEnvironmentVariable($Matches[1])) {
+            [Environment]::SetEnvironmentVariable($Matches[1], $Matches[2])
+        }
+    }
+}
+
 $NotifyHost = if ($env:CLAUDE_NOTIFY_HOST) { $env:CLAUDE_NOTIFY_HOST } else { "__HOST__" }
 $NotifyPort = if ($env:CLAUDE_NOTIFY_PORT) { $env:CLAUDE_NOTIFY_PORT } else { "__PORT__" }
+$SourceHost = if ($env:CLAUDE_NOTIFY_SOURCE_HOST) { $env:CLAUDE_NOTIFY_SOURCE_HOST } else { $env:COMPUTERNAME }
+# Shared secret / encryption key for payloads (read by mqtt-publish.exe)
+if (-not $env:CLAUDE_NOTIFY_SECRET) { $env:CLAUDE_NOTIFY_SECRET = "__SECRET__" }
+if (-not $env:CLAUDE_NOTIFY_KEY) { $env:CLAUDE_NOTIFY_KEY = "__ENCRYPTION_KEY__" }
 $ScriptDir = Split-Path -Parent $MyInvocation.MyCommand.Path
 
 # Read the statusline JSON from stdin with proper UTF-8 handling
@@ -747,38 +1977,20 @@ try {
     exit 0
 }
 
-# Extract session_id from input
-$SessionId = if ($Input_.session_id) { $Input_.session_id } else { "default-session" }
-$Topic = "claude-code/status/$SessionId"
-
 # Extract status info for display
 $Model = if ($Input_.model.display_name) { $Input_.model.display_name } else { "Claude" }
 $Cost = if ($Input_.cost.total_cost_usd) { $Input_.cost.total_cost_usd } else { 0 }
 $Context = if ($Input_.context_window.used_percentage) { $Input_.context_window.used_percentage } else { 0 }
 $LinesAdded = if ($Input_.cost.total_lines_added) { $Input_.cost.total_lines_added } else { 0 }
 $LinesRemoved = if ($Input_.cost.total_lines_removed) { $Input_.cost.total_lines_removed } else { 0 }
-$Cwd = if ($Input_.cwd) { $Input_.cwd } else { "" }
 
-# Create status payload for MQTT
-$PayloadObj = @{
-    session_id = $SessionId
-    cwd = $Cwd
-    status = @{
-        state = "active"
-        context_percent = $Context
-        cost_usd = $Cost
-        lines_added = $LinesAdded
-        lines_removed = $LinesRemoved
-    }
-    timestamp = (Get-Date -Format "o")
-}
-$Payload = $PayloadObj | ConvertTo-Json -Depth 10 -Compress
-
-# Send MQTT message in background using stdin
+# Send the status in background (mqtt-publish.exe builds the payload and publishes it retained)
 Start-Job -ScriptBlock {
-    param($exe, $h, $p, $t, $payload)
-    $payload | & $exe -h $h -p $p -t $t -r --stdin
-} -ArgumentList "$ScriptDir\mqtt-publish.exe", $NotifyHost, $NotifyPort, $Topic, $Payload | Out-Null
+    param($exe, $h, $p, $sourceHost, $inputJson)
+    # The job runs in a new PowerShell, which pipes ASCII by default
+    $OutputEncoding = [System.Text.Encoding]::UTF8
+    $inputJson | & $exe -h $h -p $p --event status --source-host $sourceHost
+} -ArgumentList "$ScriptDir\mqtt-publish.exe", $NotifyHost, $NotifyPort, $SourceHost, $InputJson | Out-Null
 
 # Output status text for Claude Code statusline display
 $CostFormatted = $Cost.ToString("F4")
@@ -789,7 +2001,7 @@ Write-Host "[$Model] `$$CostFormatted | Ctx: $ContextFormatted% | +$LinesAdded/-
 /// install.ps1 template - Automated installer for Windows
 pub const INSTALL_PS1: &str = r#"#Requires -Version 5.1
 # Claude Code Notify - Windows Installer Script
-# Generated by Claude Code Notify
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
 #
 # Usage: .\install.ps1 [-WithStatusline]
 
@@ -830,6 +2042,8 @@ Copy-Item "$ScriptSourceDir\on-notification.ps1" "$ScriptsDir\" -Force
 if ($WithStatusline) {
     Copy-Item "$ScriptSourceDir\statusline.ps1" "$ScriptsDir\" -Force
 }
+# Keep the uninstaller available after the extracted ZIP is deleted
+Copy-Item "$ScriptSourceDir\uninstall.ps1" "$ScriptsDir\" -Force
 
 Write-Host "  [OK] Scripts installed" -ForegroundColor Green
 
@@ -851,8 +2065,11 @@ if (Test-Path $SettingsFile) {
 
     try {
         $ExistingSettings = Get-Content $SettingsFile -Raw | ConvertFrom-Json -AsHashtable
+        if ($null -eq $ExistingSettings) { $ExistingSettings = @{} }
     } catch {
-        $ExistingSettings = @{}
+        # Never overwrite settings that could not be read
+        Write-Host "  [ERROR] Could not read $SettingsFile - fix the JSON and run again (settings unchanged)" -ForegroundColor Red
+        exit 1
     }
 }
 
@@ -860,9 +2077,9 @@ if (Test-Path $SettingsFile) {
 $ScriptsDirEscaped = $ScriptsDir.Replace('\', '\\')
 
 # Prepare hooks configuration
-$StopCommand = "powershell.exe -ExecutionPolicy Bypass -File `"$ScriptsDir\on-stop.ps1`""
-$PermissionCommand = "powershell.exe -ExecutionPolicy Bypass -File `"$ScriptsDir\on-permission-request.ps1`""
-$NotificationCommand = "powershell.exe -ExecutionPolicy Bypass -File `"$ScriptsDir\on-notification.ps1`""
+$StopCommand = "powershell.exe -NoProfile -ExecutionPolicy Bypass -File `"$ScriptsDir\on-stop.ps1`""
+$PermissionCommand = "powershell.exe -NoProfile -ExecutionPolicy Bypass -File `"$ScriptsDir\on-permission-request.ps1`""
+$NotificationCommand = "powershell.exe -NoProfile -ExecutionPolicy Bypass -File `"$ScriptsDir\on-notification.ps1`""
 
 # Build hooks structure
 if (-not $ExistingSettings.ContainsKey("hooks")) {
@@ -930,14 +2147,23 @@ $NotificationHook = @{
 }
 $ExistingSettings["hooks"]["Notification"] = @(Update-HookArray $ExistingSettings["hooks"]["Notification"] $NotificationHook $ScriptsDir)
 
-# Add statusline if requested
+# Add statusline if requested (a statusLine set to another command is kept and reported as a conflict)
 if ($WithStatusline) {
-    $StatuslineCommand = "powershell.exe -ExecutionPolicy Bypass -File `"$ScriptsDir\statusline.ps1`""
-    $ExistingSettings["statusLine"] = @{
-        type = "command"
-        command = $StatuslineCommand
+    $StatuslineCommand = "powershell.exe -NoProfile -ExecutionPolicy Bypass -File `"$ScriptsDir\statusline.ps1`""
+    $ExistingStatusline = if ($ExistingSettings.ContainsKey("statusLine")) { $ExistingSettings["statusLine"].command } else { $null }
+    if ($ExistingStatusline -and $ExistingStatusline -notlike "*\.claude-notify-scripts\*") {
+        Write-Host "  [CONFLICT] statusLine is already set ($ExistingStatusline) - left unchanged" -ForegroundColor Yellow
+    } else {
+        $ExistingSettings["statusLine"] = @{
+            type = "command"
+            command = $StatuslineCommand
+        }
+        Write-Host "  [OK] Statusline configured" -ForegroundColor Green
     }
-    Write-Host "  [OK] Statusline configured" -ForegroundColor Green
+}
+
+if ($ExistingSettings["disableAllHooks"] -eq $true) {
+    Write-Host "  [CONFLICT] disableAllHooks is true, so the hooks will not run" -ForegroundColor Yellow
 }
 
 # Write settings
@@ -949,6 +2175,7 @@ Write-Host ""
 Write-Host "Environment variables (optional):" -ForegroundColor Yellow
 Write-Host "  `$env:CLAUDE_NOTIFY_HOST = `"$NotifyHost`""
 Write-Host "  `$env:CLAUDE_NOTIFY_PORT = `"$NotifyPort`""
+Write-Host "  `$env:CLAUDE_NOTIFY_SOURCE_HOST = `"$env:COMPUTERNAME`"  # 通知に表示するホスト名"
 
 # Connection test
 Write-Host ""
@@ -977,6 +2204,107 @@ if ($WithStatusline) {
 }
 Write-Host ""
 Write-Host "Please restart Claude Code to apply changes."
+Write-Host "To uninstall: $ScriptsDir\uninstall.ps1"
+Write-Host "========================================" -ForegroundColor Green
+"#;
+
+/// uninstall.ps1 template - Removes the hooks installed by install.ps1
+pub const UNINSTALL_PS1: &str = r#"#Requires -Version 5.1
+# Claude Code Notify - Windows Uninstaller Script
+# Generated by Claude Code Notify (templates v__TEMPLATE_VERSION__)
+#
+# Usage: .\uninstall.ps1 [-RestoreBackup]
+#   -RestoreBackup: restore the settings backed up before the first install (later changes are lost too)
+
+param(
+    [switch]$RestoreBackup
+)
+
+$ErrorActionPreference = "Stop"
+
+# Configuration
+$ScriptsDir = "$env:USERPROFILE\.claude-notify-scripts"
+$SettingsFile = "$env:USERPROFILE\.claude\settings.json"
+$Scripts = @("mqtt-publish.exe", "on-stop.ps1", "on-permission-request.ps1", "on-notification.ps1", "statusline.ps1", "uninstall.ps1")
+
+Write-Host ""
+Write-Host "========================================" -ForegroundColor Green
+Write-Host "  Claude Code Notify Uninstaller" -ForegroundColor Green
+Write-Host "  Windows Native Edition" -ForegroundColor Green
+Write-Host "========================================" -ForegroundColor Green
+Write-Host ""
+
+# Revert Claude Code settings
+Write-Host "Reverting Claude Code settings..." -ForegroundColor Yellow
+
+# The first backup made by install.ps1 (settings before the install)
+$OldestBackup = Get-ChildItem "$SettingsFile.backup.*" -ErrorAction SilentlyContinue | Sort-Object Name | Select-Object -First 1
+
+# Whether a hook command / statusLine belongs to this app
+function Test-OurCommand {
+    param($command)
+    return $command -and $command -like "*\.claude-notify-scripts\*"
+}
+
+if (-not (Test-Path $SettingsFile)) {
+    Write-Host "  [SKIP] $SettingsFile not found" -ForegroundColor Yellow
+} elseif ($RestoreBackup -and $OldestBackup) {
+    Copy-Item $SettingsFile "$SettingsFile.uninstall.$(Get-Date -Format 'yyyyMMdd_HHmmss')"
+    Copy-Item $OldestBackup.FullName $SettingsFile -Force
+    Write-Host "  [OK] Restored $($OldestBackup.FullName)" -ForegroundColor Green
+} else {
+    if ($RestoreBackup) {
+        Write-Host "  [SKIP] No backup found, removing only this app's hooks" -ForegroundColor Yellow
+    }
+    Copy-Item $SettingsFile "$SettingsFile.uninstall.$(Get-Date -Format 'yyyyMMdd_HHmmss')"
+    $Settings = Get-Content $SettingsFile -Raw | ConvertFrom-Json -AsHashtable
+
+    # Keep user-defined hooks, drop this app's hooks and the hook types left empty
+    if ($Settings.ContainsKey("hooks")) {
+        foreach ($hookType in @($Settings["hooks"].Keys)) {
+            $remaining = @($Settings["hooks"][$hookType] | Where-Object {
+                -not ($_.hooks | Where-Object { Test-OurCommand $_.command })
+            })
+            if ($remaining.Count -gt 0) {
+                $Settings["hooks"][$hookType] = $remaining
+            } else {
+                $Settings["hooks"].Remove($hookType)
+            }
+        }
+        if ($Settings["hooks"].Count -eq 0) {
+            $Settings.Remove("hooks")
+        }
+    }
+    if ($Settings.ContainsKey("statusLine") -and (Test-OurCommand $Settings["statusLine"].command)) {
+        $Settings.Remove("statusLine")
+    }
+
+    $Settings | ConvertTo-Json -Depth 10 | Set-Content $SettingsFile -Encoding UTF8
+    Write-Host "  [OK] Removed this app's hooks" -ForegroundColor Green
+}
+
+# Remove scripts (other files in the directory are kept)
+Write-Host ""
+Write-Host "Removing scripts from $ScriptsDir..." -ForegroundColor Yellow
+if (Test-Path $ScriptsDir) {
+    foreach ($script in $Scripts) {
+        Remove-Item "$ScriptsDir\$script" -Force -ErrorAction SilentlyContinue
+    }
+    if (-not (Get-ChildItem $ScriptsDir -Force)) {
+        Remove-Item $ScriptsDir -Force
+        Write-Host "  [OK] Scripts removed" -ForegroundColor Green
+    } else {
+        Write-Host "  [SKIP] $ScriptsDir kept because it contains other files" -ForegroundColor Yellow
+    }
+} else {
+    Write-Host "  [SKIP] $ScriptsDir not found" -ForegroundColor Yellow
+}
+
+Write-Host ""
+Write-Host "========================================" -ForegroundColor Green
+Write-Host "Uninstall complete!" -ForegroundColor Green
+Write-Host ""
+Write-Host "Please restart Claude Code to apply changes."
 Write-Host "========================================" -ForegroundColor Green
 "#;
 
@@ -989,7 +2317,7 @@ pub const CLAUDE_SETTINGS_SNIPPET_WINDOWS: &str = r#"{
         "hooks": [
           {
             "type": "command",
-            "command": "powershell.exe -ExecutionPolicy Bypass -File \"__SCRIPTS_DIR__\\on-stop.ps1\""
+            "command": "powershell.exe -NoProfile -ExecutionPolicy Bypass -File \"__SCRIPTS_DIR__\\on-stop.ps1\""
           }
         ]
       }
@@ -1000,7 +2328,7 @@ pub const CLAUDE_SETTINGS_SNIPPET_WINDOWS: &str = r#"{
         "hooks": [
           {
             "type": "command",
-            "command": "powershell.exe -ExecutionPolicy Bypass -File \"__SCRIPTS_DIR__\\on-permission-request.ps1\""
+            "command": "powershell.exe -NoProfile -ExecutionPolicy Bypass -File \"__SCRIPTS_DIR__\\on-permission-request.ps1\""
           }
         ]
       }
@@ -1011,7 +2339,7 @@ pub const CLAUDE_SETTINGS_SNIPPET_WINDOWS: &str = r#"{
         "hooks": [
           {
             "type": "command",
-            "command": "powershell.exe -ExecutionPolicy Bypass -File \"__SCRIPTS_DIR__\\on-notification.ps1\""
+            "command": "powershell.exe -NoProfile -ExecutionPolicy Bypass -File \"__SCRIPTS_DIR__\\on-notification.ps1\""
           }
         ]
       }
@@ -1042,6 +2370,11 @@ pub const README_WINDOWS_TEMPLATE: &str = r#"Claude Code Notify セットアッ
 
 3. Claude Code を再起動
 
+※ アンインストール:
+   & "$env:USERPROFILE\.claude-notify-scripts\uninstall.ps1"
+   settings.json からこのアプリのフックだけを削除します。
+   最初のインストール前の設定に戻す場合は -RestoreBackup を付けてください。
+
 
 【手動セットアップ】
 ====================================
@@ -1053,6 +2386,7 @@ pub const README_WINDOWS_TEMPLATE: &str = r#"Claude Code Notify セットアッ
 - on-permission-request.ps1: 承認依頼通知 (PermissionRequest hook)
 - on-notification.ps1     : 入力要求通知 (Notification hook)
 - statusline.ps1          : ステータスライン更新 (オプション)
+- uninstall.ps1           : フックの設定とスクリプトの削除
 
 2. ファイルの配置
 -------------------
@@ -1132,6 +2466,25 @@ mod tests {
         );
     }
 
+    /// アンインストーラがインストールしたスクリプトをすべて削除し、インストーラがアンインストーラを残すことを確認
+    #[test]
+    fn test_uninstallers_remove_installed_scripts() {
+        let linux = crate::export::LINUX_SCRIPTS
+            .iter()
+            .chain(crate::export::FISH_HOOKS)
+            .chain(crate::export::NUSHELL_HOOKS);
+        for (file_name, _) in linux.filter(|(name, _)| *name != "install.sh") {
+            assert!(UNINSTALL_SH.contains(file_name), "uninstall.sh should remove {}", file_name);
+        }
+        for (file_name, _) in crate::export::WINDOWS_SCRIPTS.iter().filter(|(name, _)| *name != "install.ps1") {
+            assert!(UNINSTALL_PS1.contains(file_name), "uninstall.ps1 should remove {}", file_name);
+        }
+        assert!(UNINSTALL_PS1.contains("mqtt-publish.exe"));
+
+        assert!(INSTALL_SH.contains(r#"cp "$SCRIPT_SOURCE_DIR/uninstall.sh""#));
+        assert!(INSTALL_PS1.contains(r#"Copy-Item "$ScriptSourceDir\uninstall.ps1""#));
+    }
+
     /// PowerShellスクリプトテンプレートのプレースホルダーが存在することを確認
     #[test]
     fn test_templates_have_placeholders() {
@@ -1142,8 +2495,23 @@ mod tests {
         assert!(ON_NOTIFICATION_SH.contains("__HOST__"));
         assert!(STATUSLINE_SH.contains("__HOST__"));
         assert!(INSTALL_SH.contains("__HOST__"));
+        assert!(ON_STOP_SH.contains("__SECRET__"));
+        assert!(STATUSLINE_SH.contains("__SECRET__"));
+        assert!(ON_STOP_SH.contains("__ENCRYPTION_KEY__"));
+        assert!(ON_PERMISSION_REQUEST_SH.contains("__RESPONSE_TIMEOUT__"));
+        assert!(AWAIT_PERMISSION_RESPONSE_SH.contains("__HOST__"));
+        assert!(AWAIT_PERMISSION_RESPONSE_SH.contains("__SECRET__"));
+        assert!(CONTROL_LISTENER_SH.contains("__SECRET__"));
+        assert!(CONTROL_LISTENER_SH.contains("claude-code/control-reply/"));
+        assert!(STATUSLINE_SH.contains("__SESSION_CONTROL__"));
+        assert!(STATUSLINE_SH.contains("control-listener.sh"));
+        assert!(STATUSLINE_SH.contains("__HEARTBEAT_INTERVAL__"));
+        assert!(RESOLVE_HOST_SH.contains("__DISCOVERY__"));
 
         // Windows templates
+        assert!(ON_STOP_PS1.contains("__SECRET__"));
+        assert!(STATUSLINE_PS1.contains("__SECRET__"));
+        assert!(ON_STOP_PS1.contains("__ENCRYPTION_KEY__"));
         assert!(ON_STOP_PS1.contains("__HOST__"));
         assert!(ON_STOP_PS1.contains("__PORT__"));
         assert!(ON_PERMISSION_REQUEST_PS1.contains("__HOST__"));
@@ -1152,26 +2520,151 @@ mod tests {
         assert!(INSTALL_PS1.contains("__HOST__"));
     }
 
-    /// PowerShellスクリプトがConvertTo-Jsonを使用していることを確認
+    /// 送信元を区別できるよう、すべてのイベントにホスト名が含まれることを確認
     #[test]
-    fn test_powershell_scripts_use_convertto_json() {
-        // 各スクリプトがConvertTo-Jsonを使用してJSONを生成していることを確認
-        // これにより、バックスラッシュなどが正しくエスケープされる
-        assert!(
-            ON_STOP_PS1.contains("ConvertTo-Json"),
-            "on-stop.ps1 should use ConvertTo-Json for proper escaping"
-        );
-        assert!(
-            ON_PERMISSION_REQUEST_PS1.contains("ConvertTo-Json"),
-            "on-permission-request.ps1 should use ConvertTo-Json"
-        );
-        assert!(
-            ON_NOTIFICATION_PS1.contains("ConvertTo-Json"),
-            "on-notification.ps1 should use ConvertTo-Json"
-        );
-        assert!(
-            STATUSLINE_PS1.contains("ConvertTo-Json"),
-            "statusline.ps1 should use ConvertTo-Json"
-        );
+    fn test_payloads_include_source_host() {
+        for template in [ON_STOP_SH, ON_PERMISSION_REQUEST_SH, ON_NOTIFICATION_SH, STATUSLINE_SH] {
+            assert!(template.contains(r#""host": "${SOURCE_HOST}""#));
+        }
+        for template in [ON_STOP_PS1, ON_PERMISSION_REQUEST_PS1, ON_NOTIFICATION_PS1] {
+            assert!(template.contains("--source-host $SourceHost"));
+        }
+        assert!(STATUSLINE_PS1.contains("--source-host $sourceHost"));
+    }
+
+    /// 停止イベントに Claude の最後のメッセージの抜粋が含まれることを確認
+    #[test]
+    fn test_stop_hooks_send_last_message() {
+        for template in [ON_STOP_SH, ON_STOP_FISH, ON_STOP_NU] {
+            assert!(template.contains("transcript_path"));
+            assert!(template.contains("last_message"));
+            assert!(template.contains("__LAST_MESSAGE_LENGTH__"));
+        }
+        assert!(ON_STOP_PS1.contains("--last-message-length __LAST_MESSAGE_LENGTH__"));
+    }
+
+    /// WSL のトランスクリプトを Windows から開けるよう、イベントにディストリビューション名が含まれることを確認
+    #[test]
+    fn test_hooks_send_wsl_distro() {
+        for template in [ON_STOP_SH, ON_PERMISSION_REQUEST_SH, ON_NOTIFICATION_SH] {
+            assert!(template.contains(r#""wsl_distro": "${WSL_DISTRO_NAME}""#));
+        }
+        for template in [ON_STOP_FISH, ON_PERMISSION_REQUEST_FISH, ON_NOTIFICATION_FISH, ON_STOP_NU, ON_PERMISSION_REQUEST_NU, ON_NOTIFICATION_NU] {
+            assert!(template.contains("WSL_DISTRO_NAME"));
+        }
+        assert!(ON_STOP_SH.contains(r#""transcript_path": "${TRANSCRIPT}""#));
+    }
+
+    /// 古いスクリプトを検出できるよう、イベントにテンプレートのバージョンが含まれることを確認
+    #[test]
+    fn test_event_payloads_include_template_version() {
+        for template in [ON_STOP_SH, ON_PERMISSION_REQUEST_SH, ON_NOTIFICATION_SH] {
+            assert!(template.contains(r#""template_version": __TEMPLATE_VERSION__"#));
+        }
+        for template in [ON_STOP_PS1, ON_PERMISSION_REQUEST_PS1, ON_NOTIFICATION_PS1] {
+            assert!(template.contains("--template-version __TEMPLATE_VERSION__"));
+        }
+        for (_, template) in crate::export::LINUX_SCRIPTS.iter().chain(crate::export::WINDOWS_SCRIPTS) {
+            assert!(template.contains(" v__TEMPLATE_VERSION__"));
+        }
+    }
+
+    /// 接続先が auto の場合に、すべてのスクリプトが Docker のホストを検出することを確認
+    #[test]
+    fn test_scripts_resolve_auto_host() {
+        for template in [
+            ON_STOP_SH,
+            ON_PERMISSION_REQUEST_SH,
+            AWAIT_PERMISSION_RESPONSE_SH,
+            CONTROL_LISTENER_SH,
+            ON_NOTIFICATION_SH,
+            STATUSLINE_SH,
+            ON_STOP_FISH,
+            ON_PERMISSION_REQUEST_FISH,
+            ON_NOTIFICATION_FISH,
+            ON_STOP_NU,
+            ON_PERMISSION_REQUEST_NU,
+            ON_NOTIFICATION_NU,
+        ] {
+            assert!(template.contains("resolve-host.sh"));
+        }
+        assert!(INSTALL_SH.contains(r#"cp "$SCRIPT_SOURCE_DIR/resolve-host.sh""#));
+        assert!(RESOLVE_HOST_SH.contains("host.docker.internal"));
+        assert!(RESOLVE_HOST_SH.contains("_claude-notify._tcp"));
+    }
+
+    /// アプリから送った ~/.claude/notify.env をすべてのスクリプトが読むことを確認
+    #[test]
+    fn test_scripts_read_notify_env() {
+        for template in [
+            ON_STOP_SH,
+            ON_PERMISSION_REQUEST_SH,
+            AWAIT_PERMISSION_RESPONSE_SH,
+            CONTROL_LISTENER_SH,
+            ON_NOTIFICATION_SH,
+            STATUSLINE_SH,
+            ON_STOP_FISH,
+            ON_PERMISSION_REQUEST_FISH,
+            ON_NOTIFICATION_FISH,
+            ON_STOP_NU,
+            ON_PERMISSION_REQUEST_NU,
+            ON_NOTIFICATION_NU,
+            ON_STOP_PS1,
+            ON_PERMISSION_REQUEST_PS1,
+            ON_NOTIFICATION_PS1,
+            STATUSLINE_PS1,
+        ] {
+            assert!(template.contains("CLAUDE_NOTIFY_ENV_FILE"));
+            assert!(template.contains(".claude") && template.contains("notify.env"));
+        }
+    }
+
+    /// fish・Nushell のフックが bash 版と同じイベントを送ることを確認
+    #[test]
+    fn test_fish_and_nushell_hooks() {
+        let hooks = [
+            (ON_STOP_FISH, ON_STOP_NU, "claude-code/events/stop"),
+            (ON_PERMISSION_REQUEST_FISH, ON_PERMISSION_REQUEST_NU, "claude-code/events/permission-request"),
+            (ON_NOTIFICATION_FISH, ON_NOTIFICATION_NU, "claude-code/events/notification"),
+        ];
+        for (fish, nu, topic) in hooks {
+            assert!(fish.starts_with("#!/usr/bin/env fish\n"));
+            assert!(nu.starts_with("#!/usr/bin/env -S nu --stdin\n"));
+            assert!(fish.contains("template_version: __TEMPLATE_VERSION__, "));
+            assert!(nu.contains("template_version: __TEMPLATE_VERSION__\n"));
+            for template in [fish, nu] {
+                assert!(template.contains(topic));
+                assert!(template.contains(" v__TEMPLATE_VERSION__"));
+                for placeholder in ["__HOST__", "__PORT__", "__SECRET__", "__ENCRYPTION_KEY__"] {
+                    assert!(template.contains(placeholder), "{} should contain {}", topic, placeholder);
+                }
+                assert!(template.contains("mqtt-publish"));
+                assert!(template.contains(r#"-I "claude-code-" -u "claude-code-notify""#));
+            }
+        }
+        for template in [ON_PERMISSION_REQUEST_FISH, ON_PERMISSION_REQUEST_NU] {
+            assert!(template.contains("__RESPONSE_TIMEOUT__"));
+            assert!(template.contains("await-permission-response.sh"));
+        }
+    }
+
+    /// PowerShellスクリプトがペイロードの生成を mqtt-publish.exe に任せていることを確認
+    #[test]
+    fn test_powershell_scripts_use_mqtt_publish_events() {
+        // mqtt-publish.exe が JSON を生成するため、バックスラッシュや日本語のパスも正しくエスケープされる
+        let hooks = [
+            (ON_STOP_PS1, "--event stop "),
+            (ON_PERMISSION_REQUEST_PS1, "--event permission-request "),
+            (ON_NOTIFICATION_PS1, "--event notification "),
+            (STATUSLINE_PS1, "--event status "),
+        ];
+        for (template, event) in hooks {
+            assert!(template.contains(event), "should publish with {}", event);
+            assert!(template.contains("mqtt-publish.exe"));
+            assert!(!template.contains("ConvertTo-Json"));
+        }
+        // cmd.exe から起動された場合もプロファイルの出力が混ざらないようにする
+        assert!(CLAUDE_SETTINGS_SNIPPET_WINDOWS.contains("powershell.exe -NoProfile -ExecutionPolicy Bypass -File"));
+        assert!(INSTALL_PS1.contains("powershell.exe -NoProfile -ExecutionPolicy Bypass -File"));
     }
 }
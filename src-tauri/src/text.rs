@@ -0,0 +1,98 @@
+//! 文字列の切り詰めモジュール
+//!
+//! バイト位置で切り詰めると、日本語などのマルチバイト文字の途中で panic する。
+//! 書記素クラスタ（絵文字の結合・結合文字を含めて見た目の1文字）単位で切り詰め、
+//! 通知・セッション名に表示する文字列が壊れないようにする。
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 省略を示す文字
+pub const ELLIPSIS: &str = "…";
+
+/// 書記素クラスタの数
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// 末尾の省略記号を含めて `max_graphemes` 文字以内に切り詰める
+pub fn truncate(text: &str, max_graphemes: usize) -> String {
+    truncate_with(text, max_graphemes, ELLIPSIS)
+}
+
+/// 末尾の `ellipsis` を含めて `max_graphemes` 文字以内に切り詰める（収まる場合はそのまま返す）
+pub fn truncate_with(text: &str, max_graphemes: usize, ellipsis: &str) -> String {
+    // 収まるかどうかを確認するため、上限の1文字先まで数える
+    let mut graphemes = text.grapheme_indices(true);
+    if graphemes.nth(max_graphemes).is_none() {
+        return text.to_string();
+    }
+    let keep = max_graphemes.saturating_sub(grapheme_count(ellipsis));
+    let end = text
+        .grapheme_indices(true)
+        .nth(keep)
+        .map_or(text.len(), |(index, _)| index);
+    format!("{}{}", &text[..end], ellipsis)
+}
+
+/// 中央を省略記号にして `max_graphemes` 文字以内に切り詰める（コマンドの先頭と末尾を残す、0 の場合は切り詰めない）
+pub fn truncate_middle(text: &str, max_graphemes: usize) -> String {
+    let graphemes: Vec<&str> = text.graphemes(true).collect();
+    if max_graphemes == 0 || graphemes.len() <= max_graphemes {
+        return text.to_string();
+    }
+    let keep = max_graphemes.saturating_sub(grapheme_count(ELLIPSIS));
+    // 奇数の場合は先頭（コマンド名）を1文字多く残す
+    let tail = keep / 2;
+    let head = keep - tail;
+    format!(
+        "{}{}{}",
+        graphemes[..head].concat(),
+        ELLIPSIS,
+        graphemes[graphemes.len() - tail..].concat()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_unchanged() {
+        assert_eq!(truncate("my-app", 6), "my-app");
+        assert_eq!(truncate("", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_cjk() {
+        let text = "日本語のプロジェクト名";
+        assert_eq!(truncate(text, 5), "日本語の…");
+        assert_eq!(truncate_with(text, 6, "..."), "日本語...");
+        assert_eq!(grapheme_count(&truncate(text, 5)), 5);
+    }
+
+    #[test]
+    fn test_truncate_keeps_emoji_clusters() {
+        // 肌の色・ZWJ で結合した絵文字は1文字として扱い、途中で切らない
+        let text = "👍🏽👨‍👩‍👧‍👦🎉✨";
+        assert_eq!(grapheme_count(text), 4);
+        assert_eq!(truncate(text, 3), "👍🏽👨‍👩‍👧‍👦…");
+        // 結合文字（が = か + 濁点）
+        assert_eq!(truncate("か\u{3099}き\u{3099}く\u{3099}", 2), "か\u{3099}…");
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        let command = "rm -rf ./build && npm run deploy -- --prod";
+        assert_eq!(truncate_middle(command, 15), "rm -rf … --prod");
+        assert_eq!(grapheme_count(&truncate_middle(command, 15)), 15);
+        assert_eq!(truncate_middle("日本語のコマンド", 5), "日本…ンド");
+        // 収まる場合・上限が 0 の場合はそのまま
+        assert_eq!(truncate_middle("ls -la", 6), "ls -la");
+        assert_eq!(truncate_middle(command, 0), command);
+    }
+
+    #[test]
+    fn test_ellipsis_longer_than_limit() {
+        assert_eq!(truncate_with("abcdef", 2, "..."), "...");
+    }
+}
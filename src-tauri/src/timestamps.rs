@@ -0,0 +1,80 @@
+//! ペイロードの時刻のモジュール
+//!
+//! フックが送る `timestamp`（`date -Iseconds` などの RFC 3339）を UTC の時刻として読む。
+//! タイムゾーンのない時刻（`2026-10-17T10:00:00`・`2026-10-17 10:00:00`）はこのPCのタイムゾーンとみなす。
+//! 読めない値はエラーにせず None とし、呼び出し側は受信した時刻で代用する。
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Deserializer};
+
+/// タイムゾーンのない時刻の書式
+const NAIVE_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// 時刻の文字列を読む（読めない場合は None）
+pub fn parse(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// ペイロードの `timestamp`
+pub fn payload_timestamp(payload: &str) -> Option<DateTime<Utc>> {
+    let value: serde_json::Value = serde_json::from_str(payload).ok()?;
+    parse(value.get("timestamp")?.as_str()?)
+}
+
+/// serde: `timestamp` を読む（文字列でない値・読めない値は None にし、ペイロード全体をエラーにしない）
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(serde_json::Value::as_str).and_then(parse))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let expected = DateTime::parse_from_rfc3339("2026-10-17T01:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(parse("2026-10-17T10:00:00+09:00"), Some(expected));
+        assert_eq!(parse(" 2026-10-17T01:00:00Z\n"), Some(expected));
+        // PowerShell の Get-Date -Format o
+        assert!(parse("2026-10-17T10:00:00.1234567+09:00").is_some());
+        // タイムゾーンのない時刻はこのPCのタイムゾーン
+        let local = Local.with_ymd_and_hms(2026, 10, 17, 10, 0, 0).unwrap().with_timezone(&Utc);
+        assert_eq!(parse("2026-10-17T10:00:00"), Some(local));
+        assert_eq!(parse("2026-10-17 10:00:00"), Some(local));
+
+        assert_eq!(parse("yesterday"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn test_payload_and_deserialize() {
+        assert!(payload_timestamp(r#"{"event":"stop","timestamp":"2026-10-17T10:00:00+09:00"}"#).is_some());
+        assert_eq!(payload_timestamp(r#"{"event":"stop"}"#), None);
+        assert_eq!(payload_timestamp("not json"), None);
+
+        #[derive(Deserialize)]
+        struct Payload {
+            #[serde(default, deserialize_with = "deserialize")]
+            timestamp: Option<DateTime<Utc>>,
+        }
+        let parse_payload = |json: &str| serde_json::from_str::<Payload>(json).unwrap().timestamp;
+        assert!(parse_payload(r#"{"timestamp":"2026-10-17T10:00:00+09:00"}"#).is_some());
+        // 読めない値・文字列でない値・ない場合もペイロードは読める
+        assert_eq!(parse_payload(r#"{"timestamp":"$(date -Iseconds)"}"#), None);
+        assert_eq!(parse_payload(r#"{"timestamp":1760662800}"#), None);
+        assert_eq!(parse_payload(r#"{"timestamp":null}"#), None);
+        assert_eq!(parse_payload("{}"), None);
+    }
+}
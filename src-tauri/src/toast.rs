@@ -0,0 +1,315 @@
+//! トースト通知モジュール
+//!
+//! 通知の優先度に応じて表示スタイルを切り替える。
+//! - Windows: WinRT トーストの表示時間・シナリオ
+//! - Linux: 通知デーモンの urgency ヒント・タイムアウト・アイコン
+//! - その他: tauri-plugin-notification による通常表示
+//!
+//! 添付画像は Windows ではヒーロー画像、Linux では image-path ヒントとして表示する。
+
+use crate::i18n::Strings;
+use crate::notification_history::NotificationEventType;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::AppHandle;
+#[cfg(not(any(windows, target_os = "linux")))]
+use tauri_plugin_notification::NotificationExt;
+use tracing::{error, info};
+
+/// 通知の優先度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationPriority {
+    /// 完了報告など、見逃しても問題ない通知
+    Low,
+    #[default]
+    Normal,
+    /// 入力待ちなど、早めの対応が望ましい通知
+    High,
+    /// 承認依頼など、対応するまで作業が止まる通知
+    Critical,
+}
+
+impl NotificationPriority {
+    /// イベント種別に対応する既定の優先度
+    pub fn for_event(event_type: &NotificationEventType) -> Self {
+        match event_type {
+            NotificationEventType::Stop => NotificationPriority::Low,
+            NotificationEventType::Notification => NotificationPriority::High,
+            NotificationEventType::PermissionRequest => NotificationPriority::Critical,
+        }
+    }
+
+    /// 優先度に対応する表示スタイル
+    pub fn style(self) -> ToastStyle {
+        match self {
+            NotificationPriority::Low => ToastStyle {
+                long_duration: false,
+                persistent: false,
+                icon_name: "dialog-information",
+            },
+            NotificationPriority::Normal => ToastStyle {
+                long_duration: false,
+                persistent: false,
+                icon_name: "dialog-information",
+            },
+            NotificationPriority::High => ToastStyle {
+                long_duration: true,
+                persistent: false,
+                icon_name: "dialog-question",
+            },
+            NotificationPriority::Critical => ToastStyle {
+                long_duration: true,
+                persistent: true,
+                icon_name: "dialog-warning",
+            },
+        }
+    }
+}
+
+/// プラットフォーム共通の表示スタイル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToastStyle {
+    /// 通常より長く表示する
+    pub long_duration: bool,
+    /// ユーザーが閉じるまで表示し続ける
+    pub persistent: bool,
+    /// freedesktop のアイコン名（Linux のみ使用）
+    pub icon_name: &'static str,
+}
+
+/// トーストに表示する内容
+#[derive(Debug, Clone, Default)]
+pub struct ToastContent {
+    pub title: String,
+    pub body: String,
+    /// 添付画像のパス（対応しているプラットフォームのみ表示）
+    pub image: Option<PathBuf>,
+}
+
+/// トーストに表示するボタン
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToastAction {
+    /// クリック時にコールバックへ渡すID
+    pub id: &'static str,
+    pub label: &'static str,
+}
+
+/// トーストがクリックされた時に呼ばれるコールバック
+///
+/// ボタンのクリックではボタンのID、本文のクリックでは None を受け取る。
+pub type OnActivated = Box<dyn FnOnce(Option<&str>) + Send + 'static>;
+
+/// トースト通知を表示する（表示処理はバックグラウンドで行う）
+///
+/// `actions` のボタンと `on_activated` はコールバックがある場合のみ使われる（Windows / Linux のみ）。
+pub fn show_toast(
+    app: &AppHandle,
+    content: ToastContent,
+    priority: NotificationPriority,
+    strings: &'static Strings,
+    actions: &[ToastAction],
+    on_activated: Option<OnActivated>,
+) {
+    let style = priority.style();
+    info!("Showing toast notification ({:?})", priority);
+
+    #[cfg(windows)]
+    {
+        let app_id = windows_app_id(app);
+        let actions = actions.to_vec();
+        tauri::async_runtime::spawn_blocking(move || {
+            show_windows_toast(&app_id, &content, style, strings, &actions, on_activated);
+        });
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = strings;
+        let app_name = app.package_info().name.clone();
+        let actions = actions.to_vec();
+        tauri::async_runtime::spawn_blocking(move || {
+            show_linux_notification(&app_name, &content, style, &actions, on_activated);
+        });
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        let _ = (style, strings, actions, on_activated);
+        match app.notification().builder().title(&content.title).body(&content.body).show() {
+            Ok(_) => info!("Toast notification sent"),
+            Err(e) => error!("Failed to show toast notification: {}", e),
+        }
+    }
+}
+
+/// トーストに使用する AppUserModelID
+///
+/// 開発ビルド（target/debug, target/release から起動）ではアプリが登録されていないため、
+/// PowerShell の ID を使用する（tauri-plugin-notification と同じ挙動）。
+#[cfg(windows)]
+fn windows_app_id(app: &AppHandle) -> String {
+    use std::path::Path;
+
+    let installed = tauri::utils::platform::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .map(|dir| {
+            !(dir.ends_with(Path::new("target").join("debug"))
+                || dir.ends_with(Path::new("target").join("release")))
+        })
+        .unwrap_or(false);
+
+    if installed {
+        app.config().identifier.clone()
+    } else {
+        tauri_winrt_notification::Toast::POWERSHELL_APP_ID.to_string()
+    }
+}
+
+#[cfg(windows)]
+fn show_windows_toast(
+    app_id: &str,
+    content: &ToastContent,
+    style: ToastStyle,
+    strings: &Strings,
+    actions: &[ToastAction],
+    on_activated: Option<OnActivated>,
+) {
+    use tauri_winrt_notification::{Duration, Scenario, Toast};
+
+    let duration = if style.long_duration {
+        Duration::Long
+    } else {
+        Duration::Short
+    };
+
+    // 通知音はアプリ側で鳴らすため、トーストは無音にする
+    let mut toast = Toast::new(app_id)
+        .title(&content.title)
+        .text1(&content.body)
+        .sound(None)
+        .duration(duration);
+
+    if let Some(image) = content.image.as_deref() {
+        toast = toast.hero(image, "");
+    }
+
+    if on_activated.is_some() {
+        for action in actions {
+            toast = toast.add_button(action.label, action.id);
+        }
+    }
+
+    // reminder シナリオはボタンが1つ以上ないと通常のトーストとして扱われる
+    if style.persistent {
+        toast = toast
+            .scenario(Scenario::Reminder)
+            .add_button(strings.toast_dismiss, "dismiss");
+    }
+
+    // 本文・ボタンのどちらがクリックされても確認済みとして扱う
+    if let Some(callback) = on_activated {
+        let mut callback = Some(callback);
+        toast = toast.on_activated(move |action| {
+            if let Some(callback) = callback.take() {
+                callback(action.as_deref());
+            }
+            Ok(())
+        });
+    }
+
+    match toast.show() {
+        Ok(_) => info!("Toast notification sent"),
+        Err(e) => error!("Failed to show toast notification: {:?}", e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn show_linux_notification(
+    app_name: &str,
+    content: &ToastContent,
+    style: ToastStyle,
+    actions: &[ToastAction],
+    on_activated: Option<OnActivated>,
+) {
+    use notify_rust::{Notification, Timeout, Urgency};
+
+    let (urgency, timeout) = if style.persistent {
+        (Urgency::Critical, Timeout::Never)
+    } else if style.long_duration {
+        (Urgency::Normal, Timeout::Milliseconds(15_000))
+    } else {
+        (Urgency::Low, Timeout::Default)
+    };
+
+    let mut notification = Notification::new();
+    notification
+        .appname(app_name)
+        .summary(&content.title)
+        .body(&content.body)
+        .icon(style.icon_name)
+        .urgency(urgency)
+        .timeout(timeout);
+    if let Some(image) = content.image.as_deref() {
+        notification.image_path(&image.to_string_lossy());
+    }
+    // "default" アクションは通知本体のクリックで発火する
+    if on_activated.is_some() {
+        notification.action("default", "");
+        for action in actions {
+            notification.action(action.id, action.label);
+        }
+    }
+
+    match notification.show() {
+        Ok(handle) => {
+            info!("Toast notification sent");
+            if let Some(callback) = on_activated {
+                handle.wait_for_action(|action| match action {
+                    "default" => callback(None),
+                    // 通知が閉じられた場合は確認済みとして扱わない
+                    "__closed" => {}
+                    action => callback(Some(action)),
+                });
+            }
+        }
+        Err(e) => error!("Failed to show toast notification: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_for_event() {
+        assert_eq!(
+            NotificationPriority::for_event(&NotificationEventType::Stop),
+            NotificationPriority::Low
+        );
+        assert_eq!(
+            NotificationPriority::for_event(&NotificationEventType::Notification),
+            NotificationPriority::High
+        );
+        assert_eq!(
+            NotificationPriority::for_event(&NotificationEventType::PermissionRequest),
+            NotificationPriority::Critical
+        );
+    }
+
+    #[test]
+    fn test_only_critical_is_persistent() {
+        assert!(NotificationPriority::Critical.style().persistent);
+        assert!(!NotificationPriority::High.style().persistent);
+        assert!(NotificationPriority::High.style().long_duration);
+        assert!(!NotificationPriority::Low.style().long_duration);
+    }
+
+    #[test]
+    fn test_priority_ordering() {
+        assert!(NotificationPriority::Critical > NotificationPriority::High);
+        assert!(NotificationPriority::High > NotificationPriority::Normal);
+        assert!(NotificationPriority::Normal > NotificationPriority::Low);
+    }
+}
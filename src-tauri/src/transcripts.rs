@@ -0,0 +1,171 @@
+//! セッションのトランスクリプトモジュール
+//!
+//! Claude Code がフックに渡すトランスクリプト（`transcript_path`、JSON Lines）を通知履歴から開けるようにし、
+//! 履歴の検索でトランスクリプトの本文も探せるように索引を作る。
+//!
+//! WSL のフックが送るパス（`/home/...`・`/mnt/c/...`）は、Windows から読めるパス
+//! （`\\wsl.localhost\<ディストリビューション>\home\...`・`C:\...`）に変換して記録する。
+//! その他のリモートのマシンのトランスクリプトは、このマシンから読めないため開けない。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+use tracing::warn;
+
+/// 索引に含めるトランスクリプトの最大サイズ（これより大きいものは末尾だけを読む）
+const MAX_INDEX_BYTES: u64 = 8 * 1024 * 1024;
+
+/// WSL のパスを Windows から読めるパスに変換する（WSL 以外のパスはそのまま）
+fn windows_path(path: &str, wsl_distro: Option<&str>) -> String {
+    // Windows のドライブ（/mnt/c/...）
+    if let Some(rest) = path.strip_prefix("/mnt/") {
+        let mut chars = rest.chars();
+        if let (Some(drive), None | Some('/')) = (chars.next(), chars.next()) {
+            if drive.is_ascii_alphabetic() {
+                let rest = rest[1..].trim_start_matches('/').replace('/', "\\");
+                return format!("{}:\\{}", drive.to_ascii_uppercase(), rest);
+            }
+        }
+    }
+    // ディストリビューション内のファイル
+    match wsl_distro.map(str::trim).filter(|distro| !distro.is_empty()) {
+        Some(distro) if path.starts_with('/') => {
+            format!("\\\\wsl.localhost\\{}{}", distro, path.replace('/', "\\"))
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// フックが送ったトランスクリプトのパスを、このマシンで読めるパスにする
+pub fn local_path(path: Option<&str>, wsl_distro: Option<&str>) -> Option<String> {
+    let path = path.map(str::trim).filter(|path| !path.is_empty())?;
+    if cfg!(windows) {
+        Some(windows_path(path, wsl_distro))
+    } else {
+        Some(path.to_string())
+    }
+}
+
+/// トランスクリプトの1行から、会話の本文（ユーザーと Claude のテキスト）を取り出す
+fn message_text(line: &str) -> Option<String> {
+    let entry: serde_json::Value = serde_json::from_str(line).ok()?;
+    match entry.get("type").and_then(|t| t.as_str()) {
+        Some("user") | Some("assistant") => {}
+        _ => return None,
+    }
+    let content = entry.pointer("/message/content")?;
+    if let Some(text) = content.as_str() {
+        return Some(text.to_string());
+    }
+    let text: Vec<&str> = content
+        .as_array()?
+        .iter()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+        .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+        .collect();
+    (!text.is_empty()).then(|| text.join("\n"))
+}
+
+/// トランスクリプトの本文（検索用に小文字にしたもの）
+fn index_text(transcript: &str) -> String {
+    transcript
+        .lines()
+        .filter_map(message_text)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .to_lowercase()
+}
+
+/// トランスクリプトを読む（大きい場合は末尾だけ）
+fn read_transcript(path: &Path, len: u64) -> std::io::Result<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    if len > MAX_INDEX_BYTES {
+        file.seek(SeekFrom::Start(len - MAX_INDEX_BYTES))?;
+    }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+struct IndexedTranscript {
+    modified: SystemTime,
+    text: String,
+}
+
+/// トランスクリプトの検索用の索引（更新されたファイルだけを読み直す）
+#[derive(Default)]
+pub struct TranscriptIndex {
+    transcripts: Mutex<HashMap<String, IndexedTranscript>>,
+}
+
+impl TranscriptIndex {
+    /// トランスクリプトに文字列（小文字にしたもの）が含まれるか（読めない場合は false）
+    pub fn contains(&self, path: &str, text: &str) -> bool {
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return false;
+        };
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut transcripts = self.transcripts.lock().unwrap();
+        if transcripts.get(path).is_none_or(|indexed| indexed.modified != modified) {
+            match read_transcript(Path::new(path), metadata.len()) {
+                Ok(transcript) => {
+                    transcripts.insert(path.to_string(), IndexedTranscript {
+                        modified,
+                        text: index_text(&transcript),
+                    });
+                }
+                Err(e) => {
+                    warn!("Failed to index transcript {}: {}", path, e);
+                    return false;
+                }
+            }
+        }
+        transcripts.get(path).is_some_and(|indexed| indexed.text.contains(text))
+    }
+}
+
+/// トランスクリプトを既定のアプリで開く
+pub fn open(app: &AppHandle, path: &str) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(format!("Transcript not found: {}", path.display()));
+    }
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open transcript: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_path() {
+        assert_eq!(
+            windows_path("/home/me/.claude/projects/app/abc.jsonl", Some("Ubuntu")),
+            r"\\wsl.localhost\Ubuntu\home\me\.claude\projects\app\abc.jsonl"
+        );
+        assert_eq!(windows_path("/mnt/c/Users/me/abc.jsonl", Some("Ubuntu")), r"C:\Users\me\abc.jsonl");
+        // WSL 以外のパス・ディストリビューションが分からない場合はそのまま
+        assert_eq!(windows_path(r"C:\Users\me\abc.jsonl", None), r"C:\Users\me\abc.jsonl");
+        assert_eq!(windows_path("/home/me/abc.jsonl", None), "/home/me/abc.jsonl");
+        assert_eq!(windows_path("/mnt/data/abc.jsonl", Some("Ubuntu")), r"\\wsl.localhost\Ubuntu\mnt\data\abc.jsonl");
+    }
+
+    #[test]
+    fn test_index_text() {
+        let transcript = [
+            r#"{"type":"user","message":{"role":"user","content":"Fix the LOGIN bug"}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"修正しました"},{"type":"tool_use","input":{"secret":"x"}}]}}"#,
+            r#"{"type":"summary","summary":"ignored"}"#,
+            "not json",
+        ]
+        .join("\n");
+        assert_eq!(index_text(&transcript), "fix the login bug\n修正しました");
+    }
+}
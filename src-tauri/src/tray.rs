@@ -3,33 +3,133 @@
 //! This module provides system tray functionality including
 //! icon management, context menu, and event handling.
 
-use crate::NotificationManager;
-use std::sync::Arc;
+use crate::i18n::{self, Strings};
+use crate::{profiles, settings, updater, window_placement, NotificationManager};
+use std::sync::{Arc, Mutex};
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
-    App, AppHandle, Emitter, Manager,
+    App, AppHandle, Emitter, Manager, Wry,
 };
 use tracing::{debug, info, warn};
 
 mod menu_ids {
     pub const STATUS: &str = "status";
+    pub const PROFILES: &str = "profiles";
+    /// プロファイルの項目のID（後ろにプロファイル名が続く）
+    pub const PROFILE_PREFIX: &str = "profile:";
+    pub const DO_NOT_DISTURB: &str = "do_not_disturb";
     pub const SETTINGS: &str = "settings";
     pub const EXPORT: &str = "export";
+    pub const UPDATE: &str = "update";
     pub const QUIT: &str = "quit";
 }
 
-pub fn init_tray(app: &mut App) -> Result<TrayIcon, Box<dyn std::error::Error>> {
-    info!("Initializing system tray...");
+/// ステータスサブメニューに表示するセッションの最大数
+const MAX_STATUS_SESSIONS: usize = 10;
+
+/// ステータスサブメニューに表示する状態
+#[derive(Debug, Clone, Default)]
+pub struct TrayStatus {
+    pub broker_running: bool,
+    pub client_connected: bool,
+    /// MQTT クライアントの再起動が続けて失敗している
+    pub client_failing: bool,
+    pub unread: u32,
+    /// 最中のカレンダーの予定（予定中は通知を止めている）
+    pub calendar_event: Option<String>,
+    pub total_cost_usd: f64,
+    /// ステータスを送信しているセッション（新しい順）
+    pub sessions: Vec<TraySession>,
+}
+
+/// ステータスサブメニューに1行で表示するセッション
+#[derive(Debug, Clone)]
+pub struct TraySession {
+    pub name: String,
+    pub host: Option<String>,
+    pub state: Option<String>,
+    pub cost_usd: Option<f64>,
+}
+
+impl TrayStatus {
+    /// サブメニューの見出し
+    fn title(&self, strings: &Strings) -> String {
+        if self.calendar_event.is_some() {
+            strings.menu_status_calendar_busy.to_string()
+        } else if self.sessions.is_empty() {
+            strings.menu_status_idle.to_string()
+        } else {
+            i18n::fill(strings.menu_status_sessions, &[("count", &self.sessions.len().to_string())])
+        }
+    }
+
+    /// サブメニューの各行
+    fn lines(&self, strings: &Strings) -> Vec<String> {
+        let mut lines = vec![
+            if self.broker_running {
+                strings.menu_status_broker_running
+            } else {
+                strings.menu_status_broker_stopped
+            }
+            .to_string(),
+            if self.client_connected {
+                strings.menu_status_client_connected
+            } else if self.client_failing {
+                strings.menu_status_client_failing
+            } else {
+                strings.menu_status_client_disconnected
+            }
+            .to_string(),
+            i18n::fill(strings.menu_status_unread, &[("count", &self.unread.to_string())]),
+            i18n::fill(strings.menu_status_cost, &[("cost", &format!("{:.2}", self.total_cost_usd))]),
+        ];
+        if let Some(summary) = &self.calendar_event {
+            lines.insert(0, i18n::fill(strings.calendar_busy, &[("summary", summary)]));
+        }
+
+        if self.sessions.is_empty() {
+            lines.push(strings.menu_status_no_sessions.to_string());
+        }
+        lines.extend(self.sessions.iter().take(MAX_STATUS_SESSIONS).map(|session| {
+            let host = session.host.as_deref().map(|host| format!(" @{}", host)).unwrap_or_default();
+            let cost = session.cost_usd.map(|cost| format!(" ・ ${:.2}", cost)).unwrap_or_default();
+            format!("{}{}: {}{}", session.name, host, session.state.as_deref().unwrap_or("-"), cost)
+        }));
+        if self.sessions.len() > MAX_STATUS_SESSIONS {
+            let more = (self.sessions.len() - MAX_STATUS_SESSIONS).to_string();
+            lines.push(i18n::fill(strings.menu_status_more_sessions, &[("count", &more)]));
+        }
+        lines
+    }
+}
+
+/// トレイメニューのステータスサブメニュー（メニューを作り直さずに中身を更新するため保持する）
+struct StatusMenu(Mutex<Submenu<Wry>>);
 
-    let status_item =
-        MenuItem::with_id(app, menu_ids::STATUS, "Status: Idle", false, None::<&str>)?;
+/// トレイメニューを構築する
+fn build_menu(app: &AppHandle, strings: &Strings) -> tauri::Result<(Menu<Wry>, Submenu<Wry>)> {
+    let status_menu = Submenu::with_id(app, menu_ids::STATUS, strings.menu_status_idle, true)?;
+    fill_status_menu(app, &status_menu, &TrayStatus::default(), strings)?;
+    let profile_menu = build_profile_menu(app, strings)?;
+
+    let do_not_disturb = app
+        .try_state::<Arc<NotificationManager>>()
+        .is_some_and(|notification_manager| notification_manager.is_do_not_disturb());
+    let do_not_disturb_item = CheckMenuItem::with_id(
+        app,
+        menu_ids::DO_NOT_DISTURB,
+        strings.menu_do_not_disturb,
+        true,
+        do_not_disturb,
+        None::<&str>,
+    )?;
 
     let settings_item = MenuItem::with_id(
         app,
         menu_ids::SETTINGS,
-        "通知設定...",
+        strings.menu_settings,
         true,
         None::<&str>,
     )?;
@@ -37,23 +137,105 @@ pub fn init_tray(app: &mut App) -> Result<TrayIcon, Box<dyn std::error::Error>>
     let export_item = MenuItem::with_id(
         app,
         menu_ids::EXPORT,
-        "設定エクスポート...",
+        strings.menu_export,
         true,
         None::<&str>,
     )?;
 
-    let quit_item = MenuItem::with_id(app, menu_ids::QUIT, "終了", true, None::<&str>)?;
+    // 新しいバージョンがある場合はダウンロードページを開く項目にする
+    let available = app
+        .try_state::<updater::UpdateState>()
+        .and_then(|state| state.available());
+    let update_label = match &available {
+        Some(release) => i18n::fill(strings.menu_download_update, &[("version", &release.version)]),
+        None => strings.menu_check_update.to_string(),
+    };
+    let update_item = MenuItem::with_id(app, menu_ids::UPDATE, update_label, true, None::<&str>)?;
+
+    let quit_item = MenuItem::with_id(app, menu_ids::QUIT, strings.menu_quit, true, None::<&str>)?;
 
     let menu = MenuBuilder::new(app)
-        .item(&status_item)
+        .item(&status_menu)
+        .item(&profile_menu)
+        .item(&do_not_disturb_item)
         .separator()
         .item(&settings_item)
         .item(&export_item)
+        .item(&update_item)
         .separator()
         .item(&quit_item)
         .build()?;
+    Ok((menu, status_menu))
+}
+
+/// 設定プロファイルを切り替えるサブメニュー（有効なプロファイルにチェックを付ける）
+fn build_profile_menu(app: &AppHandle, strings: &Strings) -> tauri::Result<Submenu<Wry>> {
+    let profiles = settings::load_profiles(app);
+    let profile_menu = Submenu::with_id(app, menu_ids::PROFILES, strings.menu_profiles, true)?;
+    if profiles.profiles.is_empty() {
+        let item = MenuItem::new(app, strings.menu_profiles_empty, false, None::<&str>)?;
+        profile_menu.append(&item)?;
+    }
+    for name in profiles.names() {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{}{}", menu_ids::PROFILE_PREFIX, name),
+            &name,
+            true,
+            profiles.active.as_deref() == Some(name.as_str()),
+            None::<&str>,
+        )?;
+        profile_menu.append(&item)?;
+    }
+    Ok(profile_menu)
+}
+
+/// ステータスサブメニューの見出しと中身を状態に合わせて作り直す
+fn fill_status_menu(app: &AppHandle, status_menu: &Submenu<Wry>, status: &TrayStatus, strings: &Strings) -> tauri::Result<()> {
+    status_menu.set_text(status.title(strings))?;
+    for item in status_menu.items()? {
+        status_menu.remove(&item)?;
+    }
+    for line in status.lines(strings) {
+        let item = MenuItem::new(app, line, false, None::<&str>)?;
+        status_menu.append(&item)?;
+    }
+    Ok(())
+}
+
+/// ステータスサブメニューを最新の状態に更新する
+///
+/// 更新はメインスレッドで順に行い、複数のスレッドからの更新が混ざらないようにする。
+pub fn refresh_status_menu(app: &AppHandle, status: TrayStatus, strings: &'static Strings) {
+    let Some(status_menu) = app
+        .try_state::<StatusMenu>()
+        .and_then(|menu| menu.0.lock().ok().map(|menu| menu.clone()))
+    else {
+        return;
+    };
+
+    let handle = app.clone();
+    let result = app.run_on_main_thread(move || {
+        if let Err(e) = fill_status_menu(&handle, &status_menu, &status, strings) {
+            warn!("Failed to update status menu: {}", e);
+        }
+    });
+    if let Err(e) = result {
+        warn!("Failed to schedule status menu update: {}", e);
+    }
+}
+
+pub fn init_tray(
+    app: &mut App,
+    strings: &Strings,
+    icon_data: &[u8],
+) -> Result<TrayIcon, Box<dyn std::error::Error>> {
+    info!("Initializing system tray...");
+
+    let (menu, status_menu) = build_menu(app.handle(), strings)?;
+    app.manage(StatusMenu(Mutex::new(status_menu)));
 
-    let icon = Image::from_bytes(include_bytes!("../icons/icon.png"))?;
+    let icon = Image::from_bytes(icon_data)?;
 
     let tray = TrayIconBuilder::with_id("main-tray")
         .icon(icon)
@@ -68,6 +250,27 @@ pub fn init_tray(app: &mut App) -> Result<TrayIcon, Box<dyn std::error::Error>>
     Ok(tray)
 }
 
+/// 言語設定・設定プロファイルの変更をトレイメニューに反映する
+pub fn rebuild_menu(app: &AppHandle, strings: &Strings) {
+    let Some(tray) = app.tray_by_id("main-tray") else {
+        return;
+    };
+
+    match build_menu(app, strings) {
+        Ok((menu, status_menu)) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                warn!("Failed to update tray menu: {}", e);
+            }
+            if let Some(current) = app.try_state::<StatusMenu>() {
+                if let Ok(mut current) = current.0.lock() {
+                    *current = status_menu;
+                }
+            }
+        }
+        Err(e) => warn!("Failed to rebuild tray menu: {}", e),
+    }
+}
+
 fn handle_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
     debug!("Menu event: {:?}", event.id());
 
@@ -78,23 +281,54 @@ fn handle_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
         menu_ids::EXPORT => {
             show_main_window_with_tab(app, "export");
         }
+        menu_ids::DO_NOT_DISTURB => {
+            if let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>() {
+                notification_manager.set_do_not_disturb(app, !notification_manager.is_do_not_disturb());
+            }
+        }
+        menu_ids::UPDATE => {
+            match app.state::<updater::UpdateState>().available() {
+                Some(release) => updater::open_download_page(app, &release.url),
+                None => {
+                    let app = app.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = updater::check(&app, true) {
+                            warn!("Update check failed: {}", e);
+                        }
+                    });
+                }
+            }
+        }
         menu_ids::QUIT => {
             info!("Quit requested from tray menu");
             app.exit(0);
         }
-        _ => {}
+        id => {
+            if let Some(name) = id.strip_prefix(menu_ids::PROFILE_PREFIX) {
+                if let Err(e) = profiles::switch(app, name) {
+                    warn!("Failed to switch settings profile: {}", e);
+                }
+            }
+        }
     }
 }
 
 /// メインウィンドウを表示し、指定したタブに切り替える
-fn show_main_window_with_tab(app: &AppHandle, tab: &str) {
+pub fn show_main_window_with_tab(app: &AppHandle, tab: &str) {
     info!("Opening main window with tab: {}", tab);
 
-    // メインウィンドウを表示
+    // メインウィンドウを設定した位置に表示
     if let Some(window) = app.get_webview_window("main") {
+        place_main_window(app, &window);
         let _ = window.show();
+        let _ = window.unminimize();
         let _ = window.set_focus();
 
+        // タブに応じたウィンドウタイトルを設定
+        if let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>() {
+            let _ = window.set_title(notification_manager.strings().window_title_for_tab(tab));
+        }
+
         // フロントエンドにタブ切り替えイベントを送信
         if let Err(e) = app.emit("switch-tab", tab) {
             warn!("Failed to emit switch-tab event: {}", e);
@@ -104,6 +338,16 @@ fn show_main_window_with_tab(app: &AppHandle, tab: &str) {
     }
 }
 
+/// 非表示・最小化したメインウィンドウを、設定した位置（カーソルの近く・アクティブなモニター）に移動する
+fn place_main_window(app: &AppHandle, window: &tauri::WebviewWindow) {
+    if window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    if let Some(notification_manager) = app.try_state::<Arc<NotificationManager>>() {
+        window_placement::apply(app, window, notification_manager.get_settings().window_placement);
+    }
+}
+
 fn handle_tray_event(tray: &TrayIcon, event: TrayIconEvent) {
     match event {
         TrayIconEvent::Click {
@@ -134,6 +378,7 @@ fn handle_tray_event(tray: &TrayIcon, event: TrayIconEvent) {
             }
 
             if let Some(window) = app.get_webview_window("main") {
+                place_main_window(app, &window);
                 let _ = window.show();
                 let _ = window.set_focus();
             }
@@ -161,4 +406,70 @@ mod tests {
         assert_eq!(menu_ids::EXPORT, "export");
         assert_eq!(menu_ids::QUIT, "quit");
     }
+
+    #[test]
+    fn test_status_lines_without_sessions() {
+        let strings = i18n::Language::En.strings();
+        let status = TrayStatus {
+            broker_running: true,
+            ..TrayStatus::default()
+        };
+
+        assert_eq!(status.title(strings), "Status: Idle");
+        assert_eq!(
+            status.lines(strings),
+            vec!["Broker: running", "Client: disconnected", "Unread: 0", "Total cost: $0.00", "No sessions"]
+        );
+    }
+
+    #[test]
+    fn test_status_lines_with_sessions() {
+        let strings = i18n::Language::En.strings();
+        let session = TraySession {
+            name: "my-app (1)".to_string(),
+            host: Some("wsl".to_string()),
+            state: Some("working".to_string()),
+            cost_usd: Some(0.5),
+        };
+        let status = TrayStatus {
+            broker_running: true,
+            client_connected: true,
+            client_failing: false,
+            unread: 2,
+            calendar_event: None,
+            total_cost_usd: 0.5,
+            sessions: vec![session; MAX_STATUS_SESSIONS + 2],
+        };
+
+        assert_eq!(status.title(strings), "Status: 12 sessions");
+        let lines = status.lines(strings);
+        assert_eq!(lines[2], "Unread: 2");
+        assert_eq!(lines[4], "my-app (1) @wsl: working ・ $0.50");
+        assert_eq!(lines.len(), 4 + MAX_STATUS_SESSIONS + 1);
+        assert_eq!(lines.last().unwrap(), "+2 more sessions");
+    }
+
+    #[test]
+    fn test_status_lines_with_failing_client() {
+        let strings = i18n::Language::En.strings();
+        let status = TrayStatus {
+            broker_running: true,
+            client_failing: true,
+            ..TrayStatus::default()
+        };
+
+        assert_eq!(status.lines(strings)[1], "Client: restart failing");
+    }
+
+    #[test]
+    fn test_status_during_calendar_event() {
+        let strings = i18n::Language::En.strings();
+        let status = TrayStatus {
+            calendar_event: Some("Standup".to_string()),
+            ..TrayStatus::default()
+        };
+
+        assert_eq!(status.title(strings), "Status: In a meeting (muted)");
+        assert_eq!(status.lines(strings)[0], "Muted during calendar event: Standup");
+    }
 }
@@ -1,98 +1,310 @@
 //! トレイアイコン点滅モジュール
 //!
 //! 通知があった際にトレイアイコンを点滅させる機能を提供する。
+//! 点滅は1本の常駐スレッドで行い、開始・停止などはコマンドチャネルで送る。
 //! 通常アイコンと赤いドット付きアイコンを交互に表示して点滅効果を出す。
-
+//! 点滅が不自然に見える環境（macOS / Linux）向けに、未確認数のバッジを
+//! 常時表示するモードも提供する。承認待ちの依頼がある場合は色を変えたバッジで件数を表示する。
+//! 通常アイコンはトレイアイコンのテーマ（tray_theme）に応じて差し替えられる。
+//! ドットの色は通知元のプロジェクトの色に変えられる（project_rules）。
+//! NotificationManager からはタスクバーなどと同じく AttentionSignal として使う。
+
+use crate::attention::{AttentionProgress, AttentionSignal};
+use crate::notification_state::BadgeKind;
+use crate::settings::NotificationSettings;
 use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
 use tauri::{image::Image, AppHandle};
 use tracing::{error, info};
 
-/// 通常のトレイアイコンデータ
-static NORMAL_ICON: &[u8] = include_bytes!("../icons/icon.png");
+/// 未確認の通知があることをトレイアイコンで知らせる方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayAttentionMode {
+    /// 通常アイコンと赤いドット付きアイコンを交互に表示する
+    Flash,
+    /// 未確認数（1, 2, ... 9+）のバッジを表示し続ける
+    Badge,
+}
 
-/// 点滅状態を管理する構造体
-pub struct TrayFlasher {
-    is_flashing: Arc<AtomicBool>,
-    notification_icon: Vec<u8>,
+impl Default for TrayAttentionMode {
+    /// Windows では従来通り点滅、それ以外ではバッジ表示
+    fn default() -> Self {
+        if cfg!(windows) {
+            TrayAttentionMode::Flash
+        } else {
+            TrayAttentionMode::Badge
+        }
+    }
 }
 
-impl TrayFlasher {
-    /// 新しい TrayFlasher を作成
-    pub fn new() -> Self {
-        let notification_icon = create_notification_icon().unwrap_or_else(|e| {
+/// トレイアイコン点滅のタイミング
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlashTiming {
+    /// アイコンを切り替える間隔
+    pub interval: Duration,
+    /// 点滅を続ける最大時間（None の場合は stop_flash が呼ばれるまで点滅）
+    pub max_duration: Option<Duration>,
+}
+
+impl FlashTiming {
+    /// 点滅間隔の下限（短すぎるとトレイの更新が追いつかない）
+    pub const MIN_INTERVAL: Duration = Duration::from_millis(100);
+}
+
+impl Default for FlashTiming {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(500),
+            max_duration: None,
+        }
+    }
+}
+
+/// 点滅のドットの色
+const DOT_COLOR: Rgba<u8> = Rgba([220, 53, 69, 255]);
+/// 未確認数のバッジの色
+const BADGE_COLOR: Rgba<u8> = Rgba([220, 53, 69, 255]);
+/// 承認待ち数のバッジの色
+const PENDING_BADGE_COLOR: Rgba<u8> = Rgba([245, 124, 0, 255]);
+/// バッジ内の文字色
+const BADGE_TEXT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// 点滅に使用するアイコンの組
+struct FlashIcons {
+    normal: &'static [u8],
+    dot_color: Rgba<u8>,
+    notification: Vec<u8>,
+    /// 最後に生成したバッジアイコン（件数, 種類, PNG）
+    badge: Option<(u32, BadgeKind, Vec<u8>)>,
+}
+
+impl FlashIcons {
+    fn new(normal: &'static [u8], dot_color: Rgba<u8>) -> Self {
+        let notification = create_notification_icon(normal, dot_color).unwrap_or_else(|e| {
             error!("Failed to create notification icon: {}", e);
-            NORMAL_ICON.to_vec()
+            normal.to_vec()
         });
-
         Self {
-            is_flashing: Arc::new(AtomicBool::new(false)),
-            notification_icon,
+            normal,
+            dot_color,
+            notification,
+            badge: None,
         }
     }
 
-    /// トレイアイコンの点滅を開始（stop_flashが呼ばれるまで無限に点滅）
-    pub fn start_flash(&self, app: &AppHandle) {
-        // 既に点滅中なら何もしない
-        if self.is_flashing.swap(true, Ordering::SeqCst) {
-            return;
+    /// 表示内容に対応するアイコン画像を取得
+    fn data(&mut self, frame: Frame) -> &[u8] {
+        match frame {
+            Frame::Normal => self.normal,
+            Frame::Notification => &self.notification,
+            Frame::Badge(count, kind) => {
+                if self.badge.as_ref().map(|(cached, cached_kind, _)| (*cached, *cached_kind))
+                    != Some((count, kind))
+                {
+                    let icon = create_badge_icon(self.normal, count, kind).unwrap_or_else(|e| {
+                        error!("Failed to create badge icon: {}", e);
+                        self.notification.clone()
+                    });
+                    self.badge = Some((count, kind, icon));
+                }
+                self.badge.as_ref().map(|(_, _, icon)| icon.as_slice()).unwrap_or(self.normal)
+            }
         }
+    }
+}
 
-        let is_flashing = self.is_flashing.clone();
-        let notification_icon = self.notification_icon.clone();
-        let app_handle = app.clone();
+/// トレイアイコンに表示する内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    Normal,
+    /// 赤いドット付き
+    Notification,
+    /// 未確認数・承認待ち数のバッジ付き
+    Badge(u32, BadgeKind),
+}
 
-        std::thread::spawn(move || {
-            let mut show_notification = true;
+/// 点滅ワーカーの状態
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Idle,
+    Flashing {
+        timing: FlashTiming,
+        started: Instant,
+        lit: bool,
+        next_toggle: Instant,
+    },
+    /// 点滅せずに表示し続ける（バッジ、または最大時間経過後のドット）
+    Held(Frame),
+}
 
-            while is_flashing.load(Ordering::SeqCst) {
-                let icon_data = if show_notification {
-                    &notification_icon
-                } else {
-                    NORMAL_ICON
-                };
-
-                if let Some(tray) = app_handle.tray_by_id("main-tray") {
-                    match Image::from_bytes(icon_data) {
-                        Ok(icon) => {
-                            if let Err(e) = tray.set_icon(Some(icon)) {
-                                error!("Failed to set tray icon: {}", e);
-                            }
-                        }
-                        Err(e) => {
-                            error!("Failed to create icon from bytes: {}", e);
-                        }
-                    }
-                }
+/// 点滅ワーカーの状態遷移（トレイ操作を含まない純粋なロジック）
+struct FlashState {
+    phase: Phase,
+}
 
-                show_notification = !show_notification;
-                std::thread::sleep(Duration::from_millis(500));
-            }
+impl FlashState {
+    fn new() -> Self {
+        Self { phase: Phase::Idle }
+    }
 
-            // 点滅終了後は通常アイコンに戻す
-            if let Some(tray) = app_handle.tray_by_id("main-tray") {
-                if let Ok(icon) = Image::from_bytes(NORMAL_ICON) {
-                    let _ = tray.set_icon(Some(icon));
-                }
-            }
+    fn is_flashing(&self) -> bool {
+        matches!(self.phase, Phase::Flashing { .. })
+    }
 
-            info!("Tray icon flash stopped");
-        });
+    /// 次にアイコンを切り替える時刻
+    fn deadline(&self) -> Option<Instant> {
+        match self.phase {
+            Phase::Flashing { next_toggle, .. } => Some(next_toggle),
+            _ => None,
+        }
+    }
+
+    /// 現在表示すべき内容
+    fn current(&self) -> Frame {
+        match self.phase {
+            Phase::Idle => Frame::Normal,
+            Phase::Flashing { lit: true, .. } => Frame::Notification,
+            Phase::Flashing { lit: false, .. } => Frame::Normal,
+            Phase::Held(frame) => frame,
+        }
+    }
 
-        info!("Tray icon flash started (infinite until stopped)");
+    /// 点滅を開始（既に点滅中なら何もしない）
+    fn start(&mut self, timing: FlashTiming, now: Instant) -> Option<Frame> {
+        if self.is_flashing() {
+            return None;
+        }
+        let timing = FlashTiming {
+            interval: timing.interval.max(FlashTiming::MIN_INTERVAL),
+            ..timing
+        };
+        self.phase = Phase::Flashing {
+            timing,
+            started: now,
+            lit: true,
+            next_toggle: now + timing.interval,
+        };
+        Some(Frame::Notification)
     }
 
-    /// トレイアイコンの点滅を停止し、通常アイコンに戻す
-    pub fn stop_flash(&self, app: &AppHandle) {
-        self.is_flashing.store(false, Ordering::SeqCst);
+    /// バッジ表示に切り替える
+    fn badge(&mut self, count: u32, kind: BadgeKind) -> Frame {
+        self.phase = Phase::Held(Frame::Badge(count, kind));
+        self.current()
+    }
 
-        if let Some(tray) = app.tray_by_id("main-tray") {
-            if let Ok(icon) = Image::from_bytes(NORMAL_ICON) {
-                let _ = tray.set_icon(Some(icon));
-            }
+    /// 通常表示に戻す
+    fn stop(&mut self) -> Frame {
+        self.phase = Phase::Idle;
+        Frame::Normal
+    }
+
+    /// 時間経過による切り替え
+    fn tick(&mut self, now: Instant) -> Option<Frame> {
+        let Phase::Flashing {
+            timing,
+            started,
+            lit,
+            next_toggle,
+        } = self.phase
+        else {
+            return None;
+        };
+
+        if now < next_toggle {
+            return None;
         }
+
+        // 最大時間を過ぎたら、未確認があることが分かるようドット付きアイコンのまま止める
+        if timing.max_duration.is_some_and(|max| now.duration_since(started) >= max) {
+            self.phase = Phase::Held(Frame::Notification);
+            info!("Tray icon flash reached max duration");
+            return Some(Frame::Notification);
+        }
+
+        self.phase = Phase::Flashing {
+            timing,
+            started,
+            lit: !lit,
+            next_toggle: now + timing.interval,
+        };
+        Some(self.current())
+    }
+}
+
+/// 点滅ワーカーへのコマンド
+enum FlashCommand {
+    Start(FlashTiming),
+    ShowBadge(u32, BadgeKind),
+    Stop,
+    SetNormalIcon(&'static [u8]),
+    SetDotColor(Rgba<u8>),
+}
+
+/// トレイアイコンの点滅を管理する構造体
+///
+/// 1本の常駐スレッドがコマンドチャネル経由で要求を受け取り、トレイアイコンを更新する。
+/// アイコンの更新はすべてこのスレッドで行うため、点滅と停止が競合しない。
+pub struct TrayFlasher {
+    tx: mpsc::Sender<FlashCommand>,
+    is_flashing: Arc<AtomicBool>,
+}
+
+impl TrayFlasher {
+    /// 新しい TrayFlasher を作成し、ワーカースレッドを起動する
+    pub fn new(app: &AppHandle, normal_icon: &'static [u8]) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let is_flashing = Arc::new(AtomicBool::new(false));
+
+        let app_handle = app.clone();
+        let flashing = is_flashing.clone();
+        if let Err(e) = std::thread::Builder::new()
+            .name("tray-flasher".to_string())
+            .spawn(move || run_worker(app_handle, rx, normal_icon, flashing))
+        {
+            error!("Failed to spawn tray flasher thread: {}", e);
+        }
+
+        Self { tx, is_flashing }
+    }
+
+    fn send(&self, command: FlashCommand) {
+        if self.tx.send(command).is_err() {
+            error!("Tray flasher is not running");
+        }
+    }
+
+    /// 通常アイコンを差し替える（点滅・バッジ表示中はその表示に反映）
+    pub fn set_normal_icon(&self, normal_icon: &'static [u8]) {
+        self.send(FlashCommand::SetNormalIcon(normal_icon));
+    }
+
+    /// 点滅のドットの色を変える（None の場合は赤に戻す、点滅中はその表示に反映）
+    pub fn set_dot_color(&self, color: Option<[u8; 3]>) {
+        let color = color.map_or(DOT_COLOR, |[r, g, b]| Rgba([r, g, b, 255]));
+        self.send(FlashCommand::SetDotColor(color));
+    }
+
+    /// トレイアイコンの点滅を開始
+    ///
+    /// stop_flash が呼ばれるか最大時間が経過するまで点滅する。
+    /// 最大時間が経過した場合は、未確認があることが分かるようドット付きアイコンのまま止める。
+    pub fn start_flash(&self, timing: FlashTiming) {
+        self.send(FlashCommand::Start(timing));
+    }
+
+    /// 件数のバッジ付きアイコンを表示する（stop_flash が呼ばれるまで表示し続ける）
+    pub fn show_badge(&self, count: u32, kind: BadgeKind) {
+        self.send(FlashCommand::ShowBadge(count, kind));
+    }
+
+    /// トレイアイコンの点滅・バッジ表示を停止し、通常アイコンに戻す
+    pub fn stop_flash(&self) {
+        self.send(FlashCommand::Stop);
     }
 
     /// 点滅中かどうかを確認
@@ -102,22 +314,142 @@ impl TrayFlasher {
     }
 }
 
-/// 赤いドット付きの通知アイコンを動的に生成
-fn create_notification_icon() -> Result<Vec<u8>, String> {
+impl AttentionSignal for TrayFlasher {
+    fn name(&self) -> &'static str {
+        "tray"
+    }
+
+    fn flash(&self, _app: &AppHandle, settings: &NotificationSettings) -> Result<(), String> {
+        self.start_flash(settings.flash_timing());
+        Ok(())
+    }
+
+    fn badge(&self, _app: &AppHandle, badge: Option<(BadgeKind, u32)>) -> Result<(), String> {
+        match badge {
+            Some((kind, count)) => self.show_badge(count, kind),
+            None => self.stop_flash(),
+        }
+        Ok(())
+    }
+
+    /// トレイアイコンには進捗を表示しない
+    fn progress(&self, _app: &AppHandle, _progress: AttentionProgress) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn clear(&self, _app: &AppHandle) -> Result<(), String> {
+        self.stop_flash();
+        Ok(())
+    }
+}
+
+/// ワーカースレッドのメインループ
+fn run_worker(
+    app: AppHandle,
+    rx: mpsc::Receiver<FlashCommand>,
+    normal_icon: &'static [u8],
+    is_flashing: Arc<AtomicBool>,
+) {
+    let mut icons = FlashIcons::new(normal_icon, DOT_COLOR);
+    let mut state = FlashState::new();
+
+    loop {
+        // 点滅中は次の切り替え時刻まで、それ以外はコマンドが来るまで待つ
+        let command = match state.deadline() {
+            Some(deadline) => {
+                match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(command) => Some(command),
+                    Err(mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match rx.recv() {
+                Ok(command) => Some(command),
+                Err(_) => break,
+            },
+        };
+
+        let now = Instant::now();
+        let frame = match command {
+            Some(FlashCommand::Start(timing)) => {
+                let frame = state.start(timing, now);
+                if frame.is_some() {
+                    info!(
+                        "Tray icon flash started (interval: {:?}, max: {:?})",
+                        timing.interval, timing.max_duration
+                    );
+                }
+                frame
+            }
+            Some(FlashCommand::ShowBadge(count, kind)) => Some(state.badge(count, kind)),
+            Some(FlashCommand::Stop) => {
+                if state.is_flashing() {
+                    info!("Tray icon flash stopped");
+                }
+                Some(state.stop())
+            }
+            Some(FlashCommand::SetNormalIcon(icon)) => {
+                if icons.normal.as_ptr() == icon.as_ptr() {
+                    None
+                } else {
+                    icons = FlashIcons::new(icon, icons.dot_color);
+                    Some(state.current())
+                }
+            }
+            Some(FlashCommand::SetDotColor(color)) => {
+                if icons.dot_color == color {
+                    None
+                } else {
+                    icons = FlashIcons::new(icons.normal, color);
+                    Some(state.current())
+                }
+            }
+            None => state.tick(now),
+        };
+
+        is_flashing.store(state.is_flashing(), Ordering::SeqCst);
+        if let Some(frame) = frame {
+            set_tray_icon(&app, icons.data(frame));
+        }
+    }
+
+    // アプリ終了時などチャネルが閉じた場合も通常アイコンに戻す
+    set_tray_icon(&app, icons.normal);
+    info!("Tray flasher stopped");
+}
+
+/// トレイアイコンを設定する
+fn set_tray_icon(app: &AppHandle, icon_data: &[u8]) {
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        match Image::from_bytes(icon_data) {
+            Ok(icon) => {
+                if let Err(e) = tray.set_icon(Some(icon)) {
+                    error!("Failed to set tray icon: {}", e);
+                }
+            }
+            Err(e) => {
+                error!("Failed to create icon from bytes: {}", e);
+            }
+        }
+    }
+}
+
+/// ドット付きの通知アイコンを動的に生成
+fn create_notification_icon(base_icon: &[u8], dot_color: Rgba<u8>) -> Result<Vec<u8>, String> {
     // 元のアイコンを読み込む
-    let img = image::load_from_memory(NORMAL_ICON)
+    let img = image::load_from_memory(base_icon)
         .map_err(|e| format!("Failed to load icon: {}", e))?;
 
     let mut rgba_img: RgbaImage = img.to_rgba8();
     let (width, height) = rgba_img.dimensions();
 
-    // 赤いドットのパラメータ
+    // ドットのパラメータ
     let dot_radius = (width.min(height) / 4) as i32; // アイコンサイズの1/4
     let dot_center_x = (width as i32) - dot_radius - 1;
     let dot_center_y = dot_radius + 1;
 
-    // 赤いドットを描画（アンチエイリアス付き円）
-    draw_filled_circle(&mut rgba_img, dot_center_x, dot_center_y, dot_radius, Rgba([220, 53, 69, 255]));
+    // ドットを描画（アンチエイリアス付き円）
+    draw_filled_circle(&mut rgba_img, dot_center_x, dot_center_y, dot_radius, dot_color);
 
     // PNGにエンコード
     let mut buffer = std::io::Cursor::new(Vec::new());
@@ -128,6 +460,107 @@ fn create_notification_icon() -> Result<Vec<u8>, String> {
     Ok(buffer.into_inner())
 }
 
+/// バッジに表示する文字列（10件以上は "9+"）
+fn badge_label(count: u32) -> String {
+    if count > 9 {
+        "9+".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// バッジの種類に対応する色
+fn badge_color(kind: BadgeKind) -> Rgba<u8> {
+    match kind {
+        BadgeKind::Unread => BADGE_COLOR,
+        BadgeKind::PendingApproval => PENDING_BADGE_COLOR,
+    }
+}
+
+/// 件数のバッジ付きアイコンを動的に生成
+fn create_badge_icon(base_icon: &[u8], count: u32, kind: BadgeKind) -> Result<Vec<u8>, String> {
+    let img = image::load_from_memory(base_icon)
+        .map_err(|e| format!("Failed to load icon: {}", e))?;
+
+    let mut rgba_img: RgbaImage = img.to_rgba8();
+    let (width, height) = rgba_img.dimensions();
+
+    // 数字を読めるよう、点滅用のドットより大きめの円にする
+    let radius = (width.min(height) as f32 * 0.3) as i32;
+    let center_x = (width as i32) - radius - 1;
+    let center_y = radius + 1;
+
+    draw_filled_circle(&mut rgba_img, center_x, center_y, radius, badge_color(kind));
+    draw_text(&mut rgba_img, center_x, center_y, radius, &badge_label(count), BADGE_TEXT_COLOR);
+
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    rgba_img
+        .write_to(&mut buffer, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode icon: {}", e))?;
+
+    Ok(buffer.into_inner())
+}
+
+/// 3x5 ドットのビットマップフォント（数字と "+" のみ）
+///
+/// 各行の下位3ビットが左から右のドットに対応する。
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        _ => return None,
+    })
+}
+
+/// 円の中央に文字列を描画（円に収まる最大の整数倍率で拡大する）
+fn draw_text(img: &mut RgbaImage, cx: i32, cy: i32, radius: i32, text: &str, color: Rgba<u8>) {
+    let glyphs: Vec<[u8; 5]> = text.chars().filter_map(glyph).collect();
+    if glyphs.is_empty() {
+        return;
+    }
+
+    // 文字間は1ドット空ける
+    let text_width = glyphs.len() as i32 * 4 - 1;
+    let text_height = 5;
+    let diameter = radius * 2;
+    let scale = ((diameter * 7 / 10) / text_width)
+        .min((diameter * 6 / 10) / text_height)
+        .max(1);
+
+    let origin_x = cx - text_width * scale / 2;
+    let origin_y = cy - text_height * scale / 2;
+    let (width, height) = img.dimensions();
+
+    for (index, rows) in glyphs.iter().enumerate() {
+        let glyph_x = origin_x + index as i32 * 4 * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                let x0 = glyph_x + col * scale;
+                let y0 = origin_y + row as i32 * scale;
+                for y in y0..y0 + scale {
+                    for x in x0..x0 + scale {
+                        if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+                            img.put_pixel(x as u32, y as u32, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// 塗りつぶし円を描画
 fn draw_filled_circle(img: &mut RgbaImage, cx: i32, cy: i32, radius: i32, color: Rgba<u8>) {
     let (width, height) = img.dimensions();
@@ -179,11 +612,149 @@ fn blend_pixels(bg: &Rgba<u8>, fg: &Rgba<u8>, alpha: f32) -> Rgba<u8> {
 mod tests {
     use super::*;
 
+    use crate::tray_theme::TrayIconTheme;
+
     #[test]
     fn test_create_notification_icon() {
-        let result = create_notification_icon();
+        let result = create_notification_icon(TrayIconTheme::Color.icon_bytes(false), DOT_COLOR);
         assert!(result.is_ok(), "Should create notification icon successfully");
         let icon_data = result.unwrap();
         assert!(!icon_data.is_empty(), "Icon data should not be empty");
     }
+
+    #[test]
+    fn test_notification_icon_keeps_source_resolution() {
+        let base = TrayIconTheme::Light.icon_bytes(false);
+        let icon = create_notification_icon(base, DOT_COLOR).unwrap();
+        let src = image::load_from_memory(base).unwrap();
+        let dst = image::load_from_memory(&icon).unwrap();
+        assert_eq!((src.width(), src.height()), (dst.width(), dst.height()));
+    }
+
+    #[test]
+    fn test_notification_icon_uses_dot_color() {
+        let color = Rgba([0, 144, 255, 255]);
+        let icon = create_notification_icon(TrayIconTheme::Dark.icon_bytes(false), color).unwrap();
+        let img = image::load_from_memory(&icon).unwrap().to_rgba8();
+        let radius = img.width().min(img.height()) / 4;
+        assert_eq!(*img.get_pixel(img.width() - radius - 1, radius + 1), color);
+    }
+
+    #[test]
+    fn test_badge_label() {
+        assert_eq!(badge_label(1), "1");
+        assert_eq!(badge_label(9), "9");
+        assert_eq!(badge_label(10), "9+");
+        assert_eq!(badge_label(120), "9+");
+    }
+
+    #[test]
+    fn test_create_badge_icon_draws_text() {
+        // 白いピクセルを含まないダークアイコンを元にする
+        let base = TrayIconTheme::Dark.icon_bytes(false);
+        let icon = create_badge_icon(base, 3, BadgeKind::Unread).unwrap();
+        let img = image::load_from_memory(&icon).unwrap().to_rgba8();
+
+        // バッジ領域（右上）に白い文字のピクセルが含まれる
+        let (width, _) = img.dimensions();
+        let has_text = img
+            .enumerate_pixels()
+            .any(|(x, y, pixel)| x > width / 2 && y < width / 2 && *pixel == BADGE_TEXT_COLOR);
+        assert!(has_text);
+    }
+
+    #[test]
+    fn test_pending_badge_uses_distinct_color() {
+        let base = TrayIconTheme::Dark.icon_bytes(false);
+        let icon = create_badge_icon(base, 1, BadgeKind::PendingApproval).unwrap();
+        let img = image::load_from_memory(&icon).unwrap().to_rgba8();
+        assert!(img.pixels().any(|pixel| *pixel == PENDING_BADGE_COLOR));
+        assert!(!img.pixels().any(|pixel| *pixel == BADGE_COLOR));
+    }
+
+    fn timing(interval_ms: u64, max_ms: Option<u64>) -> FlashTiming {
+        FlashTiming {
+            interval: Duration::from_millis(interval_ms),
+            max_duration: max_ms.map(Duration::from_millis),
+        }
+    }
+
+    #[test]
+    fn test_flash_state_toggles_on_interval() {
+        let mut state = FlashState::new();
+        let t0 = Instant::now();
+
+        assert_eq!(state.start(timing(500, None), t0), Some(Frame::Notification));
+        assert!(state.is_flashing());
+        assert_eq!(state.deadline(), Some(t0 + Duration::from_millis(500)));
+
+        // 切り替え時刻前は何もしない
+        assert_eq!(state.tick(t0 + Duration::from_millis(100)), None);
+        assert_eq!(state.tick(t0 + Duration::from_millis(500)), Some(Frame::Normal));
+        assert_eq!(state.tick(t0 + Duration::from_millis(1000)), Some(Frame::Notification));
+    }
+
+    #[test]
+    fn test_flash_state_start_is_idempotent() {
+        let mut state = FlashState::new();
+        let t0 = Instant::now();
+        state.start(timing(500, None), t0);
+        assert_eq!(state.start(timing(500, None), t0 + Duration::from_millis(10)), None);
+    }
+
+    #[test]
+    fn test_flash_state_stop_always_restores_normal() {
+        let mut state = FlashState::new();
+        let t0 = Instant::now();
+        state.start(timing(500, None), t0);
+
+        assert_eq!(state.stop(), Frame::Normal);
+        assert!(!state.is_flashing());
+        assert_eq!(state.deadline(), None);
+        assert_eq!(state.tick(t0 + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn test_flash_state_holds_dot_after_max_duration() {
+        let mut state = FlashState::new();
+        let t0 = Instant::now();
+        state.start(timing(500, Some(1000)), t0);
+
+        state.tick(t0 + Duration::from_millis(500));
+        assert_eq!(state.tick(t0 + Duration::from_millis(1000)), Some(Frame::Notification));
+        assert!(!state.is_flashing());
+        assert_eq!(state.current(), Frame::Notification);
+
+        // 停止後は再度点滅を開始できる
+        assert!(state.start(timing(500, None), t0 + Duration::from_secs(2)).is_some());
+    }
+
+    #[test]
+    fn test_flash_state_clamps_interval() {
+        let mut state = FlashState::new();
+        let t0 = Instant::now();
+        state.start(timing(0, None), t0);
+        assert_eq!(state.deadline(), Some(t0 + FlashTiming::MIN_INTERVAL));
+    }
+
+    #[test]
+    fn test_flash_state_badge() {
+        let mut state = FlashState::new();
+        let t0 = Instant::now();
+        state.start(timing(500, None), t0);
+        assert_eq!(state.badge(4, BadgeKind::Unread), Frame::Badge(4, BadgeKind::Unread));
+        assert_eq!(
+            state.badge(1, BadgeKind::PendingApproval),
+            Frame::Badge(1, BadgeKind::PendingApproval)
+        );
+        assert!(!state.is_flashing());
+        assert_eq!(state.stop(), Frame::Normal);
+    }
+
+    #[test]
+    fn test_all_badge_characters_have_glyphs() {
+        for c in "0123456789+".chars() {
+            assert!(glyph(c).is_some(), "missing glyph for {}", c);
+        }
+    }
 }
@@ -0,0 +1,88 @@
+//! トレイアイコンのテーマ管理モジュール
+//!
+//! カラー / ライト / ダーク / モノクロのアイコンセットを提供する。
+//! `Auto` の場合は OS のテーマ（ダークモードかどうか）から自動選択する。
+//! 高DPI環境でも点滅・バッジのオーバーレイが粗くならないよう、
+//! 128px 以上の元画像を使用する。
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Theme};
+
+/// カラーアイコン（アプリアイコンと同じ、512px）
+static COLOR_ICON: &[u8] = include_bytes!("../icons/icon.png");
+/// ライトアイコン（暗いタスクバー向けの白いシルエット、128px）
+static LIGHT_ICON: &[u8] = include_bytes!("../icons/tray/light.png");
+/// ダークアイコン（明るいタスクバー向けの濃紺のシルエット、128px）
+static DARK_ICON: &[u8] = include_bytes!("../icons/tray/dark.png");
+/// モノクロアイコン（128px）
+static MONOCHROME_ICON: &[u8] = include_bytes!("../icons/tray/monochrome.png");
+
+/// トレイアイコンのテーマ設定
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayIconTheme {
+    /// 従来のカラーアイコン
+    #[default]
+    Color,
+    /// OS のテーマに合わせてライト / ダークを自動選択
+    Auto,
+    Light,
+    Dark,
+    Monochrome,
+}
+
+impl TrayIconTheme {
+    /// `Auto` を OS のテーマに応じて解決する
+    pub fn resolve(self, os_dark: bool) -> Self {
+        match self {
+            TrayIconTheme::Auto if os_dark => TrayIconTheme::Light,
+            TrayIconTheme::Auto => TrayIconTheme::Dark,
+            other => other,
+        }
+    }
+
+    /// テーマに対応するアイコン画像（PNG）を取得
+    pub fn icon_bytes(self, os_dark: bool) -> &'static [u8] {
+        match self.resolve(os_dark) {
+            TrayIconTheme::Light => LIGHT_ICON,
+            TrayIconTheme::Dark => DARK_ICON,
+            TrayIconTheme::Monochrome => MONOCHROME_ICON,
+            TrayIconTheme::Color | TrayIconTheme::Auto => COLOR_ICON,
+        }
+    }
+}
+
+/// OS がダークテーマかどうかを判定する
+///
+/// メインウィンドウのテーマを参照する（取得できない場合はライト扱い）。
+pub fn os_prefers_dark(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|window| window.theme().ok())
+        .map(|theme| theme == Theme::Dark)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_resolves_from_os_theme() {
+        assert_eq!(TrayIconTheme::Auto.resolve(true), TrayIconTheme::Light);
+        assert_eq!(TrayIconTheme::Auto.resolve(false), TrayIconTheme::Dark);
+        assert_eq!(TrayIconTheme::Monochrome.resolve(true), TrayIconTheme::Monochrome);
+    }
+
+    #[test]
+    fn test_icons_are_high_resolution_png() {
+        for theme in [
+            TrayIconTheme::Color,
+            TrayIconTheme::Light,
+            TrayIconTheme::Dark,
+            TrayIconTheme::Monochrome,
+        ] {
+            let img = image::load_from_memory(theme.icon_bytes(false)).unwrap();
+            assert!(img.width() >= 128, "{:?} icon should be at least 128px", theme);
+        }
+    }
+}
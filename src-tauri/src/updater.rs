@@ -0,0 +1,290 @@
+//! 更新の確認モジュール
+//!
+//! 設定で有効にした場合のみ、GitHub のリリースから最新のバージョンを1日1回確認する。
+//! 新しいバージョンがあれば、変更内容の抜粋を優先度の低いトーストで知らせ（通知音・未確認数なし）、
+//! トレイメニューにダウンロードページを開く項目を表示する。
+
+use crate::i18n;
+use crate::text;
+use crate::toast::{self, NotificationPriority, OnActivated, ToastContent};
+use crate::NotificationManager;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// 最新リリースを取得する GitHub API
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/hexylab/claude-code-notify/releases/latest";
+
+/// 自動で確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 起動直後の確認を待つ時間（起動処理と重ならないようにする）
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+/// 確認の時期が来たかを調べる間隔（設定の変更を反映するため）
+const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// トーストに表示する変更内容の最大行数
+const MAX_NOTES_LINES: usize = 3;
+
+/// トーストに表示する変更内容の最大文字数
+const MAX_NOTES_CHARS: usize = 200;
+
+/// 更新の確認の設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateCheckSettings {
+    /// GitHub のリリースを自動で確認する（既定は無効）
+    pub enabled: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateError {
+    #[error("failed to fetch latest release: {0}")]
+    Request(String),
+    #[error("invalid release response: {0}")]
+    InvalidResponse(String),
+}
+
+/// GitHub API のリリース（必要なフィールドのみ）
+#[derive(Debug, Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// 最新リリースの情報
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReleaseInfo {
+    pub version: String,
+    pub url: String,
+    /// 変更内容の抜粋
+    pub notes: String,
+}
+
+/// 確認の結果
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateStatus {
+    pub current_version: String,
+    /// 現在より新しいリリース（最新版を使用している場合は None）
+    pub available: Option<ReleaseInfo>,
+}
+
+/// `v1.2.3` 形式のバージョンを比較できる形にする（プレリリースの接尾辞は無視する）
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// リリースノートから見出しを除いた先頭の数行を取り出す
+fn notes_excerpt(body: &str) -> String {
+    let lines: Vec<&str> = body
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.trim_start_matches(['-', '*', ' ']))
+        .take(MAX_NOTES_LINES)
+        .collect();
+    let excerpt = lines.join("\n");
+    text::truncate(&excerpt, MAX_NOTES_CHARS)
+}
+
+fn release_info(release: GitHubRelease) -> Result<ReleaseInfo, UpdateError> {
+    let version = parse_version(&release.tag_name)
+        .map(|(major, minor, patch)| format!("{}.{}.{}", major, minor, patch))
+        .ok_or_else(|| UpdateError::InvalidResponse(format!("unexpected tag: {}", release.tag_name)))?;
+    Ok(ReleaseInfo {
+        version,
+        url: release.html_url,
+        notes: notes_excerpt(release.body.as_deref().unwrap_or_default()),
+    })
+}
+
+fn fetch_latest(current_version: &str) -> Result<ReleaseInfo, UpdateError> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .get(LATEST_RELEASE_URL)
+        .set("Accept", "application/vnd.github+json")
+        // GitHub API は User-Agent のないリクエストを拒否する
+        .set("User-Agent", &format!("claude-code-notify/{}", current_version))
+        .call()
+        .map_err(|e| UpdateError::Request(e.to_string()))?;
+    let release: GitHubRelease = serde_json::from_reader(response.into_reader())
+        .map_err(|e| UpdateError::InvalidResponse(e.to_string()))?;
+    release_info(release)
+}
+
+/// 確認した結果（トレイメニューに表示する）
+#[derive(Debug, Default)]
+pub struct UpdateState {
+    available: Mutex<Option<ReleaseInfo>>,
+    /// トーストで知らせたバージョン（同じバージョンは1度だけ知らせる）
+    notified: Mutex<Option<String>>,
+}
+
+impl UpdateState {
+    pub fn available(&self) -> Option<ReleaseInfo> {
+        self.available.lock().unwrap().clone()
+    }
+}
+
+/// ダウンロードページを開く
+pub fn open_download_page(app: &AppHandle, url: &str) {
+    if let Err(e) = app.opener().open_url(url, None::<&str>) {
+        warn!("Failed to open download page: {}", e);
+    }
+}
+
+/// 最新リリースを確認する（`manual` の場合は最新版でもトーストで結果を知らせる）
+pub fn check(app: &AppHandle, manual: bool) -> Result<UpdateStatus, String> {
+    let current_version = app.package_info().version.to_string();
+    let latest = fetch_latest(&current_version).map_err(|e| e.to_string())?;
+    let available = is_newer(&latest.version, &current_version).then_some(latest);
+    info!("Update check: current {}, available {:?}", current_version, available.as_ref().map(|r| &r.version));
+
+    let state = app.state::<UpdateState>();
+    let changed = {
+        let mut current = state.available.lock().unwrap();
+        let changed = *current != available;
+        *current = available.clone();
+        changed
+    };
+
+    let notification_manager = app.state::<Arc<NotificationManager>>();
+    let strings = notification_manager.strings();
+    if changed {
+        crate::tray::rebuild_menu(app, strings);
+    }
+
+    let first_notice = available.as_ref().is_some_and(|release| {
+        let mut notified = state.notified.lock().unwrap();
+        notified.replace(release.version.clone()).as_deref() != Some(release.version.as_str())
+    });
+    if (manual || first_notice) && notification_manager.get_settings().toast_notification_enabled {
+        let (content, on_activated) = match &available {
+            Some(release) => {
+                let url = release.url.clone();
+                let handle = app.clone();
+                let on_activated: OnActivated = Box::new(move |_| open_download_page(&handle, &url));
+                let body = if release.notes.is_empty() {
+                    strings.update_open_download.to_string()
+                } else {
+                    release.notes.clone()
+                };
+                let content = ToastContent {
+                    title: i18n::fill(strings.update_available_title, &[("version", &release.version)]),
+                    body,
+                    image: None,
+                };
+                (content, Some(on_activated))
+            }
+            None => {
+                let content = ToastContent {
+                    title: strings.update_up_to_date.to_string(),
+                    body: i18n::fill(strings.update_current_version, &[("version", &current_version)]),
+                    image: None,
+                };
+                (content, None)
+            }
+        };
+        toast::show_toast(app, content, NotificationPriority::Low, strings, &[], on_activated);
+    }
+
+    Ok(UpdateStatus {
+        current_version,
+        available,
+    })
+}
+
+/// 設定で有効な場合、更新を定期的に確認する
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(STARTUP_DELAY);
+        let mut last_checked: Option<Instant> = None;
+        loop {
+            let enabled = app
+                .state::<Arc<NotificationManager>>()
+                .get_settings()
+                .update_check
+                .enabled;
+            let due = last_checked.is_none_or(|checked| checked.elapsed() >= CHECK_INTERVAL);
+            if enabled && due {
+                if let Err(e) = check(&app, false) {
+                    warn!("Update check failed: {}", e);
+                }
+                last_checked = Some(Instant::now());
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// Tauriコマンド: 更新を今すぐ確認
+#[tauri::command]
+pub async fn check_for_update(app: AppHandle) -> Result<UpdateStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || check(&app, true))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_comparison() {
+        assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("0.4"), Some((0, 4, 0)));
+        assert_eq!(parse_version("v1.0.0-beta.1"), Some((1, 0, 0)));
+        assert_eq!(parse_version("nightly"), None);
+
+        assert!(is_newer("v0.4.0", "0.3.1"));
+        assert!(is_newer("0.3.10", "0.3.9"));
+        assert!(!is_newer("v0.3.1", "0.3.1"));
+        assert!(!is_newer("nightly", "0.3.1"));
+    }
+
+    #[test]
+    fn test_notes_excerpt() {
+        let body = "## What's Changed\n\n- Add heartbeat monitoring\n* Fix tray menu\n- Update docs\n- Bump deps\n";
+        assert_eq!(notes_excerpt(body), "Add heartbeat monitoring\nFix tray menu\nUpdate docs");
+
+        let long = "あ".repeat(MAX_NOTES_CHARS + 10);
+        let excerpt = notes_excerpt(&long);
+        assert_eq!(excerpt.chars().count(), MAX_NOTES_CHARS);
+        assert!(excerpt.ends_with('…'));
+    }
+
+    #[test]
+    fn test_release_info() {
+        let release: GitHubRelease = serde_json::from_str(
+            r#"{"tag_name":"v0.4.0","html_url":"https://github.com/hexylab/claude-code-notify/releases/tag/v0.4.0","body":null}"#,
+        )
+        .unwrap();
+        let info = release_info(release).unwrap();
+        assert_eq!(info.version, "0.4.0");
+        assert!(info.notes.is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+//! フックのペイロードの検証モジュール
+//!
+//! 受信したペイロードをイベントごとのフィールドの定義と照らし合わせ、
+//! 足りないフィールドや型の違いを構造化したエラーとして返す。
+//! 不正なペイロードは生の JSON をトーストに出す代わりに、原因のフィールドを示す短い通知を表示し、
+//! 生のペイロードは診断タブの「問題」に記録して後から確認できるようにする。
+
+use crate::i18n::{self, Strings};
+use crate::notification_history::NotificationEventType;
+use crate::problems::{ErrorCategory, ErrorPayload, ProblemLog};
+use crate::text;
+use crate::{show_simple_notification, NotificationManager, NotificationOrigin};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// 診断タブに記録する生のペイロードの最大長
+const MAX_RAW_PAYLOAD_CHARS: usize = 4000;
+
+/// フィールドの JSON の型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    String,
+    Object,
+}
+
+impl FieldType {
+    fn name(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Object => "object",
+        }
+    }
+
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Object => value.is_object(),
+        }
+    }
+}
+
+/// ペイロードのフィールドの定義
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    pub name: &'static str,
+    pub field_type: FieldType,
+    /// false の場合は省略・null を許す
+    pub required: bool,
+}
+
+const fn required(name: &'static str, field_type: FieldType) -> Field {
+    Field {
+        name,
+        field_type,
+        required: true,
+    }
+}
+
+const fn optional(name: &'static str, field_type: FieldType) -> Field {
+    Field {
+        name,
+        field_type,
+        required: false,
+    }
+}
+
+/// 停止イベント
+pub const STOP_EVENT: &[Field] = &[
+    required("event", FieldType::String),
+    required("cwd", FieldType::String),
+    optional("session_id", FieldType::String),
+    optional("host", FieldType::String),
+    optional("image", FieldType::String),
+    optional("last_message", FieldType::String),
+    optional("transcript_path", FieldType::String),
+    optional("wsl_distro", FieldType::String),
+];
+
+/// 承認依頼イベント
+pub const PERMISSION_REQUEST_EVENT: &[Field] = &[
+    required("event", FieldType::String),
+    required("cwd", FieldType::String),
+    required("content", FieldType::Object),
+    optional("session_id", FieldType::String),
+    optional("request_id", FieldType::String),
+    optional("host", FieldType::String),
+    optional("image", FieldType::String),
+    optional("wsl_distro", FieldType::String),
+];
+
+/// 通知イベント
+pub const NOTIFICATION_EVENT: &[Field] = &[
+    required("event", FieldType::String),
+    required("cwd", FieldType::String),
+    required("content", FieldType::Object),
+    optional("session_id", FieldType::String),
+    optional("host", FieldType::String),
+    optional("image", FieldType::String),
+    optional("wsl_distro", FieldType::String),
+];
+
+/// ペイロードの検証エラー
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PayloadError {
+    #[error("invalid JSON at line {line}, column {column}")]
+    InvalidJson { line: usize, column: usize },
+    #[error("payload is not a JSON object")]
+    NotAnObject,
+    #[error("missing field `{0}`")]
+    MissingField(&'static str),
+    #[error("field `{field}` must be {expected}, got {found}")]
+    WrongType {
+        field: &'static str,
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// 定義にないネストしたフィールドなどの誤り
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl PayloadError {
+    /// 通知・診断タブに表示する説明
+    pub fn describe(&self, strings: &Strings) -> String {
+        match self {
+            Self::InvalidJson { line, column } => i18n::fill(
+                strings.invalid_payload_json,
+                &[("line", &line.to_string()), ("column", &column.to_string())],
+            ),
+            Self::NotAnObject => strings.invalid_payload_not_object.to_string(),
+            Self::MissingField(field) => i18n::fill(strings.invalid_payload_missing_field, &[("field", field)]),
+            Self::WrongType { field, expected, found } => i18n::fill(
+                strings.invalid_payload_wrong_type,
+                &[("field", field), ("expected", expected), ("found", found)],
+            ),
+            Self::Invalid(message) => message.clone(),
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// ペイロードを検証して解釈する
+pub fn parse<T: DeserializeOwned>(payload: &str, fields: &[Field]) -> Result<T, PayloadError> {
+    let value: Value = serde_json::from_str(payload).map_err(|e| PayloadError::InvalidJson {
+        line: e.line(),
+        column: e.column(),
+    })?;
+    let object = value.as_object().ok_or(PayloadError::NotAnObject)?;
+
+    for field in fields {
+        match object.get(field.name) {
+            None | Some(Value::Null) if field.required => return Err(PayloadError::MissingField(field.name)),
+            None | Some(Value::Null) => {}
+            Some(value) if !field.field_type.accepts(value) => {
+                return Err(PayloadError::WrongType {
+                    field: field.name,
+                    expected: field.field_type.name(),
+                    found: type_name(value),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    serde_json::from_value(value).map_err(|e| PayloadError::Invalid(e.to_string()))
+}
+
+/// 不正なペイロードを診断タブに記録し、原因のフィールドを通知する
+pub fn report(
+    app: &AppHandle,
+    notification_manager: &NotificationManager,
+    topic: &str,
+    payload: &str,
+    error: &PayloadError,
+) {
+    warn!("Invalid payload on {}: {}", topic, error);
+    let strings = notification_manager.strings();
+    let reason = error.describe(strings);
+
+    // 読み取れた範囲で送信元を記録する
+    let value: Option<Value> = serde_json::from_str(payload).ok();
+    let text = |name: &str| {
+        value
+            .as_ref()
+            .and_then(|value| value.get(name))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let problem = ErrorPayload {
+        category: ErrorCategory::InvalidPayload,
+        message: format!("{}: {}", topic, reason),
+        hook: None,
+        exit_code: None,
+        host: text("host"),
+        session_id: text("session_id"),
+        cwd: text("cwd"),
+        raw: Some(text::truncate(payload, MAX_RAW_PAYLOAD_CHARS)),
+    };
+    let hint = problem.hint(strings);
+    let muted = notification_manager.get_settings().host_rules.is_muted(problem.host.as_deref());
+    app.state::<ProblemLog>().add(problem, hint);
+    let _ = app.emit("problem-added", ());
+
+    if muted {
+        info!("Invalid payload notification from muted host suppressed");
+        return;
+    }
+    let body = format!("{}\n{}", reason, hint);
+    show_simple_notification(
+        app,
+        notification_manager,
+        strings.invalid_payload_title,
+        &body,
+        NotificationEventType::Notification,
+        NotificationOrigin::default(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Event {
+        cwd: String,
+        #[serde(default)]
+        host: Option<String>,
+    }
+
+    const FIELDS: &[Field] = &[required("cwd", FieldType::String), optional("host", FieldType::String)];
+
+    #[test]
+    fn test_parse_valid_payload() {
+        let event: Event = parse(r#"{"cwd":"/work/app","host":null}"#, FIELDS).unwrap();
+        assert_eq!(event.cwd, "/work/app");
+        assert_eq!(event.host, None);
+    }
+
+    #[test]
+    fn test_structured_errors() {
+        let error = parse::<Event>(r#"{"host":"wsl"}"#, FIELDS).unwrap_err();
+        assert_eq!(error, PayloadError::MissingField("cwd"));
+
+        let error = parse::<Event>(r#"{"cwd":42}"#, FIELDS).unwrap_err();
+        assert_eq!(
+            error,
+            PayloadError::WrongType {
+                field: "cwd",
+                expected: "string",
+                found: "number"
+            }
+        );
+
+        assert_eq!(parse::<Event>("[1]", FIELDS).unwrap_err(), PayloadError::NotAnObject);
+        assert!(matches!(
+            parse::<Event>("{\"cwd\":", FIELDS).unwrap_err(),
+            PayloadError::InvalidJson { line: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_describe() {
+        let strings = i18n::Language::En.strings();
+        let error = PayloadError::WrongType {
+            field: "content",
+            expected: "object",
+            found: "string",
+        };
+        assert_eq!(error.describe(strings), "Field `content` must be object (got string)");
+        assert_eq!(
+            PayloadError::MissingField("cwd").describe(strings),
+            "Missing field `cwd`"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Webhook 連携モジュール
+//!
+//! 設定で有効にした場合、タスク完了・承認依頼・通知のイベントを指定した URL に POST する。
+//! 本文は `{event}` `{project}` `{message}` などのプレースホルダーを含むテンプレートから作り、
+//! 任意のヘッダーを付けられるため、IFTTT・Zapier・社内のサービスなどにコードを変更せずに送信できる。
+//! メッセージに含まれる機密情報は送信する前にマスクする。
+
+use crate::notification_history::NotificationEventType;
+use crate::redaction::RedactionSettings;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{info, warn};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 既定の本文のテンプレート
+pub const DEFAULT_BODY_TEMPLATE: &str = r#"{"event":"{event}","project":"{project}","message":"{message}"}"#;
+
+/// テンプレートで使えるプレースホルダー
+const PLACEHOLDERS: &[&str] = &["event", "project", "session", "host", "message", "timestamp"];
+
+/// 追加するヘッダー
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// Webhook の設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub enabled: bool,
+    /// 送信先の URL
+    pub url: String,
+    /// 本文のテンプレート（空の場合は既定のテンプレート）
+    pub body_template: String,
+    /// 追加するヘッダー（`Content-Type` を指定しない場合は `application/json`）
+    pub headers: Vec<WebhookHeader>,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            body_template: DEFAULT_BODY_TEMPLATE.to_string(),
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl WebhookSettings {
+    /// 送信先の URL（無効または未設定の場合は None）
+    pub fn active_url(&self) -> Option<&str> {
+        let url = self.url.trim();
+        (self.enabled && !url.is_empty()).then_some(url)
+    }
+
+    fn body_template(&self) -> &str {
+        if self.body_template.trim().is_empty() {
+            DEFAULT_BODY_TEMPLATE
+        } else {
+            &self.body_template
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("webhook URL is not set")]
+    MissingUrl,
+    #[error("invalid header name: {0}")]
+    InvalidHeader(String),
+    #[error("failed to send webhook: {0}")]
+    Request(String),
+}
+
+/// Webhook で送るイベント
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    pub event_type: NotificationEventType,
+    pub project: String,
+    pub session: String,
+    pub host: Option<String>,
+    pub message: String,
+}
+
+impl WebhookEvent {
+    /// `{event}` に入れる名前（フックが送るイベント名と同じ）
+    fn event_name(&self) -> &'static str {
+        match self.event_type {
+            NotificationEventType::Stop => "stop",
+            NotificationEventType::PermissionRequest => "permission_request",
+            NotificationEventType::Notification => "notification",
+        }
+    }
+
+    fn value(&self, placeholder: &str) -> Option<String> {
+        let value = match placeholder {
+            "event" => self.event_name().to_string(),
+            "project" => self.project.clone(),
+            "session" => self.session.clone(),
+            "host" => self.host.clone().unwrap_or_default(),
+            "message" => self.message.clone(),
+            "timestamp" => Utc::now().to_rfc3339(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// JSON の文字列の中に埋め込めるようにエスケープする（前後の引用符は付けない）
+fn escape_json(value: &str) -> String {
+    let quoted = serde_json::Value::String(value.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// テンプレートのプレースホルダーを置き換える
+///
+/// 値は JSON の文字列としてエスケープする。値に含まれるプレースホルダーは置き換えない。
+fn render(template: &str, event: &WebhookEvent) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let placeholder = after
+            .find('}')
+            .map(|end| &after[..end])
+            .filter(|name| PLACEHOLDERS.contains(name));
+        match placeholder.and_then(|name| event.value(name).map(|value| (name, value))) {
+            Some((name, value)) => {
+                rendered.push_str(&escape_json(&value));
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// イベントを Webhook に送信する
+fn post(settings: &WebhookSettings, url: &str, event: &WebhookEvent) -> Result<(), WebhookError> {
+    let mut request = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .post(url);
+    let mut has_content_type = false;
+    for header in &settings.headers {
+        let name = header.name.trim();
+        if name.is_empty() || !name.bytes().all(|b| b.is_ascii_graphic() && b != b':') {
+            return Err(WebhookError::InvalidHeader(header.name.clone()));
+        }
+        has_content_type |= name.eq_ignore_ascii_case("Content-Type");
+        request = request.set(name, header.value.trim());
+    }
+    if !has_content_type {
+        request = request.set("Content-Type", "application/json");
+    }
+    request
+        .send_string(&render(settings.body_template(), event))
+        .map_err(|e| WebhookError::Request(e.to_string()))?;
+    Ok(())
+}
+
+/// 設定で有効な場合、イベントをバックグラウンドで Webhook に送信する
+pub fn send(settings: &WebhookSettings, redaction: &RedactionSettings, event: WebhookEvent) {
+    let Some(url) = settings.active_url().map(str::to_string) else {
+        return;
+    };
+    let settings = settings.clone();
+    let event = WebhookEvent {
+        message: redaction.redact(&event.message),
+        ..event
+    };
+    std::thread::spawn(move || match post(&settings, &url, &event) {
+        Ok(()) => info!("Webhook sent for {} event", event.event_name()),
+        Err(e) => warn!("Webhook failed: {}", e),
+    });
+}
+
+/// Tauriコマンド: 設定画面の内容でテスト用のイベントを送信
+#[tauri::command]
+pub async fn test_webhook(settings: WebhookSettings) -> Result<(), String> {
+    let url = settings.url.trim().to_string();
+    if url.is_empty() {
+        return Err(WebhookError::MissingUrl.to_string());
+    }
+    let event = WebhookEvent {
+        event_type: NotificationEventType::Stop,
+        project: "claude-code-notify".to_string(),
+        session: "Test".to_string(),
+        host: None,
+        message: "Webhook test from Claude Code Notify".to_string(),
+    };
+    tauri::async_runtime::spawn_blocking(move || post(&settings, &url, &event))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(message: &str) -> WebhookEvent {
+        WebhookEvent {
+            event_type: NotificationEventType::PermissionRequest,
+            project: "my-app".to_string(),
+            session: "Alice (my-app)".to_string(),
+            host: Some("devbox".to_string()),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_default_template() {
+        let body = render(DEFAULT_BODY_TEMPLATE, &event("Bash"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["event"], "permission_request");
+        assert_eq!(json["project"], "my-app");
+        assert_eq!(json["message"], "Bash");
+    }
+
+    #[test]
+    fn test_render_escapes_values() {
+        // IFTTT の Webhooks は value1〜value3 を受け付ける
+        let template = r#"{"value1":"{project}@{host}","value2":"{message}","value3":"{unknown}"}"#;
+        let body = render(template, &event("say \"hi\"\nuse {project}"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["value1"], "my-app@devbox");
+        assert_eq!(json["value2"], "say \"hi\"\nuse {project}");
+        assert_eq!(json["value3"], "{unknown}");
+    }
+
+    #[test]
+    fn test_settings() {
+        let settings = WebhookSettings {
+            url: " https://example.com/hook ".to_string(),
+            body_template: " ".to_string(),
+            ..WebhookSettings::default()
+        };
+        assert_eq!(settings.active_url(), None);
+        assert_eq!(settings.body_template(), DEFAULT_BODY_TEMPLATE);
+
+        let enabled = WebhookSettings {
+            enabled: true,
+            ..settings
+        };
+        assert_eq!(enabled.active_url(), Some("https://example.com/hook"));
+    }
+}
@@ -0,0 +1,126 @@
+//! メインウィンドウの表示モードモジュール
+//!
+//! ダッシュボードを画面の隅に置いておけるように、コンパクト表示・不透明度・常に手前に表示を切り替える。
+//! コンパクト表示ではウィンドウを小さくし、フロントエンドはヘッダーとタブの名前を隠した詰めたレイアウトにする。
+//! 不透明度は Windows でのみ変更できる（レイヤードウィンドウの透明度を使う）。
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, LogicalSize, Manager, WebviewWindow};
+use tracing::warn;
+
+/// 通常表示のウィンドウの大きさ（tauri.conf.json と同じ）
+const NORMAL_SIZE: (f64, f64) = (480.0, 560.0);
+/// 通常表示のウィンドウの最小の大きさ（tauri.conf.json と同じ）
+const NORMAL_MIN_SIZE: (f64, f64) = (400.0, 450.0);
+/// コンパクト表示のウィンドウの大きさ
+const COMPACT_SIZE: (f64, f64) = (320.0, 360.0);
+/// コンパクト表示のウィンドウの最小の大きさ
+const COMPACT_MIN_SIZE: (f64, f64) = (260.0, 240.0);
+
+/// 不透明度の最小値（%、ウィンドウが見えなくならないようにする）
+const MIN_OPACITY: u8 = 30;
+
+/// メインウィンドウの表示モードの設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowModeSettings {
+    /// コンパクト表示
+    pub compact: bool,
+    /// 不透明度（%）
+    pub opacity: u8,
+    /// 常に手前に表示する
+    pub always_on_top: bool,
+}
+
+impl Default for WindowModeSettings {
+    fn default() -> Self {
+        Self {
+            compact: false,
+            opacity: 100,
+            always_on_top: false,
+        }
+    }
+}
+
+impl WindowModeSettings {
+    /// レイヤードウィンドウに設定するアルファ値（範囲外の不透明度は 30〜100% に丸める）
+    fn alpha(&self) -> u8 {
+        let opacity = u32::from(self.opacity.clamp(MIN_OPACITY, 100));
+        (opacity * 255 / 100) as u8
+    }
+}
+
+/// 表示モードをメインウィンドウに反映する
+///
+/// コンパクト表示を切り替えた場合のみウィンドウの大きさを変える（切り替えていなければ利用者が変えた大きさを保つ）。
+pub fn apply(app: &AppHandle, previous: &WindowModeSettings, settings: &WindowModeSettings) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    let (min_size, size) = if settings.compact {
+        (COMPACT_MIN_SIZE, COMPACT_SIZE)
+    } else {
+        (NORMAL_MIN_SIZE, NORMAL_SIZE)
+    };
+    if let Err(e) = window.set_min_size(Some(LogicalSize::new(min_size.0, min_size.1))) {
+        warn!("Failed to set window min size: {}", e);
+    }
+    if previous.compact != settings.compact {
+        if let Err(e) = window.set_size(LogicalSize::new(size.0, size.1)) {
+            warn!("Failed to resize main window: {}", e);
+        }
+    }
+
+    if let Err(e) = window.set_always_on_top(settings.always_on_top) {
+        warn!("Failed to set always on top: {}", e);
+    }
+    set_opacity(&window, settings.alpha());
+}
+
+/// ウィンドウの不透明度を変える
+#[cfg(windows)]
+fn set_opacity(window: &WebviewWindow, alpha: u8) {
+    use windows::Win32::Foundation::COLORREF;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA, WS_EX_LAYERED,
+    };
+
+    let Some(hwnd) = crate::taskbar::get_hwnd(window) else {
+        return;
+    };
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        if style & WS_EX_LAYERED.0 as isize == 0 {
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+        }
+        if let Err(e) = SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA) {
+            warn!("Failed to set window opacity: {}", e);
+        }
+    }
+}
+
+/// ウィンドウの不透明度を変える（Windows 以外では変更しない）
+#[cfg(not(windows))]
+fn set_opacity(_window: &WebviewWindow, _alpha: u8) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opacity(opacity: u8) -> WindowModeSettings {
+        WindowModeSettings {
+            opacity,
+            ..WindowModeSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_alpha() {
+        assert_eq!(WindowModeSettings::default().alpha(), 255);
+        assert_eq!(opacity(50).alpha(), 127);
+        // 見えなくならないように 30% 未満は 30% にする
+        assert_eq!(opacity(0).alpha(), 76);
+        assert_eq!(opacity(200).alpha(), 255);
+    }
+}
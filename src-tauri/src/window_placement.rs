@@ -0,0 +1,169 @@
+//! メインウィンドウの表示位置モジュール
+//!
+//! 通知やトレイからメインウィンドウを開いた時に、設定に応じてマウスカーソル（トレイ）の近く、
+//! またはアクティブなウィンドウがあるモニターの中央に移動する（既定では前回の位置のまま表示する）。
+//! アクティブなウィンドウの位置は Windows でのみ取得でき、その他のプラットフォームではカーソルのあるモニターを使う。
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, PhysicalPosition, WebviewWindow};
+use tracing::warn;
+
+/// カーソルとウィンドウの間隔（物理ピクセル）
+const CURSOR_MARGIN: i32 = 12;
+
+/// メインウィンドウの表示位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowPlacement {
+    /// 前回の位置のまま
+    #[default]
+    LastPosition,
+    /// マウスカーソルの近く（トレイから開いた場合はトレイの近く）
+    NearCursor,
+    /// アクティブなウィンドウがあるモニターの中央
+    ActiveMonitor,
+}
+
+/// 画面上の矩形（物理ピクセル）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Rect {
+    fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+
+    /// 作業領域からはみ出さない位置（作業領域より大きい場合は左上に合わせる）
+    fn clamp(&self, (x, y): (i32, i32), (width, height): (u32, u32)) -> (i32, i32) {
+        (
+            x.min(self.right() - width as i32).max(self.x),
+            y.min(self.bottom() - height as i32).max(self.y),
+        )
+    }
+
+    /// 作業領域の中央に置く位置
+    fn center(&self, (width, height): (u32, u32)) -> (i32, i32) {
+        let x = self.x + (self.width as i32 - width as i32) / 2;
+        let y = self.y + (self.height as i32 - height as i32) / 2;
+        self.clamp((x, y), (width, height))
+    }
+
+    /// カーソルの近くに置く位置（カーソルの上に置き、入らない場合は下に置く）
+    fn near(&self, (cursor_x, cursor_y): (i32, i32), (width, height): (u32, u32)) -> (i32, i32) {
+        let x = cursor_x - width as i32 / 2;
+        let above = cursor_y - CURSOR_MARGIN - height as i32;
+        let y = if above >= self.y { above } else { cursor_y + CURSOR_MARGIN };
+        self.clamp((x, y), (width, height))
+    }
+}
+
+/// 点を含むモニターの作業領域（タスクバーを除いた領域）
+fn work_area(app: &AppHandle, (x, y): (i32, i32)) -> Option<Rect> {
+    let monitor = app.monitor_from_point(f64::from(x), f64::from(y)).ok().flatten()?;
+    let area = monitor.work_area();
+    Some(Rect {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    })
+}
+
+fn cursor_position(app: &AppHandle) -> Option<(i32, i32)> {
+    match app.cursor_position() {
+        Ok(position) => Some((position.x as i32, position.y as i32)),
+        Err(e) => {
+            warn!("Failed to get cursor position: {}", e);
+            None
+        }
+    }
+}
+
+/// アクティブなウィンドウの中心
+#[cfg(windows)]
+fn active_window_center() -> Option<(i32, i32)> {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowRect};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+        Some(((rect.left + rect.right) / 2, (rect.top + rect.bottom) / 2))
+    }
+}
+
+/// アクティブなウィンドウの中心（Windows 以外では取得できない）
+#[cfg(not(windows))]
+fn active_window_center() -> Option<(i32, i32)> {
+    None
+}
+
+/// 設定に応じてウィンドウを移動する（位置を決められない場合は移動しない）
+pub fn apply(app: &AppHandle, window: &WebviewWindow, placement: WindowPlacement) {
+    let position = match placement {
+        WindowPlacement::LastPosition => return,
+        WindowPlacement::NearCursor => cursor_position(app),
+        WindowPlacement::ActiveMonitor => active_window_center().or_else(|| cursor_position(app)),
+    };
+    let Some(point) = position else {
+        return;
+    };
+    let Some(area) = work_area(app, point) else {
+        return;
+    };
+    let size = match window.outer_size() {
+        Ok(size) => (size.width, size.height),
+        Err(e) => {
+            warn!("Failed to get window size: {}", e);
+            return;
+        }
+    };
+
+    let (x, y) = match placement {
+        WindowPlacement::NearCursor => area.near(point, size),
+        _ => area.center(size),
+    };
+    if let Err(e) = window.set_position(PhysicalPosition::new(x, y)) {
+        warn!("Failed to move main window: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 1920,
+        y: 0,
+        width: 1920,
+        height: 1040,
+    };
+
+    #[test]
+    fn test_center() {
+        assert_eq!(AREA.center((800, 600)), (2480, 220));
+        // 作業領域より大きい場合は左上に合わせる
+        assert_eq!(AREA.center((2000, 1200)), (1920, 0));
+    }
+
+    #[test]
+    fn test_near_cursor() {
+        // トレイ（画面の右下）の近くでは、カーソルの上に置いて画面の右端に合わせる
+        assert_eq!(AREA.near((3800, 1030), (800, 600)), (3040, 418));
+        // 上に入らない場合はカーソルの下に置く
+        assert_eq!(AREA.near((2400, 100), (800, 600)), (2000, 112));
+    }
+}
@@ -4,12 +4,39 @@
 const { invoke } = window.__TAURI__.core;
 const { getCurrentWindow } = window.__TAURI__.window;
 const { getVersion } = window.__TAURI__.app;
-const { save } = window.__TAURI__.dialog;
+const { save, ask, message: showMessage } = window.__TAURI__.dialog;
 const { writeFile } = window.__TAURI__.fs;
 const { listen } = window.__TAURI__.event;
 
 // ===== グローバル状態 =====
 let currentTab = 'home';
+// 最後に読み込んだ設定（UIに表示していない項目を保存時に保持するため）
+let loadedSettings = {};
+// ブローカーの待ち受けポート（--port で変更できるため、表示とエクスポートに使う）
+let brokerPort = 1883;
+// localhost 専用リスナーのポート（開いていない場合は null）
+let localBrokerPort = null;
+// 診断タブでコストの推移を表示しているセッション
+let selectedSessionId = null;
+// 履歴タブに読み込んだ件数（「さらに読み込む」で続きを取得する位置）
+let historyLoaded = 0;
+// 履歴の検索の入力を待つタイマー
+let historySearchTimer = null;
+
+// ホストの表示色（#rrggbb）
+const HOST_COLOR_PATTERN = /^#[0-9a-fA-F]{6}$/;
+// プロジェクトの表示色のパレット（src-tauri/src/project_rules.rs の PALETTE と同じ順序）
+const PROJECT_COLORS = ['#e5484d', '#f76b15', '#f5b800', '#30a46c', '#0090ff', '#8e4ec6', '#a18072'];
+// プロファイルの自動切り替えの時刻（HH:MM）と曜日（設定ファイルでの表記）
+const TIME_PATTERN = /^([01]\d|2[0-3]):[0-5]\d$/;
+const WEEKDAYS = ['Mon', 'Tue', 'Wed', 'Thu', 'Fri', 'Sat', 'Sun'];
+const DEFAULT_WORKING_HOURS = { days: WEEKDAYS.slice(0, 5), start: '09:00', end: '18:00' };
+// 診断タブに表示する日ごとのコストの日数
+const DAILY_COST_DAYS = 14;
+// 読み込み済みの添付画像（ファイル名 → data URI）
+const attachmentCache = new Map();
+// 履歴タブで1回に読み込む件数
+const HISTORY_PAGE_SIZE = 50;
 
 // ===== DOM要素 =====
 const elements = {};
@@ -22,13 +49,31 @@ document.addEventListener('DOMContentLoaded', async () => {
     initHistoryTab();
     initSettingsTab();
     initExportTab();
+    initDiagnosticsTab();
     initFooter();
     initTauriEvents();
 
     // バージョン表示
     displayVersion();
+
+    // ホストの表示色を履歴・診断タブで使うため、設定を先に読み込む
+    loadSettings();
+
+    // 起動時に --history などで指定したタブを開く
+    openLaunchTab();
 });
 
+async function openLaunchTab() {
+    try {
+        const tab = await invoke('take_launch_tab');
+        if (tab) {
+            switchTab(tab);
+        }
+    } catch (error) {
+        console.error('Failed to get launch tab:', error);
+    }
+}
+
 function initElements() {
     // 共通
     elements.appVersion = document.getElementById('app-version');
@@ -41,23 +86,175 @@ function initElements() {
 
     // ホーム
     elements.brokerStatus = document.getElementById('broker-status');
+    elements.brokerAddress = document.getElementById('broker-address');
+    elements.brokerUptime = document.getElementById('broker-uptime');
+    elements.metricSessions = document.getElementById('metric-sessions');
+    elements.metricUnread = document.getElementById('metric-unread');
+    elements.metricCost = document.getElementById('metric-cost');
+    elements.metricContext = document.getElementById('metric-context');
+    elements.metricLines = document.getElementById('metric-lines');
 
     // 履歴
     elements.sessionFilter = document.getElementById('session-filter');
+    elements.historySearch = document.getElementById('history-search');
+    elements.historyTypeFilter = document.getElementById('history-type-filter');
+    elements.historyFrom = document.getElementById('history-from');
+    elements.historyTo = document.getElementById('history-to');
+    elements.historyLoadMore = document.getElementById('history-load-more');
+    elements.historyPinned = document.getElementById('history-pinned');
+    elements.historyPinnedList = document.getElementById('history-pinned-list');
     elements.markAllReadBtn = document.getElementById('mark-all-read');
     elements.clearHistoryBtn = document.getElementById('clear-history');
+    elements.compactHistoryBtn = document.getElementById('compact-history');
     elements.historyList = document.getElementById('history-list');
     elements.historyEmpty = document.getElementById('history-empty');
 
     // 設定
     elements.toastEnabled = document.getElementById('toast-enabled');
     elements.soundEnabled = document.getElementById('sound-enabled');
+    elements.speechEnabled = document.getElementById('speech-enabled');
     elements.flashEnabled = document.getElementById('flash-enabled');
     elements.badgeEnabled = document.getElementById('badge-enabled');
     elements.trayFlashEnabled = document.getElementById('tray-flash-enabled');
+    elements.trayIconTheme = document.getElementById('tray-icon-theme');
+    elements.windowPlacement = document.getElementById('window-placement');
+    elements.windowCompact = document.getElementById('window-compact');
+    elements.windowAlwaysOnTop = document.getElementById('window-always-on-top');
+    elements.windowOpacity = document.getElementById('window-opacity');
+    elements.trayAttentionMode = document.getElementById('tray-attention-mode');
+    elements.trayFlashInterval = document.getElementById('tray-flash-interval');
+    elements.trayFlashMaxDuration = document.getElementById('tray-flash-max-duration');
+    elements.taskbarFlashCount = document.getElementById('taskbar-flash-count');
+    elements.toastBodyMaxLength = document.getElementById('toast-body-max-length');
+    elements.brokerAclEnabled = document.getElementById('broker-acl-enabled');
+    elements.brokerAclPrefixes = document.getElementById('broker-acl-prefixes');
+    elements.brokerAclUsernames = document.getElementById('broker-acl-usernames');
+    elements.brokerLocalListenerEnabled = document.getElementById('broker-local-listener-enabled');
+    elements.brokerLocalPort = document.getElementById('broker-local-port');
+    elements.brokerDiscovery = document.getElementById('broker-discovery');
+    elements.brokerPortFallback = document.getElementById('broker-port-fallback');
+    elements.externalBrokerEnabled = document.getElementById('external-broker-enabled');
+    elements.externalBrokerHost = document.getElementById('external-broker-host');
+    elements.externalBrokerPort = document.getElementById('external-broker-port');
+    elements.externalBrokerUsername = document.getElementById('external-broker-username');
+    elements.externalBrokerPassword = document.getElementById('external-broker-password');
+    elements.externalBrokerClientId = document.getElementById('external-broker-client-id');
+    elements.payloadSigningEnabled = document.getElementById('payload-signing-enabled');
+    elements.payloadSigningSecret = document.getElementById('payload-signing-secret');
+    elements.payloadSigningWindow = document.getElementById('payload-signing-window');
+    elements.generateSecretBtn = document.getElementById('generate-secret-btn');
+    elements.payloadEncryptionEnabled = document.getElementById('payload-encryption-enabled');
+    elements.payloadEncryptionKey = document.getElementById('payload-encryption-key');
+    elements.generateKeyBtn = document.getElementById('generate-key-btn');
+    elements.restApiEnabled = document.getElementById('rest-api-enabled');
+    elements.restApiPort = document.getElementById('rest-api-port');
+    elements.apiTokenName = document.getElementById('api-token-name');
+    elements.issueTokenBtn = document.getElementById('issue-token-btn');
+    elements.issuedToken = document.getElementById('issued-token');
+    elements.issuedTokenLabel = document.getElementById('issued-token-label');
+    elements.issuedTokenValue = document.getElementById('issued-token-value');
+    elements.copyTokenBtn = document.getElementById('copy-token-btn');
+    elements.apiTokenList = document.getElementById('api-token-list');
+    elements.restApiAllowLan = document.getElementById('rest-api-allow-lan');
+    elements.dashboardUrl = document.getElementById('dashboard-url');
+    elements.restApiLanWarning = document.getElementById('rest-api-lan-warning');
+    elements.permissionResponseEnabled = document.getElementById('permission-response-enabled');
+    elements.permissionResponseTimeout = document.getElementById('permission-response-timeout');
+    elements.sessionControlEnabled = document.getElementById('session-control-enabled');
+    elements.lastMessageLength = document.getElementById('last-message-length');
+    elements.hostRules = document.getElementById('host-rules');
+    elements.stateTransitionEnabled = document.getElementById('state-transition-enabled');
+    elements.stateTransitionDebounce = document.getElementById('state-transition-debounce');
+    elements.waitingReminderEnabled = document.getElementById('waiting-reminder-enabled');
+    elements.waitingReminderMinutes = document.getElementById('waiting-reminder-minutes');
+    elements.allClearEnabled = document.getElementById('all-clear-enabled');
+    elements.lineMilestoneEnabled = document.getElementById('line-milestone-enabled');
+    elements.lineMilestoneStep = document.getElementById('line-milestone-step');
+    elements.projectAliases = document.getElementById('project-aliases');
+    elements.ignorePaths = document.getElementById('ignore-paths');
+    elements.rateLimitEnabled = document.getElementById('rate-limit-enabled');
+    elements.rateLimitMaxEvents = document.getElementById('rate-limit-max-events');
+    elements.rateLimitWindow = document.getElementById('rate-limit-window');
+    elements.rateLimitMute = document.getElementById('rate-limit-mute');
+    elements.catchUpEnabled = document.getElementById('catch-up-enabled');
+    elements.catchUpStartup = document.getElementById('catch-up-startup');
+    elements.catchUpMaxAge = document.getElementById('catch-up-max-age');
+    elements.staleEventAction = document.getElementById('stale-event-action');
+    elements.staleEventMaxAge = document.getElementById('stale-event-max-age');
+    elements.staleEventClockSkew = document.getElementById('stale-event-clock-skew');
+    elements.redactionEnabled = document.getElementById('redaction-enabled');
+    elements.redactionPatterns = document.getElementById('redaction-patterns');
+    elements.projectRules = document.getElementById('project-rules');
+    elements.projectColorToasts = document.getElementById('project-color-toasts');
+    elements.projectColorTrayDot = document.getElementById('project-color-tray-dot');
+    elements.tooltipTemplate = document.getElementById('tooltip-template');
+    elements.fullscreenEnabled = document.getElementById('fullscreen-enabled');
+    elements.fullscreenAllowCritical = document.getElementById('fullscreen-allow-critical');
+    elements.muteDuringCalls = document.getElementById('mute-during-calls');
+    elements.calendarEnabled = document.getElementById('calendar-enabled');
+    elements.calendarIcsUrl = document.getElementById('calendar-ics-url');
+    elements.heartbeatEnabled = document.getElementById('heartbeat-enabled');
+    elements.heartbeatInterval = document.getElementById('heartbeat-interval');
+    elements.heartbeatSilence = document.getElementById('heartbeat-silence');
+    elements.hostUpdateWatch = document.getElementById('host-update-watch');
+    elements.hostUpdateTargets = document.getElementById('host-update-targets');
+    elements.hostUpdateAutoPush = document.getElementById('host-update-auto-push');
+    elements.hostUpdatePushBtn = document.getElementById('host-update-push-btn');
+    elements.hostUpdateStatus = document.getElementById('host-update-status');
+    elements.digestEnabled = document.getElementById('digest-enabled');
+    elements.digestInterval = document.getElementById('digest-interval');
+    elements.digestHour = document.getElementById('digest-hour');
+    elements.digestNotify = document.getElementById('digest-notify');
+    elements.digestSlackUrl = document.getElementById('digest-slack-url');
+    elements.costReportEnabled = document.getElementById('cost-report-enabled');
+    elements.costReportTime = document.getElementById('cost-report-time');
+    elements.sendDigestBtn = document.getElementById('send-digest');
+    elements.digestStatus = document.getElementById('digest-status');
+    elements.webhookEnabled = document.getElementById('webhook-enabled');
+    elements.webhookUrl = document.getElementById('webhook-url');
+    elements.webhookBodyTemplate = document.getElementById('webhook-body-template');
+    elements.webhookHeaders = document.getElementById('webhook-headers');
+    elements.customTopics = document.getElementById('custom-topics');
+    elements.scriptHookEnabled = document.getElementById('script-hook-enabled');
+    elements.scriptHookCommand = document.getElementById('script-hook-command');
+    elements.scriptHookTimeout = document.getElementById('script-hook-timeout');
+    elements.routingEnabled = document.getElementById('routing-enabled');
+    elements.workingHoursEnabled = document.getElementById('working-hours-enabled');
+    elements.workingHours = document.getElementById('working-hours');
+    elements.openRoutingScriptBtn = document.getElementById('open-routing-script');
+    elements.routingStatus = document.getElementById('routing-status');
+    elements.testWebhookBtn = document.getElementById('test-webhook');
+    elements.webhookStatus = document.getElementById('webhook-status');
+    elements.updateCheckEnabled = document.getElementById('update-check-enabled');
+    elements.markReadOnResume = document.getElementById('mark-read-on-resume');
+    elements.historyMaxEntries = document.getElementById('history-max-entries');
+    elements.historyMetadataOnly = document.getElementById('history-metadata-only');
+    elements.historyMaxAge = document.getElementById('history-max-age');
+    elements.historyStopDays = document.getElementById('history-stop-days');
+    elements.historyPermissionDays = document.getElementById('history-permission-days');
+    elements.historyNotificationDays = document.getElementById('history-notification-days');
+    elements.checkUpdateBtn = document.getElementById('check-update');
+    elements.updateStatus = document.getElementById('update-status');
+    elements.profileSelect = document.getElementById('profile-select');
+    elements.deleteProfileBtn = document.getElementById('delete-profile-btn');
+    elements.profileName = document.getElementById('profile-name');
+    elements.saveProfileBtn = document.getElementById('save-profile-btn');
+    elements.profileHotkey = document.getElementById('profile-hotkey');
+    elements.profileRules = document.getElementById('profile-rules');
     elements.volumeSlider = document.getElementById('volume');
     elements.volumeDisplay = document.getElementById('volume-display');
     elements.testSoundBtn = document.getElementById('test-sound');
+    elements.channelTestBtns = document.querySelectorAll('.channel-test-btn');
+    elements.language = document.getElementById('language');
+    elements.soundStop = document.getElementById('sound-stop');
+    elements.soundPermission = document.getElementById('sound-permission');
+    elements.soundNotification = document.getElementById('sound-notification');
+    elements.previewBtns = document.querySelectorAll('.preview-btn');
+    elements.soundOverlap = document.getElementById('sound-overlap');
+    elements.maxConcurrentSounds = document.getElementById('max-concurrent-sounds');
+    elements.duplicateSoundWindow = document.getElementById('duplicate-sound-window');
+    elements.maxSoundDuration = document.getElementById('max-sound-duration');
+    elements.soundFade = document.getElementById('sound-fade');
     elements.saveBtn = document.getElementById('save-btn');
     elements.saveStatus = document.getElementById('save-status');
 
@@ -67,7 +264,50 @@ function initElements() {
     elements.exportLinuxBtn = document.getElementById('export-linux-btn');
     elements.exportWindowsBtn = document.getElementById('export-windows-btn');
     elements.exportStatus = document.getElementById('export-status');
+    elements.localInstallStatusline = document.getElementById('local-install-statusline');
+    elements.localInstallBtn = document.getElementById('local-install-btn');
+    elements.localInstallStatus = document.getElementById('local-install-status');
+    elements.templatePlatform = document.getElementById('template-platform');
+    elements.exportShell = document.getElementById('export-shell');
+    elements.exportPreset = document.getElementById('export-preset');
+    elements.templateFile = document.getElementById('template-file');
+    elements.templateEditor = document.getElementById('template-editor');
+    elements.saveTemplateBtn = document.getElementById('save-template');
+    elements.resetTemplateBtn = document.getElementById('reset-template');
+    elements.templateStatus = document.getElementById('template-status');
     elements.ipStatus = document.getElementById('ip-status');
+
+    // 診断
+    elements.metricConnections = document.getElementById('metric-connections');
+    elements.metricSubscriptions = document.getElementById('metric-subscriptions');
+    elements.metricRate = document.getElementById('metric-rate');
+    elements.metricPublishes = document.getElementById('metric-publishes');
+    elements.metricUpdated = document.getElementById('metric-updated');
+    elements.clientList = document.getElementById('client-list');
+    elements.clientEmpty = document.getElementById('client-empty');
+    elements.dailyCostChart = document.getElementById('daily-cost-chart');
+    elements.dailyCostSummary = document.getElementById('daily-cost-summary');
+    elements.sessionMetrics = document.getElementById('session-metrics');
+    elements.sessionCostLine = document.getElementById('session-cost-line');
+    elements.sessionMetricsSummary = document.getElementById('session-metrics-summary');
+    elements.sessionMetricsHint = document.getElementById('session-metrics-hint');
+    elements.sessionCommands = document.getElementById('session-commands');
+    elements.sessionCommandStatus = document.getElementById('session-command-status');
+    elements.metricResponded = document.getElementById('metric-responded');
+    elements.metricResponseMedian = document.getElementById('metric-response-median');
+    elements.heartbeatList = document.getElementById('heartbeat-list');
+    elements.heartbeatEmpty = document.getElementById('heartbeat-empty');
+    elements.problemList = document.getElementById('problem-list');
+    elements.problemEmpty = document.getElementById('problem-empty');
+    elements.clearProblemsBtn = document.getElementById('clear-problems');
+    elements.securityEventList = document.getElementById('security-event-list');
+    elements.securityEventEmpty = document.getElementById('security-event-empty');
+    elements.clearSecurityEventsBtn = document.getElementById('clear-security-events');
+    elements.devPanel = document.getElementById('dev-panel');
+    elements.fakeEventKind = document.getElementById('fake-event-kind');
+    elements.fakeEventFields = document.getElementById('fake-event-fields');
+    elements.sendFakeEventBtn = document.getElementById('send-fake-event');
+    elements.fakeEventStatus = document.getElementById('fake-event-status');
 }
 
 // ===== バージョン表示 =====
@@ -111,6 +351,10 @@ function switchTab(tabId) {
         loadSettings();
     } else if (tabId === 'export') {
         detectIp();
+        loadHookTemplates();
+    } else if (tabId === 'diagnostics') {
+        loadDiagnostics();
+        loadProblems();
     }
 }
 
@@ -118,18 +362,49 @@ function switchTab(tabId) {
 function initHomeTab() {
     checkBrokerStatus();
     setInterval(checkBrokerStatus, 5000);
+    loadMetrics();
 }
 
 async function checkBrokerStatus() {
     try {
-        const isRunning = await invoke('get_broker_status');
-        updateStatusDisplay(isRunning);
+        const status = await invoke('get_broker_status');
+        brokerPort = status.port;
+        localBrokerPort = status.local_port ?? null;
+        elements.brokerAddress.textContent = `${status.external_host ?? 'localhost'}:${status.port}`;
+        elements.brokerUptime.textContent = status.uptime_secs != null ? formatDuration(status.uptime_secs) : '—';
+        updateStatusDisplay(status.running);
     } catch (error) {
         console.error('Failed to check broker status:', error);
         updateStatusDisplay(false);
     }
 }
 
+// セッションの集計と未確認数（state-changed イベントで更新する）
+async function loadMetrics() {
+    try {
+        const [metrics, unread] = await Promise.all([
+            invoke('get_metrics'),
+            invoke('get_unread_count')
+        ]);
+        elements.metricSessions.textContent = metrics.active_sessions;
+        elements.metricUnread.textContent = unread;
+        elements.metricCost.textContent = `$${metrics.total_cost_usd.toFixed(2)}`;
+        elements.metricContext.textContent = `${Math.round(metrics.average_context_percent)}%`;
+        elements.metricLines.textContent = `+${metrics.total_lines_added} / -${metrics.total_lines_removed}`;
+    } catch (error) {
+        console.error('Failed to load metrics:', error);
+    }
+}
+
+function formatDuration(secs) {
+    const days = Math.floor(secs / 86400);
+    const hours = Math.floor((secs % 86400) / 3600);
+    const minutes = Math.floor((secs % 3600) / 60);
+    if (days > 0) return `${days}日${hours}時間`;
+    if (hours > 0) return `${hours}時間${minutes}分`;
+    return `${minutes}分`;
+}
+
 function updateStatusDisplay(isConnected) {
     const statusText = elements.brokerStatus.querySelector('.status-text');
 
@@ -146,19 +421,53 @@ function updateStatusDisplay(isConnected) {
 
 // ===== 履歴タブ =====
 function initHistoryTab() {
-    elements.sessionFilter.addEventListener('change', loadHistory);
+    elements.sessionFilter.addEventListener('change', () => loadHistory());
+    elements.historyTypeFilter.addEventListener('change', () => loadHistory());
+    elements.historyFrom.addEventListener('change', () => loadHistory());
+    elements.historyTo.addEventListener('change', () => loadHistory());
+    elements.historySearch.addEventListener('input', () => {
+        clearTimeout(historySearchTimer);
+        historySearchTimer = setTimeout(() => loadHistory(), 300);
+    });
+    elements.historyLoadMore.addEventListener('click', () => loadHistory({ append: true }));
     elements.markAllReadBtn.addEventListener('click', markAllRead);
     elements.clearHistoryBtn.addEventListener('click', clearHistory);
+    elements.compactHistoryBtn.addEventListener('click', compactHistory);
 }
 
-async function loadHistory() {
+// 履歴の検索条件（日付は入力した日のローカル時刻の始まり・終わり）
+function historyQuery(offset) {
+    const from = elements.historyFrom.value;
+    const to = elements.historyTo.value;
+    const eventType = elements.historyTypeFilter.value;
+    return {
+        text: elements.historySearch.value.trim() || null,
+        session_name: elements.sessionFilter.value || null,
+        // ピン留めしたものは上部に別に表示する
+        pinned: false,
+        event_types: eventType ? [eventType] : [],
+        from: from ? new Date(`${from}T00:00:00`).toISOString() : null,
+        to: to ? new Date(`${to}T23:59:59.999`).toISOString() : null,
+        offset,
+        limit: HISTORY_PAGE_SIZE
+    };
+}
+
+// 検索条件に一致する履歴を読み込む（append の場合は続きのページを追加する）
+async function loadHistory({ append = false } = {}) {
     try {
-        const filterSession = elements.sessionFilter.value || null;
-        const entries = await invoke('get_notification_history', { filterSession });
+        const offset = append ? historyLoaded : 0;
+        const page = await invoke('search_history', { query: historyQuery(offset) });
+        historyLoaded = offset + page.entries.length;
+        if (!append) {
+            const pinned = await invoke('search_history', { query: { pinned: true, limit: 500 } });
+            renderPinnedHistory(pinned.entries);
+        }
 
-        renderHistory(entries);
+        renderHistory(page.entries, append);
+        elements.historyLoadMore.classList.toggle('hidden', historyLoaded >= page.total);
         updateUnreadBadge();
-        updateSessionFilter(entries);
+        updateSessionFilter(page.session_names);
     } catch (error) {
         console.error('Failed to load history:', error);
         // バックエンドがまだ実装されていない場合は空表示
@@ -166,10 +475,12 @@ async function loadHistory() {
     }
 }
 
-function renderHistory(entries) {
-    elements.historyList.innerHTML = '';
+function renderHistory(entries, append = false) {
+    if (!append) {
+        elements.historyList.innerHTML = '';
+    }
 
-    if (entries.length === 0) {
+    if (elements.historyList.childElementCount === 0 && entries.length === 0) {
         elements.historyEmpty.classList.remove('hidden');
         elements.historyList.style.display = 'none';
         return;
@@ -184,6 +495,12 @@ function renderHistory(entries) {
     });
 }
 
+function renderPinnedHistory(entries) {
+    elements.historyPinnedList.innerHTML = '';
+    elements.historyPinned.classList.toggle('hidden', entries.length === 0);
+    entries.forEach(entry => elements.historyPinnedList.appendChild(createHistoryItem(entry)));
+}
+
 function createHistoryItem(entry) {
     const item = document.createElement('div');
     item.className = `history-item ${entry.read ? '' : 'unread'}`;
@@ -192,7 +509,8 @@ function createHistoryItem(entry) {
     const iconClass = getEventIconClass(entry.event_type);
     const icon = getEventIcon(entry.event_type);
     const typeName = getEventTypeName(entry.event_type);
-    const time = formatTime(entry.timestamp);
+    // フックが送信した時刻（ない場合は受信した時刻）
+    const time = formatTime(entry.sent_at ?? entry.timestamp);
     const project = extractProjectName(entry.cwd);
 
     item.innerHTML = `
@@ -200,18 +518,101 @@ function createHistoryItem(entry) {
         <div class="history-info">
             <div class="history-meta">
                 <span class="history-type">${typeName}</span>
-                <span class="history-time">${time}</span>
+                <span class="history-time" title="${formatHistoryTimeTitle(entry)}">${time}
+                    ${entry.transcript_path ? '<button type="button" class="history-transcript" title="トランスクリプトを開く">📄</button>' : ''}
+                    <button type="button" class="history-pin ${entry.pinned ? 'active' : ''}" title="${entry.pinned ? 'ピン留めを外す' : 'ピン留め'}">📌</button>
+                </span>
             </div>
             <div class="history-session">${entry.session_name}</div>
-            <div class="history-project">${project}</div>
+            <div class="history-project" style="--project-color: ${projectColor(entry.cwd)}">${project}</div>
+            ${createHostHtml(entry.host)}
+            ${createLastMessageHtml(entry)}
+            ${entry.image ? '<img class="history-image" alt="">' : ''}
+            ${createDecisionHtml(entry)}
+            ${createOutcomeHtml(entry)}
         </div>
     `;
+    if (entry.image) {
+        loadAttachmentImage(item.querySelector('.history-image'), entry.image);
+    }
 
     item.addEventListener('click', () => markAsRead(entry.id));
+    item.querySelector('.history-pin').addEventListener('click', (event) => {
+        event.stopPropagation();
+        pinHistoryEntry(entry.id, !entry.pinned);
+    });
+    item.querySelector('.history-transcript')?.addEventListener('click', (event) => {
+        event.stopPropagation();
+        openTranscript(entry.id);
+    });
+    item.querySelectorAll('[data-decision]').forEach(button => {
+        button.addEventListener('click', (event) => {
+            event.stopPropagation();
+            respondToPermission(entry.id, button.dataset.decision);
+        });
+    });
+    item.querySelectorAll('[data-question]').forEach(button => {
+        button.addEventListener('click', (event) => {
+            event.stopPropagation();
+            openQuestionPrompt(entry.id);
+        });
+    });
 
     return item;
 }
 
+// 添付画像を読み込んで表示（読み込めない場合は非表示）
+async function loadAttachmentImage(img, name) {
+    try {
+        if (!attachmentCache.has(name)) {
+            attachmentCache.set(name, await invoke('get_attachment_image', { name }));
+        }
+        img.src = attachmentCache.get(name);
+    } catch (error) {
+        console.error('Failed to load attachment image:', error);
+        img.remove();
+    }
+}
+
+// 承認依頼の許可・拒否ボタン、質問の回答ボタン（フックが応答を待っている場合のみ）
+function createDecisionHtml(entry) {
+    const isQuestion = entry.content === 'AskUserQuestion';
+    if (entry.decision) {
+        const label = isQuestion ? '回答済み' : (entry.decision === 'allow' ? '許可済み' : '拒否済み');
+        return `<div class="history-decision ${entry.decision}">${label}</div>`;
+    }
+    if (!entry.request_id) return '';
+    if (isQuestion) {
+        return `
+            <div class="history-decision">
+                <button type="button" class="btn-secondary" data-question>回答する</button>
+            </div>
+        `;
+    }
+    return `
+        <div class="history-decision">
+            <button type="button" class="btn-secondary" data-decision="allow">許可</button>
+            <button type="button" class="btn-secondary" data-decision="deny">拒否</button>
+        </div>
+    `;
+}
+
+// タスク完了時の Claude の最後のメッセージ
+function createLastMessageHtml(entry) {
+    if (entry.event_type !== 'Stop' || !entry.content) return '';
+    return `<div class="history-message">${escapeHtml(entry.content)}</div>`;
+}
+
+// 承認依頼のあとにセッションがどうなったか（応答までの時間）
+function createOutcomeHtml(entry) {
+    if (!entry.outcome) return '';
+    const label = entry.outcome === 'resumed' ? '作業を再開' : '作業を停止';
+    const elapsed = entry.resolved_at
+        ? `（${formatTaskDuration(Math.max(0, Math.round((new Date(entry.resolved_at) - new Date(entry.timestamp)) / 1000)))}後に応答）`
+        : '';
+    return `<div class="history-outcome ${entry.outcome}">${label}${elapsed}</div>`;
+}
+
 function getEventIconClass(eventType) {
     switch (eventType) {
         case 'Stop': return 'stop';
@@ -253,8 +654,89 @@ function formatTime(timestamp) {
     return date.toLocaleDateString('ja-JP', { month: 'numeric', day: 'numeric', hour: '2-digit', minute: '2-digit' });
 }
 
+// 日付と時刻（このPCのタイムゾーン）
+function formatDateTime(timestamp) {
+    return new Date(timestamp).toLocaleString('ja-JP');
+}
+
+// 履歴の時刻のツールチップ（送信した時刻と受信した時刻が両方ある場合は両方）
+function formatHistoryTimeTitle(entry) {
+    if (!entry.sent_at) {
+        return `受信: ${formatDateTime(entry.timestamp)}`;
+    }
+    return `送信: ${formatDateTime(entry.sent_at)}\n受信: ${formatDateTime(entry.timestamp)}`;
+}
+
+// 送信元ホストの表示（ホストごとのルールに色があればその色で表示）
+function createHostHtml(host) {
+    if (!host) return '';
+    const color = hostColor(host);
+    const style = color ? ` style="--host-color: ${color}"` : '';
+    return `<div class="host-tag"${style}>${escapeHtml(host)}</div>`;
+}
+
+function hostColor(host) {
+    const rules = loadedSettings.host_rules?.rules ?? [];
+    const rule = rules.find(r => r.host.trim().toLowerCase() === host.trim().toLowerCase());
+    return rule?.color && HOST_COLOR_PATTERN.test(rule.color) ? rule.color : null;
+}
+
+// プロジェクトの表示色（ルールに色があればその色、なければプロジェクト名のハッシュでパレットから選ぶ）
+function projectColor(cwd) {
+    const path = (cwd ?? '').replace(/[\\/]+$/, '');
+    const name = projectAlias(cwd) ?? path.split(/[\\/]/).pop() ?? '';
+    const rules = loadedSettings.project_rules?.rules ?? [];
+    const matches = (target) => rules.find(r => {
+        const project = r.project.trim().replace(/[\\/]+$/, '').toLowerCase();
+        const targetName = target.split(/[\\/]/).pop();
+        return project && (project === target.toLowerCase() || project === targetName.toLowerCase());
+    });
+    const rule = matches(path) ?? matches(name);
+    if (rule?.color && HOST_COLOR_PATTERN.test(rule.color)) {
+        return rule.color.toLowerCase();
+    }
+    // FNV-1a（project_rules.rs の hashed_color と同じ計算）
+    let hash = 0x811c9dc5;
+    for (const byte of new TextEncoder().encode(name.toLowerCase())) {
+        hash = Math.imul(hash ^ byte, 0x01000193) >>> 0;
+    }
+    return PROJECT_COLORS[hash % PROJECT_COLORS.length];
+}
+
+function escapeHtml(text) {
+    const div = document.createElement('div');
+    div.textContent = text;
+    return div.innerHTML;
+}
+
+// 作業ディレクトリをまとめるプロジェクト名（project_aliases.rs と同じ判定、一致しなければ null）
+function projectAlias(cwd) {
+    const path = normalizeProjectPath(cwd);
+    const name = path.split('/').pop();
+    const alias = (loadedSettings.projects?.aliases ?? []).find(alias => alias.name.trim() && alias.paths.some(pattern => {
+        const normalized = normalizeProjectPath(pattern);
+        if (!normalized) return false;
+        return normalized.includes('/')
+            ? globMatch(normalized, path) || globMatch(`${normalized}/*`, path)
+            : globMatch(normalized, name);
+    }));
+    return alias ? alias.name.trim() : null;
+}
+
+function normalizeProjectPath(path) {
+    return (path ?? '').trim().toLowerCase().replaceAll('\\', '/').replace(/\/+$/, '');
+}
+
+// `*` を任意の文字列として照合する
+function globMatch(pattern, text) {
+    const source = pattern.split('*').map(part => part.replace(/[.+?^${}()|[\]\\]/g, '\\$&')).join('.*');
+    return new RegExp(`^${source}$`, 's').test(text);
+}
+
 function extractProjectName(cwd) {
     if (!cwd) return '';
+    const alias = projectAlias(cwd);
+    if (alias) return alias;
     const parts = cwd.split('/');
     return parts[parts.length - 1] || cwd;
 }
@@ -268,6 +750,42 @@ async function markAsRead(id) {
     }
 }
 
+async function pinHistoryEntry(id, pinned) {
+    try {
+        await invoke('pin_notification', { id, pinned });
+        loadHistory();
+    } catch (error) {
+        console.error('Failed to pin history entry:', error);
+    }
+}
+
+// トランスクリプトを既定のアプリで開く（WSL のパスは Windows から読めるパスに変換済み）
+async function openTranscript(id) {
+    try {
+        await invoke('open_transcript', { id });
+    } catch (error) {
+        console.error('Failed to open transcript:', error);
+        await showMessage(`トランスクリプトを開けませんでした: ${error}`, { title: 'トランスクリプト', kind: 'error' });
+    }
+}
+
+async function respondToPermission(id, decision) {
+    try {
+        await invoke('respond_to_permission_request', { id, decision });
+        loadHistory();
+    } catch (error) {
+        console.error('Failed to respond to permission request:', error);
+    }
+}
+
+async function openQuestionPrompt(id) {
+    try {
+        await invoke('open_question_prompt', { id });
+    } catch (error) {
+        console.error('Failed to open question prompt:', error);
+    }
+}
+
 async function markAllRead() {
     try {
         await invoke('mark_all_notifications_read');
@@ -286,6 +804,17 @@ async function clearHistory() {
     }
 }
 
+async function compactHistory() {
+    try {
+        const removed = await invoke('compact_notification_history');
+        console.log(`Removed ${removed} history entries`);
+        loadHistory();
+        updateUnreadBadge();
+    } catch (error) {
+        console.error('Failed to compact history:', error);
+    }
+}
+
 async function updateUnreadBadge() {
     try {
         const count = await invoke('get_unread_count');
@@ -301,10 +830,7 @@ async function updateUnreadBadge() {
     }
 }
 
-function updateSessionFilter(entries) {
-    const sessions = new Set();
-    entries.forEach(e => sessions.add(e.session_name));
-
+function updateSessionFilter(sessions) {
     const currentValue = elements.sessionFilter.value;
     elements.sessionFilter.innerHTML = '<option value="">すべて</option>';
 
@@ -325,50 +851,730 @@ function initSettingsTab() {
     });
 
     elements.testSoundBtn.addEventListener('click', playTestSound);
+    elements.sendDigestBtn.addEventListener('click', sendDigestNow);
+    elements.testWebhookBtn.addEventListener('click', testWebhook);
+    elements.openRoutingScriptBtn.addEventListener('click', openRoutingScript);
+    elements.checkUpdateBtn.addEventListener('click', checkForUpdate);
+    elements.channelTestBtns.forEach(btn => {
+        btn.addEventListener('click', () => runChannelTest(btn.dataset.command));
+    });
+    elements.previewBtns.forEach(btn => {
+        btn.addEventListener('click', () => previewSound(btn.dataset.soundSelect));
+    });
+    elements.generateSecretBtn.addEventListener('click', generateSigningSecret);
+    elements.generateKeyBtn.addEventListener('click', generateEncryptionKey);
+    elements.issueTokenBtn.addEventListener('click', issueApiToken);
+    elements.copyTokenBtn.addEventListener('click', copyIssuedToken);
+    elements.hostUpdatePushBtn.addEventListener('click', pushBrokerHost);
+    elements.restApiPort.addEventListener('change', updateDashboardUrl);
+    elements.restApiAllowLan.addEventListener('change', updateDashboardUrl);
     elements.saveBtn.addEventListener('click', saveSettings);
+    elements.profileSelect.addEventListener('change', switchProfile);
+    elements.deleteProfileBtn.addEventListener('click', deleteProfile);
+    elements.saveProfileBtn.addEventListener('click', saveProfile);
+    elements.profileHotkey.addEventListener('change', saveProfileHotkey);
+    elements.profileRules.addEventListener('change', saveProfileRules);
 }
 
 async function loadSettings() {
     try {
         const settings = await invoke('get_settings');
+        loadedSettings = settings;
 
         elements.toastEnabled.checked = settings.toast_notification_enabled;
         elements.soundEnabled.checked = settings.sound_enabled;
+        const speech = settings.speech ?? {};
+        elements.speechEnabled.checked = speech.enabled ?? false;
         elements.flashEnabled.checked = settings.taskbar_flash_enabled;
         elements.badgeEnabled.checked = settings.taskbar_badge_enabled;
         elements.trayFlashEnabled.checked = settings.tray_flash_enabled ?? true;
+        elements.trayIconTheme.value = settings.tray_icon_theme ?? 'color';
+        elements.windowPlacement.value = settings.window_placement ?? 'last_position';
+        const windowMode = settings.window_mode ?? {};
+        elements.windowCompact.checked = windowMode.compact ?? false;
+        elements.windowAlwaysOnTop.checked = windowMode.always_on_top ?? false;
+        elements.windowOpacity.value = windowMode.opacity ?? 100;
+        applyCompactMode(elements.windowCompact.checked);
+        if (settings.tray_attention_mode) {
+            elements.trayAttentionMode.value = settings.tray_attention_mode;
+        }
+        elements.trayFlashInterval.value = settings.tray_flash_interval_ms ?? 500;
+        elements.trayFlashMaxDuration.value = settings.tray_flash_max_duration_secs ?? 0;
+        elements.taskbarFlashCount.value = settings.taskbar_flash_count ?? 3;
+        elements.toastBodyMaxLength.value = settings.toast_body_max_length ?? 120;
 
         const volumePercent = Math.round(settings.sound_volume * 100);
         elements.volumeSlider.value = volumePercent;
         elements.volumeDisplay.textContent = volumePercent;
+
+        elements.language.value = settings.language ?? 'auto';
+
+        const eventSounds = settings.event_sounds ?? {};
+        elements.soundStop.value = eventSounds.stop ?? 'default';
+        elements.soundPermission.value = eventSounds.permission_request ?? 'default';
+        elements.soundNotification.value = eventSounds.notification ?? 'default';
+        elements.soundOverlap.value = settings.sound_overlap ?? 'overlap';
+        elements.maxConcurrentSounds.value = settings.max_concurrent_sounds ?? 3;
+        elements.duplicateSoundWindow.value = settings.duplicate_sound_window_ms ?? 2000;
+        elements.maxSoundDuration.value = settings.max_sound_duration_ms ?? 0;
+        elements.soundFade.value = settings.sound_fade_ms ?? 0;
+
+        const brokerAcl = settings.broker_acl ?? {};
+        elements.brokerAclEnabled.checked = brokerAcl.enabled ?? false;
+        elements.brokerAclPrefixes.value = (brokerAcl.client_id_prefixes ?? ['claude-code-', 'mqtt-publish-']).join('\n');
+        elements.brokerAclUsernames.value = (brokerAcl.usernames ?? []).join('\n');
+        const brokerListeners = settings.broker_listeners ?? {};
+        elements.brokerLocalListenerEnabled.checked = brokerListeners.local_enabled ?? false;
+        elements.brokerLocalPort.value = brokerListeners.local_port ?? 1884;
+        elements.brokerDiscovery.checked = settings.broker_discovery ?? false;
+        elements.brokerPortFallback.checked = settings.broker_port_fallback ?? true;
+        const externalBroker = settings.external_broker ?? {};
+        elements.externalBrokerEnabled.checked = externalBroker.enabled ?? false;
+        elements.externalBrokerHost.value = externalBroker.host ?? '';
+        elements.externalBrokerPort.value = externalBroker.port ?? 1883;
+        elements.externalBrokerUsername.value = externalBroker.username ?? '';
+        elements.externalBrokerPassword.value = externalBroker.password ?? '';
+        elements.externalBrokerClientId.value = externalBroker.client_id ?? 'claude-code-notify-client';
+
+        const payloadSigning = settings.payload_signing ?? {};
+        elements.payloadSigningEnabled.checked = payloadSigning.enabled ?? false;
+        elements.payloadSigningSecret.value = payloadSigning.secret ?? '';
+        elements.payloadSigningWindow.value = payloadSigning.window_secs ?? 300;
+
+        const payloadEncryption = settings.payload_encryption ?? {};
+        elements.payloadEncryptionEnabled.checked = payloadEncryption.enabled ?? false;
+        elements.payloadEncryptionKey.value = payloadEncryption.key ?? '';
+
+        const restApi = settings.rest_api ?? {};
+        elements.restApiEnabled.checked = restApi.enabled ?? false;
+        elements.restApiPort.value = restApi.port ?? 18830;
+        elements.restApiAllowLan.checked = restApi.allow_lan ?? false;
+        updateDashboardUrl();
+        const permissionResponse = settings.permission_response ?? {};
+        elements.permissionResponseEnabled.checked = permissionResponse.enabled ?? false;
+        elements.permissionResponseTimeout.value = permissionResponse.timeout_secs ?? 50;
+        elements.sessionControlEnabled.checked = settings.session_control?.enabled ?? false;
+        elements.lastMessageLength.value = settings.last_message_length ?? 200;
+
+        elements.hostRules.value = formatHostRules(settings.host_rules?.rules ?? []);
+        elements.stateTransitionEnabled.checked = settings.state_transitions?.enabled ?? false;
+        elements.stateTransitionDebounce.value = settings.state_transitions?.debounce_secs ?? 5;
+        elements.waitingReminderEnabled.checked = settings.waiting_reminder?.enabled ?? false;
+        elements.waitingReminderMinutes.value = settings.waiting_reminder?.minutes ?? 15;
+        elements.allClearEnabled.checked = settings.all_clear?.enabled ?? false;
+        elements.lineMilestoneEnabled.checked = settings.line_milestones?.enabled ?? false;
+        elements.lineMilestoneStep.value = settings.line_milestones?.step ?? 500;
+        elements.projectAliases.value = formatProjectAliases(settings.projects?.aliases ?? []);
+        elements.ignorePaths.value = (settings.ignore_list?.paths ?? []).join('\n');
+        const rateLimit = settings.rate_limit ?? {};
+        elements.rateLimitEnabled.checked = rateLimit.enabled ?? true;
+        elements.rateLimitMaxEvents.value = rateLimit.max_events ?? 30;
+        elements.rateLimitWindow.value = rateLimit.window_secs ?? 10;
+        elements.rateLimitMute.value = rateLimit.mute_secs ?? 60;
+        const catchUp = settings.catch_up ?? {};
+        elements.catchUpEnabled.checked = catchUp.enabled ?? true;
+        elements.catchUpStartup.value = catchUp.startup_secs ?? 60;
+        elements.catchUpMaxAge.value = catchUp.max_age_minutes ?? 5;
+        const staleEvents = settings.stale_events ?? {};
+        elements.staleEventAction.value = staleEvents.action ?? 'demote';
+        elements.staleEventMaxAge.value = staleEvents.max_age_minutes ?? 30;
+        elements.staleEventClockSkew.value = staleEvents.clock_skew_secs ?? 120;
+        elements.redactionEnabled.checked = settings.redaction?.enabled ?? true;
+        elements.redactionPatterns.value = (settings.redaction?.patterns ?? []).join('\n');
+        elements.projectRules.value = formatProjectRules(settings.project_rules?.rules ?? []);
+        elements.projectColorToasts.checked = settings.project_rules?.color_toasts ?? false;
+        elements.projectColorTrayDot.checked = settings.project_rules?.color_tray_dot ?? false;
+        elements.tooltipTemplate.value = settings.tooltip_template ?? '';
+
+        const fullscreen = settings.fullscreen ?? {};
+        elements.fullscreenEnabled.checked = fullscreen.enabled ?? true;
+        elements.fullscreenAllowCritical.checked = fullscreen.allow_critical ?? true;
+        elements.muteDuringCalls.checked = settings.mute_during_calls ?? false;
+
+        const calendar = settings.calendar ?? {};
+        elements.calendarEnabled.checked = calendar.enabled ?? false;
+        elements.calendarIcsUrl.value = calendar.ics_url ?? '';
+
+        const heartbeat = settings.heartbeat ?? {};
+        elements.heartbeatEnabled.checked = heartbeat.enabled ?? true;
+        elements.heartbeatInterval.value = heartbeat.interval_secs ?? 60;
+        elements.heartbeatSilence.value = heartbeat.silence_minutes ?? 10;
+        const hostUpdate = settings.host_update ?? {};
+        elements.hostUpdateWatch.checked = hostUpdate.watch_ip ?? true;
+        elements.hostUpdateTargets.value = (hostUpdate.ssh_targets ?? []).join('\n');
+        elements.hostUpdateAutoPush.checked = hostUpdate.auto_push ?? false;
+
+        const digest = settings.digest ?? {};
+        elements.digestEnabled.checked = digest.enabled ?? false;
+        elements.digestInterval.value = digest.interval ?? 'daily';
+        elements.digestHour.value = digest.daily_hour ?? 9;
+        elements.digestNotify.checked = digest.notify ?? true;
+        elements.digestSlackUrl.value = digest.slack_webhook_url ?? '';
+        elements.costReportEnabled.checked = settings.cost_report?.enabled ?? false;
+        elements.costReportTime.value = settings.cost_report?.time ?? '18:00';
+        const webhook = settings.webhook ?? {};
+        elements.webhookEnabled.checked = webhook.enabled ?? false;
+        elements.webhookUrl.value = webhook.url ?? '';
+        elements.webhookBodyTemplate.value = webhook.body_template ?? '';
+        elements.webhookHeaders.value = formatWebhookHeaders(webhook.headers ?? []);
+        elements.customTopics.value = formatCustomTopics(settings.custom_topics?.topics ?? []);
+        const scriptHook = settings.script_hook ?? {};
+        elements.scriptHookEnabled.checked = scriptHook.enabled ?? false;
+        elements.scriptHookCommand.value = scriptHook.command ?? '';
+        elements.scriptHookTimeout.value = scriptHook.timeout_secs ?? 10;
+        elements.routingEnabled.checked = settings.routing?.enabled ?? false;
+        const workingHours = settings.routing?.working_hours ?? {};
+        elements.workingHoursEnabled.checked = workingHours.enabled ?? false;
+        elements.workingHours.value = formatWorkingHours(workingHours);
+        elements.updateCheckEnabled.checked = settings.update_check?.enabled ?? false;
+
+        elements.markReadOnResume.checked = settings.mark_read_on_resume ?? true;
+        elements.historyMetadataOnly.checked = settings.history_metadata_only ?? false;
+        const retention = settings.history_retention ?? {};
+        elements.historyMaxEntries.value = retention.max_entries ?? 100;
+        elements.historyMaxAge.value = retention.max_age_days ?? 0;
+        elements.historyStopDays.value = retention.stop_days ?? '';
+        elements.historyPermissionDays.value = retention.permission_request_days ?? '';
+        elements.historyNotificationDays.value = retention.notification_days ?? '';
     } catch (error) {
         console.error('Failed to load settings:', error);
         showSettingsStatus('設定の読み込みに失敗しました', 'error');
     }
+
+    await loadProfiles();
+    await loadApiTokens();
+}
+
+async function loadProfiles() {
+    try {
+        const profiles = await invoke('get_profiles');
+        elements.profileSelect.replaceChildren(
+            new Option('（なし）', ''),
+            ...profiles.names.map(name => new Option(name, name))
+        );
+        elements.profileSelect.value = profiles.active ?? '';
+        elements.profileHotkey.value = profiles.hotkey;
+        elements.profileRules.value = formatProfileRules(profiles.rules);
+    } catch (error) {
+        console.error('Failed to load profiles:', error);
+    }
+}
+
+async function switchProfile() {
+    const name = elements.profileSelect.value;
+    if (!name) {
+        return;
+    }
+    try {
+        await invoke('switch_profile', { name });
+        await loadSettings();
+        showSettingsStatus(`プロファイル「${name}」に切り替えました`, 'success');
+    } catch (error) {
+        console.error('Failed to switch profile:', error);
+        showSettingsStatus('プロファイルの切り替えに失敗しました', 'error');
+    }
+}
+
+async function saveProfile() {
+    const name = elements.profileName.value.trim();
+    if (!name) {
+        showSettingsStatus('プロファイル名を入力してください', 'error');
+        return;
+    }
+    try {
+        const settings = collectSettings();
+        await invoke('save_profile', { name, settings });
+        loadedSettings = settings;
+        elements.profileName.value = '';
+        await loadProfiles();
+        showSettingsStatus(`プロファイル「${name}」を保存しました`, 'success');
+    } catch (error) {
+        console.error('Failed to save profile:', error);
+        showSettingsStatus('プロファイルの保存に失敗しました', 'error');
+    }
+}
+
+async function deleteProfile() {
+    const name = elements.profileSelect.value;
+    if (!name) {
+        return;
+    }
+    try {
+        await invoke('delete_profile', { name });
+        await loadProfiles();
+        showSettingsStatus(`プロファイル「${name}」を削除しました`, 'success');
+    } catch (error) {
+        console.error('Failed to delete profile:', error);
+        showSettingsStatus('プロファイルの削除に失敗しました', 'error');
+    }
+}
+
+async function saveProfileHotkey() {
+    try {
+        await invoke('set_profile_hotkey', { hotkey: elements.profileHotkey.value.trim() });
+        showSettingsStatus('ホットキーを保存しました', 'success');
+    } catch (error) {
+        console.error('Failed to register hotkey:', error);
+        showSettingsStatus('ホットキーを登録できませんでした', 'error');
+    }
+}
+
+// 通知音の再生時間の上限とフェード（試聴にも入力中の値を使う）
+function soundShaping() {
+    return {
+        max_sound_duration_ms: Math.max(0, parseInt(elements.maxSoundDuration.value, 10) || 0),
+        sound_fade_ms: Math.max(0, parseInt(elements.soundFade.value, 10) || 0)
+    };
 }
 
 async function playTestSound() {
     try {
         const volume = parseFloat(elements.volumeSlider.value) / 100;
-        await invoke('play_test_sound', { volume });
+        const { max_sound_duration_ms: maxDurationMs, sound_fade_ms: fadeMs } = soundShaping();
+        await invoke('play_test_sound', { volume, maxDurationMs, fadeMs });
     } catch (error) {
         console.error('Failed to play test sound:', error);
         showSettingsStatus('テスト再生に失敗しました', 'error');
     }
 }
 
-async function saveSettings() {
+async function runChannelTest(command) {
+    try {
+        await invoke(command);
+    } catch (error) {
+        console.error(`Failed to run ${command}:`, error);
+        showSettingsStatus(`テストに失敗しました: ${error}`, 'error');
+    }
+}
+
+async function sendDigestNow() {
+    elements.digestStatus.textContent = '送信中...';
+    try {
+        await invoke('send_digest_now');
+        elements.digestStatus.textContent = 'ダイジェストを送りました';
+    } catch (error) {
+        console.error('Failed to send digest:', error);
+        elements.digestStatus.textContent = `ダイジェストを送れませんでした: ${error}`;
+    }
+}
+
+function collectWebhookSettings() {
+    return {
+        enabled: elements.webhookEnabled.checked,
+        url: elements.webhookUrl.value.trim(),
+        body_template: elements.webhookBodyTemplate.value,
+        headers: parseWebhookHeaders(elements.webhookHeaders.value)
+    };
+}
+
+async function openRoutingScript() {
+    try {
+        await invoke('open_routing_script');
+        elements.routingStatus.textContent = '';
+    } catch (error) {
+        console.error('Failed to open routing script:', error);
+        elements.routingStatus.textContent = `スクリプトを開けませんでした: ${error}`;
+    }
+}
+
+async function testWebhook() {
+    elements.webhookStatus.textContent = '送信中...';
+    try {
+        await invoke('test_webhook', { settings: collectWebhookSettings() });
+        elements.webhookStatus.textContent = 'テストイベントを送りました';
+    } catch (error) {
+        console.error('Failed to send webhook:', error);
+        elements.webhookStatus.textContent = `送信できませんでした: ${error}`;
+    }
+}
+
+async function checkForUpdate() {
+    elements.updateStatus.textContent = '確認中...';
+    try {
+        const status = await invoke('check_for_update');
+        elements.updateStatus.textContent = status.available
+            ? `v${status.available.version} があります（現在 v${status.current_version}）。トレイメニューからダウンロードできます`
+            : `最新版を使用しています（v${status.current_version}）`;
+    } catch (error) {
+        console.error('Failed to check for update:', error);
+        elements.updateStatus.textContent = '更新を確認できませんでした';
+    }
+}
+
+async function previewSound(selectId) {
     try {
-        const settings = {
-            toast_notification_enabled: elements.toastEnabled.checked,
-            sound_enabled: elements.soundEnabled.checked,
-            taskbar_flash_enabled: elements.flashEnabled.checked,
-            taskbar_badge_enabled: elements.badgeEnabled.checked,
-            tray_flash_enabled: elements.trayFlashEnabled.checked,
-            sound_volume: parseFloat(elements.volumeSlider.value) / 100
-        };
+        const sound = document.getElementById(selectId).value;
+        const volume = parseFloat(elements.volumeSlider.value) / 100;
+        const { max_sound_duration_ms: maxDurationMs, sound_fade_ms: fadeMs } = soundShaping();
+        await invoke('preview_sound', { sound, volume, maxDurationMs, fadeMs });
+    } catch (error) {
+        console.error('Failed to preview sound:', error);
+        showSettingsStatus('試聴に失敗しました', 'error');
+    }
+}
+
+async function generateSigningSecret() {
+    try {
+        elements.payloadSigningSecret.value = await invoke('generate_signing_secret');
+    } catch (error) {
+        console.error('Failed to generate signing secret:', error);
+        showSettingsStatus('シークレットの生成に失敗しました', 'error');
+    }
+}
+
+async function generateEncryptionKey() {
+    try {
+        elements.payloadEncryptionKey.value = await invoke('generate_encryption_key');
+    } catch (error) {
+        console.error('Failed to generate encryption key:', error);
+        showSettingsStatus('鍵の生成に失敗しました', 'error');
+    }
+}
 
+// REST API のトークンの一覧（トークン自体は発行した時にだけ表示する）
+async function loadApiTokens() {
+    try {
+        const tokens = await invoke('list_api_tokens');
+        elements.apiTokenList.innerHTML = '';
+        tokens.forEach(token => {
+            const item = createClientItem();
+            const actions = document.createElement('span');
+            actions.className = 'api-token-actions';
+            actions.append(
+                createTokenButton('再発行', () => rotateApiToken(token)),
+                createTokenButton('失効', () => revokeApiToken(token))
+            );
+            item.append(
+                createClientSpan('client-name', `${token.name}（…${token.hint}）`),
+                createClientSpan('client-detail', `${formatTime(token.created_at)} に発行`),
+                actions
+            );
+            elements.apiTokenList.appendChild(item);
+        });
+    } catch (error) {
+        console.error('Failed to load API tokens:', error);
+    }
+}
+
+function createTokenButton(label, onClick) {
+    const button = document.createElement('button');
+    button.type = 'button';
+    button.className = 'btn-secondary';
+    button.textContent = label;
+    button.addEventListener('click', onClick);
+    return button;
+}
+
+// 発行したトークンを一度だけ表示する
+function showIssuedToken(issued) {
+    elements.issuedTokenLabel.textContent = `「${issued.name}」のトークン（この画面を離れると再表示できません）`;
+    elements.issuedTokenValue.value = issued.token;
+    elements.issuedToken.classList.remove('hidden');
+}
+
+async function issueApiToken() {
+    const name = elements.apiTokenName.value.trim();
+    if (!name) {
+        showSettingsStatus('トークンを使うツールの名前を入力してください', 'error');
+        return;
+    }
+    try {
+        showIssuedToken(await invoke('issue_api_token', { name }));
+        elements.apiTokenName.value = '';
+        await loadApiTokens();
+    } catch (error) {
+        console.error('Failed to issue API token:', error);
+        showSettingsStatus('トークンの発行に失敗しました', 'error');
+    }
+}
+
+async function rotateApiToken(token) {
+    if (!await ask(`「${token.name}」のトークンを再発行しますか？今のトークンはすぐに使えなくなります。`, { title: 'REST API', kind: 'warning' })) {
+        return;
+    }
+    try {
+        showIssuedToken(await invoke('rotate_api_token', { id: token.id }));
+        await loadApiTokens();
+    } catch (error) {
+        console.error('Failed to rotate API token:', error);
+        showSettingsStatus('トークンの再発行に失敗しました', 'error');
+    }
+}
+
+async function revokeApiToken(token) {
+    if (!await ask(`「${token.name}」のトークンを失効させますか？`, { title: 'REST API', kind: 'warning' })) {
+        return;
+    }
+    try {
+        await invoke('revoke_api_token', { id: token.id });
+        await loadApiTokens();
+    } catch (error) {
+        console.error('Failed to revoke API token:', error);
+        showSettingsStatus('トークンの失効に失敗しました', 'error');
+    }
+}
+
+async function copyIssuedToken() {
+    try {
+        await navigator.clipboard.writeText(elements.issuedTokenValue.value);
+        showSettingsStatus('トークンをコピーしました', 'success');
+    } catch (error) {
+        console.error('Failed to copy API token:', error);
+        elements.issuedTokenValue.select();
+    }
+}
+
+// 保存した SSH の送信先に接続先（~/.claude/notify.env）を送る
+async function pushBrokerHost() {
+    elements.hostUpdatePushBtn.disabled = true;
+    elements.hostUpdateStatus.textContent = '送信中...';
+    try {
+        const host = elements.hostInput.value.trim();
+        const results = await invoke('push_broker_host', { host: host || null });
+        elements.hostUpdateStatus.textContent = results
+            .map(result => result.error ? `✗ ${result.target}: ${result.error}` : `✓ ${result.target}`)
+            .join(' / ');
+    } catch (error) {
+        console.error('Failed to push broker host:', error);
+        elements.hostUpdateStatus.textContent = `送信に失敗しました: ${error}`;
+    } finally {
+        elements.hostUpdatePushBtn.disabled = false;
+    }
+}
+
+// ダッシュボードのアドレスを表示する（LAN に公開する場合は検出した IP アドレスと、平文の HTTP であることの警告）
+async function updateDashboardUrl() {
+    elements.restApiLanWarning.classList.toggle('hidden', !elements.restApiAllowLan.checked);
+    let host = '127.0.0.1';
+    if (elements.restApiAllowLan.checked) {
+        try {
+            host = await invoke('detect_ip');
+        } catch (error) {
+            console.error('Failed to detect IP address:', error);
+        }
+    }
+    const port = parseInt(elements.restApiPort.value, 10) || 18830;
+    elements.dashboardUrl.textContent = `ダッシュボード: http://${host}:${port}/（#token=<トークン> を付けるとトークンの入力を省略できます）`;
+}
+
+// 設定画面の入力内容から設定を組み立てる
+function collectSettings() {
+    return {
+        ...loadedSettings,
+        toast_notification_enabled: elements.toastEnabled.checked,
+        sound_enabled: elements.soundEnabled.checked,
+        speech: {
+            enabled: elements.speechEnabled.checked
+        },
+        taskbar_flash_enabled: elements.flashEnabled.checked,
+        taskbar_badge_enabled: elements.badgeEnabled.checked,
+        tray_flash_enabled: elements.trayFlashEnabled.checked,
+        tray_icon_theme: elements.trayIconTheme.value,
+        window_placement: elements.windowPlacement.value,
+        window_mode: {
+            compact: elements.windowCompact.checked,
+            always_on_top: elements.windowAlwaysOnTop.checked,
+            opacity: Math.min(100, Math.max(30, parseInt(elements.windowOpacity.value, 10) || 100))
+        },
+        tray_attention_mode: elements.trayAttentionMode.value,
+        tray_flash_interval_ms: Math.max(100, parseInt(elements.trayFlashInterval.value, 10) || 500),
+        tray_flash_max_duration_secs: Math.max(0, parseInt(elements.trayFlashMaxDuration.value, 10) || 0),
+        taskbar_flash_count: Math.max(1, parseInt(elements.taskbarFlashCount.value, 10) || 3),
+        toast_body_max_length: Math.min(1000, Math.max(0, parseInt(elements.toastBodyMaxLength.value, 10) || 0)),
+        sound_volume: parseFloat(elements.volumeSlider.value) / 100,
+        language: elements.language.value,
+        event_sounds: {
+            stop: elements.soundStop.value,
+            permission_request: elements.soundPermission.value,
+            notification: elements.soundNotification.value
+        },
+        sound_overlap: elements.soundOverlap.value,
+        max_concurrent_sounds: Math.max(1, parseInt(elements.maxConcurrentSounds.value, 10) || 3),
+        duplicate_sound_window_ms: Math.max(0, parseInt(elements.duplicateSoundWindow.value, 10) || 0),
+        ...soundShaping(),
+        broker_acl: {
+            enabled: elements.brokerAclEnabled.checked,
+            client_id_prefixes: elements.brokerAclPrefixes.value
+                .split('\n')
+                .map(prefix => prefix.trim())
+                .filter(prefix => prefix.length > 0),
+            usernames: elements.brokerAclUsernames.value
+                .split('\n')
+                .map(username => username.trim())
+                .filter(username => username.length > 0)
+        },
+        broker_listeners: {
+            local_enabled: elements.brokerLocalListenerEnabled.checked,
+            local_port: Math.min(65535, Math.max(1, parseInt(elements.brokerLocalPort.value, 10) || 1884))
+        },
+        broker_discovery: elements.brokerDiscovery.checked,
+        broker_port_fallback: elements.brokerPortFallback.checked,
+        external_broker: {
+            enabled: elements.externalBrokerEnabled.checked,
+            host: elements.externalBrokerHost.value.trim(),
+            port: Math.min(65535, Math.max(1, parseInt(elements.externalBrokerPort.value, 10) || 1883)),
+            username: elements.externalBrokerUsername.value.trim(),
+            password: elements.externalBrokerPassword.value,
+            client_id: elements.externalBrokerClientId.value.trim()
+        },
+        payload_signing: {
+            enabled: elements.payloadSigningEnabled.checked,
+            secret: elements.payloadSigningSecret.value.trim(),
+            window_secs: Math.min(86400, Math.max(30, parseInt(elements.payloadSigningWindow.value, 10) || 300))
+        },
+        payload_encryption: {
+            enabled: elements.payloadEncryptionEnabled.checked,
+            key: elements.payloadEncryptionKey.value.trim()
+        },
+        rest_api: {
+            enabled: elements.restApiEnabled.checked,
+            port: Math.min(65535, Math.max(1024, parseInt(elements.restApiPort.value, 10) || 18830)),
+            allow_lan: elements.restApiAllowLan.checked
+        },
+        permission_response: {
+            enabled: elements.permissionResponseEnabled.checked,
+            timeout_secs: Math.min(55, Math.max(5, parseInt(elements.permissionResponseTimeout.value, 10) || 50))
+        },
+        session_control: {
+            enabled: elements.sessionControlEnabled.checked
+        },
+        last_message_length: Math.min(1000, Math.max(0, parseInt(elements.lastMessageLength.value, 10) || 0)),
+        host_rules: {
+            rules: parseHostRules(elements.hostRules.value)
+        },
+        state_transitions: {
+            enabled: elements.stateTransitionEnabled.checked,
+            debounce_secs: Math.min(600, Math.max(0, parseInt(elements.stateTransitionDebounce.value, 10) || 0))
+        },
+        waiting_reminder: {
+            enabled: elements.waitingReminderEnabled.checked,
+            minutes: Math.min(1440, Math.max(1, parseInt(elements.waitingReminderMinutes.value, 10) || 15))
+        },
+        all_clear: {
+            enabled: elements.allClearEnabled.checked
+        },
+        line_milestones: {
+            enabled: elements.lineMilestoneEnabled.checked,
+            step: Math.min(100000, Math.max(10, parseInt(elements.lineMilestoneStep.value, 10) || 500))
+        },
+        projects: {
+            aliases: parseProjectAliases(elements.projectAliases.value)
+        },
+        ignore_list: {
+            paths: elements.ignorePaths.value.split('\n').map(line => line.trim()).filter(Boolean)
+        },
+        rate_limit: {
+            enabled: elements.rateLimitEnabled.checked,
+            max_events: Math.min(1000, Math.max(1, parseInt(elements.rateLimitMaxEvents.value, 10) || 30)),
+            window_secs: Math.min(600, Math.max(1, parseInt(elements.rateLimitWindow.value, 10) || 10)),
+            mute_secs: Math.min(3600, Math.max(1, parseInt(elements.rateLimitMute.value, 10) || 60))
+        },
+        catch_up: {
+            enabled: elements.catchUpEnabled.checked,
+            startup_secs: Math.min(600, Math.max(5, parseInt(elements.catchUpStartup.value, 10) || 60)),
+            max_age_minutes: Math.min(1440, Math.max(1, parseInt(elements.catchUpMaxAge.value, 10) || 5))
+        },
+        stale_events: {
+            action: elements.staleEventAction.value,
+            max_age_minutes: Math.min(1440, Math.max(1, parseInt(elements.staleEventMaxAge.value, 10) || 30)),
+            clock_skew_secs: Math.min(3600, Math.max(0, parseInt(elements.staleEventClockSkew.value, 10) || 0))
+        },
+        redaction: {
+            enabled: elements.redactionEnabled.checked,
+            patterns: elements.redactionPatterns.value.split('\n').map(line => line.trim()).filter(Boolean)
+        },
+        project_rules: {
+            rules: parseProjectRules(elements.projectRules.value),
+            color_toasts: elements.projectColorToasts.checked,
+            color_tray_dot: elements.projectColorTrayDot.checked
+        },
+        tooltip_template: elements.tooltipTemplate.value.trim(),
+        fullscreen: {
+            enabled: elements.fullscreenEnabled.checked,
+            allow_critical: elements.fullscreenAllowCritical.checked
+        },
+        mute_during_calls: elements.muteDuringCalls.checked,
+        calendar: {
+            enabled: elements.calendarEnabled.checked,
+            ics_url: elements.calendarIcsUrl.value.trim()
+        },
+        heartbeat: {
+            enabled: elements.heartbeatEnabled.checked,
+            interval_secs: Math.min(600, Math.max(10, parseInt(elements.heartbeatInterval.value, 10) || 60)),
+            silence_minutes: Math.min(120, Math.max(1, parseInt(elements.heartbeatSilence.value, 10) || 10))
+        },
+        host_update: {
+            watch_ip: elements.hostUpdateWatch.checked,
+            ssh_targets: elements.hostUpdateTargets.value
+                .split('\n')
+                .map(target => target.trim())
+                .filter(target => target.length > 0),
+            auto_push: elements.hostUpdateAutoPush.checked
+        },
+        digest: {
+            enabled: elements.digestEnabled.checked,
+            interval: elements.digestInterval.value,
+            daily_hour: Math.min(23, Math.max(0, parseInt(elements.digestHour.value, 10) || 0)),
+            notify: elements.digestNotify.checked,
+            slack_webhook_url: elements.digestSlackUrl.value.trim()
+        },
+        cost_report: {
+            enabled: elements.costReportEnabled.checked,
+            time: TIME_PATTERN.test(elements.costReportTime.value) ? elements.costReportTime.value : '18:00'
+        },
+        webhook: collectWebhookSettings(),
+        custom_topics: {
+            topics: parseCustomTopics(elements.customTopics.value)
+        },
+        script_hook: {
+            enabled: elements.scriptHookEnabled.checked,
+            command: elements.scriptHookCommand.value.trim(),
+            timeout_secs: Math.min(300, Math.max(1, parseInt(elements.scriptHookTimeout.value, 10) || 10))
+        },
+        routing: {
+            enabled: elements.routingEnabled.checked,
+            working_hours: {
+                enabled: elements.workingHoursEnabled.checked,
+                ...(parseWorkingHours(elements.workingHours.value) ?? DEFAULT_WORKING_HOURS)
+            }
+        },
+        update_check: {
+            enabled: elements.updateCheckEnabled.checked
+        },
+        mark_read_on_resume: elements.markReadOnResume.checked,
+        history_retention: {
+            max_entries: Math.min(5000, Math.max(10, parseInt(elements.historyMaxEntries.value, 10) || 100)),
+            max_age_days: Math.min(3650, Math.max(0, parseInt(elements.historyMaxAge.value, 10) || 0)),
+            stop_days: parseRetentionDays(elements.historyStopDays.value),
+            permission_request_days: parseRetentionDays(elements.historyPermissionDays.value),
+            notification_days: parseRetentionDays(elements.historyNotificationDays.value)
+        },
+        history_metadata_only: elements.historyMetadataOnly.checked
+    };
+}
+
+// イベント種別ごとの保持日数（空欄は全体の保持日数に従う）
+function parseRetentionDays(value) {
+    const days = parseInt(value, 10);
+    return Number.isNaN(days) ? null : Math.min(3650, Math.max(0, days));
+}
+
+async function saveSettings() {
+    try {
+        const settings = collectSettings();
+        const invalidPattern = await findInvalidRedactionPattern(settings.redaction.patterns);
+        if (invalidPattern) {
+            showSettingsStatus(`マスクのパターンが正しくありません: ${invalidPattern}`, 'error');
+            return;
+        }
+        if (settings.history_metadata_only && !loadedSettings.history_metadata_only
+            && !await ask('記録済みの通知履歴からもメッセージ・コマンドを削除します。よろしいですか？', { title: '通知履歴', kind: 'warning' })) {
+            return;
+        }
         await invoke('save_settings_command', { settings });
+        loadedSettings = settings;
+        applyCompactMode(settings.window_mode.compact);
+        updateSessionCommands();
         showSettingsStatus('設定を保存しました', 'success');
     } catch (error) {
         console.error('Failed to save settings:', error);
@@ -376,6 +1582,222 @@ async function saveSettings() {
     }
 }
 
+// 正規表現として正しくないマスクのパターンを探す（バックエンドと同じ regex で確認する）
+async function findInvalidRedactionPattern(patterns) {
+    for (const pattern of patterns) {
+        try {
+            await invoke('validate_redaction_pattern', { pattern });
+        } catch (error) {
+            return `${pattern}（${error}）`;
+        }
+    }
+    return null;
+}
+
+// コンパクト表示のレイアウト（ウィンドウの大きさはバックエンドで変える）
+function applyCompactMode(compact) {
+    document.body.classList.toggle('compact-mode', compact);
+}
+
+async function saveProfileRules() {
+    try {
+        const rules = parseProfileRules(elements.profileRules.value);
+        await invoke('set_profile_rules', { rules });
+        elements.profileRules.value = formatProfileRules(rules);
+        showSettingsStatus('自動切り替えのルールを保存しました', 'success');
+    } catch (error) {
+        console.error('Failed to save profile rules:', error);
+        showSettingsStatus('自動切り替えのルールの保存に失敗しました', 'error');
+    }
+}
+
+// 「条件 => プロファイル名」形式の行をルールに変換する（解釈できない行は無視する）
+function parseProfileRules(text) {
+    return text
+        .split('\n')
+        .map(line => line.split('=>'))
+        .filter(parts => parts.length === 2)
+        .map(([condition, profile]) => ({
+            condition: parseProfileCondition(condition.trim()),
+            profile: profile.trim()
+        }))
+        .filter(rule => rule.condition && rule.profile);
+}
+
+function parseProfileCondition(text) {
+    const [kind = '', ...args] = text.split(/\s+/);
+    const rest = text.slice(kind.length).trim();
+    switch (kind.toLowerCase()) {
+        case 'display':
+            return { kind: 'external_display' };
+        case 'ssid':
+            return rest ? { kind: 'ssid', ssid: rest } : null;
+        case 'host':
+            return rest ? { kind: 'host', host: rest } : null;
+        case 'time': {
+            const [start, end] = (args.pop() ?? '').split('-');
+            if (!TIME_PATTERN.test(start ?? '') || !TIME_PATTERN.test(end ?? '')) {
+                return null;
+            }
+            return { kind: 'schedule', days: parseWeekdays(args.join(',')), start, end };
+        }
+        default:
+            return null;
+    }
+}
+
+// 「mon-fri」「sat,sun」形式の曜日を展開する
+function parseWeekdays(text) {
+    const indexOf = day => WEEKDAYS.findIndex(weekday => weekday.toLowerCase() === day.slice(0, 3).toLowerCase());
+    return text
+        .split(',')
+        .filter(part => part)
+        .flatMap(part => {
+            const [from, to = from] = part.split('-').map(indexOf);
+            if (from < 0 || to < 0) {
+                return [];
+            }
+            const days = [];
+            for (let i = from; ; i = (i + 1) % 7) {
+                days.push(WEEKDAYS[i]);
+                if (i === to) {
+                    break;
+                }
+            }
+            return days;
+        });
+}
+
+// 「mon-fri 09:00-18:00」形式の勤務時間を変換する（解釈できない場合は null）
+function parseWorkingHours(text) {
+    const args = text.trim().split(/\s+/);
+    const [start, end] = (args.pop() ?? '').split('-');
+    if (!TIME_PATTERN.test(start ?? '') || !TIME_PATTERN.test(end ?? '')) {
+        return null;
+    }
+    return { days: parseWeekdays(args.join(',')), start, end };
+}
+
+function formatWorkingHours(hours) {
+    const { days, start, end } = { ...DEFAULT_WORKING_HOURS, ...hours };
+    return [days.map(day => day.toLowerCase()).join(','), `${start}-${end}`]
+        .filter(Boolean)
+        .join(' ');
+}
+
+function formatProfileRules(rules) {
+    return rules
+        .map(rule => `${formatProfileCondition(rule.condition)} => ${rule.profile}`)
+        .join('\n');
+}
+
+function formatProfileCondition(condition) {
+    switch (condition.kind) {
+        case 'external_display':
+            return 'display';
+        case 'ssid':
+            return `ssid ${condition.ssid}`;
+        case 'host':
+            return `host ${condition.host}`;
+        default:
+            return ['time', condition.days.map(day => day.toLowerCase()).join(','), `${condition.start}-${condition.end}`]
+                .filter(Boolean)
+                .join(' ');
+    }
+}
+
+// 「ホスト名 [mute] [#rrggbb]」形式の行をルールに変換する
+function parseHostRules(text) {
+    return text
+        .split('\n')
+        .map(line => line.trim().split(/\s+/))
+        .filter(tokens => tokens[0])
+        .map(([host, ...options]) => ({
+            host,
+            muted: options.some(option => option.toLowerCase() === 'mute'),
+            color: options.find(option => HOST_COLOR_PATTERN.test(option)) ?? null
+        }));
+}
+
+// 「プロジェクト名 = パスまたは名前 ...」形式の行を別名に変換する
+function parseProjectAliases(text) {
+    return text
+        .split('\n')
+        .map(line => line.match(/^([^=]+)=(.*)$/))
+        .filter(match => match && match[1].trim())
+        .map(([, name, paths]) => ({
+            name: name.trim(),
+            paths: paths.trim().split(/\s+/).filter(Boolean)
+        }));
+}
+
+function formatProjectAliases(aliases) {
+    return aliases.map(alias => `${alias.name} = ${alias.paths.join(' ')}`).join('\n');
+}
+
+// 「プロジェクト [milestone=行数] [remind=分] [#rrggbb]」形式の行をルールに変換する
+function parseProjectRules(text) {
+    const option = (options, name) => {
+        const found = options.find(option => option.startsWith(`${name}=`));
+        const value = found ? parseInt(found.slice(name.length + 1), 10) : NaN;
+        return Number.isNaN(value) ? null : Math.max(0, value);
+    };
+    return text
+        .split('\n')
+        .map(line => line.trim().split(/\s+/))
+        .filter(tokens => tokens[0])
+        .map(([project, ...options]) => ({
+            project,
+            line_milestone: option(options, 'milestone'),
+            waiting_reminder: option(options, 'remind'),
+            color: options.find(option => HOST_COLOR_PATTERN.test(option)) ?? null
+        }));
+}
+
+function formatProjectRules(rules) {
+    return rules
+        .map(rule => [
+            rule.project,
+            rule.line_milestone != null ? `milestone=${rule.line_milestone}` : null,
+            rule.waiting_reminder != null ? `remind=${rule.waiting_reminder}` : null,
+            rule.color
+        ].filter(Boolean).join(' '))
+        .join('\n');
+}
+
+// 「トピック → テンプレート」形式の行を追加のトピックに変換する（矢印は省略できる）
+function parseCustomTopics(text) {
+    return text
+        .split('\n')
+        .map(line => line.trim().match(/^(\S+)\s*(?:→|->)?\s*(.*)$/))
+        .filter(Boolean)
+        .map(([, filter, template]) => ({ filter, template: template.trim() }));
+}
+
+function formatCustomTopics(topics) {
+    return topics
+        .map(topic => topic.template ? `${topic.filter} → ${topic.template}` : topic.filter)
+        .join('\n');
+}
+
+function parseWebhookHeaders(text) {
+    return text
+        .split('\n')
+        .map(line => line.split(':'))
+        .filter(([name, ...value]) => name.trim() && value.length > 0)
+        .map(([name, ...value]) => ({ name: name.trim(), value: value.join(':').trim() }));
+}
+
+function formatWebhookHeaders(headers) {
+    return headers.map(header => `${header.name}: ${header.value}`).join('\n');
+}
+
+function formatHostRules(rules) {
+    return rules
+        .map(rule => [rule.host, rule.muted ? 'mute' : null, rule.color].filter(Boolean).join(' '))
+        .join('\n');
+}
+
 function showSettingsStatus(message, type) {
     elements.saveStatus.textContent = message;
     elements.saveStatus.className = 'save-status ' + type;
@@ -391,6 +1813,107 @@ function initExportTab() {
     elements.detectIpBtn.addEventListener('click', detectIp);
     elements.exportLinuxBtn.addEventListener('click', () => exportConfig('linux_wsl'));
     elements.exportWindowsBtn.addEventListener('click', () => exportConfig('windows'));
+    elements.localInstallBtn.addEventListener('click', installHooksLocally);
+    elements.templatePlatform.addEventListener('change', () => loadHookTemplates());
+    elements.exportShell.addEventListener('change', () => loadHookTemplates());
+    elements.templateFile.addEventListener('change', showHookTemplate);
+    elements.saveTemplateBtn.addEventListener('click', saveHookTemplate);
+    elements.resetTemplateBtn.addEventListener('click', resetHookTemplate);
+}
+
+// マージで変更しなかった設定の説明
+function describeInstallConflict(conflict) {
+    switch (conflict.kind) {
+        case 'status_line_in_use':
+            return `statusLine は別のコマンド（${conflict.command}）が設定済みのため変更していません`;
+        case 'hooks_disabled':
+            return 'disableAllHooks が true のため、フックは実行されません';
+        default:
+            return conflict.kind;
+    }
+}
+
+async function installHooksLocally() {
+    elements.localInstallBtn.disabled = true;
+    elements.localInstallStatus.textContent = 'インストール中...';
+    try {
+        // このPCのフックは許可リストの対象外の localhost 専用リスナーに送る
+        const report = await invoke('install_hooks_locally', {
+            port: localBrokerPort ?? brokerPort,
+            withStatusline: elements.localInstallStatusline.checked,
+        });
+        const lines = [`${report.scripts_dir} にインストールしました。Claude Code を再起動してください`];
+        if (report.backup) {
+            lines.push(`変更前の設定: ${report.backup}`);
+        }
+        for (const conflict of report.conflicts) {
+            lines.push('⚠ ' + describeInstallConflict(conflict));
+        }
+        elements.localInstallStatus.textContent = lines.join('\n');
+    } catch (error) {
+        console.error('Local install failed:', error);
+        elements.localInstallStatus.textContent = 'インストールに失敗しました: ' + error;
+    } finally {
+        elements.localInstallBtn.disabled = false;
+    }
+}
+
+// 選択中のプラットフォームのテンプレート
+let hookTemplates = [];
+
+async function loadHookTemplates(fileName = elements.templateFile.value) {
+    try {
+        hookTemplates = await invoke('get_hook_templates', {
+            platform: elements.templatePlatform.value,
+            shell: elements.exportShell.value
+        });
+    } catch (error) {
+        console.error('Failed to load hook templates:', error);
+        elements.templateStatus.textContent = 'テンプレートの読み込みに失敗しました: ' + error;
+        return;
+    }
+    elements.templateFile.innerHTML = '';
+    for (const template of hookTemplates) {
+        const option = document.createElement('option');
+        option.value = template.file_name;
+        option.textContent = template.customized ? `${template.file_name}（編集済み）` : template.file_name;
+        elements.templateFile.appendChild(option);
+    }
+    if (hookTemplates.some(template => template.file_name === fileName)) {
+        elements.templateFile.value = fileName;
+    }
+    showHookTemplate();
+}
+
+function showHookTemplate() {
+    const template = hookTemplates.find(t => t.file_name === elements.templateFile.value);
+    elements.templateEditor.value = template ? template.content : '';
+    elements.resetTemplateBtn.disabled = !template || !template.customized;
+    elements.templateStatus.textContent = '';
+}
+
+async function saveHookTemplate() {
+    const fileName = elements.templateFile.value;
+    try {
+        await invoke('save_hook_template', { fileName, content: elements.templateEditor.value });
+        await loadHookTemplates(fileName);
+        elements.templateStatus.textContent = `${fileName} を保存しました（次のエクスポートから使います）`;
+    } catch (error) {
+        console.error('Failed to save hook template:', error);
+        elements.templateStatus.textContent = '保存に失敗しました: ' + error;
+    }
+}
+
+async function resetHookTemplate() {
+    const fileName = elements.templateFile.value;
+    try {
+        await invoke('reset_hook_template', { fileName });
+        await loadHookTemplates(fileName);
+        elements.templateStatus.textContent = `${fileName} を既定のテンプレートに戻しました`;
+    } catch (error) {
+        console.error('Failed to reset hook template:', error);
+        elements.templateStatus.textContent = '元に戻せませんでした: ' + error;
+    }
 }
 
 async function detectIp() {
@@ -423,9 +1946,11 @@ function showIpStatus(message, type) {
 
 async function exportConfig(platform) {
     const host = elements.hostInput.value.trim();
-    const port = 1883;
+    const port = brokerPort;
+    // Devcontainer 用のスクリプトは接続先を実行時に検出する
+    const preset = platform === 'windows' ? 'standard' : elements.exportPreset.value;
 
-    if (!host) {
+    if (!host && preset !== 'devcontainer') {
         showExportStatus('IPアドレスを入力してください', 'error');
         return;
     }
@@ -435,14 +1960,16 @@ async function exportConfig(platform) {
     const platformName = platform === 'windows' ? 'Windows' : 'Linux/WSL';
     const defaultFileName = platform === 'windows'
         ? 'claude-code-notify-windows-setup.zip'
-        : 'claude-code-notify-linux-setup.zip';
+        : preset === 'devcontainer'
+            ? 'claude-code-notify-devcontainer-setup.zip'
+            : 'claude-code-notify-linux-setup.zip';
 
     try {
         btn.disabled = true;
         btn.innerHTML = '<span class="btn-content">エクスポート中...</span>';
 
         const zipData = await invoke('generate_config_zip_v2', {
-            options: { host, port, platform }
+            options: { host, port, platform, shell: elements.exportShell.value, preset }
         });
 
         const filePath = await save({
@@ -477,6 +2004,386 @@ function showExportStatus(message, type) {
     }
 }
 
+// ===== 診断タブ =====
+function initDiagnosticsTab() {
+    setInterval(() => {
+        if (currentTab === 'diagnostics') {
+            loadDiagnostics();
+        }
+    }, 2000);
+
+    // 開発者向けの疑似イベントは Ctrl+Shift+D で表示する
+    document.addEventListener('keydown', (event) => {
+        if (event.ctrlKey && event.shiftKey && event.key.toUpperCase() === 'D') {
+            event.preventDefault();
+            elements.devPanel.classList.toggle('hidden');
+        }
+    });
+    elements.sendFakeEventBtn.addEventListener('click', sendFakeEvent);
+    elements.clearProblemsBtn.addEventListener('click', clearProblems);
+    elements.clearSecurityEventsBtn.addEventListener('click', clearSecurityEvents);
+    elements.sessionCommands.querySelectorAll('button[data-command]').forEach(button => {
+        button.addEventListener('click', () => sendSessionCommand(button.dataset.command));
+    });
+}
+
+const PROBLEM_CATEGORIES = {
+    hook: 'フックの失敗',
+    missing_dependency: 'コマンドが見つからない',
+    connection: '接続エラー',
+    authentication: '署名・暗号化',
+    invalid_payload: '不正なペイロード',
+    other: 'エラー',
+};
+
+// ブローカーが接続を拒否したクライアント
+async function loadSecurityEvents() {
+    try {
+        renderSecurityEvents(await invoke('get_security_events'));
+    } catch (error) {
+        console.error('Failed to load security events:', error);
+    }
+}
+
+function renderSecurityEvents(events) {
+    elements.securityEventList.innerHTML = '';
+    elements.securityEventEmpty.classList.toggle('hidden', events.length > 0);
+
+    events.forEach(event => {
+        const item = createClientItem();
+        const user = event.username ? `（ユーザー名 ${event.username}）` : '';
+        const count = event.count > 1 ? `${event.count}回 ・ ` : '';
+        item.append(
+            createClientSpan('client-name', `${event.client_id || '(クライアントIDなし)'}${user}`, event.client_id),
+            createClientSpan('client-detail', `${count}${formatTime(event.last_at)}`, `初回 ${formatTime(event.first_at)}`)
+        );
+        elements.securityEventList.appendChild(item);
+    });
+}
+
+async function clearSecurityEvents() {
+    try {
+        await invoke('clear_security_events');
+        renderSecurityEvents([]);
+    } catch (error) {
+        console.error('Failed to clear security events:', error);
+    }
+}
+
+async function loadProblems() {
+    try {
+        const problems = await invoke('get_problems');
+        renderProblems(problems);
+    } catch (error) {
+        console.error('Failed to load problems:', error);
+    }
+}
+
+function renderProblems(problems) {
+    elements.problemList.innerHTML = '';
+    elements.problemEmpty.classList.toggle('hidden', problems.length > 0);
+
+    problems.forEach(problem => {
+        const item = document.createElement('li');
+        item.className = 'problem-item';
+
+        const header = document.createElement('div');
+        header.className = 'problem-header';
+        const category = PROBLEM_CATEGORIES[problem.category] ?? problem.category;
+        const title = problem.hook ? `${problem.hook}（${category}）` : category;
+        header.append(
+            createClientSpan('client-name', title),
+            createClientSpan('client-detail', formatTime(problem.received_at))
+        );
+
+        const details = [
+            problem.exit_code != null ? `終了コード ${problem.exit_code}` : null,
+            problem.host ? `@${problem.host}` : null,
+            problem.cwd,
+        ].filter(Boolean).join(' ・ ');
+
+        const message = document.createElement('p');
+        message.className = 'problem-message';
+        message.textContent = problem.message;
+
+        const hint = document.createElement('p');
+        hint.className = 'problem-hint';
+        hint.textContent = problem.hint;
+
+        item.append(header, message);
+        if (details) {
+            item.append(createClientSpan('client-detail', details));
+        }
+        item.append(hint);
+        if (problem.raw) {
+            // 不正なペイロードは受信した内容を折りたたんで表示する
+            const raw = document.createElement('details');
+            raw.className = 'problem-raw';
+            const summary = document.createElement('summary');
+            summary.textContent = '受信した内容';
+            const pre = document.createElement('pre');
+            pre.textContent = problem.raw;
+            raw.append(summary, pre);
+            item.append(raw);
+        }
+        elements.problemList.appendChild(item);
+    });
+}
+
+async function clearProblems() {
+    try {
+        await invoke('clear_problems');
+        renderProblems([]);
+    } catch (error) {
+        console.error('Failed to clear problems:', error);
+    }
+}
+
+async function sendFakeEvent() {
+    const kind = elements.fakeEventKind.value;
+    let fields = null;
+    try {
+        const text = elements.fakeEventFields.value.trim();
+        fields = text ? JSON.parse(text) : null;
+    } catch (error) {
+        elements.fakeEventStatus.textContent = `JSON を解析できません: ${error.message}`;
+        return;
+    }
+
+    try {
+        await invoke('send_fake_event', { kind, fields });
+        elements.fakeEventStatus.textContent = `${kind} イベントを送信しました`;
+    } catch (error) {
+        console.error('Failed to send fake event:', error);
+        elements.fakeEventStatus.textContent = `送信に失敗しました: ${error}`;
+    }
+}
+
+async function loadDiagnostics() {
+    try {
+        const diagnostics = await invoke('get_broker_metrics');
+        renderDiagnostics(diagnostics);
+    } catch (error) {
+        console.error('Failed to load broker metrics:', error);
+    }
+
+    loadSecurityEvents();
+
+    loadDailyCosts();
+    if (selectedSessionId) {
+        loadSessionMetrics(selectedSessionId);
+    }
+
+    try {
+        const stats = await invoke('get_response_stats');
+        elements.metricResponded.textContent = stats.responded;
+        elements.metricResponseMedian.textContent = stats.median_secs === null ? '-' : formatTaskDuration(stats.median_secs);
+    } catch (error) {
+        console.error('Failed to load response stats:', error);
+    }
+
+    try {
+        const heartbeats = await invoke('get_heartbeats');
+        renderHeartbeats(heartbeats);
+    } catch (error) {
+        console.error('Failed to load heartbeats:', error);
+    }
+}
+
+function renderHeartbeats(heartbeats) {
+    elements.heartbeatList.innerHTML = '';
+    elements.heartbeatEmpty.classList.toggle('hidden', heartbeats.length > 0);
+
+    heartbeats.forEach(heartbeat => {
+        const item = createClientItem(hostColor(heartbeat.host));
+        let state = heartbeat.active ? '実行中' : '終了';
+        if (heartbeat.silent) {
+            state = '⚠️ 応答なし';
+        }
+        item.append(
+            createClientSpan('client-name', heartbeat.host, heartbeat.host),
+            createClientSpan(
+                'client-detail',
+                `${state} ・ ${formatElapsed(heartbeat.last_seen_secs)}`,
+                `最終報告: ${formatDateTime(heartbeat.reported_at)}`
+            )
+        );
+        elements.heartbeatList.appendChild(item);
+    });
+}
+
+function renderDiagnostics(diagnostics) {
+    const { metrics, projects, running } = diagnostics;
+    const sampled = running && metrics.last_sample_secs !== null;
+
+    elements.metricConnections.textContent = sampled ? metrics.connections : '-';
+    elements.metricSubscriptions.textContent = sampled ? metrics.subscriptions : '-';
+    elements.metricRate.textContent = sampled ? metrics.messages_per_sec.toFixed(1) : '-';
+    elements.metricPublishes.textContent = sampled ? metrics.total_publishes : '-';
+
+    if (!running) {
+        elements.metricUpdated.textContent = 'ブローカーが停止しています';
+    } else if (!sampled) {
+        elements.metricUpdated.textContent = 'メトリクスを取得中...';
+    } else {
+        elements.metricUpdated.textContent = `${metrics.last_sample_secs}秒前に更新（接続中のクライアントにはこのアプリ自身も含まれます）`;
+    }
+
+    elements.clientList.innerHTML = '';
+    elements.clientEmpty.classList.toggle('hidden', projects.length > 0);
+
+    projects.forEach(project => {
+        const color = projectColor(project.cwd);
+
+        const header = createClientItem(color);
+        header.classList.add('project-header');
+        const host = project.host ? ` @${project.host}` : '';
+        header.append(
+            createClientSpan('client-name', `${project.project}${host}`, project.cwd),
+            createClientSpan(
+                'client-detail',
+                `${project.active_sessions}セッション ・ ${formatCost(project.total_cost_usd)} ・ ${formatElapsed(project.last_activity_secs)}`
+            )
+        );
+        elements.clientList.appendChild(header);
+
+        project.sessions.forEach(session => {
+            const item = createClientItem(color);
+            item.classList.add('project-session', 'selectable');
+            item.classList.toggle('selected', session.session_id === selectedSessionId);
+            item.addEventListener('click', () => {
+                selectedSessionId = session.session_id;
+                elements.clientList.querySelectorAll('.selected').forEach(el => el.classList.remove('selected'));
+                item.classList.add('selected');
+                loadSessionMetrics(session.session_id);
+                updateSessionCommands();
+            });
+            const state = session.state ?? '-';
+            const cost = session.cost_usd != null ? ` ・ ${formatCost(session.cost_usd)}` : '';
+            const task = session.task_secs != null ? ` ・ ⏱ ${formatTaskDuration(session.task_secs)}` : '';
+            item.append(
+                createClientSpan('client-name', session.session_id, session.session_id),
+                createClientSpan(
+                    'client-detail',
+                    `${state}${task}${cost} ・ ${formatElapsed(session.last_seen_secs)}`,
+                    `最終報告: ${formatDateTime(session.reported_at)}`
+                )
+            );
+            elements.clientList.appendChild(item);
+        });
+    });
+}
+
+// 直近の日ごとのコストを棒グラフで表示する
+async function loadDailyCosts() {
+    try {
+        const costs = await invoke('get_daily_costs', { days: DAILY_COST_DAYS });
+        const max = Math.max(...costs.map(day => day.cost_usd), 0.01);
+        elements.dailyCostChart.replaceChildren(...costs.map(day => {
+            const bar = document.createElement('div');
+            bar.className = 'cost-bar';
+            bar.style.height = `${(day.cost_usd / max) * 100}%`;
+            bar.title = `${day.date}: ${formatCost(day.cost_usd)}`;
+            return bar;
+        }));
+        const total = costs.reduce((sum, day) => sum + day.cost_usd, 0);
+        elements.dailyCostSummary.textContent = `直近${costs.length}日間の1日ごとのコスト（合計 ${formatCost(total)}）`;
+    } catch (error) {
+        console.error('Failed to load daily costs:', error);
+    }
+}
+
+// 選択したセッションのコストの推移を折れ線グラフで表示する
+async function loadSessionMetrics(sessionId) {
+    try {
+        const history = await invoke('get_session_metrics', { sessionId });
+        const samples = (history?.samples ?? []).filter(sample => sample.cost_usd != null);
+        elements.sessionMetricsHint.classList.toggle('hidden', samples.length > 0);
+        elements.sessionMetrics.classList.toggle('hidden', samples.length === 0);
+        if (samples.length === 0) {
+            elements.sessionMetricsHint.textContent = 'このセッションのコストはまだ記録されていません（5分ごとに記録）';
+            return;
+        }
+
+        const start = Date.parse(samples[0].timestamp);
+        const span = Math.max(Date.parse(samples[samples.length - 1].timestamp) - start, 1);
+        const max = Math.max(...samples.map(sample => sample.cost_usd), 0.01);
+        elements.sessionCostLine.setAttribute('points', samples
+            .map(sample => {
+                const x = ((Date.parse(sample.timestamp) - start) / span) * 100;
+                const y = 40 - (sample.cost_usd / max) * 40;
+                return `${x.toFixed(2)},${y.toFixed(2)}`;
+            })
+            .join(' '));
+
+        const last = samples[samples.length - 1];
+        const lines = last.lines_added != null ? ` ・ +${last.lines_added} / -${last.lines_removed ?? 0}行` : '';
+        elements.sessionMetricsSummary.textContent =
+            `${sessionId}: ${formatCost(last.cost_usd)}${lines}（${new Date(samples[0].timestamp).toLocaleString()} から）`;
+    } catch (error) {
+        console.error('Failed to load session metrics:', error);
+    }
+}
+
+// 選択したセッションの操作ボタンを表示する
+function updateSessionCommands() {
+    const enabled = Boolean(loadedSettings?.session_control?.enabled && selectedSessionId);
+    elements.sessionCommands.classList.toggle('hidden', !enabled);
+    elements.sessionCommandStatus.textContent = '';
+}
+
+// 選択したセッションにコマンドを送る（結果はフック側の control-listener.sh から control-reply で届く）
+async function sendSessionCommand(command) {
+    const sessionId = selectedSessionId;
+    if (!sessionId) {
+        return;
+    }
+    const labels = { pause: '一時停止', stop: '中断' };
+    if (labels[command] && !await ask(`セッション ${sessionId} を${labels[command]}しますか？`, { title: 'セッションの操作', kind: 'warning' })) {
+        return;
+    }
+    try {
+        await invoke('send_session_command', { sessionId, command });
+        elements.sessionCommandStatus.textContent = `${command} を送信しました（応答を待っています）`;
+    } catch (error) {
+        console.error('Failed to send session command:', error);
+        elements.sessionCommandStatus.textContent = `送信できませんでした: ${error}`;
+    }
+}
+
+function createClientItem(color) {
+    const item = document.createElement('li');
+    item.className = 'client-item';
+    if (color) {
+        item.classList.add('has-accent-color');
+        item.style.setProperty('--accent-color', color);
+    }
+    return item;
+}
+
+function createClientSpan(className, text, title) {
+    const span = document.createElement('span');
+    span.className = className;
+    span.textContent = text;
+    if (title) span.title = title;
+    return span;
+}
+
+function formatCost(usd) {
+    return `$${usd.toFixed(2)}`;
+}
+
+// タスクにかかった時間（作業中は経過時間、1分未満は秒で表示する）
+function formatTaskDuration(secs) {
+    return secs < 60 ? `${secs}秒` : formatDuration(secs);
+}
+
+function formatElapsed(secs) {
+    if (secs < 60) return `${secs}秒前`;
+    if (secs < 3600) return `${Math.floor(secs / 60)}分前`;
+    return `${Math.floor(secs / 3600)}時間前`;
+}
+
 // ===== フッター =====
 function initFooter() {
     elements.minimizeBtn.addEventListener('click', minimizeToTray);
@@ -505,4 +2412,33 @@ function initTauriEvents() {
         }
         updateUnreadBadge();
     });
+
+    // セッション・未確認数の変化
+    listen('state-changed', () => {
+        loadMetrics();
+    });
+
+    // フックのエラー
+    listen('problem-added', () => {
+        if (currentTab === 'diagnostics') {
+            loadProblems();
+        }
+    });
+
+    // セッションの操作の結果
+    listen('control-reply', (event) => {
+        const reply = event.payload;
+        if (reply.session_id !== selectedSessionId) {
+            return;
+        }
+        const host = reply.host ? `（${reply.host}）` : '';
+        elements.sessionCommandStatus.textContent = reply.ok
+            ? `${reply.command}: 完了${host}`
+            : `${reply.command}: 失敗しました${host}`;
+    });
+
+    // トレイメニュー・ホットキーでのプロファイル切り替え
+    listen('settings-changed', () => {
+        loadSettings();
+    });
 }
@@ -0,0 +1,152 @@
+// Claude Code Notify - AskUserQuestion 回答ウィンドウ
+// 質問の選択肢をボタンで表示し、選んだ回答を Claude Code に返す
+
+const { invoke } = window.__TAURI__.core;
+const { getCurrentWindow } = window.__TAURI__.window;
+
+// ウィンドウのラベル（question-<履歴ID>）から履歴IDを取得
+const entryId = Number(getCurrentWindow().label.replace('question-', ''));
+
+// 複数選択の回答を連結する区切り（question_prompt.rs と同じ）
+const MULTI_SELECT_SEPARATOR = ', ';
+
+let questions = [];
+// 質問ごとに選択中のラベル
+const selections = new Map();
+
+const elements = {};
+
+document.addEventListener('DOMContentLoaded', async () => {
+    elements.session = document.getElementById('question-session');
+    elements.list = document.getElementById('question-list');
+    elements.status = document.getElementById('question-status');
+    elements.submit = document.getElementById('question-submit');
+
+    elements.submit.addEventListener('click', submitAnswers);
+
+    try {
+        const prompt = await invoke('get_question_prompt', { id: entryId });
+        questions = prompt.questions;
+        elements.session.textContent = prompt.session_name || 'Claude Code';
+        renderQuestions();
+    } catch (error) {
+        console.error('Failed to load question:', error);
+        elements.submit.disabled = true;
+        showStatus('この質問はすでに回答済みか、待ち時間を過ぎています', 'error');
+    }
+});
+
+function renderQuestions() {
+    elements.list.innerHTML = '';
+
+    questions.forEach((question) => {
+        selections.set(question.question, new Set());
+
+        const card = document.createElement('section');
+        card.className = 'question-card';
+
+        if (question.header) {
+            const header = document.createElement('span');
+            header.className = 'question-header';
+            header.textContent = question.header;
+            card.appendChild(header);
+        }
+
+        const text = document.createElement('p');
+        text.className = 'question-text';
+        text.textContent = question.question;
+        card.appendChild(text);
+
+        const options = document.createElement('div');
+        options.className = 'question-options';
+        question.options.forEach((option) => {
+            const button = document.createElement('button');
+            button.type = 'button';
+            button.className = 'question-option';
+            button.title = option.description || '';
+
+            const label = document.createElement('span');
+            label.className = 'question-option-label';
+            label.textContent = option.label;
+            button.appendChild(label);
+
+            if (option.description) {
+                const description = document.createElement('span');
+                description.className = 'question-option-desc';
+                description.textContent = option.description;
+                button.appendChild(description);
+            }
+
+            button.addEventListener('click', () => selectOption(question, option.label, options, button));
+            options.appendChild(button);
+        });
+        card.appendChild(options);
+
+        elements.list.appendChild(card);
+    });
+
+    updateSubmitButton();
+
+    // 単一選択の質問が1つだけなら、選択と同時に送信する
+    elements.submit.classList.toggle('hidden', isSingleChoice());
+}
+
+function isSingleChoice() {
+    return questions.length === 1 && !questions[0].multiSelect;
+}
+
+function selectOption(question, label, container, button) {
+    const selected = selections.get(question.question);
+
+    if (question.multiSelect) {
+        if (selected.has(label)) {
+            selected.delete(label);
+        } else {
+            selected.add(label);
+        }
+        button.classList.toggle('selected', selected.has(label));
+    } else {
+        selected.clear();
+        selected.add(label);
+        container.querySelectorAll('.question-option').forEach((b) => b.classList.remove('selected'));
+        button.classList.add('selected');
+    }
+
+    updateSubmitButton();
+
+    if (isSingleChoice()) {
+        submitAnswers();
+    }
+}
+
+function updateSubmitButton() {
+    elements.submit.disabled = questions.some((q) => selections.get(q.question).size === 0);
+}
+
+async function submitAnswers() {
+    const answers = {};
+    questions.forEach((question) => {
+        // 選択肢の順序で連結する
+        const selected = selections.get(question.question);
+        answers[question.question] = question.options
+            .map((option) => option.label)
+            .filter((label) => selected.has(label))
+            .join(MULTI_SELECT_SEPARATOR);
+    });
+
+    elements.submit.disabled = true;
+    try {
+        // 送信に成功するとウィンドウはアプリ側で閉じられる
+        await invoke('answer_question', { id: entryId, answers });
+    } catch (error) {
+        console.error('Failed to answer question:', error);
+        showStatus(`回答の送信に失敗しました: ${error}`, 'error');
+        updateSubmitButton();
+    }
+}
+
+function showStatus(message, type) {
+    elements.status.textContent = message;
+    elements.status.className = 'status ' + type;
+    elements.status.classList.remove('hidden');
+}